@@ -0,0 +1,13 @@
+-- 契约测试用例覆盖，运维人员按端点+工具名维护
+CREATE TABLE IF NOT EXISTS contract_test_overrides (
+    id CHAR(36) PRIMARY KEY,
+    endpoint_id CHAR(36) NOT NULL,
+    tool_name VARCHAR(255) NOT NULL,
+    arguments TEXT NULL COMMENT '覆盖的调用参数(JSON)',
+    expect_structured_content BOOLEAN NULL,
+    skipped BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    INDEX idx_endpoint_id (endpoint_id),
+    UNIQUE KEY unique_endpoint_tool (endpoint_id, tool_name)
+);
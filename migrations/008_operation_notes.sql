@@ -0,0 +1,19 @@
+-- 操作级运维备注，按 endpoint_id + method + path 关联，重新上传 spec 后仍保留
+CREATE TABLE IF NOT EXISTS operation_notes (
+    id CHAR(36) PRIMARY KEY,
+    endpoint_id CHAR(36) NOT NULL,
+    method VARCHAR(10) NOT NULL,
+    path VARCHAR(500) NOT NULL,
+    note TEXT NOT NULL COMMENT 'markdown',
+    author VARCHAR(255) NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    INDEX idx_endpoint_operation (endpoint_id, method, path)
+);
+
+-- 端点级备注设置：是否将备注追加到工具描述
+CREATE TABLE IF NOT EXISTS operation_note_settings (
+    endpoint_id CHAR(36) PRIMARY KEY,
+    propagate_to_tools BOOLEAN NOT NULL DEFAULT FALSE,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP
+);
@@ -0,0 +1,12 @@
+-- 端点删除后的外部清理任务（向量数据、解析缓存等），与数据库删除在同一事务中写入
+CREATE TABLE IF NOT EXISTS endpoint_cleanup_outbox (
+    id CHAR(36) PRIMARY KEY,
+    endpoint_id CHAR(36) NOT NULL,
+    endpoint_name VARCHAR(255) NOT NULL,
+    action VARCHAR(64) NOT NULL COMMENT 'vector_cleanup',
+    attempts INT NOT NULL DEFAULT 0,
+    last_error TEXT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    processed_at TIMESTAMP NULL,
+    INDEX idx_processed_at (processed_at)
+);
@@ -0,0 +1,13 @@
+-- 组合工具：按顺序调用端点已有工具，参数模板可引用前序步骤输出
+CREATE TABLE IF NOT EXISTS composite_tools (
+    id CHAR(36) PRIMARY KEY,
+    endpoint_id CHAR(36) NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    description TEXT NULL,
+    steps TEXT NOT NULL COMMENT '步骤定义(JSON)',
+    result_template TEXT NULL COMMENT '结果模板(JSON)',
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    INDEX idx_endpoint_id (endpoint_id),
+    UNIQUE KEY unique_endpoint_name (endpoint_id, name)
+);
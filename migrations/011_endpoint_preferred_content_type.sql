@@ -0,0 +1,2 @@
+-- 端点级请求体首选 Content-Type，为空时按 json > form > 其他 选择
+ALTER TABLE endpoints ADD COLUMN preferred_content_type VARCHAR(255) NULL;
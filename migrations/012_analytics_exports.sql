@@ -0,0 +1,14 @@
+-- 调用日志分析导出任务，参数经匿名化后以 NDJSON 写入文件存储
+CREATE TABLE IF NOT EXISTS analytics_exports (
+    id CHAR(36) PRIMARY KEY,
+    status VARCHAR(20) NOT NULL COMMENT 'pending/running/completed/failed',
+    start_time TIMESTAMP NULL,
+    end_time TIMESTAMP NULL,
+    endpoint_ids TEXT NULL COMMENT 'JSON 数组，为空表示全部端点',
+    file_path VARCHAR(1024) NULL,
+    row_count INT NOT NULL DEFAULT 0,
+    error_message TEXT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    finished_at TIMESTAMP NULL,
+    INDEX idx_created_at (created_at)
+);
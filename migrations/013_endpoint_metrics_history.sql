@@ -0,0 +1,14 @@
+-- 端点指标快照，每行记录一个快照窗口内的增量，用于按时间段回溯 SLO
+CREATE TABLE IF NOT EXISTS endpoint_metrics_history (
+    id CHAR(36) PRIMARY KEY,
+    endpoint_id CHAR(36) NOT NULL,
+    snapshot_at TIMESTAMP NOT NULL,
+    window_secs INT UNSIGNED NOT NULL,
+    request_delta BIGINT UNSIGNED NOT NULL DEFAULT 0,
+    response_delta BIGINT UNSIGNED NOT NULL DEFAULT 0,
+    error_delta BIGINT UNSIGNED NOT NULL DEFAULT 0,
+    avg_latency_ms DOUBLE NOT NULL DEFAULT 0,
+    max_connections INT NOT NULL DEFAULT 0,
+    INDEX idx_endpoint_snapshot (endpoint_id, snapshot_at),
+    INDEX idx_snapshot_at (snapshot_at)
+);
@@ -0,0 +1,6 @@
+-- 端点级工具执行策略：允许的 HTTP 方法（逗号分隔），无记录表示不限制
+CREATE TABLE IF NOT EXISTS endpoint_execution_policies (
+    endpoint_id CHAR(36) PRIMARY KEY,
+    allowed_methods VARCHAR(255) NOT NULL,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP
+);
@@ -0,0 +1,13 @@
+-- 录制模式端点的调用记录（采样、限制体积），用于合成 OpenAPI 草稿
+CREATE TABLE IF NOT EXISTS endpoint_recordings (
+    id CHAR(36) PRIMARY KEY,
+    endpoint_id CHAR(36) NOT NULL,
+    method VARCHAR(10) NOT NULL,
+    path VARCHAR(1024) NOT NULL,
+    query_params TEXT NULL,
+    request_body MEDIUMTEXT NULL,
+    status_code SMALLINT UNSIGNED NOT NULL,
+    response_body MEDIUMTEXT NULL,
+    recorded_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    INDEX idx_endpoint_recorded (endpoint_id, recorded_at)
+);
@@ -0,0 +1,17 @@
+-- 接口检索反馈：记录检索结果与智能体实际调用的接口，用于评估与训练重排模型
+CREATE TABLE IF NOT EXISTS interface_search_feedback (
+    search_id CHAR(36) PRIMARY KEY,
+    project_id VARCHAR(255) NULL,
+    query TEXT NOT NULL,
+    results MEDIUMTEXT NOT NULL,
+    searched_at DATETIME(3) NOT NULL,
+    chosen_endpoint_id CHAR(36) NULL,
+    chosen_tool VARCHAR(255) NULL,
+    chosen_method VARCHAR(10) NULL,
+    chosen_path VARCHAR(1024) NULL,
+    chosen_rank INT NULL,
+    chosen_at DATETIME(3) NULL,
+    latency_ms BIGINT NULL,
+    INDEX idx_searched_at (searched_at),
+    INDEX idx_project_searched (project_id, searched_at)
+);
@@ -0,0 +1,9 @@
+-- 端点启动预热结果，每个端点保留最近一次
+CREATE TABLE IF NOT EXISTS endpoint_warmups (
+    endpoint_id CHAR(36) PRIMARY KEY,
+    status ENUM('ok', 'degraded', 'failed') NOT NULL,
+    duration_ms BIGINT UNSIGNED NOT NULL,
+    tools INT UNSIGNED NOT NULL DEFAULT 0,
+    errors TEXT NULL,
+    warmed_at DATETIME(3) NOT NULL
+);
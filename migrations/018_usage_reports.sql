@@ -0,0 +1,31 @@
+-- 定期用量报表定义，按周期聚合 endpoint_logs 后存储并投递
+CREATE TABLE IF NOT EXISTS usage_reports (
+    id CHAR(36) PRIMARY KEY,
+    name VARCHAR(255) NOT NULL,
+    scope VARCHAR(20) NOT NULL COMMENT 'tenant/endpoints',
+    endpoint_ids TEXT NULL COMMENT 'JSON 数组，scope 为 endpoints 时有效',
+    period VARCHAR(20) NOT NULL COMMENT 'daily/weekly/monthly',
+    delivery VARCHAR(20) NOT NULL COMMENT 'webhook/file',
+    webhook_url VARCHAR(1024) NULL,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    last_period_end TIMESTAMP NULL COMMENT '已生成的最后一个周期的结束时间',
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP
+);
+
+-- 报表每个周期的生成记录，同一周期只生成一次
+CREATE TABLE IF NOT EXISTS usage_report_runs (
+    id CHAR(36) PRIMARY KEY,
+    report_id CHAR(36) NOT NULL,
+    period_start TIMESTAMP NOT NULL,
+    period_end TIMESTAMP NOT NULL,
+    status VARCHAR(20) NOT NULL COMMENT 'completed/failed',
+    json_path VARCHAR(1024) NULL,
+    markdown_path VARCHAR(1024) NULL,
+    delivery_status VARCHAR(20) NOT NULL COMMENT 'delivered/failed/skipped',
+    delivery_attempts INT UNSIGNED NOT NULL DEFAULT 0,
+    error_message TEXT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE KEY uk_report_period (report_id, period_start),
+    FOREIGN KEY (report_id) REFERENCES usage_reports(id) ON DELETE CASCADE
+);
@@ -0,0 +1,5 @@
+-- 端点级 mock 模式：工具调用不请求上游，按响应示例或 schema 合成结果
+ALTER TABLE endpoints ADD COLUMN mock_mode BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- mock 调用单独计数，不计入 request_count / error_count
+ALTER TABLE endpoint_metrics ADD COLUMN mock_count BIGINT UNSIGNED NOT NULL DEFAULT 0;
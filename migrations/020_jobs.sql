@@ -0,0 +1,29 @@
+-- 持久化后台任务，worker 以行锁认领并定期心跳，心跳超时的运行中任务会被重新认领
+CREATE TABLE IF NOT EXISTS t_jobs (
+    id CHAR(36) PRIMARY KEY,
+    job_type VARCHAR(64) NOT NULL,
+    payload TEXT NOT NULL COMMENT 'JSON',
+    dedupe_key VARCHAR(191) NULL COMMENT '同一 key 只入队一次',
+    status VARCHAR(20) NOT NULL DEFAULT 'pending' COMMENT 'pending/running/completed/failed',
+    attempts INT UNSIGNED NOT NULL DEFAULT 0,
+    next_run_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    last_error TEXT NULL,
+    locked_by VARCHAR(128) NULL,
+    locked_at TIMESTAMP NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    UNIQUE KEY uk_dedupe_key (dedupe_key),
+    INDEX idx_status_next_run (status, next_run_at),
+    INDEX idx_job_type (job_type)
+);
+
+-- 端点向量清理改由任务执行，迁移未处理的 outbox 记录
+INSERT INTO t_jobs (id, job_type, payload, status, attempts, next_run_at, last_error, created_at)
+SELECT id, 'endpoint_vector_cleanup',
+       JSON_OBJECT('endpoint_id', endpoint_id, 'endpoint_name', endpoint_name),
+       'pending', 0, CURRENT_TIMESTAMP, last_error, created_at
+FROM endpoint_cleanup_outbox
+WHERE processed_at IS NULL AND action = 'vector_cleanup';
+
+UPDATE endpoint_cleanup_outbox SET processed_at = CURRENT_TIMESTAMP
+WHERE processed_at IS NULL AND action = 'vector_cleanup';
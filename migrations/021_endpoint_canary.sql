@@ -0,0 +1,19 @@
+-- 端点金丝雀发布：按比例把工具调用路由到备用上游地址
+CREATE TABLE IF NOT EXISTS endpoint_canaries (
+    endpoint_id CHAR(36) PRIMARY KEY,
+    base_url VARCHAR(2048) NOT NULL COMMENT '金丝雀上游地址',
+    percentage DOUBLE NOT NULL DEFAULT 0 COMMENT '路由到金丝雀的流量百分比 0-100',
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP
+);
+
+-- 金丝雀期间按变体记录调用，用于对比请求数、错误率与 p95 延迟
+CREATE TABLE IF NOT EXISTS endpoint_canary_calls (
+    id BIGINT UNSIGNED AUTO_INCREMENT PRIMARY KEY,
+    endpoint_id CHAR(36) NOT NULL,
+    variant VARCHAR(16) NOT NULL COMMENT 'primary/canary',
+    success BOOLEAN NOT NULL,
+    latency_ms BIGINT UNSIGNED NOT NULL,
+    called_at DATETIME(3) NOT NULL,
+    INDEX idx_endpoint_called_at (endpoint_id, called_at)
+);
@@ -0,0 +1,16 @@
+-- 端点 WASM 插件：改写发往上游的参数与上游返回的结果，模块文件经 FileService 存储
+CREATE TABLE IF NOT EXISTS endpoint_plugins (
+    endpoint_id CHAR(36) PRIMARY KEY,
+    file_id CHAR(36) NOT NULL COMMENT 't_file.id',
+    version INT UNSIGNED NOT NULL COMMENT '每次上传递增',
+    sha256 CHAR(64) NOT NULL,
+    size BIGINT NOT NULL,
+    transform_request BOOLEAN NOT NULL,
+    transform_response BOOLEAN NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP
+);
+
+-- 经插件处理的调用记录模块版本与哈希，便于审计
+ALTER TABLE endpoint_logs ADD COLUMN plugin_version INT UNSIGNED NULL;
+ALTER TABLE endpoint_logs ADD COLUMN plugin_hash CHAR(64) NULL;
@@ -0,0 +1,8 @@
+-- 端点静态解析：主机名固定解析到指定地址，用于排查分裂 DNS（split-horizon）问题
+CREATE TABLE IF NOT EXISTS endpoint_dns_overrides (
+    endpoint_id CHAR(36) NOT NULL,
+    host VARCHAR(255) NOT NULL COMMENT '小写主机名',
+    address VARCHAR(45) NOT NULL COMMENT 'IPv4 或 IPv6 地址',
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (endpoint_id, host)
+);
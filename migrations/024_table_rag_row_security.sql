@@ -0,0 +1,15 @@
+-- 行级权限：检索时按调用方的 row_filter 过滤安全列
+ALTER TABLE `t_dataset`
+    ADD COLUMN `security_column` VARCHAR(100) DEFAULT '' COMMENT '行级权限列,为空时不限制';
+
+-- 数据集访问令牌，只保存 sha256，明文仅在创建时返回一次
+CREATE TABLE IF NOT EXISTS t_dataset_token (
+    id CHAR(36) PRIMARY KEY,
+    dataset_id CHAR(36) NOT NULL,
+    name VARCHAR(100) NOT NULL COMMENT '令牌名称',
+    token_hash CHAR(64) NOT NULL COMMENT '令牌 sha256',
+    row_filter TEXT DEFAULT NULL COMMENT '行级过滤(json: 列名 -> 值),为空时不限制',
+    create_time TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP COMMENT '创建时间',
+    UNIQUE KEY unique_token_hash (token_hash),
+    INDEX idx_dataset_id (dataset_id)
+);
@@ -0,0 +1,2 @@
+-- 端点级 servers 变量覆盖(json: 变量名 -> 值)，为空时取 spec 中的 default
+ALTER TABLE endpoints ADD COLUMN server_variables TEXT NULL;
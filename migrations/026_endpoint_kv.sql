@@ -0,0 +1,19 @@
+-- 端点内置键值存储，供 agent 跨会话暂存少量状态；namespace 由 API key 或会话派生，互相隔离
+CREATE TABLE IF NOT EXISTS t_endpoint_kv (
+    endpoint_id CHAR(36) NOT NULL,
+    namespace VARCHAR(255) NOT NULL COMMENT 'key:<API key 哈希> 或 session:<会话 id>',
+    kv_key VARCHAR(255) NOT NULL,
+    value MEDIUMTEXT NOT NULL COMMENT '值(JSON)',
+    expires_at TIMESTAMP NULL COMMENT '为空表示不过期',
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    PRIMARY KEY (endpoint_id, namespace, kv_key),
+    INDEX idx_expires_at (expires_at)
+);
+
+-- 端点级键值存储开关，默认关闭
+CREATE TABLE IF NOT EXISTS endpoint_kv_settings (
+    endpoint_id CHAR(36) PRIMARY KEY,
+    enabled BOOLEAN NOT NULL DEFAULT FALSE,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP
+);
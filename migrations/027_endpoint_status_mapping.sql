@@ -0,0 +1,2 @@
+-- 端点级上游 HTTP 状态映射(json)，为空时 4xx 映射为 isError 工具结果、5xx 映射为 JSON-RPC 错误
+ALTER TABLE endpoints ADD COLUMN status_mapping TEXT NULL;
@@ -0,0 +1,2 @@
+-- 端点级开关：tools/list 中附带工具运行统计
+ALTER TABLE endpoints ADD COLUMN tool_stats BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,2 @@
+-- 端点标签(json 字符串数组)，按团队/业务域分组，列表接口可按标签过滤
+ALTER TABLE endpoints ADD COLUMN tags TEXT NULL;
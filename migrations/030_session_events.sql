@@ -0,0 +1,15 @@
+-- MCP 会话事件存储：传输上收发的消息、工具调用摘要与会话生命周期，按 seq 保持顺序
+CREATE TABLE IF NOT EXISTS session_events (
+    seq BIGINT AUTO_INCREMENT PRIMARY KEY,
+    session_id VARCHAR(255) NOT NULL,
+    endpoint_id CHAR(36) NULL COMMENT '旧版 SSE 的 /message 请求不携带端点',
+    direction VARCHAR(16) NOT NULL COMMENT 'received / sent / gateway',
+    kind VARCHAR(16) NOT NULL COMMENT 'request / notification / response / error / tool_call / lifecycle',
+    method VARCHAR(255) NULL COMMENT '响应记录对应请求的方法',
+    message_id VARCHAR(255) NULL COMMENT 'JSON-RPC id',
+    payload MEDIUMTEXT NOT NULL COMMENT '脱敏后的消息(JSON)，超长时为前缀',
+    truncated BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at DATETIME(3) NOT NULL,
+    INDEX idx_session_events_session (session_id, seq),
+    INDEX idx_session_events_created_at (created_at)
+);
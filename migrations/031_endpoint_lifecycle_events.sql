@@ -0,0 +1,12 @@
+-- 端点生命周期审计：批量启停与启动对账的逐个端点结果，端点删除后保留
+CREATE TABLE IF NOT EXISTS endpoint_lifecycle_events (
+    id BIGINT UNSIGNED AUTO_INCREMENT PRIMARY KEY,
+    endpoint_id CHAR(36) NOT NULL,
+    endpoint_name VARCHAR(255) NOT NULL,
+    action VARCHAR(16) NOT NULL COMMENT 'start / stop / demote',
+    source VARCHAR(16) NOT NULL COMMENT 'bulk / reconcile',
+    outcome VARCHAR(16) NOT NULL COMMENT 'ok / skipped / failed',
+    reason TEXT NULL,
+    created_at DATETIME(3) NOT NULL,
+    INDEX idx_endpoint_lifecycle_events_endpoint (endpoint_id, created_at)
+);
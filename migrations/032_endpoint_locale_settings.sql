@@ -0,0 +1,7 @@
+-- 端点级语言转发设置：是否向上游转发 Accept-Language，以及调用与会话都未指定语言时的默认语言
+CREATE TABLE IF NOT EXISTS endpoint_locale_settings (
+    endpoint_id CHAR(36) PRIMARY KEY,
+    forward BOOLEAN NOT NULL DEFAULT TRUE COMMENT '为 FALSE 时不发送 Accept-Language',
+    default_locale VARCHAR(35) NULL COMMENT 'BCP 47 语言标签，如 zh-CN',
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP
+);
@@ -0,0 +1,2 @@
+-- 端点级开关：tools/list 中不列出 swagger 标记为 deprecated 的操作
+ALTER TABLE endpoints ADD COLUMN hide_deprecated BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,2 @@
+-- 端点清理已由 t_jobs 中的 endpoint_vector_cleanup 任务执行，020 已迁移未处理的记录
+DROP TABLE IF EXISTS endpoint_cleanup_outbox;
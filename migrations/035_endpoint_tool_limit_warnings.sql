@@ -0,0 +1,2 @@
+-- 创建/更新 swagger 时工具数量/体积超过软阈值的告警（JSON 数组），NULL 表示尚未检查
+ALTER TABLE endpoints ADD COLUMN tool_limit_warnings TEXT NULL;
@@ -0,0 +1,77 @@
+use crate::models::{AnalyticsExportJob, AnalyticsExportRequest};
+use crate::services::AnalyticsExportService;
+use axum::{
+    body::Body,
+    extract::{Path, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use std::sync::Arc;
+use uuid::Uuid;
+
+#[derive(Clone)]
+pub struct AnalyticsState {
+    pub service: Arc<AnalyticsExportService>,
+}
+
+fn export_error(e: anyhow::Error) -> (StatusCode, String) {
+    let msg = e.to_string();
+    if msg.contains("not found") {
+        (StatusCode::NOT_FOUND, msg)
+    } else if msg.contains("Invalid export filter") {
+        (StatusCode::BAD_REQUEST, msg)
+    } else if msg.contains("not completed") {
+        (StatusCode::CONFLICT, msg)
+    } else {
+        (StatusCode::INTERNAL_SERVER_ERROR, msg)
+    }
+}
+
+/// 创建调用日志导出任务，异步执行
+pub async fn create_analytics_export(
+    State(state): State<AnalyticsState>,
+    Json(request): Json<AnalyticsExportRequest>,
+) -> Result<(StatusCode, Json<AnalyticsExportJob>), (StatusCode, String)> {
+    state
+        .service
+        .create_export(request)
+        .await
+        .map(|job| (StatusCode::ACCEPTED, Json(job)))
+        .map_err(export_error)
+}
+
+/// 查询导出任务状态，完成后返回下载地址
+pub async fn get_analytics_export(
+    State(state): State<AnalyticsState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<AnalyticsExportJob>, (StatusCode, String)> {
+    state
+        .service
+        .get_export(id)
+        .await
+        .map(Json)
+        .map_err(export_error)
+}
+
+/// 下载导出的 NDJSON 文件，从存储分块读取并流式返回
+pub async fn download_analytics_export(
+    State(state): State<AnalyticsState>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let reader = state
+        .service
+        .open_export_file(id)
+        .await
+        .map_err(export_error)?;
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/x-ndjson".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"analytics-export-{}.ndjson\"", id),
+            ),
+        ],
+        Body::from_stream(reader),
+    ))
+}
@@ -0,0 +1,75 @@
+use crate::handlers::Adapter;
+use crate::models::{BatchToolCall, BatchToolCallResult, EndpointStatus};
+use crate::services::{
+    batch_call_config, check_batch, execution_policy_config, normalize_locale, run_batch,
+    tool_stats, LOCALE_HEADER,
+};
+use crate::state::AppState;
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    Json,
+};
+use uuid::Uuid;
+
+/// 一次请求调用端点的多个工具：有界并发执行，按请求顺序返回每个调用的结果或错误；
+/// 执行策略按请求携带的 API key 解析，与 tools/call 走同一执行路径
+pub async fn batch_call_tools(
+    State(app_state): State<AppState>,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+    Json(calls): Json<Vec<BatchToolCall>>,
+) -> Result<Json<Vec<BatchToolCallResult>>, (StatusCode, String)> {
+    let config = batch_call_config();
+    check_batch(&calls, config).map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+
+    let endpoint = app_state
+        .endpoint_service
+        .get_endpoint_by_id(id)
+        .await
+        .map_err(|e| {
+            if e.to_string().contains("not found") {
+                (StatusCode::NOT_FOUND, "Endpoint not found".to_string())
+            } else {
+                (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+            }
+        })?;
+    if endpoint.status == EndpointStatus::Deleted {
+        return Err((StatusCode::NOT_FOUND, "Endpoint not found".to_string()));
+    }
+
+    let api_key = headers
+        .get(&execution_policy_config().api_key_header)
+        .and_then(|v| v.to_str().ok());
+    let policy = app_state
+        .execution_policy_service
+        .effective_policy(id, api_key, None)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    // 没有会话，语言由 X-MCP-Locale 请求头指定，单个调用仍可用 _locale 覆盖
+    let adapter = Adapter::new(
+        app_state.mcp_service.scheduler().clone(),
+        app_state.spec_cache.clone(),
+    )
+    .with_locale(
+        headers
+            .get(LOCALE_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(normalize_locale),
+    );
+    let results = run_batch(calls, config.concurrency, |call| {
+        let (adapter, policy) = (&adapter, &policy);
+        async move {
+            let started = std::time::Instant::now();
+            let result = adapter
+                .execute_tool_call_from_id(id, &call.tool, &call.arguments, policy)
+                .await;
+            let succeeded = matches!(&result, Ok(result) if result["success"] != false);
+            tool_stats().record(id, &call.tool, started.elapsed(), succeeded);
+            result
+        }
+    })
+    .await;
+    Ok(Json(results))
+}
@@ -1,9 +1,18 @@
+use crate::models::{SessionTranscript, TranscriptQuery};
+use crate::services::{check_transcript_access, render_transcript_markdown, TranscriptAccessError};
 use crate::state::AppState;
-use crate::utils::get_china_time;
+use crate::utils::{get_china_time, try_get_selected, FieldSelection};
 use axum::extract::State;
-use axum::{extract::Query, http::StatusCode, Json as JsonResponse};
+use axum::{
+    extract::{Path, Query},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json as JsonResponse,
+};
 use chrono::{DateTime, NaiveDateTime, Utc};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::mysql::MySqlRow;
 use sqlx::Row;
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -39,58 +48,85 @@ pub struct ConnectionQueryParams {
     pub end_time: Option<String>,
     #[serde(default)]
     pub endpoint_id: Option<String>,
+    /// 逗号分隔的返回字段，可选值见 SESSION_LIST_FIELDS
+    #[serde(default)]
+    pub fields: Option<String>,
 }
 
+/// 会话列表 fields 参数可选的字段，与列同名
+pub const SESSION_LIST_FIELDS: &[&str] = &[
+    "id",
+    "endpoint_id",
+    "session_id",
+    "transport_type",
+    "connect_at",
+    "disconnect_at",
+];
+
 /// Get connection logs for a specific endpoint within a time range
 pub async fn get_endpoint_connections(
     Query(params): Query<ConnectionQueryParams>,
     State(app_state): State<AppState>,
-) -> Result<JsonResponse<Vec<ConnectionInfo>>, (StatusCode, String)> {
+) -> Result<JsonResponse<Value>, (StatusCode, String)> {
+    let fields = FieldSelection::parse(params.fields.as_deref(), SESSION_LIST_FIELDS)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+    let columns = fields.named_columns(SESSION_LIST_FIELDS, &[]).join(", ");
+
     // If endpoint_id is provided in query params, filter by it
     let endpoint_id = params.endpoint_id.clone();
 
     let query_str = if let Some(ref _id) = endpoint_id {
-        "SELECT id, endpoint_id, session_id, transport_type, connect_at, disconnect_at 
-         FROM endpoint_session_logs 
-         WHERE endpoint_id = ?
-         ORDER BY connect_at DESC LIMIT 100"
+        format!(
+            "SELECT {} FROM endpoint_session_logs WHERE endpoint_id = ? ORDER BY connect_at DESC LIMIT 100",
+            columns
+        )
     } else {
-        "SELECT id, endpoint_id, session_id, transport_type, connect_at, disconnect_at 
-         FROM endpoint_session_logs 
-         ORDER BY connect_at DESC LIMIT 100"
+        format!(
+            "SELECT {} FROM endpoint_session_logs ORDER BY connect_at DESC LIMIT 100",
+            columns
+        )
     };
 
     let rows = if let Some(id) = endpoint_id {
-        sqlx::query(query_str)
+        sqlx::query(&query_str)
             .bind(id)
             .fetch_all(&app_state.pool)
             .await
             .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
     } else {
-        sqlx::query(query_str)
+        sqlx::query(&query_str)
             .fetch_all(&app_state.pool)
             .await
             .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
     };
 
-    let connections: Vec<ConnectionInfo> = rows
-        .into_iter()
-        .map(|row| {
-            let connect_at_naive: NaiveDateTime = row.get("connect_at");
-            let disconnect_at_naive: NaiveDateTime = row.get("disconnect_at");
-
-            ConnectionInfo {
-                id: row.get("id"),
-                endpoint_id: row.get("endpoint_id"),
-                session_id: row.get("session_id"),
-                transport_type: row.get("transport_type"),
-                connect_at: DateTime::from_naive_utc_and_offset(connect_at_naive, Utc),
-                disconnect_at: DateTime::from_naive_utc_and_offset(disconnect_at_naive, Utc),
-            }
-        })
-        .collect();
+    let connections = rows
+        .iter()
+        .map(connection_from_row)
+        .collect::<Result<Vec<_>, sqlx::Error>>()
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    fields
+        .apply_each(&connections)
+        .map(JsonResponse)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+}
 
-    Ok(JsonResponse(connections))
+/// 未查询的列取默认值，序列化时由字段选择去掉
+fn connection_from_row(row: &MySqlRow) -> Result<ConnectionInfo, sqlx::Error> {
+    let time = |column: &str| -> Result<DateTime<Utc>, sqlx::Error> {
+        Ok(try_get_selected::<NaiveDateTime>(row, column)?
+            .map(|naive| DateTime::from_naive_utc_and_offset(naive, Utc))
+            .unwrap_or_default())
+    };
+    Ok(ConnectionInfo {
+        id: try_get_selected(row, "id")?.unwrap_or_default(),
+        endpoint_id: try_get_selected(row, "endpoint_id")?.unwrap_or_default(),
+        session_id: try_get_selected(row, "session_id")?.unwrap_or_default(),
+        transport_type: try_get_selected(row, "transport_type")?.unwrap_or_default(),
+        connect_at: time("connect_at")?,
+        disconnect_at: time("disconnect_at")?,
+    })
 }
 
 /// Get total connection count for a specific endpoint or all endpoints
@@ -166,3 +202,64 @@ pub async fn get_time_series_connection_counts(
 
     Ok(JsonResponse(counts))
 }
+
+/// 导出会话记录（JSON 或 markdown），需要会话所属端点的管理 API key
+pub async fn export_session_transcript(
+    Path(session_id): Path<String>,
+    Query(query): Query<TranscriptQuery>,
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Response, (StatusCode, String)> {
+    let markdown = match query.format.as_deref() {
+        None | Some("json") => false,
+        Some("markdown") | Some("md") => true,
+        Some(other) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!("Unsupported transcript format '{}'", other),
+            ))
+        }
+    };
+    let service = &app_state.session_transcript_service;
+    let owner = service
+        .session_owner(&session_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                format!("Session {} not found", session_id),
+            )
+        })?;
+    let api_key = headers
+        .get(&service.config().api_key_header)
+        .and_then(|v| v.to_str().ok());
+    check_transcript_access(service.config(), api_key, owner.endpoint_name.as_deref()).map_err(
+        |e| match e {
+            TranscriptAccessError::MissingKey | TranscriptAccessError::InvalidKey => {
+                (StatusCode::UNAUTHORIZED, e.to_string())
+            }
+            TranscriptAccessError::Forbidden => (StatusCode::FORBIDDEN, e.to_string()),
+        },
+    )?;
+
+    let transcript: SessionTranscript = service
+        .transcript(&session_id, &owner, &query)
+        .await
+        .map_err(|e| {
+            let message = e.to_string();
+            if message.contains("Invalid transcript") {
+                (StatusCode::BAD_REQUEST, message)
+            } else {
+                (StatusCode::INTERNAL_SERVER_ERROR, message)
+            }
+        })?;
+    if markdown {
+        return Ok((
+            [(header::CONTENT_TYPE, "text/markdown; charset=utf-8")],
+            render_transcript_markdown(&transcript),
+        )
+            .into_response());
+    }
+    Ok(JsonResponse(transcript).into_response())
+}
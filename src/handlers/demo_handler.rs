@@ -0,0 +1,34 @@
+use crate::models::{DemoSeedResponse, DemoTeardownResponse};
+use crate::services::{DemoSeedService, DemoSeedingDisabled};
+use axum::{extract::State, http::StatusCode, Json};
+use std::sync::Arc;
+
+#[derive(Clone)]
+pub struct DemoState {
+    pub service: Arc<DemoSeedService>,
+}
+
+fn demo_error(e: anyhow::Error) -> (StatusCode, String) {
+    let msg = e.to_string();
+    if e.downcast_ref::<DemoSeedingDisabled>().is_some() {
+        (StatusCode::FORBIDDEN, msg)
+    } else if msg.contains("Demo data conflict") {
+        (StatusCode::CONFLICT, msg)
+    } else {
+        (StatusCode::INTERNAL_SERVER_ERROR, msg)
+    }
+}
+
+/// 写入演示端点、数据集、令牌与合成调用记录；重复调用不会重复写入
+pub async fn seed_demo(
+    State(state): State<DemoState>,
+) -> Result<Json<DemoSeedResponse>, (StatusCode, String)> {
+    state.service.seed().await.map(Json).map_err(demo_error)
+}
+
+/// 清除全部演示数据
+pub async fn teardown_demo(
+    State(state): State<DemoState>,
+) -> Result<Json<DemoTeardownResponse>, (StatusCode, String)> {
+    state.service.teardown().await.map(Json).map_err(demo_error)
+}
@@ -1,13 +1,38 @@
 use crate::models::{
-    CreateEndpointRequest, EndpointDetailResponse, EndpointQueryParams,
+    CreateEndpointRequest, EndpointQueryParams,
     EndpointResponse, PaginatedEndpointsResponse, SwaggerSpec, UpdateEndpointRequest,
 };
 use crate::models::endpoint::{EndpointMetrics, PaginationInfo};
+use crate::models::endpoint::{ENDPOINT_DETAIL_FIELDS, ENDPOINT_LIST_FIELDS};
+use crate::models::{ContractTestFormat, ContractTestOverride, ContractTestQuery};
+use crate::models::{CompositeTool, CompositeToolDefinition};
+use crate::models::{
+    EffectivePolicyQuery, EffectivePolicyResponse, EndpointExecutionPolicy, PolicySource,
+};
+use crate::models::{MetricsHistoryQuery, MetricsHistoryResponse};
+use crate::models::EndpointExportQuery;
+use crate::models::{CanaryConfig, CanaryStats, CanaryStatsQuery, UpsertCanaryRequest};
+use crate::models::{EndpointDnsOverrides, PutDnsOverridesRequest};
+use crate::models::LocaleSettings;
+use crate::models::{CreateRecordingEndpointRequest, PromoteSpecRequest, SynthesizedSpecResponse};
+use crate::models::{
+    BulkLifecycleResponse, EndpointBulkFilter, EndpointLifecycleEvent, LifecycleAction,
+};
+use crate::models::{
+    CreateOperationNoteRequest, OperationNote, OperationNoteSettings, UpdateOperationNoteRequest,
+};
+use crate::services::{
+    execution_policy_config, is_recording_spec, list_lifecycle_events, promote_swagger_content,
+    recording_spec, session_policies, to_jsonrpc_script, DEFAULT_CANARY_WINDOW_SECS,
+};
 use crate::state::AppState;
+use crate::utils::{
+    json_stream_response, json_value_response, FieldSelection, FieldsQuery, JsonFraming,
+};
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
-    response::Json,
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Json, Response},
 };
 use uuid::Uuid;
 
@@ -53,7 +78,15 @@ pub async fn create_endpoint(
         Ok(endpoint) => Ok((StatusCode::CREATED, Json(endpoint))),
         Err(e) => {
             tracing::error!("Failed to create endpoint: {}", e);
-            Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+            if e.to_string().contains("Tool limits exceeded") {
+                Err((StatusCode::UNPROCESSABLE_ENTITY, e.to_string()))
+            } else if e.to_string().starts_with("Invalid tag")
+                || e.to_string().starts_with("Invalid endpoint name")
+            {
+                Err((StatusCode::BAD_REQUEST, e.to_string()))
+            } else {
+                Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+            }
         }
     }
 }
@@ -70,14 +103,43 @@ pub async fn list_endpoints(
     }
 }
 
+/// 流式导出全部端点（含 swagger_content）
+pub async fn export_endpoints(
+    State(app_state): State<AppState>,
+    Query(query): Query<EndpointExportQuery>,
+) -> Result<Response, (StatusCode, String)> {
+    let framing = match query.format.as_deref() {
+        None | Some("ndjson") => JsonFraming::Ndjson,
+        Some("json") => JsonFraming::Array,
+        Some(other) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!("Invalid export format: {}", other),
+            ))
+        }
+    };
+    let endpoints = app_state.endpoint_service.stream_all_endpoints();
+    Ok(json_stream_response(endpoints, framing))
+}
+
 /// List endpoints with pagination, search, and filter support
 pub async fn list_endpoints_paginated(
     State(app_state): State<AppState>,
     Query(params): Query<EndpointQueryParams>,
-) -> Result<Json<PaginatedEndpointsResponse>, (StatusCode, String)> {
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let fields = FieldSelection::parse(params.fields.as_deref(), ENDPOINT_LIST_FIELDS)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
     match app_state
         .endpoint_service
-        .get_endpoints_paginated(params.page, params.page_size, params.search, params.status)
+        .get_endpoints_paginated(
+            params.page,
+            params.page_size,
+            params.search,
+            params.status,
+            params.updated_after,
+            params.tag,
+            &fields,
+        )
         .await
     {
         Ok((endpoints, total)) => {
@@ -95,7 +157,12 @@ pub async fn list_endpoints_paginated(
                 },
             };
 
-            Ok(Json(response))
+            let mut body = serde_json::to_value(&response)
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+            body["endpoints"] = fields
+                .apply_each(&response.endpoints)
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+            Ok(Json(body))
         }
         Err(e) => {
             tracing::error!("Failed to list endpoints with pagination: {}", e);
@@ -107,9 +174,21 @@ pub async fn list_endpoints_paginated(
 pub async fn get_endpoint(
     State(app_state): State<AppState>,
     Path(id): Path<Uuid>,
-) -> Result<Json<EndpointDetailResponse>, (StatusCode, String)> {
-    match app_state.endpoint_service.get_endpoint_detail(id).await {
-        Ok(endpoint) => Ok(Json(endpoint)),
+    Query(query): Query<FieldsQuery>,
+) -> Result<Response, (StatusCode, String)> {
+    let fields = FieldSelection::parse(query.fields.as_deref(), ENDPOINT_DETAIL_FIELDS)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+    match app_state
+        .endpoint_service
+        .get_endpoint_detail_fields(id, &fields)
+        .await
+    {
+        // 大 swagger 的详情分块序列化，不整体拼成字符串
+        Ok(endpoint) if fields.is_all() => Ok(json_value_response(endpoint)),
+        Ok(endpoint) => fields
+            .apply(&endpoint)
+            .map(|value| Json(value).into_response())
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
         Err(e) => {
             tracing::error!("Failed to get endpoint {}: {}", id, e);
             if e.to_string().contains("not found") {
@@ -143,6 +222,14 @@ pub async fn update_endpoint(
             tracing::error!("Failed to update endpoint {}: {}", id, e);
             if e.to_string().contains("not found") {
                 Err((StatusCode::NOT_FOUND, "Endpoint not found".to_string()))
+            } else if e.to_string().contains("Tool limits exceeded") {
+                Err((StatusCode::UNPROCESSABLE_ENTITY, e.to_string()))
+            } else if e.to_string().starts_with("Server variable")
+                || e.to_string().starts_with("Invalid tag")
+                || e.to_string().starts_with("Invalid endpoint name")
+                || e.to_string().contains("status_mapping")
+            {
+                Err((StatusCode::BAD_REQUEST, e.to_string()))
             } else {
                 Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
             }
@@ -184,6 +271,35 @@ pub async fn get_endpoint_metrics(
     }
 }
 
+/// 端点指标历史，按时间范围与分辨率在服务端聚合快照
+pub async fn get_endpoint_metrics_history(
+    State(app_state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<MetricsHistoryQuery>,
+) -> Result<Json<MetricsHistoryResponse>, (StatusCode, String)> {
+    if let Err(e) = app_state.endpoint_service.get_endpoint_by_id(id).await {
+        return if e.to_string().contains("not found") {
+            Err((StatusCode::NOT_FOUND, "Endpoint not found".to_string()))
+        } else {
+            Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+        };
+    }
+    match app_state
+        .metrics_history_service
+        .query_history(id, query)
+        .await
+    {
+        Ok(history) => Ok(Json(history)),
+        Err(e) if e.to_string().starts_with("Invalid") => {
+            Err((StatusCode::BAD_REQUEST, e.to_string()))
+        }
+        Err(e) => {
+            tracing::error!("Failed to get metrics history for endpoint {}: {}", id, e);
+            Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+        }
+    }
+}
+
 /// Start an endpoint
 pub async fn start_endpoint(
     State(app_state): State<AppState>,
@@ -200,6 +316,8 @@ pub async fn start_endpoint(
                     StatusCode::CONFLICT,
                     "Endpoint is already running".to_string(),
                 ))
+            } else if e.to_string().starts_with("Invalid swagger content") {
+                Err((StatusCode::BAD_REQUEST, e.to_string()))
             } else {
                 Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
             }
@@ -230,6 +348,75 @@ pub async fn stop_endpoint(
     }
 }
 
+/// 批量启动筛选出的端点
+pub async fn start_all_endpoints(
+    State(app_state): State<AppState>,
+    Query(filter): Query<EndpointBulkFilter>,
+) -> Result<Json<BulkLifecycleResponse>, (StatusCode, String)> {
+    bulk_lifecycle(&app_state, LifecycleAction::Start, &filter).await
+}
+
+/// 批量停止筛选出的端点
+pub async fn stop_all_endpoints(
+    State(app_state): State<AppState>,
+    Query(filter): Query<EndpointBulkFilter>,
+) -> Result<Json<BulkLifecycleResponse>, (StatusCode, String)> {
+    bulk_lifecycle(&app_state, LifecycleAction::Stop, &filter).await
+}
+
+async fn bulk_lifecycle(
+    app_state: &AppState,
+    action: LifecycleAction,
+    filter: &EndpointBulkFilter,
+) -> Result<Json<BulkLifecycleResponse>, (StatusCode, String)> {
+    match app_state
+        .endpoint_service
+        .bulk_lifecycle(action, filter)
+        .await
+    {
+        Ok(results) => Ok(Json(BulkLifecycleResponse::new(action, results))),
+        Err(e) => {
+            tracing::error!("Failed to {} endpoints: {}", action.as_str(), e);
+            if e.to_string().starts_with("Invalid status filter") {
+                Err((StatusCode::BAD_REQUEST, e.to_string()))
+            } else {
+                Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+            }
+        }
+    }
+}
+
+/// 端点最近 100 条生命周期审计记录
+pub async fn get_lifecycle_events(
+    State(app_state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<EndpointLifecycleEvent>>, (StatusCode, String)> {
+    list_lifecycle_events(&app_state.pool, id, 100)
+        .await
+        .map(Json)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+}
+
+/// 根据存储的 swagger 重建 api_paths
+pub async fn rebuild_api_paths(
+    State(app_state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    match app_state.endpoint_service.rebuild_api_paths(id).await {
+        Ok(written) => Ok(Json(serde_json::json!({ "rows_written": written }))),
+        Err(e) => {
+            tracing::error!("Failed to rebuild api paths for endpoint {}: {}", id, e);
+            if e.to_string().contains("not found") {
+                Err((StatusCode::NOT_FOUND, "Endpoint not found".to_string()))
+            } else if e.to_string().contains("Invalid swagger content") {
+                Err((StatusCode::UNPROCESSABLE_ENTITY, e.to_string()))
+            } else {
+                Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+            }
+        }
+    }
+}
+
 pub async fn sync_endpoint_vector(
     State(app_state): State<AppState>,
     Path(name): Path<String>,
@@ -242,3 +429,643 @@ pub async fn sync_endpoint_vector(
         )),
     }
 }
+
+/// 生成端点契约测试清单，format=jsonrpc 时返回换行分隔的 JSON-RPC 脚本
+pub async fn get_contract_tests(
+    State(app_state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<ContractTestQuery>,
+) -> Result<Response, (StatusCode, String)> {
+    let manifest = match app_state.contract_test_service.get_manifest(id).await {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            tracing::error!("Failed to generate contract tests for {}: {}", id, e);
+            return if e.to_string().contains("not found") {
+                Err((StatusCode::NOT_FOUND, "Endpoint not found".to_string()))
+            } else {
+                Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+            };
+        }
+    };
+
+    match query.format {
+        ContractTestFormat::Json => Ok(Json(manifest).into_response()),
+        ContractTestFormat::Jsonrpc => match to_jsonrpc_script(&manifest) {
+            Ok(script) => {
+                Ok(([(header::CONTENT_TYPE, "application/x-ndjson")], script).into_response())
+            }
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
+        },
+    }
+}
+
+/// 保存端点契约测试用例覆盖（整体替换）
+pub async fn put_contract_test_overrides(
+    State(app_state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(overrides): Json<Vec<ContractTestOverride>>,
+) -> Result<Json<Vec<ContractTestOverride>>, (StatusCode, String)> {
+    match app_state
+        .contract_test_service
+        .save_overrides(id, overrides)
+        .await
+    {
+        Ok(saved) => Ok(Json(saved)),
+        Err(e) => {
+            tracing::error!("Failed to save contract test overrides for {}: {}", id, e);
+            let msg = e.to_string();
+            if msg.contains("not found") {
+                Err((StatusCode::NOT_FOUND, "Endpoint not found".to_string()))
+            } else if msg.contains("Unknown tool") || msg.contains("Duplicate override") {
+                Err((StatusCode::BAD_REQUEST, msg))
+            } else {
+                Err((StatusCode::INTERNAL_SERVER_ERROR, msg))
+            }
+        }
+    }
+}
+
+fn operation_note_error(e: anyhow::Error) -> (StatusCode, String) {
+    let msg = e.to_string();
+    if msg.contains("not found") {
+        (StatusCode::NOT_FOUND, msg)
+    } else if msg.contains("cannot be empty") {
+        (StatusCode::BAD_REQUEST, msg)
+    } else {
+        (StatusCode::INTERNAL_SERVER_ERROR, msg)
+    }
+}
+
+/// 列出端点的操作备注
+pub async fn list_operation_notes(
+    State(app_state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<OperationNote>>, (StatusCode, String)> {
+    app_state
+        .operation_note_service
+        .list_notes(id)
+        .await
+        .map(Json)
+        .map_err(operation_note_error)
+}
+
+/// 新增操作备注
+pub async fn create_operation_note(
+    State(app_state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<CreateOperationNoteRequest>,
+) -> Result<(StatusCode, Json<OperationNote>), (StatusCode, String)> {
+    app_state
+        .operation_note_service
+        .create_note(id, request)
+        .await
+        .map(|note| (StatusCode::CREATED, Json(note)))
+        .map_err(operation_note_error)
+}
+
+/// 更新操作备注
+pub async fn update_operation_note(
+    State(app_state): State<AppState>,
+    Path((id, note_id)): Path<(Uuid, Uuid)>,
+    Json(request): Json<UpdateOperationNoteRequest>,
+) -> Result<Json<OperationNote>, (StatusCode, String)> {
+    app_state
+        .operation_note_service
+        .update_note(id, note_id, request)
+        .await
+        .map(Json)
+        .map_err(operation_note_error)
+}
+
+/// 删除操作备注
+pub async fn delete_operation_note(
+    State(app_state): State<AppState>,
+    Path((id, note_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    app_state
+        .operation_note_service
+        .delete_note(id, note_id)
+        .await
+        .map(|_| StatusCode::NO_CONTENT)
+        .map_err(operation_note_error)
+}
+
+/// 获取备注传播设置
+pub async fn get_operation_note_settings(
+    State(app_state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<OperationNoteSettings>, (StatusCode, String)> {
+    app_state
+        .operation_note_service
+        .get_settings(id)
+        .await
+        .map(Json)
+        .map_err(operation_note_error)
+}
+
+/// 更新备注传播设置
+pub async fn put_operation_note_settings(
+    State(app_state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(settings): Json<OperationNoteSettings>,
+) -> Result<Json<OperationNoteSettings>, (StatusCode, String)> {
+    app_state
+        .operation_note_service
+        .update_settings(id, settings)
+        .await
+        .map(Json)
+        .map_err(operation_note_error)
+}
+
+fn composite_tool_error(e: anyhow::Error) -> (StatusCode, String) {
+    let msg = e.to_string();
+    if msg.contains("not found") {
+        (StatusCode::NOT_FOUND, msg)
+    } else if msg.contains("Invalid composite tool") {
+        (StatusCode::BAD_REQUEST, msg)
+    } else {
+        (StatusCode::INTERNAL_SERVER_ERROR, msg)
+    }
+}
+
+/// 列出端点的组合工具
+pub async fn list_composite_tools(
+    State(app_state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<CompositeTool>>, (StatusCode, String)> {
+    app_state
+        .composite_tool_service
+        .list(id)
+        .await
+        .map(Json)
+        .map_err(composite_tool_error)
+}
+
+/// 新增组合工具
+pub async fn create_composite_tool(
+    State(app_state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(definition): Json<CompositeToolDefinition>,
+) -> Result<(StatusCode, Json<CompositeTool>), (StatusCode, String)> {
+    app_state
+        .composite_tool_service
+        .create(id, definition)
+        .await
+        .map(|tool| (StatusCode::CREATED, Json(tool)))
+        .map_err(composite_tool_error)
+}
+
+/// 更新组合工具
+pub async fn update_composite_tool(
+    State(app_state): State<AppState>,
+    Path((id, tool_id)): Path<(Uuid, Uuid)>,
+    Json(definition): Json<CompositeToolDefinition>,
+) -> Result<Json<CompositeTool>, (StatusCode, String)> {
+    app_state
+        .composite_tool_service
+        .update(id, tool_id, definition)
+        .await
+        .map(Json)
+        .map_err(composite_tool_error)
+}
+
+/// 删除组合工具
+pub async fn delete_composite_tool(
+    State(app_state): State<AppState>,
+    Path((id, tool_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    app_state
+        .composite_tool_service
+        .delete(id, tool_id)
+        .await
+        .map(|_| StatusCode::NO_CONTENT)
+        .map_err(composite_tool_error)
+}
+
+fn execution_policy_error(e: anyhow::Error) -> (StatusCode, String) {
+    let msg = e.to_string();
+    if msg.contains("not found") {
+        (StatusCode::NOT_FOUND, msg)
+    } else if msg.contains("Invalid execution policy") {
+        (StatusCode::BAD_REQUEST, msg)
+    } else {
+        (StatusCode::INTERNAL_SERVER_ERROR, msg)
+    }
+}
+
+/// 获取端点级执行策略
+pub async fn get_execution_policy(
+    State(app_state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<EndpointExecutionPolicy>, (StatusCode, String)> {
+    app_state
+        .execution_policy_service
+        .get_endpoint_policy(id)
+        .await
+        .map(Json)
+        .map_err(execution_policy_error)
+}
+
+/// 更新端点级执行策略，allowed_methods 为 null 时取消限制
+pub async fn put_execution_policy(
+    State(app_state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(policy): Json<EndpointExecutionPolicy>,
+) -> Result<Json<EndpointExecutionPolicy>, (StatusCode, String)> {
+    app_state
+        .endpoint_service
+        .get_endpoint_by_id(id)
+        .await
+        .map_err(execution_policy_error)?;
+    app_state
+        .execution_policy_service
+        .update_endpoint_policy(id, policy)
+        .await
+        .map(Json)
+        .map_err(execution_policy_error)
+}
+
+/// 调试用：按端点、请求携带的 API key 与指定会话解析生效策略
+pub async fn get_effective_policy(
+    State(app_state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<EffectivePolicyQuery>,
+    headers: HeaderMap,
+) -> Result<Json<EffectivePolicyResponse>, (StatusCode, String)> {
+    let api_key = headers
+        .get(&execution_policy_config().api_key_header)
+        .and_then(|v| v.to_str().ok());
+    let session = query
+        .session
+        .as_ref()
+        .and_then(|session| session_policies().get(session).map(|m| m.clone()));
+    let policy = app_state
+        .execution_policy_service
+        .effective_policy(id, api_key, session)
+        .await
+        .map_err(execution_policy_error)?;
+    Ok(Json(EffectivePolicyResponse {
+        endpoint_id: id,
+        session: query.session,
+        resolution_order: vec![
+            PolicySource::Endpoint,
+            PolicySource::ApiKey,
+            PolicySource::Session,
+        ],
+        allowed_methods: policy.allowed_methods(),
+        layers: policy.layers,
+    }))
+}
+
+fn recording_error(e: anyhow::Error) -> (StatusCode, String) {
+    let msg = e.to_string();
+    if msg.contains("not found") {
+        (StatusCode::NOT_FOUND, msg)
+    } else if msg.contains("Invalid") {
+        (StatusCode::BAD_REQUEST, msg)
+    } else {
+        (StatusCode::INTERNAL_SERVER_ERROR, msg)
+    }
+}
+
+/// 创建录制模式端点：只提供 base_url，通过 http_request 工具转发并记录调用
+pub async fn create_recording_endpoint(
+    State(app_state): State<AppState>,
+    Json(request): Json<CreateRecordingEndpointRequest>,
+) -> Result<(StatusCode, Json<EndpointResponse>), (StatusCode, String)> {
+    if request.name.trim().is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Endpoint name is required".to_string(),
+        ));
+    }
+    if app_state
+        .endpoint_service
+        .get_endpoint_by_name(request.name.clone())
+        .await
+        .is_ok()
+    {
+        return Err((
+            StatusCode::CONFLICT,
+            format!("Endpoint {} already exists", request.name),
+        ));
+    }
+    let spec = recording_spec(&request.name, &request.base_url).map_err(recording_error)?;
+    app_state
+        .endpoint_service
+        .create_endpoint(CreateEndpointRequest {
+            name: request.name,
+            description: request.description,
+            swagger_content: spec.to_string(),
+            tags: Vec::new(),
+        })
+        .await
+        .map(|endpoint| (StatusCode::CREATED, Json(endpoint)))
+        .map_err(recording_error)
+}
+
+/// 由录制合成 OpenAPI 草稿，仅返回供审核，不修改端点
+pub async fn synthesize_endpoint_spec(
+    State(app_state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<SynthesizedSpecResponse>, (StatusCode, String)> {
+    let endpoint = app_state
+        .endpoint_service
+        .get_endpoint_by_id(id)
+        .await
+        .map_err(recording_error)?;
+    app_state
+        .recording_service
+        .synthesize(&endpoint)
+        .await
+        .map(Json)
+        .map_err(recording_error)
+}
+
+/// 将审核后的草稿写入端点 swagger_content，端点随之切换为普通工具生成
+pub async fn promote_endpoint_spec(
+    State(app_state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<PromoteSpecRequest>,
+) -> Result<Json<EndpointResponse>, (StatusCode, String)> {
+    let endpoint = app_state
+        .endpoint_service
+        .get_endpoint_by_id(id)
+        .await
+        .map_err(recording_error)?;
+    let swagger_content = match request.swagger_content {
+        Some(content) => content,
+        None => app_state
+            .recording_service
+            .synthesize(&endpoint)
+            .await
+            .map_err(recording_error)?
+            .draft
+            .to_string(),
+    };
+    let draft: serde_json::Value = serde_json::from_str(&swagger_content).map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            format!("Invalid swagger content: {}", e),
+        )
+    })?;
+    if is_recording_spec(&draft) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Invalid swagger content: draft is still marked as recording".to_string(),
+        ));
+    }
+    validate_swagger_servers(&swagger_content).map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+
+    let response = app_state
+        .endpoint_service
+        .update_endpoint(
+            id,
+            UpdateEndpointRequest {
+                name: None,
+                description: None,
+                swagger_content: Some(swagger_content),
+                status: None,
+                preferred_content_type: None,
+                mock_mode: None,
+                server_variables: None,
+                status_mapping: None,
+                tool_stats: None,
+                hide_deprecated: None,
+                tags: None,
+                force_embeddings: false,
+            },
+        )
+        .await
+        .map_err(recording_error)?;
+    app_state
+        .endpoint_service
+        .rebuild_api_paths(id)
+        .await
+        .map_err(recording_error)?;
+    Ok(Json(response))
+}
+
+fn canary_error(e: anyhow::Error) -> (StatusCode, String) {
+    let msg = e.to_string();
+    if msg.contains("not found") {
+        (StatusCode::NOT_FOUND, msg)
+    } else if msg.contains("Invalid canary config") || msg.contains("Invalid swagger content") {
+        (StatusCode::BAD_REQUEST, msg)
+    } else {
+        (StatusCode::INTERNAL_SERVER_ERROR, msg)
+    }
+}
+
+fn canary_not_found(id: Uuid) -> (StatusCode, String) {
+    (
+        StatusCode::NOT_FOUND,
+        format!("Canary for endpoint {} not found", id),
+    )
+}
+
+/// 获取端点金丝雀配置
+pub async fn get_canary(
+    State(app_state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<CanaryConfig>, (StatusCode, String)> {
+    app_state
+        .canary_service
+        .get(id)
+        .await
+        .map_err(canary_error)?
+        .map(Json)
+        .ok_or_else(|| canary_not_found(id))
+}
+
+/// 创建或修改金丝雀配置，比例调整对后续调用立即生效
+pub async fn put_canary(
+    State(app_state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<UpsertCanaryRequest>,
+) -> Result<Json<CanaryConfig>, (StatusCode, String)> {
+    app_state
+        .endpoint_service
+        .get_endpoint_by_id(id)
+        .await
+        .map_err(canary_error)?;
+    app_state
+        .canary_service
+        .upsert(id, request.base_url.trim(), request.percentage)
+        .await
+        .map(Json)
+        .map_err(canary_error)
+}
+
+/// 对比窗口内主版本与金丝雀的请求数、错误率与 p95 延迟
+pub async fn get_canary_stats(
+    State(app_state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<CanaryStatsQuery>,
+) -> Result<Json<CanaryStats>, (StatusCode, String)> {
+    app_state
+        .canary_service
+        .stats(id, query.window_secs.unwrap_or(DEFAULT_CANARY_WINDOW_SECS))
+        .await
+        .map(Json)
+        .map_err(canary_error)
+}
+
+/// 金丝雀地址写入 spec 成为主版本，并结束金丝雀
+pub async fn promote_canary(
+    State(app_state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<EndpointResponse>, (StatusCode, String)> {
+    let canary = app_state
+        .canary_service
+        .get(id)
+        .await
+        .map_err(canary_error)?
+        .ok_or_else(|| canary_not_found(id))?;
+    let endpoint = app_state
+        .endpoint_service
+        .get_endpoint_by_id(id)
+        .await
+        .map_err(canary_error)?;
+    let swagger_content = promote_swagger_content(&endpoint.swagger_content, &canary.base_url)
+        .map_err(canary_error)?;
+
+    let response = app_state
+        .endpoint_service
+        .update_endpoint(
+            id,
+            UpdateEndpointRequest {
+                name: None,
+                description: None,
+                swagger_content: Some(swagger_content),
+                status: None,
+                preferred_content_type: None,
+                mock_mode: None,
+                server_variables: None,
+                status_mapping: None,
+                tool_stats: None,
+                hide_deprecated: None,
+                tags: None,
+                force_embeddings: false,
+            },
+        )
+        .await
+        .map_err(canary_error)?;
+    app_state
+        .canary_service
+        .delete(id)
+        .await
+        .map_err(canary_error)?;
+    tracing::info!("Promoted canary {} for endpoint {}", canary.base_url, id);
+    Ok(Json(response))
+}
+
+/// 结束金丝雀，全部调用回到主版本
+pub async fn abort_canary(
+    State(app_state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    match app_state
+        .canary_service
+        .delete(id)
+        .await
+        .map_err(canary_error)?
+    {
+        Some(canary) => {
+            tracing::info!("Aborted canary {} for endpoint {}", canary.base_url, id);
+            Ok(StatusCode::NO_CONTENT)
+        }
+        None => Err(canary_not_found(id)),
+    }
+}
+
+fn dns_override_error(e: anyhow::Error) -> (StatusCode, String) {
+    let msg = e.to_string();
+    if msg.contains("not found") {
+        (StatusCode::NOT_FOUND, msg)
+    } else if msg.contains("Invalid DNS override") {
+        (StatusCode::BAD_REQUEST, msg)
+    } else {
+        (StatusCode::INTERNAL_SERVER_ERROR, msg)
+    }
+}
+
+/// 获取端点的静态解析
+pub async fn get_dns_overrides(
+    State(app_state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<EndpointDnsOverrides>, (StatusCode, String)> {
+    app_state
+        .dns_override_service
+        .get(id)
+        .await
+        .map(Json)
+        .map_err(dns_override_error)
+}
+
+/// 整体替换端点的静态解析（主机名 → IP），传空对象清除
+pub async fn put_dns_overrides(
+    State(app_state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<PutDnsOverridesRequest>,
+) -> Result<Json<EndpointDnsOverrides>, (StatusCode, String)> {
+    app_state
+        .endpoint_service
+        .get_endpoint_by_id(id)
+        .await
+        .map_err(dns_override_error)?;
+    let overrides = app_state
+        .dns_override_service
+        .replace(id, &request.overrides)
+        .await
+        .map_err(dns_override_error)?;
+    tracing::info!(
+        "Set {} DNS overrides for endpoint {}",
+        overrides.overrides.len(),
+        id
+    );
+    Ok(Json(overrides))
+}
+
+fn locale_settings_error(e: anyhow::Error) -> (StatusCode, String) {
+    let msg = e.to_string();
+    if msg.contains("not found") {
+        (StatusCode::NOT_FOUND, msg)
+    } else if msg.contains("Invalid locale settings") {
+        (StatusCode::BAD_REQUEST, msg)
+    } else {
+        (StatusCode::INTERNAL_SERVER_ERROR, msg)
+    }
+}
+
+/// 获取端点的语言转发设置
+pub async fn get_locale_settings(
+    State(app_state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<LocaleSettings>, (StatusCode, String)> {
+    app_state
+        .locale_service
+        .get_settings(id)
+        .await
+        .map(Json)
+        .map_err(locale_settings_error)
+}
+
+/// 修改端点是否转发 Accept-Language 及默认语言，对后续调用立即生效
+pub async fn put_locale_settings(
+    State(app_state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(settings): Json<LocaleSettings>,
+) -> Result<Json<LocaleSettings>, (StatusCode, String)> {
+    app_state
+        .endpoint_service
+        .get_endpoint_by_id(id)
+        .await
+        .map_err(locale_settings_error)?;
+    app_state
+        .locale_service
+        .update_settings(id, settings)
+        .await
+        .map(Json)
+        .map_err(locale_settings_error)
+}
@@ -1,12 +1,20 @@
 use crate::config::EmbeddingConfig;
 use crate::models::interface_retrieval::*;
 use crate::models::DbPool;
-use crate::services::interface_retrieval_service::InterfaceRetrievalService;
-use crate::services::EmbeddingService;
+use crate::models::SwaggerSpec;
+use crate::models::{FeedbackRangeQuery, FeedbackResult, FeedbackStats};
+use crate::services::interface_retrieval_service::{
+    InterfaceRetrievalService, InvalidVectorWeight,
+};
+use crate::services::{
+    embedding_text_builder, interfaces_from_spec, is_es_timeout, EmbeddingService,
+    SearchFeedbackService, EMBEDDING_TEXT_VERSION,
+};
+use crate::utils::{json_stream_response, JsonFraming};
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
-    response::Json,
+    response::{Json, Response},
     routing::{delete, get, post},
     Router,
 };
@@ -54,11 +62,107 @@ pub fn create_interface_relation_routes() -> Router<InterfaceRetrievalState> {
             post(parse_swagger_json),
         )
         .route("/api/interface-retrieval/search", post(search_interfaces))
+        .route(
+            "/api/interface-retrieval/endpoints/search",
+            get(search_endpoints),
+        )
         .route("/api/interface-retrieval/projects", get(get_projects))
         .route(
             "/api/interface-retrieval/projects/{project_id}",
             delete(delete_project_data),
         )
+        .route(
+            "/api/interface-retrieval/projects/{project_id}/interfaces",
+            get(list_project_interfaces),
+        )
+        .route(
+            "/api/interface-retrieval/projects/{project_id}/embedding-text",
+            get(get_embedding_text),
+        )
+        .route(
+            "/api/interface-retrieval/feedback/stats",
+            get(get_feedback_stats),
+        )
+        .route(
+            "/api/interface-retrieval/feedback/export",
+            get(export_feedback),
+        )
+}
+
+/// 向量化文本调试查询参数
+#[derive(Debug, Deserialize)]
+pub struct EmbeddingTextQuery {
+    pub method: String,
+    pub path: String,
+    /// 可选，返回该查询扩展后的文本
+    pub query: Option<String>,
+}
+
+/// 向量化文本调试结果
+#[derive(Debug, Serialize)]
+pub struct EmbeddingTextResponse {
+    pub text: String,
+    pub text_version: u32,
+    pub chars: usize,
+    pub expanded_query: Option<String>,
+}
+
+/// 返回指定操作构建出的向量化文本，用于对比调参效果
+pub async fn get_embedding_text(
+    State(state): State<InterfaceRetrievalState>,
+    Path(project_id): Path<String>,
+    Query(query): Query<EmbeddingTextQuery>,
+) -> Result<Json<EmbeddingTextResponse>, (StatusCode, Json<InterfaceRelationError>)> {
+    let not_found = |message: String| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(InterfaceRelationError {
+                code: "NOT_FOUND".to_string(),
+                message,
+                details: None,
+            }),
+        )
+    };
+    let internal = |e: anyhow::Error| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(InterfaceRelationError {
+                code: "PARSE_ERROR".to_string(),
+                message: e.to_string(),
+                details: None,
+            }),
+        )
+    };
+
+    let swagger_content: String =
+        sqlx::query_scalar("SELECT swagger_content FROM endpoints WHERE name = ?")
+            .bind(&project_id)
+            .fetch_optional(&state.db_pool)
+            .await
+            .map_err(|e| internal(e.into()))?
+            .ok_or_else(|| not_found(format!("Project not found: {}", project_id)))?;
+    let spec: SwaggerSpec =
+        serde_json::from_str(&swagger_content).map_err(|e| internal(e.into()))?;
+
+    let interface = interfaces_from_spec(&spec)
+        .map_err(internal)?
+        .into_iter()
+        .find(|i| i.method.eq_ignore_ascii_case(&query.method) && i.path == query.path)
+        .ok_or_else(|| {
+            not_found(format!(
+                "Operation not found: {} {}",
+                query.method, query.path
+            ))
+        })?;
+
+    let builder = embedding_text_builder();
+    let text = builder.build(&interface);
+    Ok(Json(EmbeddingTextResponse {
+        chars: text.chars().count(),
+        text,
+        text_version: EMBEDDING_TEXT_VERSION,
+        expanded_query: query.query.as_deref().map(|q| builder.expand_query(q)),
+    }))
 }
 
 /// 获取项目列表
@@ -90,6 +194,30 @@ pub async fn get_projects(
     }
 }
 
+/// 分页浏览项目的全部接口，返回不含向量的接口摘要
+pub async fn list_project_interfaces(
+    State(state): State<InterfaceRetrievalState>,
+    Path(project_id): Path<String>,
+    Query(query): Query<InterfaceListQuery>,
+) -> Result<Json<InterfaceListResponse>, (StatusCode, Json<InterfaceRelationError>)> {
+    state
+        .retrieval
+        .list_project_interfaces(&project_id, &query)
+        .await
+        .map(Json)
+        .map_err(|e| {
+            tracing::error!("Failed to list interfaces of {}: {}", project_id, e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(InterfaceRelationError {
+                    code: "LIST_ERROR".to_string(),
+                    message: format!("获取项目接口失败: {}", e),
+                    details: None,
+                }),
+            )
+        })
+}
+
 /// 删除项目数据
 pub async fn delete_project_data(
     State(state): State<InterfaceRetrievalState>,
@@ -171,10 +299,15 @@ pub async fn search_interfaces(
 
     let start_time = Instant::now();
     let search_type = request.search_type.clone();
+    let query = request.query.clone();
+    let project_filter = request.filters.as_ref().and_then(|f| f.project_id.clone());
     match state.retrieval.search_interfaces(request).await {
-        Ok(chunks) => {
+        Ok(result) => {
+            if result.degraded {
+                tracing::warn!("Embedding unavailable, search degraded to keyword-only");
+            }
             let mut interfaces_with_score = Vec::new();
-            for chunk in &chunks {
+            for chunk in &result.chunks {
                 let project_id = chunk
                     .meta
                     .get("project_id")
@@ -188,10 +321,18 @@ pub async fn search_interfaces(
                         project_id: Some(project_id.to_string()),
                         interface: api_interface.clone(),
                         score: chunk.score,
-                        match_reason: format!(
-                            "向量搜索匹配: {} {}",
-                            api_interface.method, api_interface.path
-                        ),
+                        match_reason: if result.degraded {
+                            format!(
+                                "关键词搜索匹配: {} {}",
+                                api_interface.method, api_interface.path
+                            )
+                        } else {
+                            format!(
+                                "向量搜索匹配: {} {}",
+                                api_interface.method, api_interface.path
+                            )
+                        },
+                        highlights: chunk.highlights.clone(),
                     };
 
                     interfaces_with_score.push(interface_with_score);
@@ -204,11 +345,50 @@ pub async fn search_interfaces(
             let total_count = interfaces_with_score.len() as u32;
 
             // 构建响应
+            let facets =
+                SearchFacets::from_interfaces(interfaces_with_score.iter().map(|i| &i.interface));
+            // 反馈记录为尽力而为，不影响检索响应
+            let search_id = uuid::Uuid::new_v4().to_string();
+            let feedback_results: Vec<FeedbackResult> = interfaces_with_score
+                .iter()
+                .enumerate()
+                .map(|(i, item)| FeedbackResult {
+                    rank: i as u32 + 1,
+                    project_id: item.project_id.clone().filter(|p| !p.is_empty()),
+                    method: item.interface.method.clone(),
+                    path: item.interface.path.clone(),
+                    score: item.score,
+                })
+                .collect();
+            let feedback = SearchFeedbackService::new(state.db_pool.clone());
+            let feedback_id = search_id.clone();
+            tokio::spawn(async move {
+                if let Err(e) = feedback
+                    .record_search(
+                        &feedback_id,
+                        &query,
+                        project_filter.as_deref(),
+                        &feedback_results,
+                    )
+                    .await
+                {
+                    tracing::warn!("Failed to record search feedback {}: {}", feedback_id, e);
+                }
+            });
+
             let response = InterfaceSearchResponse {
                 interfaces: interfaces_with_score,
                 query_time_ms,
                 total_count,
-                search_mode: format!("{:?}", search_type),
+                search_mode: if result.degraded {
+                    "KeywordFallback".to_string()
+                } else {
+                    format!("{:?}", search_type)
+                },
+                degraded: result.degraded,
+                facets,
+                search_id,
+                hint: result.hint,
             };
 
             tracing::info!(
@@ -219,6 +399,25 @@ pub async fn search_interfaces(
 
             Ok(Json(response))
         }
+        Err(e) if e.downcast_ref::<InvalidVectorWeight>().is_some() => Err((
+            StatusCode::BAD_REQUEST,
+            Json(InterfaceRelationError {
+                code: "INVALID_VECTOR_WEIGHT".to_string(),
+                message: e.to_string(),
+                details: None,
+            }),
+        )),
+        Err(e) if is_es_timeout(&e) => {
+            tracing::error!("Interface search timed out: {}", e);
+            Err((
+                StatusCode::GATEWAY_TIMEOUT,
+                Json(InterfaceRelationError {
+                    code: "SEARCH_TIMEOUT".to_string(),
+                    message: format!("搜索接口超时: {}", e),
+                    details: None,
+                }),
+            ))
+        }
         Err(e) => {
             tracing::error!("Failed to search interfaces: {}", e);
             Err((
@@ -232,3 +431,78 @@ pub async fn search_interfaces(
         }
     }
 }
+
+/// 端点级检索
+///
+/// 只查询端点摘要索引，按语义找出能完成任务的端点，再在该端点内检索接口
+pub async fn search_endpoints(
+    State(state): State<InterfaceRetrievalState>,
+    Query(query): Query<EndpointSearchQuery>,
+) -> Result<Json<Vec<EndpointMatch>>, (StatusCode, Json<InterfaceRelationError>)> {
+    if query.query.trim().is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(InterfaceRelationError {
+                code: "EMPTY_QUERY".to_string(),
+                message: "搜索查询不能为空".to_string(),
+                details: None,
+            }),
+        ));
+    }
+    state
+        .retrieval
+        .search_endpoints(&query)
+        .await
+        .map(Json)
+        .map_err(|e| {
+            tracing::error!("Failed to search endpoints: {}", e);
+            let (status, code) = if is_es_timeout(&e) {
+                (StatusCode::GATEWAY_TIMEOUT, "SEARCH_TIMEOUT")
+            } else {
+                (StatusCode::INTERNAL_SERVER_ERROR, "SEARCH_ERROR")
+            };
+            (
+                status,
+                Json(InterfaceRelationError {
+                    code: code.to_string(),
+                    message: format!("搜索端点失败: {}", e),
+                    details: None,
+                }),
+            )
+        })
+}
+
+fn feedback_error(e: anyhow::Error) -> (StatusCode, Json<InterfaceRelationError>) {
+    tracing::error!("Failed to load search feedback: {}", e);
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(InterfaceRelationError {
+            code: "FEEDBACK_ERROR".to_string(),
+            message: e.to_string(),
+            details: None,
+        }),
+    )
+}
+
+/// 检索反馈统计：按排名的点击率与按项目的 MRR
+pub async fn get_feedback_stats(
+    State(state): State<InterfaceRetrievalState>,
+    Query(range): Query<FeedbackRangeQuery>,
+) -> Result<Json<FeedbackStats>, (StatusCode, Json<InterfaceRelationError>)> {
+    SearchFeedbackService::new(state.db_pool.clone())
+        .stats(&range)
+        .await
+        .map(Json)
+        .map_err(feedback_error)
+}
+
+/// 以 NDJSON 导出带标注的 (query, 接口) 样本，供离线训练重排模型
+pub async fn export_feedback(
+    State(state): State<InterfaceRetrievalState>,
+    Query(range): Query<FeedbackRangeQuery>,
+) -> Response {
+    json_stream_response(
+        SearchFeedbackService::new(state.db_pool.clone()).stream_labeled_pairs(range),
+        JsonFraming::Ndjson,
+    )
+}
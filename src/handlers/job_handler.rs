@@ -0,0 +1,69 @@
+use crate::models::{Job, JobNotRetryable, JobQueryParams, JobStatus, PaginatedJobsResponse};
+use crate::services::JobService;
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use std::sync::Arc;
+use uuid::Uuid;
+
+#[derive(Clone)]
+pub struct JobState {
+    pub service: Arc<JobService>,
+}
+
+fn job_error(e: anyhow::Error) -> (StatusCode, String) {
+    match e.downcast_ref::<JobNotRetryable>() {
+        Some(not_retryable) => (StatusCode::CONFLICT, not_retryable.to_string()),
+        None => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+/// 列出后台任务，支持按 type、status 过滤
+pub async fn list_jobs(
+    State(state): State<JobState>,
+    Query(params): Query<JobQueryParams>,
+) -> Result<Json<PaginatedJobsResponse>, (StatusCode, String)> {
+    let status = match params.status.as_deref().filter(|s| !s.is_empty()) {
+        Some(status) => Some(JobStatus::parse(status).ok_or_else(|| {
+            (
+                StatusCode::BAD_REQUEST,
+                format!("Invalid job status: {}", status),
+            )
+        })?),
+        None => None,
+    };
+    state
+        .service
+        .list_jobs(
+            params.job_type.as_deref(),
+            status,
+            params.page,
+            params.page_size,
+        )
+        .await
+        .map(Json)
+        .map_err(job_error)
+}
+
+pub async fn get_job(
+    State(state): State<JobState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Job>, (StatusCode, String)> {
+    match state.service.get_job(id).await.map_err(job_error)? {
+        Some(job) => Ok(Json(job)),
+        None => Err((StatusCode::NOT_FOUND, format!("Job {} not found", id))),
+    }
+}
+
+/// 手动重试 pending 或 failed 的任务
+pub async fn retry_job(
+    State(state): State<JobState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Job>, (StatusCode, String)> {
+    match state.service.retry_job(id).await.map_err(job_error)? {
+        Some(job) => Ok(Json(job)),
+        None => Err((StatusCode::NOT_FOUND, format!("Job {} not found", id))),
+    }
+}
@@ -0,0 +1,123 @@
+use crate::models::{KvEntry, KvEntryQuery, KvSettings, PutKvEntryRequest};
+use crate::services::{KvQuota, KvQuotaExceeded};
+use crate::state::AppState;
+use crate::utils::ArgumentError;
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use uuid::Uuid;
+
+fn kv_store_error(e: anyhow::Error) -> (StatusCode, String) {
+    let msg = e.to_string();
+    if let Some(exceeded) = e.downcast_ref::<KvQuotaExceeded>() {
+        return match exceeded.quota {
+            KvQuota::ValueBytes => (StatusCode::PAYLOAD_TOO_LARGE, msg),
+            KvQuota::Entries => (StatusCode::CONFLICT, msg),
+        };
+    }
+    if e.downcast_ref::<ArgumentError>().is_some() {
+        (StatusCode::BAD_REQUEST, msg)
+    } else if msg.contains("not found") {
+        (StatusCode::NOT_FOUND, msg)
+    } else {
+        (StatusCode::INTERNAL_SERVER_ERROR, msg)
+    }
+}
+
+fn kv_entry_not_found(namespace: &str, key: &str) -> (StatusCode, String) {
+    (
+        StatusCode::NOT_FOUND,
+        format!("Key '{}' not found in namespace '{}'", key, namespace),
+    )
+}
+
+/// 获取端点键值存储开关
+pub async fn get_kv_settings(
+    State(app_state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<KvSettings>, (StatusCode, String)> {
+    app_state
+        .kv_store_service
+        .get_settings(id)
+        .await
+        .map(Json)
+        .map_err(kv_store_error)
+}
+
+/// 开启或关闭端点键值存储，关闭后已有数据保留
+pub async fn put_kv_settings(
+    State(app_state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(settings): Json<KvSettings>,
+) -> Result<Json<KvSettings>, (StatusCode, String)> {
+    app_state
+        .kv_store_service
+        .update_settings(id, settings)
+        .await
+        .map(Json)
+        .map_err(kv_store_error)
+}
+
+/// 列出端点未过期的记录，可按 namespace 与键前缀过滤
+pub async fn list_kv_entries(
+    State(app_state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<KvEntryQuery>,
+) -> Result<Json<Vec<KvEntry>>, (StatusCode, String)> {
+    app_state
+        .kv_store_service
+        .list(id, query.namespace.as_deref(), query.prefix.as_deref())
+        .await
+        .map(Json)
+        .map_err(kv_store_error)
+}
+
+pub async fn get_kv_entry(
+    State(app_state): State<AppState>,
+    Path((id, namespace, key)): Path<(Uuid, String, String)>,
+) -> Result<Json<KvEntry>, (StatusCode, String)> {
+    app_state
+        .kv_store_service
+        .get(id, &namespace, &key)
+        .await
+        .map_err(kv_store_error)?
+        .map(Json)
+        .ok_or_else(|| kv_entry_not_found(&namespace, &key))
+}
+
+/// 写入记录，与 kv_set 工具使用相同的配额
+pub async fn put_kv_entry(
+    State(app_state): State<AppState>,
+    Path((id, namespace, key)): Path<(Uuid, String, String)>,
+    Json(request): Json<PutKvEntryRequest>,
+) -> Result<Json<KvEntry>, (StatusCode, String)> {
+    app_state
+        .endpoint_service
+        .get_endpoint_by_id(id)
+        .await
+        .map_err(kv_store_error)?;
+    app_state
+        .kv_store_service
+        .set(id, &namespace, &key, &request.value, request.ttl_secs)
+        .await
+        .map(Json)
+        .map_err(kv_store_error)
+}
+
+pub async fn delete_kv_entry(
+    State(app_state): State<AppState>,
+    Path((id, namespace, key)): Path<(Uuid, String, String)>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let deleted = app_state
+        .kv_store_service
+        .delete(id, &namespace, &key)
+        .await
+        .map_err(kv_store_error)?;
+    if deleted {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(kv_entry_not_found(&namespace, &key))
+    }
+}
@@ -1,6 +1,13 @@
 use crate::models::endpoint::EndpointMetrics;
+use crate::models::{pool_stats, PoolStats};
+use crate::services::{mcp_method_counters, McpMethodMetricsQuery, SchedulerStats, SpecCacheStats};
 use crate::state::AppState;
-use axum::{extract::State, http::StatusCode, response::Json};
+use crate::utils::render_tool_limit_alerts;
+use axum::{
+    extract::{Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
 
 /// Get metrics for all endpoints
 ///
@@ -17,3 +24,63 @@ pub async fn get_all_endpoint_metrics(
         }
     }
 }
+
+/// 工具调用调度统计（排队数、平均排队时间等）
+pub async fn get_scheduler_metrics(State(app_state): State<AppState>) -> Json<SchedulerStats> {
+    Json(app_state.mcp_service.scheduler().stats())
+}
+
+/// swagger 解析缓存统计（命中、未命中、解析耗时）
+pub async fn get_spec_cache_metrics(State(app_state): State<AppState>) -> Json<SpecCacheStats> {
+    Json(app_state.spec_cache.stats())
+}
+
+/// 数据库连接池指标（连接数、空闲数、等待数、获取超时次数）
+pub async fn get_db_pool_metrics() -> Json<Vec<PoolStats>> {
+    Json(pool_stats())
+}
+
+/// 存有工具阈值告警的端点；format=prometheus 时输出 mcp_tool_limit_warnings{endpoint,name}
+pub async fn get_tool_limit_metrics(
+    State(app_state): State<AppState>,
+    Query(query): Query<McpMethodMetricsQuery>,
+) -> Result<Response, (StatusCode, String)> {
+    let alerts = app_state
+        .endpoint_service
+        .tool_limit_alerts()
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to load tool limit alerts: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+        })?;
+    match query.format.as_deref() {
+        None | Some("json") => Ok(Json(alerts).into_response()),
+        Some("prometheus") => Ok((
+            [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+            render_tool_limit_alerts(&alerts),
+        )
+            .into_response()),
+        Some(other) => Err((
+            StatusCode::BAD_REQUEST,
+            format!("Invalid metrics format: {}", other),
+        )),
+    }
+}
+
+/// 各端点收到的 MCP 方法计数 mcp_method_total{endpoint,method}
+pub async fn get_mcp_method_metrics(
+    Query(query): Query<McpMethodMetricsQuery>,
+) -> Result<Response, (StatusCode, String)> {
+    match query.format.as_deref() {
+        None | Some("json") => Ok(Json(mcp_method_counters().snapshot()).into_response()),
+        Some("prometheus") => Ok((
+            [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+            mcp_method_counters().render_prometheus(),
+        )
+            .into_response()),
+        Some(other) => Err((
+            StatusCode::BAD_REQUEST,
+            format!("Invalid metrics format: {}", other),
+        )),
+    }
+}
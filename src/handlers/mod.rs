@@ -1,20 +1,34 @@
+pub mod analytics_handler;
+pub mod batch_call_handler;
 pub mod connection_handler;
+pub mod demo_handler;
 pub mod endpoint_handler;
 pub mod file_handler;
 pub mod health_handler;
 pub mod interface_retrieval_handler;
+pub mod job_handler;
+pub mod kv_store_handler;
 pub mod metrics_handler;
+pub mod plugin_handler;
+pub mod report_handler;
 pub mod swagger_handler;
 pub mod swagger_mcp;
 pub mod system_handler;
 pub mod table_rag_handler;
 
+pub use analytics_handler::*;
+pub use batch_call_handler::*;
 pub use connection_handler::*;
+pub use demo_handler::*;
 pub use endpoint_handler::*;
 pub use file_handler::*;
 pub use health_handler::*;
 pub use interface_retrieval_handler::*;
+pub use job_handler::*;
+pub use kv_store_handler::*;
 pub use metrics_handler::*;
+pub use plugin_handler::*;
+pub use report_handler::*;
 pub use swagger_handler::*;
 pub use swagger_mcp::*;
 pub use system_handler::*;
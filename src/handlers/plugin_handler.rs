@@ -0,0 +1,124 @@
+use crate::models::{EndpointPlugin, TestPluginRequest, TestPluginResponse};
+use crate::services::PluginService;
+use crate::utils::PluginError;
+use axum::{
+    extract::{Multipart, Path, State},
+    http::StatusCode,
+    Json,
+};
+use std::sync::Arc;
+use uuid::Uuid;
+
+#[derive(Clone)]
+pub struct PluginState {
+    pub service: Arc<PluginService>,
+}
+
+fn plugin_error(e: anyhow::Error) -> (StatusCode, String) {
+    let msg = e.to_string();
+    match e.downcast_ref::<PluginError>() {
+        Some(PluginError::TooLarge { .. }) => (StatusCode::PAYLOAD_TOO_LARGE, msg),
+        Some(PluginError::Invalid(_)) | Some(PluginError::MissingHook(_)) => {
+            (StatusCode::BAD_REQUEST, msg)
+        }
+        // 试运行时模块 trap、超限或输出无效
+        Some(_) => (StatusCode::UNPROCESSABLE_ENTITY, msg),
+        None if msg.contains("not found") => (StatusCode::NOT_FOUND, msg),
+        None => (StatusCode::INTERNAL_SERVER_ERROR, msg),
+    }
+}
+
+fn plugin_not_found(id: Uuid) -> (StatusCode, String) {
+    (
+        StatusCode::NOT_FOUND,
+        format!("Plugin for endpoint {} not found", id),
+    )
+}
+
+/// 获取端点当前插件的版本与哈希
+pub async fn get_plugin(
+    State(state): State<PluginState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<EndpointPlugin>, (StatusCode, String)> {
+    state
+        .service
+        .get(id)
+        .await
+        .map_err(plugin_error)?
+        .map(Json)
+        .ok_or_else(|| plugin_not_found(id))
+}
+
+/// 上传插件模块（multipart 第一个文件字段），校验通过后替换当前版本
+pub async fn put_plugin(
+    State(state): State<PluginState>,
+    Path(id): Path<Uuid>,
+    mut multipart: Multipart,
+) -> Result<Json<EndpointPlugin>, (StatusCode, String)> {
+    let field = multipart
+        .next_field()
+        .await
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?
+        .ok_or_else(|| (StatusCode::BAD_REQUEST, "Missing module file".to_string()))?;
+    let bytes = field
+        .bytes()
+        .await
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+    state
+        .service
+        .upload(id, bytes.to_vec())
+        .await
+        .map(Json)
+        .map_err(plugin_error)
+}
+
+/// 删除插件，之后的调用不再经过插件
+pub async fn delete_plugin(
+    State(state): State<PluginState>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    match state.service.delete(id).await.map_err(plugin_error)? {
+        Some(_) => Ok(StatusCode::NO_CONTENT),
+        None => Err(plugin_not_found(id)),
+    }
+}
+
+/// 用样例数据试运行插件钩子，返回输出与资源消耗；失败时不透传而是返回错误
+pub async fn test_plugin(
+    State(state): State<PluginState>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<TestPluginRequest>,
+) -> Result<Json<TestPluginResponse>, (StatusCode, String)> {
+    state
+        .service
+        .test_invoke(id, request)
+        .await
+        .map(Json)
+        .map_err(plugin_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plugin_error_status_codes() {
+        let status = |e: anyhow::Error| plugin_error(e).0;
+        assert_eq!(
+            status(PluginError::TooLarge { size: 2, max: 1 }.into()),
+            StatusCode::PAYLOAD_TOO_LARGE
+        );
+        assert_eq!(
+            status(PluginError::Invalid("not a wasm binary".to_string()).into()),
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            status(PluginError::FuelExhausted(10).into()),
+            StatusCode::UNPROCESSABLE_ENTITY
+        );
+        assert_eq!(
+            status(anyhow::anyhow!("Endpoint {} not found", Uuid::nil())),
+            StatusCode::NOT_FOUND
+        );
+    }
+}
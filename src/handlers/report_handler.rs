@@ -0,0 +1,146 @@
+use crate::models::{
+    CreateUsageReportRequest, ReportRunDownloadQuery, UpdateUsageReportRequest, UsageReport,
+    UsageReportRun,
+};
+use crate::services::UsageReportService;
+use axum::{
+    body::Body,
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use std::sync::Arc;
+use uuid::Uuid;
+
+#[derive(Clone)]
+pub struct ReportState {
+    pub service: Arc<UsageReportService>,
+}
+
+fn report_error(e: anyhow::Error) -> (StatusCode, String) {
+    let msg = e.to_string();
+    if msg.contains("not found") {
+        (StatusCode::NOT_FOUND, msg)
+    } else if msg.contains("Invalid report") {
+        (StatusCode::BAD_REQUEST, msg)
+    } else if msg.contains("not completed") {
+        (StatusCode::CONFLICT, msg)
+    } else {
+        (StatusCode::INTERNAL_SERVER_ERROR, msg)
+    }
+}
+
+pub async fn create_usage_report(
+    State(state): State<ReportState>,
+    Json(request): Json<CreateUsageReportRequest>,
+) -> Result<(StatusCode, Json<UsageReport>), (StatusCode, String)> {
+    state
+        .service
+        .create_report(request)
+        .await
+        .map(|report| (StatusCode::CREATED, Json(report)))
+        .map_err(report_error)
+}
+
+pub async fn list_usage_reports(
+    State(state): State<ReportState>,
+) -> Result<Json<Vec<UsageReport>>, (StatusCode, String)> {
+    state
+        .service
+        .list_reports()
+        .await
+        .map(Json)
+        .map_err(report_error)
+}
+
+pub async fn get_usage_report(
+    State(state): State<ReportState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<UsageReport>, (StatusCode, String)> {
+    state
+        .service
+        .get_report(id)
+        .await
+        .map(Json)
+        .map_err(report_error)
+}
+
+pub async fn update_usage_report(
+    State(state): State<ReportState>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<UpdateUsageReportRequest>,
+) -> Result<Json<UsageReport>, (StatusCode, String)> {
+    state
+        .service
+        .update_report(id, request)
+        .await
+        .map(Json)
+        .map_err(report_error)
+}
+
+pub async fn delete_usage_report(
+    State(state): State<ReportState>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    state
+        .service
+        .delete_report(id)
+        .await
+        .map(|_| StatusCode::NO_CONTENT)
+        .map_err(report_error)
+}
+
+/// 报表的历史运行，附下载地址
+pub async fn list_usage_report_runs(
+    State(state): State<ReportState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<UsageReportRun>>, (StatusCode, String)> {
+    state
+        .service
+        .list_runs(id)
+        .await
+        .map(Json)
+        .map_err(report_error)
+}
+
+/// 下载运行生成的 JSON 或 Markdown 文件，从存储分块读取并流式返回
+pub async fn download_usage_report_run(
+    State(state): State<ReportState>,
+    Path((id, run_id)): Path<(Uuid, Uuid)>,
+    Query(query): Query<ReportRunDownloadQuery>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let markdown = match query.format.as_deref() {
+        None | Some("json") => false,
+        Some("markdown") | Some("md") => true,
+        Some(other) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!("Invalid report format: {}", other),
+            ))
+        }
+    };
+    let reader = state
+        .service
+        .open_run_file(id, run_id, markdown)
+        .await
+        .map_err(report_error)?;
+    let (content_type, extension) = if markdown {
+        ("text/markdown; charset=utf-8", "md")
+    } else {
+        ("application/json", "json")
+    };
+    Ok((
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!(
+                    "attachment; filename=\"usage-report-{}.{}\"",
+                    run_id, extension
+                ),
+            ),
+        ],
+        Body::from_stream(reader),
+    ))
+}
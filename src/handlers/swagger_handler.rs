@@ -1,6 +1,14 @@
-use crate::models::{SwaggerToMcpRequest, SwaggerToMcpResponse};
+use crate::models::{
+    PostmanImportRequest, PostmanImportResponse, SwaggerMergeConflict, SwaggerMergeRequest,
+    SwaggerMergeResponse, SwaggerPreviewRequest, SwaggerPreviewResponse, SwaggerToMcpRequest,
+    SwaggerToMcpResponse,
+};
 use crate::state::AppState;
+use axum::extract::Path;
+use axum::response::{IntoResponse, Response};
 use axum::{extract::State, http::StatusCode, response::Json};
+use serde_json::json;
+use uuid::Uuid;
 
 // #[utoipa::path(
 //     post,
@@ -42,9 +50,12 @@ pub async fn convert_swagger_to_mcp(
 
             // Check if it's a validation error
             let error_msg = e.to_string();
-            if error_msg.contains("OpenAPI")
+            if error_msg.starts_with("Invalid endpoint name") {
+                Err((StatusCode::BAD_REQUEST, error_msg))
+            } else if error_msg.contains("OpenAPI")
                 || error_msg.contains("swagger")
                 || error_msg.contains("parse")
+                || error_msg.starts_with("Invalid operation")
             {
                 Err((
                     StatusCode::BAD_REQUEST,
@@ -56,3 +67,111 @@ pub async fn convert_swagger_to_mcp(
         }
     }
 }
+
+/// 预览 swagger 生成的工具与接口详情，不创建端点
+pub async fn preview_swagger_tools(
+    State(app_state): State<AppState>,
+    Json(request): Json<SwaggerPreviewRequest>,
+) -> Result<Json<SwaggerPreviewResponse>, (StatusCode, String)> {
+    if request.swagger_content.trim().is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Swagger content is required".to_string(),
+        ));
+    }
+
+    app_state
+        .swagger_service
+        .preview_tools(&request.swagger_content)
+        .map(Json)
+        .map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                format!("Invalid swagger content: {}", e),
+            )
+        })
+}
+
+/// 导入 Postman Collection v2.1，转换为 OpenAPI 3 后创建或合并端点
+pub async fn import_postman_collection(
+    State(app_state): State<AppState>,
+    Json(request): Json<PostmanImportRequest>,
+) -> Result<(StatusCode, Json<PostmanImportResponse>), (StatusCode, String)> {
+    if request.endpoint_name.trim().is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Endpoint name is required".to_string(),
+        ));
+    }
+
+    match app_state.swagger_service.import_postman(request).await {
+        Ok(response) => Ok((StatusCode::CREATED, Json(response))),
+        Err(e) => {
+            tracing::error!("Failed to import Postman collection: {}", e);
+            let error_msg = e.to_string();
+            if error_msg.contains("Postman")
+                || error_msg.starts_with("Invalid tag")
+                || error_msg.starts_with("Invalid endpoint name")
+            {
+                Err((StatusCode::BAD_REQUEST, error_msg))
+            } else if error_msg.contains("OpenAPI") || error_msg.contains("At least one path") {
+                Err((
+                    StatusCode::BAD_REQUEST,
+                    format!("Invalid swagger content: {}", error_msg),
+                ))
+            } else {
+                Err((StatusCode::INTERNAL_SERVER_ERROR, error_msg))
+            }
+        }
+    }
+}
+
+/// 向已有端点合并 swagger，存在重复的路径与方法时返回 409 与完整冲突列表，不做任何修改
+pub async fn merge_endpoint_swagger(
+    State(app_state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<SwaggerMergeRequest>,
+) -> Result<Json<SwaggerMergeResponse>, Response> {
+    if request.swagger_content.trim().is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Swagger content is required".to_string(),
+        )
+            .into_response());
+    }
+
+    app_state
+        .swagger_service
+        .merge_swagger(id, request)
+        .await
+        .map(Json)
+        .map_err(|e| {
+            if let Some(conflict) = e.downcast_ref::<SwaggerMergeConflict>() {
+                return (
+                    StatusCode::CONFLICT,
+                    Json(
+                        json!({ "message": conflict.to_string(), "conflicts": conflict.conflicts }),
+                    ),
+                )
+                    .into_response();
+            }
+            let error_msg = e.to_string();
+            if error_msg.contains("not found") {
+                (StatusCode::NOT_FOUND, error_msg).into_response()
+            } else if error_msg.contains("OpenAPI")
+                || error_msg.contains("swagger")
+                || error_msg.contains("parse")
+                || error_msg.starts_with("Invalid operation")
+                || error_msg.starts_with("Tool limits exceeded")
+            {
+                (
+                    StatusCode::BAD_REQUEST,
+                    format!("Invalid swagger content: {}", error_msg),
+                )
+                    .into_response()
+            } else {
+                tracing::error!("Failed to merge swagger into endpoint {}: {}", id, e);
+                (StatusCode::INTERNAL_SERVER_ERROR, error_msg).into_response()
+            }
+        })
+}
@@ -1,32 +1,136 @@
 #![allow(dead_code)]
 
-use crate::models::{Endpoint, DB_POOL};
+use crate::models::endpoint::WebhookDetail;
+use crate::models::{
+    acquire_connection, CompositeTool, DbPool, Endpoint, RecordedCall, SwaggerSpec, DB_POOL,
+    MCP_CALL_POOL,
+};
+use crate::services::{
+    annotate_blocked_tools, annotate_mocked_tools, annotate_tool_stats, api_key_methods,
+    apply_status_mapping, cap_body, composite_to_mcp_tool, endpoint_select,
+    execution_policy_config, is_kv_tool, is_mocked, kv_namespace, kv_store_config, kv_tools,
+    list_composite_tools, list_tools_result, locale_from_capability, log_cancelled_call,
+    log_composite_step, mcp_method_counters, narrow, normalize_locale, parse_methods, record_call,
+    recording_config, render_template, session_methods_from_capability, session_policies,
+    should_record, step_failed, step_output, tool_call_error, tool_error_result, tool_stats,
+    tools_version, with_queue_wait, EffectivePolicy, ExecutionPolicyService, FairScheduler,
+    KvStoreService, McpService, OperationNoteService, OperationNotes, SearchFeedbackService,
+    SpecCache, CANARY_ARGUMENT, CANARY_HEADER, HTTP_REQUEST_TOOL, IF_VERSION_META_KEY,
+    LOCALE_ARGUMENT, LOCALE_CAPABILITY, LOCALE_HEADER, OPERATOR_NOTES_MAX_CHARS,
+    QUEUE_WAIT_META_KEY, SEARCH_ID_META_KEY, SESSION_POLICY_CAPABILITY, TOOLS_VERSION_CAPABILITY,
+};
 use crate::utils::{
-    build_base_url, build_url, extract_endpoint_id, extract_request_parts, parse_tool_name,
-    update_metrics,
+    build_base_url, cancellation_registry, classify_call_error, deadline_config,
+    endpoint_http_client, extract_endpoint_id, fault_injector, generate_webhook_details,
+    http_client, inject_faults, is_idempotent_method, propagate_deadline, request_id_key,
+    take_client_deadline, update_metrics, upstream_guard, with_call_deadline, CallDeadline,
+    DeadlineExceeded, RequestCancelled, SessionTerminated, DEADLINE_META_KEY,
 };
 use anyhow::{anyhow, Error};
 use reqwest::Client;
 use rmcp::model::CallToolResult;
-use rmcp::{model::*, service::RequestContext, ErrorData as McpError, RoleServer, ServerHandler};
+use rmcp::transport::common::http_header::HEADER_SESSION_ID;
+use rmcp::{
+    model::*,
+    service::{NotificationContext, RequestContext},
+    ErrorData as McpError, RoleServer, ServerHandler,
+};
 use serde_json::{json, Value};
+use std::collections::BTreeSet;
 use std::future::Future;
+use std::sync::{Arc, RwLock};
 use uuid::Uuid;
 
+/// 请求层策略输入：API key 请求头与会话策略（initialize 声明与当前请求头的交集）
+fn request_policy_inputs(
+    declared: Option<BTreeSet<String>>,
+    parts: Option<&axum::http::request::Parts>,
+) -> (Option<String>, Option<BTreeSet<String>>) {
+    let config = execution_policy_config();
+    let header = |name: &str| {
+        parts
+            .and_then(|p| p.headers.get(name))
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string)
+    };
+    let session = narrow(
+        declared,
+        header(&config.session_header).map(|v| parse_methods(v.split(','))),
+    );
+    (header(&config.api_key_header), session)
+}
+
 #[derive(Clone)]
 pub struct Adapter {
     http_client: Client,
+    /// 会话在 initialize 时声明的允许方法，Adapter 按会话创建
+    session_policy: Arc<RwLock<Option<BTreeSet<String>>>>,
+    /// 无会话 id 时（如无状态模式）代替会话 id 登记进行中的调用
+    instance_id: String,
+    /// 请求携带的会话 id，用于金丝雀粘性分配与取消登记
+    session_id: Arc<RwLock<Option<String>>>,
+    /// 会话在 initialize 时指定的语言，以 Accept-Language 转发给上游
+    session_locale: Arc<RwLock<Option<String>>>,
+    /// 与 HTTP 接口共用的工具调用调度器
+    scheduler: Arc<FairScheduler>,
+    /// 与 HTTP 接口共用的端点解析缓存
+    spec_cache: Arc<SpecCache>,
 }
 
 impl Adapter {
-    pub fn new() -> Self {
+    pub fn new(scheduler: Arc<FairScheduler>, spec_cache: Arc<SpecCache>) -> Self {
         Self {
-            http_client: Client::new(),
+            http_client: http_client().clone(),
+            session_policy: Arc::new(RwLock::new(None)),
+            instance_id: Uuid::new_v4().to_string(),
+            session_id: Arc::new(RwLock::new(None)),
+            session_locale: Arc::new(RwLock::new(None)),
+            scheduler,
+            spec_cache,
+        }
+    }
+
+    /// 无 MCP 会话的调用（如批量调用接口）按请求头指定语言
+    pub fn with_locale(self, locale: Option<String>) -> Self {
+        if let Ok(mut current) = self.session_locale.write() {
+            *current = locale;
+        }
+        self
+    }
+
+    /// 端点、API key、会话三层执行策略；会话层为 initialize 声明与当前请求头的交集
+    async fn effective_policy(
+        &self,
+        endpoint_id: Uuid,
+        parts: Option<&axum::http::request::Parts>,
+    ) -> anyhow::Result<EffectivePolicy> {
+        let (api_key, session) = self.request_policy_inputs(parts);
+
+        let session_id = parts.and_then(request_session_id);
+        if let (Some(session_id), Some(methods)) = (&session_id, &session) {
+            session_policies().insert(session_id.clone(), methods.clone());
+        }
+        if let (Some(session_id), Ok(mut current)) = (session_id, self.session_id.write()) {
+            *current = Some(session_id);
         }
+
+        ExecutionPolicyService::new(self.pool().clone(), self.spec_cache.clone())
+            .effective_policy(endpoint_id, api_key.as_deref(), session)
+            .await
+    }
+
+    /// 请求层策略输入，会话在 initialize 时声明的策略取自当前 Adapter
+    fn request_policy_inputs(
+        &self,
+        parts: Option<&axum::http::request::Parts>,
+    ) -> (Option<String>, Option<BTreeSet<String>>) {
+        let declared = self.session_policy.read().ok().and_then(|p| p.clone());
+        request_policy_inputs(declared, parts)
     }
 
     async fn inner_list_tools(
         &self,
+        request: Option<PaginatedRequestParam>,
         context: RequestContext<RoleServer>,
     ) -> Result<ListToolsResult, McpError> {
         tracing::info!("listing tools");
@@ -36,17 +140,228 @@ impl Adapter {
         } else {
             Err(McpError::parse_error("not found endpoint", None))
         }?;
+        let parts = context.extensions.get::<axum::http::request::Parts>();
+        let mut tools = self.endpoint_tools(endpoint_id, parts).await;
+        // 开启运行统计的端点附带统计快照，统计不影响工具列表版本
+        if self
+            .get_endpoint(endpoint_id)
+            .await
+            .is_ok_and(|endpoint| endpoint.tool_stats)
+        {
+            annotate_tool_stats(&mut tools, endpoint_id, tool_stats());
+        }
+        // 客户端携带的版本未变化时返回空列表，版本见 Mcp-Tools-Etag 响应头
+        let if_version = context
+            .meta
+            .get(IF_VERSION_META_KEY)
+            .and_then(|v| v.as_str());
+        let cursor = request.as_ref().and_then(|r| r.cursor.as_deref());
+        list_tools_result(tools, if_version, cursor)
+            .map_err(|e| McpError::invalid_params(e.to_string(), None))
+    }
+
+    /// 端点对当前请求可见的工具：spec 工具（含运维备注、策略与 mock 标注）与组合工具
+    async fn endpoint_tools(
+        &self,
+        endpoint_id: Uuid,
+        parts: Option<&axum::http::request::Parts>,
+    ) -> Vec<Tool> {
+        let policy = self.effective_policy(endpoint_id, parts).await;
+        self.visible_tools(endpoint_id, policy).await.0
+    }
+
+    /// 按已解析的执行策略列出工具；任一输入加载失败时降级返回，第二项为 false 表示结果不完整
+    async fn visible_tools(
+        &self,
+        endpoint_id: Uuid,
+        policy: anyhow::Result<EffectivePolicy>,
+    ) -> (Vec<Tool>, bool) {
         if let Ok(endpoint) = self.get_endpoint(endpoint_id).await {
-            let tools = <Vec<Tool>>::from(&endpoint);
+            let mut complete = true;
+            let policy = match policy {
+                Ok(policy) => policy,
+                Err(e) => {
+                    tracing::warn!(
+                        "Failed to resolve execution policy for {}: {}",
+                        endpoint_id,
+                        e
+                    );
+                    complete = false;
+                    EffectivePolicy::default()
+                }
+            };
+            let mut tools = match self.spec_cache.get_or_parse(&endpoint).await {
+                Ok(cached) => {
+                    let notes = match self.tool_notes(endpoint_id).await {
+                        Ok(notes) => notes,
+                        Err(e) => {
+                            tracing::warn!(
+                                "Failed to load operator notes for {}: {}",
+                                endpoint_id,
+                                e
+                            );
+                            complete = false;
+                            None
+                        }
+                    };
+                    let mut tools = match notes {
+                        Some(notes) => cached.tools_with_notes(&notes, OPERATOR_NOTES_MAX_CHARS),
+                        None => cached.tools.clone(),
+                    };
+                    if endpoint.hide_deprecated {
+                        tools.retain(|tool| !cached.is_deprecated(&tool.name));
+                    }
+                    // 被策略拦截的工具保留在列表中并标注
+                    annotate_blocked_tools(&mut tools, &policy, |name| {
+                        cached
+                            .operation(name)
+                            .ok()
+                            .map(|(method, _, _)| method.to_string())
+                    });
+                    annotate_mocked_tools(&mut tools, |name| {
+                        cached
+                            .operation(name)
+                            .is_ok_and(|(_, _, operation)| is_mocked(&endpoint, operation))
+                    });
+                    tools
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to parse swagger for {}: {}", endpoint_id, e);
+                    complete = false;
+                    vec![]
+                }
+            };
+            // 组合工具与普通工具一同暴露
+            match list_composite_tools(self.pool(), endpoint_id).await {
+                Ok(composites) => tools.extend(
+                    composites
+                        .iter()
+                        .map(|composite| Tool::from(&composite_to_mcp_tool(composite))),
+                ),
+                Err(e) => {
+                    tracing::warn!("Failed to load composite tools for {}: {}", endpoint_id, e);
+                    complete = false;
+                }
+            }
+            // 开启键值存储时追加 kv_* 工具，与 spec 工具重名时以 spec 为准
+            let kv_enabled = match self.kv_settings_enabled(endpoint_id).await {
+                Ok(enabled) => enabled,
+                Err(e) => {
+                    tracing::warn!("Failed to load kv settings for {}: {}", endpoint_id, e);
+                    complete = false;
+                    false
+                }
+            };
+            if kv_enabled {
+                let kv_tools: Vec<Tool> = kv_tools()
+                    .into_iter()
+                    .filter(|kv| tools.iter().all(|tool| tool.name != kv.name))
+                    .collect();
+                tools.extend(kv_tools);
+            }
             tracing::info!("tools size: {}", tools.len());
             tracing::debug!("tools content: {:?}", tools);
-            Ok(ListToolsResult::with_all_items(tools))
+            (tools, complete)
         } else {
             tracing::info!("empty tools");
-            Ok(ListToolsResult::with_all_items(vec![]))
+            (vec![], false)
+        }
+    }
+
+    /// 按 HTTP 请求计算端点工具版本。Adapter 由 tools_etag 中间件共享，不读写会话状态：
+    /// 会话在 initialize 时声明的策略从全局会话策略中读取；有输入加载失败时不缓存版本
+    pub async fn request_tools_version(
+        &self,
+        endpoint_id: Uuid,
+        parts: &axum::http::request::Parts,
+    ) -> String {
+        let declared = parts
+            .headers
+            .get(HEADER_SESSION_ID)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|session_id| session_policies().get(session_id).map(|m| m.clone()));
+        // 工具列表只随请求层策略解析出的方法集合变化，据此区分缓存
+        let (api_key, session) = request_policy_inputs(declared, Some(parts));
+        let policy_key = format!("{:?}|{:?}", api_key_methods(api_key.as_deref()), session);
+        let endpoint = self.get_endpoint(endpoint_id).await.ok();
+        if let Some(version) = endpoint
+            .as_ref()
+            .and_then(|endpoint| self.spec_cache.cached_tools_version(endpoint, &policy_key))
+        {
+            return version;
+        }
+        let policy = ExecutionPolicyService::new(self.pool().clone(), self.spec_cache.clone())
+            .effective_policy(endpoint_id, api_key.as_deref(), session)
+            .await;
+        let (tools, complete) = self.visible_tools(endpoint_id, policy).await;
+        let version = tools_version(&tools);
+        if let (Some(endpoint), true) = (&endpoint, complete) {
+            self.spec_cache
+                .store_tools_version(endpoint, policy_key, version.clone());
+        }
+        version
+    }
+
+    /// 端点是否开启键值存储，查询失败时视为关闭
+    async fn kv_enabled(&self, endpoint_id: Uuid) -> bool {
+        match self.kv_settings_enabled(endpoint_id).await {
+            Ok(enabled) => enabled,
+            Err(e) => {
+                tracing::warn!("Failed to load kv settings for {}: {}", endpoint_id, e);
+                false
+            }
         }
     }
 
+    async fn kv_settings_enabled(&self, endpoint_id: Uuid) -> anyhow::Result<bool> {
+        Ok(self.kv_store().get_settings(endpoint_id).await?.enabled)
+    }
+
+    fn kv_store(&self) -> KvStoreService {
+        KvStoreService::new(
+            self.pool().clone(),
+            kv_store_config().clone(),
+            self.spec_cache.clone(),
+        )
+    }
+
+    /// 键值存储工具调用所属的 namespace；不是键值存储工具（未开启或与 spec 工具重名）时返回 None
+    async fn kv_call_namespace(
+        &self,
+        endpoint_id: Uuid,
+        tool_name: &str,
+        parts: Option<&axum::http::request::Parts>,
+    ) -> Option<anyhow::Result<String>> {
+        if !is_kv_tool(tool_name) || !self.kv_enabled(endpoint_id).await {
+            return None;
+        }
+        if let Ok(endpoint) = self.get_endpoint(endpoint_id).await {
+            if let Ok(cached) = self.spec_cache.get_or_parse(&endpoint).await {
+                if cached.operation(tool_name).is_ok() {
+                    return None;
+                }
+            }
+        }
+        let api_key = parts
+            .and_then(|p| p.headers.get(&execution_policy_config().api_key_header))
+            .and_then(|v| v.to_str().ok());
+        let session_id = parts.and_then(request_session_id);
+        Some(
+            kv_namespace(api_key, session_id.as_deref())
+                .ok_or_else(|| anyhow!("Key-value tools require an API key or a session")),
+        )
+    }
+
+    /// 端点开启备注传播时的运维备注
+    async fn tool_notes(&self, endpoint_id: Uuid) -> anyhow::Result<Option<OperationNotes>> {
+        let Some(pool) = DB_POOL.get() else {
+            return Ok(None);
+        };
+        OperationNoteService::new(pool.clone(), self.spec_cache.clone())
+            .notes_for_tools(endpoint_id)
+            .await
+    }
+
     fn get_endpoint_id(&self, context: &RequestContext<RoleServer>) -> Option<Uuid> {
         if let Some(http_request_part) = context.extensions.get::<axum::http::request::Parts>() {
             // let initialize_headers = &http_request_part.headers;
@@ -59,6 +374,13 @@ impl Adapter {
         None
     }
 
+    /// 按端点累计收到的 MCP 方法，即 mcp_method_total{endpoint,method}
+    fn count_method(&self, context: &RequestContext<RoleServer>, method: &str) {
+        if let Some(endpoint_id) = self.get_endpoint_id(context) {
+            mcp_method_counters().increment(endpoint_id, method);
+        }
+    }
+
     async fn inner_call_tool(
         &self,
         CallToolRequestParam { name, arguments }: CallToolRequestParam,
@@ -70,168 +392,630 @@ impl Adapter {
             Err(McpError::parse_error("not found endpoint", None))
         }?;
 
-        let arguments = arguments.map(|v| Value::Object(v)).unwrap_or(Value::Null);
+        let mut arguments = arguments.map(|v| Value::Object(v)).unwrap_or(Value::Null);
+        // X-Canary 请求头等同于 _canary 参数，参数中已指定时以参数为准
+        if let Some(forced) = context
+            .extensions
+            .get::<axum::http::request::Parts>()
+            .and_then(|p| p.headers.get(CANARY_HEADER))
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.trim().parse::<bool>().ok())
+        {
+            if arguments.is_null() {
+                arguments = json!({});
+            }
+            if let Value::Object(map) = &mut arguments {
+                map.entry(CANARY_ARGUMENT).or_insert(json!(forced));
+            }
+        }
+        // X-MCP-Locale 请求头覆盖会话语言，参数中已指定 _locale 时以参数为准
+        if let Some(locale) = context
+            .extensions
+            .get::<axum::http::request::Parts>()
+            .and_then(|p| p.headers.get(LOCALE_HEADER))
+            .and_then(|v| v.to_str().ok())
+            .and_then(normalize_locale)
+        {
+            if arguments.is_null() {
+                arguments = json!({});
+            }
+            if let Value::Object(map) = &mut arguments {
+                map.entry(LOCALE_ARGUMENT).or_insert(json!(locale));
+            }
+        }
+        // 客户端声明的截止时间（参数或 _meta.timeout）与服务端超时取较小者
+        let deadline = CallDeadline::resolve(
+            take_client_deadline(&mut arguments, context.meta.get(DEADLINE_META_KEY)),
+            deadline_config(),
+        );
         tracing::info!("call tool arguments: {}", arguments);
-        match self
-            .execute_tool_call_from_id(endpoint_id, name.as_ref(), &arguments)
+        let policy = self
+            .effective_policy(
+                endpoint_id,
+                context.extensions.get::<axum::http::request::Parts>(),
+            )
             .await
+            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        // 开启键值存储的端点上 kv_* 工具由网关直接处理
+        let parts = context.extensions.get::<axum::http::request::Parts>();
+        let execute = async {
+            match self
+                .kv_call_namespace(endpoint_id, name.as_ref(), parts)
+                .await
+            {
+                Some(Ok(namespace)) => {
+                    self.kv_store()
+                        .call_tool(endpoint_id, &namespace, name.as_ref(), &arguments)
+                        .await
+                }
+                Some(Err(e)) => Err(e),
+                None => {
+                    self.execute_tool_call_from_id(endpoint_id, name.as_ref(), &arguments, &policy)
+                        .await
+                }
+            }
+        };
+        // 到达截止时间与客户端取消一样丢弃上游请求，登记随调用结束移除
+        let started = std::time::Instant::now();
+        let execute = async {
+            let Some(deadline) = deadline else {
+                return execute.await;
+            };
+            let work = with_call_deadline(Some(started + deadline.budget), execute);
+            match tokio::time::timeout(deadline.budget, work).await {
+                Ok(result) => result,
+                Err(_) => Err(deadline.exceeded().into()),
+            }
+        };
+        // 按 (会话, 请求 id) 登记，notifications/cancelled 时丢弃上游请求
+        let execution = cancellation_registry()
+            .run(
+                &self.session_key(),
+                &request_id_key(&context.id),
+                context.ct.clone(),
+                with_queue_wait(execute),
+            )
+            .await;
+        let Ok((execution, queue_wait)) = execution else {
+            tracing::info!(
+                "Tool call {} cancelled by client after {}ms",
+                name,
+                started.elapsed().as_millis()
+            );
+            self.record_cancelled_call(endpoint_id, name.to_string(), arguments, started.elapsed());
+            // streamable HTTP 上该结果帧由 cancelled_results 中间件丢弃
+            return Err(McpError::internal_error(
+                RequestCancelled.to_string(),
+                Some(json!({"cancelled": true})),
+            ));
+        };
+        let succeeded = matches!(&execution, Ok(result) if result["success"] != false);
+        tool_stats().record(endpoint_id, name.as_ref(), started.elapsed(), succeeded);
+        if let Some(search_id) = context
+            .meta
+            .get(SEARCH_ID_META_KEY)
+            .and_then(|v| v.as_str())
         {
-            Ok(result) => Ok(CallToolResult::structured(result)),
-            Err(error) => Err(McpError::internal_error(
-                "call http error",
-                Some(Value::String(error.to_string())),
+            self.record_search_feedback(search_id.to_string(), endpoint_id, name.to_string());
+        }
+        match execution {
+            Ok(result) => Ok(with_queue_wait_meta(
+                CallToolResult::structured(result),
+                queue_wait,
             )),
+            Err(error) => {
+                if let Some(result) = tool_error_result(&error) {
+                    return Ok(with_queue_wait_meta(
+                        CallToolResult::structured_error(result.clone()),
+                        queue_wait,
+                    ));
+                }
+                if let Some(exceeded) = error.downcast_ref::<DeadlineExceeded>() {
+                    tracing::info!("Tool call {} aborted: {}", name, exceeded);
+                    self.record_cancelled_call(
+                        endpoint_id,
+                        name.to_string(),
+                        arguments,
+                        started.elapsed(),
+                    );
+                }
+                Err(tool_call_error(&error))
+            }
         }
     }
 
+    /// 按计划注入故障后执行调用；已被故障终止的会话直接拒绝
+    async fn call_tool_with_faults(
+        &self,
+        request: CallToolRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        let injector = fault_injector();
+        let Some(endpoint_id) = injector
+            .is_enabled()
+            .then(|| self.get_endpoint_id(&context))
+            .flatten()
+        else {
+            return self.inner_call_tool(request, context).await;
+        };
+        let session = context
+            .extensions
+            .get::<axum::http::request::Parts>()
+            .and_then(request_session_id)
+            .unwrap_or_else(|| self.session_key());
+        if let Some(fault_id) = injector.terminated_by(&session) {
+            return Err(McpError::from(&SessionTerminated { fault_id }));
+        }
+        let plan = injector.plan(endpoint_id, request.name.as_ref(), &session);
+        let ct = context.ct.clone();
+        inject_faults(plan, ct, self.inner_call_tool(request, context)).await
+    }
+
+    /// 取消登记使用的会话键
+    fn session_key(&self) -> String {
+        self.session_id
+            .read()
+            .ok()
+            .and_then(|s| s.clone())
+            .unwrap_or_else(|| self.instance_id.clone())
+    }
+
+    /// 在调用日志中记录取消；失败只记日志
+    fn record_cancelled_call(
+        &self,
+        endpoint_id: Uuid,
+        tool: String,
+        arguments: Value,
+        elapsed: std::time::Duration,
+    ) {
+        let adapter = self.clone();
+        tokio::spawn(async move {
+            let recorded = async {
+                let endpoint = adapter.get_endpoint(endpoint_id).await?;
+                // 组合工具等没有对应操作时以工具名代替路径
+                let (method, path) = match adapter.spec_cache.get_or_parse(&endpoint).await {
+                    Ok(cached) => cached
+                        .operation(&tool)
+                        .map(|(method, path, _)| (method.to_string(), path.to_string()))
+                        .unwrap_or_else(|_| ("MCP".to_string(), tool.clone())),
+                    Err(_) => ("MCP".to_string(), tool.clone()),
+                };
+                log_cancelled_call(
+                    adapter.pool(),
+                    endpoint_id,
+                    &method,
+                    &path,
+                    elapsed,
+                    &arguments,
+                )
+                .await
+            }
+            .await;
+            if let Err(e) = recorded {
+                tracing::warn!("Failed to log cancelled call {}: {}", tool, e);
+            }
+        });
+    }
+
+    /// 关联此前的接口检索，记录实际调用的工具；失败只记日志，不影响调用结果
+    fn record_search_feedback(&self, search_id: String, endpoint_id: Uuid, tool: String) {
+        let adapter = self.clone();
+        tokio::spawn(async move {
+            let recorded = async {
+                let endpoint = adapter.get_endpoint(endpoint_id).await?;
+                // 组合工具等没有对应操作时只记录工具名
+                let operation = match adapter.spec_cache.get_or_parse(&endpoint).await {
+                    Ok(cached) => cached
+                        .operation(&tool)
+                        .ok()
+                        .map(|(method, path, _)| (method.to_string(), path.to_string())),
+                    Err(_) => None,
+                };
+                SearchFeedbackService::new(adapter.pool().clone())
+                    .record_call(
+                        &search_id,
+                        endpoint_id,
+                        &endpoint.name,
+                        &tool,
+                        operation.as_ref().map(|(m, p)| (m.as_str(), p.as_str())),
+                    )
+                    .await
+            }
+            .await;
+            if let Err(e) = recorded {
+                tracing::warn!("Failed to record search feedback {}: {}", search_id, e);
+            }
+        });
+    }
+
     pub async fn execute_tool_call_from_id(
         &self,
         endpoint_id: Uuid,
         tool_name: &str,
         arguments: &Value,
+        policy: &EffectivePolicy,
     ) -> anyhow::Result<Value> {
         match self.get_endpoint(endpoint_id).await {
             Ok(endpoint) => {
-                self.execute_tool_call(&endpoint, tool_name, arguments)
-                    .await
+                // 组合工具名不与普通工具重名，命中解析缓存的工具无需查询组合工具
+                let cached = self.spec_cache.get_or_parse(&endpoint).await?;
+                let composite = if cached.operation(tool_name).is_ok()
+                    || (cached.recording && tool_name == HTTP_REQUEST_TOOL)
+                {
+                    None
+                } else {
+                    list_composite_tools(self.pool(), endpoint_id)
+                        .await?
+                        .into_iter()
+                        .find(|composite| composite.name == tool_name)
+                };
+                match composite {
+                    Some(composite) => {
+                        self.execute_composite_tool(&endpoint, &composite, arguments, policy)
+                            .await
+                    }
+                    None => {
+                        let result = self
+                            .execute_tool_call(&endpoint, tool_name, arguments, policy)
+                            .await?;
+                        apply_status_mapping(&endpoint.status_mapping, result)
+                    }
+                }
             }
             Err(error) => Err(Error::from(error).context("Failed to execute tool call")),
         }
     }
 
-    pub async fn get_endpoint(&self, endpoint_id: Uuid) -> anyhow::Result<Endpoint> {
-        let endpoint = sqlx::query_as::<_, Endpoint>(
-            "SELECT id, name, description, swagger_content, status, created_at, updated_at, connection_count FROM endpoints WHERE id = ?"
-        )
-            .bind(endpoint_id.to_string())
-            .fetch_one(DB_POOL.get().expect("DB_POOL not initialized"))
-            .await?;
-
-        Ok(endpoint)
+    fn pool(&self) -> &'static DbPool {
+        DB_POOL.get().expect("DB_POOL not initialized")
     }
 
-    pub async fn execute_tool_call(
+    /// 依次执行组合工具的各个步骤，每步走普通工具调用流程并记录日志；
+    /// 任一步骤失败即中止并返回步骤名
+    pub async fn execute_composite_tool(
         &self,
         endpoint: &Endpoint,
-        tool_name: &str,
+        composite: &CompositeTool,
         arguments: &Value,
+        policy: &EffectivePolicy,
     ) -> anyhow::Result<Value> {
-        tracing::info!(
-            "Executing tool call: {} for endpoint: {}",
-            tool_name,
-            endpoint.name
-        );
-        tracing::debug!("Arguments: {}", arguments);
+        let request_id = Uuid::new_v4();
+        let cached = self.spec_cache.get_or_parse(endpoint).await?;
+        let mut context = json!({"input": arguments, "steps": {}});
+        let mut last_output = Value::Null;
 
-        // Parse swagger content to get API specifications
-        let swagger_spec: crate::models::SwaggerSpec =
-            serde_json::from_str(&endpoint.swagger_content)?;
+        for step in &composite.steps {
+            let (method, path, _) = cached.operation(&step.tool)?;
+            let step_arguments = render_template(&step.arguments, &context)
+                .map_err(|e| anyhow!("Composite step '{}' failed: {}", step.id, e))?;
 
-        // Parse tool name to extract method, path and operation info
-        let (method, path, operation) = parse_tool_name(&swagger_spec, tool_name)?;
+            let start = std::time::Instant::now();
+            let result = self
+                .execute_tool_call(endpoint, &step.tool, &step_arguments, policy)
+                .await;
+            let elapsed_ms = start.elapsed().as_millis() as u64;
 
-        // Build the base URL from swagger spec
-        let base_url = build_base_url(&swagger_spec)?;
+            let (status, error) = match &result {
+                Ok(output) if step_failed(output) => (
+                    output["status"].as_u64().map(|s| s as u16),
+                    Some(format!("upstream returned {}", output["status"])),
+                ),
+                Ok(output) => (output["status"].as_u64().map(|s| s as u16), None),
+                Err(e) => (None, Some(e.to_string())),
+            };
+            if let Err(e) = log_composite_step(
+                self.pool(),
+                endpoint.id,
+                request_id,
+                method,
+                path,
+                status,
+                elapsed_ms,
+                &step_arguments,
+                result.as_ref().ok(),
+                error.as_deref(),
+            )
+            .await
+            {
+                tracing::warn!("Failed to log composite step {}: {}", step.id, e);
+            }
 
-        // Build the full URL with path parameters
-        let full_url = build_url(&base_url, &path, arguments)?;
+            if let Some(error) = error {
+                return Err(anyhow!(
+                    "Composite step '{}' ({}) failed: {}",
+                    step.id,
+                    step.tool,
+                    error
+                ));
+            }
+            last_output = step_output(&result?);
+            context["steps"][step.id.as_str()] = last_output.clone();
+        }
 
-        // Extract query parameters, headers, and body from arguments based on Swagger spec
-        let (query_params, headers, body) = extract_request_parts(arguments, &operation)?;
+        let response = match &composite.result {
+            Some(template) => render_template(template, &context)?,
+            None => last_output,
+        };
+        Ok(json!({
+            "status": 200,
+            "success": true,
+            "response": response,
+            "_meta": {
+                "request_id": request_id.to_string(),
+                "steps": composite.steps.len()
+            }
+        }))
+    }
 
-        tracing::info!("Making HTTP request to: {}", full_url);
-        tracing::debug!(
-            "Method: {}, Query params: {:?}, Headers: {:?}, Body: {:?}",
-            method,
-            query_params,
-            headers,
-            body
-        );
+    /// 优先读取端点缓存，未命中时查库；连接池繁忙时返回 PoolSaturated
+    pub async fn get_endpoint(&self, endpoint_id: Uuid) -> anyhow::Result<Arc<Endpoint>> {
+        if let Some(endpoint) = self.spec_cache.cached_endpoint(&endpoint_id) {
+            return Ok(endpoint);
+        }
+        let mut conn = acquire_connection(self.pool(), MCP_CALL_POOL).await?;
+        let endpoint =
+            sqlx::query_as::<_, Endpoint>(&format!("{} WHERE id = ?", endpoint_select()))
+                .bind(endpoint_id.to_string())
+                .fetch_one(&mut *conn)
+                .await?;
 
-        // Make the HTTP request
-        let mut request = match method.to_uppercase().as_str() {
-            "GET" => self.http_client.get(&full_url),
-            "POST" => self.http_client.post(&full_url),
-            "PUT" => self.http_client.put(&full_url),
-            "DELETE" => self.http_client.delete(&full_url),
-            "PATCH" => self.http_client.patch(&full_url),
-            _ => return Err(anyhow!("Unsupported HTTP method: {}", method)),
-        };
+        Ok(self.spec_cache.store_endpoint(endpoint))
+    }
+
+    async fn list_webhook_details(
+        &self,
+        context: &RequestContext<RoleServer>,
+    ) -> anyhow::Result<Vec<WebhookDetail>> {
+        let endpoint_id = self
+            .get_endpoint_id(context)
+            .ok_or_else(|| anyhow!("not found endpoint"))?;
+        let endpoint = self.get_endpoint(endpoint_id).await?;
+        let cached = self.spec_cache.get_or_parse(&endpoint).await?;
+        generate_webhook_details(&cached.spec)
+    }
+
+    /// 录制端点的 http_request 在此转发，其余工具调用交给 McpService 执行
+    pub async fn execute_tool_call(
+        &self,
+        endpoint: &Endpoint,
+        tool_name: &str,
+        arguments: &Value,
+        policy: &EffectivePolicy,
+    ) -> anyhow::Result<Value> {
+        let cached = self.spec_cache.get_or_parse(endpoint).await?;
+        if !(cached.recording && tool_name == HTTP_REQUEST_TOOL) {
+            return self
+                .mcp_service()
+                .execute(endpoint, tool_name, arguments, policy)
+                .await;
+        }
+
+        // 与其他工具调用一样经 McpService 按端点公平调度
+        let _permit = self.mcp_service().acquire(endpoint.id).await;
+        cached.validate_arguments(tool_name, arguments)?;
+        self.execute_recording_call(endpoint, &cached.spec, arguments, policy)
+            .await
+    }
+
+    /// 与 HTTP 接口共用同一调度器
+    fn mcp_service(&self) -> McpService {
+        McpService::new(
+            self.pool().clone(),
+            self.scheduler.clone(),
+            self.spec_cache.clone(),
+        )
+        .with_pool_name(MCP_CALL_POOL)
+        .with_session(self.session_id.read().ok().and_then(|s| s.clone()))
+        .with_locale(self.session_locale.read().ok().and_then(|l| l.clone()))
+    }
+}
+
+impl Adapter {
+    /// 录制模式端点的通用转发：按参数中的 method/path 请求上游，采样记录调用与响应
+    async fn execute_recording_call(
+        &self,
+        endpoint: &Endpoint,
+        spec: &SwaggerSpec,
+        arguments: &Value,
+        policy: &EffectivePolicy,
+    ) -> anyhow::Result<Value> {
+        let method = arguments["method"]
+            .as_str()
+            .ok_or_else(|| anyhow!("http_request requires a method"))?
+            .to_ascii_uppercase();
+        let path = arguments["path"]
+            .as_str()
+            .ok_or_else(|| anyhow!("http_request requires a path"))?;
+        policy.check(&method)?;
+
+        let base_url = build_base_url(spec, &endpoint.server_variables)?;
+        let full_url = format!("{}{}", base_url.trim_end_matches('/'), path);
+        upstream_guard()
+            .check_for_endpoint(&full_url, endpoint.id)
+            .await?;
+        let query = arguments["query"].as_object().cloned().unwrap_or_default();
+        let query_params: Vec<(String, String)> = query
+            .iter()
+            .map(|(key, value)| match value {
+                Value::String(s) => (key.clone(), s.clone()),
+                other => (key.clone(), other.to_string()),
+            })
+            .collect();
+        let body = arguments.get("body").filter(|b| !b.is_null()).cloned();
 
-        // Add query parameters
+        let http_method = reqwest::Method::from_bytes(method.as_bytes())
+            .map_err(|_| anyhow!("Unsupported HTTP method: {}", method))?;
+        let client = endpoint_http_client(endpoint.id).unwrap_or_else(|| self.http_client.clone());
+        let mut request = client.request(http_method, &full_url);
         if !query_params.is_empty() {
             request = request.query(&query_params);
         }
-
-        // Add headers
-        for (key, value) in headers {
-            request = request.header(key, value);
+        if let Some(headers) = arguments["headers"].as_object() {
+            for (key, value) in headers {
+                if let Some(value) = value.as_str() {
+                    request = request.header(key, value);
+                }
+            }
         }
-
-        // Add body for POST/PUT/PATCH requests
-        if let Some(body_data) = body {
-            tracing::debug!(
-                "Request body: {}",
-                serde_json::to_string_pretty(&body_data)?
-            );
-            request = request.json(&body_data);
+        if let Some(body) = &body {
+            request = request.json(body);
         }
 
-        // Execute the request
-        let response = request.send().await?;
+        tracing::info!("Recording endpoint request: {} {}", method, full_url);
+        let started = std::time::Instant::now();
+        let response = propagate_deadline(request)
+            .send()
+            .await
+            .map_err(|e| classify_call_error(e, is_idempotent_method(&method)))?;
         let status = response.status();
         let response_text = response.text().await?;
+        let mut conn = acquire_connection(self.pool(), MCP_CALL_POOL).await?;
+        update_metrics(
+            &mut *conn,
+            endpoint.id,
+            status.is_success(),
+            started.elapsed(),
+        )
+        .await?;
 
-        tracing::info!("Received response with status: {}", status);
-        tracing::debug!("Response body: {}", response_text);
-
-        // Update metrics
-        let pool = DB_POOL.get().expect("DB_POOL not initialized");
-        update_metrics(pool, endpoint.id, status.is_success()).await?;
-
-        // Format response
-        let response_value = match serde_json::from_str::<Value>(&response_text) {
-            Ok(parsed) => parsed,
-            Err(e) => {
-                tracing::warn!("Failed to parse response as JSON: {}", e);
-                Value::String(response_text.clone())
+        let parsed = serde_json::from_str::<Value>(&response_text).ok();
+        let config = recording_config();
+        if should_record(config.sample_rate) {
+            let call = RecordedCall {
+                method: method.clone(),
+                path: path.split('?').next().unwrap_or(path).to_string(),
+                query,
+                request_body: cap_body(body, config.max_body_bytes),
+                status: status.as_u16(),
+                response_body: cap_body(parsed.clone(), config.max_body_bytes),
+            };
+            if let Err(e) = record_call(self.pool(), endpoint.id, &call).await {
+                tracing::warn!("Failed to record call for {}: {}", endpoint.name, e);
             }
-        };
+        }
 
-        let result = json!({
+        Ok(json!({
             "status": status.as_u16(),
             "success": status.is_success(),
-            "response": response_value
-        });
-
-        tracing::info!(
-            "Tool call result: {}",
-            serde_json::to_string_pretty(&result)?
-        );
-        Ok(result)
+            "response": parsed.unwrap_or(Value::String(response_text)),
+        }))
     }
 }
 
+/// 排队时间写入 CallToolResult._meta，不混入上游结果的 structuredContent
+fn with_queue_wait_meta(
+    mut result: CallToolResult,
+    queue_wait: std::time::Duration,
+) -> CallToolResult {
+    let meta = result.meta.get_or_insert_with(Meta::new);
+    meta.insert(
+        QUEUE_WAIT_META_KEY.to_string(),
+        json!(queue_wait.as_millis() as u64),
+    );
+    result
+}
+
+/// 请求所属的会话：streamable HTTP 取会话请求头，SSE 取 sessionId 查询参数
+fn request_session_id(parts: &axum::http::request::Parts) -> Option<String> {
+    parts
+        .headers
+        .get(HEADER_SESSION_ID)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string)
+        .or_else(|| {
+            parts
+                .uri
+                .query()
+                .and_then(|q| q.split('&').find_map(|kv| kv.strip_prefix("sessionId=")))
+                .map(str::to_string)
+        })
+}
+
 impl ServerHandler for Adapter {
     async fn initialize(
         &self,
-        _request: InitializeRequestParam,
+        request: InitializeRequestParam,
         context: RequestContext<RoleServer>,
     ) -> Result<InitializeResult, McpError> {
+        self.count_method(&context, "initialize");
+        let mut session_methods = request
+            .capabilities
+            .experimental
+            .as_ref()
+            .and_then(|experimental| experimental.get(SESSION_POLICY_CAPABILITY))
+            .and_then(session_methods_from_capability);
+        // 会话语言：X-MCP-Locale 请求头优先于 experimental.locale 能力声明
+        let mut locale = request
+            .capabilities
+            .experimental
+            .as_ref()
+            .and_then(|experimental| experimental.get(LOCALE_CAPABILITY))
+            .and_then(locale_from_capability);
         if let Some(http_request_part) = context.extensions.get::<axum::http::request::Parts>() {
             let initialize_headers = &http_request_part.headers;
             let initialize_uri = &http_request_part.uri;
             tracing::info!(?initialize_headers, %initialize_uri, "initialize from http server");
+            let header_methods = initialize_headers
+                .get(&execution_policy_config().session_header)
+                .and_then(|v| v.to_str().ok())
+                .map(|v| parse_methods(v.split(',')));
+            session_methods = narrow(session_methods, header_methods);
+            if let Some(header_locale) = initialize_headers
+                .get(LOCALE_HEADER)
+                .and_then(|v| v.to_str().ok())
+                .and_then(normalize_locale)
+            {
+                locale = Some(header_locale);
+            }
+        }
+        if let Ok(mut policy) = self.session_policy.write() {
+            *policy = session_methods;
         }
-        Ok(self.get_info())
+        if let Ok(mut session_locale) = self.session_locale.write() {
+            *session_locale = locale;
+        }
+        let mut info = self.get_info();
+        // 工具列表版本，客户端可据此判断缓存的 tools/list 是否仍然有效
+        if let Some(endpoint_id) = self.get_endpoint_id(&context) {
+            let parts = context.extensions.get::<axum::http::request::Parts>();
+            let version = tools_version(&self.endpoint_tools(endpoint_id, parts).await);
+            let mut capability = serde_json::Map::new();
+            capability.insert("version".to_string(), Value::String(version));
+            info.capabilities
+                .experimental
+                .get_or_insert_with(Default::default)
+                .insert(TOOLS_VERSION_CAPABILITY.to_string(), capability);
+        }
+        Ok(info)
     }
     async fn list_resources(
         &self,
         _request: Option<PaginatedRequestParam>,
-        _: RequestContext<RoleServer>,
+        context: RequestContext<RoleServer>,
     ) -> Result<ListResourcesResult, McpError> {
+        self.count_method(&context, "resources/list");
+        // webhook 定义作为只读资源暴露
+        let resources = match self.list_webhook_details(&context).await {
+            Ok(webhooks) => webhooks
+                .into_iter()
+                .map(|webhook| {
+                    let mut raw = RawResource::new(
+                        webhook.uri.clone(),
+                        format!("{} {}", webhook.method, webhook.name),
+                    );
+                    raw.description = webhook.summary.or(webhook.description);
+                    raw.mime_type = Some("application/json".to_string());
+                    raw.no_annotation()
+                })
+                .collect(),
+            Err(e) => {
+                tracing::warn!("Failed to list webhook resources: {}", e);
+                vec![]
+            }
+        };
+
         Ok(ListResourcesResult {
-            resources: vec![],
+            resources,
             next_cursor: None,
         })
     }
@@ -239,8 +1023,31 @@ impl ServerHandler for Adapter {
     async fn read_resource(
         &self,
         ReadResourceRequestParam { uri }: ReadResourceRequestParam,
-        _: RequestContext<RoleServer>,
+        context: RequestContext<RoleServer>,
     ) -> Result<ReadResourceResult, McpError> {
+        self.count_method(&context, "resources/read");
+        if uri.starts_with("webhook://") {
+            let webhooks = self
+                .list_webhook_details(&context)
+                .await
+                .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+            return match webhooks.into_iter().find(|w| w.uri == uri) {
+                Some(webhook) => {
+                    let text = serde_json::to_string_pretty(&webhook)
+                        .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+                    Ok(ReadResourceResult {
+                        contents: vec![ResourceContents::text(text, uri)],
+                    })
+                }
+                None => Err(McpError::resource_not_found(
+                    "resource_not_found",
+                    Some(json!({
+                        "uri": uri
+                    })),
+                )),
+            };
+        }
+
         match uri.as_str() {
             "str:////Users/to/some/path/" => {
                 let cwd = "/Users/to/some/path/";
@@ -263,21 +1070,41 @@ impl ServerHandler for Adapter {
         }
     }
 
+    async fn on_cancelled(
+        &self,
+        notification: CancelledNotificationParam,
+        _context: NotificationContext<RoleServer>,
+    ) {
+        // 未知或已结束的请求为空操作
+        if cancellation_registry().cancel(
+            &self.session_key(),
+            &request_id_key(&notification.request_id),
+        ) {
+            tracing::info!(
+                "Cancelled request {:?}: {}",
+                notification.request_id,
+                notification.reason.as_deref().unwrap_or("no reason")
+            );
+        }
+    }
+
     fn call_tool(
         &self,
         request: CallToolRequestParam,
         context: RequestContext<RoleServer>,
     ) -> impl Future<Output = Result<CallToolResult, McpError>> + Send + '_ {
-        self.inner_call_tool(request, context)
+        self.count_method(&context, "tools/call");
+        self.call_tool_with_faults(request, context)
     }
 
     fn list_tools(
         &self,
-        _request: Option<PaginatedRequestParam>,
+        request: Option<PaginatedRequestParam>,
         context: RequestContext<RoleServer>,
     ) -> impl Future<Output = Result<ListToolsResult, McpError>> + Send + '_ {
         tracing::info!("context: {:?}", context);
-        self.inner_list_tools(context)
+        self.count_method(&context, "tools/list");
+        self.inner_list_tools(request, context)
     }
 
     fn get_info(&self) -> ServerInfo {
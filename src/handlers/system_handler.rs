@@ -1,7 +1,18 @@
+use crate::middleware::{is_read_only, set_read_only};
+use crate::models::{ActiveFault, CleanupOutboxItem, FaultList, FaultSpec};
+use crate::services::pending_cleanup_items;
 use crate::state::AppState;
-use crate::utils::get_china_time;
-use axum::{extract::State, http::StatusCode, response::Json};
+use crate::utils::{
+    circuit_breakers, dns_resolver, fault_injector, get_china_time, require_admin,
+    CircuitBreakerStatus, FaultInjectionDisabled, HostDnsStats,
+};
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::Json,
+};
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
 #[derive(Serialize, Deserialize)]
 pub struct SystemStatus {
@@ -20,3 +31,80 @@ pub async fn get_system_status(
 
     Ok(Json(status))
 }
+
+/// 前端使用的系统信息
+#[derive(Serialize, Deserialize)]
+pub struct SystemInfo {
+    pub version: String,
+    pub read_only: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ReadOnlyToggle {
+    pub enabled: bool,
+}
+
+pub async fn get_system_info() -> Json<SystemInfo> {
+    Json(SystemInfo {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        read_only: is_read_only(),
+    })
+}
+
+/// 运行时切换只读模式，需要管理员凭据
+pub async fn put_read_only(
+    headers: HeaderMap,
+    Json(toggle): Json<ReadOnlyToggle>,
+) -> Result<Json<ReadOnlyToggle>, (StatusCode, String)> {
+    require_admin(&headers).map_err(|e| (e.status_code(), e.to_string()))?;
+    set_read_only(toggle.enabled);
+    tracing::info!("Read-only mode set to {}", toggle.enabled);
+    Ok(Json(ReadOnlyToggle {
+        enabled: is_read_only(),
+    }))
+}
+
+/// 查看待执行的端点清理任务
+pub async fn get_cleanup_outbox(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<CleanupOutboxItem>>, (StatusCode, String)> {
+    pending_cleanup_items(&state.pool)
+        .await
+        .map(Json)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+}
+
+/// 当前处于限流暂停期的上游主机
+pub async fn get_circuit_breakers() -> Json<Vec<CircuitBreakerStatus>> {
+    Json(circuit_breakers().statuses())
+}
+
+/// 上游主机的解析统计：查询次数、缓存命中、失败次数与最近的地址
+pub async fn get_dns_stats() -> Json<Vec<HostDnsStats>> {
+    Json(dns_resolver().stats())
+}
+
+/// 生效中的故障；未以 --enable-fault-injection 启动时 enabled 为 false
+pub async fn list_faults() -> Json<FaultList> {
+    Json(fault_injector().list())
+}
+
+/// 为端点或工具开启一个故障，ttl_secs 后自动失效
+pub async fn create_fault(
+    Json(spec): Json<FaultSpec>,
+) -> Result<(StatusCode, Json<ActiveFault>), (StatusCode, String)> {
+    match fault_injector().add(spec) {
+        Ok(fault) => Ok((StatusCode::CREATED, Json(fault))),
+        Err(e) if e.downcast_ref::<FaultInjectionDisabled>().is_some() => {
+            Err((StatusCode::FORBIDDEN, e.to_string()))
+        }
+        Err(e) => Err((StatusCode::BAD_REQUEST, e.to_string())),
+    }
+}
+
+pub async fn delete_fault(Path(id): Path<Uuid>) -> Result<Json<ActiveFault>, (StatusCode, String)> {
+    fault_injector()
+        .remove(id)
+        .map(Json)
+        .ok_or_else(|| (StatusCode::NOT_FOUND, format!("Fault {} not found", id)))
+}
@@ -1,18 +1,28 @@
 use axum::extract::{Path, Query};
+use axum::http::HeaderMap;
+use axum::response::{IntoResponse, Response};
 use axum::{extract::State, http::StatusCode, Json};
 use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
 use std::sync::Arc;
 use uuid::Uuid;
 
 use crate::models::table_rag::{
-    ColumnSchema, CreateDatasetRequest, DatasetDetailResponse, DatasetResponse,
-    PaginatedDatasetsResponse, UpdateDatasetRequest,
+    ColumnSchema, CreateDatasetRequest, CreateDatasetTokenRequest, CreatedDatasetToken,
+    DatasetAccessError, DatasetCaller, DatasetDetailResponse, DatasetFileRemoval, DatasetResponse,
+    DatasetStats, DatasetToken, IngestInProgress, PaginatedDatasetsResponse, SchemaValidationError,
+    SearchBoost, SearchFilter, SearchFilterError, UpdateDatasetRequest, DATASET_LIST_FIELDS,
 };
-use crate::services::TableRagService;
+use crate::services::{
+    execution_policy_config, is_es_timeout, validate_dataset_schema, DatasetAccessService,
+    TableRagService,
+};
+use crate::utils::{require_admin, AdminAccessError, FieldSelection};
 
 #[derive(Clone)]
 pub struct TableRagState {
     pub service: Arc<TableRagService>,
+    pub access: Arc<DatasetAccessService>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -27,6 +37,12 @@ pub struct TableSearchRequest {
     pub query: String,
     pub max_results: Option<u32>,
     pub similarity_threshold: Option<f32>,
+    /// 结构化过滤，多个条件同时满足
+    #[serde(default)]
+    pub filters: Vec<SearchFilter>,
+    /// 业务加权，与向量相似度融合排序
+    #[serde(default)]
+    pub boosts: Vec<SearchBoost>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -43,38 +59,91 @@ pub struct IngestResult {
     pub task_id: Option<String>,
 }
 
+fn schema_error_response(e: &SchemaValidationError) -> Response {
+    (
+        StatusCode::BAD_REQUEST,
+        Json(json!({ "message": e.to_string(), "errors": e.errors })),
+    )
+        .into_response()
+}
+
+/// ES 请求超时返回 504，缺少凭据或令牌无效返回 401，无权检索返回 403，其余错误返回 500
+fn search_error(e: anyhow::Error) -> (StatusCode, String) {
+    if let Some(access_error) = e.downcast_ref::<DatasetAccessError>() {
+        let status = match access_error {
+            DatasetAccessError::MissingCredential | DatasetAccessError::InvalidToken => {
+                StatusCode::UNAUTHORIZED
+            }
+            _ => StatusCode::FORBIDDEN,
+        };
+        return (status, access_error.to_string());
+    }
+    if is_es_timeout(&e) {
+        (
+            StatusCode::GATEWAY_TIMEOUT,
+            format!("Elasticsearch request timed out: {}", e),
+        )
+    } else {
+        (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+    }
+}
+
+/// 先校验原始 schema，列类型非法等问题也以字段错误返回，而不是反序列化失败
 pub async fn create_dataset_handler(
     State(state): State<TableRagState>,
-    Json(req): Json<CreateDatasetRequest>,
-) -> Result<Json<DatasetResponse>, (StatusCode, String)> {
+    Json(body): Json<Value>,
+) -> Result<Json<DatasetResponse>, Response> {
+    validate_dataset_schema(body.get("schema").unwrap_or(&Value::Null))
+        .map_err(|e| schema_error_response(&e))?;
+    let req: CreateDatasetRequest = serde_json::from_value(body)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()).into_response())?;
     state
         .service
         .create_dataset(req)
         .await
         .map(Json)
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+        .map_err(|e| match e.downcast_ref::<SchemaValidationError>() {
+            Some(schema_error) => schema_error_response(schema_error),
+            None => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        })
 }
 
 #[derive(Debug, Deserialize)]
 pub struct ListDatasetsQuery {
     pub page: Option<u32>,
     pub page_size: Option<u32>,
+    /// 逗号分隔的返回字段，可选值见 DATASET_LIST_FIELDS
+    pub fields: Option<String>,
 }
 
 pub async fn list_datasets_handler(
     State(state): State<TableRagState>,
     Query(query): Query<ListDatasetsQuery>,
-) -> Result<Json<PaginatedDatasetsResponse>, (StatusCode, String)> {
+) -> Result<Json<Value>, (StatusCode, String)> {
+    let fields = FieldSelection::parse(query.fields.as_deref(), DATASET_LIST_FIELDS)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
     let page = query.page.unwrap_or(1);
     let page_size = query.page_size.unwrap_or(20);
-    state
+    let response = state
         .service
-        .list_datasets_paged(page, page_size)
+        .list_datasets_paged(page, page_size, &fields)
         .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    paginated_datasets_body(&response, &fields)
         .map(Json)
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
 }
 
+/// 分页结果中的每个数据集只保留选中字段
+fn paginated_datasets_body(
+    response: &PaginatedDatasetsResponse,
+    fields: &FieldSelection,
+) -> serde_json::Result<Value> {
+    let mut body = serde_json::to_value(response)?;
+    body["datasets"] = fields.apply_each(&response.datasets)?;
+    Ok(body)
+}
+
 pub async fn get_dataset_handler(
     State(state): State<TableRagState>,
     Path(id): Path<String>,
@@ -93,23 +162,97 @@ pub async fn get_dataset_handler(
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
 }
 
+/// 数据集存储统计，便于评估 ES 占用
+pub async fn dataset_stats_handler(
+    State(state): State<TableRagState>,
+    Path(id): Path<String>,
+) -> Result<Json<DatasetStats>, (StatusCode, String)> {
+    let dataset_id = Uuid::parse_str(&id).map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            format!("Invalid dataset_id: {}", e),
+        )
+    })?;
+    state
+        .service
+        .dataset_stats(dataset_id)
+        .await
+        .map(Json)
+        .map_err(|e| match e.downcast_ref::<sqlx::Error>() {
+            Some(sqlx::Error::RowNotFound) => (
+                StatusCode::NOT_FOUND,
+                format!("Dataset {} not found", dataset_id),
+            ),
+            _ => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+        })
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct RemoveDatasetFileQuery {
+    /// 同时删除文件记录与存储的文件（未被其他数据集使用时）
+    #[serde(default)]
+    pub delete_file: bool,
+}
+
+pub async fn remove_dataset_file_handler(
+    State(state): State<TableRagState>,
+    Path((id, file_id)): Path<(String, String)>,
+    Query(query): Query<RemoveDatasetFileQuery>,
+) -> Result<Json<DatasetFileRemoval>, (StatusCode, String)> {
+    let dataset_id = Uuid::parse_str(&id).map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            format!("Invalid dataset_id: {}", e),
+        )
+    })?;
+    let file_id = Uuid::parse_str(&file_id)
+        .map_err(|e| (StatusCode::BAD_REQUEST, format!("Invalid file_id: {}", e)))?;
+    match state
+        .service
+        .remove_dataset_file(dataset_id, file_id, query.delete_file)
+        .await
+    {
+        Ok(Some(removal)) => Ok(Json(removal)),
+        Ok(None) => Err((
+            StatusCode::NOT_FOUND,
+            format!("File {} not found in dataset {}", file_id, dataset_id),
+        )),
+        Err(e) => {
+            if let Some(in_progress) = e.downcast_ref::<IngestInProgress>() {
+                return Err((StatusCode::CONFLICT, in_progress.to_string()));
+            }
+            Err(match e.downcast_ref::<sqlx::Error>() {
+                Some(sqlx::Error::RowNotFound) => (
+                    StatusCode::NOT_FOUND,
+                    format!("Dataset {} not found", dataset_id),
+                ),
+                _ => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+            })
+        }
+    }
+}
+
 pub async fn update_dataset_handler(
     State(state): State<TableRagState>,
     Path(id): Path<String>,
     Json(req): Json<UpdateDatasetRequest>,
-) -> Result<Json<DatasetResponse>, (StatusCode, String)> {
+) -> Result<Json<DatasetResponse>, Response> {
     let dataset_id = Uuid::parse_str(&id).map_err(|e| {
         (
             StatusCode::BAD_REQUEST,
             format!("Invalid dataset_id: {}", e),
         )
+            .into_response()
     })?;
     state
         .service
         .update_dataset(dataset_id, req)
         .await
         .map(Json)
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+        .map_err(|e| match e.downcast_ref::<SchemaValidationError>() {
+            Some(schema_error) => schema_error_response(schema_error),
+            None => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        })
 }
 
 pub async fn ingest_dataset_file_handler(
@@ -124,46 +267,84 @@ pub async fn ingest_dataset_file_handler(
     })?;
     let file_id = Uuid::parse_str(&params.file_id)
         .map_err(|e| (StatusCode::BAD_REQUEST, format!("Invalid file_id: {}", e)))?;
-    // 两段式：先创建任务，再后台执行
+    // 两段式：先创建任务，再由后台 job 执行
     let task_id = state
         .service
-        .create_ingest_task(dataset_id, file_id)
+        .submit_ingest_task(dataset_id, file_id)
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
-    let service = state.service.clone();
-    tokio::spawn(async move {
-        if let Err(err) = service.run_ingest_task(task_id).await {
-            tracing::error!("table_rag ingest task failed: {}", err);
-        }
-    });
     Ok(Json(IngestResult {
         ingested_rows: 0,
         task_id: Some(task_id.to_string()),
     }))
 }
 
+/// 按请求携带的数据集令牌、管理员凭据或 API key 识别调用方
+async fn identify_caller(
+    state: &TableRagState,
+    dataset_id: Uuid,
+    headers: &HeaderMap,
+) -> Result<DatasetCaller, (StatusCode, String)> {
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+    state
+        .access
+        .identify_caller(
+            dataset_id,
+            header(state.access.token_header()),
+            header(&execution_policy_config().api_key_header),
+            require_admin(headers).is_ok(),
+        )
+        .await
+        .map_err(search_error)
+}
+
+fn admin_error(e: AdminAccessError) -> (StatusCode, String) {
+    (e.status_code(), e.to_string())
+}
+
 pub async fn search_handler(
     State(state): State<TableRagState>,
+    headers: HeaderMap,
     Json(req): Json<TableSearchRequest>,
-) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+) -> Result<Json<serde_json::Value>, Response> {
     let dataset_id = Uuid::parse_str(&req.dataset_id).map_err(|e| {
         (
             StatusCode::BAD_REQUEST,
             format!("Invalid dataset_id: {}", e),
         )
+            .into_response()
     })?;
+    let caller = identify_caller(&state, dataset_id, &headers)
+        .await
+        .map_err(IntoResponse::into_response)?;
     // If max_results is not provided, let service decide based on dataset defaults
     let max = req.max_results.unwrap_or(0);
     state
         .service
-        .search(dataset_id, &req.query, max, req.similarity_threshold)
+        .search(
+            dataset_id,
+            &req.query,
+            max,
+            req.similarity_threshold,
+            &req.filters,
+            &req.boosts,
+            &caller,
+        )
         .await
         .map(Json)
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+        .map_err(|e| match e.downcast_ref::<SearchFilterError>() {
+            Some(filter_error) => (
+                StatusCode::BAD_REQUEST,
+                Json(json!({ "message": filter_error.to_string(), "errors": filter_error.errors })),
+            )
+                .into_response(),
+            None => search_error(e).into_response(),
+        })
 }
 
 pub async fn search_paged_handler(
     State(state): State<TableRagState>,
+    headers: HeaderMap,
     Json(req): Json<TableSearchPagedRequest>,
 ) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
     let dataset_id = Uuid::parse_str(&req.dataset_id).map_err(|e| {
@@ -172,16 +353,90 @@ pub async fn search_paged_handler(
             format!("Invalid dataset_id: {}", e),
         )
     })?;
+    let caller = identify_caller(&state, dataset_id, &headers).await?;
     let page = req.page.unwrap_or(1);
     let page_size = req.page_size.unwrap_or(20);
     state
         .service
-        .search_paged(dataset_id, &req.query, page, page_size)
+        .search_paged(dataset_id, &req.query, page, page_size, &caller)
+        .await
+        .map(Json)
+        .map_err(search_error)
+}
+
+/// 创建数据集访问令牌，明文只在响应中出现一次；令牌管理需要管理员凭据
+pub async fn create_dataset_token_handler(
+    State(state): State<TableRagState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    Json(req): Json<CreateDatasetTokenRequest>,
+) -> Result<(StatusCode, Json<CreatedDatasetToken>), (StatusCode, String)> {
+    require_admin(&headers).map_err(admin_error)?;
+    let dataset_id = Uuid::parse_str(&id).map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            format!("Invalid dataset_id: {}", e),
+        )
+    })?;
+    match state.access.create_token(dataset_id, req).await {
+        Ok(token) => Ok((StatusCode::CREATED, Json(token))),
+        Err(e) => {
+            let message = e.to_string();
+            if message.starts_with("Invalid") {
+                Err((StatusCode::BAD_REQUEST, message))
+            } else if message.contains("not found") {
+                Err((StatusCode::NOT_FOUND, message))
+            } else {
+                Err((StatusCode::INTERNAL_SERVER_ERROR, message))
+            }
+        }
+    }
+}
+
+pub async fn list_dataset_tokens_handler(
+    State(state): State<TableRagState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Result<Json<Vec<DatasetToken>>, (StatusCode, String)> {
+    require_admin(&headers).map_err(admin_error)?;
+    let dataset_id = Uuid::parse_str(&id).map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            format!("Invalid dataset_id: {}", e),
+        )
+    })?;
+    state
+        .access
+        .list_tokens(dataset_id)
         .await
         .map(Json)
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
 }
 
+pub async fn revoke_dataset_token_handler(
+    State(state): State<TableRagState>,
+    headers: HeaderMap,
+    Path((id, token_id)): Path<(String, String)>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    require_admin(&headers).map_err(admin_error)?;
+    let dataset_id = Uuid::parse_str(&id).map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            format!("Invalid dataset_id: {}", e),
+        )
+    })?;
+    let token_id = Uuid::parse_str(&token_id)
+        .map_err(|e| (StatusCode::BAD_REQUEST, format!("Invalid token_id: {}", e)))?;
+    match state.access.revoke_token(dataset_id, token_id).await {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err((
+            StatusCode::NOT_FOUND,
+            format!("Token {} not found in dataset {}", token_id, dataset_id),
+        )),
+        Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct PreviewSchemaRequest {
     pub file_ids: Vec<String>,
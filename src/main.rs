@@ -28,20 +28,35 @@ use crate::middleware::stream_requests_interceptor;
 use crate::models::DB_POOL;
 use crate::routes::*;
 use crate::services::{
-    EmbeddingService, EndpointListener, FileService, McpService, SessionService, TableRagService,
+    register_vector_cleanup_job, AnalyticsExportService, CanaryService, DatasetAccessService,
+    DemoSeedService, DnsOverrideService, EmbeddingService, EmbeddingTextBuilder, EndpointListener,
+    FairScheduler, FileService, JobService, LocaleService, McpService, PluginService,
+    SessionRecorder, SessionService, TableRagService, ToolStatsAggregator, UsageReportService,
+    BATCH_CALL_CONFIG, EMBEDDING_TEXT_BUILDER, EXECUTION_POLICY_CONFIG, KV_STORE_CONFIG,
+    RECORDING_CONFIG, SESSION_RECORDER, SESSION_TRANSCRIPT_CONFIG, TOOL_STATS,
+};
+use crate::utils::{
+    serve, CachingResolver, CircuitBreakers, FaultInjector, InboundTimeouts,
+    MonitoredSessionManager, PluginRuntime, SessionSecrets, UpstreamGuard, ADMIN_CONFIG,
+    CIRCUIT_BREAKERS, DEADLINE_CONFIG, DNS_RESOLVER, FAULT_INJECTOR, PLUGIN_RUNTIME,
+    SESSION_SECRETS, UPSTREAM_GUARD,
 };
-use crate::utils::MonitoredSessionManager;
 use config::Settings;
 use handlers::*;
-use middleware::cors_layer;
-use models::create_pool;
+use middleware::{
+    arguments_limit, cancelled_results, cors_layer, limit_request_body, read_only_guard,
+    session_transcript, set_max_arguments_bytes, set_read_only, set_session_idle_timeout,
+    set_sse_heartbeat_interval, sse_heartbeat, sse_idle_timeout, sse_session_secret, tools_etag,
+    unknown_notifications, BodyLimitFormat,
+};
+use models::{create_pool, set_strict_spec_parsing, MAIN_POOL, MCP_CALL_POOL};
 use rmcp::transport::streamable_http_server::session::local::LocalSessionManager;
-use services::{EndpointService, SwaggerService};
+use services::{set_max_tools_page_bytes, EndpointService, SpecCache, SwaggerService};
 use state::AppState;
 use tokio::sync::mpsc;
 use tokio::sync::mpsc::UnboundedReceiver;
 use tower::ServiceBuilder;
-use utils::shutdown_signal;
+use utils::{set_flatten_limits, shutdown_signal};
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
@@ -50,6 +65,13 @@ async fn main() -> anyhow::Result<()> {
         eprintln!("Failed to load configuration, using defaults");
         Settings::default()
     });
+    set_read_only(settings.read_only);
+    set_sse_heartbeat_interval(settings.stream.heartbeat_interval_secs);
+    set_session_idle_timeout(settings.server.session_idle_secs);
+    set_max_arguments_bytes(settings.tool_limits.max_arguments_bytes);
+    set_max_tools_page_bytes(settings.tool_limits.soft_max_list_bytes);
+    set_flatten_limits(&settings.schema_flatten);
+    set_strict_spec_parsing(settings.spec_parsing.strict);
 
     // Initialize tracing with configuration
     setup_logging(&settings.logging)?;
@@ -59,7 +81,8 @@ async fn main() -> anyhow::Result<()> {
 
     // Create database connection pool
     let external_pool = create_pool(
-        &settings.database.url,
+        &settings.database,
+        MCP_CALL_POOL,
         settings.database.mcp_call_max_connections,
     )
     .await?;
@@ -67,19 +90,111 @@ async fn main() -> anyhow::Result<()> {
         .set(external_pool)
         .expect("external_pool already initialized");
 
-    let pool = create_pool(&settings.database.url, settings.database.max_connections).await?;
+    let pool = create_pool(
+        &settings.database,
+        MAIN_POOL,
+        settings.database.max_connections,
+    )
+    .await?;
     tracing::info!("Database connection pool created");
     let db_pool = Arc::new(pool);
 
     let (tx, rx) = mpsc::channel(100);
 
     // Create services
-    let endpoint_service = Arc::new(EndpointService::new((*db_pool).clone(), tx.clone()));
+    let spec_cache = Arc::new(SpecCache::new(&settings.spec_cache));
+    let endpoint_service = Arc::new(
+        EndpointService::new((*db_pool).clone(), tx.clone(), spec_cache.clone())
+            .with_event_retry(
+                settings.endpoint_event.send_retries,
+                Duration::from_millis(settings.endpoint_event.retry_interval_ms),
+            )
+            .with_tool_limits(settings.tool_limits.clone())
+            .with_warmup(settings.warmup.clone())
+            .with_lifecycle(settings.lifecycle.clone()),
+    );
     let swagger_service = Arc::new(SwaggerService::new((*endpoint_service).clone()));
-    let mcp_service = Arc::new(McpService::new((*db_pool).clone()));
+    let scheduler = Arc::new(FairScheduler::new(&settings.scheduler)?);
+    EXECUTION_POLICY_CONFIG
+        .set(settings.execution_policy.clone())
+        .unwrap_or_else(|_| panic!("execution policy config already initialized"));
+    RECORDING_CONFIG
+        .set(settings.recording.clone())
+        .unwrap_or_else(|_| panic!("recording config already initialized"));
+    KV_STORE_CONFIG
+        .set(settings.kv_store.clone())
+        .unwrap_or_else(|_| panic!("kv store config already initialized"));
+    SESSION_TRANSCRIPT_CONFIG
+        .set(settings.session_transcript.clone())
+        .unwrap_or_else(|_| panic!("session transcript config already initialized"));
+    SESSION_RECORDER
+        .set(SessionRecorder::spawn(
+            (*db_pool).clone(),
+            settings.session_transcript.clone(),
+        ))
+        .unwrap_or_else(|_| panic!("session recorder already initialized"));
+    DEADLINE_CONFIG
+        .set(settings.deadline.clone())
+        .unwrap_or_else(|_| panic!("deadline config already initialized"));
+    BATCH_CALL_CONFIG
+        .set(settings.batch_call.clone())
+        .unwrap_or_else(|_| panic!("batch call config already initialized"));
+    let tool_stats = Arc::new(ToolStatsAggregator::new(&settings.tool_stats));
+    TOOL_STATS
+        .set(tool_stats.clone())
+        .unwrap_or_else(|_| panic!("tool stats already initialized"));
+    tool_stats.spawn_refresher(settings.tool_stats.refresh_secs);
+    // 上游客户端创建前初始化解析缓存
+    DNS_RESOLVER
+        .set(Arc::new(CachingResolver::from_config(&settings.dns)))
+        .unwrap_or_else(|_| panic!("dns resolver already initialized"));
+    UPSTREAM_GUARD
+        .set(UpstreamGuard::from_config(&settings.upstream)?)
+        .unwrap_or_else(|_| panic!("upstream guard already initialized"));
+    CIRCUIT_BREAKERS
+        .set(CircuitBreakers::new(&settings.circuit_breaker))
+        .unwrap_or_else(|_| panic!("circuit breakers already initialized"));
+    ADMIN_CONFIG
+        .set(settings.admin.clone())
+        .unwrap_or_else(|_| panic!("admin config already initialized"));
+    SESSION_SECRETS
+        .set(SessionSecrets::new(&settings.sse_session))
+        .unwrap_or_else(|_| panic!("session secrets already initialized"));
+    PLUGIN_RUNTIME
+        .set(PluginRuntime::new(&settings.plugins)?)
+        .unwrap_or_else(|_| panic!("plugin runtime already initialized"));
+    // 故障注入只能由启动参数开启
+    let fault_injector = FaultInjector::from_args(std::env::args());
+    if fault_injector.is_enabled() {
+        tracing::warn!("Fault injection enabled, faults can be added via /api/system/faults");
+    }
+    FAULT_INJECTOR
+        .set(fault_injector)
+        .unwrap_or_else(|_| panic!("fault injector already initialized"));
+    let mcp_service = Arc::new(McpService::new(
+        (*db_pool).clone(),
+        scheduler.clone(),
+        spec_cache.clone(),
+    ));
+    // 金丝雀配置常驻内存，之后经配置接口修改即时生效
+    match CanaryService::new((*db_pool).clone()).load_all().await {
+        Ok(count) => tracing::info!("Loaded {} canary configs", count),
+        Err(e) => tracing::warn!("Failed to load canary configs: {}", e),
+    }
+    match DnsOverrideService::new((*db_pool).clone()).load_all().await {
+        Ok(count) => tracing::info!("Loaded DNS overrides for {} endpoints", count),
+        Err(e) => tracing::warn!("Failed to load DNS overrides: {}", e),
+    }
+    match LocaleService::new((*db_pool).clone()).load_all().await {
+        Ok(count) => tracing::info!("Loaded locale settings for {} endpoints", count),
+        Err(e) => tracing::warn!("Failed to load locale settings: {}", e),
+    }
 
     // Initialize EmbeddingService
     let embedding_config = settings.embedding.clone();
+    EMBEDDING_TEXT_BUILDER
+        .set(EmbeddingTextBuilder::new(embedding_config.text.clone()))
+        .unwrap_or_else(|_| panic!("embedding text builder already initialized"));
     let embedding_service = Arc::new(EmbeddingService::from_config(embedding_config.clone())?);
     tracing::info!("EmbeddingService initialized");
 
@@ -93,8 +208,17 @@ async fn main() -> anyhow::Result<()> {
     .map_err(|e| anyhow::anyhow!("Failed to create interface relation state: {}", e))?;
 
     let retrieval_service = interface_retrieval_state.retrieval.clone();
+    // 持久化后台任务，各服务注册处理函数后启动 worker
+    let job_service = Arc::new(JobService::new((*db_pool).clone(), settings.jobs.clone()));
+    register_vector_cleanup_job(&job_service, retrieval_service.clone(), spec_cache.clone());
     let endpoint_listener = EndpointListener::new(retrieval_service, endpoint_service.clone(), tx);
     EndpointListener::run(endpoint_listener, rx);
+    // 数据库恢复或环境克隆后，标记为运行中的端点可能已不可用，降级为 stopped
+    if settings.lifecycle.reconcile_on_startup {
+        if let Err(e) = endpoint_service.reconcile_running_endpoints().await {
+            tracing::warn!("Failed to reconcile running endpoints: {}", e);
+        }
+    }
     // Create File upload state (must be before TableRag to inject dependency)
     let file_service = Arc::new(FileService::new(
         (*db_pool).clone(),
@@ -103,6 +227,31 @@ async fn main() -> anyhow::Result<()> {
     let file_state = handlers::FileState {
         service: file_service.clone(),
     };
+    let analytics_state = handlers::AnalyticsState {
+        service: Arc::new(AnalyticsExportService::new(
+            (*db_pool).clone(),
+            file_service.clone(),
+            settings.analytics.clone(),
+        )),
+    };
+    let plugin_service = Arc::new(PluginService::new((*db_pool).clone(), file_service.clone()));
+    // 插件模块启动时编译并常驻内存，之后经插件接口上传或删除即时生效
+    match plugin_service.load_all().await {
+        Ok(count) => tracing::info!("Loaded {} endpoint plugins", count),
+        Err(e) => tracing::warn!("Failed to load endpoint plugins: {}", e),
+    }
+    let plugin_state = handlers::PluginState {
+        service: plugin_service,
+    };
+    let report_service = Arc::new(UsageReportService::new(
+        (*db_pool).clone(),
+        file_service.clone(),
+        settings.reports.clone(),
+    )?);
+    report_service.clone().spawn_scheduler();
+    let report_state = handlers::ReportState {
+        service: report_service,
+    };
 
     // Create Table RAG state
     let table_rag_service = Arc::new(
@@ -111,11 +260,37 @@ async fn main() -> anyhow::Result<()> {
             embedding_service.clone(),
             (*db_pool).clone(),
             file_service.clone(),
+            &settings.ingest,
+            job_service.clone(),
         )
         .await?,
     );
+    table_rag_service.register_jobs();
+    let dataset_access_service = Arc::new(DatasetAccessService::new(
+        (*db_pool).clone(),
+        settings.dataset_access.clone(),
+    ));
     let table_rag_state = handlers::TableRagState {
         service: table_rag_service.clone(),
+        access: dataset_access_service.clone(),
+    };
+    // 演示数据接口默认关闭，只应在演示环境开启
+    if settings.demo.seed_enabled {
+        tracing::warn!("Demo seeding enabled, demo data can be written via /api/system/seed-demo");
+    }
+    let demo_state = handlers::DemoState {
+        service: Arc::new(DemoSeedService::new(
+            (*db_pool).clone(),
+            settings.demo.clone(),
+            endpoint_service.clone(),
+            table_rag_service.clone(),
+            file_service.clone(),
+            dataset_access_service,
+        )),
+    };
+    job_service.clone().spawn_workers();
+    let job_state = handlers::JobState {
+        service: job_service,
     };
 
     let addr = format!("{}:{}", settings.server.host, settings.server.port);
@@ -142,9 +317,14 @@ async fn main() -> anyhow::Result<()> {
         swagger_service,
         mcp_service.clone(),
         embedding_service,
+        spec_cache.clone(),
         (*db_pool).clone(),
         connect_tx,
     );
+    app_state
+        .metrics_history_service
+        .clone()
+        .spawn_snapshot_task(settings.metrics_history.clone());
 
     let session_service = Arc::new(SessionService::new((*db_pool).clone()));
 
@@ -159,7 +339,10 @@ async fn main() -> anyhow::Result<()> {
         MonitoredSessionManager::new(LocalSessionManager::default(), session_service);
 
     let stream_http_service = StreamableHttpService::new(
-        || Ok(Adapter::new()),
+        {
+            let (scheduler, spec_cache) = (scheduler.clone(), spec_cache.clone());
+            move || Ok(Adapter::new(scheduler.clone(), spec_cache.clone()))
+        },
         session_manager.into(),
         StreamableHttpServerConfig {
             sse_keep_alive: Some(Duration::from_secs(60)),
@@ -173,19 +356,30 @@ async fn main() -> anyhow::Result<()> {
     };
 
     // Build application router with API endpoints
-    let app = Router::new()
+    let server_config = &settings.server;
+    let management_routes = Router::new()
         .merge(create_health_routes())
         .merge(create_endpoint_routes())
         .merge(create_metrics_routes())
-        .merge(create_swagger_routes())
         .merge(create_system_routes())
         .merge(create_connection_routes())
         // Interface relation routes
         .merge(create_interface_relation_routes().with_state(interface_retrieval_state))
         // Table RAG routes
         .merge(create_table_rag_routes().with_state(table_rag_state))
-        // File routes
+        // Analytics export routes
+        .merge(create_analytics_routes().with_state(analytics_state))
+        // Usage report routes
+        .merge(create_report_routes().with_state(report_state))
+        // Background job routes
+        .merge(create_job_routes().with_state(job_state))
+        // Demo data seeding routes
+        .merge(create_demo_routes().with_state(demo_state));
+    // File and endpoint plugin upload routes
+    let upload_routes = Router::new()
         .merge(create_file_routes().with_state(file_state))
+        .merge(create_plugin_routes().with_state(plugin_state));
+    let mcp_routes = Router::new()
         .route(
             "/{endpoint_id}/sse",
             get(sse_handler).with_state(merge_state.clone()),
@@ -195,9 +389,43 @@ async fn main() -> anyhow::Result<()> {
             post(post_event_handler).with_state(merge_state.clone()),
         )
         .nest_service("/stream", stream_http_service)
+        .layer(axum::middleware::from_fn(unknown_notifications))
+        .layer(axum::middleware::from_fn(arguments_limit))
+        .layer(axum::middleware::from_fn_with_state(
+            Adapter::new(scheduler.clone(), spec_cache.clone()),
+            tools_etag,
+        ))
+        .layer(axum::middleware::from_fn(session_transcript));
+
+    let app = Router::new()
+        .merge(limit_request_body(
+            management_routes,
+            server_config.body_limit_bytes,
+            BodyLimitFormat::Problem,
+        ))
+        .merge(limit_request_body(
+            create_swagger_routes(),
+            server_config.swagger_body_limit_bytes,
+            BodyLimitFormat::Problem,
+        ))
+        .merge(limit_request_body(
+            upload_routes,
+            server_config.file_body_limit_bytes,
+            BodyLimitFormat::Problem,
+        ))
+        .merge(limit_request_body(
+            mcp_routes,
+            server_config.mcp_body_limit_bytes,
+            BodyLimitFormat::JsonRpc,
+        ))
         .layer(
             ServiceBuilder::new()
                 .layer(cors_layer())
+                .layer(axum::middleware::from_fn(read_only_guard))
+                .layer(axum::middleware::from_fn(sse_heartbeat))
+                .layer(axum::middleware::from_fn(sse_session_secret))
+                .layer(axum::middleware::from_fn(sse_idle_timeout))
+                .layer(axum::middleware::from_fn(cancelled_results))
                 // .layer(axum::middleware::from_fn(logging::log_requests))
                 .layer(axum::middleware::from_fn_with_state(
                     app_state,
@@ -221,14 +449,13 @@ async fn main() -> anyhow::Result<()> {
     };
 
     // Start server with enhanced graceful shutdown
-    let server = axum::serve(listener, app).with_graceful_shutdown(shutdown_future);
-
-    tokio::spawn(async move {
-        if let Err(e) = server.await {
-            tracing::error!(error = %e, "sse server shutdown with error");
-        }
-    });
-    let ct = sse_server.with_service(Adapter::new);
+    tokio::spawn(serve(
+        listener,
+        app,
+        InboundTimeouts::from(&settings.server),
+        shutdown_future,
+    ));
+    let ct = sse_server.with_service(move || Adapter::new(scheduler.clone(), spec_cache.clone()));
 
     tokio::signal::ctrl_c().await?;
     ct.cancel();
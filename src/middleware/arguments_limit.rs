@@ -0,0 +1,171 @@
+use axum::{
+    body::{to_bytes, Body},
+    http::{header, Method, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde_json::{json, Value};
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// tools/call 参数序列化后的字节上限，0 表示不限制，启动时由配置初始化
+static MAX_ARGUMENTS_BYTES: AtomicUsize = AtomicUsize::new(256 * 1024);
+
+pub fn set_max_arguments_bytes(bytes: usize) {
+    MAX_ARGUMENTS_BYTES.store(bytes, Ordering::Relaxed);
+}
+
+fn max_arguments_bytes() -> Option<usize> {
+    match MAX_ARGUMENTS_BYTES.load(Ordering::Relaxed) {
+        0 => None,
+        bytes => Some(bytes),
+    }
+}
+
+/// 只计数的 writer，超过上限后中止序列化
+struct ByteCounter {
+    bytes: usize,
+    limit: usize,
+}
+
+impl Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.bytes += buf.len();
+        if self.bytes > self.limit {
+            return Err(io::Error::other("limit exceeded"));
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// 参数超过上限时返回已计数的字节数（不小于 limit + 1）
+fn arguments_over_limit(arguments: &Value, limit: usize) -> Option<usize> {
+    let mut counter = ByteCounter { bytes: 0, limit };
+    serde_json::to_writer(&mut counter, arguments)
+        .err()
+        .map(|_| counter.bytes)
+}
+
+/// 单条或批量 JSON-RPC 消息中第一个参数超限的 tools/call，返回其 id 与计数
+fn oversized_call(body: &[u8], limit: usize) -> Option<(Value, usize)> {
+    let check = |message: &Value| {
+        if message["method"] != "tools/call" {
+            return None;
+        }
+        arguments_over_limit(&message["params"]["arguments"], limit)
+            .map(|bytes| (message["id"].clone(), bytes))
+    };
+    match serde_json::from_slice::<Value>(body).ok()? {
+        Value::Array(messages) => messages.iter().find_map(check),
+        message => check(&message),
+    }
+}
+
+fn arguments_too_large(id: Value, limit: usize, bytes: usize) -> Response {
+    let error = rmcp::ErrorData::invalid_params(
+        format!("Tool call arguments exceed the limit of {} bytes", limit),
+        Some(json!({ "limit_bytes": limit, "arguments_bytes_at_least": bytes })),
+    );
+    let body = json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": error,
+    });
+    (
+        StatusCode::PAYLOAD_TOO_LARGE,
+        [(header::CONTENT_TYPE, "application/json")],
+        body.to_string(),
+    )
+        .into_response()
+}
+
+/// MCP 传输（/message、/stream）收到参数超限的 tools/call 时直接拒绝，不再解析转发；
+/// 需放在请求体上限之内，读取请求体时才受路由组上限约束
+pub async fn arguments_limit(req: Request<Body>, next: Next) -> Response {
+    let path = req.uri().path();
+    let is_transport = path == "/message" || path.starts_with("/stream");
+    let Some(limit) =
+        max_arguments_bytes().filter(|_| is_transport && req.method() == Method::POST)
+    else {
+        return next.run(req).await;
+    };
+
+    let (parts, body) = req.into_parts();
+    let bytes = match to_bytes(body, usize::MAX).await {
+        Ok(bytes) => bytes,
+        // 外层路由组的请求体上限在此触发，保留 413 以便统一改写
+        Err(e) if e.to_string().contains("length limit exceeded") => {
+            return (StatusCode::PAYLOAD_TOO_LARGE, e.to_string()).into_response()
+        }
+        Err(e) => return (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+    };
+    if let Some((id, arguments_bytes)) = oversized_call(&bytes, limit) {
+        tracing::warn!(
+            path = %parts.uri.path(),
+            limit,
+            "rejected tools/call with oversized arguments"
+        );
+        return arguments_too_large(id, limit, arguments_bytes);
+    }
+    next.run(Request::from_parts(parts, Body::from(bytes)))
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{routing::post, Router};
+    use tower::ServiceExt;
+
+    #[test]
+    fn test_oversized_call_in_batch() {
+        let small = json!({"jsonrpc": "2.0", "id": 1, "method": "tools/call", "params": {"name": "getPet", "arguments": {"id": 1}}});
+        let large = json!({"jsonrpc": "2.0", "id": "big", "method": "tools/call", "params": {"name": "addPet", "arguments": {"note": "x".repeat(100)}}});
+        let body = serde_json::to_vec(&json!([small, large])).unwrap();
+        let (id, bytes) = oversized_call(&body, 64).unwrap();
+        assert_eq!(id, "big");
+        assert!(bytes > 64);
+        assert!(oversized_call(&body, 1024).is_none());
+        assert!(oversized_call(b"not json", 1).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_oversized_arguments_are_rejected() {
+        let app = Router::new()
+            .route("/message", post(|| async { StatusCode::ACCEPTED }))
+            .layer(axum::middleware::from_fn(arguments_limit));
+        let post_call = |arguments: Value| {
+            let app = app.clone();
+            async move {
+                let message = json!({
+                    "jsonrpc": "2.0",
+                    "id": 7,
+                    "method": "tools/call",
+                    "params": {"name": "addPet", "arguments": arguments}
+                });
+                let req = Request::builder()
+                    .method("POST")
+                    .uri("/message?sessionId=abc")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(message.to_string()))
+                    .unwrap();
+                app.oneshot(req).await.unwrap()
+            }
+        };
+
+        let response = post_call(json!({"name": "doggie"})).await;
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+
+        let response = post_call(json!({"name": "x".repeat(300 * 1024)})).await;
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["id"], 7);
+        assert_eq!(body["error"]["code"], -32602);
+        assert_eq!(body["error"]["data"]["limit_bytes"], 256 * 1024);
+    }
+}
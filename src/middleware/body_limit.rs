@@ -0,0 +1,209 @@
+use axum::{
+    body::{to_bytes, Body},
+    extract::DefaultBodyLimit,
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Router,
+};
+use serde_json::json;
+use tower_http::limit::RequestBodyLimitLayer;
+
+/// 超限拒绝的响应体（tower-http 的预检与 axum 读取 body 失败）都含有该文本，
+/// 借此与处理函数自身返回的 413 区分
+const LENGTH_LIMIT_EXCEEDED: &str = "length limit exceeded";
+
+/// 识别 413 响应时最多读取的字节数
+const REJECTION_PEEK_BYTES: usize = 64 * 1024;
+
+/// 请求体超限时的错误格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BodyLimitFormat {
+    /// 管理接口，返回 problem+json
+    Problem,
+    /// MCP 传输（/message、/stream），返回 JSON-RPC 错误
+    JsonRpc,
+}
+
+/// 请求体超过 limit 字节时的统一错误响应
+pub fn payload_too_large(limit: usize, format: BodyLimitFormat) -> Response {
+    let detail = format!("Request body exceeds the limit of {} bytes", limit);
+    match format {
+        BodyLimitFormat::Problem => {
+            let body = json!({
+                "type": "about:blank",
+                "title": "Payload too large",
+                "status": StatusCode::PAYLOAD_TOO_LARGE.as_u16(),
+                "detail": detail,
+                "limit_bytes": limit,
+            });
+            (
+                StatusCode::PAYLOAD_TOO_LARGE,
+                [(header::CONTENT_TYPE, "application/problem+json")],
+                body.to_string(),
+            )
+                .into_response()
+        }
+        BodyLimitFormat::JsonRpc => {
+            let error =
+                rmcp::ErrorData::invalid_request(detail, Some(json!({ "limit_bytes": limit })));
+            let body = json!({
+                "jsonrpc": "2.0",
+                "id": null,
+                "error": error,
+            });
+            (
+                StatusCode::PAYLOAD_TOO_LARGE,
+                [(header::CONTENT_TYPE, "application/json")],
+                body.to_string(),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// 把超限拒绝改写为统一格式，其他响应原样返回
+async fn map_rejection(response: Response, limit: usize, format: BodyLimitFormat) -> Response {
+    if response.status() != StatusCode::PAYLOAD_TOO_LARGE {
+        return response;
+    }
+    let (parts, body) = response.into_parts();
+    match to_bytes(body, REJECTION_PEEK_BYTES).await {
+        Ok(bytes) if String::from_utf8_lossy(&bytes).contains(LENGTH_LIMIT_EXCEEDED) => {
+            payload_too_large(limit, format)
+        }
+        Ok(bytes) => Response::from_parts(parts, Body::from(bytes)),
+        Err(_) => Response::from_parts(parts, Body::empty()),
+    }
+}
+
+/// 为路由组设置请求体上限，超限时按 format 返回统一的错误格式；
+/// axum 默认的 2MB 上限同时关闭，以路由组的上限为准
+pub fn limit_request_body<S>(router: Router<S>, limit: usize, format: BodyLimitFormat) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    router
+        .layer(DefaultBodyLimit::disable())
+        .layer(RequestBodyLimitLayer::new(limit))
+        .layer(axum::middleware::map_response(move |response: Response| {
+            map_rejection(response, limit, format)
+        }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Bytes, http::Request, routing::post, Json};
+    use serde_json::Value;
+    use tower::ServiceExt;
+
+    async fn send(app: Router, uri: &str, body: Body) -> (StatusCode, String, Value) {
+        let req = Request::builder()
+            .method("POST")
+            .uri(uri)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(body)
+            .unwrap();
+        let response = app.oneshot(req).await.unwrap();
+        let status = response.status();
+        let content_type = response
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .map(|v| v.to_str().unwrap().to_string())
+            .unwrap_or_default();
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body = serde_json::from_slice(&bytes).unwrap_or(Value::Null);
+        (status, content_type, body)
+    }
+
+    /// 不带 Content-Length 的分块请求体，只有读取时才会超限
+    fn chunked(size: usize) -> Body {
+        let chunks =
+            (0..size / 1024).map(|_| Ok::<_, std::io::Error>(Bytes::from(vec![b' '; 1024])));
+        Body::from_stream(futures::stream::iter(chunks))
+    }
+
+    #[tokio::test]
+    async fn test_oversized_mcp_message_returns_json_rpc_error() {
+        let app = limit_request_body(
+            Router::new().route(
+                "/message",
+                post(|Json(v): Json<Value>| async move { Json(v) }),
+            ),
+            1024,
+            BodyLimitFormat::JsonRpc,
+        );
+
+        let message = json!({"jsonrpc": "2.0", "id": 1, "method": "ping"}).to_string();
+        let (status, _, body) = send(app.clone(), "/message", Body::from(message)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["method"], "ping");
+
+        // 带 Content-Length 时由预检直接拒绝
+        let message = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"{}"}}"#,
+            "x".repeat(2048)
+        );
+        let (status, content_type, body) = send(app, "/message", Body::from(message)).await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(content_type, "application/json");
+        assert_eq!(body["jsonrpc"], "2.0");
+        assert_eq!(body["id"], Value::Null);
+        assert_eq!(body["error"]["code"], -32600);
+        assert_eq!(body["error"]["data"]["limit_bytes"], 1024);
+    }
+
+    #[tokio::test]
+    async fn test_oversized_swagger_payload_returns_problem_json() {
+        let swagger = Router::new().route(
+            "/api/swagger",
+            post(|body: Bytes| async move { body.len().to_string() }),
+        );
+        let app = limit_request_body(swagger, 4 * 1024 * 1024, BodyLimitFormat::Problem);
+
+        // 超过 axum 默认 2MB、但在路由组上限内的请求体仍可读取
+        let (status, _, body) = send(app.clone(), "/api/swagger", chunked(3 * 1024 * 1024)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, json!(3 * 1024 * 1024));
+
+        let (status, content_type, body) =
+            send(app, "/api/swagger", chunked(5 * 1024 * 1024)).await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(content_type, "application/problem+json");
+        assert_eq!(body["status"], 413);
+        assert_eq!(body["limit_bytes"], 4 * 1024 * 1024);
+        assert_eq!(
+            body["detail"],
+            "Request body exceeds the limit of 4194304 bytes"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_handler_payload_too_large_is_kept() {
+        let app = limit_request_body(
+            Router::new().route(
+                "/api/endpoints/1/plugin",
+                post(|| async {
+                    (
+                        StatusCode::PAYLOAD_TOO_LARGE,
+                        "Plugin module is 2 bytes, exceeds limit of 1 bytes",
+                    )
+                }),
+            ),
+            1024,
+            BodyLimitFormat::Problem,
+        );
+        let req = Request::builder()
+            .method("POST")
+            .uri("/api/endpoints/1/plugin")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(req).await.unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(
+            &bytes[..],
+            b"Plugin module is 2 bytes, exceeds limit of 1 bytes"
+        );
+    }
+}
@@ -0,0 +1,193 @@
+use crate::utils::{cancellation_registry, CancellationRegistry};
+use axum::{
+    body::{Body, Bytes},
+    http::{header, Method, Request},
+    middleware::Next,
+    response::Response,
+};
+use futures::stream::{BoxStream, Stream, StreamExt};
+use rmcp::transport::common::http_header::HEADER_SESSION_ID;
+use serde_json::Value;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// 按 SSE 事件过滤响应体，丢弃已被客户端取消的请求的结果帧；
+/// 不完整的事件先缓存，直到收到事件分隔符
+pub struct CancelledResultFilter {
+    inner: BoxStream<'static, Result<Bytes, axum::Error>>,
+    session: String,
+    registry: &'static CancellationRegistry,
+    buffer: Vec<u8>,
+}
+
+impl CancelledResultFilter {
+    pub fn new(
+        inner: BoxStream<'static, Result<Bytes, axum::Error>>,
+        session: String,
+        registry: &'static CancellationRegistry,
+    ) -> Self {
+        Self {
+            // 上游结束后还会输出缓冲区剩余内容，之后可能再次轮询
+            inner: inner.fuse().boxed(),
+            session,
+            registry,
+            buffer: Vec::new(),
+        }
+    }
+
+    /// 取出缓冲区中完整的事件，去掉被取消请求的结果
+    fn drain_events(&mut self) -> Vec<u8> {
+        let mut output = Vec::new();
+        while let Some(end) = self.buffer.windows(2).position(|w| w == b"\n\n") {
+            let event: Vec<u8> = self.buffer.drain(..end + 2).collect();
+            if !self.is_cancelled_result(&event) {
+                output.extend_from_slice(&event);
+            }
+        }
+        output
+    }
+
+    fn is_cancelled_result(&self, event: &[u8]) -> bool {
+        let data: String = String::from_utf8_lossy(event)
+            .lines()
+            .filter_map(|line| line.strip_prefix("data:"))
+            .map(str::trim_start)
+            .collect();
+        let Ok(message) = serde_json::from_str::<Value>(&data) else {
+            return false;
+        };
+        let is_response = message.get("result").is_some() || message.get("error").is_some();
+        match message.get("id") {
+            Some(id) if is_response => self.registry.take_cancelled(&self.session, &id.to_string()),
+            _ => false,
+        }
+    }
+}
+
+impl Stream for CancelledResultFilter {
+    type Item = Result<Bytes, axum::Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            match self.inner.poll_next_unpin(cx) {
+                Poll::Ready(Some(Ok(chunk))) => {
+                    self.buffer.extend_from_slice(&chunk);
+                    let output = self.drain_events();
+                    if !output.is_empty() {
+                        return Poll::Ready(Some(Ok(Bytes::from(output))));
+                    }
+                }
+                Poll::Ready(Some(Err(e))) => return Poll::Ready(Some(Err(e))),
+                Poll::Ready(None) if !self.buffer.is_empty() => {
+                    let rest = std::mem::take(&mut self.buffer);
+                    return Poll::Ready(Some(Ok(Bytes::from(rest))));
+                }
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+/// streamable HTTP 的 POST 响应中，被 notifications/cancelled 取消的请求不再发出结果帧。
+/// SSE 传输的结果经由长连接发出，不在此过滤
+pub async fn cancelled_results(req: Request<Body>, next: Next) -> Response {
+    let session = match req.method() {
+        &Method::POST if req.uri().path().starts_with("/stream") => req
+            .headers()
+            .get(HEADER_SESSION_ID)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string),
+        _ => None,
+    };
+    let response = next.run(req).await;
+    let Some(session) = session else {
+        return response;
+    };
+    let is_event_stream = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("text/event-stream"));
+    if !is_event_stream {
+        return response;
+    }
+
+    let (parts, body) = response.into_parts();
+    let stream = CancelledResultFilter::new(
+        body.into_data_stream().boxed(),
+        session,
+        cancellation_registry(),
+    );
+    Response::from_parts(parts, Body::from_stream(stream))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::stream;
+    use std::time::Duration;
+    use tokio_util::sync::CancellationToken;
+
+    async fn filtered(session: &str, chunks: Vec<&'static str>) -> String {
+        let inner = stream::iter(
+            chunks
+                .into_iter()
+                .map(|chunk| Ok(Bytes::from_static(chunk.as_bytes()))),
+        )
+        .boxed();
+        let body = CancelledResultFilter::new(inner, session.to_string(), cancellation_registry())
+            .map(|chunk| chunk.unwrap())
+            .collect::<Vec<Bytes>>()
+            .await
+            .concat();
+        String::from_utf8(body).unwrap()
+    }
+
+    /// 登记一个请求并将其取消，留下取消标记
+    async fn cancel(session: &str, request_id: &str) {
+        let registry = cancellation_registry();
+        let call = registry.run(session, request_id, CancellationToken::new(), async {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+        });
+        tokio::pin!(call);
+        assert!(futures::poll!(call.as_mut()).is_pending());
+        assert!(registry.cancel(session, request_id));
+        assert!(call.await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_result_frame_is_dropped() {
+        let session = uuid::Uuid::new_v4().to_string();
+        cancel(&session, "2").await;
+
+        // 结果帧跨越多个分块；心跳与其他请求的结果照常输出
+        let body = filtered(
+            &session,
+            vec![
+                ": ping\n\n",
+                "data: {\"jsonrpc\":\"2.0\",\"id\":2,",
+                "\"error\":{\"code\":-32603,\"message\":\"Request cancelled by client\"}}\n\n",
+                "data: {\"jsonrpc\":\"2.0\",\"id\":3,\"result\":{}}\n\n",
+            ],
+        )
+        .await;
+        assert_eq!(
+            body,
+            ": ping\n\ndata: {\"jsonrpc\":\"2.0\",\"id\":3,\"result\":{}}\n\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_other_sessions_and_string_ids_untouched() {
+        let session = uuid::Uuid::new_v4().to_string();
+        cancel(&session, "\"7\"").await;
+
+        // 数字 id 7 与字符串 id "7" 不同；其他会话中的同一 id 不受影响
+        let numeric = "data: {\"jsonrpc\":\"2.0\",\"id\":7,\"result\":{}}\n\n";
+        assert_eq!(filtered(&session, vec![numeric]).await, numeric);
+        let string = "data: {\"jsonrpc\":\"2.0\",\"id\":\"7\",\"result\":{}}\n\n";
+        assert_eq!(filtered("other-session", vec![string]).await, string);
+        assert!(filtered(&session, vec![string]).await.is_empty());
+    }
+}
@@ -0,0 +1,127 @@
+use axum::{
+    body::{Body, Bytes},
+    http::{header, Request},
+    middleware::Next,
+    response::Response,
+};
+use futures::stream::{BoxStream, Stream, StreamExt};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::time::{interval_at, Instant, Interval, MissedTickBehavior};
+
+/// SSE 注释帧，客户端忽略，仅用于保持代理连接活跃
+pub const HEARTBEAT_FRAME: &[u8] = b": ping\n\n";
+
+/// 心跳间隔（秒），0 表示关闭，启动时由配置初始化
+static HEARTBEAT_INTERVAL_SECS: AtomicU64 = AtomicU64::new(15);
+
+pub fn set_sse_heartbeat_interval(secs: u64) {
+    HEARTBEAT_INTERVAL_SECS.store(secs, Ordering::Relaxed);
+}
+
+fn heartbeat_interval() -> Option<Duration> {
+    match HEARTBEAT_INTERVAL_SECS.load(Ordering::Relaxed) {
+        0 => None,
+        secs => Some(Duration::from_secs(secs)),
+    }
+}
+
+/// 在 SSE 响应体空闲期间插入心跳帧；有数据输出时重新计时，响应体结束后停止
+pub struct HeartbeatStream {
+    inner: BoxStream<'static, Result<Bytes, axum::Error>>,
+    interval: Interval,
+    period: Duration,
+}
+
+impl HeartbeatStream {
+    pub fn new(inner: BoxStream<'static, Result<Bytes, axum::Error>>, period: Duration) -> Self {
+        let mut interval = interval_at(Instant::now() + period, period);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        Self {
+            inner,
+            interval,
+            period,
+        }
+    }
+}
+
+impl Stream for HeartbeatStream {
+    type Item = Result<Bytes, axum::Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        match self.inner.poll_next_unpin(cx) {
+            Poll::Ready(item) => {
+                let next = Instant::now() + self.period;
+                self.interval.reset_at(next);
+                Poll::Ready(item)
+            }
+            Poll::Pending => match self.interval.poll_tick(cx) {
+                Poll::Ready(_) => Poll::Ready(Some(Ok(Bytes::from_static(HEARTBEAT_FRAME)))),
+                Poll::Pending => Poll::Pending,
+            },
+        }
+    }
+}
+
+/// streamable 传输的 SSE 响应加心跳，避免长耗时 tools/call 被空闲超时的代理断开
+pub async fn sse_heartbeat(req: Request<Body>, next: Next) -> Response {
+    let streamable = req.uri().path().starts_with("/stream");
+    let response = next.run(req).await;
+    let Some(period) = heartbeat_interval().filter(|_| streamable) else {
+        return response;
+    };
+    let is_event_stream = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("text/event-stream"));
+    if !is_event_stream {
+        return response;
+    }
+
+    let (parts, body) = response.into_parts();
+    let stream = HeartbeatStream::new(body.into_data_stream().boxed(), period);
+    Response::from_parts(parts, Body::from_stream(stream))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::stream;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_heartbeats_emitted_during_delayed_response() {
+        let delayed = stream::once(async {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            Ok(Bytes::from_static(b"data: {\"result\":{}}\n\n"))
+        })
+        .boxed();
+        let frames: Vec<Bytes> = HeartbeatStream::new(delayed, Duration::from_secs(15))
+            .map(|frame| frame.unwrap())
+            .collect()
+            .await;
+
+        // 60 秒内每 15 秒一次心跳（第 60 秒与结果同时就绪时优先输出结果），最后是结果且流正常结束
+        let heartbeats = frames
+            .iter()
+            .filter(|f| f.as_ref() == HEARTBEAT_FRAME)
+            .count();
+        assert!(heartbeats >= 3, "expected heartbeats, got {:?}", frames);
+        assert_eq!(
+            frames.last().unwrap().as_ref(),
+            b"data: {\"result\":{}}\n\n"
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_no_heartbeat_for_prompt_response() {
+        let ready = stream::iter(vec![Ok(Bytes::from_static(b"data: {}\n\n"))]).boxed();
+        let frames: Vec<Bytes> = HeartbeatStream::new(ready, Duration::from_secs(15))
+            .map(|frame| frame.unwrap())
+            .collect()
+            .await;
+        assert_eq!(frames, vec![Bytes::from_static(b"data: {}\n\n")]);
+    }
+}
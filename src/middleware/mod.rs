@@ -1,6 +1,26 @@
+pub mod arguments_limit;
+pub mod body_limit;
+pub mod cancelled_results;
 pub mod cors;
+pub mod heartbeat;
 mod interceptor;
+pub mod notifications;
+pub mod read_only;
+pub mod session_idle;
+pub mod session_secret;
+pub mod session_transcript;
+pub mod tools_etag;
 // mod metrics;
 
+pub use arguments_limit::*;
+pub use body_limit::*;
+pub use cancelled_results::*;
 pub use cors::*;
+pub use heartbeat::*;
 pub use interceptor::*;
+pub use notifications::*;
+pub use read_only::*;
+pub use session_idle::*;
+pub use session_secret::*;
+pub use session_transcript::*;
+pub use tools_etag::*;
@@ -0,0 +1,144 @@
+use axum::{
+    body::{to_bytes, Body},
+    http::{header, Method, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde_json::Value;
+
+/// rmcp 能解析的客户端通知，其余通知在转发前丢弃
+const KNOWN_NOTIFICATIONS: &[&str] = &[
+    "notifications/initialized",
+    "notifications/cancelled",
+    "notifications/progress",
+    "notifications/roots/list_changed",
+];
+
+/// 不带 id 且 rmcp 无法识别的通知；JSON-RPC 不允许对通知返回响应
+fn is_unknown_notification(message: &Value) -> bool {
+    message.get("id").is_none()
+        && message["method"]
+            .as_str()
+            .is_some_and(|method| !KNOWN_NOTIFICATIONS.contains(&method))
+}
+
+/// 去掉单条或批量消息中的未知通知，返回 None 表示无需改写；
+/// 返回空数组表示全部为未知通知
+fn strip_unknown_notifications(body: &[u8]) -> Option<Vec<Value>> {
+    match serde_json::from_slice::<Value>(body).ok()? {
+        Value::Array(messages) if messages.iter().any(is_unknown_notification) => Some(
+            messages
+                .into_iter()
+                .filter(|message| !is_unknown_notification(message))
+                .collect(),
+        ),
+        message if is_unknown_notification(&message) => Some(Vec::new()),
+        _ => None,
+    }
+}
+
+/// MCP 传输（/message、/stream）收到未知通知时直接返回 202，不转发给 rmcp，
+/// 避免对 notifications/* 等无 id 消息返回 Method not found 错误帧
+pub async fn unknown_notifications(req: Request<Body>, next: Next) -> Response {
+    let path = req.uri().path();
+    let is_transport = path == "/message" || path.starts_with("/stream");
+    if !is_transport || req.method() != Method::POST {
+        return next.run(req).await;
+    }
+
+    let (mut parts, body) = req.into_parts();
+    let bytes = match to_bytes(body, usize::MAX).await {
+        Ok(bytes) => bytes,
+        // 保留请求体上限触发的 413，交给路由组统一改写
+        Err(e) if e.to_string().contains("length limit exceeded") => {
+            return (StatusCode::PAYLOAD_TOO_LARGE, e.to_string()).into_response()
+        }
+        Err(e) => return (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+    };
+    let body = match strip_unknown_notifications(&bytes) {
+        None => Body::from(bytes),
+        Some(messages) if messages.is_empty() => {
+            tracing::debug!(path = %parts.uri.path(), "ignored unknown notification");
+            return StatusCode::ACCEPTED.into_response();
+        }
+        Some(messages) => {
+            let body = Value::Array(messages).to_string();
+            parts.headers.remove(header::CONTENT_LENGTH);
+            Body::from(body)
+        }
+    };
+    next.run(Request::from_parts(parts, body)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{routing::post, Router};
+    use serde_json::json;
+    use tower::ServiceExt;
+
+    #[test]
+    fn test_strip_unknown_notifications() {
+        let unknown = json!({"jsonrpc": "2.0", "method": "notifications/custom", "params": {}});
+        let progress = json!({"jsonrpc": "2.0", "method": "notifications/progress", "params": {}});
+        let request = json!({"jsonrpc": "2.0", "id": 1, "method": "unknown/method"});
+
+        let body = serde_json::to_vec(&unknown).unwrap();
+        assert_eq!(strip_unknown_notifications(&body), Some(Vec::new()));
+        // 已知通知与带 id 的请求交给 rmcp 处理
+        for message in [&progress, &request] {
+            let body = serde_json::to_vec(message).unwrap();
+            assert_eq!(strip_unknown_notifications(&body), None);
+        }
+
+        let body = serde_json::to_vec(&json!([unknown, request])).unwrap();
+        assert_eq!(strip_unknown_notifications(&body), Some(vec![request]));
+        assert_eq!(strip_unknown_notifications(b"not json"), None);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_notification_has_no_response_body() {
+        let app = Router::new()
+            .route(
+                "/message",
+                post(|body: String| async move { (StatusCode::OK, body) }),
+            )
+            .layer(axum::middleware::from_fn(unknown_notifications));
+        let send = |message: Value| {
+            let app = app.clone();
+            async move {
+                let req = Request::builder()
+                    .method("POST")
+                    .uri("/message?sessionId=abc")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(message.to_string()))
+                    .unwrap();
+                let response = app.oneshot(req).await.unwrap();
+                let status = response.status();
+                let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                (status, String::from_utf8(body.to_vec()).unwrap())
+            }
+        };
+
+        let (status, body) = send(json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/message_read",
+            "params": {"id": 3}
+        }))
+        .await;
+        assert_eq!(status, StatusCode::ACCEPTED);
+        assert!(body.is_empty());
+
+        let (status, body) = send(json!([
+            {"jsonrpc": "2.0", "method": "notifications/message_read"},
+            {"jsonrpc": "2.0", "id": 2, "method": "ping"}
+        ]))
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let forwarded: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(
+            forwarded,
+            json!([{"jsonrpc": "2.0", "id": 2, "method": "ping"}])
+        );
+    }
+}
@@ -0,0 +1,197 @@
+use crate::utils::require_admin;
+use axum::{
+    body::Body,
+    http::{header, Method, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde_json::json;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// 只读模式开关，启动时由配置初始化，运行时管理员可通过 /api/system/read-only 切换
+static READ_ONLY: AtomicBool = AtomicBool::new(false);
+
+/// 只读模式下仍允许的 POST/PUT 管理路由（查询、预览类）
+const SAFE_MUTATING_ROUTES: &[&str] = &[
+    "/api/interface-retrieval/search",
+    "/api/swagger/preview-tools",
+    "/api/table-rag/search",
+    "/api/table-rag/search-paged",
+    "/api/table-rag/preview-schema",
+    "/api/table-rag/remote/test-connection",
+    "/api/table-rag/remote/list-tables",
+];
+
+/// 只读模式下仍允许的带路径参数的 POST 路由后缀（工具调用）
+const SAFE_MUTATING_SUFFIXES: &[&str] = &["/tools/batch-call"];
+
+/// 切换只读模式的路由，只读期间仅放行携带管理员凭据的请求
+pub const READ_ONLY_TOGGLE_ROUTE: &str = "/api/system/read-only";
+
+pub fn set_read_only(enabled: bool) {
+    READ_ONLY.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_read_only() -> bool {
+    READ_ONLY.load(Ordering::Relaxed)
+}
+
+/// 是否为只读模式需拦截的请求：/api 下的变更方法，且不在白名单中；
+/// MCP 传输（/sse、/message、/stream）与所有 GET 不受影响
+pub fn is_mutating_management_request(method: &Method, path: &str) -> bool {
+    let mutating = matches!(
+        *method,
+        Method::POST | Method::PUT | Method::PATCH | Method::DELETE
+    );
+    mutating
+        && path.starts_with("/api/")
+        && !SAFE_MUTATING_ROUTES.contains(&path)
+        && !SAFE_MUTATING_SUFFIXES
+            .iter()
+            .any(|suffix| path.ends_with(suffix))
+}
+
+/// 只读模式中间件，拒绝变更类管理请求并返回 problem+json
+pub async fn read_only_guard(req: Request<Body>, next: Next) -> Response {
+    let admin_toggle =
+        req.uri().path() == READ_ONLY_TOGGLE_ROUTE && require_admin(req.headers()).is_ok();
+    if is_read_only()
+        && !admin_toggle
+        && is_mutating_management_request(req.method(), req.uri().path())
+    {
+        let body = json!({
+            "type": "about:blank",
+            "title": "Read-only mode",
+            "status": StatusCode::FORBIDDEN.as_u16(),
+            "detail": format!(
+                "{} {} is disabled because the gateway is running in read-only mode",
+                req.method(),
+                req.uri().path()
+            ),
+        });
+        return (
+            StatusCode::FORBIDDEN,
+            [(header::CONTENT_TYPE, "application/problem+json")],
+            body.to_string(),
+        )
+            .into_response();
+    }
+    next.run(req).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_enforcement_matrix() {
+        let blocked = [
+            (Method::POST, "/api/endpoint"),
+            (Method::PUT, "/api/endpoint/1"),
+            (Method::DELETE, "/api/endpoint/1"),
+            (Method::POST, "/api/endpoint/1/start"),
+            (Method::POST, "/api/table-rag/datasets"),
+            (Method::PUT, "/api/table-rag/datasets/1"),
+            (Method::POST, "/api/table-rag/ingest"),
+            (Method::POST, "/api/files/upload"),
+            (Method::DELETE, "/api/interface-retrieval/projects/demo"),
+            (Method::PUT, "/api/system/read-only"),
+        ];
+        for (method, path) in &blocked {
+            assert!(
+                is_mutating_management_request(method, path),
+                "{} {} should be blocked",
+                method,
+                path
+            );
+        }
+
+        let allowed = [
+            (Method::GET, "/api/endpoints"),
+            (Method::GET, "/api/endpoint/1"),
+            (Method::GET, "/api/system/info"),
+            (Method::POST, "/api/interface-retrieval/search"),
+            (Method::POST, "/api/swagger/preview-tools"),
+            (Method::POST, "/api/table-rag/search"),
+            (Method::POST, "/api/table-rag/preview-schema"),
+            (Method::POST, "/api/endpoints/1/tools/batch-call"),
+            (Method::POST, "/message"),
+            (Method::POST, "/stream/1"),
+            (Method::GET, "/health"),
+        ];
+        for (method, path) in &allowed {
+            assert!(
+                !is_mutating_management_request(method, path),
+                "{} {} should be allowed",
+                method,
+                path
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_runtime_toggle() {
+        use axum::routing::{post, put};
+        use tower::ServiceExt;
+
+        let admin = crate::utils::ADMIN_CONFIG.get_or_init(|| crate::config::AdminConfig {
+            api_keys: vec!["test-admin-key".to_string()],
+            ..Default::default()
+        });
+        let app = axum::Router::new()
+            .route(
+                "/api/endpoint",
+                post(|| async { "created" }).get(|| async { "list" }),
+            )
+            .route(READ_ONLY_TOGGLE_ROUTE, put(|| async { "toggled" }))
+            .layer(axum::middleware::from_fn(read_only_guard));
+        let call = |method: Method, uri: &'static str, admin_key: Option<&'static str>| {
+            let app = app.clone();
+            async move {
+                let mut req = Request::builder().method(method).uri(uri);
+                if let Some(key) = admin_key {
+                    req = req.header(admin.api_key_header.as_str(), key);
+                }
+                app.oneshot(req.body(Body::empty()).unwrap()).await.unwrap()
+            }
+        };
+
+        set_read_only(true);
+        let response = call(Method::POST, "/api/endpoint", None).await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            "application/problem+json"
+        );
+        assert_eq!(
+            call(Method::GET, "/api/endpoint", None).await.status(),
+            StatusCode::OK
+        );
+        // 只读期间只有管理员能关闭开关，管理员凭据不放行其他变更
+        assert_eq!(
+            call(Method::PUT, READ_ONLY_TOGGLE_ROUTE, None)
+                .await
+                .status(),
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            call(Method::PUT, READ_ONLY_TOGGLE_ROUTE, Some("test-admin-key"))
+                .await
+                .status(),
+            StatusCode::OK
+        );
+        assert_eq!(
+            call(Method::POST, "/api/endpoint", Some("test-admin-key"))
+                .await
+                .status(),
+            StatusCode::FORBIDDEN
+        );
+
+        // 运行时关闭后立即生效
+        set_read_only(false);
+        assert_eq!(
+            call(Method::POST, "/api/endpoint", None).await.status(),
+            StatusCode::OK
+        );
+    }
+}
@@ -0,0 +1,278 @@
+use axum::{
+    body::{Body, Bytes},
+    http::{header, Method, Request},
+    middleware::Next,
+    response::Response,
+};
+use dashmap::DashMap;
+use futures::stream::{BoxStream, Stream, StreamExt};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::time::{sleep_until, Instant, Sleep};
+
+/// 旧版 SSE 会话的空闲超时（秒），0 表示关闭，启动时由配置初始化
+static SESSION_IDLE_SECS: AtomicU64 = AtomicU64::new(1800);
+
+pub fn set_session_idle_timeout(secs: u64) {
+    SESSION_IDLE_SECS.store(secs, Ordering::Relaxed);
+}
+
+fn session_idle_timeout() -> Option<Duration> {
+    match SESSION_IDLE_SECS.load(Ordering::Relaxed) {
+        0 => None,
+        secs => Some(Duration::from_secs(secs)),
+    }
+}
+
+/// 打开中的旧版 SSE 会话最近一次收到 POST /message 的时间
+fn session_activity() -> &'static DashMap<String, Instant> {
+    static SESSION_ACTIVITY: OnceLock<DashMap<String, Instant>> = OnceLock::new();
+    SESSION_ACTIVITY.get_or_init(DashMap::new)
+}
+
+/// 记录会话的入站消息，未登记的会话忽略
+fn touch_session(session_id: &str) {
+    if let Some(mut last) = session_activity().get_mut(session_id) {
+        *last = Instant::now();
+    }
+}
+
+/// endpoint 事件 url 中的会话 id
+fn endpoint_session(event: &str) -> Option<String> {
+    let is_endpoint = event
+        .lines()
+        .any(|line| line.strip_prefix("event:").map(str::trim) == Some("endpoint"));
+    if !is_endpoint {
+        return None;
+    }
+    event
+        .lines()
+        .find_map(|line| line.strip_prefix("data:"))?
+        .trim()
+        .split_once('?')?
+        .1
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("sessionId="))
+        .map(str::to_string)
+}
+
+/// 旧版 SSE 响应体：从 endpoint 事件登记会话，超过空闲时间没有入站消息时结束响应体；
+/// 响应体结束后 rmcp 按客户端断开处理，从会话表中移除会话
+pub struct IdleTimeoutStream {
+    inner: BoxStream<'static, Result<Bytes, axum::Error>>,
+    timeout: Duration,
+    deadline: Pin<Box<Sleep>>,
+    /// 收到完整的 endpoint 事件之前缓存的响应体
+    buffer: Vec<u8>,
+    session: Option<String>,
+    closed: bool,
+}
+
+impl IdleTimeoutStream {
+    pub fn new(inner: BoxStream<'static, Result<Bytes, axum::Error>>, timeout: Duration) -> Self {
+        Self {
+            inner,
+            timeout,
+            deadline: Box::pin(sleep_until(Instant::now() + timeout)),
+            buffer: Vec::new(),
+            session: None,
+            closed: false,
+        }
+    }
+
+    fn register_session(&mut self, chunk: &[u8]) {
+        self.buffer.extend_from_slice(chunk);
+        while let Some(end) = self.buffer.windows(2).position(|w| w == b"\n\n") {
+            let event: Vec<u8> = self.buffer.drain(..end + 2).collect();
+            if let Some(session) = endpoint_session(&String::from_utf8_lossy(&event)) {
+                let now = Instant::now();
+                session_activity().insert(session.clone(), now);
+                self.deadline.as_mut().reset(now + self.timeout);
+                self.session = Some(session);
+                self.buffer = Vec::new();
+                return;
+            }
+        }
+    }
+}
+
+impl Stream for IdleTimeoutStream {
+    type Item = Result<Bytes, axum::Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if self.closed {
+            return Poll::Ready(None);
+        }
+        match self.inner.poll_next_unpin(cx) {
+            Poll::Ready(Some(Ok(chunk))) => {
+                if self.session.is_none() {
+                    self.register_session(&chunk);
+                }
+                return Poll::Ready(Some(Ok(chunk)));
+            }
+            Poll::Ready(item) => return Poll::Ready(item),
+            Poll::Pending => {}
+        }
+
+        let this = &mut *self;
+        let Some(session) = &this.session else {
+            return Poll::Pending;
+        };
+        // 到期时按最近一次入站消息重新计算，期间有消息则顺延
+        while this.deadline.as_mut().poll(cx).is_ready() {
+            let last = session_activity()
+                .get(session)
+                .map(|last| *last)
+                .unwrap_or_else(Instant::now);
+            let deadline = last + this.timeout;
+            if deadline <= Instant::now() {
+                tracing::info!(
+                    "Closing SSE session {} idle for more than {}s",
+                    session,
+                    this.timeout.as_secs()
+                );
+                this.closed = true;
+                return Poll::Ready(None);
+            }
+            this.deadline.as_mut().reset(deadline);
+        }
+        Poll::Pending
+    }
+}
+
+impl Drop for IdleTimeoutStream {
+    fn drop(&mut self) {
+        if let Some(session) = &self.session {
+            session_activity().remove(session);
+        }
+    }
+}
+
+/// 旧版 SSE 传输的空闲超时：POST /message 刷新会话的活跃时间，
+/// 超过 server.session_idle_secs 没有入站消息的会话被关闭，防止客户端停止读取后长期占用会话
+pub async fn sse_idle_timeout(req: Request<Body>, next: Next) -> Response {
+    let Some(timeout) = session_idle_timeout() else {
+        return next.run(req).await;
+    };
+    let path = req.uri().path();
+    if req.method() == Method::POST && path == "/message" {
+        let session = req
+            .uri()
+            .query()
+            .unwrap_or_default()
+            .split('&')
+            .find_map(|pair| pair.strip_prefix("sessionId="));
+        if let Some(session) = session {
+            touch_session(session);
+        }
+        return next.run(req).await;
+    }
+
+    let is_sse =
+        req.method() == Method::GET && !path.starts_with("/api/") && path.ends_with("/sse");
+    let response = next.run(req).await;
+    let is_event_stream = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("text/event-stream"));
+    if !is_sse || !is_event_stream {
+        return response;
+    }
+
+    let (parts, body) = response.into_parts();
+    let stream = IdleTimeoutStream::new(body.into_data_stream().boxed(), timeout);
+    Response::from_parts(parts, Body::from_stream(stream))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        http::StatusCode,
+        routing::{get, post},
+        Router,
+    };
+    use futures::stream;
+    use tower::ServiceExt;
+
+    /// 先发出 endpoint 事件，之后保持打开且不再输出
+    fn sse_body(session_id: &str) -> BoxStream<'static, Result<Bytes, axum::Error>> {
+        let event = format!(
+            "event: endpoint\ndata: /message?sessionId={}\n\n",
+            session_id
+        );
+        stream::once(async move { Ok(Bytes::from(event)) })
+            .chain(stream::pending())
+            .boxed()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_idle_session_is_closed() {
+        let session = uuid::Uuid::new_v4().to_string();
+        let mut body = IdleTimeoutStream::new(sse_body(&session), Duration::from_secs(60));
+        let first = body.next().await.unwrap().unwrap();
+        assert!(String::from_utf8_lossy(&first).contains(&session));
+        assert!(session_activity().contains_key(&session));
+
+        let started = Instant::now();
+        assert!(body.next().await.is_none());
+        assert_eq!(started.elapsed(), Duration::from_secs(60));
+        assert!(body.next().await.is_none());
+        drop(body);
+        assert!(!session_activity().contains_key(&session));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_inbound_messages_extend_session() {
+        set_session_idle_timeout(60);
+        let session = uuid::Uuid::new_v4().to_string();
+        let body_session = session.clone();
+        let app = Router::new()
+            .route(
+                "/{endpoint_id}/sse",
+                get(move || {
+                    let body = sse_body(&body_session);
+                    async move {
+                        Response::builder()
+                            .header(header::CONTENT_TYPE, "text/event-stream")
+                            .body(Body::from_stream(body))
+                            .unwrap()
+                    }
+                }),
+            )
+            .route("/message", post(|| async { StatusCode::ACCEPTED }))
+            .layer(axum::middleware::from_fn(sse_idle_timeout));
+
+        let response = app
+            .clone()
+            .oneshot(Request::get("/e1/sse").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let mut body = response.into_body().into_data_stream();
+        body.next().await.unwrap().unwrap();
+
+        // 每 40 秒一条消息，会话保持打开
+        let started = Instant::now();
+        for _ in 0..3 {
+            tokio::time::sleep(Duration::from_secs(40)).await;
+            let response = app
+                .clone()
+                .oneshot(
+                    Request::post(format!("/message?sessionId={}", session))
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::ACCEPTED);
+        }
+        // 最后一条消息之后 60 秒关闭
+        assert!(body.next().await.is_none());
+        assert_eq!(started.elapsed(), Duration::from_secs(180));
+    }
+}
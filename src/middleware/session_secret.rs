@@ -0,0 +1,390 @@
+use crate::utils::{session_secrets, SecretCheck, SessionSecrets};
+use axum::{
+    body::{Body, Bytes},
+    extract::{ConnectInfo, Query},
+    http::{header, Method, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use futures::stream::{BoxStream, Stream, StreamExt};
+use serde_json::json;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::time::{interval_at, Instant, Interval, MissedTickBehavior};
+
+/// POST /message 携带会话密钥的请求头
+pub const SESSION_SECRET_HEADER: &str = "x-mcp-session-secret";
+/// 也可以作为查询参数携带，endpoint 事件下发的 url 中已包含
+pub const SESSION_SECRET_PARAM: &str = "sessionSecret";
+/// 轮换后下发新密钥的 SSE 事件
+pub const SESSION_SECRET_EVENT: &str = "session_secret";
+
+/// 在 endpoint 事件的 url 中附加会话密钥，返回会话 id 与改写后的事件
+fn rewrite_endpoint_event(event: &str, secrets: &SessionSecrets) -> Option<(String, String)> {
+    let is_endpoint = event
+        .lines()
+        .any(|line| line.strip_prefix("event:").map(str::trim) == Some("endpoint"));
+    if !is_endpoint {
+        return None;
+    }
+    let url = event
+        .lines()
+        .find_map(|line| line.strip_prefix("data:"))?
+        .trim();
+    let session_id = url
+        .split_once('?')?
+        .1
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("sessionId="))?
+        .to_string();
+    let secret = secrets.issue(&session_id);
+    let rewritten = event
+        .lines()
+        .map(|line| match line.strip_prefix("data:") {
+            Some(_) => format!("data: {}&{}={}", url, SESSION_SECRET_PARAM, secret),
+            None => line.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    Some((session_id, format!("{}\n\n", rewritten)))
+}
+
+/// 旧版 SSE 响应体：在 endpoint 事件中下发会话密钥，按配置定期轮换，连接断开后移除会话
+pub struct SessionSecretStream {
+    inner: BoxStream<'static, Result<Bytes, axum::Error>>,
+    secrets: &'static SessionSecrets,
+    /// 收到完整的 endpoint 事件之前缓存的响应体
+    buffer: Vec<u8>,
+    session: Option<String>,
+    rotation: Option<Interval>,
+}
+
+impl SessionSecretStream {
+    pub fn new(
+        inner: BoxStream<'static, Result<Bytes, axum::Error>>,
+        secrets: &'static SessionSecrets,
+    ) -> Self {
+        Self {
+            inner,
+            secrets,
+            buffer: Vec::new(),
+            session: None,
+            rotation: None,
+        }
+    }
+
+    /// 取出缓冲区中完整的事件，找到 endpoint 事件后改写并登记会话
+    fn drain_events(&mut self) -> Vec<u8> {
+        let mut output = Vec::new();
+        while let Some(end) = self.buffer.windows(2).position(|w| w == b"\n\n") {
+            let event: Vec<u8> = self.buffer.drain(..end + 2).collect();
+            let text = String::from_utf8_lossy(&event);
+            match rewrite_endpoint_event(text.trim_end(), self.secrets) {
+                Some((session, rewritten)) => {
+                    output.extend_from_slice(rewritten.as_bytes());
+                    output.append(&mut self.buffer);
+                    self.rotation = self.secrets.rotation_interval().map(|period| {
+                        let mut interval = interval_at(Instant::now() + period, period);
+                        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+                        interval
+                    });
+                    self.session = Some(session);
+                    break;
+                }
+                None => output.extend_from_slice(&event),
+            }
+        }
+        output
+    }
+}
+
+impl Stream for SessionSecretStream {
+    type Item = Result<Bytes, axum::Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            match self.inner.poll_next_unpin(cx) {
+                Poll::Ready(Some(Ok(chunk))) if self.session.is_none() => {
+                    self.buffer.extend_from_slice(&chunk);
+                    let output = self.drain_events();
+                    if !output.is_empty() {
+                        return Poll::Ready(Some(Ok(Bytes::from(output))));
+                    }
+                }
+                Poll::Ready(None) if !self.buffer.is_empty() => {
+                    let rest = std::mem::take(&mut self.buffer);
+                    return Poll::Ready(Some(Ok(Bytes::from(rest))));
+                }
+                Poll::Ready(item) => return Poll::Ready(item),
+                Poll::Pending => {
+                    let this = &mut *self;
+                    let (Some(session), Some(rotation)) = (&this.session, &mut this.rotation)
+                    else {
+                        return Poll::Pending;
+                    };
+                    if rotation.poll_tick(cx).is_pending() {
+                        return Poll::Pending;
+                    }
+                    if let Some(secret) = this.secrets.rotate(session) {
+                        let event = format!(
+                            "event: {}\ndata: {}\n\n",
+                            SESSION_SECRET_EVENT,
+                            json!({ "secret": secret })
+                        );
+                        return Poll::Ready(Some(Ok(Bytes::from(event))));
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Drop for SessionSecretStream {
+    fn drop(&mut self) {
+        if let Some(session) = &self.session {
+            self.secrets.remove(session);
+        }
+    }
+}
+
+fn rejection(status: StatusCode, message: &str) -> Response {
+    let error = rmcp::ErrorData::invalid_request(message.to_string(), None);
+    let body = json!({
+        "jsonrpc": "2.0",
+        "id": null,
+        "error": error,
+    });
+    (
+        status,
+        [(header::CONTENT_TYPE, "application/json")],
+        body.to_string(),
+    )
+        .into_response()
+}
+
+/// 校验 POST /message 的会话密钥；未登记的会话交给传输层返回会话不存在
+fn check_message_secret(req: &Request<Body>, secrets: &SessionSecrets) -> Option<Response> {
+    let Query(params) = Query::<HashMap<String, String>>::try_from_uri(req.uri()).ok()?;
+    let session_id = params.get("sessionId")?;
+    let ip = req
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|info| info.0.ip());
+    if ip.is_some_and(|ip| secrets.is_blocked(ip)) {
+        return Some(rejection(
+            StatusCode::TOO_MANY_REQUESTS,
+            "Too many failed session secret attempts",
+        ));
+    }
+    let secret = req
+        .headers()
+        .get(SESSION_SECRET_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .or_else(|| params.get(SESSION_SECRET_PARAM).map(String::as_str));
+    match secrets.verify(session_id, secret) {
+        SecretCheck::Mismatch => {
+            if let Some(ip) = ip {
+                secrets.record_failure(ip);
+            }
+            tracing::warn!(session_id = %session_id, ip = ?ip, "rejected message with invalid session secret");
+            Some(rejection(StatusCode::FORBIDDEN, "Invalid session secret"))
+        }
+        SecretCheck::Valid | SecretCheck::UnknownSession => None,
+    }
+}
+
+/// 旧版 SSE 传输的会话密钥：建立连接时下发，POST /message 时校验
+pub async fn sse_session_secret(req: Request<Body>, next: Next) -> Response {
+    let path = req.uri().path();
+    if req.method() == Method::POST && path == "/message" {
+        if let Some(rejection) = check_message_secret(&req, session_secrets()) {
+            return rejection;
+        }
+        return next.run(req).await;
+    }
+
+    let is_sse =
+        req.method() == Method::GET && !path.starts_with("/api/") && path.ends_with("/sse");
+    let response = next.run(req).await;
+    let is_event_stream = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("text/event-stream"));
+    if !is_sse || !is_event_stream {
+        return response;
+    }
+
+    let (parts, body) = response.into_parts();
+    let stream = SessionSecretStream::new(body.into_data_stream().boxed(), session_secrets());
+    Response::from_parts(parts, Body::from_stream(stream))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SseSessionConfig;
+    use axum::{
+        routing::{get, post},
+        Router,
+    };
+    use futures::stream;
+    use std::time::Duration;
+    use tower::ServiceExt;
+
+    fn app(session_id: &'static str) -> Router {
+        Router::new()
+            .route(
+                "/{endpoint_id}/sse",
+                get(move || async move {
+                    let endpoint = format!(
+                        "event: endpoint\ndata: /message?sessionId={}\n\n",
+                        session_id
+                    );
+                    let events = stream::once(async move { Ok::<_, std::io::Error>(endpoint) })
+                        .chain(stream::pending());
+                    (
+                        [(header::CONTENT_TYPE, "text/event-stream")],
+                        Body::from_stream(events),
+                    )
+                }),
+            )
+            .route("/message", post(|| async { StatusCode::ACCEPTED }))
+            .layer(axum::middleware::from_fn(sse_session_secret))
+    }
+
+    async fn post_message(
+        app: &Router,
+        uri: &str,
+        secret_header: Option<&str>,
+        ip: [u8; 4],
+    ) -> StatusCode {
+        let mut req = Request::builder().method("POST").uri(uri);
+        if let Some(secret) = secret_header {
+            req = req.header(SESSION_SECRET_HEADER, secret);
+        }
+        let mut req = req.body(Body::empty()).unwrap();
+        req.extensions_mut()
+            .insert(ConnectInfo(SocketAddr::from((ip, 40000))));
+        app.clone().oneshot(req).await.unwrap().status()
+    }
+
+    /// 建立 SSE 连接，返回响应体（保持连接）与下发的密钥
+    async fn connect(app: &Router) -> (BoxStream<'static, Result<Bytes, axum::Error>>, String) {
+        let req = Request::builder()
+            .uri("/petstore/sse")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(req).await.unwrap();
+        let mut body = response.into_body().into_data_stream().boxed();
+        let event = body.next().await.unwrap().unwrap();
+        let event = String::from_utf8(event.to_vec()).unwrap();
+        assert!(event.starts_with("event: endpoint\n"), "{}", event);
+        let secret = event
+            .trim_end()
+            .rsplit_once("sessionSecret=")
+            .unwrap()
+            .1
+            .to_string();
+        (body, secret)
+    }
+
+    #[tokio::test]
+    async fn test_message_requires_session_secret() {
+        let session = "7d2f3c1e9a8b4c5d8e7f6a5b4c3d2e1f";
+        let app = app(session);
+        let (body, secret) = connect(&app).await;
+        let uri = format!("/message?sessionId={}", session);
+        let ip = [192, 0, 2, 1];
+
+        assert_eq!(
+            post_message(&app, &uri, None, ip).await,
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            post_message(&app, &uri, Some("guessed"), ip).await,
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            post_message(&app, &uri, Some(&secret), ip).await,
+            StatusCode::ACCEPTED
+        );
+        let with_param = format!("{}&{}={}", uri, SESSION_SECRET_PARAM, secret);
+        assert_eq!(
+            post_message(&app, &with_param, None, ip).await,
+            StatusCode::ACCEPTED
+        );
+
+        // 连接断开后会话移除，交给传输层处理
+        drop(body);
+        assert_eq!(
+            session_secrets().verify(session, Some(&secret)),
+            SecretCheck::UnknownSession
+        );
+    }
+
+    #[tokio::test]
+    async fn test_failed_attempts_are_rate_limited() {
+        let session = "0b1c2d3e4f5a6b7c8d9e0f1a2b3c4d5e";
+        let app = app(session);
+        let (_body, secret) = connect(&app).await;
+        let uri = format!("/message?sessionId={}", session);
+        let attacker = [198, 51, 100, 7];
+
+        for _ in 0..SseSessionConfig::default().max_failed_attempts {
+            assert_eq!(
+                post_message(&app, &uri, Some("guessed"), attacker).await,
+                StatusCode::FORBIDDEN
+            );
+        }
+        // 超过上限后即使密钥正确也被拒绝，其他来源不受影响
+        assert_eq!(
+            post_message(&app, &uri, Some(&secret), attacker).await,
+            StatusCode::TOO_MANY_REQUESTS
+        );
+        assert_eq!(
+            post_message(&app, &uri, Some(&secret), [198, 51, 100, 8]).await,
+            StatusCode::ACCEPTED
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_rotation_pushes_new_secret() {
+        let secrets: &'static SessionSecrets =
+            Box::leak(Box::new(SessionSecrets::new(&SseSessionConfig {
+                rotation_interval_secs: 60,
+                grace_period_secs: 10,
+                ..Default::default()
+            })));
+        let events = stream::once(async {
+            Ok(Bytes::from_static(
+                b"event: endpoint\ndata: /message?sessionId=abc\n\n",
+            ))
+        })
+        .chain(stream::pending())
+        .boxed();
+        let mut stream = SessionSecretStream::new(events, secrets);
+
+        let endpoint = stream.next().await.unwrap().unwrap();
+        let endpoint = String::from_utf8(endpoint.to_vec()).unwrap();
+        let old = endpoint.trim_end().rsplit_once("sessionSecret=").unwrap().1;
+        assert_eq!(secrets.verify("abc", Some(old)), SecretCheck::Valid);
+
+        let event = stream.next().await.unwrap().unwrap();
+        let event = String::from_utf8(event.to_vec()).unwrap();
+        let data = event
+            .strip_prefix("event: session_secret\ndata: ")
+            .unwrap()
+            .trim_end();
+        let new: serde_json::Value = serde_json::from_str(data).unwrap();
+        let new = new["secret"].as_str().unwrap();
+        assert_ne!(new, old);
+        assert_eq!(secrets.verify("abc", Some(new)), SecretCheck::Valid);
+
+        tokio::time::advance(Duration::from_secs(11)).await;
+        assert_eq!(secrets.verify("abc", Some(old)), SecretCheck::Mismatch);
+        assert_eq!(secrets.verify("abc", Some(new)), SecretCheck::Valid);
+    }
+}
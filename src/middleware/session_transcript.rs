@@ -0,0 +1,353 @@
+use crate::services::{session_recorder, SessionRecorder, LIFECYCLE_CLOSED, LIFECYCLE_OPENED};
+use axum::{
+    body::{to_bytes, Body, Bytes},
+    http::{header, HeaderMap, Method, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use futures::stream::{BoxStream, Stream, StreamExt};
+use rmcp::transport::common::http_header::HEADER_SESSION_ID;
+use serde_json::{json, Value};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use uuid::Uuid;
+
+/// 需要记录的 MCP 传输请求
+#[derive(Debug, Clone, PartialEq)]
+enum TransportRoute {
+    /// POST /stream/{endpoint_id}
+    StreamPost(Uuid),
+    /// GET /stream/{endpoint_id}
+    StreamGet(Uuid),
+    /// POST /message?sessionId=
+    Message(String),
+    /// GET /{endpoint_id}/sse
+    Sse(Uuid),
+}
+
+fn transport_route(method: &Method, path: &str, query: Option<&str>) -> Option<TransportRoute> {
+    if let Some(id) = path.strip_prefix("/stream/") {
+        let id = Uuid::parse_str(id.trim_end_matches('/')).ok()?;
+        return match *method {
+            Method::POST => Some(TransportRoute::StreamPost(id)),
+            Method::GET => Some(TransportRoute::StreamGet(id)),
+            _ => None,
+        };
+    }
+    if *method == Method::POST && path == "/message" {
+        let session = query?
+            .split('&')
+            .find_map(|pair| pair.strip_prefix("sessionId="))?;
+        return Some(TransportRoute::Message(session.to_string()));
+    }
+    let id = path.strip_prefix('/')?.strip_suffix("/sse")?;
+    match *method {
+        Method::GET => Uuid::parse_str(id).ok().map(TransportRoute::Sse),
+        _ => None,
+    }
+}
+
+fn session_header(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(HEADER_SESSION_ID)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string)
+}
+
+fn is_event_stream(response: &Response) -> bool {
+    response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("text/event-stream"))
+}
+
+/// SSE 事件的 data 拼接后解析为 JSON-RPC 消息
+fn event_message(event: &[u8]) -> Option<Value> {
+    let data: String = String::from_utf8_lossy(event)
+        .lines()
+        .filter_map(|line| line.strip_prefix("data:"))
+        .map(str::trim_start)
+        .collect();
+    serde_json::from_str(&data).ok()
+}
+
+/// 旧版 SSE 的 endpoint 事件中的会话 id
+fn endpoint_event_session(event: &[u8]) -> Option<String> {
+    let text = String::from_utf8_lossy(event);
+    let is_endpoint = text
+        .lines()
+        .any(|line| line.strip_prefix("event:").map(str::trim) == Some("endpoint"));
+    if !is_endpoint {
+        return None;
+    }
+    text.lines()
+        .find_map(|line| line.strip_prefix("data:"))?
+        .trim()
+        .split_once('?')?
+        .1
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("sessionId="))
+        .map(str::to_string)
+}
+
+/// 原样转发响应体，同时按 SSE 事件记录发给客户端的消息；
+/// 旧版 SSE 连接从 endpoint 事件得到会话 id，连接断开时记录 closed
+pub struct TranscriptStream {
+    inner: BoxStream<'static, Result<Bytes, axum::Error>>,
+    recorder: &'static SessionRecorder,
+    session: Option<String>,
+    endpoint_id: Uuid,
+    /// 是否为旧版 SSE 长连接
+    legacy: bool,
+    buffer: Vec<u8>,
+}
+
+impl TranscriptStream {
+    pub fn new(
+        inner: BoxStream<'static, Result<Bytes, axum::Error>>,
+        recorder: &'static SessionRecorder,
+        session: Option<String>,
+        endpoint_id: Uuid,
+        legacy: bool,
+    ) -> Self {
+        Self {
+            inner,
+            recorder,
+            session,
+            endpoint_id,
+            legacy,
+            buffer: Vec::new(),
+        }
+    }
+
+    fn record_events(&mut self) {
+        while let Some(end) = self.buffer.windows(2).position(|w| w == b"\n\n") {
+            let event: Vec<u8> = self.buffer.drain(..end + 2).collect();
+            match &self.session {
+                Some(session) => {
+                    if let Some(message) = event_message(&event) {
+                        self.recorder
+                            .record_sent(session, Some(self.endpoint_id), &message);
+                    }
+                }
+                None if self.legacy => {
+                    self.session = endpoint_event_session(&event);
+                    if let Some(session) = &self.session {
+                        self.recorder.record_lifecycle(
+                            session,
+                            Some(self.endpoint_id),
+                            LIFECYCLE_OPENED,
+                            json!({"transport": "sse"}),
+                        );
+                    }
+                }
+                None => {}
+            }
+        }
+    }
+}
+
+impl Stream for TranscriptStream {
+    type Item = Result<Bytes, axum::Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let item = self.inner.poll_next_unpin(cx);
+        if let Poll::Ready(Some(Ok(chunk))) = &item {
+            self.buffer.extend_from_slice(chunk);
+            self.record_events();
+        }
+        item
+    }
+}
+
+impl Drop for TranscriptStream {
+    fn drop(&mut self) {
+        if let (true, Some(session)) = (self.legacy, &self.session) {
+            self.recorder.record_lifecycle(
+                session,
+                Some(self.endpoint_id),
+                LIFECYCLE_CLOSED,
+                Value::Null,
+            );
+        }
+    }
+}
+
+/// 记录 MCP 传输上收发的消息，供 /api/sessions/{id}/transcript 导出；
+/// 请求与响应原样转发
+pub async fn session_transcript(req: Request<Body>, next: Next) -> Response {
+    let Some(recorder) = session_recorder() else {
+        return next.run(req).await;
+    };
+    let Some(route) = transport_route(req.method(), req.uri().path(), req.uri().query()) else {
+        return next.run(req).await;
+    };
+
+    match route {
+        TransportRoute::StreamPost(endpoint_id) => {
+            let (parts, body) = req.into_parts();
+            let bytes = match to_bytes(body, usize::MAX).await {
+                Ok(bytes) => bytes,
+                // 保留请求体上限触发的 413，交给路由组统一改写
+                Err(e) if e.to_string().contains("length limit exceeded") => {
+                    return (StatusCode::PAYLOAD_TOO_LARGE, e.to_string()).into_response()
+                }
+                Err(e) => return (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+            };
+            let message = serde_json::from_slice::<Value>(&bytes).ok();
+            let mut session = session_header(&parts.headers);
+            // 已有会话的请求先于响应记录；initialize 在得到会话 id 后记录
+            if let (Some(session), Some(message)) = (&session, &message) {
+                recorder.record_received(session, Some(endpoint_id), message);
+            }
+            let response = next
+                .run(Request::from_parts(parts, Body::from(bytes)))
+                .await;
+            if session.is_none() {
+                session = session_header(response.headers());
+                if let (Some(session), Some(message)) = (&session, &message) {
+                    recorder.record_received(session, Some(endpoint_id), message);
+                }
+            }
+            let Some(session) = session else {
+                return response;
+            };
+            record_response(recorder, response, session, endpoint_id).await
+        }
+        TransportRoute::StreamGet(endpoint_id) => {
+            let session = session_header(req.headers());
+            let response = next.run(req).await;
+            match session {
+                Some(session) if is_event_stream(&response) => {
+                    let (parts, body) = response.into_parts();
+                    let stream = TranscriptStream::new(
+                        body.into_data_stream().boxed(),
+                        recorder,
+                        Some(session),
+                        endpoint_id,
+                        false,
+                    );
+                    Response::from_parts(parts, Body::from_stream(stream))
+                }
+                _ => response,
+            }
+        }
+        TransportRoute::Message(session) => {
+            let (parts, body) = req.into_parts();
+            let bytes = match to_bytes(body, usize::MAX).await {
+                Ok(bytes) => bytes,
+                Err(e) if e.to_string().contains("length limit exceeded") => {
+                    return (StatusCode::PAYLOAD_TOO_LARGE, e.to_string()).into_response()
+                }
+                Err(e) => return (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+            };
+            // 旧版 SSE 的响应经由长连接发出，会话所属端点以 opened 事件为准
+            if let Ok(message) = serde_json::from_slice::<Value>(&bytes) {
+                recorder.record_received(&session, None, &message);
+            }
+            next.run(Request::from_parts(parts, Body::from(bytes)))
+                .await
+        }
+        TransportRoute::Sse(endpoint_id) => {
+            let response = next.run(req).await;
+            if !is_event_stream(&response) {
+                return response;
+            }
+            let (parts, body) = response.into_parts();
+            let stream = TranscriptStream::new(
+                body.into_data_stream().boxed(),
+                recorder,
+                None,
+                endpoint_id,
+                true,
+            );
+            Response::from_parts(parts, Body::from_stream(stream))
+        }
+    }
+}
+
+/// streamable POST 的响应：SSE 按事件记录，JSON 读完后记录
+async fn record_response(
+    recorder: &'static SessionRecorder,
+    response: Response,
+    session: String,
+    endpoint_id: Uuid,
+) -> Response {
+    let event_stream = is_event_stream(&response);
+    let (parts, body) = response.into_parts();
+    if event_stream {
+        let stream = TranscriptStream::new(
+            body.into_data_stream().boxed(),
+            recorder,
+            Some(session),
+            endpoint_id,
+            false,
+        );
+        return Response::from_parts(parts, Body::from_stream(stream));
+    }
+    let bytes = match to_bytes(body, usize::MAX).await {
+        Ok(bytes) => bytes,
+        Err(e) => return (StatusCode::BAD_GATEWAY, e.to_string()).into_response(),
+    };
+    if let Ok(message) = serde_json::from_slice::<Value>(&bytes) {
+        recorder.record_sent(&session, Some(endpoint_id), &message);
+    }
+    Response::from_parts(parts, Body::from(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transport_route() {
+        let id = Uuid::new_v4();
+        let stream = format!("/stream/{}", id);
+        assert_eq!(
+            transport_route(&Method::POST, &stream, None),
+            Some(TransportRoute::StreamPost(id))
+        );
+        assert_eq!(
+            transport_route(&Method::GET, &stream, None),
+            Some(TransportRoute::StreamGet(id))
+        );
+        assert_eq!(transport_route(&Method::DELETE, &stream, None), None);
+        assert_eq!(
+            transport_route(&Method::GET, &format!("/{}/sse", id), None),
+            Some(TransportRoute::Sse(id))
+        );
+        assert_eq!(
+            transport_route(
+                &Method::POST,
+                "/message",
+                Some("sessionId=abc&sessionSecret=s")
+            ),
+            Some(TransportRoute::Message("abc".to_string()))
+        );
+        assert_eq!(transport_route(&Method::POST, "/message", None), None);
+        assert_eq!(
+            transport_route(&Method::GET, "/api/endpoint/sse", None),
+            None
+        );
+    }
+
+    #[test]
+    fn test_event_parsing() {
+        let event =
+            b"event: message\nid: 3\ndata: {\"jsonrpc\":\"2.0\",\"id\":1,\"result\":{}}\n\n";
+        assert_eq!(
+            event_message(event),
+            Some(json!({"jsonrpc": "2.0", "id": 1, "result": {}}))
+        );
+        assert_eq!(event_message(b": ping\n\n"), None);
+        assert_eq!(
+            endpoint_event_session(b"event: endpoint\ndata: /message?sessionId=abc\n\n"),
+            Some("abc".to_string())
+        );
+        assert_eq!(
+            endpoint_event_session(b"data: /message?sessionId=abc\n\n"),
+            None
+        );
+    }
+}
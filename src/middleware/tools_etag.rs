@@ -0,0 +1,110 @@
+use crate::handlers::Adapter;
+use crate::services::TOOLS_ETAG_HEADER;
+use axum::{
+    body::{to_bytes, Body},
+    extract::State,
+    http::{HeaderValue, Method, Request},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use http_body_util::LengthLimitError;
+use serde_json::Value;
+use uuid::Uuid;
+
+/// streamable HTTP 的 tools/list 响应附带 Mcp-Tools-Etag。
+/// 响应为 SSE 流，响应头先于工具列表发出，因此在转发前按同一请求计算版本；
+/// 版本由启动时创建的共享 Adapter 计算，不按请求创建
+pub async fn tools_etag(
+    State(adapter): State<Adapter>,
+    req: Request<Body>,
+    next: Next,
+) -> Response {
+    let endpoint_id = match req.method() {
+        &Method::POST => req
+            .uri()
+            .path()
+            .strip_prefix("/stream/")
+            .and_then(|id| Uuid::parse_str(id.trim_end_matches('/')).ok()),
+        _ => None,
+    };
+    let Some(endpoint_id) = endpoint_id else {
+        return next.run(req).await;
+    };
+
+    let (parts, body) = req.into_parts();
+    let bytes = match to_bytes(body, usize::MAX).await {
+        Ok(bytes) => bytes,
+        // 保留请求体上限触发的 413，交给路由组统一改写
+        Err(e) if exceeds_length_limit(&e) => {
+            return (axum::http::StatusCode::PAYLOAD_TOO_LARGE, e.to_string()).into_response()
+        }
+        Err(e) => return (axum::http::StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+    };
+    let version = if is_tools_list(&bytes) {
+        Some(adapter.request_tools_version(endpoint_id, &parts).await)
+    } else {
+        None
+    };
+
+    let mut response = next
+        .run(Request::from_parts(parts, Body::from(bytes)))
+        .await;
+    if let Some(value) = version.and_then(|v| HeaderValue::from_str(&v).ok()) {
+        response.headers_mut().insert(TOOLS_ETAG_HEADER, value);
+    }
+    response
+}
+
+/// 读取请求体的错误由路由组的 RequestBodyLimitLayer 触发，错误可能被多层包装
+fn exceeds_length_limit(error: &axum::Error) -> bool {
+    let mut source: Option<&(dyn std::error::Error + 'static)> = Some(error);
+    while let Some(e) = source {
+        if e.is::<LengthLimitError>() {
+            return true;
+        }
+        source = e.source();
+    }
+    false
+}
+
+/// 单条或批量 JSON-RPC 消息中包含 tools/list 请求
+fn is_tools_list(body: &[u8]) -> bool {
+    let is_list = |message: &Value| message["method"] == "tools/list";
+    match serde_json::from_slice::<Value>(body) {
+        Ok(Value::Array(messages)) => messages.iter().any(is_list),
+        Ok(message) => is_list(&message),
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_tools_list() {
+        assert!(is_tools_list(
+            br#"{"jsonrpc":"2.0","id":1,"method":"tools/list","params":{"_meta":{"ifVersion":"a3f9aba45f21494c"}}}"#
+        ));
+        assert!(is_tools_list(
+            br#"[{"jsonrpc":"2.0","id":1,"method":"ping"},{"jsonrpc":"2.0","id":2,"method":"tools/list"}]"#
+        ));
+        assert!(!is_tools_list(
+            br#"{"jsonrpc":"2.0","id":1,"method":"tools/call","params":{"name":"listPets"}}"#
+        ));
+        assert!(!is_tools_list(b"not json"));
+    }
+
+    #[tokio::test]
+    async fn test_length_limit_detected_through_wrapping() {
+        // 路由组的上限包在请求体内，读取时不再限制
+        let limited = Body::new(http_body_util::Limited::new(Body::from(vec![0u8; 16]), 8));
+        let error = to_bytes(limited, usize::MAX).await.unwrap_err();
+        assert!(exceeds_length_limit(&error));
+
+        let error = to_bytes(Body::from(vec![0u8; 16]), 8).await.unwrap_err();
+        assert!(exceeds_length_limit(&error));
+        let error = axum::Error::new(std::io::Error::other("connection reset"));
+        assert!(!exceeds_length_limit(&error));
+    }
+}
@@ -0,0 +1,57 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportStatus {
+    Pending,
+    Running,
+    Completed,
+    Failed,
+}
+
+impl ExportStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExportStatus::Pending => "pending",
+            ExportStatus::Running => "running",
+            ExportStatus::Completed => "completed",
+            ExportStatus::Failed => "failed",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "pending" => Some(ExportStatus::Pending),
+            "running" => Some(ExportStatus::Running),
+            "completed" => Some(ExportStatus::Completed),
+            "failed" => Some(ExportStatus::Failed),
+            _ => None,
+        }
+    }
+}
+
+/// 导出过滤条件，时间区间为 [start_time, end_time)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AnalyticsExportRequest {
+    pub start_time: Option<DateTime<Utc>>,
+    pub end_time: Option<DateTime<Utc>>,
+    /// 为空表示全部端点
+    pub endpoint_ids: Option<Vec<Uuid>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnalyticsExportJob {
+    pub id: Uuid,
+    pub status: ExportStatus,
+    pub start_time: Option<DateTime<Utc>>,
+    pub end_time: Option<DateTime<Utc>>,
+    pub endpoint_ids: Option<Vec<Uuid>>,
+    pub row_count: i32,
+    pub error_message: Option<String>,
+    /// 完成后可下载
+    pub download_url: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
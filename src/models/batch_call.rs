@@ -0,0 +1,41 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// 批量调用中的一次工具调用
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchToolCall {
+    pub tool: String,
+    #[serde(default)]
+    pub arguments: Value,
+}
+
+/// ok：正常结果；tool_error：上游状态映射为 isError 工具结果；error：调用失败
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchCallStatus {
+    Ok,
+    ToolError,
+    Error,
+}
+
+/// 与 tools/call 返回的 JSON-RPC 错误一致
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BatchCallError {
+    pub code: i32,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+}
+
+/// 按请求顺序返回的单次调用结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchToolCallResult {
+    pub index: usize,
+    pub tool: String,
+    pub status: BatchCallStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<BatchCallError>,
+    pub elapsed_ms: u64,
+}
@@ -0,0 +1,68 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// 端点金丝雀配置：percentage% 的调用路由到 base_url
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CanaryConfig {
+    pub endpoint_id: Uuid,
+    pub base_url: String,
+    /// 0-100，可带小数
+    pub percentage: f64,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct UpsertCanaryRequest {
+    pub base_url: String,
+    pub percentage: f64,
+}
+
+/// 调用实际路由到的上游版本
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CanaryVariant {
+    Primary,
+    Canary,
+}
+
+impl CanaryVariant {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CanaryVariant::Primary => "primary",
+            CanaryVariant::Canary => "canary",
+        }
+    }
+}
+
+impl std::fmt::Display for CanaryVariant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct CanaryStatsQuery {
+    /// 统计窗口，默认最近 1 小时
+    pub window_secs: Option<u64>,
+}
+
+/// 单个变体在窗口内的调用统计
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CanaryVariantStats {
+    pub variant: CanaryVariant,
+    pub requests: u64,
+    pub errors: u64,
+    pub error_rate: f64,
+    pub p95_latency_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CanaryStats {
+    pub endpoint_id: Uuid,
+    pub window_secs: u64,
+    /// 已 promote 或 abort 时为 None，统计仍按历史调用计算
+    pub config: Option<CanaryConfig>,
+    pub primary: CanaryVariantStats,
+    pub canary: CanaryVariantStats,
+}
@@ -0,0 +1,49 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+
+/// 组合工具中的一个步骤
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompositeStep {
+    /// 步骤标识，后续步骤通过 steps.<id> 引用其输出
+    pub id: String,
+    /// 调用的端点工具名
+    pub tool: String,
+    /// 参数模板，"{{ expr }}" 为 JMESPath 表达式，上下文为 {input, steps}
+    #[serde(default)]
+    pub arguments: Value,
+}
+
+/// 组合工具定义
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompositeToolDefinition {
+    pub name: String,
+    pub description: Option<String>,
+    pub steps: Vec<CompositeStep>,
+    /// 结果模板，缺省返回最后一步输出
+    pub result: Option<Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompositeTool {
+    pub id: Uuid,
+    pub endpoint_id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub steps: Vec<CompositeStep>,
+    pub result: Option<Value>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl CompositeTool {
+    pub fn definition(&self) -> CompositeToolDefinition {
+        CompositeToolDefinition {
+            name: self.name.clone(),
+            description: self.description.clone(),
+            steps: self.steps.clone(),
+            result: self.result.clone(),
+        }
+    }
+}
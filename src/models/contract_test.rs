@@ -0,0 +1,58 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+
+/// 端点契约测试清单（兼容 MCP Inspector 的用例格式）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContractTestManifest {
+    pub endpoint_id: Uuid,
+    pub endpoint_name: String,
+    pub generated_at: DateTime<Utc>,
+    pub cases: Vec<ContractTestCase>,
+}
+
+/// 单个工具的测试用例
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContractTestCase {
+    pub tool_name: String,
+    /// 根据 inputSchema 合成的最小合法参数
+    pub arguments: Value,
+    pub expect: ContractTestExpectation,
+}
+
+/// 期望的响应特征
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContractTestExpectation {
+    /// 固定为 success
+    pub status: String,
+    /// 工具定义了 output_schema 时应返回 structuredContent
+    pub structured_content: bool,
+}
+
+/// 运维人员保存的用例覆盖
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContractTestOverride {
+    pub tool_name: String,
+    /// 覆盖参数，对象类型按字段合并，否则整体替换
+    pub arguments: Option<Value>,
+    pub expect_structured_content: Option<bool>,
+    /// 跳过该工具
+    #[serde(default)]
+    pub skipped: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ContractTestFormat {
+    #[default]
+    Json,
+    /// 换行分隔的 JSON-RPC 脚本，可直接通过管道输入 stdio 传输
+    Jsonrpc,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ContractTestQuery {
+    #[serde(default)]
+    pub format: ContractTestFormat,
+}
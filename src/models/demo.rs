@@ -0,0 +1,37 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// 演示数据的种类
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DemoEntityKind {
+    Endpoint,
+    Dataset,
+    DatasetToken,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DemoEntity {
+    pub kind: DemoEntityKind,
+    pub id: Uuid,
+    pub name: String,
+}
+
+/// created 为本次新建的数据，existing 为此前已写入、本次未改动的数据
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DemoSeedResponse {
+    pub created: Vec<DemoEntity>,
+    pub existing: Vec<DemoEntity>,
+    /// 演示令牌明文，只在本次新建令牌时返回
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dataset_token: Option<String>,
+    /// 本次摄取的数据集行数
+    pub ingested_rows: u32,
+    /// 本次写入的合成调用日志条数
+    pub synthetic_logs: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DemoTeardownResponse {
+    pub removed: Vec<DemoEntity>,
+}
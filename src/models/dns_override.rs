@@ -0,0 +1,16 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// 端点的静态解析，主机名 → IP，优先于 DNS
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EndpointDnsOverrides {
+    pub endpoint_id: Uuid,
+    pub overrides: BTreeMap<String, String>,
+}
+
+/// 整体替换端点的静态解析，为空时清除
+#[derive(Debug, Clone, Deserialize)]
+pub struct PutDnsOverridesRequest {
+    pub overrides: BTreeMap<String, String>,
+}
@@ -1,9 +1,10 @@
-use crate::models::SwaggerSpec;
-use crate::utils::generate_mcp_tools;
+use crate::models::{SkippedOperation, SwaggerSpec};
+use crate::utils::{generate_mcp_tools, try_get_selected};
 use chrono::{DateTime, Utc};
 use rmcp::model::Tool;
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
+use std::collections::{BTreeMap, HashMap};
 use uuid::Uuid;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -17,6 +18,177 @@ pub struct Endpoint {
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub connection_count: i32,
+    /// 请求体首选 Content-Type，覆盖默认的 json > form > 其他
+    #[serde(default)]
+    pub preferred_content_type: Option<String>,
+    /// mock 模式：工具调用不请求上游，按响应示例或 schema 合成结果
+    #[serde(default)]
+    pub mock_mode: bool,
+    /// 覆盖 swagger servers 中的变量值，未覆盖的变量取 default
+    #[serde(default)]
+    pub server_variables: HashMap<String, String>,
+    /// 上游 4xx/5xx 响应映射为 MCP 结果的方式
+    #[serde(default)]
+    pub status_mapping: StatusMapping,
+    /// tools/list 中附带工具运行统计
+    #[serde(default)]
+    pub tool_stats: bool,
+    /// tools/list 中不列出 swagger 标记为 deprecated 的操作
+    #[serde(default)]
+    pub hide_deprecated: bool,
+    /// 分组标签，已去除首尾空白并转为小写
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// 最近一次写入 swagger 时的工具阈值告警，None 表示尚未检查
+    #[serde(default)]
+    pub tool_limit_warnings: Option<Vec<String>>,
+}
+
+/// 测试用端点：运行中、其余字段取空值，用例只覆盖关心的字段
+#[cfg(test)]
+impl Default for Endpoint {
+    fn default() -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4(),
+            name: "test-endpoint".to_string(),
+            description: None,
+            swagger_content: String::new(),
+            status: EndpointStatus::Running,
+            created_at: now,
+            updated_at: now,
+            connection_count: 0,
+            preferred_content_type: None,
+            mock_mode: false,
+            server_variables: HashMap::new(),
+            status_mapping: StatusMapping::default(),
+            tool_stats: false,
+            hide_deprecated: false,
+            tags: Vec::new(),
+            tool_limit_warnings: None,
+        }
+    }
+}
+
+/// 上游非 2xx 响应返回给 MCP 客户端的形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StatusOutcome {
+    /// 作为普通工具结果返回，由调用方检查 status
+    Result,
+    /// isError 为 true 的工具结果
+    ToolError,
+    /// JSON-RPC 错误
+    RpcError,
+}
+
+/// 上游 HTTP 状态到 MCP 结果的映射，overrides 优先于按 4xx/5xx 的默认映射
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StatusMapping {
+    pub client_error: StatusOutcome,
+    pub server_error: StatusOutcome,
+    /// 单个状态码的映射，如 404 -> result
+    pub overrides: BTreeMap<u16, StatusOutcome>,
+    /// 映射为 JSON-RPC 错误时使用的错误码
+    pub error_code: i32,
+}
+
+impl Default for StatusMapping {
+    fn default() -> Self {
+        Self {
+            client_error: StatusOutcome::ToolError,
+            server_error: StatusOutcome::RpcError,
+            overrides: BTreeMap::new(),
+            error_code: -32035,
+        }
+    }
+}
+
+impl StatusMapping {
+    pub fn outcome(&self, status: u16) -> StatusOutcome {
+        if let Some(outcome) = self.overrides.get(&status) {
+            return *outcome;
+        }
+        match status {
+            400..=499 => self.client_error,
+            500..=599 => self.server_error,
+            _ => StatusOutcome::Result,
+        }
+    }
+
+    /// overrides 中的状态码须为合法的 HTTP 状态码
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if let Some(status) = self.overrides.keys().find(|s| !(100..=599).contains(*s)) {
+            anyhow::bail!(
+                "Invalid HTTP status in status_mapping.overrides: {}",
+                status
+            );
+        }
+        Ok(())
+    }
+}
+
+/// 单个端点的标签数上限
+pub const MAX_ENDPOINT_TAGS: usize = 20;
+/// 单个标签的最大字符数
+pub const MAX_TAG_CHARS: usize = 64;
+
+/// 去除首尾空白、转为小写并去重，保留首次出现的顺序
+pub fn normalize_tags(tags: &[String]) -> anyhow::Result<Vec<String>> {
+    let mut normalized: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = tag.trim().to_lowercase();
+        if tag.is_empty() || tag.chars().count() > MAX_TAG_CHARS {
+            anyhow::bail!(
+                "Invalid tag '{}': must be 1-{} characters",
+                tag,
+                MAX_TAG_CHARS
+            );
+        }
+        if !normalized.contains(&tag) {
+            normalized.push(tag);
+        }
+    }
+    if normalized.len() > MAX_ENDPOINT_TAGS {
+        anyhow::bail!(
+            "Invalid tags: at most {} tags per endpoint",
+            MAX_ENDPOINT_TAGS
+        );
+    }
+    Ok(normalized)
+}
+
+/// 端点名称的最大字符数
+pub const MAX_ENDPOINT_NAME_CHARS: usize = 128;
+
+/// 去除首尾空白并将连续空白合并为单个空格；名称只允许字母、数字、空格与 `-`、`_`、`.`
+pub fn normalize_endpoint_name(name: &str) -> anyhow::Result<String> {
+    let normalized = name.split_whitespace().collect::<Vec<_>>().join(" ");
+    if normalized.is_empty() {
+        anyhow::bail!("Invalid endpoint name: name is required");
+    }
+    if normalized.chars().count() > MAX_ENDPOINT_NAME_CHARS {
+        anyhow::bail!(
+            "Invalid endpoint name: must be at most {} characters",
+            MAX_ENDPOINT_NAME_CHARS
+        );
+    }
+    if let Some(c) = normalized
+        .chars()
+        .find(|c| !(c.is_alphanumeric() || matches!(c, ' ' | '-' | '_' | '.')))
+    {
+        anyhow::bail!("Invalid endpoint name: character {:?} is not allowed", c);
+    }
+    Ok(normalized)
+}
+
+/// 标签列的存储值，没有标签时为 None
+pub fn tags_column(tags: &[String]) -> anyhow::Result<Option<String>> {
+    if tags.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(serde_json::to_string(tags)?))
 }
 
 impl From<&Endpoint> for Vec<Tool> {
@@ -49,6 +221,7 @@ mod uuid_as_string {
 }
 
 // Custom FromRow implementation for database compatibility
+// 按字段选择查询时未查询的列取默认值，只有 id 必须查询
 impl FromRow<'_, sqlx::mysql::MySqlRow> for Endpoint {
     fn from_row(row: &sqlx::mysql::MySqlRow) -> Result<Self, sqlx::Error> {
         use sqlx::Row;
@@ -57,27 +230,53 @@ impl FromRow<'_, sqlx::mysql::MySqlRow> for Endpoint {
         let id = Uuid::parse_str(&id_str)
             .map_err(|e| sqlx::Error::Decode(format!("Invalid UUID format: {}", e).into()))?;
 
-        let status_str: String = row.try_get("status")?;
-        let status = match status_str.as_str() {
-            "running" => EndpointStatus::Running,
-            "stopped" => EndpointStatus::Stopped,
-            "deleted" => EndpointStatus::Deleted,
-            _ => {
+        let status_str: Option<String> = try_get_selected(row, "status")?;
+        let status = match status_str.as_deref() {
+            Some("running") => EndpointStatus::Running,
+            Some("stopped") | None => EndpointStatus::Stopped,
+            Some("deleted") => EndpointStatus::Deleted,
+            Some(other) => {
                 return Err(sqlx::Error::Decode(
-                    format!("Invalid status: {}", status_str).into(),
+                    format!("Invalid status: {}", other).into(),
                 ))
             }
         };
+        let json_column = |column: &str| -> Result<Option<String>, sqlx::Error> {
+            Ok(try_get_selected::<Option<String>>(row, column)?.flatten())
+        };
 
         Ok(Self {
             id,
-            name: row.try_get("name")?,
-            description: row.try_get("description")?,
-            swagger_content: row.try_get("swagger_content")?,
+            name: try_get_selected(row, "name")?.unwrap_or_default(),
+            description: try_get_selected(row, "description")?.flatten(),
+            swagger_content: try_get_selected(row, "swagger_content")?.unwrap_or_default(),
             status,
-            created_at: row.try_get("created_at")?,
-            updated_at: row.try_get("updated_at")?,
-            connection_count: row.try_get("connection_count")?,
+            created_at: try_get_selected(row, "created_at")?.unwrap_or_default(),
+            updated_at: try_get_selected(row, "updated_at")?.unwrap_or_default(),
+            connection_count: try_get_selected(row, "connection_count")?.unwrap_or_default(),
+            preferred_content_type: try_get_selected(row, "preferred_content_type")?.flatten(),
+            mock_mode: try_get_selected(row, "mock_mode")?.unwrap_or_default(),
+            server_variables: json_column("server_variables")?
+                .map(|vars| serde_json::from_str(&vars))
+                .transpose()
+                .map_err(|e| sqlx::Error::Decode(e.into()))?
+                .unwrap_or_default(),
+            status_mapping: json_column("status_mapping")?
+                .map(|mapping| serde_json::from_str(&mapping))
+                .transpose()
+                .map_err(|e| sqlx::Error::Decode(e.into()))?
+                .unwrap_or_default(),
+            tool_stats: try_get_selected(row, "tool_stats")?.unwrap_or_default(),
+            hide_deprecated: try_get_selected(row, "hide_deprecated")?.unwrap_or_default(),
+            tags: json_column("tags")?
+                .map(|tags| serde_json::from_str(&tags))
+                .transpose()
+                .map_err(|e| sqlx::Error::Decode(e.into()))?
+                .unwrap_or_default(),
+            tool_limit_warnings: json_column("tool_limit_warnings")?
+                .map(|warnings| serde_json::from_str(&warnings))
+                .transpose()
+                .map_err(|e| sqlx::Error::Decode(e.into()))?,
         })
     }
 }
@@ -95,6 +294,9 @@ pub struct CreateEndpointRequest {
     pub name: String,
     pub description: Option<String>,
     pub swagger_content: String,
+    /// 分组标签，同名端点合并时取并集
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -103,6 +305,30 @@ pub struct UpdateEndpointRequest {
     pub description: Option<String>,
     pub swagger_content: Option<String>,
     pub status: Option<EndpointStatus>,
+    /// 空字符串表示清除
+    #[serde(default)]
+    pub preferred_content_type: Option<String>,
+    /// 运行时切换 mock 模式
+    #[serde(default)]
+    pub mock_mode: Option<bool>,
+    /// 覆盖 swagger servers 中的变量值，空对象表示清除
+    #[serde(default)]
+    pub server_variables: Option<HashMap<String, String>>,
+    /// 上游 4xx/5xx 的映射方式，整体替换已有映射
+    #[serde(default)]
+    pub status_mapping: Option<StatusMapping>,
+    /// 开启或关闭 tools/list 中的工具运行统计
+    #[serde(default)]
+    pub tool_stats: Option<bool>,
+    /// 开启或关闭 tools/list 中废弃操作的隐藏
+    #[serde(default)]
+    pub hide_deprecated: Option<bool>,
+    /// 整体替换标签，空数组表示清除
+    #[serde(default)]
+    pub tags: Option<Vec<String>>,
+    /// 强制重新向量化全部接口，默认只重新向量化文本有变化的接口
+    #[serde(default)]
+    pub force_embeddings: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -114,6 +340,39 @@ pub struct EndpointResponse {
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub connection_count: i32,
+    pub preferred_content_type: Option<String>,
+    pub mock_mode: bool,
+    pub server_variables: HashMap<String, String>,
+    pub status_mapping: StatusMapping,
+    pub tool_stats: bool,
+    pub hide_deprecated: bool,
+    pub tags: Vec<String>,
+    /// 最近一次写入 swagger 时工具数量/体积超过软阈值的告警
+    #[serde(default)]
+    pub warnings: Vec<String>,
+    /// 创建或合并时因不合法而跳过的操作
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skipped_operations: Vec<SkippedOperation>,
+}
+
+/// 端点预热结果：Degraded 表示已可用但部分步骤失败（如健康探测），Failed 表示 swagger 无法解析
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "warmup_status", rename_all = "lowercase")]
+pub enum WarmupStatus {
+    Ok,
+    Degraded,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EndpointWarmup {
+    pub status: WarmupStatus,
+    pub duration_ms: u64,
+    /// 预生成的工具数
+    pub tools: usize,
+    pub errors: Vec<String>,
+    pub warmed_at: DateTime<Utc>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -125,12 +384,71 @@ pub struct EndpointDetailResponse {
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub connection_count: i32,
+    pub preferred_content_type: Option<String>,
+    pub mock_mode: bool,
+    pub server_variables: HashMap<String, String>,
+    pub status_mapping: StatusMapping,
+    pub tool_stats: bool,
+    pub hide_deprecated: bool,
+    pub tags: Vec<String>,
     pub swagger_spec: serde_json::Value,
     pub mcp_config: McpConfig,
     pub api_details: Vec<ApiDetail>,
+    pub webhooks: Vec<WebhookDetail>,
+    /// 工具数量/体积超过软阈值的告警
+    pub warnings: Vec<String>,
+    /// 因不合法而未生成工具的操作
+    pub skipped_operations: Vec<SkippedOperation>,
+    /// 最近一次启动预热结果，未预热过为 None
+    pub warmup: Option<EndpointWarmup>,
     pub base_url: Option<String>,
 }
 
+/// 端点列表 fields 参数可选的字段
+pub const ENDPOINT_LIST_FIELDS: &[&str] = &[
+    "id",
+    "name",
+    "description",
+    "status",
+    "created_at",
+    "updated_at",
+    "connection_count",
+    "preferred_content_type",
+    "mock_mode",
+    "server_variables",
+    "status_mapping",
+    "tool_stats",
+    "hide_deprecated",
+    "tags",
+    "warnings",
+];
+
+/// 端点详情 fields 参数可选的字段
+pub const ENDPOINT_DETAIL_FIELDS: &[&str] = &[
+    "id",
+    "name",
+    "description",
+    "status",
+    "created_at",
+    "updated_at",
+    "connection_count",
+    "preferred_content_type",
+    "mock_mode",
+    "server_variables",
+    "status_mapping",
+    "tool_stats",
+    "hide_deprecated",
+    "tags",
+    "swagger_spec",
+    "mcp_config",
+    "api_details",
+    "webhooks",
+    "warnings",
+    "skipped_operations",
+    "warmup",
+    "base_url",
+];
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct McpConfig {
     pub server_name: String,
@@ -151,6 +469,21 @@ pub struct ApiDetail {
     pub request_body_schema: Option<serde_json::Value>,
     pub response_schema: Option<serde_json::Value>,
     pub responses: serde_json::Value,
+    /// 操作上声明的回调名称
+    pub callbacks: Vec<String>,
+    /// 最新的运维备注
+    pub notes: Option<String>,
+}
+
+/// OpenAPI 3.1 webhook 定义（只读）
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WebhookDetail {
+    pub name: String,
+    pub method: String,
+    pub summary: Option<String>,
+    pub description: Option<String>,
+    pub request_body_schema: Option<serde_json::Value>,
+    pub uri: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -162,6 +495,14 @@ pub struct ApiParameter {
     pub schema: Option<serde_json::Value>,
 }
 
+/// 存有工具阈值告警的端点
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolLimitAlert {
+    pub endpoint_id: Uuid,
+    pub endpoint_name: String,
+    pub warnings: Vec<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct EndpointMetrics {
     pub endpoint_id: Uuid,
@@ -171,6 +512,8 @@ pub struct EndpointMetrics {
     pub avg_response_time: f64,
     pub current_connections: i32,
     pub total_connection_time: u64,
+    /// mock 模式下的调用数，不计入 request/response/error
+    pub mock_count: u64,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -193,6 +536,18 @@ pub struct EndpointQueryParams {
     pub page_size: Option<u32>,
     pub search: Option<String>,
     pub status: Option<String>,
+    /// 只返回该时间之后更新的端点，用于增量同步
+    pub updated_after: Option<DateTime<Utc>>,
+    /// 只返回带有该标签的端点
+    pub tag: Option<String>,
+    /// 逗号分隔的返回字段，可选值见 ENDPOINT_LIST_FIELDS
+    pub fields: Option<String>,
+}
+
+/// 批量导出格式，默认 ndjson（末行为汇总），json 为单个数组
+#[derive(Debug, Default, Deserialize)]
+pub struct EndpointExportQuery {
+    pub format: Option<String>,
 }
 
 impl From<Endpoint> for EndpointResponse {
@@ -205,6 +560,15 @@ impl From<Endpoint> for EndpointResponse {
             created_at: endpoint.created_at,
             updated_at: endpoint.updated_at,
             connection_count: endpoint.connection_count,
+            preferred_content_type: endpoint.preferred_content_type,
+            mock_mode: endpoint.mock_mode,
+            server_variables: endpoint.server_variables,
+            status_mapping: endpoint.status_mapping,
+            tool_stats: endpoint.tool_stats,
+            hide_deprecated: endpoint.hide_deprecated,
+            tags: endpoint.tags,
+            warnings: endpoint.tool_limit_warnings.unwrap_or_default(),
+            skipped_operations: Vec::new(),
         }
     }
 }
@@ -0,0 +1,116 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// 批量启停的筛选条件，均为空时作用于全部未删除端点
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct EndpointBulkFilter {
+    /// running 或 stopped
+    pub status: Option<String>,
+    pub tag: Option<String>,
+    /// 端点名称，支持 * 通配，如 "pet*"
+    pub name: Option<String>,
+}
+
+/// 端点生命周期操作：demote 为启动时对账将不可用的运行中端点降级为 stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LifecycleAction {
+    Start,
+    Stop,
+    Demote,
+}
+
+impl LifecycleAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LifecycleAction::Start => "start",
+            LifecycleAction::Stop => "stop",
+            LifecycleAction::Demote => "demote",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "start" => Some(LifecycleAction::Start),
+            "stop" => Some(LifecycleAction::Stop),
+            "demote" => Some(LifecycleAction::Demote),
+            _ => None,
+        }
+    }
+}
+
+/// ok：状态已变更；skipped：已处于目标状态；failed：校验或执行失败，原因见 reason
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LifecycleOutcome {
+    Ok,
+    Skipped,
+    Failed,
+}
+
+impl LifecycleOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LifecycleOutcome::Ok => "ok",
+            LifecycleOutcome::Skipped => "skipped",
+            LifecycleOutcome::Failed => "failed",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "ok" => Some(LifecycleOutcome::Ok),
+            "skipped" => Some(LifecycleOutcome::Skipped),
+            "failed" => Some(LifecycleOutcome::Failed),
+            _ => None,
+        }
+    }
+}
+
+/// 批量操作中单个端点的结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkLifecycleResult {
+    pub id: Uuid,
+    pub name: String,
+    pub outcome: LifecycleOutcome,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkLifecycleResponse {
+    pub action: LifecycleAction,
+    pub matched: usize,
+    pub succeeded: usize,
+    pub skipped: usize,
+    pub failed: usize,
+    pub results: Vec<BulkLifecycleResult>,
+}
+
+impl BulkLifecycleResponse {
+    pub fn new(action: LifecycleAction, results: Vec<BulkLifecycleResult>) -> Self {
+        let count = |outcome| results.iter().filter(|r| r.outcome == outcome).count();
+        Self {
+            action,
+            matched: results.len(),
+            succeeded: count(LifecycleOutcome::Ok),
+            skipped: count(LifecycleOutcome::Skipped),
+            failed: count(LifecycleOutcome::Failed),
+            results,
+        }
+    }
+}
+
+/// 生命周期审计记录，端点删除后仍保留；source 为 bulk 或 reconcile
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EndpointLifecycleEvent {
+    pub id: u64,
+    pub endpoint_id: Uuid,
+    pub endpoint_name: String,
+    pub action: LifecycleAction,
+    pub source: String,
+    pub outcome: LifecycleOutcome,
+    pub reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
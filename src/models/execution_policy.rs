@@ -0,0 +1,57 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use uuid::Uuid;
+
+/// 策略来源，解析顺序：端点 -> API key -> 会话，生效策略为各层交集
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PolicySource {
+    Endpoint,
+    ApiKey,
+    Session,
+}
+
+impl PolicySource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PolicySource::Endpoint => "endpoint",
+            PolicySource::ApiKey => "api_key",
+            PolicySource::Session => "session",
+        }
+    }
+}
+
+impl std::fmt::Display for PolicySource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// 单层策略允许的方法集合（大写）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PolicyLayer {
+    pub source: PolicySource,
+    pub allowed_methods: BTreeSet<String>,
+}
+
+/// 端点级执行策略，allowed_methods 为空表示不限制
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EndpointExecutionPolicy {
+    #[serde(default)]
+    pub allowed_methods: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct EffectivePolicyQuery {
+    pub session: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EffectivePolicyResponse {
+    pub endpoint_id: Uuid,
+    pub session: Option<String>,
+    pub resolution_order: Vec<PolicySource>,
+    pub layers: Vec<PolicyLayer>,
+    /// 各层交集，None 表示不限制
+    pub allowed_methods: Option<BTreeSet<String>>,
+}
@@ -0,0 +1,69 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// 注入的故障类型
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FaultKind {
+    /// 调用前延迟，min_ms 与 max_ms 相等时为固定延迟
+    Latency { min_ms: u64, max_ms: u64 },
+    /// 以指定 JSON-RPC 错误码返回
+    Error { code: i32, message: String },
+    /// 结果文本截断到 max_bytes
+    Truncate { max_bytes: usize },
+    /// 丢弃结果事件，客户端收不到响应
+    DropEvent,
+    /// 会话内第 after_calls 次调用后终止会话
+    TerminateSession { after_calls: u32 },
+}
+
+impl FaultKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FaultKind::Latency { .. } => "latency",
+            FaultKind::Error { .. } => "error",
+            FaultKind::Truncate { .. } => "truncate",
+            FaultKind::DropEvent => "drop_event",
+            FaultKind::TerminateSession { .. } => "terminate_session",
+        }
+    }
+}
+
+fn default_percentage() -> u8 {
+    100
+}
+
+/// 故障作用范围与存活时间；tool 为空时作用于端点的全部工具
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FaultSpec {
+    pub endpoint_id: Uuid,
+    #[serde(default)]
+    pub tool: Option<String>,
+    #[serde(flatten)]
+    pub kind: FaultKind,
+    /// 到期后自动失效
+    pub ttl_secs: u64,
+    /// 命中的调用中注入的比例
+    #[serde(default = "default_percentage")]
+    pub percentage: u8,
+}
+
+/// 生效中的故障
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ActiveFault {
+    pub id: Uuid,
+    #[serde(flatten)]
+    pub spec: FaultSpec,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    /// 已注入次数
+    pub injected: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FaultList {
+    /// 进程启动时是否开启了故障注入
+    pub enabled: bool,
+    pub faults: Vec<ActiveFault>,
+}
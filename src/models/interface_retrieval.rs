@@ -1,7 +1,7 @@
 use crate::models::endpoint::ApiDetail;
 use crate::services::Filter;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use utoipa::ToSchema;
 
 /// 接口节点 - 表示一个API接口，基于ApiDetail结构设计
@@ -143,6 +143,9 @@ pub struct InterfaceWithScore {
     pub score: f64,
     /// 匹配原因说明
     pub match_reason: String,
+    /// 关键词命中的高亮片段
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub highlights: Vec<String>,
 }
 
 /// 错误类型
@@ -177,7 +180,7 @@ pub struct InterfaceSearchRequest {
     pub max_results: u32,
     /// 向量搜索相似度阈值（0.0-1.0）
     pub similarity_threshold: Option<f32>,
-    /// 向量搜索权重（0.0-1.0），用于混合搜索
+    /// 向量搜索权重（0.0-1.0），用于混合搜索；未指定时使用 embedding.default_vector_weight
     pub vector_weight: Option<f32>,
     /// 过滤条件
     pub filters: Option<Filter>,
@@ -194,4 +197,170 @@ pub struct InterfaceSearchResponse {
     pub total_count: u32,
     /// 搜索模式
     pub search_mode: String,
+    /// 向量化服务不可用，已降级为关键词搜索
+    #[serde(default)]
+    pub degraded: bool,
+    /// 结果集的标签/方法/领域分面计数
+    #[serde(default)]
+    pub facets: SearchFacets,
+    /// 本次检索标识，tools/call 通过 _meta._search_id 回传以记录反馈
+    #[serde(default)]
+    pub search_id: String,
+    /// 尚未索引任何接口时的提示
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
+}
+
+/// 搜索结果分面计数
+#[derive(Debug, Default, Serialize, Deserialize, ToSchema)]
+pub struct SearchFacets {
+    pub tags: BTreeMap<String, u32>,
+    pub methods: BTreeMap<String, u32>,
+    pub domains: BTreeMap<String, u32>,
+}
+
+impl SearchFacets {
+    pub fn from_interfaces<'a>(interfaces: impl IntoIterator<Item = &'a ApiInterface>) -> Self {
+        let mut facets = Self::default();
+        for interface in interfaces {
+            for tag in &interface.tags {
+                *facets.tags.entry(tag.clone()).or_default() += 1;
+            }
+            *facets
+                .methods
+                .entry(interface.method.to_uppercase())
+                .or_default() += 1;
+            if let Some(domain) = &interface.domain {
+                *facets.domains.entry(domain.clone()).or_default() += 1;
+            }
+        }
+        facets
+    }
+}
+
+/// 项目接口目录中的一项，不含参数明细与向量
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct InterfaceSummary {
+    pub path: String,
+    pub method: String,
+    pub summary: Option<String>,
+    pub operation_id: Option<String>,
+    pub tags: Vec<String>,
+    pub domain: Option<String>,
+    pub deprecated: bool,
+}
+
+impl From<ApiInterface> for InterfaceSummary {
+    fn from(interface: ApiInterface) -> Self {
+        Self {
+            path: interface.path,
+            method: interface.method,
+            summary: interface.summary,
+            operation_id: interface.operation_id,
+            tags: interface.tags,
+            domain: interface.domain,
+            deprecated: interface.deprecated,
+        }
+    }
+}
+
+/// 项目接口分页查询参数，limit 默认 50、最大 500
+#[derive(Debug, Default, Deserialize)]
+pub struct InterfaceListQuery {
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+}
+
+/// 项目接口分页结果，按 path、method 排序
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct InterfaceListResponse {
+    pub project_id: String,
+    pub interfaces: Vec<InterfaceSummary>,
+    pub limit: u32,
+    pub offset: u32,
+    /// 之后是否还有接口
+    pub has_more: bool,
+    /// 尚未索引任何接口时的提示
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
+}
+
+/// 端点摘要文档，每个端点一条：服务标题、描述、前若干个操作摘要与标签
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EndpointSummaryDoc {
+    pub endpoint_id: String,
+    /// 端点名称，与接口文档的 project_id 相同
+    pub slug: String,
+    /// running / stopped
+    pub status: String,
+    pub text: String,
+}
+
+/// 端点级检索命中
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct EndpointMatch {
+    pub endpoint_id: String,
+    pub slug: String,
+    pub status: String,
+    /// 命中的摘要文本
+    pub summary: String,
+    pub score: f64,
+}
+
+/// 端点级检索参数，max_results 默认 5、最大 50
+#[derive(Debug, Default, Deserialize)]
+pub struct EndpointSearchQuery {
+    pub query: String,
+    pub max_results: Option<u32>,
+    /// 只返回运行中的端点
+    #[serde(default)]
+    pub only_running: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn interface(method: &str, tags: &[&str], domain: Option<&str>) -> ApiInterface {
+        ApiInterface {
+            path: "/users".to_string(),
+            method: method.to_string(),
+            summary: None,
+            description: None,
+            operation_id: None,
+            path_params: vec![],
+            query_params: vec![],
+            header_params: vec![],
+            body_params: vec![],
+            request_schema: None,
+            response_schema: None,
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            domain: domain.map(str::to_string),
+            deprecated: false,
+            service_description: None,
+            embedding: None,
+            embedding_model: None,
+            embedding_updated_at: None,
+        }
+    }
+
+    #[test]
+    fn test_facet_counts() {
+        let interfaces = vec![
+            interface("GET", &["user", "admin"], Some("user")),
+            interface("post", &["user"], Some("user")),
+            interface("GET", &["order"], Some("order")),
+            interface("DELETE", &[], None),
+        ];
+        let facets = SearchFacets::from_interfaces(&interfaces);
+
+        assert_eq!(facets.tags["user"], 2);
+        assert_eq!(facets.tags["admin"], 1);
+        assert_eq!(facets.tags["order"], 1);
+        assert_eq!(facets.methods["GET"], 2);
+        assert_eq!(facets.methods["POST"], 1);
+        assert_eq!(facets.methods["DELETE"], 1);
+        assert_eq!(facets.domains["user"], 2);
+        assert_eq!(facets.domains.len(), 2);
+    }
 }
@@ -0,0 +1,92 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+
+use super::endpoint::PaginationInfo;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    Pending,
+    Running,
+    Completed,
+    /// 重试次数用尽，需手动重试
+    Failed,
+}
+
+impl JobStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobStatus::Pending => "pending",
+            JobStatus::Running => "running",
+            JobStatus::Completed => "completed",
+            JobStatus::Failed => "failed",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "pending" => Some(JobStatus::Pending),
+            "running" => Some(JobStatus::Running),
+            "completed" => Some(JobStatus::Completed),
+            "failed" => Some(JobStatus::Failed),
+            _ => None,
+        }
+    }
+}
+
+/// 持久化后台任务
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Job {
+    pub id: Uuid,
+    pub job_type: String,
+    pub payload: Value,
+    pub status: JobStatus,
+    /// 已执行次数，含崩溃后被重新认领的执行
+    pub attempts: u32,
+    pub next_run_at: DateTime<Utc>,
+    pub last_error: Option<String>,
+    /// 认领任务的 worker，运行中通过心跳刷新 locked_at
+    pub locked_by: Option<String>,
+    pub locked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct JobQueryParams {
+    #[serde(rename = "type")]
+    pub job_type: Option<String>,
+    pub status: Option<String>,
+    pub page: Option<u32>,
+    pub page_size: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PaginatedJobsResponse {
+    pub jobs: Vec<Job>,
+    pub pagination: PaginationInfo,
+}
+
+/// 端点删除后尚未完成的外部清理，由 endpoint_vector_cleanup 任务执行
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CleanupOutboxItem {
+    /// 任务 id，可用于 /api/jobs/{id}/retry
+    pub id: Uuid,
+    pub endpoint_id: Uuid,
+    pub endpoint_name: String,
+    pub action: String,
+    pub status: JobStatus,
+    pub attempts: u32,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// 只有 pending 与 failed 的任务可手动重试
+#[derive(Debug, thiserror::Error)]
+#[error("Job {id} is {} and cannot be retried", status.as_str())]
+pub struct JobNotRetryable {
+    pub id: Uuid,
+    pub status: JobStatus,
+}
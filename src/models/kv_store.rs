@@ -0,0 +1,37 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+
+/// 端点键值存储中的一条记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KvEntry {
+    pub endpoint_id: Uuid,
+    pub namespace: String,
+    pub key: String,
+    pub value: Value,
+    /// 为空表示不过期
+    pub expires_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// 端点级键值存储设置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct KvSettings {
+    /// 是否在 tools/list 中暴露 kv_set / kv_get / kv_delete / kv_list
+    pub enabled: bool,
+}
+
+/// 运维写入一条记录，ttl_secs 为空时使用默认过期时间
+#[derive(Debug, Clone, Deserialize)]
+pub struct PutKvEntryRequest {
+    pub value: Value,
+    pub ttl_secs: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct KvEntryQuery {
+    pub namespace: Option<String>,
+    pub prefix: Option<String>,
+}
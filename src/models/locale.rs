@@ -0,0 +1,20 @@
+use serde::{Deserialize, Serialize};
+
+/// 端点级语言转发设置，未配置的端点转发会话语言且没有默认语言
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LocaleSettings {
+    /// 为 false 时不向上游发送 Accept-Language
+    pub forward: bool,
+    /// 调用与会话都未指定语言时使用，如 "zh-CN"
+    pub default_locale: Option<String>,
+}
+
+impl Default for LocaleSettings {
+    fn default() -> Self {
+        Self {
+            forward: true,
+            default_locale: None,
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// 单个快照窗口内的指标增量
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetricsSnapshot {
+    pub endpoint_id: Uuid,
+    pub snapshot_at: DateTime<Utc>,
+    pub window_secs: u32,
+    pub request_delta: u64,
+    pub response_delta: u64,
+    pub error_delta: u64,
+    /// 窗口内响应的平均耗时
+    pub avg_latency_ms: f64,
+    /// 窗口内观察到的最大并发连接数
+    pub max_connections: i32,
+}
+
+/// 历史查询参数，时间区间为 [from, to)，默认最近 24 小时、按小时聚合
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct MetricsHistoryQuery {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub resolution_secs: Option<i64>,
+}
+
+/// 按分辨率聚合后的数据点，bucket_start 为桶起始时间
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetricsHistoryPoint {
+    pub bucket_start: DateTime<Utc>,
+    pub request_count: u64,
+    pub response_count: u64,
+    pub error_count: u64,
+    pub error_rate: f64,
+    /// 按响应数加权的平均耗时
+    pub avg_latency_ms: f64,
+    pub max_connections: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetricsHistoryResponse {
+    pub endpoint_id: Uuid,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub resolution_secs: i64,
+    pub points: Vec<MetricsHistoryPoint>,
+}
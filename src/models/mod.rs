@@ -1,10 +1,50 @@
+pub mod analytics_export;
+pub mod batch_call;
+pub mod canary;
+pub mod composite_tool;
+pub mod contract_test;
 pub mod database;
+pub mod demo;
+pub mod dns_override;
 pub mod endpoint;
+pub mod endpoint_lifecycle;
+pub mod execution_policy;
+pub mod fault;
 pub mod interface_retrieval;
+pub mod job;
+pub mod kv_store;
+pub mod locale;
+pub mod metrics_history;
+pub mod operation_note;
+pub mod plugin;
+pub mod recording;
+pub mod search_feedback;
+pub mod session_transcript;
 pub mod swagger;
 pub mod table_rag;
+pub mod usage_report;
 
+pub use analytics_export::*;
+pub use batch_call::*;
+pub use canary::*;
+pub use composite_tool::*;
+pub use contract_test::*;
 pub use database::*;
-pub use endpoint::{Endpoint, EndpointStatus, CreateEndpointRequest, UpdateEndpointRequest, EndpointResponse, EndpointDetailResponse, PaginatedEndpointsResponse, EndpointQueryParams};
+pub use demo::*;
+pub use dns_override::*;
+pub use execution_policy::*;
+pub use fault::*;
+pub use job::*;
+pub use kv_store::*;
+pub use locale::*;
+pub use endpoint::{Endpoint, EndpointStatus, CreateEndpointRequest, UpdateEndpointRequest, EndpointResponse, EndpointDetailResponse, PaginatedEndpointsResponse, EndpointQueryParams, EndpointExportQuery, EndpointWarmup, WarmupStatus, StatusMapping, StatusOutcome};
+pub use endpoint_lifecycle::*;
+pub use metrics_history::*;
+pub use operation_note::*;
+pub use plugin::*;
+pub use recording::*;
+pub use search_feedback::*;
+pub use session_transcript::*;
 pub use swagger::*;
-pub use table_rag::{Dataset, DatasetType, ColumnType, ColumnSchema, FileMeta, DatasetFileMap, IngestTask, TaskStatus, CreateDatasetRequest, UpdateDatasetRequest, DatasetResponse, DatasetDetailResponse, PaginatedDatasetsResponse};
+pub use table_rag::{Dataset, DatasetType, ColumnType, ColumnSchema, FileMeta, DatasetFileMap, IngestTask, TaskStatus, CreateDatasetRequest, UpdateDatasetRequest, DatasetResponse, DatasetDetailResponse, DatasetStats, PaginatedDatasetsResponse, DatasetFileRemoval, IngestInProgress};
+pub use usage_report::*;
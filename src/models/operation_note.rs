@@ -0,0 +1,41 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// 操作级运维备注
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OperationNote {
+    pub id: Uuid,
+    pub endpoint_id: Uuid,
+    pub method: String,
+    pub path: String,
+    /// markdown 文本
+    pub note: String,
+    pub author: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    /// 对应操作已不在当前 spec 中
+    #[serde(default)]
+    pub orphaned: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateOperationNoteRequest {
+    pub method: String,
+    pub path: String,
+    pub note: String,
+    pub author: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateOperationNoteRequest {
+    pub note: String,
+    pub author: Option<String>,
+}
+
+/// 端点级备注设置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OperationNoteSettings {
+    /// 是否将最新备注追加到工具描述
+    pub propagate_to_tools: bool,
+}
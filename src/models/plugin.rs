@@ -0,0 +1,59 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+
+/// 端点当前生效的 WASM 插件
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EndpointPlugin {
+    pub endpoint_id: Uuid,
+    pub file_id: Uuid,
+    /// 每次上传递增
+    pub version: u32,
+    pub sha256: String,
+    pub size: i64,
+    /// 模块导出了 transform_request
+    pub transform_request: bool,
+    /// 模块导出了 transform_response
+    pub transform_response: bool,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// 插件钩子，对应模块导出的函数
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PluginHook {
+    Request,
+    Response,
+}
+
+impl PluginHook {
+    pub fn export_name(&self) -> &'static str {
+        match self {
+            PluginHook::Request => "transform_request",
+            PluginHook::Response => "transform_response",
+        }
+    }
+}
+
+impl std::fmt::Display for PluginHook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.export_name())
+    }
+}
+
+/// 用样例数据试运行插件
+#[derive(Debug, Clone, Deserialize)]
+pub struct TestPluginRequest {
+    pub hook: PluginHook,
+    pub payload: Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestPluginResponse {
+    pub version: u32,
+    pub sha256: String,
+    pub output: Value,
+    pub fuel_consumed: u64,
+    pub elapsed_ms: u64,
+}
@@ -0,0 +1,53 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// 一次经 http_request 工具转发的调用，请求头不记录
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedCall {
+    pub method: String,
+    /// 实际请求路径，不含查询串
+    pub path: String,
+    #[serde(default)]
+    pub query: Map<String, Value>,
+    /// 仅记录 JSON 请求体，超过体积上限时为 None
+    pub request_body: Option<Value>,
+    pub status: u16,
+    /// 仅记录 JSON 响应体，超过体积上限时为 None
+    pub response_body: Option<Value>,
+}
+
+/// 创建录制模式端点：只有 base_url，没有 swagger
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateRecordingEndpointRequest {
+    pub name: String,
+    pub description: Option<String>,
+    pub base_url: String,
+}
+
+/// 合成出的单个操作
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SynthesizedOperation {
+    pub method: String,
+    /// 路径模板，如 /users/{userId}
+    pub path: String,
+    pub operation_id: String,
+    /// 参与合成的录制条数
+    pub samples: usize,
+}
+
+/// OpenAPI 草稿及预检结果，供运维审核后再提升为正式 swagger
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SynthesizedSpecResponse {
+    pub endpoint_id: uuid::Uuid,
+    pub recording_count: usize,
+    pub operations: Vec<SynthesizedOperation>,
+    /// 草稿生成的工具名
+    pub tools: Vec<String>,
+    pub draft: Value,
+}
+
+/// 提升草稿为端点正式 swagger；未提供时按当前录制重新合成
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct PromoteSpecRequest {
+    pub swagger_content: Option<String>,
+}
@@ -0,0 +1,77 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// 检索返回的单个接口及其排名（从 1 开始）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeedbackResult {
+    pub rank: u32,
+    pub project_id: Option<String>,
+    pub method: String,
+    pub path: String,
+    pub score: f64,
+}
+
+/// 一次检索及其后实际调用的接口
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchFeedback {
+    pub search_id: String,
+    pub project_id: Option<String>,
+    pub query: String,
+    pub results: Vec<FeedbackResult>,
+    pub searched_at: DateTime<Utc>,
+    pub chosen_tool: Option<String>,
+    pub chosen_method: Option<String>,
+    pub chosen_path: Option<String>,
+    /// 实际调用的接口在检索结果中的排名，不在结果中为 None
+    pub chosen_rank: Option<u32>,
+    pub chosen_at: Option<DateTime<Utc>>,
+    /// 从检索到调用的间隔
+    pub latency_ms: Option<i64>,
+}
+
+/// 反馈统计/导出的时间范围，缺省为不限
+#[derive(Debug, Default, Deserialize)]
+pub struct FeedbackRangeQuery {
+    pub start: Option<DateTime<Utc>>,
+    pub end: Option<DateTime<Utc>>,
+}
+
+/// 某个排名位置的点击率
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RankClickThrough {
+    pub rank: u32,
+    /// 返回结果数不少于该排名的检索次数
+    pub impressions: u64,
+    pub clicks: u64,
+    pub ctr: f64,
+}
+
+/// 项目维度的平均倒数排名，未调用或调用了结果外接口的检索记 0
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProjectMrr {
+    pub project_id: String,
+    pub searches: u64,
+    pub mrr: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeedbackStats {
+    pub searches: u64,
+    /// 后续发生了工具调用的检索数
+    pub calls: u64,
+    pub click_through_by_rank: Vec<RankClickThrough>,
+    pub projects: Vec<ProjectMrr>,
+}
+
+/// 离线重排训练样本：chosen 为 1 表示该接口被实际调用
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LabeledPair {
+    pub search_id: String,
+    pub query: String,
+    pub project_id: Option<String>,
+    pub method: String,
+    pub path: String,
+    pub rank: u32,
+    pub score: f64,
+    pub label: u8,
+}
@@ -0,0 +1,144 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+
+/// 会话事件的方向：客户端发来、网关发出，或网关记录的会话事件
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TranscriptDirection {
+    Received,
+    Sent,
+    Gateway,
+}
+
+impl TranscriptDirection {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TranscriptDirection::Received => "received",
+            TranscriptDirection::Sent => "sent",
+            TranscriptDirection::Gateway => "gateway",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "received" => Some(TranscriptDirection::Received),
+            "sent" => Some(TranscriptDirection::Sent),
+            "gateway" => Some(TranscriptDirection::Gateway),
+            _ => None,
+        }
+    }
+}
+
+/// 会话事件类型，导出时可按类型过滤
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TranscriptKind {
+    Request,
+    Notification,
+    Response,
+    Error,
+    /// 工具调用摘要，在 tools/call 的响应之后记录
+    ToolCall,
+    Lifecycle,
+}
+
+impl TranscriptKind {
+    pub const ALL: [TranscriptKind; 6] = [
+        TranscriptKind::Request,
+        TranscriptKind::Notification,
+        TranscriptKind::Response,
+        TranscriptKind::Error,
+        TranscriptKind::ToolCall,
+        TranscriptKind::Lifecycle,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TranscriptKind::Request => "request",
+            TranscriptKind::Notification => "notification",
+            TranscriptKind::Response => "response",
+            TranscriptKind::Error => "error",
+            TranscriptKind::ToolCall => "tool_call",
+            TranscriptKind::Lifecycle => "lifecycle",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.as_str() == value)
+    }
+}
+
+/// 待写入事件存储的会话事件，payload 已脱敏
+#[derive(Debug, Clone)]
+pub struct SessionEventRecord {
+    pub session_id: String,
+    pub endpoint_id: Option<Uuid>,
+    pub direction: TranscriptDirection,
+    pub kind: TranscriptKind,
+    pub method: Option<String>,
+    pub message_id: Option<String>,
+    pub payload: Value,
+    pub at: DateTime<Utc>,
+}
+
+/// 导出的会话事件，seq 为事件存储中的顺序号
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptEvent {
+    pub seq: i64,
+    pub at: DateTime<Utc>,
+    pub direction: TranscriptDirection,
+    pub kind: TranscriptKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message_id: Option<String>,
+    /// 超过 max_payload_bytes 的消息为字符串前缀
+    pub payload: Value,
+    pub truncated: bool,
+}
+
+/// 工具调用摘要，参数已脱敏并截断
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolCallSummary {
+    pub tool: String,
+    pub request_id: String,
+    pub latency_ms: u64,
+    /// ok / tool_error / error
+    pub status: String,
+    pub arguments: Value,
+}
+
+/// initialize 握手：客户端信息与协商的协议版本
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptInitialize {
+    pub client_info: Option<Value>,
+    pub client_capabilities: Option<Value>,
+    pub requested_version: Option<String>,
+    pub negotiated_version: Option<String>,
+    pub server_info: Option<Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionTranscript {
+    pub session_id: String,
+    pub endpoint_id: Option<Uuid>,
+    pub endpoint_name: Option<String>,
+    pub initialize: Option<TranscriptInitialize>,
+    pub events: Vec<TranscriptEvent>,
+    /// 下一页的 cursor，为空表示没有更多事件
+    pub next_cursor: Option<i64>,
+}
+
+/// 导出参数：format 为 json（默认）或 markdown，types 为逗号分隔的事件类型，
+/// cursor 为上一页返回的 next_cursor，时间区间为 [from, to)
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TranscriptQuery {
+    pub format: Option<String>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub types: Option<String>,
+    pub cursor: Option<i64>,
+    pub limit: Option<usize>,
+}
@@ -1,17 +1,139 @@
+use indexmap::IndexMap;
 use rmcp::model::Tool;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use serde_json::Value;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
-use super::endpoint::McpConfig;
+use super::endpoint::{ApiDetail, McpConfig};
 
+/// 严格模式：任一操作不合法时整个 spec 解析失败，启动时由配置初始化
+static STRICT_SPEC_PARSING: AtomicBool = AtomicBool::new(false);
+
+pub fn set_strict_spec_parsing(strict: bool) {
+    STRICT_SPEC_PARSING.store(strict, Ordering::Relaxed);
+}
+
+pub fn is_strict_spec_parsing() -> bool {
+    STRICT_SPEC_PARSING.load(Ordering::Relaxed)
+}
+
+/// 生成工具的 HTTP 方法，与 PathItem 的字段一致
+pub const HTTP_METHODS: [&str; 5] = ["get", "post", "put", "delete", "patch"];
+
+/// 解析时跳过的不合法操作
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SkippedOperation {
+    pub path: String,
+    /// 大写方法名；路径项本身不合法时为 *
+    pub method: String,
+    pub error: String,
+}
+
+/// 各 map 保留 spec 中的声明顺序，工具列表与详情的输出顺序随之稳定；
+/// 单个操作不合法时跳过该操作并记录在 skipped_operations 中（严格模式除外）
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "RawSwaggerSpec")]
 pub struct SwaggerSpec {
     pub openapi: String,
     pub info: Info,
     pub servers: Option<Vec<Server>>,
-    pub paths: HashMap<String, PathItem>,
+    #[serde(default)]
+    pub paths: IndexMap<String, PathItem>,
     pub components: Option<Components>,
+    /// OpenAPI 3.1 webhooks，仅作展示/资源，不生成工具
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub webhooks: Option<IndexMap<String, PathItem>>,
+    /// x-mcp-flatten 扩展：为 true 时该端点全部工具的 inputSchema 展开为单层
+    #[serde(rename = "x-mcp-flatten", skip_serializing_if = "Option::is_none")]
+    pub flatten: Option<bool>,
+    /// x-mcp-tool-allowlist 扩展：只为列出的工具名生成工具，超过工具阈值时用于缩减暴露范围
+    #[serde(
+        rename = "x-mcp-tool-allowlist",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub tool_allowlist: Option<Vec<String>>,
+    /// 解析时跳过的操作，不序列化
+    #[serde(skip)]
+    pub skipped_operations: Vec<SkippedOperation>,
+}
+
+/// 路径项先按 JSON 读取，再逐个操作转换
+#[derive(Deserialize)]
+struct RawSwaggerSpec {
+    openapi: String,
+    info: Info,
+    servers: Option<Vec<Server>>,
+    #[serde(default)]
+    paths: IndexMap<String, Value>,
+    components: Option<Components>,
+    webhooks: Option<IndexMap<String, PathItem>>,
+    #[serde(rename = "x-mcp-flatten")]
+    flatten: Option<bool>,
+    #[serde(rename = "x-mcp-tool-allowlist")]
+    tool_allowlist: Option<Vec<String>>,
+}
+
+/// 转换路径项中的各个操作，不合法的操作记录后跳过
+fn convert_path_item(
+    path: &str,
+    item: Value,
+    skipped: &mut Vec<SkippedOperation>,
+) -> Option<PathItem> {
+    let Value::Object(mut item) = item else {
+        skipped.push(SkippedOperation {
+            path: path.to_string(),
+            method: "*".to_string(),
+            error: "path item is not an object".to_string(),
+        });
+        return None;
+    };
+    let mut path_item = PathItem::default();
+    for method in HTTP_METHODS {
+        let Some(operation) = item.remove(method).filter(|v| !v.is_null()) else {
+            continue;
+        };
+        match serde_json::from_value::<Operation>(operation) {
+            Ok(operation) => *path_item.operation_mut(method) = Some(operation),
+            Err(e) => skipped.push(SkippedOperation {
+                path: path.to_string(),
+                method: method.to_uppercase(),
+                error: e.to_string(),
+            }),
+        }
+    }
+    Some(path_item)
+}
+
+impl TryFrom<RawSwaggerSpec> for SwaggerSpec {
+    type Error = String;
+
+    fn try_from(raw: RawSwaggerSpec) -> Result<Self, Self::Error> {
+        let mut skipped = Vec::new();
+        let mut paths = IndexMap::with_capacity(raw.paths.len());
+        for (path, item) in raw.paths {
+            if let Some(path_item) = convert_path_item(&path, item, &mut skipped) {
+                paths.insert(path, path_item);
+            }
+        }
+        if let (true, Some(first)) = (is_strict_spec_parsing(), skipped.first()) {
+            return Err(format!(
+                "Invalid operation {} {}: {}",
+                first.method, first.path, first.error
+            ));
+        }
+        Ok(SwaggerSpec {
+            openapi: raw.openapi,
+            info: raw.info,
+            servers: raw.servers,
+            paths,
+            components: raw.components,
+            webhooks: raw.webhooks,
+            flatten: raw.flatten,
+            tool_allowlist: raw.tool_allowlist,
+            skipped_operations: skipped,
+        })
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,9 +147,22 @@ pub struct Info {
 pub struct Server {
     pub url: String,
     pub description: Option<String>,
+    /// url 中 `{name}` 模板变量的定义
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub variables: Option<IndexMap<String, ServerVariable>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerVariable {
+    pub default: String,
+    /// 可选值，为空时不限制
+    #[serde(rename = "enum", skip_serializing_if = "Option::is_none")]
+    pub enum_values: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct PathItem {
     pub get: Option<Operation>,
     pub post: Option<Operation>,
@@ -36,6 +171,18 @@ pub struct PathItem {
     pub patch: Option<Operation>,
 }
 
+impl PathItem {
+    fn operation_mut(&mut self, method: &str) -> &mut Option<Operation> {
+        match method {
+            "get" => &mut self.get,
+            "post" => &mut self.post,
+            "put" => &mut self.put,
+            "delete" => &mut self.delete,
+            _ => &mut self.patch,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Operation {
     #[serde(rename = "operationId")]
@@ -45,8 +192,23 @@ pub struct Operation {
     pub parameters: Option<Vec<Parameter>>,
     #[serde(rename = "requestBody")]
     pub request_body: Option<RequestBody>,
-    pub responses: Option<HashMap<String, Response>>,
+    pub responses: Option<IndexMap<String, Response>>,
     pub tags: Option<Vec<String>>,
+    /// 标记为废弃的操作，工具描述加前缀并在 inputSchema._meta 中标注
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deprecated: Option<bool>,
+    /// 回调定义原样保留，不生成工具
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub callbacks: Option<IndexMap<String, serde_json::Value>>,
+    /// x-mcp-mock 扩展：为 true 时该操作以 mock 模式执行
+    #[serde(rename = "x-mcp-mock", skip_serializing_if = "Option::is_none")]
+    pub mock: Option<bool>,
+    /// x-mcp-flatten 扩展：覆盖端点级别的展开设置
+    #[serde(rename = "x-mcp-flatten", skip_serializing_if = "Option::is_none")]
+    pub flatten: Option<bool>,
+    /// x-mcp-idempotent 扩展：覆盖按 HTTP 方法判断的幂等性，决定失败后能否安全重试
+    #[serde(rename = "x-mcp-idempotent", skip_serializing_if = "Option::is_none")]
+    pub idempotent: Option<bool>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -57,24 +219,34 @@ pub struct Parameter {
     pub required: Option<bool>,
     pub description: Option<String>,
     pub schema: Option<Schema>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub example: Option<serde_json::Value>,
+    /// 命名示例，value 为示例内容
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub examples: Option<IndexMap<String, serde_json::Value>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RequestBody {
     pub description: Option<String>,
     pub required: Option<bool>,
-    pub content: HashMap<String, MediaType>,
+    pub content: IndexMap<String, MediaType>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Response {
     pub description: String,
-    pub content: Option<HashMap<String, MediaType>>,
+    pub content: Option<IndexMap<String, MediaType>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MediaType {
     pub schema: Option<Schema>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub example: Option<serde_json::Value>,
+    /// 命名示例，value 为示例内容
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub examples: Option<IndexMap<String, serde_json::Value>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -83,16 +255,45 @@ pub struct Schema {
     pub schema_type: Option<String>,
     pub format: Option<String>,
     pub description: Option<String>,
-    pub properties: Option<HashMap<String, Schema>>,
+    pub properties: Option<IndexMap<String, Schema>>,
     pub items: Option<Box<Schema>>,
     pub required: Option<Vec<String>>,
     #[serde(rename = "$ref")]
     pub reference: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub example: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default: Option<serde_json::Value>,
+    #[serde(rename = "enum", skip_serializing_if = "Option::is_none")]
+    pub enum_values: Option<Vec<serde_json::Value>>,
+    #[serde(rename = "const", skip_serializing_if = "Option::is_none")]
+    pub const_value: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub minimum: Option<serde_json::Number>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub maximum: Option<serde_json::Number>,
+    /// OpenAPI 3.0 为布尔值（修饰 minimum），3.1 为数值
+    #[serde(rename = "exclusiveMinimum", skip_serializing_if = "Option::is_none")]
+    pub exclusive_minimum: Option<serde_json::Value>,
+    #[serde(rename = "exclusiveMaximum", skip_serializing_if = "Option::is_none")]
+    pub exclusive_maximum: Option<serde_json::Value>,
+    #[serde(rename = "minLength", skip_serializing_if = "Option::is_none")]
+    pub min_length: Option<u64>,
+    #[serde(rename = "maxLength", skip_serializing_if = "Option::is_none")]
+    pub max_length: Option<u64>,
+    #[serde(rename = "minItems", skip_serializing_if = "Option::is_none")]
+    pub min_items: Option<u64>,
+    #[serde(rename = "maxItems", skip_serializing_if = "Option::is_none")]
+    pub max_items: Option<u64>,
+    #[serde(rename = "multipleOf", skip_serializing_if = "Option::is_none")]
+    pub multiple_of: Option<serde_json::Number>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Components {
-    pub schemas: Option<HashMap<String, Schema>>,
+    pub schemas: Option<IndexMap<String, Schema>>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -107,6 +308,83 @@ pub struct SwaggerToMcpResponse {
     pub endpoint_id: uuid::Uuid,
     pub mcp_config: McpConfig,
     pub tools: Vec<McpTool>,
+    /// 不合法而未生成工具的操作
+    #[serde(default)]
+    pub skipped_operations: Vec<SkippedOperation>,
+}
+
+/// 导入 Postman Collection v2.1，转换为 OpenAPI 3 后创建端点，同名端点合并
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PostmanImportRequest {
+    pub endpoint_name: String,
+    pub description: Option<String>,
+    /// Postman Collection v2.1 JSON
+    pub collection: serde_json::Value,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PostmanImportResponse {
+    pub endpoint_id: uuid::Uuid,
+    pub mcp_config: McpConfig,
+    pub tools: Vec<McpTool>,
+    /// 转换中被忽略的脚本、不支持的 auth 等
+    pub warnings: Vec<String>,
+}
+
+/// 预览 swagger 生成的工具，不创建端点
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SwaggerPreviewRequest {
+    pub swagger_content: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SwaggerPreviewResponse {
+    pub tools: Vec<McpTool>,
+    pub api_details: Vec<ApiDetail>,
+    #[serde(default)]
+    pub skipped_operations: Vec<SkippedOperation>,
+    /// 工具数量/体积超过软阈值的告警，超过硬阈值时预览直接报错
+    #[serde(default)]
+    pub warnings: Vec<String>,
+}
+
+/// 显式合并到已有端点的 swagger；dry_run 为 true 时只返回合并结果，不写入
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SwaggerMergeRequest {
+    pub swagger_content: String,
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// 路径与大写方法名
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OperationRef {
+    pub path: String,
+    pub method: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SwaggerMergeResponse {
+    pub endpoint_id: uuid::Uuid,
+    /// dry_run 时为 false
+    pub applied: bool,
+    pub added_operations: Vec<OperationRef>,
+    /// 合并后的完整 swagger
+    pub swagger_spec: Value,
+    #[serde(default)]
+    pub skipped_operations: Vec<SkippedOperation>,
+    /// 合并后工具数量/体积超过软阈值的告警
+    #[serde(default)]
+    pub warnings: Vec<String>,
+}
+
+/// 合并的 swagger 与端点已有的路径和方法重复，整体拒绝，conflicts 列出全部重复项
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[error("Swagger merge conflict: {} operation(s) already exist", .conflicts.len())]
+pub struct SwaggerMergeConflict {
+    pub conflicts: Vec<OperationRef>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -118,6 +396,12 @@ pub struct McpTool {
     pub input_schema: serde_json::Value,
     #[serde(rename = "outputSchema")]
     pub output_schema: Option<serde_json::Value>,
+    /// 展开前的 inputSchema，调用时还原参数后按它校验；未展开时为 None
+    #[serde(skip)]
+    pub nested_input_schema: Option<serde_json::Value>,
+    /// 生成工具时的告警，如无法展开的参数
+    #[serde(skip)]
+    pub warnings: Vec<String>,
 }
 
 impl From<&McpTool> for Tool {
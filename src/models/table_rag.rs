@@ -1,6 +1,8 @@
+use crate::utils::try_get_selected;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::{mysql::MySqlRow, FromRow, Row};
+use std::collections::BTreeMap;
 use uuid::Uuid;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -28,11 +30,16 @@ pub struct ColumnSchema {
     /// 参与检索：是否作为检索字段
     #[serde(default)]
     pub searchable: bool,
-    /// 参与回复：是否在命中后作为上下文返回
-    #[serde(default)]
+    /// 参与回复：是否在命中后作为上下文返回；为 false 时仍可被检索但不出现在结果中。
+    /// 未声明时默认返回，与旧数据集行为一致
+    #[serde(default = "default_retrievable")]
     pub retrievable: bool,
 }
 
+fn default_retrievable() -> bool {
+    true
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Dataset {
     #[serde(with = "uuid_as_string")]
@@ -49,47 +56,54 @@ pub struct Dataset {
     pub retrieval_column: String,
     #[serde(default)]
     pub reply_column: String,
+    /// 行级权限列，为空时不按调用方过滤
+    #[serde(default)]
+    pub security_column: String,
     pub similarity_threshold: f32,
     pub max_results: i32,
     pub create_time: DateTime<Utc>,
     pub update_time: DateTime<Utc>,
 }
 
+/// 按字段选择查询时未查询的列取默认值，只有 id 必须查询
 impl FromRow<'_, MySqlRow> for Dataset {
     fn from_row(row: &MySqlRow) -> Result<Self, sqlx::Error> {
         let id_str: String = row.try_get("id")?;
         let id = Uuid::parse_str(&id_str)
             .map_err(|e| sqlx::Error::Decode(format!("Invalid UUID: {}", e).into()))?;
-        let type_str: String = row.try_get("type")?;
-        let dtype = match type_str.as_str() {
-            "upload" => DatasetType::Upload,
-            "remote" => DatasetType::Remote,
+        let type_str: Option<String> = try_get_selected(row, "type")?;
+        let dtype = match type_str.as_deref() {
+            Some("remote") => DatasetType::Remote,
             _ => DatasetType::Upload,
         };
-        let schema_str: String = row.try_get("table_schema")?;
-        let table_schema: serde_json::Value = serde_json::from_str(&schema_str)
-            .map_err(|e| sqlx::Error::Decode(format!("Invalid JSON: {}", e).into()))?;
+        let table_schema: serde_json::Value = try_get_selected::<String>(row, "table_schema")?
+            .map(|schema_str| serde_json::from_str(&schema_str))
+            .transpose()
+            .map_err(|e| sqlx::Error::Decode(format!("Invalid JSON: {}", e).into()))?
+            .unwrap_or_default();
         let index_mapping: Option<serde_json::Value> =
-            match row.try_get::<Option<String>, _>("index_mapping")? {
+            match try_get_selected::<Option<String>>(row, "index_mapping")?.flatten() {
                 Some(s) => serde_json::from_str(&s).ok(),
                 None => None,
             };
 
         Ok(Self {
             id,
-            name: row.try_get("name")?,
-            description: row.try_get("description")?,
+            name: try_get_selected(row, "name")?.unwrap_or_default(),
+            description: try_get_selected(row, "description")?.flatten(),
             r#type: dtype,
-            table_name: row.try_get("table_name")?,
-            index_name: row.try_get("index_name")?,
+            table_name: try_get_selected(row, "table_name")?.unwrap_or_default(),
+            index_name: try_get_selected(row, "index_name")?.unwrap_or_default(),
             table_schema,
             index_mapping,
             retrieval_column: row.try_get("retrieval_column").unwrap_or_default(),
             reply_column: row.try_get("reply_column").unwrap_or_default(),
-            similarity_threshold: row.try_get::<f32, _>("similarity_threshold")?,
-            max_results: row.try_get::<i32, _>("max_results")?,
-            create_time: row.try_get("create_time")?,
-            update_time: row.try_get("update_time")?,
+            security_column: row.try_get("security_column").unwrap_or_default(),
+            similarity_threshold: try_get_selected::<f32>(row, "similarity_threshold")?
+                .unwrap_or_default(),
+            max_results: try_get_selected::<i32>(row, "max_results")?.unwrap_or_default(),
+            create_time: try_get_selected(row, "create_time")?.unwrap_or_default(),
+            update_time: try_get_selected(row, "update_time")?.unwrap_or_default(),
         })
     }
 }
@@ -216,6 +230,83 @@ pub struct CreateDatasetRequest {
     pub retrieval_column: Option<String>,
     #[serde(default)]
     pub reply_column: Option<String>,
+    #[serde(default)]
+    pub security_column: Option<String>,
+}
+
+/// 字段级校验错误，field 形如 schema[1].name
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+/// 数据集 schema 校验失败，handler 转为 400 并返回全部字段错误
+#[derive(Debug, thiserror::Error)]
+#[error("Invalid dataset schema: {}", summarize_field_errors(.errors))]
+pub struct SchemaValidationError {
+    pub errors: Vec<FieldError>,
+}
+
+/// 检索过滤运算符，范围运算仅适用于数值与时间列
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FilterOp {
+    Eq,
+    Ne,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+    In,
+}
+
+impl FilterOp {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FilterOp::Eq => "eq",
+            FilterOp::Ne => "ne",
+            FilterOp::Gt => "gt",
+            FilterOp::Gte => "gte",
+            FilterOp::Lt => "lt",
+            FilterOp::Lte => "lte",
+            FilterOp::In => "in",
+        }
+    }
+}
+
+/// 检索结构化过滤条件，如 region eq "APAC"、amount gt 100；in 的 value 为数组
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchFilter {
+    pub column: String,
+    pub op: FilterOp,
+    pub value: serde_json::Value,
+}
+
+/// 检索加权：数值列按列值加权，时间列越新权重越高，与向量相似度相加后排序
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchBoost {
+    pub column: String,
+    /// 加权系数，必须大于 0
+    pub factor: f64,
+    /// 时间列的衰减尺度，如 "30d"，距今该时长的行权重减半；默认 30d
+    #[serde(default)]
+    pub scale: Option<String>,
+}
+
+/// 检索过滤或加权条件与 schema 不符，handler 转为 400 并返回全部字段错误
+#[derive(Debug, thiserror::Error)]
+#[error("Invalid search filters: {}", summarize_field_errors(.errors))]
+pub struct SearchFilterError {
+    pub errors: Vec<FieldError>,
+}
+
+fn summarize_field_errors(errors: &[FieldError]) -> String {
+    errors
+        .iter()
+        .map(|e| format!("{}: {}", e.field, e.message))
+        .collect::<Vec<_>>()
+        .join("; ")
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -228,6 +319,9 @@ pub struct UpdateDatasetRequest {
     pub retrieval_column: Option<String>,
     #[serde(default)]
     pub reply_column: Option<String>,
+    /// 传空字符串取消行级权限
+    #[serde(default)]
+    pub security_column: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -241,6 +335,17 @@ pub struct DatasetResponse {
     pub max_results: i32,
 }
 
+/// 数据集列表 fields 参数可选的字段，与列同名
+pub const DATASET_LIST_FIELDS: &[&str] = &[
+    "id",
+    "name",
+    "description",
+    "type",
+    "table_name",
+    "similarity_threshold",
+    "max_results",
+];
+
 impl From<Dataset> for DatasetResponse {
     fn from(d: Dataset) -> Self {
         Self {
@@ -255,6 +360,38 @@ impl From<Dataset> for DatasetResponse {
     }
 }
 
+/// 数据集存储统计：ES 文档数、索引占用与最近一次成功摄取时间
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatasetStats {
+    pub dataset_id: Uuid,
+    pub index_name: String,
+    pub doc_count: u64,
+    /// 索引在 ES 中的总存储（含副本），单位字节
+    pub size_bytes: u64,
+    pub last_ingest_at: Option<DateTime<Utc>>,
+}
+
+/// 从数据集移除单个文件的结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatasetFileRemoval {
+    pub dataset_id: Uuid,
+    pub file_id: Uuid,
+    /// 按 task_id 删除的 ES 文档数
+    pub deleted_documents: u64,
+    /// 同时删除的摄取任务数
+    pub deleted_tasks: u64,
+    /// t_file 记录与存储文件是否已删除，被其他数据集引用时保留
+    pub file_deleted: bool,
+}
+
+/// 文件仍在摄取中，删除会与写入冲突
+#[derive(Debug, thiserror::Error)]
+#[error("File {file_id} is still being ingested into dataset {dataset_id}")]
+pub struct IngestInProgress {
+    pub dataset_id: Uuid,
+    pub file_id: Uuid,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct DatasetDetailResponse {
     pub id: Uuid,
@@ -267,6 +404,7 @@ pub struct DatasetDetailResponse {
     pub index_mapping: Option<serde_json::Value>,
     pub retrieval_column: String,
     pub reply_column: String,
+    pub security_column: String,
     pub similarity_threshold: f32,
     pub max_results: i32,
 }
@@ -286,12 +424,75 @@ impl From<Dataset> for DatasetDetailResponse {
             index_mapping: d.index_mapping,
             retrieval_column: d.retrieval_column,
             reply_column: d.reply_column,
+            security_column: d.security_column,
             similarity_threshold: d.similarity_threshold,
             max_results: d.max_results,
         }
     }
 }
 
+/// 行级过滤：安全列名 → 允许访问的值
+pub type RowFilter = BTreeMap<String, String>;
+
+/// 检索实际应用的行级过滤，随检索结果返回
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AppliedRowFilter {
+    pub column: String,
+    pub value: String,
+}
+
+/// 检索的调用方身份
+#[derive(Debug, Clone, PartialEq)]
+pub enum DatasetCaller {
+    /// 携带管理员凭据，可检索全部行
+    Admin,
+    /// 数据集令牌或配置了行级过滤的 API key
+    Restricted(RowFilter),
+    /// 未携带有效凭据，只能检索未设置安全列的数据集
+    Anonymous,
+}
+
+/// 数据集访问令牌，明文只在创建时返回
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatasetToken {
+    pub id: Uuid,
+    pub dataset_id: Uuid,
+    pub name: String,
+    /// 为空时只能检索未设置安全列的数据集
+    pub row_filter: Option<RowFilter>,
+    pub create_time: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateDatasetTokenRequest {
+    pub name: String,
+    #[serde(default)]
+    pub row_filter: Option<RowFilter>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreatedDatasetToken {
+    /// 令牌明文，之后无法再次获取
+    pub token: String,
+    #[serde(flatten)]
+    pub info: DatasetToken,
+}
+
+/// 调用方无权检索：缺少凭据或令牌无效（401），令牌/行级过滤不适用于该数据集（403）
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum DatasetAccessError {
+    #[error("Dataset access token or API key is required")]
+    MissingCredential,
+    #[error("Invalid dataset access token")]
+    InvalidToken,
+    #[error("Access token is not valid for dataset {0}")]
+    WrongDataset(Uuid),
+    #[error("Row filter has no value for security column '{0}'")]
+    MissingRowFilter(String),
+    #[error("Row filter value '{value}' is not valid for security column '{column}'")]
+    InvalidRowFilter { column: String, value: String },
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PaginatedDatasetsResponse {
     pub datasets: Vec<DatasetResponse>,
@@ -0,0 +1,235 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// 报表范围：网关为单租户部署，tenant 即全部端点
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportScope {
+    Tenant,
+    Endpoints,
+}
+
+impl ReportScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReportScope::Tenant => "tenant",
+            ReportScope::Endpoints => "endpoints",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "tenant" => Some(ReportScope::Tenant),
+            "endpoints" => Some(ReportScope::Endpoints),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportPeriod {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+impl ReportPeriod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReportPeriod::Daily => "daily",
+            ReportPeriod::Weekly => "weekly",
+            ReportPeriod::Monthly => "monthly",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "daily" => Some(ReportPeriod::Daily),
+            "weekly" => Some(ReportPeriod::Weekly),
+            "monthly" => Some(ReportPeriod::Monthly),
+            _ => None,
+        }
+    }
+}
+
+/// 投递方式：webhook 推送，或仅存储文件供下载
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportDelivery {
+    Webhook,
+    File,
+}
+
+impl ReportDelivery {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReportDelivery::Webhook => "webhook",
+            ReportDelivery::File => "file",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "webhook" => Some(ReportDelivery::Webhook),
+            "file" => Some(ReportDelivery::File),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageReport {
+    pub id: Uuid,
+    pub name: String,
+    pub scope: ReportScope,
+    /// scope 为 endpoints 时的端点列表
+    pub endpoint_ids: Vec<Uuid>,
+    pub period: ReportPeriod,
+    pub delivery: ReportDelivery,
+    pub webhook_url: Option<String>,
+    pub enabled: bool,
+    /// 已生成的最后一个周期的结束时间
+    pub last_period_end: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateUsageReportRequest {
+    pub name: String,
+    pub scope: ReportScope,
+    #[serde(default)]
+    pub endpoint_ids: Vec<Uuid>,
+    pub period: ReportPeriod,
+    pub delivery: ReportDelivery,
+    pub webhook_url: Option<String>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpdateUsageReportRequest {
+    pub name: Option<String>,
+    pub scope: Option<ReportScope>,
+    pub endpoint_ids: Option<Vec<Uuid>>,
+    pub period: Option<ReportPeriod>,
+    pub delivery: Option<ReportDelivery>,
+    pub webhook_url: Option<String>,
+    pub enabled: Option<bool>,
+}
+
+/// 单个端点在报表周期内的调用情况
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EndpointUsage {
+    pub endpoint_id: String,
+    pub endpoint_name: Option<String>,
+    pub calls: u64,
+    pub errors: u64,
+    pub error_rate: f64,
+    pub avg_latency_ms: f64,
+}
+
+/// 按接口（方法 + 路径）聚合的调用
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolUsage {
+    pub endpoint_id: String,
+    pub method: String,
+    pub path: String,
+    pub calls: u64,
+    pub errors: u64,
+}
+
+/// 报表内容，周期区间为 [period_start, period_end)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UsageReportSummary {
+    pub report_id: Uuid,
+    pub report_name: String,
+    pub period: ReportPeriod,
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+    pub total_calls: u64,
+    pub total_errors: u64,
+    pub endpoints: Vec<EndpointUsage>,
+    pub top_tools: Vec<ToolUsage>,
+    /// 错误数最多的接口
+    pub error_hotspots: Vec<ToolUsage>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportRunStatus {
+    Completed,
+    Failed,
+}
+
+impl ReportRunStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReportRunStatus::Completed => "completed",
+            ReportRunStatus::Failed => "failed",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "completed" => Some(ReportRunStatus::Completed),
+            "failed" => Some(ReportRunStatus::Failed),
+            _ => None,
+        }
+    }
+}
+
+/// 投递结果，file 方式不投递记为 skipped
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportDeliveryStatus {
+    Delivered,
+    Failed,
+    Skipped,
+}
+
+impl ReportDeliveryStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReportDeliveryStatus::Delivered => "delivered",
+            ReportDeliveryStatus::Failed => "failed",
+            ReportDeliveryStatus::Skipped => "skipped",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "delivered" => Some(ReportDeliveryStatus::Delivered),
+            "failed" => Some(ReportDeliveryStatus::Failed),
+            "skipped" => Some(ReportDeliveryStatus::Skipped),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageReportRun {
+    pub id: Uuid,
+    pub report_id: Uuid,
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+    pub status: ReportRunStatus,
+    pub delivery_status: ReportDeliveryStatus,
+    pub delivery_attempts: u32,
+    pub error_message: Option<String>,
+    /// 生成成功后可下载，format=markdown 下载 Markdown 摘要
+    pub download_url: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// 运行文件下载格式：json（默认）或 markdown
+#[derive(Debug, Default, Deserialize)]
+pub struct ReportRunDownloadQuery {
+    pub format: Option<String>,
+}
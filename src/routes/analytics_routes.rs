@@ -0,0 +1,17 @@
+use crate::handlers::{
+    create_analytics_export, download_analytics_export, get_analytics_export, AnalyticsState,
+};
+use axum::{
+    routing::{get, post},
+    Router,
+};
+
+pub fn create_analytics_routes() -> Router<AnalyticsState> {
+    Router::new()
+        .route("/api/analytics/export", post(create_analytics_export))
+        .route("/api/analytics/export/{id}", get(get_analytics_export))
+        .route(
+            "/api/analytics/export/{id}/download",
+            get(download_analytics_export),
+        )
+}
@@ -1,5 +1,6 @@
 use crate::handlers::{
-    get_endpoint_connection_count, get_endpoint_connections, get_time_series_connection_counts,
+    export_session_transcript, get_endpoint_connection_count, get_endpoint_connections,
+    get_time_series_connection_counts,
 };
 use crate::state::MergeState;
 use axum::{routing::get, Router};
@@ -17,4 +18,9 @@ pub fn create_connection_routes() -> Router<MergeState> {
             "/api/connections/time-series",
             get(get_time_series_connection_counts),
         )
+        // Session transcript export
+        .route(
+            "/api/sessions/{id}/transcript",
+            get(export_session_transcript),
+        )
 }
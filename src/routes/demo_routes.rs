@@ -0,0 +1,10 @@
+use crate::handlers::{seed_demo, teardown_demo, DemoState};
+use axum::{routing::post, Router};
+
+/// 演示数据路由，需开启 demo.seed_enabled
+pub fn create_demo_routes() -> Router<DemoState> {
+    Router::new().route(
+        "/api/system/seed-demo",
+        post(seed_demo).delete(teardown_demo),
+    )
+}
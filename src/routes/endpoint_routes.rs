@@ -1,10 +1,20 @@
 use crate::handlers::{
-    create_endpoint, delete_endpoint, get_endpoint, get_endpoint_metrics, list_endpoints,
-    list_endpoints_paginated, start_endpoint, stop_endpoint, sync_endpoint_vector, update_endpoint,
+    abort_canary, batch_call_tools, create_composite_tool, create_endpoint, create_operation_note,
+    create_recording_endpoint, delete_composite_tool, delete_endpoint, delete_kv_entry,
+    delete_operation_note, export_endpoints, get_canary, get_canary_stats, get_contract_tests,
+    get_dns_overrides, get_effective_policy, get_endpoint, get_endpoint_metrics,
+    get_endpoint_metrics_history, get_execution_policy, get_kv_entry, get_kv_settings,
+    get_lifecycle_events, get_locale_settings, get_operation_note_settings, list_composite_tools,
+    list_endpoints, list_endpoints_paginated, list_kv_entries, list_operation_notes,
+    promote_canary, promote_endpoint_spec, put_canary, put_contract_test_overrides,
+    put_dns_overrides, put_execution_policy, put_kv_entry, put_kv_settings, put_locale_settings,
+    put_operation_note_settings, rebuild_api_paths, start_all_endpoints, start_endpoint,
+    stop_all_endpoints, stop_endpoint, sync_endpoint_vector, synthesize_endpoint_spec,
+    update_composite_tool, update_endpoint, update_operation_note,
 };
 use crate::state::MergeState;
 use axum::{
-    routing::{get, post},
+    routing::{get, post, put},
     Router,
 };
 
@@ -14,6 +24,10 @@ pub fn create_endpoint_routes() -> Router<MergeState> {
         // Endpoint management routes
         .route("/api/endpoint", post(create_endpoint).get(list_endpoints))
         .route("/api/endpoints", get(list_endpoints_paginated))
+        .route("/api/endpoints/recording", post(create_recording_endpoint))
+        .route("/api/endpoints/export", get(export_endpoints))
+        .route("/api/endpoints/start-all", post(start_all_endpoints))
+        .route("/api/endpoints/stop-all", post(stop_all_endpoints))
         .route(
             "/api/endpoint/{id}",
             get(get_endpoint)
@@ -21,8 +35,85 @@ pub fn create_endpoint_routes() -> Router<MergeState> {
                 .delete(delete_endpoint),
         )
         .route("/api/endpoint/{id}/metrics", get(get_endpoint_metrics))
+        .route(
+            "/api/endpoints/{id}/metrics/history",
+            get(get_endpoint_metrics_history),
+        )
         .route("/api/endpoint/{id}/start", post(start_endpoint))
         .route("/api/endpoint/{id}/stop", post(stop_endpoint))
+        .route(
+            "/api/endpoints/{id}/lifecycle-events",
+            get(get_lifecycle_events),
+        )
+        .route("/api/endpoint/{id}/rebuild-paths", post(rebuild_api_paths))
+        .route(
+            "/api/endpoints/{id}/contract-tests",
+            get(get_contract_tests).put(put_contract_test_overrides),
+        )
+        .route(
+            "/api/endpoints/{id}/paths/notes",
+            get(list_operation_notes).post(create_operation_note),
+        )
+        .route(
+            "/api/endpoints/{id}/paths/notes/{note_id}",
+            put(update_operation_note).delete(delete_operation_note),
+        )
+        .route(
+            "/api/endpoints/{id}/paths/notes-settings",
+            get(get_operation_note_settings).put(put_operation_note_settings),
+        )
+        .route(
+            "/api/endpoints/{id}/composite-tools",
+            get(list_composite_tools).post(create_composite_tool),
+        )
+        .route(
+            "/api/endpoints/{id}/composite-tools/{tool_id}",
+            put(update_composite_tool).delete(delete_composite_tool),
+        )
+        .route(
+            "/api/endpoints/{id}/execution-policy",
+            get(get_execution_policy).put(put_execution_policy),
+        )
+        .route(
+            "/api/endpoints/{id}/effective-policy",
+            get(get_effective_policy),
+        )
+        .route(
+            "/api/endpoints/{id}/synthesize-spec",
+            post(synthesize_endpoint_spec),
+        )
+        .route(
+            "/api/endpoints/{id}/promote-spec",
+            post(promote_endpoint_spec),
+        )
+        .route(
+            "/api/endpoints/{id}/canary",
+            get(get_canary).put(put_canary).delete(abort_canary),
+        )
+        .route("/api/endpoints/{id}/canary/stats", get(get_canary_stats))
+        .route("/api/endpoints/{id}/canary/promote", post(promote_canary))
+        .route("/api/endpoints/{id}/canary/abort", post(abort_canary))
+        .route(
+            "/api/endpoints/{id}/dns-overrides",
+            get(get_dns_overrides).put(put_dns_overrides),
+        )
+        .route(
+            "/api/endpoints/{id}/locale-settings",
+            get(get_locale_settings).put(put_locale_settings),
+        )
+        .route(
+            "/api/endpoints/{id}/tools/batch-call",
+            post(batch_call_tools),
+        )
+        .route("/api/endpoints/{id}/kv", get(list_kv_entries))
+        .route(
+            "/api/endpoints/{id}/kv/settings",
+            get(get_kv_settings).put(put_kv_settings),
+        )
+        .route(
+            "/api/endpoints/{id}/kv/{namespace}/{*key}",
+            get(get_kv_entry).put(put_kv_entry).delete(delete_kv_entry),
+        )
         .route(
             "/api/endpoint/{name}/sync_vector",
             post(sync_endpoint_vector),
@@ -1,4 +1,4 @@
-use crate::handlers::{actuator_health, get_api_health};
+use crate::handlers::{actuator_health, get_api_health, health_ready, healthz};
 use crate::state::MergeState;
 use axum::{routing::get, Router};
 
@@ -7,6 +7,8 @@ pub fn create_health_routes() -> Router<MergeState> {
     Router::new()
         // Health check routes
         .route("/health", get(get_api_health))
+        .route("/healthz", get(healthz))
+        .route("/health/ready", get(health_ready))
         .route("/ready", get(|| async { "Ready" }))
         .route("/live", get(|| async { "Live" }))
         .route("/actuator/health", get(actuator_health))
@@ -0,0 +1,12 @@
+use crate::handlers::{get_job, list_jobs, retry_job, JobState};
+use axum::{
+    routing::{get, post},
+    Router,
+};
+
+pub fn create_job_routes() -> Router<JobState> {
+    Router::new()
+        .route("/api/jobs", get(list_jobs))
+        .route("/api/jobs/{id}", get(get_job))
+        .route("/api/jobs/{id}/retry", post(retry_job))
+}
@@ -1,4 +1,7 @@
-use crate::handlers::get_all_endpoint_metrics;
+use crate::handlers::{
+    get_all_endpoint_metrics, get_db_pool_metrics, get_mcp_method_metrics, get_scheduler_metrics,
+    get_spec_cache_metrics, get_tool_limit_metrics,
+};
 use crate::state::MergeState;
 use axum::{routing::get, Router};
 
@@ -7,4 +10,9 @@ pub fn create_metrics_routes() -> Router<MergeState> {
     Router::new()
         // Metrics routes
         .route("/api/metrics/endpoints", get(get_all_endpoint_metrics))
+        .route("/api/metrics/scheduler", get(get_scheduler_metrics))
+        .route("/api/metrics/spec-cache", get(get_spec_cache_metrics))
+        .route("/api/metrics/db-pools", get(get_db_pool_metrics))
+        .route("/api/metrics/mcp-methods", get(get_mcp_method_metrics))
+        .route("/api/metrics/tool-limits", get(get_tool_limit_metrics))
 }
@@ -1,17 +1,27 @@
+pub mod analytics_routes;
 pub mod connection_routes;
+pub mod demo_routes;
 pub mod endpoint_routes;
 pub mod file_routes;
 pub mod health_routes;
+pub mod job_routes;
 pub mod metrics_routes;
+pub mod plugin_routes;
+pub mod report_routes;
 pub mod swagger_routes;
 pub mod system_routes;
 pub mod table_rag_routes;
 
+pub use analytics_routes::*;
 pub use connection_routes::*;
+pub use demo_routes::*;
 pub use endpoint_routes::*;
 pub use file_routes::*;
 pub use health_routes::*;
+pub use job_routes::*;
 pub use metrics_routes::*;
+pub use plugin_routes::*;
+pub use report_routes::*;
 pub use swagger_routes::*;
 pub use system_routes::*;
 pub use table_rag_routes::*;
@@ -0,0 +1,14 @@
+use crate::handlers::{delete_plugin, get_plugin, put_plugin, test_plugin, PluginState};
+use axum::{
+    routing::{get, post},
+    Router,
+};
+
+pub fn create_plugin_routes() -> Router<PluginState> {
+    Router::new()
+        .route(
+            "/api/endpoints/{id}/plugin",
+            get(get_plugin).put(put_plugin).delete(delete_plugin),
+        )
+        .route("/api/endpoints/{id}/plugin/test", post(test_plugin))
+}
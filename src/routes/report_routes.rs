@@ -0,0 +1,24 @@
+use crate::handlers::{
+    create_usage_report, delete_usage_report, download_usage_report_run, get_usage_report,
+    list_usage_report_runs, list_usage_reports, update_usage_report, ReportState,
+};
+use axum::{routing::get, Router};
+
+pub fn create_report_routes() -> Router<ReportState> {
+    Router::new()
+        .route(
+            "/api/reports",
+            get(list_usage_reports).post(create_usage_report),
+        )
+        .route(
+            "/api/reports/{id}",
+            get(get_usage_report)
+                .put(update_usage_report)
+                .delete(delete_usage_report),
+        )
+        .route("/api/reports/{id}/runs", get(list_usage_report_runs))
+        .route(
+            "/api/reports/{id}/runs/{run_id}/download",
+            get(download_usage_report_run),
+        )
+}
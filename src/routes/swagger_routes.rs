@@ -1,4 +1,7 @@
-use crate::handlers::convert_swagger_to_mcp;
+use crate::handlers::{
+    convert_swagger_to_mcp, import_postman_collection, merge_endpoint_swagger,
+    preview_swagger_tools,
+};
 use crate::state::MergeState;
 use axum::{routing::post, Router};
 
@@ -7,4 +10,13 @@ pub fn create_swagger_routes() -> Router<MergeState> {
     Router::new()
         // Swagger conversion route
         .route("/api/swagger", post(convert_swagger_to_mcp))
+        .route("/api/swagger/preview-tools", post(preview_swagger_tools))
+        .route(
+            "/api/swagger/import-postman",
+            post(import_postman_collection),
+        )
+        .route(
+            "/api/endpoints/{id}/swagger/merge",
+            post(merge_endpoint_swagger),
+        )
 }
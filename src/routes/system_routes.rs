@@ -1,10 +1,23 @@
-use crate::handlers::get_system_status;
+use crate::handlers::{
+    create_fault, delete_fault, get_circuit_breakers, get_cleanup_outbox, get_dns_stats,
+    get_system_info, get_system_status, list_faults, put_read_only,
+};
 use crate::state::MergeState;
-use axum::{routing::get, Router};
+use axum::{
+    routing::{delete, get, put},
+    Router,
+};
 
 /// 创建系统状态路由
 pub fn create_system_routes() -> Router<MergeState> {
     Router::new()
         // System status route
         .route("/api/system/status", get(get_system_status))
+        .route("/api/system/outbox", get(get_cleanup_outbox))
+        .route("/api/system/info", get(get_system_info))
+        .route("/api/system/read-only", put(put_read_only))
+        .route("/api/system/circuit-breakers", get(get_circuit_breakers))
+        .route("/api/system/dns", get(get_dns_stats))
+        .route("/api/system/faults", get(list_faults).post(create_fault))
+        .route("/api/system/faults/{id}", delete(delete_fault))
 }
@@ -1,11 +1,12 @@
 use crate::handlers::{
-    create_dataset_handler, get_dataset_handler, ingest_dataset_file_handler,
+    create_dataset_handler, create_dataset_token_handler, dataset_stats_handler,
+    get_dataset_handler, ingest_dataset_file_handler, list_dataset_tokens_handler,
     list_datasets_handler, list_remote_tables_handler, list_tasks_handler, preview_schema_handler,
-    search_handler, search_paged_handler, test_remote_connection_handler, update_dataset_handler,
-    TableRagState,
+    remove_dataset_file_handler, revoke_dataset_token_handler, search_handler,
+    search_paged_handler, test_remote_connection_handler, update_dataset_handler, TableRagState,
 };
 use axum::{
-    routing::{get, post},
+    routing::{delete, get, post},
     Router,
 };
 
@@ -19,6 +20,22 @@ pub fn create_table_rag_routes() -> Router<TableRagState> {
             "/api/table-rag/datasets/{id}",
             get(get_dataset_handler).put(update_dataset_handler),
         )
+        .route(
+            "/api/table-rag/datasets/{id}/stats",
+            get(dataset_stats_handler),
+        )
+        .route(
+            "/api/table-rag/datasets/{id}/files/{file_id}",
+            delete(remove_dataset_file_handler),
+        )
+        .route(
+            "/api/table-rag/datasets/{id}/tokens",
+            post(create_dataset_token_handler).get(list_dataset_tokens_handler),
+        )
+        .route(
+            "/api/table-rag/datasets/{id}/tokens/{token_id}",
+            delete(revoke_dataset_token_handler),
+        )
         .route("/api/table-rag/ingest", post(ingest_dataset_file_handler))
         .route(
             "/api/table-rag/preview-schema",
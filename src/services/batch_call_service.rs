@@ -0,0 +1,215 @@
+use crate::config::BatchCallConfig;
+use crate::models::{BatchCallError, BatchCallStatus, BatchToolCall, BatchToolCallResult};
+use crate::services::{tool_call_error, tool_error_result};
+use futures::stream::{self, StreamExt};
+use serde_json::Value;
+use std::future::Future;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+pub static BATCH_CALL_CONFIG: OnceLock<BatchCallConfig> = OnceLock::new();
+
+pub fn batch_call_config() -> &'static BatchCallConfig {
+    BATCH_CALL_CONFIG.get_or_init(BatchCallConfig::default)
+}
+
+/// 批量调用请求不合法，整体拒绝
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum BatchCallRejected {
+    #[error("Batch must contain at least one call")]
+    Empty,
+    #[error("Batch contains {count} calls, at most {max} allowed")]
+    TooManyCalls { count: usize, max: usize },
+    #[error("Call {index} has an empty tool name")]
+    MissingTool { index: usize },
+}
+
+pub fn check_batch(
+    calls: &[BatchToolCall],
+    config: &BatchCallConfig,
+) -> Result<(), BatchCallRejected> {
+    if calls.is_empty() {
+        return Err(BatchCallRejected::Empty);
+    }
+    if calls.len() > config.max_calls {
+        return Err(BatchCallRejected::TooManyCalls {
+            count: calls.len(),
+            max: config.max_calls,
+        });
+    }
+    match calls.iter().position(|call| call.tool.trim().is_empty()) {
+        Some(index) => Err(BatchCallRejected::MissingTool { index }),
+        None => Ok(()),
+    }
+}
+
+/// 单次调用的结果：映射为 isError 的上游状态记为 tool_error，其余错误按 tools/call 的映射记为 error
+pub fn batch_call_result(
+    index: usize,
+    tool: String,
+    elapsed: Duration,
+    outcome: anyhow::Result<Value>,
+) -> BatchToolCallResult {
+    let (status, result, error) = match outcome {
+        Ok(result) => (BatchCallStatus::Ok, Some(result), None),
+        Err(error) => match tool_error_result(&error) {
+            Some(result) => (BatchCallStatus::ToolError, Some(result.clone()), None),
+            None => {
+                let error = tool_call_error(&error);
+                let error = BatchCallError {
+                    code: error.code.0,
+                    message: error.message.to_string(),
+                    data: error.data,
+                };
+                (BatchCallStatus::Error, None, Some(error))
+            }
+        },
+    };
+    BatchToolCallResult {
+        index,
+        tool,
+        status,
+        result,
+        error,
+        elapsed_ms: elapsed.as_millis() as u64,
+    }
+}
+
+/// 最多 concurrency 个调用同时执行，结果按请求顺序返回；单个调用失败不影响其余调用
+pub async fn run_batch<F, Fut>(
+    calls: Vec<BatchToolCall>,
+    concurrency: usize,
+    call: F,
+) -> Vec<BatchToolCallResult>
+where
+    F: Fn(BatchToolCall) -> Fut,
+    Fut: Future<Output = anyhow::Result<Value>>,
+{
+    stream::iter(calls.into_iter().enumerate())
+        .map(|(index, request)| {
+            let tool = request.tool.clone();
+            let execution = call(request);
+            async move {
+                let started = std::time::Instant::now();
+                let outcome = execution.await;
+                batch_call_result(index, tool, started.elapsed(), outcome)
+            }
+        })
+        .buffered(concurrency.max(1))
+        .collect()
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::StatusOutcome;
+    use crate::services::UpstreamStatusError;
+    use crate::utils::ArgumentError;
+    use anyhow::anyhow;
+    use serde_json::json;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn call(tool: &str, arguments: Value) -> BatchToolCall {
+        BatchToolCall {
+            tool: tool.to_string(),
+            arguments,
+        }
+    }
+
+    #[test]
+    fn test_check_batch() {
+        let config = BatchCallConfig {
+            max_calls: 2,
+            concurrency: 1,
+        };
+        assert_eq!(check_batch(&[], &config), Err(BatchCallRejected::Empty));
+        let calls = vec![call("a", Value::Null); 3];
+        assert_eq!(
+            check_batch(&calls, &config),
+            Err(BatchCallRejected::TooManyCalls { count: 3, max: 2 })
+        );
+        assert_eq!(
+            check_batch(&[call("a", Value::Null), call(" ", Value::Null)], &config),
+            Err(BatchCallRejected::MissingTool { index: 1 })
+        );
+        assert_eq!(check_batch(&calls[..2], &config), Ok(()));
+    }
+
+    #[tokio::test]
+    async fn test_run_batch_keeps_order_and_bounds_concurrency() {
+        let running = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+        let calls = (0..6)
+            .map(|i| call(&format!("tool{}", i), json!({"delay": 60 - i * 10})))
+            .collect();
+        let results = run_batch(calls, 2, |request| {
+            let (running, peak) = (&running, &peak);
+            async move {
+                let current = running.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(current, Ordering::SeqCst);
+                let delay = request.arguments["delay"].as_u64().unwrap();
+                tokio::time::sleep(Duration::from_millis(delay)).await;
+                running.fetch_sub(1, Ordering::SeqCst);
+                if request.tool == "tool3" {
+                    return Err(anyhow!("boom"));
+                }
+                Ok(json!({"tool": request.tool}))
+            }
+        })
+        .await;
+
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+        let tools: Vec<&str> = results.iter().map(|r| r.tool.as_str()).collect();
+        assert_eq!(
+            tools,
+            ["tool0", "tool1", "tool2", "tool3", "tool4", "tool5"]
+        );
+        for (index, result) in results.iter().enumerate() {
+            assert_eq!(result.index, index);
+        }
+        assert_eq!(results[3].status, BatchCallStatus::Error);
+        assert_eq!(results[3].error.as_ref().unwrap().data, Some(json!("boom")));
+        assert_eq!(results[4].status, BatchCallStatus::Ok);
+        assert_eq!(results[4].result, Some(json!({"tool": "tool4"})));
+    }
+
+    #[test]
+    fn test_batch_call_result_errors() {
+        let invalid = ArgumentError {
+            path: "petId".to_string(),
+            constraint: "required".to_string(),
+            message: "missing".to_string(),
+        };
+        let result = batch_call_result(0, "getPetById".into(), Duration::ZERO, Err(invalid.into()));
+        assert_eq!(result.status, BatchCallStatus::Error);
+        let error = result.error.unwrap();
+        assert_eq!(error.code, -32602);
+        assert_eq!(error.data.unwrap()["path"], "petId");
+
+        let upstream = |outcome| UpstreamStatusError {
+            status: 404,
+            outcome,
+            error_code: -32004,
+            result: json!({"status": 404, "success": false}),
+        };
+        let tool_error = batch_call_result(
+            1,
+            "getPetById".into(),
+            Duration::ZERO,
+            Err(upstream(StatusOutcome::ToolError).into()),
+        );
+        assert_eq!(tool_error.status, BatchCallStatus::ToolError);
+        assert_eq!(tool_error.result.unwrap()["status"], 404);
+        assert!(tool_error.error.is_none());
+
+        let rpc_error = batch_call_result(
+            2,
+            "getPetById".into(),
+            Duration::ZERO,
+            Err(upstream(StatusOutcome::RpcError).into()),
+        );
+        assert_eq!(rpc_error.status, BatchCallStatus::Error);
+        assert_eq!(rpc_error.error.unwrap().code, -32004);
+    }
+}
@@ -0,0 +1,391 @@
+use crate::models::{CanaryConfig, CanaryStats, CanaryVariant, CanaryVariantStats, DbPool};
+use crate::utils::get_china_time;
+use anyhow::{anyhow, Result};
+use dashmap::DashMap;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use sqlx::Row;
+use std::sync::OnceLock;
+use std::time::Duration;
+use uuid::Uuid;
+
+/// 强制路由的请求头，true 走金丝雀，false 走主版本
+pub const CANARY_HEADER: &str = "X-Canary";
+/// 强制路由的工具参数，执行前从参数中移除，不会发往上游
+pub const CANARY_ARGUMENT: &str = "_canary";
+/// 统计窗口默认 1 小时
+pub const DEFAULT_CANARY_WINDOW_SECS: u64 = 3600;
+
+/// 路由比例的精度：万分之一
+const CANARY_BUCKETS: u64 = 10_000;
+
+/// 端点金丝雀配置的内存副本，启动时加载，配置接口修改后立即生效，无需重启
+static CANARY_CONFIGS: OnceLock<DashMap<Uuid, CanaryConfig>> = OnceLock::new();
+
+pub fn canary_configs() -> &'static DashMap<Uuid, CanaryConfig> {
+    CANARY_CONFIGS.get_or_init(DashMap::new)
+}
+
+/// 从参数中取出 _canary，返回去掉该键后的参数与强制路由标记
+pub fn take_canary_override(arguments: &Value) -> (Value, Option<bool>) {
+    match arguments {
+        Value::Object(map) if map.contains_key(CANARY_ARGUMENT) => {
+            let mut map = map.clone();
+            let forced = map.remove(CANARY_ARGUMENT).and_then(|v| v.as_bool());
+            (Value::Object(map), forced)
+        }
+        _ => (arguments.clone(), None),
+    }
+}
+
+/// 选择变体：强制标记优先；有会话时按会话与端点哈希分桶，同一会话始终落在同一变体，
+/// 且调高比例时已在金丝雀的会话不会切回主版本；无会话时每次随机分桶
+pub fn choose_variant(
+    config: &CanaryConfig,
+    session: Option<&str>,
+    forced: Option<bool>,
+) -> CanaryVariant {
+    if let Some(forced) = forced {
+        return if forced {
+            CanaryVariant::Canary
+        } else {
+            CanaryVariant::Primary
+        };
+    }
+    let key = match session {
+        Some(session) => session.to_string(),
+        None => Uuid::new_v4().to_string(),
+    };
+    let threshold = (config.percentage.clamp(0.0, 100.0) * (CANARY_BUCKETS / 100) as f64) as u64;
+    if bucket(config.endpoint_id, &key) < threshold {
+        CanaryVariant::Canary
+    } else {
+        CanaryVariant::Primary
+    }
+}
+
+fn bucket(endpoint_id: Uuid, key: &str) -> u64 {
+    let digest = Sha256::digest(format!("{}:{}", endpoint_id, key).as_bytes());
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&digest[..8]);
+    u64::from_be_bytes(bytes) % CANARY_BUCKETS
+}
+
+/// 按变体汇总调用，p95 取最近秩（nearest-rank）
+pub fn variant_stats(variant: CanaryVariant, calls: &[(bool, u64)]) -> CanaryVariantStats {
+    let requests = calls.len() as u64;
+    let errors = calls.iter().filter(|(success, _)| !success).count() as u64;
+    let mut latencies: Vec<u64> = calls.iter().map(|(_, latency)| *latency).collect();
+    latencies.sort_unstable();
+    let p95_latency_ms = match latencies.len() {
+        0 => None,
+        n => Some(latencies[(n * 95).div_ceil(100) - 1]),
+    };
+    CanaryVariantStats {
+        variant,
+        requests,
+        errors,
+        error_rate: if requests == 0 {
+            0.0
+        } else {
+            errors as f64 / requests as f64
+        },
+        p95_latency_ms,
+    }
+}
+
+/// promote：金丝雀地址写入 servers[0].url，成为主版本
+pub fn promote_swagger_content(swagger_content: &str, base_url: &str) -> Result<String> {
+    let mut spec: Value = serde_json::from_str(swagger_content)?;
+    let servers = spec
+        .as_object_mut()
+        .ok_or_else(|| anyhow!("Invalid swagger content: not an object"))?
+        .entry("servers")
+        .or_insert_with(|| json!([]));
+    match servers.as_array_mut() {
+        Some(servers) if !servers.is_empty() => servers[0]["url"] = json!(base_url),
+        Some(servers) => servers.push(json!({ "url": base_url })),
+        None => *servers = json!([{ "url": base_url }]),
+    }
+    Ok(spec.to_string())
+}
+
+/// 记录金丝雀期间的一次调用，executor 可以是连接池或调用方已获取的连接
+pub async fn record_canary_call<'e, E>(
+    executor: E,
+    endpoint_id: Uuid,
+    variant: CanaryVariant,
+    success: bool,
+    latency: Duration,
+) -> Result<()>
+where
+    E: sqlx::Executor<'e, Database = sqlx::MySql>,
+{
+    sqlx::query(
+        "INSERT INTO endpoint_canary_calls (endpoint_id, variant, success, latency_ms, called_at) VALUES (?, ?, ?, ?, ?)",
+    )
+    .bind(endpoint_id.to_string())
+    .bind(variant.as_str())
+    .bind(success)
+    .bind(latency.as_millis() as u64)
+    .bind(get_china_time())
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+pub struct CanaryService {
+    pool: DbPool,
+}
+
+impl CanaryService {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    /// 启动时加载全部金丝雀配置
+    pub async fn load_all(&self) -> Result<usize> {
+        let rows = sqlx::query(
+            "SELECT endpoint_id, base_url, percentage, updated_at FROM endpoint_canaries",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        canary_configs().clear();
+        for row in &rows {
+            let config = config_from_row(row)?;
+            canary_configs().insert(config.endpoint_id, config);
+        }
+        Ok(rows.len())
+    }
+
+    pub async fn get(&self, endpoint_id: Uuid) -> Result<Option<CanaryConfig>> {
+        let row = sqlx::query(
+            "SELECT endpoint_id, base_url, percentage, updated_at FROM endpoint_canaries WHERE endpoint_id = ?",
+        )
+        .bind(endpoint_id.to_string())
+        .fetch_optional(&self.pool)
+        .await?;
+        row.as_ref().map(config_from_row).transpose()
+    }
+
+    /// 创建或修改金丝雀配置，比例变化对后续调用立即生效
+    pub async fn upsert(
+        &self,
+        endpoint_id: Uuid,
+        base_url: &str,
+        percentage: f64,
+    ) -> Result<CanaryConfig> {
+        if !(0.0..=100.0).contains(&percentage) {
+            return Err(anyhow!(
+                "Invalid canary config: percentage must be between 0 and 100"
+            ));
+        }
+        let parsed = reqwest::Url::parse(base_url)
+            .map_err(|e| anyhow!("Invalid canary config: base_url {}", e))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err(anyhow!(
+                "Invalid canary config: base_url must be http or https"
+            ));
+        }
+        sqlx::query(
+            "INSERT INTO endpoint_canaries (endpoint_id, base_url, percentage, updated_at) VALUES (?, ?, ?, ?) ON DUPLICATE KEY UPDATE base_url = VALUES(base_url), percentage = VALUES(percentage), updated_at = VALUES(updated_at)",
+        )
+        .bind(endpoint_id.to_string())
+        .bind(base_url)
+        .bind(percentage)
+        .bind(get_china_time())
+        .execute(&self.pool)
+        .await?;
+        let config = self
+            .get(endpoint_id)
+            .await?
+            .ok_or_else(|| anyhow!("Canary for endpoint {} not found", endpoint_id))?;
+        canary_configs().insert(endpoint_id, config.clone());
+        Ok(config)
+    }
+
+    /// abort 与 promote 都会删除配置，之后的调用全部走主版本；调用记录保留用于事后对比
+    pub async fn delete(&self, endpoint_id: Uuid) -> Result<Option<CanaryConfig>> {
+        let config = self.get(endpoint_id).await?;
+        sqlx::query("DELETE FROM endpoint_canaries WHERE endpoint_id = ?")
+            .bind(endpoint_id.to_string())
+            .execute(&self.pool)
+            .await?;
+        canary_configs().remove(&endpoint_id);
+        Ok(config)
+    }
+
+    /// 对比窗口内主版本与金丝雀的请求数、错误率与 p95 延迟
+    pub async fn stats(&self, endpoint_id: Uuid, window_secs: u64) -> Result<CanaryStats> {
+        let since = get_china_time() - chrono::Duration::seconds(window_secs as i64);
+        let rows = sqlx::query(
+            "SELECT variant, success, latency_ms FROM endpoint_canary_calls WHERE endpoint_id = ? AND called_at >= ?",
+        )
+        .bind(endpoint_id.to_string())
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut primary = Vec::new();
+        let mut canary = Vec::new();
+        for row in rows {
+            let variant: String = row.try_get("variant")?;
+            let call = (
+                row.try_get::<bool, _>("success")?,
+                row.try_get::<u64, _>("latency_ms")?,
+            );
+            if variant == CanaryVariant::Canary.as_str() {
+                canary.push(call);
+            } else {
+                primary.push(call);
+            }
+        }
+        Ok(CanaryStats {
+            endpoint_id,
+            window_secs,
+            config: self.get(endpoint_id).await?,
+            primary: variant_stats(CanaryVariant::Primary, &primary),
+            canary: variant_stats(CanaryVariant::Canary, &canary),
+        })
+    }
+}
+
+fn config_from_row(row: &sqlx::mysql::MySqlRow) -> Result<CanaryConfig> {
+    let endpoint_id: String = row.try_get("endpoint_id")?;
+    Ok(CanaryConfig {
+        endpoint_id: Uuid::parse_str(&endpoint_id)?,
+        base_url: row.try_get("base_url")?,
+        percentage: row.try_get("percentage")?,
+        updated_at: row.try_get("updated_at")?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(percentage: f64) -> CanaryConfig {
+        CanaryConfig {
+            // 固定端点 id，分桶结果在每次运行中一致
+            endpoint_id: Uuid::from_u128(0x2207),
+            base_url: "http://canary.internal".to_string(),
+            percentage,
+            updated_at: get_china_time(),
+        }
+    }
+
+    #[test]
+    fn test_ten_percent_split_within_tolerance() {
+        let config = config(10.0);
+        let canary = (0..200)
+            .map(|i| choose_variant(&config, Some(&format!("session-{}", i)), None))
+            .filter(|variant| *variant == CanaryVariant::Canary)
+            .count();
+        // 期望 20 次，标准差约 4.2，允许 ±3 个标准差
+        assert!((8..=32).contains(&canary), "canary calls: {}", canary);
+
+        let unsessioned = (0..200)
+            .map(|_| choose_variant(&config, None, None))
+            .filter(|variant| *variant == CanaryVariant::Canary)
+            .count();
+        // 无会话时随机分桶，放宽到约 ±5 个标准差避免偶发失败
+        assert!(
+            (1..=41).contains(&unsessioned),
+            "canary calls: {}",
+            unsessioned
+        );
+
+        assert!((0..200).all(|i| {
+            choose_variant(&config(0.0), Some(&i.to_string()), None) == CanaryVariant::Primary
+        }));
+        assert!((0..200).all(|i| {
+            choose_variant(&config(100.0), Some(&i.to_string()), None) == CanaryVariant::Canary
+        }));
+    }
+
+    #[test]
+    fn test_assignment_is_sticky_per_session() {
+        let mut config = config(10.0);
+        for i in 0..50 {
+            let session = format!("session-{}", i);
+            let first = choose_variant(&config, Some(&session), None);
+            assert!((0..20).all(|_| choose_variant(&config, Some(&session), None) == first));
+        }
+
+        // 调高比例后，原先在金丝雀的会话保持不变
+        let sessions: Vec<String> = (0..200).map(|i| format!("session-{}", i)).collect();
+        let before: Vec<CanaryVariant> = sessions
+            .iter()
+            .map(|s| choose_variant(&config, Some(s), None))
+            .collect();
+        config.percentage = 50.0;
+        for (session, before) in sessions.iter().zip(before) {
+            if before == CanaryVariant::Canary {
+                assert_eq!(
+                    choose_variant(&config, Some(session), None),
+                    CanaryVariant::Canary
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_forced_override() {
+        let (arguments, forced) = take_canary_override(&json!({"name": "Rex", "_canary": true}));
+        assert_eq!(arguments, json!({"name": "Rex"}));
+        assert_eq!(forced, Some(true));
+        assert_eq!(
+            choose_variant(&config(0.0), Some("s"), forced),
+            CanaryVariant::Canary
+        );
+        assert_eq!(
+            choose_variant(&config(100.0), Some("s"), Some(false)),
+            CanaryVariant::Primary
+        );
+
+        let (arguments, forced) = take_canary_override(&json!({"name": "Rex"}));
+        assert_eq!(arguments, json!({"name": "Rex"}));
+        assert_eq!(forced, None);
+    }
+
+    #[test]
+    fn test_variant_stats() {
+        // 20 次调用，延迟 10..=200ms，其中 3 次失败
+        let calls: Vec<(bool, u64)> = (1..=20).map(|i| (i % 7 != 0 && i != 20, i * 10)).collect();
+        let stats = variant_stats(CanaryVariant::Canary, &calls);
+        assert_eq!(stats.requests, 20);
+        assert_eq!(stats.errors, 3);
+        assert!((stats.error_rate - 0.15).abs() < f64::EPSILON);
+        // 最近秩：ceil(0.95 * 20) = 19
+        assert_eq!(stats.p95_latency_ms, Some(190));
+
+        let single = variant_stats(CanaryVariant::Primary, &[(true, 42)]);
+        assert_eq!(single.p95_latency_ms, Some(42));
+        assert_eq!(single.error_rate, 0.0);
+
+        let empty = variant_stats(CanaryVariant::Primary, &[]);
+        assert_eq!(empty.requests, 0);
+        assert_eq!(empty.error_rate, 0.0);
+        assert_eq!(empty.p95_latency_ms, None);
+    }
+
+    #[test]
+    fn test_promote_swagger_content() {
+        let spec = json!({"openapi": "3.0.0", "servers": [{"url": "http://old"}, {"url": "http://backup"}]});
+        let promoted: Value = serde_json::from_str(
+            &promote_swagger_content(&spec.to_string(), "http://canary.internal").unwrap(),
+        )
+        .unwrap();
+        assert_eq!(promoted["servers"][0]["url"], "http://canary.internal");
+        assert_eq!(promoted["servers"][1]["url"], "http://backup");
+
+        let promoted: Value = serde_json::from_str(
+            &promote_swagger_content(r#"{"openapi": "3.0.0"}"#, "http://canary.internal").unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            promoted["servers"],
+            json!([{"url": "http://canary.internal"}])
+        );
+    }
+}
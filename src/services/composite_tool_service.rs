@@ -0,0 +1,504 @@
+use crate::models::{
+    CompositeStep, CompositeTool, CompositeToolDefinition, DbPool, McpTool, SwaggerSpec,
+};
+use crate::services::SpecCache;
+use crate::utils::{generate_mcp_tools, get_china_time};
+use anyhow::{anyhow, Result};
+use serde_json::{json, Map, Value};
+use sqlx::mysql::MySqlRow;
+use sqlx::Row;
+use std::collections::{BTreeSet, HashSet};
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// 组合工具服务
+pub struct CompositeToolService {
+    pool: DbPool,
+    /// 修改后失效工具列表版本
+    spec_cache: Arc<SpecCache>,
+}
+
+impl CompositeToolService {
+    pub fn new(pool: DbPool, spec_cache: Arc<SpecCache>) -> Self {
+        Self { pool, spec_cache }
+    }
+
+    pub async fn list(&self, endpoint_id: Uuid) -> Result<Vec<CompositeTool>> {
+        list_composite_tools(&self.pool, endpoint_id).await
+    }
+
+    pub async fn create(
+        &self,
+        endpoint_id: Uuid,
+        definition: CompositeToolDefinition,
+    ) -> Result<CompositeTool> {
+        let tools = self.endpoint_tools(endpoint_id).await?;
+        validate_definition(&definition, &tools)?;
+
+        let id = Uuid::new_v4();
+        let now = get_china_time();
+        sqlx::query(
+            "INSERT INTO composite_tools (id, endpoint_id, name, description, steps, result_template, created_at, updated_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(id.to_string())
+        .bind(endpoint_id.to_string())
+        .bind(&definition.name)
+        .bind(&definition.description)
+        .bind(serde_json::to_string(&definition.steps)?)
+        .bind(definition.result.as_ref().map(Value::to_string))
+        .bind(now)
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+        // 工具列表随之变化，重算 tools/list 版本
+        self.spec_cache.invalidate_tools_version(&endpoint_id);
+
+        self.get(endpoint_id, id).await
+    }
+
+    pub async fn update(
+        &self,
+        endpoint_id: Uuid,
+        id: Uuid,
+        definition: CompositeToolDefinition,
+    ) -> Result<CompositeTool> {
+        let tools = self.endpoint_tools(endpoint_id).await?;
+        validate_definition(&definition, &tools)?;
+
+        let result = sqlx::query(
+            "UPDATE composite_tools SET name = ?, description = ?, steps = ?, result_template = ?, updated_at = ? WHERE id = ? AND endpoint_id = ?",
+        )
+        .bind(&definition.name)
+        .bind(&definition.description)
+        .bind(serde_json::to_string(&definition.steps)?)
+        .bind(definition.result.as_ref().map(Value::to_string))
+        .bind(get_china_time())
+        .bind(id.to_string())
+        .bind(endpoint_id.to_string())
+        .execute(&self.pool)
+        .await?;
+        if result.rows_affected() == 0 {
+            return Err(anyhow!("Composite tool not found"));
+        }
+        self.spec_cache.invalidate_tools_version(&endpoint_id);
+
+        self.get(endpoint_id, id).await
+    }
+
+    pub async fn delete(&self, endpoint_id: Uuid, id: Uuid) -> Result<()> {
+        let result = sqlx::query("DELETE FROM composite_tools WHERE id = ? AND endpoint_id = ?")
+            .bind(id.to_string())
+            .bind(endpoint_id.to_string())
+            .execute(&self.pool)
+            .await?;
+        if result.rows_affected() == 0 {
+            return Err(anyhow!("Composite tool not found"));
+        }
+        self.spec_cache.invalidate_tools_version(&endpoint_id);
+        Ok(())
+    }
+
+    async fn get(&self, endpoint_id: Uuid, id: Uuid) -> Result<CompositeTool> {
+        let row = sqlx::query(
+            "SELECT id, endpoint_id, name, description, steps, result_template, created_at, updated_at FROM composite_tools WHERE id = ? AND endpoint_id = ?",
+        )
+        .bind(id.to_string())
+        .bind(endpoint_id.to_string())
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| anyhow!("Composite tool not found"))?;
+        composite_from_row(&row)
+    }
+
+    /// 端点 swagger 生成的工具名
+    async fn endpoint_tools(&self, endpoint_id: Uuid) -> Result<HashSet<String>> {
+        let row = sqlx::query("SELECT swagger_content FROM endpoints WHERE id = ?")
+            .bind(endpoint_id.to_string())
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or_else(|| anyhow!("Endpoint not found"))?;
+        let content: String = row.try_get("swagger_content")?;
+        let spec: SwaggerSpec = serde_json::from_str(&content)?;
+        Ok(generate_mcp_tools(&spec)?
+            .into_iter()
+            .map(|tool| tool.name)
+            .collect())
+    }
+}
+
+pub async fn list_composite_tools(pool: &DbPool, endpoint_id: Uuid) -> Result<Vec<CompositeTool>> {
+    let rows = sqlx::query(
+        "SELECT id, endpoint_id, name, description, steps, result_template, created_at, updated_at FROM composite_tools WHERE endpoint_id = ? ORDER BY name",
+    )
+    .bind(endpoint_id.to_string())
+    .fetch_all(pool)
+    .await?;
+    rows.iter().map(composite_from_row).collect()
+}
+
+/// 记录组合工具单个步骤的调用日志
+#[allow(clippy::too_many_arguments)]
+pub async fn log_composite_step(
+    pool: &DbPool,
+    endpoint_id: Uuid,
+    request_id: Uuid,
+    method: &str,
+    path: &str,
+    status_code: Option<u16>,
+    response_time_ms: u64,
+    request_body: &Value,
+    response_body: Option<&Value>,
+    error_message: Option<&str>,
+) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO endpoint_logs (id, endpoint_id, request_id, method, path, status_code, response_time_ms, request_body, response_body, error_message) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(Uuid::new_v4().to_string())
+    .bind(endpoint_id.to_string())
+    .bind(request_id.to_string())
+    .bind(method)
+    .bind(path)
+    .bind(status_code.map(i32::from))
+    .bind(response_time_ms as i64)
+    .bind(request_body.to_string())
+    .bind(response_body.map(Value::to_string))
+    .bind(error_message)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// 保存前校验：名称不与端点工具冲突，步骤引用的工具存在，
+/// 表达式可编译，且只引用前序步骤（禁止自引用、前向引用即环）
+pub fn validate_definition(
+    definition: &CompositeToolDefinition,
+    endpoint_tools: &HashSet<String>,
+) -> Result<()> {
+    if definition.name.trim().is_empty() {
+        return Err(anyhow!("Invalid composite tool: name cannot be empty"));
+    }
+    if endpoint_tools.contains(&definition.name) {
+        return Err(anyhow!(
+            "Invalid composite tool: name '{}' conflicts with an existing tool",
+            definition.name
+        ));
+    }
+    if definition.steps.is_empty() {
+        return Err(anyhow!(
+            "Invalid composite tool: at least one step is required"
+        ));
+    }
+
+    let mut defined: HashSet<&str> = HashSet::new();
+    for step in &definition.steps {
+        if step.id.trim().is_empty() {
+            return Err(anyhow!("Invalid composite tool: step id cannot be empty"));
+        }
+        if defined.contains(step.id.as_str()) {
+            return Err(anyhow!(
+                "Invalid composite tool: duplicate step '{}'",
+                step.id
+            ));
+        }
+        if !endpoint_tools.contains(&step.tool) {
+            return Err(anyhow!(
+                "Invalid composite tool: step '{}' references unknown tool '{}'",
+                step.id,
+                step.tool
+            ));
+        }
+        check_references(&step.arguments, &defined, &format!("step '{}'", step.id))?;
+        defined.insert(step.id.as_str());
+    }
+    if let Some(result) = &definition.result {
+        check_references(result, &defined, "result")?;
+    }
+    Ok(())
+}
+
+fn check_references(template: &Value, defined: &HashSet<&str>, location: &str) -> Result<()> {
+    for expression in template_expressions(template) {
+        jmespath::compile(&expression).map_err(|e| {
+            anyhow!(
+                "Invalid composite tool: {} has invalid expression '{}': {}",
+                location,
+                expression,
+                e
+            )
+        })?;
+        for reference in step_references(&expression) {
+            if !defined.contains(reference.as_str()) {
+                return Err(anyhow!(
+                    "Invalid composite tool: {} references undefined or later step '{}'",
+                    location,
+                    reference
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 模板中出现的全部表达式
+fn template_expressions(template: &Value) -> Vec<String> {
+    let mut expressions = Vec::new();
+    collect_expressions(template, &mut expressions);
+    expressions
+}
+
+fn collect_expressions(template: &Value, expressions: &mut Vec<String>) {
+    match template {
+        Value::String(s) => {
+            let mut rest = s.as_str();
+            while let Some(start) = rest.find("{{") {
+                match rest[start + 2..].find("}}") {
+                    Some(end) => {
+                        expressions.push(rest[start + 2..start + 2 + end].trim().to_string());
+                        rest = &rest[start + 2 + end + 2..];
+                    }
+                    None => break,
+                }
+            }
+        }
+        Value::Array(items) => items
+            .iter()
+            .for_each(|item| collect_expressions(item, expressions)),
+        Value::Object(map) => map
+            .values()
+            .for_each(|item| collect_expressions(item, expressions)),
+        _ => {}
+    }
+}
+
+/// 表达式中 prefix 之后的标识符，如 steps.user.profile -> user
+fn identifiers_after(expression: &str, prefix: &str) -> Vec<String> {
+    let mut identifiers = Vec::new();
+    let mut rest = expression;
+    while let Some(index) = rest.find(prefix) {
+        let preceded_by_ident = rest[..index]
+            .chars()
+            .last()
+            .is_some_and(|c| c.is_alphanumeric() || c == '_' || c == '.');
+        rest = &rest[index + prefix.len()..];
+        let identifier: String = rest
+            .chars()
+            .take_while(|c| c.is_alphanumeric() || *c == '_')
+            .collect();
+        if !preceded_by_ident && !identifier.is_empty() {
+            identifiers.push(identifier);
+        }
+    }
+    identifiers
+}
+
+fn step_references(expression: &str) -> Vec<String> {
+    identifiers_after(expression, "steps.")
+}
+
+/// 模板中引用的输入变量（input.<name>），作为组合工具的 inputSchema
+pub fn input_variables(definition: &CompositeToolDefinition) -> BTreeSet<String> {
+    definition
+        .steps
+        .iter()
+        .map(|step| &step.arguments)
+        .chain(definition.result.as_ref())
+        .flat_map(template_expressions)
+        .flat_map(|expression| identifiers_after(&expression, "input."))
+        .collect()
+}
+
+/// 组合工具在 tools/list 中的描述
+pub fn composite_to_mcp_tool(composite: &CompositeTool) -> McpTool {
+    let variables = input_variables(&composite.definition());
+    let properties: Map<String, Value> = variables
+        .iter()
+        .map(|name| (name.clone(), json!({})))
+        .collect();
+    let steps: Vec<&str> = composite.steps.iter().map(|s| s.tool.as_str()).collect();
+    McpTool {
+        name: composite.name.clone(),
+        title: composite.name.clone(),
+        description: composite
+            .description
+            .clone()
+            .unwrap_or_else(|| format!("Composite of: {}", steps.join(" -> "))),
+        input_schema: json!({
+            "type": "object",
+            "properties": properties,
+            "required": variables,
+        }),
+        output_schema: None,
+        nested_input_schema: None,
+        warnings: Vec::new(),
+    }
+}
+
+/// 渲染模板：整串为 "{{ expr }}" 时替换为表达式结果（保留类型），否则按字符串插值
+pub fn render_template(template: &Value, context: &Value) -> Result<Value> {
+    match template {
+        Value::String(s) => {
+            let trimmed = s.trim();
+            if trimmed.starts_with("{{")
+                && trimmed.ends_with("}}")
+                && trimmed[2..].find("}}") == Some(trimmed.len() - 4)
+            {
+                return evaluate(trimmed[2..trimmed.len() - 2].trim(), context);
+            }
+            let mut rendered = String::new();
+            let mut rest = s.as_str();
+            while let Some(start) = rest.find("{{") {
+                let Some(end) = rest[start + 2..].find("}}") else {
+                    break;
+                };
+                rendered.push_str(&rest[..start]);
+                match evaluate(rest[start + 2..start + 2 + end].trim(), context)? {
+                    Value::String(value) => rendered.push_str(&value),
+                    Value::Null => {}
+                    value => rendered.push_str(&value.to_string()),
+                }
+                rest = &rest[start + 2 + end + 2..];
+            }
+            rendered.push_str(rest);
+            Ok(Value::String(rendered))
+        }
+        Value::Array(items) => Ok(Value::Array(
+            items
+                .iter()
+                .map(|item| render_template(item, context))
+                .collect::<Result<_>>()?,
+        )),
+        Value::Object(map) => Ok(Value::Object(
+            map.iter()
+                .map(|(key, item)| Ok((key.clone(), render_template(item, context)?)))
+                .collect::<Result<_>>()?,
+        )),
+        other => Ok(other.clone()),
+    }
+}
+
+fn evaluate(expression: &str, context: &Value) -> Result<Value> {
+    let compiled = jmespath::compile(expression)
+        .map_err(|e| anyhow!("Invalid expression '{}': {}", expression, e))?;
+    let result = compiled
+        .search(context.clone())
+        .map_err(|e| anyhow!("Failed to evaluate '{}': {}", expression, e))?;
+    Ok(serde_json::to_value(&*result)?)
+}
+
+/// 步骤执行结果是否失败（上游非 2xx）
+pub fn step_failed(output: &Value) -> bool {
+    output.get("success").and_then(Value::as_bool) == Some(false)
+}
+
+/// 步骤输出，仅保留上游响应体供后续步骤引用
+pub fn step_output(output: &Value) -> Value {
+    output.get("response").cloned().unwrap_or(Value::Null)
+}
+
+fn composite_from_row(row: &MySqlRow) -> Result<CompositeTool> {
+    let id: String = row.try_get("id")?;
+    let endpoint_id: String = row.try_get("endpoint_id")?;
+    let steps: String = row.try_get("steps")?;
+    let result: Option<String> = row.try_get("result_template")?;
+    Ok(CompositeTool {
+        id: Uuid::parse_str(&id)?,
+        endpoint_id: Uuid::parse_str(&endpoint_id)?,
+        name: row.try_get("name")?,
+        description: row.try_get("description")?,
+        steps: serde_json::from_str::<Vec<CompositeStep>>(&steps)?,
+        result: result.map(|r| serde_json::from_str(&r)).transpose()?,
+        created_at: row.try_get("created_at")?,
+        updated_at: row.try_get("updated_at")?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn definition() -> CompositeToolDefinition {
+        serde_json::from_value(json!({
+            "name": "notifyUserEmail",
+            "description": null,
+            "steps": [
+                {"id": "user", "tool": "getUser", "arguments": {"id": "{{ input.user_id }}"}},
+                {"id": "notify", "tool": "notify", "arguments": {
+                    "body": {"email": "{{ steps.user.profile.email }}", "text": "Hi {{ steps.user.name }}: {{ input.message }}"}
+                }}
+            ],
+            "result": {"sent_to": "{{ steps.user.profile.email }}", "status": "{{ steps.notify.status }}"}
+        }))
+        .unwrap()
+    }
+
+    fn tools() -> HashSet<String> {
+        ["getUser", "notify"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_validate_definition() {
+        assert!(validate_definition(&definition(), &tools()).is_ok());
+
+        // 引用未定义步骤
+        let mut undefined = definition();
+        undefined.steps[1].arguments = json!({"email": "{{ steps.missing.email }}"});
+        let err = validate_definition(&undefined, &tools()).unwrap_err();
+        assert!(err.to_string().contains("'missing'"));
+
+        // 引用后续步骤（环）
+        let mut cycle = definition();
+        cycle.steps[0].arguments = json!({"id": "{{ steps.notify.id }}"});
+        assert!(validate_definition(&cycle, &tools()).is_err());
+
+        // 自引用
+        let mut self_ref = definition();
+        self_ref.steps[0].arguments = json!({"id": "{{ steps.user.id }}"});
+        assert!(validate_definition(&self_ref, &tools()).is_err());
+
+        let mut unknown_tool = definition();
+        unknown_tool.steps[0].tool = "deleteEverything".to_string();
+        assert!(validate_definition(&unknown_tool, &tools()).is_err());
+
+        let mut conflict = definition();
+        conflict.name = "getUser".to_string();
+        assert!(validate_definition(&conflict, &tools()).is_err());
+    }
+
+    #[test]
+    fn test_render_chained_templates() {
+        let context = json!({
+            "input": {"user_id": 7, "message": "welcome"},
+            "steps": {"user": {"name": "Ann", "profile": {"email": "ann@example.com"}}}
+        });
+        let definition = definition();
+
+        let first = render_template(&definition.steps[0].arguments, &context).unwrap();
+        assert_eq!(first, json!({"id": 7}));
+
+        let second = render_template(&definition.steps[1].arguments, &context).unwrap();
+        assert_eq!(
+            second,
+            json!({"body": {"email": "ann@example.com", "text": "Hi Ann: welcome"}})
+        );
+    }
+
+    #[test]
+    fn test_input_schema_from_unbound_variables() {
+        let composite = CompositeTool {
+            id: Uuid::new_v4(),
+            endpoint_id: Uuid::new_v4(),
+            name: "notifyUserEmail".to_string(),
+            description: None,
+            steps: definition().steps,
+            result: definition().result,
+            created_at: get_china_time(),
+            updated_at: get_china_time(),
+        };
+        let tool = composite_to_mcp_tool(&composite);
+        assert_eq!(tool.input_schema["required"], json!(["message", "user_id"]));
+        assert_eq!(tool.description, "Composite of: getUser -> notify");
+    }
+}
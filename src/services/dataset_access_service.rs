@@ -0,0 +1,193 @@
+use crate::config::DatasetAccessConfig;
+use crate::models::table_rag::{
+    CreateDatasetTokenRequest, CreatedDatasetToken, DatasetAccessError, DatasetCaller,
+    DatasetToken, RowFilter,
+};
+use crate::models::DbPool;
+use crate::utils::get_china_time;
+use anyhow::{anyhow, Result};
+use sha2::{Digest, Sha256};
+use sqlx::Row;
+use uuid::Uuid;
+
+/// 访问令牌前缀，便于在日志与配置中识别
+const TOKEN_PREFIX: &str = "dst_";
+
+pub fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+fn generate_token() -> String {
+    format!(
+        "{}{}{}",
+        TOKEN_PREFIX,
+        Uuid::new_v4().simple(),
+        Uuid::new_v4().simple()
+    )
+}
+
+/// 列名与值均不能为空
+pub fn validate_row_filter(filter: &RowFilter) -> Result<()> {
+    for (column, value) in filter {
+        if column.trim().is_empty() || value.trim().is_empty() {
+            return Err(anyhow!(
+                "Invalid row filter: column and value must not be empty"
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// 数据集访问令牌的管理与调用方识别
+pub struct DatasetAccessService {
+    pool: DbPool,
+    config: DatasetAccessConfig,
+}
+
+impl DatasetAccessService {
+    pub fn new(pool: DbPool, config: DatasetAccessConfig) -> Self {
+        Self { pool, config }
+    }
+
+    pub fn token_header(&self) -> &str {
+        &self.config.token_header
+    }
+
+    /// 创建令牌，明文只在此返回一次
+    pub async fn create_token(
+        &self,
+        dataset_id: Uuid,
+        request: CreateDatasetTokenRequest,
+    ) -> Result<CreatedDatasetToken> {
+        let name = request.name.trim();
+        if name.is_empty() {
+            return Err(anyhow!("Invalid token: name must not be empty"));
+        }
+        if let Some(filter) = &request.row_filter {
+            validate_row_filter(filter)?;
+        }
+        let exists: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM t_dataset WHERE id = ?")
+            .bind(dataset_id.to_string())
+            .fetch_one(&self.pool)
+            .await?;
+        if exists == 0 {
+            return Err(anyhow!("Dataset {} not found", dataset_id));
+        }
+
+        let token = generate_token();
+        let info = DatasetToken {
+            id: Uuid::new_v4(),
+            dataset_id,
+            name: name.to_string(),
+            row_filter: request.row_filter,
+            create_time: get_china_time(),
+        };
+        sqlx::query(
+            "INSERT INTO t_dataset_token (id, dataset_id, name, token_hash, row_filter, create_time) VALUES (?, ?, ?, ?, ?, ?)",
+        )
+        .bind(info.id.to_string())
+        .bind(dataset_id.to_string())
+        .bind(&info.name)
+        .bind(hash_token(&token))
+        .bind(info.row_filter.as_ref().map(serde_json::to_string).transpose()?)
+        .bind(info.create_time)
+        .execute(&self.pool)
+        .await?;
+        Ok(CreatedDatasetToken { token, info })
+    }
+
+    pub async fn list_tokens(&self, dataset_id: Uuid) -> Result<Vec<DatasetToken>> {
+        let rows = sqlx::query(
+            "SELECT id, dataset_id, name, row_filter, create_time FROM t_dataset_token WHERE dataset_id = ? ORDER BY create_time",
+        )
+        .bind(dataset_id.to_string())
+        .fetch_all(&self.pool)
+        .await?;
+        rows.iter().map(token_from_row).collect()
+    }
+
+    /// 吊销令牌，之后使用该令牌的检索返回 401
+    pub async fn revoke_token(&self, dataset_id: Uuid, token_id: Uuid) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM t_dataset_token WHERE id = ? AND dataset_id = ?")
+            .bind(token_id.to_string())
+            .bind(dataset_id.to_string())
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// 识别调用方：令牌优先，其次为管理员凭据，再次为配置了过滤的 API key；
+    /// 都没有时为匿名调用，不能检索设置了安全列的数据集
+    pub async fn identify_caller(
+        &self,
+        dataset_id: Uuid,
+        token: Option<&str>,
+        api_key: Option<&str>,
+        admin: bool,
+    ) -> Result<DatasetCaller> {
+        if let Some(token) = token {
+            let row = sqlx::query(
+                "SELECT id, dataset_id, name, row_filter, create_time FROM t_dataset_token WHERE token_hash = ?",
+            )
+            .bind(hash_token(token))
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or(DatasetAccessError::InvalidToken)?;
+            let token = token_from_row(&row)?;
+            if token.dataset_id != dataset_id {
+                return Err(DatasetAccessError::WrongDataset(dataset_id).into());
+            }
+            return Ok(DatasetCaller::Restricted(
+                token.row_filter.unwrap_or_default(),
+            ));
+        }
+        if admin {
+            return Ok(DatasetCaller::Admin);
+        }
+        Ok(api_key
+            .and_then(|key| self.config.api_keys.get(key).cloned())
+            .map_or(DatasetCaller::Anonymous, DatasetCaller::Restricted))
+    }
+}
+
+fn token_from_row(row: &sqlx::mysql::MySqlRow) -> Result<DatasetToken> {
+    let id: String = row.try_get("id")?;
+    let dataset_id: String = row.try_get("dataset_id")?;
+    let row_filter: Option<String> = row.try_get("row_filter")?;
+    Ok(DatasetToken {
+        id: Uuid::parse_str(&id)?,
+        dataset_id: Uuid::parse_str(&dataset_id)?,
+        name: row.try_get("name")?,
+        row_filter: row_filter
+            .map(|filter| serde_json::from_str(&filter))
+            .transpose()?,
+        create_time: row.try_get("create_time")?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generated_tokens_are_unique_and_hashed() {
+        let first = generate_token();
+        let second = generate_token();
+        assert!(first.starts_with(TOKEN_PREFIX));
+        assert_eq!(first.len(), TOKEN_PREFIX.len() + 64);
+        assert_ne!(first, second);
+        assert_eq!(hash_token(&first).len(), 64);
+        assert_ne!(hash_token(&first), hash_token(&second));
+    }
+
+    #[test]
+    fn test_validate_row_filter() {
+        let filter: RowFilter = [("org_id".to_string(), "acme".to_string())].into();
+        assert!(validate_row_filter(&filter).is_ok());
+        let filter: RowFilter = [("org_id".to_string(), " ".to_string())].into();
+        assert!(validate_row_filter(&filter)
+            .unwrap_err()
+            .to_string()
+            .starts_with("Invalid row filter"));
+    }
+}
@@ -0,0 +1,455 @@
+use crate::config::DemoConfig;
+use crate::models::table_rag::{CreateDatasetTokenRequest, RowFilter};
+use crate::models::{
+    CreateDatasetRequest, CreateEndpointRequest, DatasetType, DbPool, DemoEntity, DemoEntityKind,
+    DemoSeedResponse, DemoTeardownResponse, Endpoint, EndpointStatus, UpdateEndpointRequest,
+};
+use crate::services::{
+    validate_dataset_schema, DatasetAccessService, EndpointService, FileService, TableRagService,
+};
+use crate::utils::get_china_time;
+use anyhow::{anyhow, Result};
+use serde_json::json;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// 演示端点带有该标签，同名但无此标签的端点视为用户数据，不会被覆盖或删除
+pub const DEMO_TAG: &str = "demo";
+pub const DEMO_DATASET_NAME: &str = "demo-products";
+pub const DEMO_TABLE_NAME: &str = "demo_products";
+/// 演示令牌只能检索 visibility 为 public 的行
+pub const DEMO_TOKEN_NAME: &str = "demo-public-readonly";
+
+/// 每个新建的演示端点写入的合成调用日志条数
+const SYNTHETIC_CALLS_PER_ENDPOINT: usize = 24;
+
+const DEMO_PRODUCTS_CSV: &str = include_str!("../../fixtures/demo/products.csv");
+
+struct DemoEndpoint {
+    name: &'static str,
+    description: &'static str,
+    spec: &'static str,
+    /// 合成调用日志轮流使用的 (method, path, arguments)
+    calls: &'static [(&'static str, &'static str, &'static str)],
+}
+
+const DEMO_ENDPOINTS: &[DemoEndpoint] = &[
+    DemoEndpoint {
+        name: "demo-petstore",
+        description: "演示端点：宠物商店，无需鉴权",
+        spec: include_str!("../../fixtures/demo/petstore.json"),
+        calls: &[
+            ("GET", "/pet/findByStatus", r#"{"status":"available"}"#),
+            ("GET", "/pet/{petId}", r#"{"petId":1}"#),
+        ],
+    },
+    DemoEndpoint {
+        name: "demo-orders",
+        description: "演示端点：订单服务，需在 X-Api-Key 请求头中携带密钥",
+        spec: include_str!("../../fixtures/demo/orders.json"),
+        calls: &[
+            ("GET", "/orders", r#"{"status":"paid"}"#),
+            ("GET", "/orders/{orderId}", r#"{"orderId":"SO-1001"}"#),
+            ("POST", "/orders", r#"{"sku":"DEMO-002","quantity":1}"#),
+        ],
+    },
+];
+
+/// 未开启 demo.seed_enabled
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[error("Demo seeding is disabled, set demo.seed_enabled = true to enable it")]
+pub struct DemoSeedingDisabled;
+
+/// 第 index 条合成调用的状态码与耗时：大部分成功，夹杂少量 404 与 500
+fn synthetic_outcome(index: usize) -> (u16, i64) {
+    let status = match index % 12 {
+        5 => 404,
+        11 => 500,
+        _ => 200,
+    };
+    (status, 40 + (index as i64 * 37) % 260)
+}
+
+fn demo_entity(kind: DemoEntityKind, id: Uuid, name: &str) -> DemoEntity {
+    DemoEntity {
+        kind,
+        id,
+        name: name.to_string(),
+    }
+}
+
+/// 演示环境数据的写入与清除，只处理带演示命名空间的数据
+pub struct DemoSeedService {
+    pool: DbPool,
+    config: DemoConfig,
+    endpoints: Arc<EndpointService>,
+    table_rag: Arc<TableRagService>,
+    files: Arc<FileService>,
+    access: Arc<DatasetAccessService>,
+}
+
+impl DemoSeedService {
+    pub fn new(
+        pool: DbPool,
+        config: DemoConfig,
+        endpoints: Arc<EndpointService>,
+        table_rag: Arc<TableRagService>,
+        files: Arc<FileService>,
+        access: Arc<DatasetAccessService>,
+    ) -> Self {
+        Self {
+            pool,
+            config,
+            endpoints,
+            table_rag,
+            files,
+            access,
+        }
+    }
+
+    fn ensure_enabled(&self) -> Result<()> {
+        if !self.config.seed_enabled {
+            return Err(DemoSeedingDisabled.into());
+        }
+        Ok(())
+    }
+
+    /// 同名端点不存在时返回 None；存在但不是演示数据时报冲突
+    async fn find_demo_endpoint(&self, name: &str) -> Result<Option<Endpoint>> {
+        let endpoint = match self.endpoints.get_endpoint_by_name(name.to_string()).await {
+            Ok(endpoint) => endpoint,
+            Err(e)
+                if matches!(
+                    e.downcast_ref::<sqlx::Error>(),
+                    Some(sqlx::Error::RowNotFound)
+                ) =>
+            {
+                return Ok(None)
+            }
+            Err(e) => return Err(e),
+        };
+        if !endpoint.tags.iter().any(|tag| tag == DEMO_TAG) {
+            return Err(anyhow!(
+                "Demo data conflict: endpoint '{}' exists and is not demo data",
+                name
+            ));
+        }
+        Ok(Some(endpoint))
+    }
+
+    /// 名称与表名均匹配才视为演示数据集，只有其一匹配时报冲突
+    async fn find_demo_dataset(&self) -> Result<Option<Uuid>> {
+        let datasets = self.table_rag.list_datasets().await?;
+        let matched: Vec<_> = datasets
+            .iter()
+            .filter(|d| {
+                d.name == DEMO_DATASET_NAME || d.table_name.eq_ignore_ascii_case(DEMO_TABLE_NAME)
+            })
+            .collect();
+        match matched.as_slice() {
+            [] => Ok(None),
+            [dataset]
+                if dataset.name == DEMO_DATASET_NAME
+                    && dataset.table_name.eq_ignore_ascii_case(DEMO_TABLE_NAME) =>
+            {
+                Ok(Some(dataset.id))
+            }
+            _ => Err(anyhow!(
+                "Demo data conflict: dataset '{}' or table '{}' exists and is not demo data",
+                DEMO_DATASET_NAME,
+                DEMO_TABLE_NAME
+            )),
+        }
+    }
+
+    /// 写入演示数据；已存在的演示数据保持不变，可重复调用
+    pub async fn seed(&self) -> Result<DemoSeedResponse> {
+        self.ensure_enabled()?;
+        // 先检查全部冲突，避免写入一半后失败
+        let mut existing_endpoints = Vec::new();
+        for demo in DEMO_ENDPOINTS {
+            existing_endpoints.push(self.find_demo_endpoint(demo.name).await?);
+        }
+        let existing_dataset = self.find_demo_dataset().await?;
+
+        let mut response = DemoSeedResponse {
+            created: Vec::new(),
+            existing: Vec::new(),
+            dataset_token: None,
+            ingested_rows: 0,
+            synthetic_logs: 0,
+        };
+        for (demo, existing) in DEMO_ENDPOINTS.iter().zip(existing_endpoints) {
+            match existing {
+                Some(endpoint) => {
+                    if endpoint.status != EndpointStatus::Running {
+                        self.endpoints.start_endpoint(endpoint.id).await?;
+                    }
+                    response.existing.push(demo_entity(
+                        DemoEntityKind::Endpoint,
+                        endpoint.id,
+                        &endpoint.name,
+                    ));
+                }
+                None => {
+                    let id = self.create_endpoint(demo).await?;
+                    response.synthetic_logs += self.write_synthetic_calls(id, demo).await?;
+                    response
+                        .created
+                        .push(demo_entity(DemoEntityKind::Endpoint, id, demo.name));
+                }
+            }
+        }
+
+        let dataset_id = match existing_dataset {
+            Some(id) => {
+                response
+                    .existing
+                    .push(demo_entity(DemoEntityKind::Dataset, id, DEMO_DATASET_NAME));
+                id
+            }
+            None => {
+                let (id, rows) = self.create_dataset().await?;
+                response.ingested_rows = rows;
+                response
+                    .created
+                    .push(demo_entity(DemoEntityKind::Dataset, id, DEMO_DATASET_NAME));
+                id
+            }
+        };
+
+        let tokens = self.access.list_tokens(dataset_id).await?;
+        match tokens.iter().find(|t| t.name == DEMO_TOKEN_NAME) {
+            Some(token) => response.existing.push(demo_entity(
+                DemoEntityKind::DatasetToken,
+                token.id,
+                &token.name,
+            )),
+            None => {
+                let row_filter =
+                    RowFilter::from([("visibility".to_string(), "public".to_string())]);
+                let created = self
+                    .access
+                    .create_token(
+                        dataset_id,
+                        CreateDatasetTokenRequest {
+                            name: DEMO_TOKEN_NAME.to_string(),
+                            row_filter: Some(row_filter),
+                        },
+                    )
+                    .await?;
+                response.created.push(demo_entity(
+                    DemoEntityKind::DatasetToken,
+                    created.info.id,
+                    DEMO_TOKEN_NAME,
+                ));
+                response.dataset_token = Some(created.token);
+            }
+        }
+
+        tracing::info!(
+            "Seeded demo data: {} created, {} existing",
+            response.created.len(),
+            response.existing.len()
+        );
+        Ok(response)
+    }
+
+    /// 新建演示端点：开启 mock 模式，不依赖外部上游即可调用
+    async fn create_endpoint(&self, demo: &DemoEndpoint) -> Result<Uuid> {
+        let endpoint = self
+            .endpoints
+            .create_endpoint(CreateEndpointRequest {
+                name: demo.name.to_string(),
+                description: Some(demo.description.to_string()),
+                swagger_content: demo.spec.to_string(),
+                tags: vec![DEMO_TAG.to_string()],
+            })
+            .await?;
+        self.endpoints
+            .update_endpoint(
+                endpoint.id,
+                UpdateEndpointRequest {
+                    name: None,
+                    description: None,
+                    swagger_content: None,
+                    status: None,
+                    preferred_content_type: None,
+                    mock_mode: Some(true),
+                    server_variables: None,
+                    status_mapping: None,
+                    tool_stats: None,
+                    hide_deprecated: None,
+                    tags: None,
+                    force_embeddings: false,
+                },
+            )
+            .await?;
+        self.endpoints.start_endpoint(endpoint.id).await?;
+        Ok(endpoint.id)
+    }
+
+    /// 写入过去一天的合成调用日志与对应的端点指标
+    async fn write_synthetic_calls(&self, endpoint_id: Uuid, demo: &DemoEndpoint) -> Result<usize> {
+        let now = get_china_time();
+        let mut errors = 0u64;
+        let mut total_ms = 0i64;
+        let mut tx = self.pool.begin().await?;
+        for index in 0..SYNTHETIC_CALLS_PER_ENDPOINT {
+            let (method, path, arguments) = demo.calls[index % demo.calls.len()];
+            let (status, response_time_ms) = synthetic_outcome(index);
+            if status >= 400 {
+                errors += 1;
+            }
+            total_ms += response_time_ms;
+            let created_at = now - chrono::Duration::hours(index as i64);
+            sqlx::query(
+                "INSERT INTO endpoint_logs (id, endpoint_id, request_id, method, path, status_code, response_time_ms, request_body, error_message, created_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            )
+            .bind(Uuid::new_v4().to_string())
+            .bind(endpoint_id.to_string())
+            .bind(Uuid::new_v4().to_string())
+            .bind(method)
+            .bind(path)
+            .bind(i32::from(status))
+            .bind(response_time_ms)
+            .bind(arguments)
+            .bind((status >= 400).then(|| format!("Upstream returned HTTP {}", status)))
+            .bind(created_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+        let calls = SYNTHETIC_CALLS_PER_ENDPOINT as u64;
+        sqlx::query(
+            "INSERT INTO endpoint_metrics (id, endpoint_id, request_count, response_count, error_count, avg_response_time, current_connections, total_connection_time) VALUES (?, ?, ?, ?, ?, ?, 0, 0)",
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(endpoint_id.to_string())
+        .bind(calls)
+        .bind(calls)
+        .bind(errors)
+        .bind(total_ms as f64 / calls as f64)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(SYNTHETIC_CALLS_PER_ENDPOINT)
+    }
+
+    /// 新建演示数据集并按常规摄取流程写入内置 CSV
+    async fn create_dataset(&self) -> Result<(Uuid, u32)> {
+        let schema = validate_dataset_schema(&json!([
+            {"name": "sku", "type": "string", "description": "商品编号"},
+            {"name": "name", "type": "string", "description": "商品名称", "searchable": true},
+            {"name": "description", "type": "string", "description": "商品描述", "searchable": true},
+            {"name": "category", "type": "string", "description": "分类", "searchable": true},
+            {"name": "price", "type": "double", "description": "价格"},
+            {"name": "visibility", "type": "string", "description": "可见范围：public 或 internal"}
+        ]))?;
+        let dataset = self
+            .table_rag
+            .create_dataset(CreateDatasetRequest {
+                name: DEMO_DATASET_NAME.to_string(),
+                description: Some("演示数据集：商品目录".to_string()),
+                r#type: DatasetType::Upload,
+                table_name: DEMO_TABLE_NAME.to_string(),
+                schema,
+                similarity_threshold: None,
+                max_results: None,
+                retrieval_column: None,
+                reply_column: None,
+                security_column: Some("visibility".to_string()),
+            })
+            .await?;
+        let file = self
+            .files
+            .upload_and_save(
+                &format!("{}.csv", DEMO_TABLE_NAME),
+                DEMO_PRODUCTS_CSV.as_bytes().to_vec(),
+            )
+            .await?;
+        let task_id = self
+            .table_rag
+            .create_ingest_task(dataset.id, file.id)
+            .await?;
+        let rows = self.table_rag.run_ingest_task(task_id).await?;
+        Ok((dataset.id, rows))
+    }
+
+    /// 清除演示数据：端点按级联删除，数据集连同索引、令牌与文件一并删除；非演示数据不受影响
+    pub async fn teardown(&self) -> Result<DemoTeardownResponse> {
+        self.ensure_enabled()?;
+        let mut removed = Vec::new();
+        for demo in DEMO_ENDPOINTS {
+            let endpoint = match self.find_demo_endpoint(demo.name).await {
+                Ok(Some(endpoint)) => endpoint,
+                Ok(None) => continue,
+                Err(e) => {
+                    tracing::warn!("Skipping endpoint {}: {}", demo.name, e);
+                    continue;
+                }
+            };
+            self.endpoints.delete_endpoint(endpoint.id).await?;
+            removed.push(demo_entity(
+                DemoEntityKind::Endpoint,
+                endpoint.id,
+                &endpoint.name,
+            ));
+        }
+
+        match self.find_demo_dataset().await {
+            Ok(Some(dataset_id)) => {
+                for token in self.access.list_tokens(dataset_id).await? {
+                    removed.push(demo_entity(
+                        DemoEntityKind::DatasetToken,
+                        token.id,
+                        &token.name,
+                    ));
+                }
+                if self.table_rag.delete_dataset(dataset_id).await? {
+                    removed.push(demo_entity(
+                        DemoEntityKind::Dataset,
+                        dataset_id,
+                        DEMO_DATASET_NAME,
+                    ));
+                }
+            }
+            Ok(None) => {}
+            Err(e) => tracing::warn!("Skipping dataset {}: {}", DEMO_DATASET_NAME, e),
+        }
+
+        tracing::info!("Removed {} demo entities", removed.len());
+        Ok(DemoTeardownResponse { removed })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::SwaggerSpec;
+
+    #[test]
+    fn test_demo_fixtures_parse() {
+        for demo in DEMO_ENDPOINTS {
+            assert!(demo.name.starts_with("demo-"));
+            let spec: SwaggerSpec = serde_json::from_str(demo.spec).unwrap();
+            for (_, path, arguments) in demo.calls {
+                assert!(spec.paths.contains_key(*path), "{} {}", demo.name, path);
+                serde_json::from_str::<serde_json::Value>(arguments).unwrap();
+            }
+        }
+        let header = DEMO_PRODUCTS_CSV.lines().next().unwrap();
+        assert_eq!(header, "sku,name,description,category,price,visibility");
+        assert!(DEMO_PRODUCTS_CSV.lines().any(|l| l.ends_with(",internal")));
+    }
+
+    #[test]
+    fn test_synthetic_outcome_mixes_errors() {
+        let outcomes: Vec<(u16, i64)> = (0..SYNTHETIC_CALLS_PER_ENDPOINT)
+            .map(synthetic_outcome)
+            .collect();
+        assert!(outcomes.iter().any(|(status, _)| *status == 404));
+        assert!(outcomes.iter().any(|(status, _)| *status == 500));
+        assert!(outcomes.iter().filter(|(status, _)| *status == 200).count() > 18);
+        assert!(outcomes.iter().all(|(_, ms)| (40..300).contains(ms)));
+    }
+}
@@ -0,0 +1,142 @@
+use crate::models::{DbPool, EndpointDnsOverrides};
+use crate::utils::{set_endpoint_dns_overrides, HostOverrides};
+use anyhow::{anyhow, Result};
+use sqlx::Row;
+use std::collections::{BTreeMap, HashMap};
+use std::net::IpAddr;
+use uuid::Uuid;
+
+/// 校验并规范化静态解析：主机名转小写，不能是 IP 字面量，地址须为合法 IP
+pub fn parse_dns_overrides(overrides: &BTreeMap<String, String>) -> Result<HostOverrides> {
+    overrides
+        .iter()
+        .map(|(host, address)| {
+            let host = host.trim().trim_end_matches('.').to_ascii_lowercase();
+            if host.is_empty() || host.parse::<IpAddr>().is_ok() {
+                return Err(anyhow!(
+                    "Invalid DNS override: '{}' is not a host name",
+                    host
+                ));
+            }
+            let ip = address.trim().parse::<IpAddr>().map_err(|_| {
+                anyhow!(
+                    "Invalid DNS override: '{}' for {} is not an IP address",
+                    address,
+                    host
+                )
+            })?;
+            Ok((host, ip))
+        })
+        .collect()
+}
+
+pub struct DnsOverrideService {
+    pool: DbPool,
+}
+
+impl DnsOverrideService {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    /// 启动时加载全部端点的静态解析
+    pub async fn load_all(&self) -> Result<usize> {
+        let rows = sqlx::query("SELECT endpoint_id, host, address FROM endpoint_dns_overrides")
+            .fetch_all(&self.pool)
+            .await?;
+        let mut by_endpoint: HashMap<Uuid, BTreeMap<String, String>> = HashMap::new();
+        for row in &rows {
+            let endpoint_id: String = row.try_get("endpoint_id")?;
+            by_endpoint
+                .entry(Uuid::parse_str(&endpoint_id)?)
+                .or_default()
+                .insert(row.try_get("host")?, row.try_get("address")?);
+        }
+        let endpoints = by_endpoint.len();
+        for (endpoint_id, overrides) in by_endpoint {
+            match parse_dns_overrides(&overrides) {
+                Ok(overrides) => set_endpoint_dns_overrides(endpoint_id, overrides),
+                Err(e) => tracing::warn!(
+                    "Failed to load DNS overrides of endpoint {}: {}",
+                    endpoint_id,
+                    e
+                ),
+            }
+        }
+        Ok(endpoints)
+    }
+
+    pub async fn get(&self, endpoint_id: Uuid) -> Result<EndpointDnsOverrides> {
+        let rows = sqlx::query(
+            "SELECT host, address FROM endpoint_dns_overrides WHERE endpoint_id = ? ORDER BY host",
+        )
+        .bind(endpoint_id.to_string())
+        .fetch_all(&self.pool)
+        .await?;
+        let overrides = rows
+            .iter()
+            .map(|row| Ok((row.try_get("host")?, row.try_get("address")?)))
+            .collect::<Result<_>>()?;
+        Ok(EndpointDnsOverrides {
+            endpoint_id,
+            overrides,
+        })
+    }
+
+    /// 整体替换端点的静态解析，对后续调用立即生效
+    pub async fn replace(
+        &self,
+        endpoint_id: Uuid,
+        overrides: &BTreeMap<String, String>,
+    ) -> Result<EndpointDnsOverrides> {
+        let parsed = parse_dns_overrides(overrides)?;
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("DELETE FROM endpoint_dns_overrides WHERE endpoint_id = ?")
+            .bind(endpoint_id.to_string())
+            .execute(&mut *tx)
+            .await?;
+        for (host, ip) in &parsed {
+            sqlx::query(
+                "INSERT INTO endpoint_dns_overrides (endpoint_id, host, address) VALUES (?, ?, ?)",
+            )
+            .bind(endpoint_id.to_string())
+            .bind(host)
+            .bind(ip.to_string())
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        set_endpoint_dns_overrides(endpoint_id, parsed);
+        self.get(endpoint_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_dns_overrides() {
+        let overrides: BTreeMap<String, String> = [
+            ("API.Internal.".to_string(), " 10.0.0.5 ".to_string()),
+            ("v6.internal".to_string(), "fd00::5".to_string()),
+        ]
+        .into();
+        let parsed = parse_dns_overrides(&overrides).unwrap();
+        assert_eq!(
+            parsed["api.internal"],
+            "10.0.0.5".parse::<IpAddr>().unwrap()
+        );
+        assert_eq!(parsed["v6.internal"], "fd00::5".parse::<IpAddr>().unwrap());
+
+        for (host, address) in [
+            ("api.internal", "not-an-ip"),
+            ("10.0.0.1", "10.0.0.2"),
+            (" ", "10.0.0.2"),
+        ] {
+            let overrides = [(host.to_string(), address.to_string())].into();
+            let error = parse_dns_overrides(&overrides).unwrap_err();
+            assert!(error.to_string().starts_with("Invalid DNS override"));
+        }
+    }
+}
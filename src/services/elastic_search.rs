@@ -1,21 +1,134 @@
-use crate::config::EmbeddingConfig;
+use crate::config::{ElasticsearchConfig, EmbeddingConfig};
 use crate::models::interface_retrieval::*;
 use crate::models::swagger::SwaggerSpec;
-use crate::services::{merge_content, Chunk, EmbeddingService, Filter, Meta, Search};
-use crate::utils::generate_api_details;
+use crate::services::{
+    degrade_vector_results, document_metadata, embedding_text_builder, interfaces_from_spec,
+    merge_content, Chunk, EmbeddingService, Filter, Meta, Search, SearchResult,
+};
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
-use elasticsearch::http::transport::Transport;
+use elasticsearch::http::transport::{SingleNodeConnectionPool, TransportBuilder};
+use elasticsearch::indices::IndicesAnalyzeParts;
 use elasticsearch::indices::IndicesCreateParts;
+use elasticsearch::indices::IndicesGetMappingParts;
 use elasticsearch::indices::IndicesRefreshParts;
-use elasticsearch::{BulkParts, DeleteByQueryParts, Elasticsearch, SearchParts};
+use elasticsearch::{
+    BulkParts, CountParts, DeleteByQueryParts, DeleteParts, Elasticsearch, IndexParts, SearchParts,
+};
 use serde_json::{json, Map, Number, Value};
 use std::sync::Arc;
+use std::time::Duration;
 use tracing::log::error;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 use uuid::Uuid;
 
 const INDEX: &str = "interface_v2";
+/// 端点摘要索引，每个端点一个文档，_id 为端点名称
+const ENDPOINT_INDEX: &str = "endpoint_summary_v1";
+/// 分页读取项目接口时单次请求的文档数
+const PROJECT_PAGE_SIZE: u32 = 500;
+/// 未配置分词器或配置的分词器不可用时使用
+const DEFAULT_ANALYZER: &str = "standard";
+/// 高亮片段长度与每个文档返回的片段数
+const HIGHLIGHT_FRAGMENT_SIZE: u32 = 150;
+const HIGHLIGHT_FRAGMENTS: u32 = 3;
+/// 未配置 request_timeout_ms 时的 ES 请求超时
+pub const DEFAULT_ES_REQUEST_TIMEOUT_MS: u64 = 30_000;
+
+/// 按配置创建 ES 客户端，所有请求受 request_timeout_ms 限制
+pub fn es_client(config: &ElasticsearchConfig) -> Result<Elasticsearch> {
+    let url = format!(
+        r#"http://{}:{}@{}:{}"#,
+        config.user, config.password, config.host, config.port
+    );
+    let timeout = config
+        .request_timeout_ms
+        .unwrap_or(DEFAULT_ES_REQUEST_TIMEOUT_MS);
+    let transport = TransportBuilder::new(SingleNodeConnectionPool::new(url.parse()?))
+        .timeout(Duration::from_millis(timeout))
+        .build()?;
+    Ok(Elasticsearch::new(transport))
+}
+
+/// 错误链中包含 ES 请求超时，handler 据此返回 504
+pub fn is_es_timeout(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| {
+        cause
+            .downcast_ref::<elasticsearch::Error>()
+            .is_some_and(|e| e.is_timeout())
+    })
+}
+
+/// bulk 请求中写入失败的条目，row 为条目在本次请求中的序号（从 0 开始）
+#[derive(Debug, Clone, PartialEq)]
+pub struct BulkItemFailure {
+    pub row: usize,
+    pub id: Option<String>,
+    pub status: u16,
+    pub reason: String,
+}
+
+impl std::fmt::Display for BulkItemFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "row {}: {}", self.row, self.reason)
+    }
+}
+
+/// 解析 bulk 响应 items 中的逐条错误；响应 errors 为 false 时没有失败条目
+pub fn bulk_failures(response: &Value) -> Vec<BulkItemFailure> {
+    if !response["errors"].as_bool().unwrap_or(false) {
+        return vec![];
+    }
+    let Some(items) = response["items"].as_array() else {
+        return vec![];
+    };
+    items
+        .iter()
+        .enumerate()
+        .filter_map(|(row, item)| {
+            // 每个条目以操作名为键：index / create / update / delete
+            let result = item.as_object()?.values().next()?;
+            let error = result.get("error")?;
+            Some(BulkItemFailure {
+                row,
+                id: result["_id"].as_str().map(str::to_string),
+                status: result["status"].as_u64().unwrap_or_default() as u16,
+                reason: bulk_error_reason(error),
+            })
+        })
+        .collect()
+}
+
+/// "type: reason"，带 caused_by 时附上根因，如字段解析失败的原始值
+fn bulk_error_reason(error: &Value) -> String {
+    let reason = match (error["type"].as_str(), error["reason"].as_str()) {
+        (Some(kind), Some(reason)) => format!("{}: {}", kind, reason),
+        (None, Some(reason)) => reason.to_string(),
+        (Some(kind), None) => kind.to_string(),
+        (None, None) => error.to_string(),
+    };
+    match error["caused_by"]["reason"].as_str() {
+        Some(cause) => format!("{} (caused by: {})", reason, cause),
+        None => reason,
+    }
+}
+
+/// 提交 bulk 请求并返回写入失败的条目；请求整体失败时返回错误
+pub async fn send_bulk(
+    client: &Elasticsearch,
+    index: &str,
+    body: Vec<String>,
+) -> Result<Vec<BulkItemFailure>> {
+    let response = client
+        .bulk(BulkParts::Index(index))
+        .body(body)
+        .send()
+        .await?
+        .error_for_status_code()?;
+    let response_body = response.json::<Value>().await?;
+    debug!("Bulk response body: {:?}", response_body);
+    Ok(bulk_failures(&response_body))
+}
 
 impl From<&Value> for Chunk {
     fn from(hit: &Value) -> Self {
@@ -51,6 +164,16 @@ impl From<&Value> for Chunk {
             }
         };
 
+        let highlights = hit["highlight"]["page_content"]
+            .as_array()
+            .map(|fragments| {
+                fragments
+                    .iter()
+                    .filter_map(|f| f.as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default();
+
         Self {
             id: uuid,
             // 修复：避免使用 to_string() 导致带引号的 JSON 字符串
@@ -59,12 +182,166 @@ impl From<&Value> for Chunk {
             score,
             embedding,
             api_content,
+            highlights,
             created_at: None,
             updated_at: None,
         }
     }
 }
 
+/// 配置的索引与查询分词器，查询分词器缺省时与索引分词器相同
+fn configured_analyzers(config: &ElasticsearchConfig) -> (&str, Option<&str>) {
+    let analyzer = config
+        .analyzer
+        .as_deref()
+        .filter(|a| !a.is_empty())
+        .unwrap_or(DEFAULT_ANALYZER);
+    let search_analyzer = config.search_analyzer.as_deref().filter(|a| !a.is_empty());
+    (analyzer, search_analyzer)
+}
+
+/// 接口索引的 mapping，page_content 使用配置的分词器，向量维度与 embedding.dimension 一致
+fn index_mapping(analyzer: &str, search_analyzer: &str, dims: usize) -> Value {
+    json!({
+        "mappings": {
+            "properties": {
+                "page_content": {
+                    "type": "text",
+                    "analyzer": analyzer,
+                    "search_analyzer": search_analyzer
+                },
+                "api_content": {
+                    "type": "text",
+                },
+                "vector": {
+                    "type": "dense_vector",
+                    "dims": dims,
+                    "index": true,
+                    "similarity": "cosine",
+                },
+                "metadata": {
+                    "type": "object",
+                    "properties": {
+                        "project_id": {"type": "keyword"},
+                        "path": {"type": "keyword"},
+                        "method": {"type": "keyword"},
+                        "text_version": {"type": "integer"},
+                    },
+                }
+            }
+        }
+    })
+}
+
+/// 端点摘要索引的 mapping，向量维度与接口索引一致
+fn endpoint_index_mapping(dims: usize) -> Value {
+    json!({
+        "mappings": {
+            "properties": {
+                "page_content": {"type": "text"},
+                "vector": {
+                    "type": "dense_vector",
+                    "dims": dims,
+                    "index": true,
+                    "similarity": "cosine",
+                },
+                "metadata": {
+                    "type": "object",
+                    "properties": {
+                        "endpoint_id": {"type": "keyword"},
+                        "slug": {"type": "keyword"},
+                        "status": {"type": "keyword"},
+                    },
+                }
+            }
+        }
+    })
+}
+
+/// 端点级 knn 检索请求体，only_running 时按 metadata.status 过滤
+fn endpoint_search_body(query_vector: Vec<f32>, max_results: u32, only_running: bool) -> Value {
+    let mut knn = json!({
+        "field": "vector",
+        "query_vector": query_vector,
+        "k": max_results,
+        "num_candidates": (max_results * 10).max(100),
+    });
+    if only_running {
+        knn["filter"] = json!({"term": {"metadata.status": "running"}});
+    }
+    json!({
+        "knn": knn,
+        "_source": {"excludes": ["vector"]},
+        "size": max_results,
+    })
+}
+
+fn endpoint_match(hit: &Value) -> EndpointMatch {
+    let source = &hit["_source"];
+    let metadata = &source["metadata"];
+    let field = |value: &Value| value.as_str().unwrap_or_default().to_string();
+    EndpointMatch {
+        endpoint_id: field(&metadata["endpoint_id"]),
+        slug: field(&metadata["slug"]),
+        status: field(&metadata["status"]),
+        summary: field(&source["page_content"]),
+        score: hit["_score"].as_f64().unwrap_or(0.0),
+    }
+}
+
+/// 按 project_id/path/method 精确定位单个接口的删除条件；term 只接受单个字段，三个条件分别过滤
+fn delete_by_meta_query(meta: &Meta) -> Result<Value> {
+    let missing = meta.missing_fields();
+    if !missing.is_empty() {
+        return Err(anyhow!("Meta is missing {}", missing.join(", ")));
+    }
+
+    Ok(json!({
+        "query": {
+            "bool": {
+                "filter": [
+                    {"term": {"metadata.project_id": meta.project_id}},
+                    {"term": {"metadata.path": meta.path}},
+                    {"term": {"metadata.method": meta.method}}
+                ]
+            }
+        }
+    }))
+}
+
+/// 已有索引的向量维度；响应以实际索引名为 key，别名时取第一个
+fn mapped_dims(mapping: &Value) -> Option<u64> {
+    mapping
+        .as_object()?
+        .values()
+        .next()?
+        .pointer("/mappings/properties/vector/dims")?
+        .as_u64()
+}
+
+/// 已有索引的维度与配置不一致时报错，避免写入与模型不匹配的向量
+fn check_index_dims(mapping: &Value, dimension: usize, config: &ElasticsearchConfig) -> Result<()> {
+    match mapped_dims(mapping) {
+        Some(dims) if dims == dimension as u64 => Ok(()),
+        existing => Err(anyhow!(
+            "Elasticsearch index '{index}' has vector dims {existing} but embedding.dimension is {dimension}. \
+             Recreate the index and re-embed after changing the embedding model: \
+             curl -u <user>:<password> -X DELETE 'http://{host}:{port}/{index}', restart the gateway, \
+             then PUT /api/endpoint/{{id}} with {{\"force_embeddings\": true}} for each endpoint",
+            index = INDEX,
+            existing = existing.map_or_else(|| "<missing>".to_string(), |d| d.to_string()),
+            dimension = dimension,
+            host = config.host,
+            port = config.port,
+        )),
+    }
+}
+
+/// 响应表示索引不存在（如全新安装或索引被手动删除）
+fn is_index_not_found(response_body: &Value) -> bool {
+    response_body["error"]["type"] == "index_not_found_exception"
+}
+
 fn extract_response(response_body: Value) -> Result<Vec<Chunk>> {
     if let Some(hits) = response_body["hits"]["hits"].as_array() {
         Ok(hits.iter().map(Chunk::from).collect())
@@ -77,6 +354,9 @@ fn extract_response(response_body: Value) -> Result<Vec<Chunk>> {
 pub struct ElasticSearch {
     client: Elasticsearch,
     embedding_service: Arc<EmbeddingService>,
+    /// 索引被删除后首次检索时按原配置重建
+    config: ElasticsearchConfig,
+    dimension: usize,
 }
 
 impl ElasticSearch {
@@ -89,13 +369,7 @@ impl ElasticSearch {
             .elasticsearch
             .as_ref()
             .ok_or_else(|| anyhow!("Elasticsearch configuration not found"))?;
-        let url = format!(
-            r#"http://{}:{}@{}:{}"#,
-            elastic_config.user, elastic_config.password, elastic_config.host, elastic_config.port
-        );
-
-        let transport = Transport::single_node(&url)?;
-        let client = Elasticsearch::new(transport);
+        let client = es_client(elastic_config)?;
         if let Err(_) = client.ping().send().await {
             return Err(anyhow!("Elasticsearch connection error"));
         }
@@ -103,53 +377,117 @@ impl ElasticSearch {
         let service = Self {
             client,
             embedding_service,
+            config: elastic_config.clone(),
+            dimension: config.dimension,
         };
-        service.init_schema().await?;
+        service
+            .init_schema(elastic_config, config.dimension)
+            .await?;
         Ok(service)
     }
 
-    /// 初始化数据库schema
-    async fn init_schema(&self) -> Result<()> {
+    /// 执行检索；索引不存在时重建索引并返回空命中，而不是把 ES 错误交给调用方
+    async fn search_index(&self, index: &str, body: Value) -> Result<Value> {
+        let response_body = self
+            .client
+            .search(SearchParts::Index(&[index]))
+            .body(body)
+            .send()
+            .await?
+            .json::<Value>()
+            .await?;
+        if !is_index_not_found(&response_body) {
+            return Ok(response_body);
+        }
+        warn!("Index '{}' does not exist, recreating it", index);
+        if let Err(e) = self.init_schema(&self.config, self.dimension).await {
+            warn!("Failed to recreate index '{}': {}", index, e);
+        }
+        Ok(json!({"hits": {"hits": []}}))
+    }
+
+    /// 初始化数据库schema，索引已存在时校验向量维度
+    async fn init_schema(&self, config: &ElasticsearchConfig, dimension: usize) -> Result<()> {
+        let (analyzer, search_analyzer) = configured_analyzers(config);
+        let analyzer = self.resolve_analyzer(analyzer).await;
+        let search_analyzer = match search_analyzer {
+            Some(name) => self.resolve_analyzer(name).await,
+            None => analyzer.clone(),
+        };
         let create_response = self
             .client
             .indices()
             .create(IndicesCreateParts::Index(INDEX))
-            .body(json!({
-                "mappings": {
-                    "properties": {
-                        "page_content": {
-                            "type": "text",
-                            "analyzer": "ik_max_word",
-                            "search_analyzer": "ik_smart"
-                        },
-                        "api_content": {
-                            "type": "text",
-                        },
-                        "vector": {
-                            "type": "dense_vector",
-                            "dims": 1024,
-                            "index": true,
-                            "similarity": "cosine",
-                        },
-                        "metadata": {
-                            "type": "object",
-                                "properties": {
-                                    "project_id": {"type": "keyword"},
-                                    "path": {"type": "keyword"},
-                                    "method": {"type": "keyword"},
-                                },
-                        }
-                    }
-                }
-            }))
+            .body(index_mapping(&analyzer, &search_analyzer, dimension))
             .send()
             .await?;
         let status = create_response.status_code();
-        if status.is_success() || status.as_u16() == 400 {
-            info!("Index '{}' ready!", INDEX);
-            Ok(())
-        } else {
-            Err(anyhow!("Failed to create index. Status: {:?}", status))
+        if status.as_u16() == 400 {
+            // 索引已存在
+            let mapping = self
+                .client
+                .indices()
+                .get_mapping(IndicesGetMappingParts::Index(&[INDEX]))
+                .send()
+                .await?
+                .error_for_status_code()?
+                .json::<Value>()
+                .await?;
+            check_index_dims(&mapping, dimension, config)?;
+        } else if !status.is_success() {
+            return Err(anyhow!("Failed to create index. Status: {:?}", status));
+        }
+        info!("Index '{}' ready!", INDEX);
+
+        let status = self
+            .client
+            .indices()
+            .create(IndicesCreateParts::Index(ENDPOINT_INDEX))
+            .body(endpoint_index_mapping(dimension))
+            .send()
+            .await?
+            .status_code();
+        // 400 表示索引已存在
+        if !status.is_success() && status.as_u16() != 400 {
+            return Err(anyhow!(
+                "Failed to create index '{}'. Status: {:?}",
+                ENDPOINT_INDEX,
+                status
+            ));
+        }
+        Ok(())
+    }
+
+    /// 通过 _analyze 确认分词器存在（如 IK 需要安装插件），不存在时回退到 standard
+    async fn resolve_analyzer(&self, name: &str) -> String {
+        if name == DEFAULT_ANALYZER {
+            return name.to_string();
+        }
+        let response = self
+            .client
+            .indices()
+            .analyze(IndicesAnalyzeParts::None)
+            .body(json!({"analyzer": name, "text": "analyzer check"}))
+            .send()
+            .await;
+        match response {
+            Ok(response) if response.status_code().is_success() => name.to_string(),
+            Ok(response) => {
+                warn!(
+                    "Analyzer '{}' is not available ({}), falling back to '{}'",
+                    name,
+                    response.status_code(),
+                    DEFAULT_ANALYZER
+                );
+                DEFAULT_ANALYZER.to_string()
+            }
+            Err(e) => {
+                warn!(
+                    "Failed to check analyzer '{}': {}, falling back to '{}'",
+                    name, e, DEFAULT_ANALYZER
+                );
+                DEFAULT_ANALYZER.to_string()
+            }
         }
     }
 
@@ -177,35 +515,14 @@ impl ElasticSearch {
                     "page_content": text,
                     "vector": embedding,
                     "api_content": api_content,
-                    "metadata": {
-                        "project_id": project_id,
-                        "path": interface.path,
-                        "method": interface.method
-                    }
+                    "metadata": document_metadata(project_id, interface)
                 })
                 .to_string(),
             );
         }
 
-        let response = self
-            .client
-            .bulk(BulkParts::Index(INDEX))
-            .body(body)
-            .send()
-            .await?;
-        let response_body = response.json::<Value>().await?;
-
-        debug!("Response body: {:?}", response_body);
-
-        let mut error_count = 0;
-        if let Some(errors) = response_body["errors"].as_bool() {
-            if errors {
-                if let Some(items) = response_body["items"].as_array() {
-                    error_count += items.len();
-                    error!("Index errors: {:?}", items);
-                }
-            }
-        }
+        let failures = send_bulk(&self.client, INDEX, body).await?;
+        log_interface_failures(interfaces, project_id, &failures);
 
         // 刷新索引以确保数据立即可搜索
         let _refresh_response = self
@@ -215,7 +532,7 @@ impl ElasticSearch {
             .send()
             .await?;
 
-        Ok((interfaces.len() - error_count) as u32)
+        Ok((interfaces.len() - failures.len()) as u32)
     }
 
     async fn store_interfaces_without_embeddings(
@@ -246,35 +563,14 @@ impl ElasticSearch {
                     "page_content": text,
                     "vector": embedding,
                     "api_content": api_content,
-                    "metadata": {
-                        "project_id": project_id,
-                        "path": interface.path,
-                        "method": interface.method
-                    }
+                    "metadata": document_metadata(project_id, interface)
                 })
                 .to_string(),
             );
         }
 
-        let response = self
-            .client
-            .bulk(BulkParts::Index(INDEX))
-            .body(body)
-            .send()
-            .await?;
-        let response_body = response.json::<Value>().await?;
-
-        debug!("Response body: {:?}", response_body);
-
-        let mut error_count = 0;
-        if let Some(errors) = response_body["errors"].as_bool() {
-            if errors {
-                if let Some(items) = response_body["items"].as_array() {
-                    error_count += items.len();
-                    error!("Index errors: {:?}", items);
-                }
-            }
-        }
+        let failures = send_bulk(&self.client, INDEX, body).await?;
+        log_interface_failures(interfaces, project_id, &failures);
 
         // 刷新索引以确保数据立即可搜索
         let _refresh_response = self
@@ -284,7 +580,7 @@ impl ElasticSearch {
             .send()
             .await?;
 
-        Ok((interfaces.len() - error_count) as u32)
+        Ok((interfaces.len() - failures.len()) as u32)
     }
 
     fn build_filter(&self, filters: Option<&Filter>) -> Vec<Value> {
@@ -346,6 +642,10 @@ impl ElasticSearch {
             .await?;
 
         let response_body = response.json::<Value>().await?;
+        // 索引不存在时没有可删除的文档
+        if is_index_not_found(&response_body) {
+            return Ok(json!({"deleted": 0}));
+        }
 
         // 刷新索引以确保删除操作立即生效
         let _refresh_response = self
@@ -372,20 +672,9 @@ impl Search for ElasticSearch {
 
         // 解析Swagger JSON
         let swagger_spec: SwaggerSpec = serde_json::from_value(request.swagger_json)?;
-        let api_details = generate_api_details(&swagger_spec)?;
+        let interfaces = interfaces_from_spec(&swagger_spec)?;
 
-        info!("Found {} interfaces in Swagger", api_details.len());
-
-        // 转换为ApiInterface
-        let interfaces: Vec<ApiInterface> = api_details
-            .into_iter()
-            .map(|detail| {
-                let mut interface = ApiInterface::from(detail);
-                interface.service_description = swagger_spec.info.description.clone();
-                interface.tags = vec![swagger_spec.info.title.clone()];
-                interface
-            })
-            .collect();
+        info!("Found {} interfaces in Swagger", interfaces.len());
 
         // 根据generate_embeddings参数决定是否生成嵌入向量
         let stored_count = if request.generate_embeddings.unwrap_or(false) {
@@ -411,7 +700,6 @@ impl Search for ElasticSearch {
         similarity_threshold: f32,
         filters: Option<&Filter>,
     ) -> Result<Vec<Chunk>> {
-        info!("filter: {:?}", filters);
         // 获取查询向量
         let query_embedding = self
             .embedding_service
@@ -429,16 +717,10 @@ impl Search for ElasticSearch {
         root.insert("_source".to_string(), Value::Bool(true));
         root.insert("size".to_string(), Value::Number(Number::from(max_results)));
 
-        let query_json = serde_json::to_string_pretty(&Value::Object(root.clone())).unwrap();
-        info!("🔍 Vector search query: {}", query_json);
+        let body = Value::Object(root);
+        log_search_query("Vector", query, max_results, filters, &body);
 
-        let search_response = self
-            .client
-            .search(SearchParts::Index(&[INDEX]))
-            .body(Value::Object(root))
-            .send()
-            .await?;
-        let response_body = search_response.json::<Value>().await?;
+        let response_body = self.search_index(INDEX, body).await?;
 
         let mut results = extract_response(response_body)?;
 
@@ -485,22 +767,30 @@ impl Search for ElasticSearch {
                 }
             })]),
         );
+        // 返回命中的片段，便于展示匹配原因
+        root.insert(
+            "highlight".to_string(),
+            json!({
+                "pre_tags": ["<em>"],
+                "post_tags": ["</em>"],
+                "fields": {
+                    "page_content": {
+                        "fragment_size": HIGHLIGHT_FRAGMENT_SIZE,
+                        "number_of_fragments": HIGHLIGHT_FRAGMENTS
+                    }
+                }
+            }),
+        );
 
-        let query_json = serde_json::to_string_pretty(&Value::Object(root.clone())).unwrap();
-        info!("🔍 Keyword search query: {}", query_json);
+        let body = Value::Object(root);
+        log_search_query("Keyword", query, max_results, filters, &body);
 
-        let search_response = self
-            .client
-            .search(SearchParts::Index(&[INDEX]))
-            .body(Value::Object(root))
-            .send()
-            .await?;
-        let response_body = search_response.json::<Value>().await?;
+        let response_body = self.search_index(INDEX, body).await?;
 
         extract_response(response_body)
     }
 
-    async fn hybrid_search(&self, request: InterfaceSearchRequest) -> Result<Vec<Chunk>> {
+    async fn hybrid_search(&self, request: InterfaceSearchRequest) -> Result<SearchResult> {
         let (vector_weight, keyword_weight) = match request.search_type {
             SearchType::Vector => (1.0f32, 0.0f32),
             SearchType::Keyword => (0.0f32, 1.0f32),
@@ -515,14 +805,22 @@ impl Search for ElasticSearch {
         let max_results = request.max_results;
 
         // 分别执行向量搜索和关键词搜索
-        let vector_results = self
-            .vector_search(
+        let (vector_results, degraded) = match request.search_type {
+            SearchType::Keyword => (Vec::new(), false),
+            _ => degrade_vector_results(
                 &request.query,
-                max_results,
-                0.0, // 不在这里应用阈值，稍后统一处理
-                request.filters.as_ref(),
-            )
-            .await?;
+                self.vector_search(
+                    // 短查询追加同义词
+                    &embedding_text_builder().expand_query(&request.query),
+                    max_results,
+                    0.0, // 不在这里应用阈值，稍后统一处理
+                    request.filters.as_ref(),
+                )
+                .await,
+            ),
+        };
+        // 降级时仅使用关键词结果
+        let keyword_weight = if degraded { 1.0f32 } else { keyword_weight };
 
         let keyword_results = self
             .keyword_search(&request.query, max_results, request.filters.as_ref())
@@ -543,6 +841,7 @@ impl Search for ElasticSearch {
             chunk.score = chunk.score * keyword_weight as f64;
             if let Some(existing) = combined_results.get_mut(&chunk.id.to_string()) {
                 existing.score += chunk.score;
+                existing.highlights = chunk.highlights;
             } else {
                 combined_results.insert(chunk.id.to_string(), chunk);
             }
@@ -571,38 +870,75 @@ impl Search for ElasticSearch {
             println!("  结果 {}: ID={}, 分数={:.6}", i + 1, chunk.id, chunk.score);
         }
 
-        Ok(results)
+        Ok(SearchResult {
+            chunks: results,
+            degraded,
+            hint: None,
+        })
     }
 
-    async fn get_project_interfaces(&self, project_id: &str) -> Result<Vec<Chunk>> {
-        let mut bool = serde_json::map::Map::new();
-
-        // 添加match_all查询
-        bool.insert("must".to_string(), json!([{"match_all": {}}]));
-
+    async fn get_project_interfaces(
+        &self,
+        project_id: &str,
+        limit: u32,
+        offset: u32,
+    ) -> Result<Vec<Chunk>> {
         let filter = Filter {
             project_id: Some(project_id.to_string()),
             prefix_path: None,
             methods: None,
+            ef_search: None,
+            probes: None,
         };
         let filter = self.build_filter(Some(&filter));
-        bool.insert("filter".to_string(), Value::Array(filter));
 
-        let mut root = serde_json::map::Map::new();
-        let mut query_obj = serde_json::map::Map::new();
-        query_obj.insert("bool".to_string(), Value::Object(bool));
-        root.insert("query".to_string(), Value::Object(query_obj));
-        root.insert("size".to_string(), Value::Number(Number::from(100))); // 设置返回数量
+        // 使用 search_after 逐页读取，避免 from + size 超过 max_result_window
+        let mut results = Vec::new();
+        let mut skipped = 0u32;
+        let mut search_after: Option<Value> = None;
+        while (results.len() as u32) < limit {
+            let remaining = (offset - skipped) + (limit - results.len() as u32);
+            let page_size = remaining.min(PROJECT_PAGE_SIZE);
 
-        let search_response = self
-            .client
-            .search(SearchParts::Index(&[INDEX]))
-            .body(Value::Object(root))
-            .send()
-            .await?;
-        let response_body = search_response.json::<Value>().await?;
+            let mut body = json!({
+                "query": {
+                    "bool": {
+                        "must": [{"match_all": {}}],
+                        "filter": filter
+                    }
+                },
+                "sort": [
+                    {"metadata.path": "asc"},
+                    {"metadata.method": "asc"}
+                ],
+                "size": page_size
+            });
+            if let Some(after) = search_after.take() {
+                body["search_after"] = after;
+            }
 
-        extract_response(response_body)
+            let response_body = self.search_index(INDEX, body).await?;
+            search_after = response_body["hits"]["hits"]
+                .as_array()
+                .and_then(|hits| hits.last())
+                .map(|hit| hit["sort"].clone());
+
+            let chunks = extract_response(response_body)?;
+            let fetched = chunks.len() as u32;
+            for chunk in chunks {
+                if skipped < offset {
+                    skipped += 1;
+                } else if (results.len() as u32) < limit {
+                    results.push(chunk);
+                }
+            }
+
+            if fetched < page_size || search_after.is_none() {
+                break;
+            }
+        }
+
+        Ok(results)
     }
 
     async fn delete_project_data(&self, project_id: &str) -> Result<u64> {
@@ -624,26 +960,494 @@ impl Search for ElasticSearch {
     }
 
     async fn delete_by_meta(&self, meta: Meta) -> Result<()> {
-        if meta.any_empty() {
-            return Err(anyhow!("Meta is empty"));
+        let response = self.delete(delete_by_meta_query(&meta)?).await?;
+
+        if let Some(_) = response["deleted"].as_u64() {
+            Ok(())
+        } else {
+            Err(anyhow!("未能获取删除的文档数量"))
         }
+    }
 
-        let response = self
-            .delete(json!({
-                "query": {
-                    "term": {
-                        "metadata.project_id": meta.project_id,
-                        "metadata.path": meta.path,
-                        "metadata.method": meta.method,
-                    }
+    async fn store_endpoint_summary(&self, summary: EndpointSummaryDoc) -> Result<()> {
+        let embedding = self.embedding_service.embed_text(&summary.text).await?;
+        self.client
+            .index(IndexParts::IndexId(ENDPOINT_INDEX, &summary.slug))
+            .body(json!({
+                "page_content": summary.text,
+                "vector": embedding,
+                "metadata": {
+                    "endpoint_id": summary.endpoint_id,
+                    "slug": summary.slug,
+                    "status": summary.status,
                 }
             }))
+            .send()
+            .await?
+            .error_for_status_code()?;
+        self.client
+            .indices()
+            .refresh(IndicesRefreshParts::Index(&[ENDPOINT_INDEX]))
+            .send()
             .await?;
+        Ok(())
+    }
 
-        if let Some(_) = response["deleted"].as_u64() {
+    async fn delete_endpoint_summary(&self, slug: &str) -> Result<()> {
+        let response = self
+            .client
+            .delete(DeleteParts::IndexId(ENDPOINT_INDEX, slug))
+            .send()
+            .await?;
+        // 摘要尚未生成时返回 404
+        if response.status_code().as_u16() != 404 {
+            response.error_for_status_code()?;
+        }
+        Ok(())
+    }
+
+    async fn search_endpoints(
+        &self,
+        query: &str,
+        max_results: u32,
+        only_running: bool,
+    ) -> Result<Vec<EndpointMatch>> {
+        let query_vector = self.embedding_service.embed_text(query).await?;
+        let response_body = self
+            .search_index(
+                ENDPOINT_INDEX,
+                endpoint_search_body(query_vector, max_results, only_running),
+            )
+            .await?;
+        Ok(response_body["hits"]["hits"]
+            .as_array()
+            .map(|hits| hits.iter().map(endpoint_match).collect())
+            .unwrap_or_default())
+    }
+
+    async fn document_count(&self) -> Result<u64> {
+        let response_body = self
+            .client
+            .count(CountParts::Index(&[INDEX]))
+            .send()
+            .await?
+            .json::<Value>()
+            .await?;
+        if is_index_not_found(&response_body) {
+            return Ok(0);
+        }
+        response_body["count"]
+            .as_u64()
+            .ok_or_else(|| anyhow!("Unexpected count response: {}", response_body))
+    }
+}
+
+/// 搜索日志：INFO 只记录查询摘要，完整查询体仅在 DEBUG 级别输出
+/// 逐条记录写入失败的接口，bulk 条目与 interfaces 一一对应
+fn log_interface_failures(
+    interfaces: &[ApiInterface],
+    project_id: &str,
+    failures: &[BulkItemFailure],
+) {
+    for failure in failures {
+        match interfaces.get(failure.row) {
+            Some(interface) => error!(
+                "Failed to index interface {} {} of project {}: {}",
+                interface.method, interface.path, project_id, failure.reason
+            ),
+            None => error!(
+                "Failed to index interface of project {}: {}",
+                project_id, failure
+            ),
+        }
+    }
+}
+
+fn log_search_query(
+    kind: &str,
+    query: &str,
+    max_results: u32,
+    filters: Option<&Filter>,
+    body: &Value,
+) {
+    info!(
+        "🔍 {} search: query={:?}, k={}, filters={:?}",
+        kind, query, max_results, filters
+    );
+    if tracing::enabled!(tracing::Level::DEBUG) {
+        debug!("🔍 {} search body: {}", kind, redact_query_vector(body));
+    }
+}
+
+/// 查询向量只保留维度，避免每次搜索输出上千个浮点数
+fn redact_query_vector(body: &Value) -> Value {
+    let mut body = body.clone();
+    if let Some(knn) = body.get_mut("knn").and_then(Value::as_object_mut) {
+        if let Some(dims) = knn
+            .get("query_vector")
+            .and_then(Value::as_array)
+            .map(Vec::len)
+        {
+            knn.insert(
+                "query_vector".to_string(),
+                json!(format!("<{} dims>", dims)),
+            );
+        }
+    }
+    body
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn es_config(analyzer: Option<&str>, search_analyzer: Option<&str>) -> ElasticsearchConfig {
+        ElasticsearchConfig {
+            host: "localhost".to_string(),
+            port: "9200".to_string(),
+            user: "elastic".to_string(),
+            password: "elastic".to_string(),
+            analyzer: analyzer.map(str::to_string),
+            search_analyzer: search_analyzer.map(str::to_string),
+            request_timeout_ms: None,
+        }
+    }
+
+    #[test]
+    fn test_configured_analyzer_flows_into_mapping() {
+        let config = es_config(Some("ik_max_word"), Some("ik_smart"));
+        let (analyzer, search_analyzer) = configured_analyzers(&config);
+        let mapping = index_mapping(analyzer, search_analyzer.unwrap_or(analyzer), 1024);
+        let page_content = &mapping["mappings"]["properties"]["page_content"];
+        assert_eq!(page_content["analyzer"], "ik_max_word");
+        assert_eq!(page_content["search_analyzer"], "ik_smart");
+    }
+
+    #[test]
+    fn test_delete_by_meta_query_filters_each_field() {
+        let meta = Meta {
+            project_id: "p1".to_string(),
+            path: "/api/users/{id}".to_string(),
+            method: "get".to_string(),
+            text_version: None,
+        };
+        assert_eq!(
+            delete_by_meta_query(&meta).unwrap(),
+            json!({"query": {"bool": {"filter": [
+                {"term": {"metadata.project_id": "p1"}},
+                {"term": {"metadata.path": "/api/users/{id}"}},
+                {"term": {"metadata.method": "get"}}
+            ]}}})
+        );
+
+        let meta = Meta {
+            project_id: "p1".to_string(),
+            path: " ".to_string(),
+            method: String::new(),
+            text_version: None,
+        };
+        assert_eq!(
+            delete_by_meta_query(&meta).unwrap_err().to_string(),
+            "Meta is missing path, method"
+        );
+    }
+
+    #[test]
+    fn test_analyzer_defaults_to_standard() {
+        let config = es_config(None, None);
+        assert_eq!(configured_analyzers(&config), ("standard", None));
+
+        let config = es_config(Some("english"), Some(""));
+        let (analyzer, search_analyzer) = configured_analyzers(&config);
+        let mapping = index_mapping(analyzer, search_analyzer.unwrap_or(analyzer), 1024);
+        let page_content = &mapping["mappings"]["properties"]["page_content"];
+        assert_eq!(page_content["analyzer"], "english");
+        assert_eq!(page_content["search_analyzer"], "english");
+    }
+
+    #[test]
+    fn test_index_dims_mismatch_is_rejected() {
+        let config = es_config(None, None);
+        let mapping = json!({
+            INDEX: index_mapping("standard", "standard", 1024)
+        });
+        assert_eq!(mapped_dims(&mapping), Some(1024));
+        assert!(check_index_dims(&mapping, 1024, &config).is_ok());
+
+        // 更换模型后维度变化
+        let error = check_index_dims(&mapping, 1536, &config)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("has vector dims 1024 but embedding.dimension is 1536"));
+        assert!(error
+            .contains("curl -u <user>:<password> -X DELETE 'http://localhost:9200/interface_v2'"));
+        assert!(error.contains("force_embeddings"));
+
+        // 缺少向量字段同样拒绝
+        let mapping = json!({INDEX: {"mappings": {"properties": {}}}});
+        let error = check_index_dims(&mapping, 1024, &config)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("has vector dims <missing>"));
+    }
+
+    #[test]
+    fn test_endpoint_search_body_filters_running() {
+        let body = endpoint_search_body(vec![0.1, 0.2], 5, false);
+        assert_eq!(body["knn"]["k"], 5);
+        assert_eq!(body["knn"]["num_candidates"], 100);
+        assert!(body["knn"].get("filter").is_none());
+
+        let body = endpoint_search_body(vec![0.1, 0.2], 5, true);
+        assert_eq!(
+            body["knn"]["filter"],
+            json!({"term": {"metadata.status": "running"}})
+        );
+
+        let hit = json!({
+            "_id": "payments",
+            "_score": 0.87,
+            "_source": {
+                "page_content": "Payments",
+                "metadata": {"endpoint_id": "e-1", "slug": "payments", "status": "running"}
+            }
+        });
+        let matched = endpoint_match(&hit);
+        assert_eq!(matched.slug, "payments");
+        assert_eq!(matched.endpoint_id, "e-1");
+        assert_eq!(matched.summary, "Payments");
+        assert_eq!(matched.score, 0.87);
+    }
+
+    fn bulk_response_with_failure() -> Value {
+        json!({
+            "took": 3,
+            "errors": true,
+            "items": [
+                {"index": {"_index": INDEX, "_id": "a", "status": 201, "result": "created"}},
+                {"index": {
+                    "_index": INDEX,
+                    "_id": "b",
+                    "status": 400,
+                    "error": {
+                        "type": "document_parsing_exception",
+                        "reason": "failed to parse field [age] of type [long]",
+                        "caused_by": {
+                            "type": "illegal_argument_exception",
+                            "reason": "For input string: \"abc\""
+                        }
+                    }
+                }},
+                {"index": {"_index": INDEX, "_id": "c", "status": 201, "result": "created"}}
+            ]
+        })
+    }
+
+    #[test]
+    fn test_bulk_failures_report_failed_items() {
+        let failures = bulk_failures(&bulk_response_with_failure());
+        assert_eq!(
+            failures,
+            vec![BulkItemFailure {
+                row: 1,
+                id: Some("b".to_string()),
+                status: 400,
+                reason: "document_parsing_exception: failed to parse field [age] of type [long] (caused by: For input string: \"abc\")".to_string(),
+            }]
+        );
+
+        // errors 为 false 时不逐条检查
+        let mut ok = bulk_response_with_failure();
+        ok["errors"] = json!(false);
+        assert!(bulk_failures(&ok).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_send_bulk_returns_failed_rows() {
+        let app =
+            axum::Router::new().fallback(|| async { axum::Json(bulk_response_with_failure()) });
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+
+        let mut config = es_config(None, None);
+        config.host = addr.ip().to_string();
+        config.port = addr.port().to_string();
+        let client = es_client(&config).unwrap();
+
+        let body = ["a", "b", "c"]
+            .iter()
+            .flat_map(|id| {
+                [
+                    json!({"index": {"_index": INDEX, "_id": id}}).to_string(),
+                    json!({"page_content": id}).to_string(),
+                ]
+            })
+            .collect();
+        let failures = send_bulk(&client, INDEX, body).await.unwrap();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].row, 1);
+        assert_eq!(failures[0].id.as_deref(), Some("b"));
+        assert!(failures[0]
+            .to_string()
+            .starts_with("row 1: document_parsing_exception"));
+    }
+
+    #[tokio::test]
+    async fn test_missing_index_reads_as_empty_and_is_recreated() {
+        // 模拟全新安装的 ES：除创建索引外的请求都返回 index_not_found
+        let creates = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = creates.clone();
+        let app = axum::Router::new().fallback(
+            move |method: axum::http::Method, uri: axum::http::Uri| {
+                let recorded = recorded.clone();
+                async move {
+                    if method == axum::http::Method::PUT {
+                        recorded.lock().unwrap().push(uri.path().to_string());
+                        return (
+                            axum::http::StatusCode::OK,
+                            axum::Json(json!({"acknowledged": true})),
+                        );
+                    }
+                    (
+                        axum::http::StatusCode::NOT_FOUND,
+                        axum::Json(json!({
+                            "error": {"type": "index_not_found_exception", "reason": "no such index"},
+                            "status": 404
+                        })),
+                    )
+                }
+            },
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+
+        let mut config = es_config(None, None);
+        config.host = addr.ip().to_string();
+        config.port = addr.port().to_string();
+        let search = ElasticSearch {
+            client: es_client(&config).unwrap(),
+            embedding_service: Arc::new(EmbeddingService::new(
+                crate::config::Settings::default().embedding,
+            )),
+            config,
+            dimension: 4,
+        };
+
+        assert!(search
+            .keyword_search("users", 10, None)
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(search
+            .get_project_interfaces("p", 10, 0)
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(search.document_count().await.unwrap(), 0);
+        assert_eq!(search.delete_project_data("p").await.unwrap(), 0);
+        // 每次检索发现索引缺失时都会尝试重建两个索引
+        let recreated = [format!("/{}", INDEX), format!("/{}", ENDPOINT_INDEX)];
+        assert_eq!(
+            *creates.lock().unwrap(),
+            [recreated.clone(), recreated].concat()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_slow_es_request_times_out() {
+        // 模拟过载的 ES：任何请求都在超时之后才响应
+        let app = axum::Router::new().fallback(|| async {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            axum::Json(json!({"hits": {"hits": []}}))
+        });
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+
+        let mut config = es_config(None, None);
+        config.host = addr.ip().to_string();
+        config.port = addr.port().to_string();
+        config.request_timeout_ms = Some(200);
+        let client = es_client(&config).unwrap();
+
+        let started = std::time::Instant::now();
+        let error: anyhow::Error = client
+            .search(SearchParts::Index(&[INDEX]))
+            .body(json!({"query": {"match_all": {}}}))
+            .send()
+            .await
+            .unwrap_err()
+            .into();
+        assert!(is_es_timeout(&error));
+        assert!(started.elapsed() < Duration::from_secs(2));
+
+        // 包装后的超时同样识别，其他错误不映射为 504
+        assert!(is_es_timeout(&error.context("search interfaces")));
+        assert!(!is_es_timeout(&anyhow!("Elasticsearch connection error")));
+    }
+
+    /// 捕获指定级别及以上的日志输出
+    fn capture_logs(level: tracing::Level, f: impl FnOnce()) -> String {
+        let buffer = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let writer = {
+            let buffer = buffer.clone();
+            move || LogWriter(buffer.clone())
+        };
+        let subscriber = tracing_subscriber::fmt()
+            .with_max_level(level)
+            .with_ansi(false)
+            .with_writer(writer)
+            .finish();
+        tracing::subscriber::with_default(subscriber, f);
+        let logs = buffer.lock().unwrap().clone();
+        String::from_utf8(logs).unwrap()
+    }
+
+    struct LogWriter(Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl std::io::Write for LogWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
             Ok(())
-        } else {
-            Err(anyhow!("未能获取删除的文档数量"))
         }
     }
+
+    #[test]
+    fn test_query_vector_not_logged_at_info() {
+        let body = json!({
+            "knn": {
+                "field": "vector",
+                "query_vector": vec![0.123456f32; 1024],
+                "k": 5
+            },
+            "size": 5
+        });
+
+        let info = capture_logs(tracing::Level::INFO, || {
+            log_search_query("Vector", "查询宠物", 5, None, &body)
+        });
+        assert!(info.contains("query=\"查询宠物\", k=5, filters=None"));
+        assert!(!info.contains("0.123456"));
+        assert!(!info.contains("search body"));
+
+        // DEBUG 输出完整查询体，但向量只记录维度
+        let debug = capture_logs(tracing::Level::DEBUG, || {
+            log_search_query("Vector", "查询宠物", 5, None, &body)
+        });
+        assert!(debug.contains("\"query_vector\":\"<1024 dims>\""));
+        assert!(debug.contains("\"k\":5"));
+        assert!(!debug.contains("0.123456"));
+    }
 }
@@ -1,6 +1,76 @@
 use crate::config::EmbeddingConfig;
 use anyhow::Result;
+use lru::LruCache;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::num::NonZeroUsize;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+/// 向量化失败原因，调用方据此决定重试还是快速失败
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum EmbeddingError {
+    /// 触发服务商限流，retry_after 来自 Retry-After 响应头
+    #[error("Embedding provider rate limited (retry after {retry_after:?})")]
+    RateLimited { retry_after: Option<Duration> },
+    #[error("Embedding provider rejected credentials: {0}")]
+    Unauthorized(String),
+    #[error("Embedding request failed: {0}")]
+    Network(String),
+    #[error("Embedding dimension mismatch: expected {expected}, got {actual}")]
+    DimensionMismatch { expected: usize, actual: usize },
+    #[error("Embedding provider error (status {status:?}): {message}")]
+    Provider {
+        status: Option<u16>,
+        message: String,
+    },
+}
+
+impl EmbeddingError {
+    /// 限流、网络错误与服务商 5xx 可重试，其余应直接失败
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            EmbeddingError::RateLimited { .. } | EmbeddingError::Network(_) => true,
+            EmbeddingError::Provider {
+                status: Some(status),
+                ..
+            } => *status >= 500,
+            _ => false,
+        }
+    }
+
+    /// 按 HTTP 状态码与响应体中的错误码归类服务商的失败响应
+    pub fn from_provider_response(status: u16, body: &str, retry_after: Option<Duration>) -> Self {
+        let code = serde_json::from_str::<AliyunErrorResponse>(body)
+            .ok()
+            .and_then(|e| e.code)
+            .unwrap_or_default();
+        if status == 429 || code.starts_with("Throttling") {
+            EmbeddingError::RateLimited { retry_after }
+        } else if status == 401 || status == 403 || code == "InvalidApiKey" {
+            EmbeddingError::Unauthorized(body.to_string())
+        } else {
+            EmbeddingError::Provider {
+                status: Some(status),
+                message: body.to_string(),
+            }
+        }
+    }
+}
+
+impl From<reqwest::Error> for EmbeddingError {
+    fn from(e: reqwest::Error) -> Self {
+        if e.is_decode() || e.is_builder() {
+            EmbeddingError::Provider {
+                status: e.status().map(|s| s.as_u16()),
+                message: e.to_string(),
+            }
+        } else {
+            EmbeddingError::Network(e.to_string())
+        }
+    }
+}
 
 /// 阿里云百炼嵌入请求结构
 #[derive(Debug, Serialize)]
@@ -39,29 +109,86 @@ struct AliyunEmbeddingOutput {
 
 #[derive(Debug, Deserialize)]
 struct AliyunEmbedding {
-    #[allow(dead_code)]
     text_index: i32,
     embedding: Vec<f32>,
 }
 
+/// 阿里云百炼错误响应结构
+#[derive(Debug, Deserialize)]
+struct AliyunErrorResponse {
+    code: Option<String>,
+}
+
 #[derive(Debug, Deserialize)]
 struct AliyunUsage {
     #[allow(dead_code)]
     total_tokens: i32,
 }
 
+/// 令牌桶限流：按 qps 匀速补充令牌，桶容量为一秒的配额（至少 1 个）。
+/// 等待时持有锁，排队的调用按到达顺序依次放行
+struct TokenBucket {
+    qps: f64,
+    capacity: f64,
+    state: Mutex<BucketState>,
+}
+
+struct BucketState {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl TokenBucket {
+    fn new(qps: f64) -> Self {
+        let capacity = qps.max(1.0);
+        Self {
+            qps,
+            capacity,
+            state: Mutex::new(BucketState {
+                tokens: capacity,
+                refilled_at: Instant::now(),
+            }),
+        }
+    }
+
+    async fn acquire(&self) {
+        let mut state = self.state.lock().await;
+        let now = Instant::now();
+        let elapsed = now.duration_since(state.refilled_at).as_secs_f64();
+        state.tokens = (state.tokens + elapsed * self.qps).min(self.capacity);
+        state.refilled_at = now;
+        if state.tokens < 1.0 {
+            let wait = Duration::from_secs_f64((1.0 - state.tokens) / self.qps);
+            tokio::time::sleep(wait).await;
+            state.tokens = 1.0;
+            state.refilled_at = now + wait;
+        }
+        state.tokens -= 1.0;
+    }
+}
+
 /// 向量化服务
 pub struct EmbeddingService {
     config: EmbeddingConfig,
     client: reqwest::Client,
+    /// 配置了 max_qps 时对所有请求限流，与并发的入库任务数无关
+    rate_limiter: Option<TokenBucket>,
+    /// 向量 LRU 缓存，键为 (模型, 维度, 文本) 的 sha256；关闭时为 None
+    cache: Option<std::sync::Mutex<LruCache<[u8; 32], Vec<f32>>>>,
 }
 
 impl EmbeddingService {
     /// 创建新的向量化服务实例
     pub fn new(config: EmbeddingConfig) -> Self {
+        let rate_limiter = (config.max_qps > 0.0).then(|| TokenBucket::new(config.max_qps));
+        let cache = NonZeroUsize::new(config.cache.capacity)
+            .filter(|_| config.cache.enabled)
+            .map(|capacity| std::sync::Mutex::new(LruCache::new(capacity)));
         Self {
             config,
             client: reqwest::Client::new(),
+            rate_limiter,
+            cache,
         }
     }
 
@@ -70,11 +197,80 @@ impl EmbeddingService {
         Ok(Self::new(config))
     }
 
-    /// 获取文本的向量表示
-    pub async fn embed_text(&self, text: &str) -> Result<Vec<f32>> {
-        match &self.config.aliyun {
-            Some(_) => self.aliyun_embed_text(text).await,
-            None => Err(anyhow::anyhow!("Missing config")),
+    /// 获取文本的向量表示，命中缓存时不请求服务商
+    pub async fn embed_text(&self, text: &str) -> Result<Vec<f32>, EmbeddingError> {
+        let key = self.cache_key(text);
+        if let Some(embedding) = self.cached(&key) {
+            return Ok(embedding);
+        }
+        let embedding = match &self.config.aliyun {
+            Some(_) => self.aliyun_embed_text(text).await?,
+            None => {
+                return Err(EmbeddingError::Provider {
+                    status: None,
+                    message: "Missing config".to_string(),
+                })
+            }
+        };
+        self.store(key, &embedding);
+        Ok(embedding)
+    }
+
+    /// 批量获取文本的向量表示，结果顺序与输入一致；只请求未命中缓存的文本
+    pub async fn embed_texts(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, EmbeddingError> {
+        if texts.is_empty() {
+            return Ok(Vec::new());
+        }
+        let keys: Vec<[u8; 32]> = texts.iter().map(|text| self.cache_key(text)).collect();
+        let mut embeddings: Vec<Option<Vec<f32>>> = keys.iter().map(|k| self.cached(k)).collect();
+        let missing: Vec<usize> = (0..texts.len())
+            .filter(|&i| embeddings[i].is_none())
+            .collect();
+        if !missing.is_empty() {
+            let missing_texts: Vec<String> = missing.iter().map(|&i| texts[i].clone()).collect();
+            let embedded = match &self.config.aliyun {
+                Some(_) => self.aliyun_embed_texts(&missing_texts).await?,
+                None => {
+                    return Err(EmbeddingError::Provider {
+                        status: None,
+                        message: "Missing config".to_string(),
+                    })
+                }
+            };
+            for (i, embedding) in missing.into_iter().zip(embedded) {
+                self.store(keys[i], &embedding);
+                embeddings[i] = Some(embedding);
+            }
+        }
+        Ok(embeddings.into_iter().flatten().collect())
+    }
+
+    /// 同一模型同一维度下，相同文本的向量相同
+    fn cache_key(&self, text: &str) -> [u8; 32] {
+        let model = self
+            .config
+            .aliyun
+            .as_ref()
+            .map(|aliyun| aliyun.model.as_str())
+            .unwrap_or(&self.config.model_type);
+        let mut hasher = Sha256::new();
+        hasher.update(model.as_bytes());
+        hasher.update([0]);
+        hasher.update(self.config.dimension.to_be_bytes());
+        hasher.update(text.as_bytes());
+        let mut key = [0u8; 32];
+        key.copy_from_slice(&hasher.finalize());
+        key
+    }
+
+    fn cached(&self, key: &[u8; 32]) -> Option<Vec<f32>> {
+        let mut cache = self.cache.as_ref()?.lock().ok()?;
+        cache.get(key).cloned()
+    }
+
+    fn store(&self, key: [u8; 32], embedding: &[f32]) {
+        if let Some(mut cache) = self.cache.as_ref().and_then(|c| c.lock().ok()) {
+            cache.put(key, embedding.to_vec());
         }
     }
 
@@ -84,17 +280,26 @@ impl EmbeddingService {
     }
 
     /// 使用阿里云百炼 API 进行文本向量化
-    async fn aliyun_embed_text(&self, text: &str) -> Result<Vec<f32>> {
+    async fn aliyun_embed_text(&self, text: &str) -> Result<Vec<f32>, EmbeddingError> {
+        // 数量已在 collect_embeddings 中校验，必然恰好一个
+        let mut embeddings = self.aliyun_embed_texts(&[text.to_string()]).await?;
+        Ok(embeddings.remove(0))
+    }
+
+    async fn aliyun_embed_texts(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, EmbeddingError> {
         let config = self
             .config
             .aliyun
             .as_ref()
-            .ok_or_else(|| anyhow::anyhow!("阿里云百炼配置未设置"))?;
+            .ok_or_else(|| EmbeddingError::Provider {
+                status: None,
+                message: "阿里云百炼配置未设置".to_string(),
+            })?;
 
         let request = AliyunEmbeddingRequest {
             model: config.model.clone(),
             input: AliyunEmbeddingInput {
-                texts: vec![text.to_string()],
+                texts: texts.to_vec(),
             },
             parameters: Some(AliyunEmbeddingParameters {
                 text_type: "document".to_string(),
@@ -103,49 +308,72 @@ impl EmbeddingService {
             encoding_format: Some("float".to_string()),
         };
 
-        let mut headers = reqwest::header::HeaderMap::new();
-        headers.insert(
-            "Authorization",
-            format!("Bearer {}", config.api_key).parse()?,
-        );
-        headers.insert("Content-Type", "application/json".parse()?);
-
+        let mut request_builder = self
+            .client
+            .post(&config.endpoint)
+            .bearer_auth(&config.api_key)
+            .json(&request);
         // 如果有工作空间 ID，添加到请求头
         if let Some(workspace_id) = &config.workspace_id {
-            headers.insert("X-DashScope-WorkSpace", workspace_id.parse()?);
+            request_builder = request_builder.header("X-DashScope-WorkSpace", workspace_id);
         }
 
-        let response = self
-            .client
-            .post(&config.endpoint)
-            .headers(headers)
-            .json(&request)
-            .send()
-            .await?;
+        if let Some(rate_limiter) = &self.rate_limiter {
+            rate_limiter.acquire().await;
+        }
+        let response = request_builder.send().await?;
 
         if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response.text().await?;
-            return Err(anyhow::anyhow!(
-                "阿里云百炼 API 调用失败: HTTP {}, 响应: {}",
+            let status = response.status().as_u16();
+            let retry_after = response
+                .headers()
+                .get(reqwest::header::RETRY_AFTER)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.trim().parse::<u64>().ok())
+                .map(Duration::from_secs);
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(EmbeddingError::from_provider_response(
                 status,
-                error_text
+                &error_text,
+                retry_after,
             ));
         }
 
         let api_response: AliyunEmbeddingResponse = response.json().await?;
-
-        if api_response.output.embeddings.is_empty() {
-            return Err(anyhow::anyhow!("阿里云百炼 API 返回空的向量结果"));
-        }
-
-        // 添加调试日志，打印返回的向量信息
-        let embedding = &api_response.output.embeddings[0].embedding;
         tracing::debug!(
             "阿里云百炼 API 返回向量数据长度: {:?}",
             &api_response.output.embeddings.len()
         );
-        Ok(embedding.clone())
+        self.collect_embeddings(api_response, texts.len())
+    }
+
+    /// 按 text_index 还原输入顺序，并校验数量与维度
+    fn collect_embeddings(
+        &self,
+        response: AliyunEmbeddingResponse,
+        expected_count: usize,
+    ) -> Result<Vec<Vec<f32>>, EmbeddingError> {
+        let mut embeddings = response.output.embeddings;
+        if embeddings.len() != expected_count {
+            return Err(EmbeddingError::Provider {
+                status: None,
+                message: format!(
+                    "阿里云百炼 API 返回 {} 个向量，期望 {} 个",
+                    embeddings.len(),
+                    expected_count
+                ),
+            });
+        }
+        embeddings.sort_by_key(|e| e.text_index);
+        for embedding in &embeddings {
+            if embedding.embedding.len() != self.config.dimension {
+                return Err(EmbeddingError::DimensionMismatch {
+                    expected: self.config.dimension,
+                    actual: embedding.embedding.len(),
+                });
+            }
+        }
+        Ok(embeddings.into_iter().map(|e| e.embedding).collect())
     }
 }
 
@@ -155,6 +383,234 @@ mod tests {
     use crate::config::Settings;
     use tracing::warn;
 
+    #[test]
+    fn test_provider_response_classification() {
+        let throttled = r#"{"code":"Throttling.RateQuota","message":"Requests rate limit exceeded","request_id":"1"}"#;
+        assert_eq!(
+            EmbeddingError::from_provider_response(429, throttled, Some(Duration::from_secs(2))),
+            EmbeddingError::RateLimited {
+                retry_after: Some(Duration::from_secs(2))
+            }
+        );
+        // 部分限流以 400 + Throttling 错误码返回
+        assert!(matches!(
+            EmbeddingError::from_provider_response(400, throttled, None),
+            EmbeddingError::RateLimited { retry_after: None }
+        ));
+
+        let invalid_key =
+            r#"{"code":"InvalidApiKey","message":"Invalid API-key provided.","request_id":"2"}"#;
+        assert!(matches!(
+            EmbeddingError::from_provider_response(401, invalid_key, None),
+            EmbeddingError::Unauthorized(_)
+        ));
+        assert!(matches!(
+            EmbeddingError::from_provider_response(403, "forbidden", None),
+            EmbeddingError::Unauthorized(_)
+        ));
+
+        let bad_request = r#"{"code":"InvalidParameter","message":"Range of input length should be [1, 2048]","request_id":"3"}"#;
+        let error = EmbeddingError::from_provider_response(400, bad_request, None);
+        assert_eq!(
+            error,
+            EmbeddingError::Provider {
+                status: Some(400),
+                message: bad_request.to_string()
+            }
+        );
+        assert!(!error.is_retryable());
+
+        let unavailable = EmbeddingError::from_provider_response(503, "<html>", None);
+        assert!(matches!(
+            unavailable,
+            EmbeddingError::Provider {
+                status: Some(503),
+                ..
+            }
+        ));
+        assert!(unavailable.is_retryable());
+    }
+
+    #[test]
+    fn test_retryable_variants() {
+        assert!(EmbeddingError::RateLimited { retry_after: None }.is_retryable());
+        assert!(EmbeddingError::Network("connection reset".to_string()).is_retryable());
+        assert!(!EmbeddingError::Unauthorized("bad key".to_string()).is_retryable());
+        assert!(!EmbeddingError::DimensionMismatch {
+            expected: 1024,
+            actual: 768
+        }
+        .is_retryable());
+    }
+
+    #[test]
+    fn test_collect_embeddings_checks_dimension_and_order() {
+        let service = EmbeddingService::new(EmbeddingConfig {
+            dimension: 2,
+            ..EmbeddingConfig::default()
+        });
+        let response = |vectors: Vec<(i32, Vec<f32>)>| AliyunEmbeddingResponse {
+            output: AliyunEmbeddingOutput {
+                embeddings: vectors
+                    .into_iter()
+                    .map(|(text_index, embedding)| AliyunEmbedding {
+                        text_index,
+                        embedding,
+                    })
+                    .collect(),
+            },
+            usage: None,
+            request_id: "test".to_string(),
+        };
+
+        let embeddings = service
+            .collect_embeddings(response(vec![(1, vec![0.3, 0.4]), (0, vec![0.1, 0.2])]), 2)
+            .unwrap();
+        assert_eq!(embeddings, vec![vec![0.1, 0.2], vec![0.3, 0.4]]);
+
+        assert_eq!(
+            service
+                .collect_embeddings(response(vec![(0, vec![0.1, 0.2, 0.3])]), 1)
+                .unwrap_err(),
+            EmbeddingError::DimensionMismatch {
+                expected: 2,
+                actual: 3
+            }
+        );
+        assert!(matches!(
+            service.collect_embeddings(response(vec![]), 1),
+            Err(EmbeddingError::Provider { status: None, .. })
+        ));
+    }
+
+    /// 本地模拟的百炼接口，返回按文本长度生成的二维向量并计数
+    async fn spawn_provider() -> (String, std::sync::Arc<std::sync::atomic::AtomicUsize>) {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        let calls = std::sync::Arc::new(AtomicUsize::new(0));
+        let app = axum::Router::new().route(
+            "/embeddings",
+            axum::routing::post({
+                let calls = calls.clone();
+                move |axum::Json(body): axum::Json<serde_json::Value>| {
+                    let calls = calls.clone();
+                    async move {
+                        calls.fetch_add(1, Ordering::SeqCst);
+                        let texts = body["input"]["texts"]
+                            .as_array()
+                            .cloned()
+                            .unwrap_or_default();
+                        let embeddings: Vec<_> = texts
+                            .iter()
+                            .enumerate()
+                            .map(|(i, text)| {
+                                let len = text.as_str().unwrap_or_default().len() as f32;
+                                serde_json::json!({"text_index": i, "embedding": [len, 1.0]})
+                            })
+                            .collect();
+                        axum::Json(serde_json::json!({
+                            "output": {"embeddings": embeddings},
+                            "request_id": "test"
+                        }))
+                    }
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        (format!("http://{}/embeddings", addr), calls)
+    }
+
+    fn provider_config(endpoint: String, cache_enabled: bool) -> EmbeddingConfig {
+        EmbeddingConfig {
+            dimension: 2,
+            aliyun: Some(crate::config::AliyunBailianConfig {
+                api_key: "test".to_string(),
+                model: "text-embedding-v4".to_string(),
+                endpoint,
+                workspace_id: None,
+            }),
+            cache: crate::config::EmbeddingCacheConfig {
+                enabled: cache_enabled,
+                capacity: 2,
+            },
+            ..EmbeddingConfig::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_repeated_text_served_from_cache() {
+        use std::sync::atomic::Ordering;
+        let (endpoint, calls) = spawn_provider().await;
+        let service = EmbeddingService::new(provider_config(endpoint, true));
+
+        let first = service.embed_text("list pets").await.unwrap();
+        let second = service.embed_text("list pets").await.unwrap();
+        assert_eq!(first, second);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // 批量请求只发送未命中的文本，结果顺序不变
+        let texts = vec!["list pets".to_string(), "get pet".to_string()];
+        let batch = service.embed_texts(&texts).await.unwrap();
+        assert_eq!(batch, vec![vec![9.0, 1.0], vec![7.0, 1.0]]);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        service.embed_texts(&texts).await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        // 容量为 2，最久未使用的条目被淘汰
+        service.embed_text("delete pet").await.unwrap();
+        service.embed_text("list pets").await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn test_disabled_cache_always_calls_provider() {
+        use std::sync::atomic::Ordering;
+        let (endpoint, calls) = spawn_provider().await;
+        let service = EmbeddingService::new(provider_config(endpoint, false));
+        service.embed_text("list pets").await.unwrap();
+        service.embed_text("list pets").await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_token_bucket_paces_calls() {
+        let bucket = TokenBucket::new(1.0);
+        let start = Instant::now();
+        for _ in 0..5 {
+            bucket.acquire().await;
+        }
+        // 首个令牌立即可用，其余每秒一个
+        assert!(start.elapsed() >= Duration::from_secs(4));
+        assert!(start.elapsed() < Duration::from_secs(5));
+
+        // 空闲后最多累积一秒的配额
+        tokio::time::sleep(Duration::from_secs(10)).await;
+        let start = Instant::now();
+        bucket.acquire().await;
+        bucket.acquire().await;
+        assert!(start.elapsed() >= Duration::from_secs(1));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_token_bucket_shared_by_concurrent_callers() {
+        let bucket = std::sync::Arc::new(TokenBucket::new(20.0));
+        let start = Instant::now();
+        let tasks: Vec<_> = (0..60)
+            .map(|_| {
+                let bucket = bucket.clone();
+                tokio::spawn(async move { bucket.acquire().await })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap();
+        }
+        // 20 个突发配额，其余 40 个按 20 QPS 放行
+        assert!(start.elapsed() >= Duration::from_secs(2));
+    }
+
     #[tokio::test]
     async fn test_embedding_service_creation() {
         use crate::config::Settings;
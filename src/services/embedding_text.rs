@@ -0,0 +1,220 @@
+use crate::config::EmbeddingTextConfig;
+use crate::models::interface_retrieval::{ApiInterface, ApiParameter};
+use std::sync::OnceLock;
+
+/// 向量化文本构建器版本，写入文档 metadata.text_version；
+/// 版本 1 为旧的 summary | description | service_description 拼接
+pub const EMBEDDING_TEXT_VERSION: u32 = 2;
+
+const SEPARATOR: &str = " | ";
+
+/// 全局文本构建器，写入与查询共用
+pub static EMBEDDING_TEXT_BUILDER: OnceLock<EmbeddingTextBuilder> = OnceLock::new();
+
+pub fn embedding_text_builder() -> &'static EmbeddingTextBuilder {
+    EMBEDDING_TEXT_BUILDER.get_or_init(|| EmbeddingTextBuilder::new(EmbeddingTextConfig::default()))
+}
+
+/// 接口文档向量化文本构建：
+/// 按权重从高到低排列字段，权重即重复次数；schema 文本单独限长，最后按字符预算截断
+pub struct EmbeddingTextBuilder {
+    config: EmbeddingTextConfig,
+}
+
+impl EmbeddingTextBuilder {
+    pub fn new(config: EmbeddingTextConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn build(&self, interface: &ApiInterface) -> String {
+        let params = interface
+            .path_params
+            .iter()
+            .chain(&interface.query_params)
+            .chain(&interface.body_params)
+            .map(param_text)
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        // 同权重时保持此顺序
+        let mut sections = vec![
+            (
+                self.config.summary_weight,
+                interface.summary.clone().unwrap_or_default(),
+            ),
+            (self.config.path_weight, path_tokens(&interface.path)),
+            (
+                self.config.description_weight,
+                interface.description.clone().unwrap_or_default(),
+            ),
+            (self.config.params_weight, params),
+            (self.config.tags_weight, interface.tags.join(" ")),
+        ];
+        sections.sort_by(|a, b| b.0.cmp(&a.0));
+
+        let mut parts = Vec::new();
+        for (weight, text) in sections {
+            let text = text.trim();
+            if text.is_empty() {
+                continue;
+            }
+            for _ in 0..weight {
+                parts.push(text.to_string());
+            }
+        }
+        if let Some(service_description) = &interface.service_description {
+            if !service_description.trim().is_empty() {
+                parts.push(service_description.trim().to_string());
+            }
+        }
+
+        let schema = [&interface.request_schema, &interface.response_schema]
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join(" ");
+        let schema = truncate_chars(schema.trim(), self.config.max_schema_chars);
+        if !schema.is_empty() {
+            parts.push(schema);
+        }
+
+        truncate_chars(&parts.join(SEPARATOR), self.config.max_chars)
+    }
+
+    /// 短查询追加同义词，长查询原样返回
+    pub fn expand_query(&self, query: &str) -> String {
+        let terms: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+        if terms.is_empty() || terms.len() > self.config.short_query_max_terms {
+            return query.to_string();
+        }
+
+        let mut expanded = query.to_string();
+        for term in &terms {
+            if let Some(synonyms) = self.config.synonyms.get(term) {
+                for synonym in synonyms {
+                    if !terms.contains(&synonym.to_lowercase()) {
+                        expanded.push(' ');
+                        expanded.push_str(synonym);
+                    }
+                }
+            }
+        }
+        expanded
+    }
+}
+
+fn param_text(param: &ApiParameter) -> String {
+    match &param.description {
+        Some(description) if !description.trim().is_empty() => {
+            format!("{} {}", param.name, description.trim())
+        }
+        _ => param.name.clone(),
+    }
+}
+
+/// /api/users/{userId} -> "api users userId"
+fn path_tokens(path: &str) -> String {
+    path.split(|c: char| !c.is_alphanumeric())
+        .filter(|token| !token.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// 按字符截断，避免依赖具体分词器
+fn truncate_chars(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((index, _)) => text[..index].to_string(),
+        None => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn interface() -> ApiInterface {
+        ApiInterface {
+            path: "/api/users/{userId}".to_string(),
+            method: "GET".to_string(),
+            summary: Some("获取用户".to_string()),
+            description: Some("根据用户ID查询用户详情".to_string()),
+            operation_id: Some("getUser".to_string()),
+            path_params: vec![ApiParameter {
+                name: "userId".to_string(),
+                param_type: "string".to_string(),
+                required: true,
+                description: Some("用户ID".to_string()),
+                example: None,
+                default_value: None,
+                enum_values: None,
+                format: None,
+            }],
+            query_params: vec![],
+            header_params: vec![],
+            body_params: vec![],
+            request_schema: None,
+            response_schema: Some(format!("{{\"properties\":{{{}}}}}", "x".repeat(2000))),
+            tags: vec!["user".to_string()],
+            domain: None,
+            deprecated: false,
+            service_description: Some("用户服务".to_string()),
+            embedding: None,
+            embedding_model: None,
+            embedding_updated_at: None,
+        }
+    }
+
+    #[test]
+    fn test_weighting_order() {
+        let text = EmbeddingTextBuilder::new(EmbeddingTextConfig::default()).build(&interface());
+        let parts: Vec<&str> = text.split(SEPARATOR).collect();
+        assert_eq!(&parts[..3], &["获取用户"; 3]);
+        assert_eq!(&parts[3..5], &["api users userId"; 2]);
+        assert_eq!(parts[5], "根据用户ID查询用户详情");
+        assert_eq!(parts[6], "userId 用户ID");
+        assert_eq!(parts[7], "user");
+        assert_eq!(parts[8], "用户服务");
+
+        // 权重为 0 的字段不参与
+        let config = EmbeddingTextConfig {
+            path_weight: 0,
+            description_weight: 4,
+            ..EmbeddingTextConfig::default()
+        };
+        let text = EmbeddingTextBuilder::new(config).build(&interface());
+        assert!(text.starts_with("根据用户ID查询用户详情"));
+        assert!(!text.contains("api users userId"));
+    }
+
+    #[test]
+    fn test_budget_enforcement() {
+        let builder = EmbeddingTextBuilder::new(EmbeddingTextConfig::default());
+        let text = builder.build(&interface());
+        let schema = text.split(SEPARATOR).last().unwrap();
+        assert_eq!(schema.chars().count(), 512);
+
+        let config = EmbeddingTextConfig {
+            max_chars: 10,
+            ..EmbeddingTextConfig::default()
+        };
+        let text = EmbeddingTextBuilder::new(config).build(&interface());
+        assert_eq!(text.chars().count(), 10);
+        assert!(text.starts_with("获取用户"));
+    }
+
+    #[test]
+    fn test_expand_short_query() {
+        let mut config = EmbeddingTextConfig::default();
+        config.synonyms.insert(
+            "user".to_string(),
+            vec!["account".to_string(), "member".to_string()],
+        );
+        let builder = EmbeddingTextBuilder::new(config);
+        assert_eq!(builder.expand_query("User"), "User account member");
+        assert_eq!(
+            builder.expand_query("find user by account"),
+            "find user by account"
+        );
+    }
+}
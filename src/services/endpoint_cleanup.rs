@@ -0,0 +1,141 @@
+use crate::models::{CleanupOutboxItem, DbPool, Endpoint, JobStatus};
+use crate::services::interface_retrieval_service::InterfaceRetrievalService;
+use crate::services::{
+    canary_configs, endpoint_plugins, enqueue_job, locale_settings, JobService, SpecCache,
+};
+use crate::utils::{set_endpoint_dns_overrides, HostOverrides};
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use sqlx::Row;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// 清理向量库中端点的接口数据
+pub const JOB_ENDPOINT_VECTOR_CLEANUP: &str = "endpoint_vector_cleanup";
+
+/// outbox 视图中向量清理的 action，与原 endpoint_cleanup_outbox 保持一致
+pub const ACTION_VECTOR_CLEANUP: &str = "vector_cleanup";
+
+/// 引用 endpoint_id 的表，删除端点时一并清理
+pub const ENDPOINT_DEPENDENT_TABLES: &[&str] = &[
+    "api_paths",
+    "endpoint_metrics",
+    "endpoint_metrics_history",
+    "endpoint_logs",
+    "endpoint_session_logs",
+    "endpoint_connection_counts",
+    "contract_test_overrides",
+    "operation_notes",
+    "operation_note_settings",
+    "composite_tools",
+    "endpoint_execution_policies",
+    "endpoint_recordings",
+    "endpoint_warmups",
+    "endpoint_canaries",
+    "endpoint_canary_calls",
+    "endpoint_plugins",
+    "endpoint_dns_overrides",
+    "t_endpoint_kv",
+    "endpoint_kv_settings",
+    "endpoint_locale_settings",
+];
+
+/// 在同一事务内删除端点及其关联数据，并入队向量清理任务
+pub async fn delete_endpoint_cascade(pool: &DbPool, endpoint: &Endpoint) -> Result<()> {
+    let endpoint_id = endpoint.id.to_string();
+    let mut tx = pool.begin().await?;
+
+    for table in ENDPOINT_DEPENDENT_TABLES {
+        sqlx::query(&format!("DELETE FROM {} WHERE endpoint_id = ?", table))
+            .bind(&endpoint_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+    sqlx::query("DELETE FROM endpoints WHERE id = ?")
+        .bind(&endpoint_id)
+        .execute(&mut *tx)
+        .await?;
+    enqueue_job(
+        &mut *tx,
+        JOB_ENDPOINT_VECTOR_CLEANUP,
+        &json!({ "endpoint_id": endpoint_id, "endpoint_name": endpoint.name }),
+        None,
+    )
+    .await?;
+
+    tx.commit().await?;
+    canary_configs().remove(&endpoint.id);
+    endpoint_plugins().remove(&endpoint.id);
+    locale_settings().remove(&endpoint.id);
+    set_endpoint_dns_overrides(endpoint.id, HostOverrides::new());
+    Ok(())
+}
+
+/// 未完成的向量清理任务（含重试用尽的 failed），按创建时间排序
+pub async fn pending_cleanup_items(pool: &DbPool) -> Result<Vec<CleanupOutboxItem>> {
+    let rows = sqlx::query(
+        "SELECT id, payload, status, attempts, last_error, created_at FROM t_jobs WHERE job_type = ? AND status <> ? ORDER BY created_at",
+    )
+    .bind(JOB_ENDPOINT_VECTOR_CLEANUP)
+    .bind(JobStatus::Completed.as_str())
+    .fetch_all(pool)
+    .await?;
+
+    rows.iter()
+        .map(|row| {
+            let id: String = row.try_get("id")?;
+            let payload: String = row.try_get("payload")?;
+            let payload: serde_json::Value = serde_json::from_str(&payload)?;
+            let status: String = row.try_get("status")?;
+            Ok(CleanupOutboxItem {
+                id: Uuid::parse_str(&id)?,
+                endpoint_id: Uuid::parse_str(payload["endpoint_id"].as_str().unwrap_or_default())?,
+                endpoint_name: payload["endpoint_name"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string(),
+                action: ACTION_VECTOR_CLEANUP.to_string(),
+                status: JobStatus::parse(&status)
+                    .ok_or_else(|| anyhow!("Unknown job status: {}", status))?,
+                attempts: row.try_get("attempts")?,
+                last_error: row.try_get("last_error")?,
+                created_at: row.try_get("created_at")?,
+            })
+        })
+        .collect()
+}
+
+/// 注册向量清理任务的处理函数，失败按任务重试策略重试
+pub fn register_vector_cleanup_job(
+    jobs: &JobService,
+    retrieval: Arc<InterfaceRetrievalService>,
+    spec_cache: Arc<SpecCache>,
+) {
+    jobs.register(JOB_ENDPOINT_VECTOR_CLEANUP, move |job| {
+        let retrieval = retrieval.clone();
+        let spec_cache = spec_cache.clone();
+        async move {
+            let endpoint_name = job.payload["endpoint_name"]
+                .as_str()
+                .ok_or_else(|| anyhow!("Job {} has no endpoint_name", job.id))?;
+            spec_cache.invalidate_name(endpoint_name);
+            let deleted = retrieval.delete_project_data(endpoint_name).await?;
+            tracing::info!(
+                "Deleted vector data of endpoint {}: {}",
+                endpoint_name,
+                deleted
+            );
+            Ok(())
+        }
+    });
+}
+
+/// 清理早于 before 的指标快照，返回删除行数
+pub async fn purge_metrics_history(pool: &DbPool, before: DateTime<Utc>) -> Result<u64> {
+    let result = sqlx::query("DELETE FROM endpoint_metrics_history WHERE snapshot_at < ?")
+        .bind(before)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected())
+}
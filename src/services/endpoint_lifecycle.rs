@@ -0,0 +1,103 @@
+use crate::models::{
+    DbPool, EndpointBulkFilter, EndpointLifecycleEvent, LifecycleAction, LifecycleOutcome,
+};
+use crate::utils::get_china_time;
+use anyhow::{anyhow, Result};
+use sqlx::Row;
+use uuid::Uuid;
+
+/// 审计记录来源：批量启停接口
+pub const LIFECYCLE_SOURCE_BULK: &str = "bulk";
+/// 审计记录来源：启动时的状态对账
+pub const LIFECYCLE_SOURCE_RECONCILE: &str = "reconcile";
+
+/// 校验筛选条件中的状态，返回小写状态；为空或 all 表示不按状态筛选
+pub fn bulk_status_filter(filter: &EndpointBulkFilter) -> Result<Option<String>> {
+    let status = match filter.status.as_deref().map(|s| s.trim().to_lowercase()) {
+        None => return Ok(None),
+        Some(status) => status,
+    };
+    match status.as_str() {
+        "" | "all" => Ok(None),
+        "running" | "stopped" => Ok(Some(status)),
+        _ => Err(anyhow!("Invalid status filter: {}", status)),
+    }
+}
+
+pub async fn record_lifecycle_event(
+    pool: &DbPool,
+    endpoint_id: Uuid,
+    endpoint_name: &str,
+    action: LifecycleAction,
+    source: &str,
+    outcome: LifecycleOutcome,
+    reason: Option<&str>,
+) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO endpoint_lifecycle_events (endpoint_id, endpoint_name, action, source, outcome, reason, created_at) VALUES (?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(endpoint_id.to_string())
+    .bind(endpoint_name)
+    .bind(action.as_str())
+    .bind(source)
+    .bind(outcome.as_str())
+    .bind(reason)
+    .bind(get_china_time())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// 端点最近的生命周期记录，新的在前
+pub async fn list_lifecycle_events(
+    pool: &DbPool,
+    endpoint_id: Uuid,
+    limit: u32,
+) -> Result<Vec<EndpointLifecycleEvent>> {
+    let rows = sqlx::query(
+        "SELECT id, endpoint_id, endpoint_name, action, source, outcome, reason, created_at FROM endpoint_lifecycle_events WHERE endpoint_id = ? ORDER BY id DESC LIMIT ?",
+    )
+    .bind(endpoint_id.to_string())
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+    rows.iter().map(event_from_row).collect()
+}
+
+fn event_from_row(row: &sqlx::mysql::MySqlRow) -> Result<EndpointLifecycleEvent> {
+    let endpoint_id: String = row.try_get("endpoint_id")?;
+    let action: String = row.try_get("action")?;
+    let outcome: String = row.try_get("outcome")?;
+    Ok(EndpointLifecycleEvent {
+        id: row.try_get("id")?,
+        endpoint_id: Uuid::parse_str(&endpoint_id)?,
+        endpoint_name: row.try_get("endpoint_name")?,
+        action: LifecycleAction::parse(&action)
+            .ok_or_else(|| anyhow!("Unknown lifecycle action: {}", action))?,
+        source: row.try_get("source")?,
+        outcome: LifecycleOutcome::parse(&outcome)
+            .ok_or_else(|| anyhow!("Unknown lifecycle outcome: {}", outcome))?,
+        reason: row.try_get("reason")?,
+        created_at: row.try_get("created_at")?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bulk_status_filter() {
+        let filter = |status: Option<&str>| EndpointBulkFilter {
+            status: status.map(str::to_string),
+            ..Default::default()
+        };
+        assert_eq!(bulk_status_filter(&filter(None)).unwrap(), None);
+        assert_eq!(bulk_status_filter(&filter(Some("all"))).unwrap(), None);
+        assert_eq!(
+            bulk_status_filter(&filter(Some(" Running "))).unwrap(),
+            Some("running".to_string())
+        );
+        assert!(bulk_status_filter(&filter(Some("deleted"))).is_err());
+    }
+}
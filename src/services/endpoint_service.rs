@@ -1,26 +1,251 @@
 use crate::models::{
-    CreateEndpointRequest, DbPool, Endpoint, EndpointDetailResponse,
-    EndpointResponse, EndpointStatus, UpdateEndpointRequest,
+    BulkLifecycleResult, CreateEndpointRequest, DbPool, Endpoint, EndpointBulkFilter, EndpointDetailResponse,
+    EndpointResponse, EndpointStatus, LifecycleAction, LifecycleOutcome, SkippedOperation, SwaggerMergeConflict, SwaggerMergeResponse, UpdateEndpointRequest, WarmupStatus,
 };
-use crate::models::endpoint::{McpConfig, EndpointMetrics};
-use crate::services::EndpointEvent;
-use crate::utils::{generate_api_details, get_china_time};
-use anyhow::Result;
+use crate::models::endpoint::{normalize_endpoint_name, normalize_tags, tags_column, McpConfig, EndpointMetrics, ToolLimitAlert};
+use crate::config::{EndpointLifecycleConfig, ToolLimitsConfig, WarmupConfig};
+use crate::services::{
+    bulk_status_filter, delete_endpoint_cascade, duplicate_operations, is_http_method,
+    is_recording_spec, latest_notes, latest_warmup, mcp_method_counters, record_lifecycle_event, record_warmup,
+    spec_operations, tool_stats, validate_swagger_spec, warm_endpoint, wildcard_match,
+    EndpointEvent, SpecCache, LIFECYCLE_SOURCE_BULK, LIFECYCLE_SOURCE_RECONCILE,
+};
+use crate::utils::{
+    check_tool_limits, generate_api_details, generate_mcp_tools, generate_webhook_details,
+    get_china_time, resolve_server_url, validate_server_variables, FieldSelection,
+};
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use futures::stream::{self, BoxStream, StreamExt};
 use serde_json::Value;
 use sqlx::Row;
 use std::convert::TryInto;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
 use tokio::sync::mpsc;
+use tokio::sync::mpsc::error::{SendTimeoutError, TrySendError};
 use uuid::Uuid;
 
+/// 端点列表与详情中与列同名的字段
+const ENDPOINT_COLUMNS: &[(&str, &str)] = &[
+    ("name", "name"),
+    ("description", "description"),
+    ("status", "status"),
+    ("created_at", "created_at"),
+    ("updated_at", "updated_at"),
+    ("connection_count", "connection_count"),
+    ("preferred_content_type", "preferred_content_type"),
+    ("mock_mode", "mock_mode"),
+    ("server_variables", "server_variables"),
+    ("status_mapping", "status_mapping"),
+    ("tool_stats", "tool_stats"),
+    ("hide_deprecated", "hide_deprecated"),
+    ("tags", "tags"),
+    ("warnings", "tool_limit_warnings"),
+];
+
+/// 查询完整 Endpoint 的 SELECT 前缀，列取自 ENDPOINT_COLUMNS；端点新增列只需加入 ENDPOINT_COLUMNS
+pub fn endpoint_select() -> &'static str {
+    static ENDPOINT_SELECT: OnceLock<String> = OnceLock::new();
+    ENDPOINT_SELECT.get_or_init(|| {
+        let columns = FieldSelection::all().columns(ENDPOINT_COLUMNS, &["id", "swagger_content"]);
+        format!("SELECT {} FROM endpoints", columns.join(", "))
+    })
+}
+
+/// 端点详情中由其他列派生的字段
+const ENDPOINT_DERIVED_COLUMNS: &[(&str, &str)] = &[
+    ("mcp_config", "name"),
+    ("swagger_spec", "swagger_content"),
+    ("api_details", "swagger_content"),
+    ("webhooks", "swagger_content"),
+    ("warnings", "swagger_content"),
+    ("skipped_operations", "swagger_content"),
+    ("base_url", "swagger_content"),
+    ("base_url", "server_variables"),
+];
+
+/// 写入 swagger 后发出工具阈值告警；端点当前的告警存于 tool_limit_warnings，
+/// 由 /api/metrics/tool-limits 以指标形式输出
+fn alert_tool_limit_warnings(endpoint_name: &str, warnings: &[String]) {
+    for warning in warnings {
+        tracing::warn!(endpoint = endpoint_name, "Tool limit warning: {}", warning);
+    }
+}
+
+/// 以 swagger 中解析成功的操作重写端点的 api_paths，返回写入的行数
+async fn write_api_paths(
+    conn: &mut sqlx::MySqlConnection,
+    endpoint_id: Uuid,
+    swagger_spec: &Value,
+) -> Result<usize> {
+    // Clear existing entries for this endpoint
+    sqlx::query("DELETE FROM api_paths WHERE endpoint_id = ?")
+        .bind(endpoint_id.to_string())
+        .execute(&mut *conn)
+        .await?;
+
+    let mut written = 0usize;
+
+    // 只写入解析成功的操作，跳过的不合法操作不生成工具
+    let spec: crate::models::SwaggerSpec = serde_json::from_value(swagger_spec.clone())?;
+    for (path, path_item) in &spec.paths {
+        let methods = [
+            ("GET", &path_item.get),
+            ("POST", &path_item.post),
+            ("PUT", &path_item.put),
+            ("DELETE", &path_item.delete),
+            ("PATCH", &path_item.patch),
+        ];
+        for (method, operation) in methods {
+            let Some(operation) = operation else {
+                continue;
+            };
+
+            // Insert the API path entry
+            let api_path_id = Uuid::new_v4();
+            sqlx::query(
+                "INSERT INTO api_paths (id, endpoint_id, path, method, operation_id, summary, description) VALUES (?, ?, ?, ?, ?, ?, ?)"
+            )
+                .bind(api_path_id.to_string())
+                .bind(endpoint_id.to_string())
+                .bind(path)
+                .bind(method)
+                .bind(&operation.operation_id)
+                .bind(&operation.summary)
+                .bind(&operation.description)
+                .execute(&mut *conn)
+                .await?;
+            written += 1;
+        }
+    }
+
+    Ok(written)
+}
+
 #[derive(Clone)]
 pub struct EndpointService {
     pool: DbPool,
     event_sender: mpsc::Sender<EndpointEvent>,
+    event_retries: u32,
+    event_retry_interval: Duration,
+    tool_limits: ToolLimitsConfig,
+    warmup: WarmupConfig,
+    lifecycle: EndpointLifecycleConfig,
+    /// 端点变更后失效的解析缓存，与 Adapter 共用
+    spec_cache: Arc<SpecCache>,
 }
 
 impl EndpointService {
-    pub fn new(pool: DbPool, event_sender: mpsc::Sender<EndpointEvent>) -> Self {
-        Self { pool, event_sender }
+    pub fn new(
+        pool: DbPool,
+        event_sender: mpsc::Sender<EndpointEvent>,
+        spec_cache: Arc<SpecCache>,
+    ) -> Self {
+        Self {
+            pool,
+            event_sender,
+            event_retries: 3,
+            event_retry_interval: Duration::from_millis(500),
+            tool_limits: ToolLimitsConfig::default(),
+            warmup: WarmupConfig::default(),
+            lifecycle: EndpointLifecycleConfig::default(),
+            spec_cache,
+        }
+    }
+
+    pub fn spec_cache(&self) -> &Arc<SpecCache> {
+        &self.spec_cache
+    }
+
+    /// 设置事件通道满时的后台重试策略
+    pub fn with_event_retry(mut self, retries: u32, interval: Duration) -> Self {
+        self.event_retries = retries;
+        self.event_retry_interval = interval;
+        self
+    }
+
+    /// 设置工具数量/体积阈值
+    pub fn with_tool_limits(mut self, limits: ToolLimitsConfig) -> Self {
+        self.tool_limits = limits;
+        self
+    }
+
+    /// 设置端点启动预热
+    pub fn with_warmup(mut self, warmup: WarmupConfig) -> Self {
+        self.warmup = warmup;
+        self
+    }
+
+    /// 设置批量启停并发与启动对账
+    pub fn with_lifecycle(mut self, lifecycle: EndpointLifecycleConfig) -> Self {
+        self.lifecycle = lifecycle;
+        self
+    }
+
+    /// 检查 swagger 生成的工具是否超过阈值：超过硬阈值返回错误，超过软阈值返回告警
+    pub fn check_tool_limits(&self, swagger: &crate::models::SwaggerSpec) -> Result<Vec<String>> {
+        let tools = generate_mcp_tools(swagger)?;
+        let report = check_tool_limits(&tools, &self.tool_limits);
+        if !report.violations.is_empty() {
+            return Err(anyhow!(
+                "Tool limits exceeded: {}. Split the spec or list the tools to expose in x-mcp-tool-allowlist",
+                report.violations.join("; ")
+            ));
+        }
+        Ok(report.warnings)
+    }
+
+    /// 解析 swagger 并检查工具阈值，返回解析时跳过的不合法操作与工具阈值告警
+    fn check_swagger_value(&self, swagger: &Value) -> Result<(Vec<SkippedOperation>, Vec<String>)> {
+        let spec: crate::models::SwaggerSpec = serde_json::from_value(swagger.clone())?;
+        let warnings = self.check_tool_limits(&spec)?;
+        for skipped in &spec.skipped_operations {
+            tracing::warn!(
+                "Skipping invalid operation {} {}: {}",
+                skipped.method,
+                skipped.path,
+                skipped.error
+            );
+        }
+        Ok((spec.skipped_operations, warnings))
+    }
+
+    /// 发送端点事件，失败不影响已落库的结果，仅记录日志并在后台重试
+    fn publish_event(&self, event: EndpointEvent) {
+        match self.event_sender.try_send(event) {
+            Ok(()) => {}
+            Err(TrySendError::Full(event)) => {
+                tracing::warn!("Endpoint event channel is full, retrying in background");
+                let sender = self.event_sender.clone();
+                let retries = self.event_retries;
+                let interval = self.event_retry_interval;
+                tokio::spawn(async move {
+                    let mut event = event;
+                    for attempt in 1..=retries {
+                        match sender.send_timeout(event, interval).await {
+                            Ok(()) => return,
+                            Err(SendTimeoutError::Timeout(e)) => {
+                                tracing::warn!(
+                                    "Endpoint event send timed out (attempt {}/{})",
+                                    attempt,
+                                    retries
+                                );
+                                event = e;
+                            }
+                            Err(SendTimeoutError::Closed(_)) => {
+                                tracing::error!("Endpoint event listener is closed, event dropped");
+                                return;
+                            }
+                        }
+                    }
+                    tracing::error!("Endpoint event dropped after {} retries", retries);
+                });
+            }
+            Err(TrySendError::Closed(_)) => {
+                tracing::error!("Endpoint event listener is closed, event dropped");
+            }
+        }
     }
 
     pub fn get_pool(&self) -> &DbPool {
@@ -29,15 +254,17 @@ impl EndpointService {
 
     pub async fn create_endpoint(
         &self,
-        request: CreateEndpointRequest,
+        mut request: CreateEndpointRequest,
     ) -> Result<EndpointResponse> {
+        // 名称是合并键，规范化后仅空白不同的名称视为同一端点
+        request.name = normalize_endpoint_name(&request.name)?;
+
         // First, check if an endpoint with the same name already exists
-        let existing_endpoint = sqlx::query_as::<_, Endpoint>(
-            "SELECT id, name, description, swagger_content, status, created_at, updated_at, connection_count FROM endpoints WHERE name = ?"
-        )
-            .bind(&request.name)
-            .fetch_optional(&self.pool)
-            .await?;
+        let existing_endpoint =
+            sqlx::query_as::<_, Endpoint>(&format!("{} WHERE name = ?", endpoint_select()))
+                .bind(&request.name)
+                .fetch_optional(&self.pool)
+                .await?;
 
         if let Some(endpoint) = existing_endpoint {
             // If endpoint with same name exists, merge the data instead of creating new one
@@ -52,59 +279,78 @@ impl EndpointService {
 
             // Merge the swagger specifications
             let merged_swagger = self.merge_swagger_specs(existing_swagger, new_swagger)?;
+            let (skipped_operations, warnings) = self.check_swagger_value(&merged_swagger)?;
+
+            // 标签取并集
+            let mut tags = endpoint.tags.clone();
+            tags.extend(request.tags.iter().cloned());
+            let tags = normalize_tags(&tags)?;
 
             // Update the existing endpoint with merged data
             let now = get_china_time();
             sqlx::query(
-                "UPDATE endpoints SET description = COALESCE(?, description), swagger_content = ?, updated_at = ? WHERE id = ?"
+                "UPDATE endpoints SET description = COALESCE(?, description), swagger_content = ?, tags = ?, tool_limit_warnings = ?, updated_at = ? WHERE id = ?"
             )
                 .bind(&request.description)
                 .bind(serde_json::to_string(&merged_swagger)?)
+                .bind(tags_column(&tags)?)
+                .bind(serde_json::to_string(&warnings)?)
                 .bind(now)
                 .bind(endpoint.id.to_string())
                 .execute(&self.pool)
                 .await?;
+            self.spec_cache.invalidate(&endpoint.id);
+            alert_tool_limit_warnings(&endpoint.name, &warnings);
 
             // Update API paths table with new paths
             self.update_api_paths_table(endpoint.id, &merged_swagger)
                 .await?;
 
             let updated_endpoint = self.get_endpoint_by_id(endpoint.id).await?;
-            self.event_sender
-                .send(EndpointEvent::UPDATE(endpoint.name))
-                .await?;
-            Ok(updated_endpoint.into())
+            self.publish_event(EndpointEvent::UPDATE(endpoint.name));
+            Ok(EndpointResponse {
+                skipped_operations,
+                ..updated_endpoint.into()
+            })
         } else {
             // Create new endpoint
+            let swagger_spec: Value = serde_json::from_str(&request.swagger_content)?;
+            let (skipped_operations, warnings) = self.check_swagger_value(&swagger_spec)?;
+            let tags = normalize_tags(&request.tags)?;
+
             let id = Uuid::new_v4();
             let now = get_china_time();
 
             let _endpoint_result = sqlx::query(
                 r#"
-                INSERT INTO endpoints (id, name, description, swagger_content, status, created_at, updated_at, connection_count)
-                VALUES (?, ?, ?, ?, 'stopped', ?, ?, 0)
+                INSERT INTO endpoints (id, name, description, swagger_content, tags, tool_limit_warnings, status, created_at, updated_at, connection_count)
+                VALUES (?, ?, ?, ?, ?, ?, 'stopped', ?, ?, 0)
                 "#,
             )
                 .bind(id.to_string())
                 .bind(&request.name)
                 .bind(&request.description)
                 .bind(&request.swagger_content)
+                .bind(tags_column(&tags)?)
+                .bind(serde_json::to_string(&warnings)?)
                 .bind(now)
                 .bind(now)
                 .execute(&self.pool)
                 .await?;
 
-            // Parse swagger content and populate API paths table
-            let swagger_spec: Value = serde_json::from_str(&request.swagger_content)?;
+            alert_tool_limit_warnings(&request.name, &warnings);
+
+            // Populate API paths table
             self.update_api_paths_table(id, &swagger_spec).await?;
 
             let endpoint = self.get_endpoint_by_id(id).await?;
 
-            self.event_sender
-                .send(EndpointEvent::Created(endpoint.name.clone()))
-                .await?;
+            self.publish_event(EndpointEvent::Created(endpoint.name.clone()));
 
-            Ok(endpoint.into())
+            Ok(EndpointResponse {
+                skipped_operations,
+                ..endpoint.into()
+            })
         }
     }
 
@@ -130,7 +376,7 @@ impl EndpointService {
                     ) {
                         // Merge HTTP methods
                         for (method, new_operation) in new_path_item {
-                            if method.to_uppercase() != *method {
+                            if !is_http_method(method) {
                                 // Skip non-HTTP methods
                                 continue;
                             }
@@ -152,84 +398,155 @@ impl EndpointService {
             }
         }
 
+        // Merge webhooks (OpenAPI 3.1), keeping existing definitions on conflict
+        if let Some(new_webhooks) = new.get("webhooks").and_then(|v| v.as_object()) {
+            if let Some(merged_obj) = merged.as_object_mut() {
+                let existing_webhooks = merged_obj
+                    .entry("webhooks")
+                    .or_insert_with(|| Value::Object(serde_json::Map::new()));
+                if let Some(existing_webhooks) = existing_webhooks.as_object_mut() {
+                    for (name, webhook) in new_webhooks {
+                        if !existing_webhooks.contains_key(name) {
+                            existing_webhooks.insert(name.clone(), webhook.clone());
+                        }
+                    }
+                }
+            }
+        }
+
         Ok(merged)
     }
 
+    /// 显式合并 swagger：事务内锁定端点，路径与方法有重复时整体拒绝，
+    /// 否则写入合并结果并重建 api_paths；dry_run 时只返回合并结果
+    pub async fn merge_swagger(
+        &self,
+        id: Uuid,
+        new_swagger: Value,
+        dry_run: bool,
+    ) -> Result<SwaggerMergeResponse> {
+        let mut tx = self.pool.begin().await?;
+        let row =
+            sqlx::query("SELECT name, swagger_content FROM endpoints WHERE id = ? FOR UPDATE")
+                .bind(id.to_string())
+                .fetch_optional(&mut *tx)
+                .await?
+                .ok_or_else(|| anyhow!("Endpoint not found"))?;
+        let name: String = row.try_get("name")?;
+        let swagger_content: String = row.try_get("swagger_content")?;
+        let existing: Value = serde_json::from_str(&swagger_content)?;
+
+        let conflicts = duplicate_operations(&existing, &new_swagger);
+        if !conflicts.is_empty() {
+            return Err(SwaggerMergeConflict { conflicts }.into());
+        }
+        let added_operations = spec_operations(&new_swagger);
+        let merged = self.merge_swagger_specs(existing, new_swagger)?;
+        let (skipped_operations, warnings) = self.check_swagger_value(&merged)?;
+        if dry_run {
+            return Ok(SwaggerMergeResponse {
+                endpoint_id: id,
+                applied: false,
+                added_operations,
+                swagger_spec: merged,
+                skipped_operations,
+                warnings,
+            });
+        }
+
+        sqlx::query(
+            "UPDATE endpoints SET swagger_content = ?, tool_limit_warnings = ?, updated_at = ? WHERE id = ?",
+        )
+        .bind(serde_json::to_string(&merged)?)
+        .bind(serde_json::to_string(&warnings)?)
+        .bind(get_china_time())
+        .bind(id.to_string())
+        .execute(&mut *tx)
+        .await?;
+        write_api_paths(&mut tx, id, &merged).await?;
+        tx.commit().await?;
+        self.spec_cache.invalidate(&id);
+        alert_tool_limit_warnings(&name, &warnings);
+        self.publish_event(EndpointEvent::UPDATE(name.clone()));
+        tracing::info!(
+            "Merged {} operations into endpoint: {} ({})",
+            added_operations.len(),
+            name,
+            id
+        );
+        Ok(SwaggerMergeResponse {
+            endpoint_id: id,
+            applied: true,
+            added_operations,
+            swagger_spec: merged,
+            skipped_operations,
+            warnings,
+        })
+    }
+
     /// Update the api_paths table with paths and methods from swagger spec
-    async fn update_api_paths_table(&self, endpoint_id: Uuid, swagger_spec: &Value) -> Result<()> {
-        // Clear existing entries for this endpoint
-        sqlx::query("DELETE FROM api_paths WHERE endpoint_id = ?")
-            .bind(endpoint_id.to_string())
-            .execute(&self.pool)
-            .await?;
+    /// 返回写入的行数
+    async fn update_api_paths_table(&self, endpoint_id: Uuid, swagger_spec: &Value) -> Result<usize> {
+        let mut conn = self.pool.acquire().await?;
+        write_api_paths(&mut conn, endpoint_id, swagger_spec).await
+    }
 
-        // Extract paths and methods from swagger spec
-        if let Some(paths) = swagger_spec.get("paths").and_then(|v| v.as_object()) {
-            for (path, path_item) in paths {
-                if let Some(path_item_obj) = path_item.as_object() {
-                    // Process each HTTP method for this path
-                    for (method, operation) in path_item_obj {
-                        if method.to_uppercase() != *method {
-                            // Skip non-HTTP methods
-                            continue;
-                        }
+    /// 根据已存储的 swagger_content 重建 api_paths（修复漂移）
+    pub async fn rebuild_api_paths(&self, id: Uuid) -> Result<usize> {
+        let endpoint = self.get_endpoint_by_id(id).await?;
 
-                        let operation_id = operation
-                            .get("operationId")
-                            .and_then(|v| v.as_str())
-                            .map(|s| s.to_string());
-                        let summary = operation
-                            .get("summary")
-                            .and_then(|v| v.as_str())
-                            .map(|s| s.to_string());
-                        let description = operation
-                            .get("description")
-                            .and_then(|v| v.as_str())
-                            .map(|s| s.to_string());
-
-                        // Insert the API path entry
-                        let api_path_id = Uuid::new_v4();
-                        sqlx::query(
-                            "INSERT INTO api_paths (id, endpoint_id, path, method, operation_id, summary, description) VALUES (?, ?, ?, ?, ?, ?, ?)"
-                        )
-                            .bind(api_path_id.to_string())
-                            .bind(endpoint_id.to_string())
-                            .bind(path)
-                            .bind(method.to_uppercase())
-                            .bind(operation_id)
-                            .bind(summary)
-                            .bind(description)
-                            .execute(&self.pool)
-                            .await?;
-                    }
-                }
-            }
-        }
+        let swagger_spec: Value = serde_json::from_str(&endpoint.swagger_content)
+            .map_err(|e| anyhow::anyhow!("Invalid swagger content: {}", e))?;
 
-        Ok(())
+        let written = self.update_api_paths_table(id, &swagger_spec).await?;
+        tracing::info!(
+            "Rebuilt {} api_paths rows for endpoint: {} ({})",
+            written,
+            endpoint.name,
+            id
+        );
+        Ok(written)
     }
 
     pub async fn get_endpoints(&self) -> Result<Vec<EndpointResponse>> {
-        let endpoints = sqlx::query_as::<_, Endpoint>(
-            "SELECT id, name, description, swagger_content, status, created_at, updated_at, connection_count FROM endpoints ORDER BY created_at DESC"
-        )
-            .fetch_all(&self.pool)
-            .await?;
+        let endpoints = sqlx::query_as::<_, Endpoint>(&format!(
+            "{} ORDER BY created_at DESC",
+            endpoint_select()
+        ))
+        .fetch_all(&self.pool)
+        .await?;
 
         Ok(endpoints.into_iter().map(|e| e.into()).collect())
     }
 
     /// Get all endpoints with full data (including swagger_content)
     pub async fn get_all_endpoints(&self) -> Result<Vec<Endpoint>> {
-        let endpoints = sqlx::query_as::<_, Endpoint>(
-            "SELECT id, name, description, swagger_content, status, created_at, updated_at, connection_count FROM endpoints ORDER BY created_at DESC"
-        )
-            .fetch_all(&self.pool)
-            .await?;
+        let endpoints = sqlx::query_as::<_, Endpoint>(&format!(
+            "{} ORDER BY created_at DESC",
+            endpoint_select()
+        ))
+        .fetch_all(&self.pool)
+        .await?;
 
         Ok(endpoints)
     }
 
+    /// 逐行读取全部端点（含 swagger_content），用于流式导出；接收端断开时停止读取
+    pub fn stream_all_endpoints(&self) -> BoxStream<'static, Result<Endpoint>> {
+        let pool = self.pool.clone();
+        let (tx, rx) = mpsc::channel::<Result<Endpoint>>(16);
+        tokio::spawn(async move {
+            let sql = format!("{} ORDER BY created_at DESC", endpoint_select());
+            let mut rows = sqlx::query_as::<_, Endpoint>(&sql).fetch(&pool);
+            while let Some(row) = rows.next().await {
+                if tx.send(row.map_err(anyhow::Error::from)).await.is_err() {
+                    break;
+                }
+            }
+        });
+        stream::unfold(rx, |mut rx| async move { rx.recv().await.map(|row| (row, rx)) }).boxed()
+    }
+
     /// Get endpoints with pagination, search and filter support
     pub async fn get_endpoints_paginated(
         &self,
@@ -237,6 +554,9 @@ impl EndpointService {
         page_size: Option<u32>,
         search: Option<String>,
         status_filter: Option<String>,
+        updated_after: Option<DateTime<Utc>>,
+        tag: Option<String>,
+        fields: &FieldSelection,
     ) -> Result<(Vec<EndpointResponse>, u64)> {
         let page = page.unwrap_or(1);
         let page_size = page_size.unwrap_or(10);
@@ -264,19 +584,50 @@ impl EndpointService {
             }
         }
 
+        // Add updated_after filter
+        if let Some(updated_after) = updated_after {
+            where_conditions.push("updated_at > ?".to_string());
+            params.push(
+                updated_after
+                    .naive_utc()
+                    .format("%Y-%m-%d %H:%M:%S%.6f")
+                    .to_string(),
+            );
+        }
+
+        // 标签已规范化为小写存储
+        if let Some(tag) = tag {
+            if !tag.trim().is_empty() {
+                where_conditions.push("JSON_CONTAINS(tags, JSON_QUOTE(?))".to_string());
+                params.push(tag.trim().to_lowercase());
+            }
+        }
+
+        // 列表不返回 swagger，只查询选中字段对应的列
+        let columns = fields.columns(ENDPOINT_COLUMNS, &["id"]).join(", ");
+
         // Build WHERE clause
         let (_where_clause, count_query, query) = if where_conditions.is_empty() {
             (
                 String::new(),
                 "SELECT COUNT(*) as total FROM endpoints".to_string(),
-                "SELECT id, name, description, swagger_content, status, created_at, updated_at, connection_count FROM endpoints ORDER BY created_at DESC LIMIT ? OFFSET ?".to_string(),
+                format!(
+                    "SELECT {} FROM endpoints ORDER BY created_at DESC LIMIT ? OFFSET ?",
+                    columns
+                ),
             )
         } else {
             let where_clause = where_conditions.join(" AND ");
             (
                 where_clause.clone(),
-                format!("SELECT COUNT(*) as total FROM endpoints WHERE {}", where_clause),
-                format!("SELECT id, name, description, swagger_content, status, created_at, updated_at, connection_count FROM endpoints WHERE {} ORDER BY created_at DESC LIMIT ? OFFSET ?", where_clause),
+                format!(
+                    "SELECT COUNT(*) as total FROM endpoints WHERE {}",
+                    where_clause
+                ),
+                format!(
+                    "SELECT {} FROM endpoints WHERE {} ORDER BY created_at DESC LIMIT ? OFFSET ?",
+                    columns, where_clause
+                ),
             )
         };
 
@@ -305,24 +656,22 @@ impl EndpointService {
     }
 
     pub async fn get_endpoint_by_id(&self, id: Uuid) -> Result<Endpoint> {
-        let endpoint = sqlx::query_as::<_, Endpoint>(
-            "SELECT id, name, description, swagger_content, status, created_at, updated_at, connection_count FROM endpoints WHERE id = ?"
-        )
-            .bind(id.to_string())
-            .fetch_optional(&self.pool)
-            .await?
-            .ok_or_else(|| anyhow::anyhow!("Endpoint not found"))?;
+        let endpoint =
+            sqlx::query_as::<_, Endpoint>(&format!("{} WHERE id = ?", endpoint_select()))
+                .bind(id.to_string())
+                .fetch_optional(&self.pool)
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("Endpoint not found"))?;
 
         Ok(endpoint)
     }
 
     pub async fn get_endpoint_by_name(&self, name: String) -> Result<Endpoint> {
-        let endpoint = sqlx::query_as::<_, Endpoint>(
-            "SELECT id, name, description, swagger_content, status, created_at, updated_at, connection_count FROM endpoints WHERE name = ?"
-        )
-            .bind(name)
-            .fetch_one(&self.pool)
-            .await?;
+        let endpoint =
+            sqlx::query_as::<_, Endpoint>(&format!("{} WHERE name = ?", endpoint_select()))
+                .bind(name)
+                .fetch_one(&self.pool)
+                .await?;
 
         Ok(endpoint)
     }
@@ -336,10 +685,7 @@ impl EndpointService {
         let placeholders: Vec<String> = (1..=names.len()).map(|i| format!("${}", i)).collect();
         let in_clause = placeholders.join(", ");
 
-        let query = format!(
-            "SELECT id, name, description, swagger_content, status, created_at, updated_at, connection_count FROM endpoints WHERE name IN ({})",
-            in_clause
-        );
+        let query = format!("{} WHERE name IN ({})", endpoint_select(), in_clause);
 
         let mut query_builder = sqlx::query_as::<_, Endpoint>(&query);
 
@@ -353,10 +699,79 @@ impl EndpointService {
     }
 
     pub async fn get_endpoint_detail(&self, id: Uuid) -> Result<EndpointDetailResponse> {
-        let endpoint = self.get_endpoint_by_id(id).await?;
+        self.get_endpoint_detail_fields(id, &FieldSelection::all())
+            .await
+    }
+
+    /// 只查询并生成 fields 选中的部分，未选中的字段为空值，序列化时由调用方去掉；
+    /// 不需要 swagger 派生字段时不读取 swagger_content
+    pub async fn get_endpoint_detail_fields(
+        &self,
+        id: Uuid,
+        fields: &FieldSelection,
+    ) -> Result<EndpointDetailResponse> {
+        let columns = fields.columns(
+            &[ENDPOINT_COLUMNS, ENDPOINT_DERIVED_COLUMNS].concat(),
+            &["id"],
+        );
+        let endpoint = sqlx::query_as::<_, Endpoint>(&format!(
+            "SELECT {} FROM endpoints WHERE id = ?",
+            columns.join(", ")
+        ))
+        .bind(id.to_string())
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| anyhow!("Endpoint not found"))?;
+
+        let warmup = if fields.includes("warmup") {
+            match latest_warmup(&self.pool, id).await {
+                Ok(warmup) => warmup,
+                Err(e) => {
+                    tracing::warn!("Failed to load warmup for endpoint {}: {}", id, e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        // Generate MCP config
+        let mcp_config = McpConfig {
+            server_name: format!("mcp-{}", endpoint.name),
+            command: vec!["mcp-gateway".to_string()],
+            args: vec!["--endpoint-id".to_string(), id.to_string()],
+        };
+
+        let mut detail = EndpointDetailResponse {
+            id: endpoint.id,
+            name: endpoint.name,
+            description: endpoint.description,
+            status: endpoint.status,
+            created_at: endpoint.created_at,
+            updated_at: endpoint.updated_at,
+            connection_count: endpoint.connection_count,
+            preferred_content_type: endpoint.preferred_content_type,
+            mock_mode: endpoint.mock_mode,
+            server_variables: endpoint.server_variables,
+            status_mapping: endpoint.status_mapping,
+            tool_stats: endpoint.tool_stats,
+            hide_deprecated: endpoint.hide_deprecated,
+            tags: endpoint.tags,
+            swagger_spec: Value::Null,
+            mcp_config,
+            api_details: Vec::new(),
+            webhooks: Vec::new(),
+            warnings: endpoint.tool_limit_warnings.clone().unwrap_or_default(),
+            skipped_operations: Vec::new(),
+            warmup,
+            base_url: None,
+        };
+        if !columns.contains(&"swagger_content") {
+            return Ok(detail);
+        }
 
         // Parse swagger content
-        tracing::debug!("Parsing swagger content for endpoint: {}", endpoint.name);
+        tracing::debug!("Parsing swagger content for endpoint: {}", detail.name);
         tracing::debug!("Swagger content length: {}", endpoint.swagger_content.len());
 
         let swagger_spec: crate::models::SwaggerSpec =
@@ -373,49 +788,54 @@ impl EndpointService {
             };
 
         // Generate API details
-        let api_details = generate_api_details(&swagger_spec)?;
+        if fields.includes("api_details") {
+            detail.api_details = generate_api_details(&swagger_spec)?;
+            // 附加每个操作的最新运维备注
+            let notes = latest_notes(&self.pool, id).await?;
+            for api_detail in &mut detail.api_details {
+                api_detail.notes = notes
+                    .get(&(api_detail.method.clone(), api_detail.path.clone()))
+                    .cloned();
+            }
+        }
+        if fields.includes("webhooks") {
+            detail.webhooks = generate_webhook_details(&swagger_spec)?;
+        }
+        // 迁移前写入、尚未检查过的端点按当前阈值现场计算
+        if fields.includes("warnings") && endpoint.tool_limit_warnings.is_none() {
+            let report = check_tool_limits(&generate_mcp_tools(&swagger_spec)?, &self.tool_limits);
+            detail.warnings = report.warnings;
+            detail.warnings.extend(report.violations);
+        }
 
         // Get base URL
-        let base_url = swagger_spec
+        detail.base_url = swagger_spec
             .servers
             .as_ref()
             .and_then(|servers| servers.first())
-            .map(|server| server.url.clone());
-
-        // Generate MCP config
-        let mcp_config = McpConfig {
-            server_name: format!("mcp-{}", endpoint.name),
-            command: vec!["mcp-gateway".to_string()],
-            args: vec!["--endpoint-id".to_string(), id.to_string()],
-        };
+            .map(|server| {
+                resolve_server_url(server, &detail.server_variables)
+                    .unwrap_or_else(|_| server.url.clone())
+            });
 
         // 尝试序列化swagger_spec，添加错误处理
-        let swagger_spec_value = match serde_json::to_value(&swagger_spec) {
-            Ok(value) => {
-                tracing::debug!("Successfully serialized swagger spec to JSON value");
-                value
-            }
-            Err(e) => {
-                tracing::error!("Failed to serialize swagger spec to JSON value: {}", e);
-                // 记录swagger_spec的详细信息以帮助调试
-                tracing::error!("Swagger spec debug: {:#?}", swagger_spec);
-                return Err(e.into());
-            }
-        };
+        if fields.includes("swagger_spec") {
+            detail.swagger_spec = match serde_json::to_value(&swagger_spec) {
+                Ok(value) => {
+                    tracing::debug!("Successfully serialized swagger spec to JSON value");
+                    value
+                }
+                Err(e) => {
+                    tracing::error!("Failed to serialize swagger spec to JSON value: {}", e);
+                    // 记录swagger_spec的详细信息以帮助调试
+                    tracing::error!("Swagger spec debug: {:#?}", swagger_spec);
+                    return Err(e.into());
+                }
+            };
+        }
+        detail.skipped_operations = swagger_spec.skipped_operations;
 
-        Ok(EndpointDetailResponse {
-            id: endpoint.id,
-            name: endpoint.name,
-            description: endpoint.description,
-            status: endpoint.status,
-            created_at: endpoint.created_at,
-            updated_at: endpoint.updated_at,
-            connection_count: endpoint.connection_count,
-            swagger_spec: swagger_spec_value,
-            mcp_config,
-            api_details,
-            base_url,
-        })
+        Ok(detail)
     }
 
     pub async fn update_endpoint(
@@ -425,10 +845,11 @@ impl EndpointService {
     ) -> Result<EndpointResponse> {
         let mut query = "UPDATE endpoints SET updated_at = ?".to_string();
         let mut params: Vec<String> = vec![get_china_time().to_rfc3339()];
+        let mut tool_limit_warnings = None;
 
         if let Some(name) = &request.name {
             query.push_str(", name = ?");
-            params.push(name.clone());
+            params.push(normalize_endpoint_name(name)?);
         }
 
         if let Some(description) = &request.description {
@@ -437,8 +858,12 @@ impl EndpointService {
         }
 
         if let Some(swagger_content) = &request.swagger_content {
-            query.push_str(", swagger_content = ?");
+            let swagger_spec: Value = serde_json::from_str(swagger_content)?;
+            let (_, warnings) = self.check_swagger_value(&swagger_spec)?;
+            query.push_str(", swagger_content = ?, tool_limit_warnings = ?");
             params.push(swagger_content.clone());
+            params.push(serde_json::to_string(&warnings)?);
+            tool_limit_warnings = Some(warnings);
         }
 
         if let Some(status) = &request.status {
@@ -450,6 +875,52 @@ impl EndpointService {
             });
         }
 
+        if let Some(content_type) = &request.preferred_content_type {
+            query.push_str(", preferred_content_type = NULLIF(?, '')");
+            params.push(content_type.trim().to_string());
+        }
+
+        if let Some(mock_mode) = request.mock_mode {
+            query.push_str(", mock_mode = ?");
+            params.push(if mock_mode { "1" } else { "0" }.to_string());
+        }
+
+        if let Some(server_variables) = &request.server_variables {
+            let swagger_content = match &request.swagger_content {
+                Some(swagger_content) => swagger_content.clone(),
+                None => self.get_endpoint_by_id(id).await?.swagger_content,
+            };
+            let swagger_spec: crate::models::SwaggerSpec = serde_json::from_str(&swagger_content)?;
+            validate_server_variables(&swagger_spec, server_variables)?;
+            query.push_str(", server_variables = NULLIF(?, '')");
+            params.push(if server_variables.is_empty() {
+                String::new()
+            } else {
+                serde_json::to_string(server_variables)?
+            });
+        }
+
+        if let Some(status_mapping) = &request.status_mapping {
+            status_mapping.validate()?;
+            query.push_str(", status_mapping = ?");
+            params.push(serde_json::to_string(status_mapping)?);
+        }
+
+        if let Some(tool_stats) = request.tool_stats {
+            query.push_str(", tool_stats = ?");
+            params.push(if tool_stats { "1" } else { "0" }.to_string());
+        }
+
+        if let Some(hide_deprecated) = request.hide_deprecated {
+            query.push_str(", hide_deprecated = ?");
+            params.push(if hide_deprecated { "1" } else { "0" }.to_string());
+        }
+
+        if let Some(tags) = &request.tags {
+            query.push_str(", tags = NULLIF(?, '')");
+            params.push(tags_column(&normalize_tags(tags)?)?.unwrap_or_default());
+        }
+
         query.push_str(" WHERE id = ?");
         params.push(id.to_string());
 
@@ -459,25 +930,28 @@ impl EndpointService {
         }
 
         query_builder.execute(&self.pool).await?;
+        self.spec_cache.invalidate(&id);
 
         let endpoint = self.get_endpoint_by_id(id).await?;
-        self.event_sender
-            .send(EndpointEvent::UPDATE(endpoint.name.clone()))
-            .await?;
+        if let Some(warnings) = &tool_limit_warnings {
+            alert_tool_limit_warnings(&endpoint.name, warnings);
+        }
+        self.publish_event(if request.force_embeddings {
+            EndpointEvent::Reembed(endpoint.name.clone())
+        } else {
+            EndpointEvent::UPDATE(endpoint.name.clone())
+        });
         Ok(endpoint.into())
     }
 
     pub async fn delete_endpoint(&self, id: Uuid) -> Result<()> {
         match self.get_endpoint_by_id(id).await {
             Ok(endpoint) => {
-                // 物理删除端点记录
-                sqlx::query("DELETE FROM endpoints WHERE id = ?")
-                    .bind(id.to_string())
-                    .execute(&self.pool)
-                    .await?;
-                self.event_sender
-                    .send(EndpointEvent::DELETE(endpoint.name))
-                    .await?;
+                // 事务内删除端点及关联数据，向量清理作为后台任务入队
+                delete_endpoint_cascade(&self.pool, &endpoint).await?;
+                self.spec_cache.invalidate(&id);
+                tool_stats().remove_endpoint(id);
+                mcp_method_counters().remove_endpoint(id);
                 Ok(())
             }
             Err(_) => Ok(()),
@@ -486,7 +960,7 @@ impl EndpointService {
 
     pub async fn get_endpoint_metrics(&self, id: Uuid) -> Result<EndpointMetrics> {
         let metrics = sqlx::query(
-            "SELECT endpoint_id, request_count, response_count, error_count, avg_response_time, current_connections, total_connection_time FROM endpoint_metrics WHERE endpoint_id = ?"
+            "SELECT endpoint_id, request_count, response_count, error_count, avg_response_time, current_connections, total_connection_time, mock_count FROM endpoint_metrics WHERE endpoint_id = ?"
         )
             .bind(id.to_string())
             .fetch_optional(&self.pool)
@@ -505,6 +979,7 @@ impl EndpointService {
                 avg_response_time: avg_response_time_f64,
                 current_connections: row.get::<i32, _>("current_connections"),
                 total_connection_time: row.get::<u64, _>("total_connection_time"),
+                mock_count: row.get::<u64, _>("mock_count"),
             })
         } else {
             // Create default metrics if not exists
@@ -525,6 +1000,7 @@ impl EndpointService {
                 avg_response_time: 0.0,
                 current_connections: 0,
                 total_connection_time: 0,
+                mock_count: 0,
             })
         }
     }
@@ -554,6 +1030,26 @@ impl EndpointService {
         Ok(all_metrics)
     }
 
+    /// 存有工具阈值告警的端点，不含已删除的端点
+    pub async fn tool_limit_alerts(&self) -> Result<Vec<ToolLimitAlert>> {
+        let rows = sqlx::query(
+            "SELECT id, name, tool_limit_warnings FROM endpoints WHERE status != 'deleted' AND tool_limit_warnings IS NOT NULL AND tool_limit_warnings != '[]' ORDER BY name",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        rows.iter()
+            .map(|row| {
+                let id: String = row.try_get("id")?;
+                let warnings: String = row.try_get("tool_limit_warnings")?;
+                Ok(ToolLimitAlert {
+                    endpoint_id: Uuid::parse_str(&id)?,
+                    endpoint_name: row.try_get("name")?,
+                    warnings: serde_json::from_str(&warnings)?,
+                })
+            })
+            .collect()
+    }
+
     /// Start an endpoint (set status to running)
     pub async fn start_endpoint(&self, id: Uuid) -> Result<()> {
         // Verify endpoint exists and is not deleted
@@ -567,8 +1063,8 @@ impl EndpointService {
             return Err(anyhow::anyhow!("Endpoint is already running"));
         }
 
-        // Validate swagger content before starting
-        let _: serde_json::Value = serde_json::from_str(&endpoint.swagger_content)
+        // 启动后无法列出或调用工具的端点直接拒绝
+        check_startable(&endpoint.swagger_content)
             .map_err(|e| anyhow::anyhow!("Invalid swagger content: {}", e))?;
 
         sqlx::query("UPDATE endpoints SET status = 'running', updated_at = ? WHERE id = ?")
@@ -576,11 +1072,43 @@ impl EndpointService {
             .bind(id.to_string())
             .execute(&self.pool)
             .await?;
+        self.spec_cache.invalidate(&id);
+        self.publish_event(EndpointEvent::StatusChanged(endpoint.name.clone()));
 
         tracing::info!("Started endpoint: {} ({})", endpoint.name, id);
+        if self.warmup.enabled {
+            let service = self.clone();
+            tokio::spawn(async move { service.warm_up(id).await });
+        }
         Ok(())
     }
 
+    /// 启动后的后台预热，结果写入 endpoint_warmups；启动时间已更新，需重新读取端点
+    async fn warm_up(&self, id: Uuid) {
+        let endpoint = match self.get_endpoint_by_id(id).await {
+            Ok(endpoint) => endpoint,
+            Err(e) => {
+                tracing::warn!("Failed to load endpoint {} for warmup: {}", id, e);
+                return;
+            }
+        };
+        let warmup = warm_endpoint(&self.spec_cache, &endpoint, &self.warmup).await;
+        if warmup.status != WarmupStatus::Failed {
+            // 只补齐文本有变化或缺失的接口向量
+            self.publish_event(EndpointEvent::Warmup(endpoint.name.clone()));
+        }
+        if let Err(e) = record_warmup(&self.pool, id, &warmup).await {
+            tracing::warn!("Failed to record warmup for endpoint {}: {}", id, e);
+        }
+        tracing::info!(
+            endpoint = %endpoint.name,
+            status = ?warmup.status,
+            duration_ms = warmup.duration_ms,
+            tools = warmup.tools,
+            "Endpoint warmed"
+        );
+    }
+
     /// Stop an endpoint (set status to stopped)
     pub async fn stop_endpoint(&self, id: Uuid) -> Result<()> {
         // Verify endpoint exists and is not deleted
@@ -599,13 +1127,212 @@ impl EndpointService {
             .bind(id.to_string())
             .execute(&self.pool)
             .await?;
+        self.spec_cache.invalidate(&id);
+        self.publish_event(EndpointEvent::StatusChanged(endpoint.name.clone()));
 
         tracing::info!("Stopped endpoint: {} ({})", endpoint.name, id);
         Ok(())
     }
 
+    /// 按筛选条件列出未删除的端点，按创建时间排序
+    async fn bulk_candidates(&self, filter: &EndpointBulkFilter) -> Result<Vec<(Uuid, String)>> {
+        let status = bulk_status_filter(filter)?;
+        let tag = filter
+            .tag
+            .as_deref()
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .map(str::to_lowercase);
+        let mut sql = "SELECT id, name FROM endpoints WHERE status != 'deleted'".to_string();
+        if status.is_some() {
+            sql.push_str(" AND status = ?");
+        }
+        if tag.is_some() {
+            sql.push_str(" AND JSON_CONTAINS(tags, JSON_QUOTE(?))");
+        }
+        sql.push_str(" ORDER BY created_at");
+
+        let mut query = sqlx::query(&sql);
+        if let Some(status) = &status {
+            query = query.bind(status);
+        }
+        if let Some(tag) = &tag {
+            query = query.bind(tag);
+        }
+        let rows = query.fetch_all(&self.pool).await?;
+        let pattern = filter
+            .name
+            .as_deref()
+            .map(str::trim)
+            .filter(|p| !p.is_empty());
+        let mut candidates = Vec::with_capacity(rows.len());
+        for row in rows {
+            let name: String = row.try_get("name")?;
+            if pattern.is_none_or(|pattern| wildcard_match(pattern, &name)) {
+                let id: String = row.try_get("id")?;
+                candidates.push((Uuid::parse_str(&id)?, name));
+            }
+        }
+        Ok(candidates)
+    }
+
+    /// 批量启动或停止筛选出的端点：逐个走单个启停的校验，有界并发，按筛选顺序返回结果；
+    /// 已处于目标状态的记为 skipped，每个端点的结果写入生命周期审计
+    pub async fn bulk_lifecycle(
+        &self,
+        action: LifecycleAction,
+        filter: &EndpointBulkFilter,
+    ) -> Result<Vec<BulkLifecycleResult>> {
+        let candidates = self.bulk_candidates(filter).await?;
+        let results: Vec<BulkLifecycleResult> = stream::iter(candidates)
+            .map(|(id, name)| async move {
+                let result = match action {
+                    LifecycleAction::Start => self.start_endpoint(id).await,
+                    _ => self.stop_endpoint(id).await,
+                };
+                let (outcome, reason) = match result {
+                    Ok(()) => (LifecycleOutcome::Ok, None),
+                    Err(e) => {
+                        let reason = e.to_string();
+                        if reason.contains("already running") || reason.contains("already stopped")
+                        {
+                            (LifecycleOutcome::Skipped, Some(reason))
+                        } else {
+                            (LifecycleOutcome::Failed, Some(reason))
+                        }
+                    }
+                };
+                if let Err(e) = record_lifecycle_event(
+                    &self.pool,
+                    id,
+                    &name,
+                    action,
+                    LIFECYCLE_SOURCE_BULK,
+                    outcome,
+                    reason.as_deref(),
+                )
+                .await
+                {
+                    tracing::warn!("Failed to record lifecycle event for {}: {}", id, e);
+                }
+                BulkLifecycleResult {
+                    id,
+                    name,
+                    outcome,
+                    reason,
+                }
+            })
+            .buffered(self.lifecycle.bulk_concurrency.max(1))
+            .collect()
+            .await;
+
+        tracing::info!(
+            action = action.as_str(),
+            matched = results.len(),
+            failed = results
+                .iter()
+                .filter(|r| r.outcome == LifecycleOutcome::Failed)
+                .count(),
+            "Bulk endpoint lifecycle finished"
+        );
+        Ok(results)
+    }
+
+    /// 启动时对账：重新校验标记为 running 的端点，swagger 不可用或（开启探测时）上游探测失败的
+    /// 降级为 stopped，记录原因并发送状态变更事件；返回被降级的端点
+    pub async fn reconcile_running_endpoints(&self) -> Result<Vec<BulkLifecycleResult>> {
+        let filter = EndpointBulkFilter {
+            status: Some("running".to_string()),
+            ..Default::default()
+        };
+        let candidates = self.bulk_candidates(&filter).await?;
+        let checked = candidates.len();
+        let demoted: Vec<BulkLifecycleResult> = stream::iter(candidates)
+            .map(|(id, name)| async move {
+                let reason = match self.get_endpoint_by_id(id).await {
+                    Ok(endpoint) => self.unavailable_reason(&endpoint).await?,
+                    Err(e) => {
+                        tracing::warn!("Failed to load endpoint {} for reconciliation: {}", id, e);
+                        return None;
+                    }
+                };
+                let outcome = match self.demote_endpoint(id, &name, &reason).await {
+                    Ok(()) => LifecycleOutcome::Ok,
+                    Err(e) => {
+                        tracing::warn!("Failed to demote endpoint {} ({}): {}", name, id, e);
+                        LifecycleOutcome::Failed
+                    }
+                };
+                if let Err(e) = record_lifecycle_event(
+                    &self.pool,
+                    id,
+                    &name,
+                    LifecycleAction::Demote,
+                    LIFECYCLE_SOURCE_RECONCILE,
+                    outcome,
+                    Some(&reason),
+                )
+                .await
+                {
+                    tracing::warn!("Failed to record lifecycle event for {}: {}", id, e);
+                }
+                Some(BulkLifecycleResult {
+                    id,
+                    name,
+                    outcome,
+                    reason: Some(reason),
+                })
+            })
+            .buffered(self.lifecycle.bulk_concurrency.max(1))
+            .filter_map(|result| async move { result })
+            .collect()
+            .await;
+
+        tracing::info!(
+            checked,
+            demoted = demoted.len(),
+            "Reconciled running endpoints"
+        );
+        Ok(demoted)
+    }
+
+    /// 运行中端点不可用的原因，可用时返回 None
+    async fn unavailable_reason(&self, endpoint: &Endpoint) -> Option<String> {
+        if let Err(e) = check_startable(&endpoint.swagger_content) {
+            return Some(format!("Invalid swagger content: {}", e));
+        }
+        let probe = self
+            .warmup
+            .probe_path
+            .as_deref()
+            .is_some_and(|p| !p.is_empty());
+        if self.lifecycle.reconcile_probe && probe {
+            let warmup = warm_endpoint(&self.spec_cache, endpoint, &self.warmup).await;
+            if !warmup.errors.is_empty() {
+                return Some(warmup.errors.join("; "));
+            }
+        }
+        None
+    }
+
+    /// 将运行中的端点降级为 stopped；期间已被停止或删除的端点不受影响
+    async fn demote_endpoint(&self, id: Uuid, name: &str, reason: &str) -> Result<()> {
+        sqlx::query(
+            "UPDATE endpoints SET status = 'stopped', updated_at = ? WHERE id = ? AND status = 'running'",
+        )
+        .bind(get_china_time())
+        .bind(id.to_string())
+        .execute(&self.pool)
+        .await?;
+        self.spec_cache.invalidate(&id);
+        self.publish_event(EndpointEvent::StatusChanged(name.to_string()));
+
+        tracing::warn!("Demoted endpoint {} ({}) to stopped: {}", name, id, reason);
+        Ok(())
+    }
+
     pub async fn sync_endpoint_vector(&self, name: String) -> Result<()> {
-        let r = self.event_sender.send(EndpointEvent::UPDATE(name)).await?;
+        let r = self.event_sender.send(EndpointEvent::Reembed(name)).await?;
         Ok(r)
     }
 
@@ -620,10 +1347,29 @@ impl EndpointService {
     }
 }
 
+/// 端点可启动的条件：swagger 能完整解析、通过校验且至少生成一个工具；
+/// 录制端点没有 paths，启动后暴露 http_request 工具
+fn check_startable(swagger_content: &str) -> Result<()> {
+    let spec: crate::models::SwaggerSpec = serde_json::from_str(swagger_content)?;
+    if is_recording_spec(&serde_json::from_str(swagger_content)?) {
+        return Ok(());
+    }
+    validate_swagger_spec(&spec)?;
+    if generate_mcp_tools(&spec)?.is_empty() {
+        return Err(anyhow!("No tools can be generated from the swagger"));
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::endpoint::{
+        ENDPOINT_DETAIL_FIELDS, ENDPOINT_LIST_FIELDS, MAX_ENDPOINT_NAME_CHARS,
+    };
     use crate::models::{CreateEndpointRequest, EndpointStatus};
+    use crate::services::{ENDPOINT_DEPENDENT_TABLES, JOB_ENDPOINT_VECTOR_CLEANUP};
+    use std::sync::{Arc, Mutex};
 
     async fn create_test_pool() -> DbPool {
         let database_url = std::env::var("TEST_DATABASE_URL").unwrap_or_else(|_| {
@@ -640,12 +1386,13 @@ mod tests {
     async fn test_create_endpoint() {
         let (tx, _rx) = mpsc::channel(100);
         let pool = create_test_pool().await;
-        let service = EndpointService::new(pool, tx);
+        let service = EndpointService::new(pool, tx, Default::default());
 
         let request = CreateEndpointRequest {
             name: "Test Endpoint".to_string(),
             description: Some("A test endpoint".to_string()),
             swagger_content: r#"{"openapi":"3.0.0"}"#.to_string(),
+            tags: Vec::new(),
         };
 
         let result = service.create_endpoint(request).await;
@@ -661,7 +1408,7 @@ mod tests {
     async fn test_create_endpoint_with_same_name_merges_data() {
         let (tx, _rx) = mpsc::channel(100);
         let pool = create_test_pool().await;
-        let service = EndpointService::new(pool, tx);
+        let service = EndpointService::new(pool, tx, Default::default());
 
         // 创建第一个端点
         let request1 = CreateEndpointRequest {
@@ -670,6 +1417,7 @@ mod tests {
             swagger_content:
                 r#"{"openapi":"3.0.0", "paths": {"/test1": {"get": {"summary": "Test 1"}}}}"#
                     .to_string(),
+            tags: Vec::new(),
         };
 
         let result1 = service.create_endpoint(request1).await;
@@ -683,6 +1431,7 @@ mod tests {
             swagger_content:
                 r#"{"openapi":"3.0.0", "paths": {"/test2": {"post": {"summary": "Test 2"}}}}"#
                     .to_string(),
+            tags: Vec::new(),
         };
 
         let result2 = service.create_endpoint(request2).await;
@@ -714,7 +1463,7 @@ mod tests {
     async fn test_merge_swagger_specs_no_duplicates() {
         let (tx, _rx) = mpsc::channel(100);
         let pool = create_test_pool().await;
-        let service = EndpointService::new(pool, tx);
+        let service = EndpointService::new(pool, tx, Default::default());
 
         let existing =
             serde_json::from_str(r#"{"paths": {"/test": {"get": {"summary": "Existing"}}}}"#)
@@ -736,7 +1485,7 @@ mod tests {
     async fn test_merge_swagger_specs_with_duplicates() {
         let (tx, _rx) = mpsc::channel(100);
         let pool = create_test_pool().await;
-        let service = EndpointService::new(pool, tx);
+        let service = EndpointService::new(pool, tx, Default::default());
 
         let existing =
             serde_json::from_str(r#"{"paths": {"/test": {"get": {"summary": "Existing"}}}}"#)
@@ -753,4 +1502,1099 @@ mod tests {
         assert!(test_path.contains_key("get"));
         assert!(test_path.contains_key("post"));
     }
+
+    #[tokio::test]
+    async fn test_publish_event_does_not_block_on_full_channel() {
+        let (tx, mut rx) = mpsc::channel(1);
+        let pool = sqlx::MySqlPool::connect_lazy("mysql://test").unwrap();
+        let service = EndpointService::new(pool, tx, Default::default())
+            .with_event_retry(5, Duration::from_millis(50));
+
+        // 填满通道
+        service.publish_event(EndpointEvent::Created("a".to_string()));
+        // 通道已满，不应阻塞或报错
+        tokio::time::timeout(Duration::from_millis(20), async {
+            service.publish_event(EndpointEvent::UPDATE("b".to_string()));
+        })
+        .await
+        .expect("publish_event should not block");
+
+        // 消费后，后台重试将事件补发
+        assert!(matches!(rx.recv().await, Some(EndpointEvent::Created(name)) if name == "a"));
+        let retried = tokio::time::timeout(Duration::from_secs(1), rx.recv())
+            .await
+            .unwrap();
+        assert!(matches!(retried, Some(EndpointEvent::UPDATE(name)) if name == "b"));
+    }
+
+    #[tokio::test]
+    #[ignore] // 需要测试数据库
+    async fn test_create_endpoint_with_full_channel() {
+        let (tx, _rx) = mpsc::channel(1);
+        let pool = create_test_pool().await;
+        let service = EndpointService::new(pool, tx, Default::default());
+
+        // 监听器停滞：先占满通道
+        service.publish_event(EndpointEvent::UPDATE("stalled".to_string()));
+
+        let request = CreateEndpointRequest {
+            name: "Full Channel Endpoint".to_string(),
+            description: None,
+            swagger_content: r#"{"openapi":"3.0.0", "paths": {"/a": {"get": {"summary": "A"}}}}"#
+                .to_string(),
+            tags: Vec::new(),
+        };
+        let result = service.create_endpoint(request).await;
+        assert!(result.is_ok());
+
+        service.delete_endpoint(result.unwrap().id).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_merge_swagger_specs_preserves_webhooks_and_callbacks() {
+        let (tx, _rx) = mpsc::channel(100);
+        let pool = sqlx::MySqlPool::connect_lazy("mysql://test").unwrap();
+        let service = EndpointService::new(pool, tx, Default::default());
+
+        let existing = serde_json::from_str(
+            r#"{"paths": {"/orders": {"post": {"summary": "Create", "callbacks": {"onPaid": {"{$request.body#/url}": {"post": {"summary": "Paid"}}}}}}}, "webhooks": {"orderShipped": {"post": {"summary": "Shipped"}}}}"#,
+        )
+        .unwrap();
+        let new = serde_json::from_str(
+            r#"{"paths": {"/users": {"get": {"summary": "Users"}}}, "webhooks": {"userCreated": {"post": {"summary": "Created"}}}}"#,
+        )
+        .unwrap();
+
+        let merged = service.merge_swagger_specs(existing, new).unwrap();
+        let webhooks = merged.get("webhooks").unwrap().as_object().unwrap();
+        assert!(webhooks.contains_key("orderShipped"));
+        assert!(webhooks.contains_key("userCreated"));
+        assert!(merged["paths"]["/orders"]["post"]["callbacks"]
+            .get("onPaid")
+            .is_some());
+    }
+
+    #[tokio::test]
+    async fn test_merged_spec_keeps_path_declaration_order() {
+        let (tx, _rx) = mpsc::channel(100);
+        let pool = sqlx::MySqlPool::connect_lazy("mysql://test").unwrap();
+        let service = EndpointService::new(pool, tx, Default::default());
+
+        let existing = serde_json::from_str(
+            r#"{"openapi": "3.0.0", "info": {"title": "t", "version": "1"}, "paths": {"/zebras": {"get": {"operationId": "listZebras"}}, "/apples": {"get": {"operationId": "listApples"}}}}"#,
+        )
+        .unwrap();
+        let new = serde_json::from_str(
+            r#"{"paths": {"/mangos": {"post": {"operationId": "createMango"}, "get": {"operationId": "listMangos"}}}}"#,
+        )
+        .unwrap();
+
+        // 与落库后 spec 缓存的解析路径一致：序列化为文本再解析
+        let merged = service.merge_swagger_specs(existing, new).unwrap();
+        let spec: crate::models::SwaggerSpec =
+            serde_json::from_str(&serde_json::to_string(&merged).unwrap()).unwrap();
+        let names: Vec<String> = generate_mcp_tools(&spec)
+            .unwrap()
+            .into_iter()
+            .map(|tool| tool.name)
+            .collect();
+        assert_eq!(
+            names,
+            vec!["listZebras", "listApples", "listMangos", "createMango"]
+        );
+    }
+
+    #[tokio::test]
+    #[ignore] // 需要测试数据库
+    async fn test_rebuild_api_paths_restores_rows() {
+        let (tx, _rx) = mpsc::channel(100);
+        let pool = create_test_pool().await;
+        let service = EndpointService::new(pool.clone(), tx, Default::default());
+
+        let request = CreateEndpointRequest {
+            name: "Rebuild Paths Endpoint".to_string(),
+            description: None,
+            swagger_content: r#"{"openapi":"3.0.0", "paths": {"/a": {"get": {"summary": "A"}, "post": {"summary": "A2"}}, "/b": {"delete": {"summary": "B"}}}}"#
+                .to_string(),
+            tags: Vec::new(),
+        };
+        let endpoint = service.create_endpoint(request).await.unwrap();
+
+        // 模拟漂移：直接删除 api_paths 行
+        sqlx::query("DELETE FROM api_paths WHERE endpoint_id = ?")
+            .bind(endpoint.id.to_string())
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let written = service.rebuild_api_paths(endpoint.id).await.unwrap();
+        assert_eq!(written, 3);
+
+        let row = sqlx::query("SELECT COUNT(*) as total FROM api_paths WHERE endpoint_id = ?")
+            .bind(endpoint.id.to_string())
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        let total: i64 = row.get("total");
+        assert_eq!(total, 3);
+
+        service.delete_endpoint(endpoint.id).await.unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore] // 需要测试数据库
+    async fn test_delete_endpoint_cascades_and_enqueues_cleanup() {
+        let (tx, _rx) = mpsc::channel(100);
+        let pool = create_test_pool().await;
+        let service = EndpointService::new(pool.clone(), tx, Default::default());
+
+        let request = CreateEndpointRequest {
+            name: format!("cascade-{}", Uuid::new_v4()),
+            description: None,
+            swagger_content:
+                r#"{"openapi":"3.0.0", "paths": {"/a": {"get": {"operationId": "getA"}}}}"#
+                    .to_string(),
+            tags: Vec::new(),
+        };
+        let endpoint = service.create_endpoint(request).await.unwrap();
+        let id = endpoint.id.to_string();
+        service.get_endpoint_metrics(endpoint.id).await.unwrap();
+        sqlx::query(
+            "INSERT INTO contract_test_overrides (id, endpoint_id, tool_name, skipped) VALUES (?, ?, 'getA', TRUE)",
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(&id)
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO operation_notes (id, endpoint_id, method, path, note) VALUES (?, ?, 'GET', '/a', 'note')",
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(&id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        service.delete_endpoint(endpoint.id).await.unwrap();
+
+        for table in ENDPOINT_DEPENDENT_TABLES {
+            let row = sqlx::query(&format!(
+                "SELECT COUNT(*) as total FROM {} WHERE endpoint_id = ?",
+                table
+            ))
+            .bind(&id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+            let total: i64 = row.get("total");
+            assert_eq!(total, 0, "dangling rows in {}", table);
+        }
+
+        // 向量清理任务与删除在同一事务中入队
+        let row = sqlx::query(
+            "SELECT COUNT(*) as total FROM t_jobs WHERE job_type = ? AND status = 'pending' AND JSON_UNQUOTE(JSON_EXTRACT(payload, '$.endpoint_name')) = ?",
+        )
+        .bind(JOB_ENDPOINT_VECTOR_CLEANUP)
+        .bind(&endpoint.name)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        let total: i64 = row.get("total");
+        assert_eq!(total, 1);
+    }
+
+    #[tokio::test]
+    #[ignore] // 需要测试数据库
+    async fn test_get_endpoints_paginated_updated_after() {
+        let (tx, _rx) = mpsc::channel(100);
+        let pool = create_test_pool().await;
+        let service = EndpointService::new(pool, tx, Default::default());
+        let prefix = format!("sync-{}", Uuid::new_v4());
+        let swagger = r#"{"openapi":"3.0.0", "paths": {"/a": {"get": {"summary": "A"}}}}"#;
+
+        let older = service
+            .create_endpoint(CreateEndpointRequest {
+                name: format!("{}-older", prefix),
+                description: None,
+                swagger_content: swagger.to_string(),
+                tags: Vec::new(),
+            })
+            .await
+            .unwrap();
+        let cutoff = service
+            .get_endpoint_by_id(older.id)
+            .await
+            .unwrap()
+            .updated_at;
+
+        // TIMESTAMP 精度为秒
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+        let newer = service
+            .create_endpoint(CreateEndpointRequest {
+                name: format!("{}-newer", prefix),
+                description: None,
+                swagger_content: swagger.to_string(),
+                tags: Vec::new(),
+            })
+            .await
+            .unwrap();
+
+        let (endpoints, total) = service
+            .get_endpoints_paginated(
+                None,
+                None,
+                Some(prefix.clone()),
+                None,
+                Some(cutoff),
+                None,
+                &FieldSelection::all(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(total, 1);
+        assert_eq!(endpoints[0].id, newer.id);
+
+        let (_, total) = service
+            .get_endpoints_paginated(
+                None,
+                None,
+                Some(prefix),
+                None,
+                None,
+                None,
+                &FieldSelection::all(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(total, 2);
+
+        service.delete_endpoint(older.id).await.unwrap();
+        service.delete_endpoint(newer.id).await.unwrap();
+    }
+
+    #[test]
+    fn test_normalize_tags() {
+        let tags = ["Billing", " billing ", "team-a"].map(String::from);
+        assert_eq!(normalize_tags(&tags).unwrap(), vec!["billing", "team-a"]);
+        assert!(normalize_tags(&[" ".to_string()]).is_err());
+        assert!(normalize_tags(&["x".repeat(65)]).is_err());
+        let many: Vec<String> = (0..21).map(|i| format!("tag-{}", i)).collect();
+        assert!(normalize_tags(&many).is_err());
+    }
+
+    #[test]
+    fn test_normalize_endpoint_name() {
+        assert_eq!(
+            normalize_endpoint_name("  pet   store\t api ").unwrap(),
+            "pet store api"
+        );
+        assert_eq!(
+            normalize_endpoint_name("订单服务_v2.1").unwrap(),
+            "订单服务_v2.1"
+        );
+        for empty in ["", "   ", "\n\t"] {
+            assert!(normalize_endpoint_name(empty)
+                .unwrap_err()
+                .to_string()
+                .starts_with("Invalid endpoint name"));
+        }
+        assert!(normalize_endpoint_name(&"a".repeat(MAX_ENDPOINT_NAME_CHARS)).is_ok());
+        assert!(normalize_endpoint_name(&"a".repeat(MAX_ENDPOINT_NAME_CHARS + 1)).is_err());
+        assert!(normalize_endpoint_name("pets/v1").is_err());
+        assert!(normalize_endpoint_name("pets\u{0}").is_err());
+    }
+
+    #[tokio::test]
+    #[ignore] // 需要测试数据库
+    async fn test_create_endpoint_whitespace_variant_names_merge() {
+        let (tx, _rx) = mpsc::channel(100);
+        let pool = create_test_pool().await;
+        let service = EndpointService::new(pool, tx, Default::default());
+        let name = format!("names-{}", Uuid::new_v4());
+        let create = |name: String, path: &str| CreateEndpointRequest {
+            name,
+            description: None,
+            swagger_content: format!(
+                r#"{{"openapi":"3.0.0", "paths": {{"{}": {{"get": {{"summary": "A"}}}}}}}}"#,
+                path
+            ),
+            tags: Vec::new(),
+        };
+
+        let first = service
+            .create_endpoint(create(name.clone(), "/a"))
+            .await
+            .unwrap();
+        let second = service
+            .create_endpoint(create(format!("  {}  ", name), "/b"))
+            .await
+            .unwrap();
+        assert_eq!(first.id, second.id);
+        assert_eq!(second.name, name);
+
+        let renamed = service
+            .update_endpoint(
+                first.id,
+                UpdateEndpointRequest {
+                    name: Some(format!("{}   renamed ", name)),
+                    description: None,
+                    swagger_content: None,
+                    status: None,
+                    preferred_content_type: None,
+                    mock_mode: None,
+                    server_variables: None,
+                    status_mapping: None,
+                    tool_stats: None,
+                    hide_deprecated: None,
+                    tags: None,
+                    force_embeddings: false,
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(renamed.name, format!("{} renamed", name));
+        let invalid = service
+            .update_endpoint(
+                first.id,
+                UpdateEndpointRequest {
+                    name: Some(" \n ".to_string()),
+                    description: None,
+                    swagger_content: None,
+                    status: None,
+                    preferred_content_type: None,
+                    mock_mode: None,
+                    server_variables: None,
+                    status_mapping: None,
+                    tool_stats: None,
+                    hide_deprecated: None,
+                    tags: None,
+                    force_embeddings: false,
+                },
+            )
+            .await;
+        assert!(invalid.is_err());
+
+        service.delete_endpoint(first.id).await.unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore] // 需要测试数据库
+    async fn test_filter_endpoints_by_tag() {
+        let (tx, _rx) = mpsc::channel(100);
+        let pool = create_test_pool().await;
+        let service = EndpointService::new(pool, tx, Default::default());
+        let prefix = format!("tags-{}", Uuid::new_v4());
+        let swagger = r#"{"openapi":"3.0.0", "paths": {"/a": {"get": {"summary": "A"}}}}"#;
+        let create = |suffix: &str, tags: &[&str]| CreateEndpointRequest {
+            name: format!("{}-{}", prefix, suffix),
+            description: None,
+            swagger_content: swagger.to_string(),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+        };
+
+        let billing = service
+            .create_endpoint(create("billing", &["Billing", "team-a"]))
+            .await
+            .unwrap();
+        assert_eq!(billing.tags, vec!["billing", "team-a"]);
+        let search = service
+            .create_endpoint(create("search", &["team-a"]))
+            .await
+            .unwrap();
+
+        let (endpoints, total) = service
+            .get_endpoints_paginated(
+                None,
+                None,
+                Some(prefix.clone()),
+                None,
+                None,
+                Some("BILLING".to_string()),
+                &FieldSelection::all(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(total, 1);
+        assert_eq!(endpoints[0].id, billing.id);
+        let (_, total) = service
+            .get_endpoints_paginated(
+                None,
+                None,
+                Some(prefix.clone()),
+                None,
+                None,
+                Some("team-a".to_string()),
+                &FieldSelection::all(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(total, 2);
+
+        // 更新整体替换标签
+        let updated = service
+            .update_endpoint(
+                search.id,
+                UpdateEndpointRequest {
+                    name: None,
+                    description: None,
+                    swagger_content: None,
+                    status: None,
+                    preferred_content_type: None,
+                    mock_mode: None,
+                    server_variables: None,
+                    status_mapping: None,
+                    tool_stats: None,
+                    hide_deprecated: None,
+                    tags: Some(vec!["billing".to_string()]),
+                    force_embeddings: false,
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(updated.tags, vec!["billing"]);
+        let (_, total) = service
+            .get_endpoints_paginated(
+                None,
+                None,
+                Some(prefix),
+                None,
+                None,
+                Some("team-a".into()),
+                &FieldSelection::all(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(total, 1);
+
+        service.delete_endpoint(billing.id).await.unwrap();
+        service.delete_endpoint(search.id).await.unwrap();
+    }
+
+    /// 三个操作的 swagger，allowlist 非空时写入 x-mcp-tool-allowlist
+    fn tool_limits_swagger(allowlist: &[&str]) -> String {
+        let mut spec = serde_json::json!({
+            "openapi": "3.0.0",
+            "info": {"title": "Tool limits", "version": "1.0.0"},
+            "paths": {
+                "/pets": {"get": {"operationId": "listPets"}, "post": {"operationId": "createPet"}},
+                "/pets/{id}": {"delete": {"operationId": "deletePet"}}
+            }
+        });
+        if !allowlist.is_empty() {
+            spec["x-mcp-tool-allowlist"] = serde_json::json!(allowlist);
+        }
+        spec.to_string()
+    }
+
+    fn swagger_update(swagger_content: String) -> UpdateEndpointRequest {
+        UpdateEndpointRequest {
+            name: None,
+            description: None,
+            swagger_content: Some(swagger_content),
+            status: None,
+            preferred_content_type: None,
+            mock_mode: None,
+            server_variables: None,
+            status_mapping: None,
+            tool_stats: None,
+            hide_deprecated: None,
+            tags: None,
+            force_embeddings: false,
+        }
+    }
+
+    #[tokio::test]
+    #[ignore] // 需要测试数据库
+    async fn test_tool_limits_reject_create_and_update() {
+        let (tx, _rx) = mpsc::channel(100);
+        let service = EndpointService::new(create_test_pool().await, tx, Default::default())
+            .with_tool_limits(ToolLimitsConfig {
+                soft_max_tools: 1,
+                hard_max_tools: 2,
+                ..ToolLimitsConfig::default()
+            });
+        let name = format!("tool-limits-{}", Uuid::new_v4().simple());
+        let create = |swagger_content: String| CreateEndpointRequest {
+            name: name.clone(),
+            description: None,
+            swagger_content,
+            tags: Vec::new(),
+        };
+
+        // 三个工具超过硬阈值：拒绝创建，错误中提示 allowlist
+        let error = service
+            .create_endpoint(create(tool_limits_swagger(&[])))
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(
+            error.contains("tool count 3 exceeds hard limit 2"),
+            "{}",
+            error
+        );
+        assert!(error.contains("x-mcp-tool-allowlist"), "{}", error);
+        assert!(service.get_endpoint_by_name(name.clone()).await.is_err());
+
+        // allowlist 缩减到两个工具：创建成功，软阈值告警随响应返回并存储
+        let created = service
+            .create_endpoint(create(tool_limits_swagger(&["listPets", "deletePet"])))
+            .await
+            .unwrap();
+        let warning = "tool count 2 exceeds soft limit 1".to_string();
+        assert_eq!(created.warnings, vec![warning.clone()]);
+        assert_eq!(
+            service
+                .get_endpoint_by_id(created.id)
+                .await
+                .unwrap()
+                .tool_limit_warnings,
+            Some(vec![warning.clone()])
+        );
+        let detail = service.get_endpoint_detail(created.id).await.unwrap();
+        assert_eq!(detail.warnings, vec![warning.clone()]);
+        let names: Vec<String> = detail
+            .api_details
+            .iter()
+            .filter_map(|d| d.operation_id.clone())
+            .collect();
+        assert_eq!(names.len(), 3);
+
+        // 更新为超过硬阈值的 swagger：拒绝，已存储的 swagger 与告警不变
+        let error = service
+            .update_endpoint(created.id, swagger_update(tool_limits_swagger(&[])))
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("x-mcp-tool-allowlist"), "{}", error);
+        let stored = service.get_endpoint_by_id(created.id).await.unwrap();
+        assert!(stored.swagger_content.contains("x-mcp-tool-allowlist"));
+        assert_eq!(stored.tool_limit_warnings, Some(vec![warning]));
+
+        // 更新为一个工具：告警清空
+        let updated = service
+            .update_endpoint(
+                created.id,
+                swagger_update(tool_limits_swagger(&["listPets"])),
+            )
+            .await
+            .unwrap();
+        assert!(updated.warnings.is_empty());
+        assert_eq!(
+            service
+                .get_endpoint_by_id(created.id)
+                .await
+                .unwrap()
+                .tool_limit_warnings,
+            Some(Vec::new())
+        );
+
+        service.delete_endpoint(created.id).await.unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore] // 需要测试数据库
+    async fn test_start_warms_endpoint_without_blocking_on_probe() {
+        let (tx, _rx) = mpsc::channel(100);
+        let service = EndpointService::new(create_test_pool().await, tx, Default::default())
+            .with_warmup(WarmupConfig {
+                enabled: true,
+                probe_path: Some("/health".to_string()),
+                probe_timeout_ms: 2000,
+            });
+        let endpoint = service
+            .create_endpoint(CreateEndpointRequest {
+                name: format!("warmup-{}", Uuid::new_v4()),
+                description: None,
+                swagger_content: serde_json::json!({
+                    "openapi": "3.0.0",
+                    "info": {"title": "Warmup", "version": "1.0.0"},
+                    // 10.255.255.1 不可达，探测会等待到超时
+                    "servers": [{"url": "http://10.255.255.1"}],
+                    "paths": {"/pets": {"get": {"operationId": "listPets"}}}
+                })
+                .to_string(),
+                tags: Vec::new(),
+            })
+            .await
+            .unwrap();
+
+        let started = std::time::Instant::now();
+        service.start_endpoint(endpoint.id).await.unwrap();
+        assert!(started.elapsed() < Duration::from_millis(1000));
+
+        let mut warmup = None;
+        for _ in 0..50 {
+            warmup = latest_warmup(&service.pool, endpoint.id).await.unwrap();
+            if warmup.is_some() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        let warmup = warmup.expect("warmup recorded");
+        assert_eq!(warmup.status, WarmupStatus::Degraded);
+        assert_eq!(warmup.tools, 1);
+
+        let detail = service.get_endpoint_detail(endpoint.id).await.unwrap();
+        assert_eq!(
+            detail.warmup.map(|w| w.status),
+            Some(WarmupStatus::Degraded)
+        );
+        // 预热后解析缓存已就绪
+        let current = service.get_endpoint_by_id(endpoint.id).await.unwrap();
+        let parses = service.spec_cache().stats().parses;
+        service.spec_cache().get_or_parse(&current).await.unwrap();
+        assert_eq!(service.spec_cache().stats().parses, parses);
+
+        service.delete_endpoint(endpoint.id).await.unwrap();
+    }
+
+    #[test]
+    fn test_check_startable() {
+        let spec = |paths: &str| {
+            format!(
+                r#"{{"openapi": "3.0.0", "info": {{"title": "t", "version": "1"}}, "paths": {}}}"#,
+                paths
+            )
+        };
+        assert!(
+            check_startable(&spec(r#"{"/pets": {"get": {"operationId": "listPets"}}}"#)).is_ok()
+        );
+
+        let error = check_startable(&spec("{}")).unwrap_err().to_string();
+        assert_eq!(error, "At least one path is required");
+        assert!(check_startable("{}").is_err());
+        assert!(check_startable("not json").is_err());
+
+        // 只有非 HTTP 方法键的路径生成不出工具
+        let error = check_startable(&spec(r#"{"/pets": {"summary": "pets"}}"#))
+            .unwrap_err()
+            .to_string();
+        assert_eq!(error, "No tools can be generated from the swagger");
+
+        // 录制端点的 paths 为空，仍可启动
+        let recording = crate::services::recording_spec("rec", "http://legacy.local").unwrap();
+        assert!(check_startable(&recording.to_string()).is_ok());
+    }
+
+    #[tokio::test]
+    #[ignore] // 需要测试数据库
+    async fn test_start_recording_endpoint() {
+        let (tx, _rx) = mpsc::channel(100);
+        let service = EndpointService::new(create_test_pool().await, tx, Default::default())
+            .with_warmup(WarmupConfig {
+                enabled: false,
+                ..Default::default()
+            });
+        let name = format!("recording-{}", Uuid::new_v4().simple());
+        let spec = crate::services::recording_spec(&name, "http://127.0.0.1:1").unwrap();
+        let endpoint = service
+            .create_endpoint(CreateEndpointRequest {
+                name,
+                description: None,
+                swagger_content: spec.to_string(),
+                tags: Vec::new(),
+            })
+            .await
+            .unwrap();
+        assert_eq!(endpoint.status, EndpointStatus::Stopped);
+
+        service.start_endpoint(endpoint.id).await.unwrap();
+        let started = service.get_endpoint_by_id(endpoint.id).await.unwrap();
+        assert_eq!(started.status, EndpointStatus::Running);
+        // 启动对账不会把运行中的录制端点降级
+        assert!(service.unavailable_reason(&started).await.is_none());
+
+        service.delete_endpoint(endpoint.id).await.unwrap();
+    }
+
+    async fn create_lifecycle_endpoint(service: &EndpointService, name: &str, tag: &str) -> Uuid {
+        service
+            .create_endpoint(CreateEndpointRequest {
+                name: name.to_string(),
+                description: None,
+                swagger_content: serde_json::json!({
+                    "openapi": "3.0.0",
+                    "info": {"title": "Lifecycle", "version": "1.0.0"},
+                    "servers": [{"url": "http://127.0.0.1:1"}],
+                    "paths": {"/pets": {"get": {"operationId": "listPets"}}}
+                })
+                .to_string(),
+                tags: vec![tag.to_string()],
+            })
+            .await
+            .unwrap()
+            .id
+    }
+
+    #[tokio::test]
+    #[ignore] // 需要测试数据库
+    async fn test_stop_all_with_filter() {
+        let (tx, _rx) = mpsc::channel(100);
+        let service = EndpointService::new(create_test_pool().await, tx, Default::default())
+            .with_warmup(WarmupConfig {
+                enabled: false,
+                ..Default::default()
+            });
+        let suffix = Uuid::new_v4().simple().to_string();
+        let tag = format!("bulk-{}", &suffix[..8]);
+        let name = |n: &str| format!("bulk-{}-{}", suffix, n);
+
+        // 五个端点：两个运行中、一个已停止、一个名称不匹配、一个已删除
+        let running_a = create_lifecycle_endpoint(&service, &name("a"), &tag).await;
+        let running_b = create_lifecycle_endpoint(&service, &name("b"), &tag).await;
+        let stopped = create_lifecycle_endpoint(&service, &name("c"), &tag).await;
+        let other = create_lifecycle_endpoint(&service, &format!("other-{}", suffix), &tag).await;
+        let deleted = create_lifecycle_endpoint(&service, &name("e"), &tag).await;
+        for id in [running_a, running_b, other] {
+            service.start_endpoint(id).await.unwrap();
+        }
+        sqlx::query("UPDATE endpoints SET status = 'deleted' WHERE id = ?")
+            .bind(deleted.to_string())
+            .execute(&service.pool)
+            .await
+            .unwrap();
+
+        let filter = EndpointBulkFilter {
+            tag: Some(tag.clone()),
+            name: Some(format!("bulk-{}-*", suffix)),
+            ..Default::default()
+        };
+        let mut results = service
+            .bulk_lifecycle(LifecycleAction::Stop, &filter)
+            .await
+            .unwrap();
+        results.sort_by(|a, b| a.name.cmp(&b.name));
+        let outcomes: Vec<(Uuid, LifecycleOutcome)> =
+            results.iter().map(|r| (r.id, r.outcome)).collect();
+        assert_eq!(
+            outcomes,
+            vec![
+                (running_a, LifecycleOutcome::Ok),
+                (running_b, LifecycleOutcome::Ok),
+                (stopped, LifecycleOutcome::Skipped),
+            ]
+        );
+        assert!(results[2]
+            .reason
+            .as_deref()
+            .unwrap()
+            .contains("already stopped"));
+
+        for (id, status) in [
+            (running_a, EndpointStatus::Stopped),
+            (running_b, EndpointStatus::Stopped),
+            (other, EndpointStatus::Running),
+        ] {
+            assert_eq!(service.get_endpoint_by_id(id).await.unwrap().status, status);
+        }
+        let events = crate::services::list_lifecycle_events(&service.pool, running_a, 10)
+            .await
+            .unwrap();
+        assert_eq!(events[0].action, LifecycleAction::Stop);
+        assert_eq!(events[0].source, LIFECYCLE_SOURCE_BULK);
+        assert_eq!(events[0].outcome, LifecycleOutcome::Ok);
+
+        // 状态筛选不合法时整体拒绝
+        let invalid = EndpointBulkFilter {
+            status: Some("paused".to_string()),
+            ..Default::default()
+        };
+        assert!(service
+            .bulk_lifecycle(LifecycleAction::Start, &invalid)
+            .await
+            .is_err());
+
+        for id in [running_a, running_b, stopped, other, deleted] {
+            service.delete_endpoint(id).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    #[ignore] // 需要测试数据库
+    async fn test_reconcile_demotes_corrupted_endpoint() {
+        let (tx, mut rx) = mpsc::channel(100);
+        let service = EndpointService::new(create_test_pool().await, tx, Default::default())
+            .with_warmup(WarmupConfig {
+                enabled: false,
+                ..Default::default()
+            });
+        let suffix = Uuid::new_v4().simple().to_string();
+        let tag = format!("reconcile-{}", &suffix[..8]);
+        let healthy =
+            create_lifecycle_endpoint(&service, &format!("reconcile-{}-ok", suffix), &tag).await;
+        let corrupted =
+            create_lifecycle_endpoint(&service, &format!("reconcile-{}-bad", suffix), &tag).await;
+        for id in [healthy, corrupted] {
+            service.start_endpoint(id).await.unwrap();
+        }
+        sqlx::query("UPDATE endpoints SET swagger_content = '{\"openapi\": ' WHERE id = ?")
+            .bind(corrupted.to_string())
+            .execute(&service.pool)
+            .await
+            .unwrap();
+        while rx.try_recv().is_ok() {}
+
+        let demoted = service.reconcile_running_endpoints().await.unwrap();
+        let demoted: Vec<&BulkLifecycleResult> = demoted
+            .iter()
+            .filter(|r| r.id == corrupted || r.id == healthy)
+            .collect();
+        assert_eq!(demoted.len(), 1, "{:?}", demoted);
+        assert_eq!(demoted[0].id, corrupted);
+        assert!(demoted[0]
+            .reason
+            .as_deref()
+            .unwrap()
+            .starts_with("Invalid swagger content"));
+
+        assert_eq!(
+            service.get_endpoint_by_id(corrupted).await.unwrap().status,
+            EndpointStatus::Stopped
+        );
+        assert_eq!(
+            service.get_endpoint_by_id(healthy).await.unwrap().status,
+            EndpointStatus::Running
+        );
+        let mut status_events = Vec::new();
+        while let Ok(event) = rx.try_recv() {
+            if let EndpointEvent::StatusChanged(name) = event {
+                status_events.push(name);
+            }
+        }
+        assert!(status_events.contains(&format!("reconcile-{}-bad", suffix)));
+        let events = crate::services::list_lifecycle_events(&service.pool, corrupted, 10)
+            .await
+            .unwrap();
+        assert_eq!(events[0].action, LifecycleAction::Demote);
+        assert_eq!(events[0].source, LIFECYCLE_SOURCE_RECONCILE);
+        assert!(events[0]
+            .reason
+            .as_deref()
+            .unwrap()
+            .contains("Invalid swagger content"));
+
+        for id in [healthy, corrupted] {
+            service.delete_endpoint(id).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    #[ignore] // 需要测试数据库
+    async fn test_merge_swagger_rejects_conflicts_atomically() {
+        let (tx, _rx) = mpsc::channel(100);
+        let service = EndpointService::new(create_test_pool().await, tx, Default::default());
+        let tag = format!("merge-{}", &Uuid::new_v4().simple().to_string()[..8]);
+        let id = create_lifecycle_endpoint(&service, &tag, &tag).await;
+        let api_paths = |id: Uuid| {
+            let pool = service.pool.clone();
+            async move {
+                sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM api_paths WHERE endpoint_id = ?")
+                    .bind(id.to_string())
+                    .fetch_one(&pool)
+                    .await
+                    .unwrap()
+            }
+        };
+
+        // 干净的合并：新路径和已有路径上的新方法
+        let clean = serde_json::json!({"paths": {
+            "/pets": {"post": {"operationId": "createPet"}},
+            "/orders": {"get": {"operationId": "listOrders"}}
+        }});
+        let preview = service
+            .merge_swagger(id, clean.clone(), true)
+            .await
+            .unwrap();
+        assert!(!preview.applied);
+        assert_eq!(preview.added_operations.len(), 2);
+        assert_eq!(api_paths(id).await, 1);
+
+        let merged = service.merge_swagger(id, clean, false).await.unwrap();
+        assert!(merged.applied);
+        assert!(merged.swagger_spec["paths"]["/pets"]["get"].is_object());
+        assert!(merged.swagger_spec["paths"]["/pets"]["post"].is_object());
+        assert_eq!(api_paths(id).await, 3);
+
+        // 有冲突时列出全部冲突，不做任何修改
+        let conflicting = serde_json::json!({"paths": {
+            "/pets": {"GET": {"operationId": "listPets2"}, "put": {"operationId": "updatePets"}},
+            "/orders": {"get": {"operationId": "listOrders2"}}
+        }});
+        let before = service
+            .get_endpoint_by_id(id)
+            .await
+            .unwrap()
+            .swagger_content;
+        let error = service
+            .merge_swagger(id, conflicting, false)
+            .await
+            .unwrap_err();
+        let conflict = error.downcast_ref::<SwaggerMergeConflict>().unwrap();
+        assert_eq!(conflict.conflicts.len(), 2);
+        assert_eq!(
+            service
+                .get_endpoint_by_id(id)
+                .await
+                .unwrap()
+                .swagger_content,
+            before
+        );
+        assert_eq!(api_paths(id).await, 3);
+
+        service.delete_endpoint(id).await.unwrap();
+    }
+
+    #[test]
+    fn test_field_whitelists_match_responses() {
+        let endpoint: Endpoint = serde_json::from_value(serde_json::json!({
+            "id": Uuid::new_v4().to_string(),
+            "name": "petstore",
+            "description": null,
+            "swagger_content": "{}",
+            "status": "Running",
+            "created_at": "2026-01-01T00:00:00Z",
+            "updated_at": "2026-01-01T00:00:00Z",
+            "connection_count": 0
+        }))
+        .unwrap();
+        let response = serde_json::to_value(EndpointResponse::from(endpoint)).unwrap();
+        let mut keys: Vec<&str> = response
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(String::as_str)
+            .collect();
+        let mut list_fields = ENDPOINT_LIST_FIELDS.to_vec();
+        keys.sort();
+        list_fields.sort();
+        assert_eq!(keys, list_fields);
+
+        // 详情字段都能映射到列，warmup 单独查询
+        for field in ENDPOINT_DETAIL_FIELDS {
+            assert!(
+                *field == "warmup"
+                    || ENDPOINT_COLUMNS
+                        .iter()
+                        .chain(ENDPOINT_DERIVED_COLUMNS)
+                        .any(|(name, _)| name == field),
+                "{}",
+                field
+            );
+        }
+        // 列表默认不读取 swagger_content
+        assert!(!FieldSelection::all()
+            .columns(ENDPOINT_COLUMNS, &["id"])
+            .contains(&"swagger_content"));
+        // 完整查询包含 FromRow 需要的全部列
+        for column in [
+            "id",
+            "swagger_content",
+            "status_mapping",
+            "hide_deprecated",
+            "tags",
+        ] {
+            assert!(endpoint_select().contains(column), "{}", column);
+        }
+        assert!(endpoint_select().ends_with(" FROM endpoints"));
+    }
+
+    /// 记录 sqlx 执行的语句
+    fn capture_sql(buffer: Arc<Mutex<Vec<u8>>>) -> tracing::subscriber::DefaultGuard {
+        let subscriber = tracing_subscriber::fmt()
+            .with_env_filter(tracing_subscriber::EnvFilter::new("sqlx::query=debug"))
+            .with_ansi(false)
+            .with_writer(move || SqlLogWriter(buffer.clone()))
+            .finish();
+        tracing::subscriber::set_default(subscriber)
+    }
+
+    struct SqlLogWriter(Arc<Mutex<Vec<u8>>>);
+
+    impl std::io::Write for SqlLogWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    #[ignore] // 需要测试数据库
+    async fn test_field_selection_skips_large_swagger() {
+        let (tx, _rx) = mpsc::channel(100);
+        let service = EndpointService::new(create_test_pool().await, tx, Default::default());
+        let name = format!("fields-{}", &Uuid::new_v4().simple().to_string()[..8]);
+        let id = create_lifecycle_endpoint(&service, &name, "fields").await;
+        let large = serde_json::json!({
+            "openapi": "3.0.0",
+            "info": {"title": "Large", "version": "1.0.0", "description": "x".repeat(2 * 1024 * 1024)},
+            "servers": [{"url": "http://127.0.0.1:1"}],
+            "paths": {"/pets": {"get": {"operationId": "listPets"}}}
+        });
+        sqlx::query("UPDATE endpoints SET swagger_content = ? WHERE id = ?")
+            .bind(large.to_string())
+            .bind(id.to_string())
+            .execute(&service.pool)
+            .await
+            .unwrap();
+
+        let buffer = Arc::new(Mutex::new(Vec::new()));
+        let guard = capture_sql(buffer.clone());
+        let list_fields =
+            FieldSelection::parse(Some("id,name,status"), ENDPOINT_LIST_FIELDS).unwrap();
+        let (endpoints, _) = service
+            .get_endpoints_paginated(
+                None,
+                None,
+                Some(name.clone()),
+                None,
+                None,
+                None,
+                &list_fields,
+            )
+            .await
+            .unwrap();
+        let detail_fields =
+            FieldSelection::parse(Some("name,status,mcp_config"), ENDPOINT_DETAIL_FIELDS).unwrap();
+        let detail = service
+            .get_endpoint_detail_fields(id, &detail_fields)
+            .await
+            .unwrap();
+        drop(guard);
+
+        let list_body = serde_json::to_vec(&list_fields.apply_each(&endpoints).unwrap()).unwrap();
+        let detail_body = serde_json::to_vec(&detail_fields.apply(&detail).unwrap()).unwrap();
+        assert!(list_body.len() < 4096, "{}", list_body.len());
+        assert!(detail_body.len() < 4096, "{}", detail_body.len());
+        assert_eq!(
+            detail_fields.apply(&detail).unwrap(),
+            serde_json::json!({
+                "name": name,
+                "status": "Stopped",
+                "mcp_config": {
+                    "server_name": format!("mcp-{}", name),
+                    "command": ["mcp-gateway"],
+                    "args": ["--endpoint-id", id.to_string()]
+                }
+            })
+        );
+
+        let sql = String::from_utf8(buffer.lock().unwrap().clone()).unwrap();
+        // 确认捕获到了语句，格式化后的语句中关键字与表名可能分行
+        assert!(sql.contains("endpoints"), "{}", sql);
+        assert!(!sql.contains("swagger_content"), "{}", sql);
+
+        // 未指定 fields 时详情仍包含 swagger
+        let full = service.get_endpoint_detail(id).await.unwrap();
+        assert_eq!(
+            full.swagger_spec["paths"]["/pets"]["get"]["operationId"],
+            "listPets"
+        );
+
+        service.delete_endpoint(id).await.unwrap();
+    }
 }
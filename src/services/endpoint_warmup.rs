@@ -0,0 +1,218 @@
+use crate::config::WarmupConfig;
+use crate::models::{DbPool, Endpoint, EndpointWarmup, WarmupStatus};
+use crate::services::SpecCache;
+use crate::utils::{
+    build_base_url, classify_send_error, endpoint_http_client, get_china_time, http_client,
+    upstream_guard,
+};
+use anyhow::Result;
+use sqlx::Row;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// 预热端点：解析并缓存 swagger 与工具列表，初始化共享 HTTP 客户端，
+/// 配置了健康探测时请求一次以建立上游连接。探测失败只标记 Degraded，不影响端点可用；
+/// 解析、连接与超时失败分别体现在原因中，如 "health probe failed: Upstream dns failure for ..."
+pub async fn warm_endpoint(
+    cache: &SpecCache,
+    endpoint: &Endpoint,
+    config: &WarmupConfig,
+) -> EndpointWarmup {
+    let start = Instant::now();
+    let mut errors = Vec::new();
+
+    let (status, tools) = match cache.get_or_parse(endpoint).await {
+        Ok(cached) => {
+            let client = endpoint_http_client(endpoint.id).unwrap_or_else(|| http_client().clone());
+            if let Some(probe_path) = config.probe_path.as_deref().filter(|p| !p.is_empty()) {
+                let probe = async {
+                    let base_url = build_base_url(&cached.spec, &endpoint.server_variables)?;
+                    let url = format!(
+                        "{}/{}",
+                        base_url.trim_end_matches('/'),
+                        probe_path.trim_start_matches('/')
+                    );
+                    upstream_guard()
+                        .check_for_endpoint(&url, endpoint.id)
+                        .await?;
+                    let response = client
+                        .get(&url)
+                        .timeout(Duration::from_millis(config.probe_timeout_ms))
+                        .send()
+                        .await
+                        .map_err(classify_send_error)?;
+                    if !response.status().is_success() {
+                        anyhow::bail!("returned {}", response.status());
+                    }
+                    anyhow::Ok(())
+                };
+                if let Err(e) = probe.await {
+                    errors.push(format!("health probe failed: {}", e));
+                }
+            }
+            let status = if errors.is_empty() {
+                WarmupStatus::Ok
+            } else {
+                WarmupStatus::Degraded
+            };
+            (status, cached.tools.len())
+        }
+        Err(e) => {
+            errors.push(format!("failed to parse swagger: {}", e));
+            (WarmupStatus::Failed, 0)
+        }
+    };
+
+    EndpointWarmup {
+        status,
+        duration_ms: start.elapsed().as_millis() as u64,
+        tools,
+        errors,
+        warmed_at: get_china_time(),
+    }
+}
+
+pub async fn record_warmup(
+    pool: &DbPool,
+    endpoint_id: Uuid,
+    warmup: &EndpointWarmup,
+) -> Result<()> {
+    sqlx::query(
+        "REPLACE INTO endpoint_warmups (endpoint_id, status, duration_ms, tools, errors, warmed_at) VALUES (?, ?, ?, ?, ?, ?)",
+    )
+    .bind(endpoint_id.to_string())
+    .bind(warmup.status)
+    .bind(warmup.duration_ms)
+    .bind(warmup.tools as u32)
+    .bind(serde_json::to_string(&warmup.errors)?)
+    .bind(warmup.warmed_at)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn latest_warmup(pool: &DbPool, endpoint_id: Uuid) -> Result<Option<EndpointWarmup>> {
+    let row = sqlx::query(
+        "SELECT status, duration_ms, tools, errors, warmed_at FROM endpoint_warmups WHERE endpoint_id = ?",
+    )
+    .bind(endpoint_id.to_string())
+    .fetch_optional(pool)
+    .await?;
+    row.map(|row| {
+        let errors: Option<String> = row.try_get("errors")?;
+        let tools: u32 = row.try_get("tools")?;
+        Ok(EndpointWarmup {
+            status: row.try_get("status")?,
+            duration_ms: row.try_get("duration_ms")?,
+            tools: tools as usize,
+            errors: errors
+                .and_then(|e| serde_json::from_str(&e).ok())
+                .unwrap_or_default(),
+            warmed_at: row.try_get("warmed_at")?,
+        })
+    })
+    .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn endpoint(swagger: serde_json::Value) -> Endpoint {
+        Endpoint {
+            name: "warmup-test".to_string(),
+            swagger_content: swagger.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn petstore(server: &str) -> serde_json::Value {
+        serde_json::json!({
+            "openapi": "3.0.0",
+            "info": {"title": "Warmup", "version": "1.0.0"},
+            "servers": [{"url": server}],
+            "paths": {
+                "/pets": {"get": {"operationId": "listPets"}},
+                "/pets/{id}": {"get": {"operationId": "getPet"}}
+            }
+        })
+    }
+
+    #[tokio::test]
+    async fn test_warmup_populates_spec_cache() {
+        let cache = SpecCache::default();
+        let endpoint = endpoint(petstore("http://127.0.0.1:1"));
+
+        let warmup = warm_endpoint(&cache, &endpoint, &WarmupConfig::default()).await;
+        assert_eq!(warmup.status, WarmupStatus::Ok);
+        assert_eq!(warmup.tools, 2);
+        assert_eq!(cache.stats().parses, 1);
+
+        // 预热后的首次调用命中缓存，不再解析
+        cache.get_or_parse(&endpoint).await.unwrap();
+        let stats = cache.stats();
+        assert_eq!(stats.parses, 1);
+        assert_eq!(stats.hits, 1);
+    }
+
+    #[tokio::test]
+    async fn test_failing_probe_marks_degraded() {
+        let cache = SpecCache::default();
+        let endpoint = endpoint(petstore("http://127.0.0.1:1"));
+        let config = WarmupConfig {
+            probe_path: Some("/health".to_string()),
+            probe_timeout_ms: 500,
+            ..WarmupConfig::default()
+        };
+
+        let warmup = warm_endpoint(&cache, &endpoint, &config).await;
+        assert_eq!(warmup.status, WarmupStatus::Degraded);
+        assert!(warmup.errors[0].starts_with("health probe failed"));
+        // 连接被拒绝与 DNS 失败在原因中可区分
+        assert!(
+            warmup.errors[0].contains("Upstream connect failure for 127.0.0.1"),
+            "{}",
+            warmup.errors[0]
+        );
+        // 探测失败不影响缓存预热
+        assert_eq!(warmup.tools, 2);
+        assert_eq!(cache.stats().entries, 1);
+    }
+
+    #[tokio::test]
+    async fn test_probe_uses_static_dns_override() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let app = axum::Router::new().route("/health", axum::routing::get(|| async { "ok" }));
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let cache = SpecCache::default();
+        let endpoint = endpoint(petstore(&format!("http://api.warmup.invalid:{}", port)));
+        crate::utils::set_endpoint_dns_overrides(
+            endpoint.id,
+            [(
+                "api.warmup.invalid".to_string(),
+                "127.0.0.1".parse().unwrap(),
+            )]
+            .into(),
+        );
+        let config = WarmupConfig {
+            probe_path: Some("/health".to_string()),
+            ..WarmupConfig::default()
+        };
+
+        let warmup = warm_endpoint(&cache, &endpoint, &config).await;
+        assert_eq!(warmup.status, WarmupStatus::Ok, "{:?}", warmup.errors);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_swagger_marks_failed() {
+        let cache = SpecCache::default();
+        let mut endpoint = endpoint(petstore("http://127.0.0.1:1"));
+        endpoint.swagger_content = "not json".to_string();
+
+        let warmup = warm_endpoint(&cache, &endpoint, &WarmupConfig::default()).await;
+        assert_eq!(warmup.status, WarmupStatus::Failed);
+        assert_eq!(warmup.tools, 0);
+    }
+}
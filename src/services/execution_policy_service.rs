@@ -0,0 +1,354 @@
+use crate::config::ExecutionPolicyConfig;
+use crate::models::{DbPool, EndpointExecutionPolicy, PolicyLayer, PolicySource};
+use crate::services::SpecCache;
+use anyhow::{anyhow, Result};
+use dashmap::DashMap;
+use rmcp::model::Tool;
+use serde_json::{json, Value};
+use sqlx::Row;
+use std::collections::BTreeSet;
+use std::sync::{Arc, OnceLock};
+use uuid::Uuid;
+
+/// 调用被执行策略拦截时返回的 JSON-RPC 错误码
+pub const POLICY_VIOLATION_CODE: i32 = -32031;
+
+/// 会话在 initialize 时通过 experimental 能力声明策略使用的键
+pub const SESSION_POLICY_CAPABILITY: &str = "executionPolicy";
+
+/// 全局执行策略配置，启动时设置
+pub static EXECUTION_POLICY_CONFIG: OnceLock<ExecutionPolicyConfig> = OnceLock::new();
+
+pub fn execution_policy_config() -> &'static ExecutionPolicyConfig {
+    EXECUTION_POLICY_CONFIG.get_or_init(ExecutionPolicyConfig::default)
+}
+
+/// 会话 id 到会话层策略的映射，仅用于 effective-policy 查询
+static SESSION_POLICIES: OnceLock<DashMap<String, BTreeSet<String>>> = OnceLock::new();
+
+pub fn session_policies() -> &'static DashMap<String, BTreeSet<String>> {
+    SESSION_POLICIES.get_or_init(DashMap::new)
+}
+
+/// 调用方法不在生效策略中，policy 为首个拒绝该方法的策略层
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[error("Method {method} is blocked by the {policy} execution policy")]
+pub struct PolicyViolation {
+    pub method: String,
+    pub policy: PolicySource,
+}
+
+/// 规范化方法列表：去空白、转大写、去重
+pub fn parse_methods<I, S>(methods: I) -> BTreeSet<String>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    methods
+        .into_iter()
+        .map(|m| m.as_ref().trim().to_ascii_uppercase())
+        .filter(|m| !m.is_empty())
+        .collect()
+}
+
+/// 按解析顺序叠加的策略层，未配置的层不参与
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EffectivePolicy {
+    pub layers: Vec<PolicyLayer>,
+}
+
+impl EffectivePolicy {
+    pub fn resolve(
+        endpoint: Option<BTreeSet<String>>,
+        api_key: Option<BTreeSet<String>>,
+        session: Option<BTreeSet<String>>,
+    ) -> Self {
+        let layers = [
+            (PolicySource::Endpoint, endpoint),
+            (PolicySource::ApiKey, api_key),
+            (PolicySource::Session, session),
+        ]
+        .into_iter()
+        .filter_map(|(source, methods)| {
+            methods.map(|allowed_methods| PolicyLayer {
+                source,
+                allowed_methods,
+            })
+        })
+        .collect();
+        Self { layers }
+    }
+
+    /// 各层交集，None 表示不限制；会话层只能收窄，不能放宽上层策略
+    pub fn allowed_methods(&self) -> Option<BTreeSet<String>> {
+        self.layers.iter().fold(None, |acc, layer| match acc {
+            None => Some(layer.allowed_methods.clone()),
+            Some(acc) => Some(acc.intersection(&layer.allowed_methods).cloned().collect()),
+        })
+    }
+
+    pub fn check(&self, method: &str) -> std::result::Result<(), PolicyViolation> {
+        let method = method.to_ascii_uppercase();
+        match self
+            .layers
+            .iter()
+            .find(|layer| !layer.allowed_methods.contains(&method))
+        {
+            Some(layer) => Err(PolicyViolation {
+                method,
+                policy: layer.source,
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+/// 被策略拦截的工具仍出现在 tools/list 中，inputSchema._meta 标注拦截信息供客户端置灰
+pub fn annotate_blocked_tools<F>(tools: &mut [Tool], policy: &EffectivePolicy, method_of: F)
+where
+    F: Fn(&str) -> Option<String>,
+{
+    if policy.layers.is_empty() {
+        return;
+    }
+    for tool in tools.iter_mut() {
+        let Some(method) = method_of(tool.name.as_ref()) else {
+            continue;
+        };
+        if let Err(violation) = policy.check(&method) {
+            let schema = Arc::make_mut(&mut tool.input_schema);
+            let meta = schema
+                .entry("_meta")
+                .or_insert_with(|| Value::Object(Default::default()));
+            if let Value::Object(meta) = meta {
+                meta.insert(
+                    SESSION_POLICY_CAPABILITY.to_string(),
+                    json!({
+                        "blocked": true,
+                        "method": violation.method,
+                        "policy": violation.policy,
+                    }),
+                );
+            }
+        }
+    }
+}
+
+/// initialize 请求 experimental 能力中声明的允许方法，形如 {"executionPolicy": {"allowedMethods": ["GET"]}}
+pub fn session_methods_from_capability(
+    policy: &serde_json::Map<String, Value>,
+) -> Option<BTreeSet<String>> {
+    policy
+        .get("allowedMethods")
+        .and_then(Value::as_array)
+        .map(|methods| parse_methods(methods.iter().filter_map(Value::as_str)))
+}
+
+/// 会话层两个来源（能力声明与请求头）同时存在时取交集
+pub fn narrow(
+    current: Option<BTreeSet<String>>,
+    other: Option<BTreeSet<String>>,
+) -> Option<BTreeSet<String>> {
+    match (current, other) {
+        (Some(a), Some(b)) => Some(a.intersection(&b).cloned().collect()),
+        (a, b) => a.or(b),
+    }
+}
+
+pub struct ExecutionPolicyService {
+    pool: DbPool,
+    /// 修改后失效工具列表版本
+    spec_cache: Arc<SpecCache>,
+}
+
+impl ExecutionPolicyService {
+    pub fn new(pool: DbPool, spec_cache: Arc<SpecCache>) -> Self {
+        Self { pool, spec_cache }
+    }
+
+    pub async fn get_endpoint_policy(&self, endpoint_id: Uuid) -> Result<EndpointExecutionPolicy> {
+        let row = sqlx::query(
+            "SELECT allowed_methods FROM endpoint_execution_policies WHERE endpoint_id = ?",
+        )
+        .bind(endpoint_id.to_string())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(match row {
+            Some(row) => {
+                let methods: String = row.try_get("allowed_methods")?;
+                EndpointExecutionPolicy {
+                    allowed_methods: Some(parse_methods(methods.split(',')).into_iter().collect()),
+                }
+            }
+            None => EndpointExecutionPolicy::default(),
+        })
+    }
+
+    /// allowed_methods 为 None 时删除端点策略
+    pub async fn update_endpoint_policy(
+        &self,
+        endpoint_id: Uuid,
+        policy: EndpointExecutionPolicy,
+    ) -> Result<EndpointExecutionPolicy> {
+        match &policy.allowed_methods {
+            Some(methods) => {
+                let methods = parse_methods(methods);
+                if let Some(invalid) = methods.iter().find(|m| !is_http_method(m)) {
+                    return Err(anyhow!(
+                        "Invalid execution policy: unknown method {}",
+                        invalid
+                    ));
+                }
+                sqlx::query(
+                    "INSERT INTO endpoint_execution_policies (endpoint_id, allowed_methods) VALUES (?, ?) ON DUPLICATE KEY UPDATE allowed_methods = VALUES(allowed_methods)",
+                )
+                .bind(endpoint_id.to_string())
+                .bind(methods.iter().cloned().collect::<Vec<_>>().join(","))
+                .execute(&self.pool)
+                .await?;
+            }
+            None => {
+                sqlx::query("DELETE FROM endpoint_execution_policies WHERE endpoint_id = ?")
+                    .bind(endpoint_id.to_string())
+                    .execute(&self.pool)
+                    .await?;
+            }
+        }
+        // 被拦截工具的标注随之变化，重算 tools/list 版本
+        self.spec_cache.invalidate_tools_version(&endpoint_id);
+        self.get_endpoint_policy(endpoint_id).await
+    }
+
+    /// 解析端点、API key、会话三层策略
+    pub async fn effective_policy(
+        &self,
+        endpoint_id: Uuid,
+        api_key: Option<&str>,
+        session: Option<BTreeSet<String>>,
+    ) -> Result<EffectivePolicy> {
+        let endpoint = self
+            .get_endpoint_policy(endpoint_id)
+            .await?
+            .allowed_methods
+            .map(parse_methods);
+        Ok(EffectivePolicy::resolve(
+            endpoint,
+            api_key_methods(api_key),
+            session,
+        ))
+    }
+}
+
+/// API key 层允许的方法，未配置的 key 不参与
+pub fn api_key_methods(api_key: Option<&str>) -> Option<BTreeSet<String>> {
+    api_key
+        .and_then(|key| execution_policy_config().api_keys.get(key))
+        .map(parse_methods)
+}
+
+fn is_http_method(method: &str) -> bool {
+    matches!(
+        method,
+        "GET" | "HEAD" | "POST" | "PUT" | "PATCH" | "DELETE" | "OPTIONS" | "TRACE"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::SwaggerSpec;
+    use crate::utils::generate_mcp_tools;
+
+    fn methods(list: &[&str]) -> Option<BTreeSet<String>> {
+        Some(parse_methods(list))
+    }
+
+    #[test]
+    fn test_api_key_policy_blocks_mutating_methods() {
+        let policy = EffectivePolicy::resolve(None, methods(&["get", "head"]), None);
+        assert_eq!(policy.check("GET"), Ok(()));
+        assert_eq!(
+            policy.check("post"),
+            Err(PolicyViolation {
+                method: "POST".to_string(),
+                policy: PolicySource::ApiKey,
+            })
+        );
+        let error = policy.check("DELETE").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Method DELETE is blocked by the api_key execution policy"
+        );
+    }
+
+    #[test]
+    fn test_session_cannot_widen_key_policy() {
+        let policy = EffectivePolicy::resolve(
+            methods(&["GET", "POST", "DELETE"]),
+            methods(&["GET"]),
+            methods(&["GET", "POST"]),
+        );
+        assert_eq!(policy.allowed_methods(), methods(&["GET"]));
+        assert_eq!(
+            policy.check("POST").unwrap_err().policy,
+            PolicySource::ApiKey
+        );
+
+        // 会话可以进一步收窄
+        let policy = EffectivePolicy::resolve(None, methods(&["GET", "POST"]), methods(&["GET"]));
+        assert_eq!(
+            policy.check("POST").unwrap_err().policy,
+            PolicySource::Session
+        );
+
+        // 无任何策略时不限制
+        let policy = EffectivePolicy::resolve(None, None, None);
+        assert_eq!(policy.allowed_methods(), None);
+        assert_eq!(policy.check("DELETE"), Ok(()));
+    }
+
+    #[test]
+    fn test_blocked_tools_are_annotated() -> anyhow::Result<()> {
+        let spec: SwaggerSpec = serde_json::from_value(json!({
+            "openapi": "3.0.0",
+            "info": {"title": "Pets", "version": "1.0.0"},
+            "paths": {
+                "/pets": {
+                    "get": {"operationId": "listPets"},
+                    "post": {"operationId": "createPet"}
+                }
+            }
+        }))?;
+        let mut tools: Vec<Tool> = generate_mcp_tools(&spec)?.iter().map(Tool::from).collect();
+        let method_of = |name: &str| {
+            crate::utils::parse_tool_name(&spec, name)
+                .ok()
+                .map(|(method, _, _)| method.to_ascii_uppercase())
+        };
+        let policy = EffectivePolicy::resolve(None, methods(&["GET"]), None);
+        annotate_blocked_tools(&mut tools, &policy, method_of);
+
+        // 被拦截的工具仍在列表中
+        assert_eq!(tools.len(), 2);
+        for tool in &tools {
+            let meta = tool.input_schema.get("_meta");
+            match method_of(tool.name.as_ref()).as_deref() {
+                Some("POST") => assert_eq!(
+                    meta.unwrap()[SESSION_POLICY_CAPABILITY],
+                    json!({"blocked": true, "method": "POST", "policy": "api_key"})
+                ),
+                _ => assert!(meta.is_none(), "{} should not be blocked", tool.name),
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_session_methods_from_capability() {
+        let capability = json!({"allowedMethods": ["get", " head "]});
+        let parsed = session_methods_from_capability(capability.as_object().unwrap());
+        assert_eq!(parsed, methods(&["GET", "HEAD"]));
+        assert_eq!(narrow(parsed, methods(&["GET"])), methods(&["GET"]));
+    }
+}
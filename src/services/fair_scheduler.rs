@@ -0,0 +1,448 @@
+use crate::config::SchedulerConfig;
+use anyhow::{anyhow, Result};
+use dashmap::DashMap;
+use serde::Serialize;
+use std::cell::Cell;
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::oneshot;
+use uuid::Uuid;
+
+/// CallToolResult._meta 中的排队时间（毫秒）
+pub const QUEUE_WAIT_META_KEY: &str = "queue_wait_ms";
+
+tokio::task_local! {
+    /// 当前工具调用累计的排队时间，组合调用的各步骤依次累加
+    static CALL_QUEUE_WAIT: Cell<Duration>;
+}
+
+/// 在作用域内执行工具调用，返回结果与期间累计的排队时间
+pub async fn with_queue_wait<F: Future>(work: F) -> (F::Output, Duration) {
+    CALL_QUEUE_WAIT
+        .scope(Cell::new(Duration::ZERO), async move {
+            let output = work.await;
+            (output, CALL_QUEUE_WAIT.with(Cell::get))
+        })
+        .await
+}
+
+/// 将排队时间计入当前调用；不在 with_queue_wait 作用域内时忽略
+pub fn record_queue_wait(queue_wait: Duration) {
+    let _ = CALL_QUEUE_WAIT.try_with(|total| total.set(total.get() + queue_wait));
+}
+
+#[derive(Default)]
+struct SchedulerState {
+    /// 正在执行的调用数
+    running: usize,
+    /// 排队中的调用数
+    queued: usize,
+    /// 按端点划分的等待队列
+    queues: HashMap<Uuid, VecDeque<oneshot::Sender<()>>>,
+    /// 加权轮询顺序
+    order: VecDeque<Uuid>,
+    /// 队首端点本轮已分发次数
+    served_in_turn: u32,
+}
+
+/// 调度统计
+#[derive(Debug, Clone, Serialize)]
+pub struct SchedulerStats {
+    pub max_concurrency: usize,
+    pub bypass_threshold: usize,
+    pub running: usize,
+    pub queued: usize,
+    pub total_bypassed: u64,
+    pub total_queued: u64,
+    pub avg_queue_wait_ms: f64,
+}
+
+/// 端点间加权公平调度：
+/// 低负载时直接执行，高负载时按端点权重轮询分发执行槽位，
+/// 避免单个端点的突发流量占满所有槽位
+pub struct FairScheduler {
+    max_concurrency: usize,
+    bypass_threshold: usize,
+    default_weight: u32,
+    weights: DashMap<Uuid, u32>,
+    /// 上游限流暂停的端点及其恢复时间，暂停期内让出调度顺序
+    paused: DashMap<Uuid, Instant>,
+    state: Mutex<SchedulerState>,
+    total_bypassed: AtomicU64,
+    total_queued: AtomicU64,
+    total_wait_ms: AtomicU64,
+}
+
+/// 执行许可，drop 时释放槽位
+pub struct SchedulerPermit {
+    scheduler: Arc<FairScheduler>,
+    pub queue_wait: Duration,
+    pub bypassed: bool,
+}
+
+impl Drop for SchedulerPermit {
+    fn drop(&mut self) {
+        self.scheduler.release();
+    }
+}
+
+/// 等待中的许可；若 acquire 被取消而槽位已分配，则归还槽位
+struct PendingAcquire {
+    rx: Option<oneshot::Receiver<()>>,
+    scheduler: Arc<FairScheduler>,
+}
+
+impl Drop for PendingAcquire {
+    fn drop(&mut self) {
+        if let Some(mut rx) = self.rx.take() {
+            rx.close();
+            if rx.try_recv().is_ok() {
+                self.scheduler.release();
+            }
+        }
+    }
+}
+
+impl FairScheduler {
+    /// 配置不合法（没有执行槽位或直通阈值超过槽位数）时拒绝启动，而不是静默调整
+    pub fn new(config: &SchedulerConfig) -> Result<Self> {
+        if config.max_concurrency == 0 {
+            return Err(anyhow!("scheduler.max_concurrency must be greater than 0"));
+        }
+        if config.bypass_threshold > config.max_concurrency {
+            return Err(anyhow!(
+                "scheduler.bypass_threshold ({}) must not exceed scheduler.max_concurrency ({})",
+                config.bypass_threshold,
+                config.max_concurrency
+            ));
+        }
+        let weights = DashMap::new();
+        for (endpoint_id, weight) in &config.weights {
+            match Uuid::parse_str(endpoint_id) {
+                Ok(id) => {
+                    weights.insert(id, (*weight).max(1));
+                }
+                Err(e) => tracing::warn!("Invalid scheduler weight key {}: {}", endpoint_id, e),
+            }
+        }
+
+        Ok(Self {
+            max_concurrency: config.max_concurrency,
+            bypass_threshold: config.bypass_threshold,
+            default_weight: config.default_weight.max(1),
+            weights,
+            paused: DashMap::new(),
+            state: Mutex::new(SchedulerState::default()),
+            total_bypassed: AtomicU64::new(0),
+            total_queued: AtomicU64::new(0),
+            total_wait_ms: AtomicU64::new(0),
+        })
+    }
+
+    /// 设置端点权重
+    pub fn set_weight(&self, endpoint_id: Uuid, weight: u32) {
+        self.weights.insert(endpoint_id, weight.max(1));
+    }
+
+    /// 上游限流期间降低端点优先级，到期自动恢复
+    pub fn deprioritize(&self, endpoint_id: Uuid, until: Instant) {
+        self.paused.insert(endpoint_id, until);
+    }
+
+    fn is_paused(&self, endpoint_id: &Uuid) -> bool {
+        let now = Instant::now();
+        self.paused.remove_if(endpoint_id, |_, until| *until <= now);
+        self.paused.contains_key(endpoint_id)
+    }
+
+    fn weight(&self, endpoint_id: &Uuid) -> u32 {
+        self.weights
+            .get(endpoint_id)
+            .map(|w| *w)
+            .unwrap_or(self.default_weight)
+    }
+
+    /// 获取执行许可
+    pub async fn acquire(self: &Arc<Self>, endpoint_id: Uuid) -> SchedulerPermit {
+        let start = Instant::now();
+        let rx = {
+            let mut guard = self.state.lock().unwrap();
+            let state = &mut *guard;
+
+            // 低负载直接执行，不经过队列
+            if state.queued == 0 && state.running < self.bypass_threshold {
+                state.running += 1;
+                self.total_bypassed.fetch_add(1, Ordering::Relaxed);
+                return SchedulerPermit {
+                    scheduler: self.clone(),
+                    queue_wait: Duration::ZERO,
+                    bypassed: true,
+                };
+            }
+
+            let (tx, rx) = oneshot::channel();
+            let queue = state.queues.entry(endpoint_id).or_default();
+            if queue.is_empty() {
+                state.order.push_back(endpoint_id);
+            }
+            queue.push_back(tx);
+            state.queued += 1;
+            self.dispatch_locked(state);
+            rx
+        };
+
+        let mut pending = PendingAcquire {
+            rx: Some(rx),
+            scheduler: self.clone(),
+        };
+        if let Some(rx) = pending.rx.as_mut() {
+            if rx.await.is_err() {
+                tracing::warn!("Scheduler waiter dropped for endpoint {}", endpoint_id);
+            }
+        }
+        pending.rx = None;
+
+        let queue_wait = start.elapsed();
+        self.total_queued.fetch_add(1, Ordering::Relaxed);
+        self.total_wait_ms
+            .fetch_add(queue_wait.as_millis() as u64, Ordering::Relaxed);
+
+        SchedulerPermit {
+            scheduler: self.clone(),
+            queue_wait,
+            bypassed: false,
+        }
+    }
+
+    fn release(&self) {
+        let mut guard = self.state.lock().unwrap();
+        let state = &mut *guard;
+        state.running = state.running.saturating_sub(1);
+        self.dispatch_locked(state);
+    }
+
+    /// 按加权轮询分发空闲槽位
+    fn dispatch_locked(&self, state: &mut SchedulerState) {
+        while state.running < self.max_concurrency {
+            let Some(endpoint_id) = state.order.front().copied() else {
+                break;
+            };
+            // 暂停中的端点让位于其他端点；全部暂停时照常分发，调用会快速失败
+            if self.is_paused(&endpoint_id) {
+                if let Some(position) = state.order.iter().position(|id| !self.is_paused(id)) {
+                    state.order.rotate_left(position);
+                    state.served_in_turn = 0;
+                    continue;
+                }
+            }
+            let weight = self.weight(&endpoint_id);
+
+            let (waiter, queue_empty) = match state.queues.get_mut(&endpoint_id) {
+                Some(queue) => {
+                    let waiter = queue.pop_front();
+                    (waiter, queue.is_empty())
+                }
+                None => (None, true),
+            };
+
+            if let Some(tx) = waiter {
+                state.queued = state.queued.saturating_sub(1);
+                // 等待方已取消时跳过
+                if tx.send(()).is_ok() {
+                    state.running += 1;
+                    state.served_in_turn += 1;
+                }
+            }
+
+            if queue_empty {
+                state.queues.remove(&endpoint_id);
+                state.order.pop_front();
+                state.served_in_turn = 0;
+            } else if state.served_in_turn >= weight {
+                state.order.rotate_left(1);
+                state.served_in_turn = 0;
+            }
+        }
+    }
+
+    pub fn stats(&self) -> SchedulerStats {
+        let (running, queued) = {
+            let state = self.state.lock().unwrap();
+            (state.running, state.queued)
+        };
+        let total_queued = self.total_queued.load(Ordering::Relaxed);
+        let total_wait_ms = self.total_wait_ms.load(Ordering::Relaxed);
+
+        SchedulerStats {
+            max_concurrency: self.max_concurrency,
+            bypass_threshold: self.bypass_threshold,
+            running,
+            queued,
+            total_bypassed: self.total_bypassed.load(Ordering::Relaxed),
+            total_queued,
+            avg_queue_wait_ms: if total_queued == 0 {
+                0.0
+            } else {
+                total_wait_ms as f64 / total_queued as f64
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scheduler(max_concurrency: usize, bypass_threshold: usize) -> Arc<FairScheduler> {
+        Arc::new(
+            FairScheduler::new(&SchedulerConfig {
+                max_concurrency,
+                bypass_threshold,
+                default_weight: 1,
+                weights: HashMap::new(),
+            })
+            .unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_invalid_config_is_rejected() {
+        let config = |max_concurrency, bypass_threshold| SchedulerConfig {
+            max_concurrency,
+            bypass_threshold,
+            ..SchedulerConfig::default()
+        };
+        assert!(FairScheduler::new(&config(64, 16)).is_ok());
+        assert!(FairScheduler::new(&config(2, 2)).is_ok());
+        assert!(FairScheduler::new(&config(2, 16))
+            .unwrap_err()
+            .to_string()
+            .contains("bypass_threshold (16)"));
+        assert!(FairScheduler::new(&config(0, 0)).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_queue_wait_accumulates_within_scope() {
+        let ((), total) = with_queue_wait(async {
+            record_queue_wait(Duration::from_millis(3));
+            record_queue_wait(Duration::from_millis(4));
+        })
+        .await;
+        assert_eq!(total, Duration::from_millis(7));
+
+        // 作用域外记录不生效也不 panic
+        record_queue_wait(Duration::from_millis(5));
+    }
+
+    #[tokio::test]
+    async fn test_low_load_bypasses_queue() {
+        let scheduler = scheduler(4, 2);
+        let permit = scheduler.acquire(Uuid::new_v4()).await;
+        assert!(permit.bypassed);
+        assert_eq!(permit.queue_wait, Duration::ZERO);
+        assert_eq!(scheduler.stats().running, 1);
+
+        drop(permit);
+        assert_eq!(scheduler.stats().running, 0);
+        assert_eq!(scheduler.stats().total_bypassed, 1);
+    }
+
+    #[tokio::test]
+    async fn test_weighted_round_robin_between_endpoints() {
+        let scheduler = scheduler(1, 0);
+        let light = Uuid::new_v4();
+        let heavy = Uuid::new_v4();
+        scheduler.set_weight(light, 1);
+        scheduler.set_weight(heavy, 3);
+
+        // 占住唯一槽位，让后续请求全部排队
+        let holder = scheduler.acquire(Uuid::new_v4()).await;
+
+        let completed = Arc::new(Mutex::new(Vec::new()));
+        let mut handles = Vec::new();
+        for endpoint_id in std::iter::repeat(light)
+            .take(8)
+            .chain(std::iter::repeat(heavy).take(8))
+        {
+            let scheduler = scheduler.clone();
+            let completed = completed.clone();
+            handles.push(tokio::spawn(async move {
+                let permit = scheduler.acquire(endpoint_id).await;
+                assert!(!permit.bypassed);
+                completed.lock().unwrap().push(endpoint_id);
+            }));
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(scheduler.stats().queued, 16);
+
+        drop(holder);
+        for handle in handles {
+            handle.await.unwrap();
+        }
+
+        let completed = completed.lock().unwrap();
+        let first_window = &completed[..8];
+        let heavy_count = first_window.iter().filter(|id| **id == heavy).count();
+        // 权重 1:3，前 8 次中 heavy 约占 6 次
+        assert!(heavy_count >= 5, "heavy completed {} of 8", heavy_count);
+        assert_eq!(scheduler.stats().running, 0);
+        assert_eq!(scheduler.stats().queued, 0);
+    }
+
+    #[tokio::test]
+    async fn test_paused_endpoint_yields_to_others() {
+        let scheduler = scheduler(1, 0);
+        let paused = Uuid::new_v4();
+        let other = Uuid::new_v4();
+        let holder = scheduler.acquire(Uuid::new_v4()).await;
+
+        let completed = Arc::new(Mutex::new(Vec::new()));
+        let mut handles = Vec::new();
+        for endpoint_id in [paused, paused, other] {
+            let scheduler = scheduler.clone();
+            let completed = completed.clone();
+            handles.push(tokio::spawn(async move {
+                let _permit = scheduler.acquire(endpoint_id).await;
+                completed.lock().unwrap().push(endpoint_id);
+            }));
+            tokio::task::yield_now().await;
+        }
+        scheduler.deprioritize(paused, Instant::now() + Duration::from_secs(60));
+
+        drop(holder);
+        for handle in handles {
+            handle.await.unwrap();
+        }
+        // 暂停端点先排队，但在其他端点之后执行
+        assert_eq!(*completed.lock().unwrap(), vec![other, paused, paused]);
+
+        // 暂停到期后恢复正常调度
+        scheduler.deprioritize(paused, Instant::now());
+        assert!(!scheduler.is_paused(&paused));
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_waiter_does_not_leak_slot() {
+        let scheduler = scheduler(1, 0);
+        let holder = scheduler.acquire(Uuid::new_v4()).await;
+
+        let waiting = tokio::spawn({
+            let scheduler = scheduler.clone();
+            async move {
+                let _permit = scheduler.acquire(Uuid::new_v4()).await;
+            }
+        });
+        tokio::task::yield_now().await;
+        waiting.abort();
+        let _ = waiting.await;
+
+        drop(holder);
+        assert_eq!(scheduler.stats().running, 0);
+        let permit = scheduler.acquire(Uuid::new_v4()).await;
+        assert_eq!(scheduler.stats().running, 1);
+        drop(permit);
+    }
+}
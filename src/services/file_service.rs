@@ -109,11 +109,38 @@ impl FileService {
 
     /// Read file content by stored path value (compatible with local/OSS).
     pub async fn read_by_path(&self, path: &str) -> Result<Vec<u8>> {
-        // Stored path is like "{root}/{id}/{filename}". Convert to operator key.
-        let root = self.root.trim_end_matches('/');
-        let prefix = format!("{}/", root);
-        let key = path.strip_prefix(&prefix).unwrap_or(path);
-        let data = self.operator.read(key).await?;
+        let data = self.operator.read(self.key_of(path)).await?;
         Ok(data)
     }
+
+    /// 按存储路径打开文件读取流，用于大文件下载，不整体读入内存
+    pub async fn reader_by_path(&self, path: &str) -> Result<opendal::Reader> {
+        let reader = self.operator.reader(self.key_of(path)).await?;
+        Ok(reader)
+    }
+
+    /// 删除文件记录与存储的文件，记录不存在时忽略
+    pub async fn delete_file(&self, id: Uuid) -> Result<()> {
+        let path: Option<String> = sqlx::query_scalar(r#"SELECT path FROM t_file WHERE id = ?"#)
+            .bind(id.to_string())
+            .fetch_optional(&self.pool)
+            .await?;
+        let Some(path) = path else {
+            return Ok(());
+        };
+        self.operator.delete(self.key_of(&path)).await?;
+        sqlx::query(r#"DELETE FROM t_file WHERE id = ?"#)
+            .bind(id.to_string())
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Stored path is like "{root}/{id}/{filename}". Convert to operator key.
+    fn key_of<'a>(&self, path: &'a str) -> &'a str {
+        let root = self.root.trim_end_matches('/');
+        path.strip_prefix(root)
+            .and_then(|rest| rest.strip_prefix('/'))
+            .unwrap_or(path)
+    }
 }
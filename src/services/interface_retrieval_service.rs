@@ -1,12 +1,102 @@
 use crate::config::{EmbeddingConfig, VectorType};
 use crate::models::interface_retrieval::*;
-use crate::services::{Chunk, ElasticSearch, EmbeddingService, Meta, PgvectorRsSearch, Search};
-use anyhow::Result;
+use crate::models::{Endpoint, EndpointStatus, SwaggerSpec};
+use crate::services::{
+    endpoint_summary_text, interfaces_from_spec, merge_content, Chunk, ElasticSearch,
+    EmbeddingService, Meta, PgvectorRsSearch, Search, SearchResult, ENDPOINT_SUMMARY_OPERATIONS,
+};
+use anyhow::{Context, Result};
+use std::collections::HashMap;
 use std::sync::Arc;
 
+/// 增量同步时逐页读取已存储接口的页大小
+const SYNC_PAGE_SIZE: u32 = 100;
+/// 接口目录分页的默认与最大页大小
+const DEFAULT_LIST_LIMIT: u32 = 50;
+const MAX_LIST_LIMIT: u32 = 500;
+/// 端点级检索的默认与最大结果数
+const DEFAULT_ENDPOINT_RESULTS: u32 = 5;
+const MAX_ENDPOINT_RESULTS: u32 = 50;
+
+/// 检索后端中还没有任何接口时随空结果返回
+pub const EMPTY_INDEX_HINT: &str =
+    "No interfaces have been indexed yet. Register an endpoint with POST /api/swagger first.";
+
+/// 混合检索的向量权重不在 0.0 到 1.0 之间
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[error("Invalid vector_weight {0}: must be between 0.0 and 1.0")]
+pub struct InvalidVectorWeight(pub f32);
+
+pub fn check_vector_weight(weight: f32) -> Result<f32, InvalidVectorWeight> {
+    if (0.0..=1.0).contains(&weight) {
+        Ok(weight)
+    } else {
+        Err(InvalidVectorWeight(weight))
+    }
+}
+
+/// 项目接口同步结果
+#[derive(Debug, Default, PartialEq)]
+pub struct SyncStats {
+    pub reembedded: usize,
+    pub unchanged: usize,
+    pub removed: usize,
+}
+
+/// 增量同步计划
+#[derive(Debug, Default)]
+pub struct SyncPlan {
+    pub reembed: Vec<ApiInterface>,
+    pub remove: Vec<Meta>,
+    pub unchanged: usize,
+}
+
+/// 对比已存储文档与最新接口：向量化文本未变且构建器版本最新的保留原向量，
+/// 其余重新向量化，spec 中已不存在的删除
+pub fn plan_interface_sync(
+    project_id: &str,
+    existing: &[Chunk],
+    latest: Vec<ApiInterface>,
+) -> SyncPlan {
+    let mut stored: HashMap<(String, String), &Chunk> = existing
+        .iter()
+        .map(|chunk| {
+            let meta = chunk.get_meta();
+            ((meta.method.to_uppercase(), meta.path), chunk)
+        })
+        .collect();
+
+    let mut plan = SyncPlan::default();
+    for interface in latest {
+        let key = (interface.method.to_uppercase(), interface.path.clone());
+        match stored.remove(&key) {
+            Some(chunk)
+                if !chunk.get_meta().text_outdated() && chunk.text == merge_content(&interface) =>
+            {
+                plan.unchanged += 1
+            }
+            _ => plan.reembed.push(interface),
+        }
+    }
+    plan.remove = stored
+        .into_keys()
+        .map(|(method, path)| Meta {
+            project_id: project_id.to_string(),
+            path,
+            method,
+            text_version: None,
+        })
+        .collect();
+    plan
+}
+
 /// 接口关系服务 - 重新设计用于swagger解析和向量搜索
 pub struct InterfaceRetrievalService {
     search: Box<dyn Search>,
+    /// 请求未指定 similarity_threshold 时使用
+    default_similarity_threshold: f32,
+    /// 请求未指定 vector_weight 时使用，各检索后端共用
+    default_vector_weight: f32,
 }
 
 impl InterfaceRetrievalService {
@@ -23,8 +113,22 @@ impl InterfaceRetrievalService {
                 Box::new(PgvectorRsSearch::new(config, embedding_service.clone()).await?)
             }
         };
-        let service = Self { search };
-        Ok(service)
+        Self::from_search(search, config)
+    }
+
+    /// 使用已创建的检索后端
+    pub fn from_search(search: Box<dyn Search>, config: &EmbeddingConfig) -> Result<Self> {
+        Ok(Self {
+            search,
+            default_similarity_threshold: config.default_similarity_threshold,
+            default_vector_weight: check_vector_weight(config.default_vector_weight)
+                .context("embedding.default_vector_weight")?,
+        })
+    }
+
+    /// 后端尚未存储任何接口；计数失败时视为非空，保留原始结果或错误
+    async fn is_empty(&self) -> bool {
+        matches!(self.search.document_count().await, Ok(0))
     }
 
     /// 解析Swagger JSON并存储接口信息
@@ -32,14 +136,40 @@ impl InterfaceRetrievalService {
         self.search.parse_and_store_swagger(request).await
     }
 
-    /// 搜索接口 - 支持关键词和向量搜索
-    pub async fn search_interfaces(&self, request: InterfaceSearchRequest) -> Result<Vec<Chunk>> {
-        Ok(self.search.hybrid_search(request).await?)
+    /// 搜索接口 - 支持关键词和向量搜索；vector_weight 超出 0.0-1.0 时返回 InvalidVectorWeight；
+    /// 后端没有任何接口时返回带 hint 的空结果，不返回检索错误
+    pub async fn search_interfaces(
+        &self,
+        mut request: InterfaceSearchRequest,
+    ) -> Result<SearchResult> {
+        request
+            .similarity_threshold
+            .get_or_insert(self.default_similarity_threshold);
+        request.vector_weight = Some(match request.vector_weight {
+            Some(weight) => check_vector_weight(weight)?,
+            None => self.default_vector_weight,
+        });
+        match self.search.hybrid_search(request).await {
+            Ok(result) if !result.chunks.is_empty() => Ok(result),
+            _ if self.is_empty().await => Ok(SearchResult {
+                hint: Some(EMPTY_INDEX_HINT.to_string()),
+                ..SearchResult::default()
+            }),
+            result => result,
+        }
     }
 
-    /// 获取项目的所有接口
-    pub async fn get_project_interfaces(&self, project_id: &str) -> Result<Vec<ApiInterface>> {
-        let chunks = self.search.get_project_interfaces(project_id).await?;
+    /// 分页获取项目接口
+    pub async fn get_project_interfaces(
+        &self,
+        project_id: &str,
+        limit: u32,
+        offset: u32,
+    ) -> Result<Vec<ApiInterface>> {
+        let chunks = self
+            .search
+            .get_project_interfaces(project_id, limit, offset)
+            .await?;
 
         // 从chunks中提取ApiInterface
         let interfaces = chunks
@@ -50,17 +180,150 @@ impl InterfaceRetrievalService {
         Ok(interfaces)
     }
 
-    /// 删除项目数据
+    /// 分页浏览项目接口目录，多取一条判断是否还有下一页
+    pub async fn list_project_interfaces(
+        &self,
+        project_id: &str,
+        query: &InterfaceListQuery,
+    ) -> Result<InterfaceListResponse> {
+        let limit = query
+            .limit
+            .unwrap_or(DEFAULT_LIST_LIMIT)
+            .clamp(1, MAX_LIST_LIMIT);
+        let offset = query.offset.unwrap_or(0);
+        let mut interfaces = match self
+            .get_project_interfaces(project_id, limit + 1, offset)
+            .await
+        {
+            Ok(interfaces) => interfaces,
+            Err(_) if self.is_empty().await => Vec::new(),
+            Err(e) => return Err(e),
+        };
+        let has_more = interfaces.len() > limit as usize;
+        interfaces.truncate(limit as usize);
+        let hint = if interfaces.is_empty() && self.is_empty().await {
+            Some(EMPTY_INDEX_HINT.to_string())
+        } else {
+            None
+        };
+        Ok(InterfaceListResponse {
+            project_id: project_id.to_string(),
+            interfaces: interfaces.into_iter().map(InterfaceSummary::from).collect(),
+            limit,
+            offset,
+            has_more,
+            hint,
+        })
+    }
+
+    /// 删除项目数据，同时删除端点摘要
     pub async fn delete_project_data(&self, project_id: &str) -> Result<String> {
         let count = self.search.delete_project_data(project_id).await?;
+        self.search.delete_endpoint_summary(project_id).await?;
         Ok(count.to_string())
     }
 
+    /// 按端点当前的 spec、描述与状态重建摘要文档
+    pub async fn sync_endpoint_summary(&self, endpoint: &Endpoint) -> Result<()> {
+        let spec: SwaggerSpec = serde_json::from_str(&endpoint.swagger_content)?;
+        let status = match endpoint.status {
+            EndpointStatus::Running => "running",
+            EndpointStatus::Stopped => "stopped",
+            EndpointStatus::Deleted => "deleted",
+        };
+        self.search
+            .store_endpoint_summary(EndpointSummaryDoc {
+                endpoint_id: endpoint.id.to_string(),
+                slug: endpoint.name.clone(),
+                status: status.to_string(),
+                text: endpoint_summary_text(
+                    &spec,
+                    endpoint.description.as_deref(),
+                    ENDPOINT_SUMMARY_OPERATIONS,
+                ),
+            })
+            .await
+    }
+
+    /// 端点级检索：只查询端点摘要，用于先选出端点再检索接口
+    pub async fn search_endpoints(
+        &self,
+        query: &EndpointSearchQuery,
+    ) -> Result<Vec<EndpointMatch>> {
+        let max_results = query
+            .max_results
+            .unwrap_or(DEFAULT_ENDPOINT_RESULTS)
+            .clamp(1, MAX_ENDPOINT_RESULTS);
+        match self
+            .search
+            .search_endpoints(&query.query, max_results, query.only_running)
+            .await
+        {
+            Err(_) if self.is_empty().await => Ok(Vec::new()),
+            result => result,
+        }
+    }
+
+    /// 按最新 spec 同步项目接口；force 为 true 时删除项目数据并全部重新向量化
+    pub async fn sync_project(
+        &self,
+        project_id: &str,
+        swagger_json: serde_json::Value,
+        force: bool,
+    ) -> Result<SyncStats> {
+        if force {
+            let spec: SwaggerSpec = serde_json::from_value(swagger_json.clone())?;
+            let count = interfaces_from_spec(&spec)?.len();
+            self.search.delete_project_data(project_id).await?;
+            self.search
+                .parse_and_store_swagger(SwaggerParseRequest {
+                    project_id: project_id.to_string(),
+                    swagger_json,
+                    version: Some("1.0.0".to_string()),
+                    generate_embeddings: Some(true),
+                })
+                .await?;
+            return Ok(SyncStats {
+                reembedded: count,
+                ..SyncStats::default()
+            });
+        }
+
+        let spec: SwaggerSpec = serde_json::from_value(swagger_json)?;
+        let mut existing = Vec::new();
+        loop {
+            let page = self
+                .search
+                .get_project_interfaces(project_id, SYNC_PAGE_SIZE, existing.len() as u32)
+                .await?;
+            let done = (page.len() as u32) < SYNC_PAGE_SIZE;
+            existing.extend(page);
+            if done {
+                break;
+            }
+        }
+
+        let plan = plan_interface_sync(project_id, &existing, interfaces_from_spec(&spec)?);
+        let stats = SyncStats {
+            reembedded: plan.reembed.len(),
+            unchanged: plan.unchanged,
+            removed: plan.remove.len(),
+        };
+        for meta in plan.remove {
+            self.search.delete_by_meta(meta).await?;
+        }
+        for interface in &plan.reembed {
+            self.update(interface, project_id.to_string()).await?;
+        }
+        Ok(stats)
+    }
+
     pub async fn update(&self, interface: &ApiInterface, project_id: String) -> Result<()> {
         let meta = Meta {
             project_id: project_id.clone(),
             path: interface.path.clone(),
             method: interface.method.clone(),
+            text_version: None,
         };
         self.search.delete_by_meta(meta).await?;
         self.search
@@ -69,3 +332,521 @@ impl InterfaceRetrievalService {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::{document_metadata, Filter};
+    use async_trait::async_trait;
+    use std::sync::Mutex;
+
+    /// 内存实现，每次向量化生成新的向量值，便于判断是否重新计算；克隆共享存储
+    #[derive(Default, Clone)]
+    struct MemorySearch {
+        chunks: Arc<Mutex<Vec<Chunk>>>,
+        embed_calls: Arc<Mutex<usize>>,
+        /// 每次检索请求携带的阈值
+        thresholds: Arc<Mutex<Vec<Option<f32>>>>,
+        /// 每次检索请求携带的向量权重
+        vector_weights: Arc<Mutex<Vec<Option<f32>>>>,
+        summaries: Arc<Mutex<Vec<EndpointSummaryDoc>>>,
+        /// 接口索引与端点摘要索引各自的查询次数
+        interface_queries: Arc<Mutex<usize>>,
+        endpoint_queries: Arc<Mutex<usize>>,
+    }
+
+    impl MemorySearch {
+        fn embedding_of(&self, path: &str) -> Vec<f32> {
+            let chunks = self.chunks.lock().unwrap();
+            chunks
+                .iter()
+                .find(|chunk| chunk.get_meta().path == path)
+                .map(|chunk| chunk.embedding.clone())
+                .unwrap()
+        }
+    }
+
+    #[async_trait]
+    impl Search for MemorySearch {
+        async fn parse_and_store_swagger(&self, request: SwaggerParseRequest) -> Result<()> {
+            let spec: SwaggerSpec = serde_json::from_value(request.swagger_json)?;
+            for interface in interfaces_from_spec(&spec)? {
+                self.store_interface(interface, request.project_id.clone())
+                    .await?;
+            }
+            Ok(())
+        }
+
+        async fn store_interface(&self, interface: ApiInterface, project_id: String) -> Result<()> {
+            let mut calls = self.embed_calls.lock().unwrap();
+            *calls += 1;
+            self.chunks.lock().unwrap().push(Chunk {
+                id: uuid::Uuid::new_v4(),
+                text: merge_content(&interface),
+                meta: document_metadata(&project_id, &interface),
+                score: 0.0,
+                embedding: vec![*calls as f32],
+                api_content: Some(interface),
+                highlights: Vec::new(),
+                created_at: None,
+                updated_at: None,
+            });
+            Ok(())
+        }
+
+        async fn vector_search(
+            &self,
+            _query: &str,
+            _max_results: u32,
+            _similarity_threshold: f32,
+            _filters: Option<&Filter>,
+        ) -> Result<Vec<Chunk>> {
+            *self.interface_queries.lock().unwrap() += 1;
+            Ok(Vec::new())
+        }
+
+        async fn keyword_search(
+            &self,
+            _query: &str,
+            _max_results: u32,
+            _filters: Option<&Filter>,
+        ) -> Result<Vec<Chunk>> {
+            *self.interface_queries.lock().unwrap() += 1;
+            Ok(Vec::new())
+        }
+
+        async fn hybrid_search(&self, request: InterfaceSearchRequest) -> Result<SearchResult> {
+            *self.interface_queries.lock().unwrap() += 1;
+            self.thresholds
+                .lock()
+                .unwrap()
+                .push(request.similarity_threshold);
+            self.vector_weights
+                .lock()
+                .unwrap()
+                .push(request.vector_weight);
+            Ok(SearchResult::default())
+        }
+
+        async fn get_project_interfaces(
+            &self,
+            project_id: &str,
+            limit: u32,
+            offset: u32,
+        ) -> Result<Vec<Chunk>> {
+            let chunks = self.chunks.lock().unwrap();
+            Ok(chunks
+                .iter()
+                .filter(|chunk| chunk.get_meta().project_id == project_id)
+                .skip(offset as usize)
+                .take(limit as usize)
+                .map(|chunk| Chunk {
+                    id: chunk.id,
+                    text: chunk.text.clone(),
+                    meta: chunk.meta.clone(),
+                    score: chunk.score,
+                    embedding: chunk.embedding.clone(),
+                    api_content: chunk.api_content.clone(),
+                    highlights: chunk.highlights.clone(),
+                    created_at: None,
+                    updated_at: None,
+                })
+                .collect())
+        }
+
+        async fn delete_project_data(&self, project_id: &str) -> Result<u64> {
+            let mut chunks = self.chunks.lock().unwrap();
+            let before = chunks.len();
+            chunks.retain(|chunk| chunk.get_meta().project_id != project_id);
+            Ok((before - chunks.len()) as u64)
+        }
+
+        async fn delete_by_meta(&self, meta: Meta) -> Result<()> {
+            self.chunks.lock().unwrap().retain(|chunk| {
+                let stored = chunk.get_meta();
+                !(stored.project_id == meta.project_id
+                    && stored.path == meta.path
+                    && stored.method.eq_ignore_ascii_case(&meta.method))
+            });
+            Ok(())
+        }
+
+        async fn store_endpoint_summary(&self, summary: EndpointSummaryDoc) -> Result<()> {
+            let mut summaries = self.summaries.lock().unwrap();
+            summaries.retain(|stored| stored.slug != summary.slug);
+            summaries.push(summary);
+            Ok(())
+        }
+
+        async fn delete_endpoint_summary(&self, slug: &str) -> Result<()> {
+            self.summaries
+                .lock()
+                .unwrap()
+                .retain(|stored| stored.slug != slug);
+            Ok(())
+        }
+
+        /// 以查询词在摘要中出现的个数作为分数
+        async fn search_endpoints(
+            &self,
+            query: &str,
+            max_results: u32,
+            only_running: bool,
+        ) -> Result<Vec<EndpointMatch>> {
+            *self.endpoint_queries.lock().unwrap() += 1;
+            let summaries = self.summaries.lock().unwrap();
+            let mut matches: Vec<EndpointMatch> = summaries
+                .iter()
+                .filter(|summary| !only_running || summary.status == "running")
+                .map(|summary| {
+                    let text = summary.text.to_lowercase();
+                    let score = query
+                        .to_lowercase()
+                        .split_whitespace()
+                        .filter(|word| text.contains(word))
+                        .count();
+                    EndpointMatch {
+                        endpoint_id: summary.endpoint_id.clone(),
+                        slug: summary.slug.clone(),
+                        status: summary.status.clone(),
+                        summary: summary.text.clone(),
+                        score: score as f64,
+                    }
+                })
+                .filter(|matched| matched.score > 0.0)
+                .collect();
+            matches.sort_by(|a, b| b.score.total_cmp(&a.score));
+            matches.truncate(max_results as usize);
+            Ok(matches)
+        }
+
+        async fn document_count(&self) -> Result<u64> {
+            Ok(self.chunks.lock().unwrap().len() as u64)
+        }
+    }
+
+    fn spec(users_description: &str) -> serde_json::Value {
+        serde_json::json!({
+            "openapi": "3.0.0",
+            "info": {"title": "Users", "version": "1.0.0"},
+            "paths": {
+                "/users": {"get": {"summary": "list users", "description": users_description}},
+                "/orders": {"get": {"summary": "list orders"}}
+            }
+        })
+    }
+
+    #[tokio::test]
+    async fn test_list_project_interfaces_paginates() -> Result<()> {
+        let memory = MemorySearch::default();
+        let service = InterfaceRetrievalService {
+            search: Box::new(memory.clone()),
+            default_similarity_threshold: 0.3,
+            default_vector_weight: 0.5,
+        };
+        let paths: serde_json::Map<String, serde_json::Value> = (1..=5)
+            .map(|i| {
+                (
+                    format!("/items/{}", i),
+                    serde_json::json!({"get": {"summary": format!("item {}", i)}}),
+                )
+            })
+            .collect();
+        let catalog = serde_json::json!({
+            "openapi": "3.0.0",
+            "info": {"title": "Catalog", "version": "1.0.0"},
+            "paths": paths
+        });
+        service
+            .parse_and_store_swagger(SwaggerParseRequest {
+                project_id: "catalog".to_string(),
+                swagger_json: catalog,
+                version: None,
+                generate_embeddings: None,
+            })
+            .await?;
+        service
+            .parse_and_store_swagger(SwaggerParseRequest {
+                project_id: "other".to_string(),
+                swagger_json: spec("all users"),
+                version: None,
+                generate_embeddings: None,
+            })
+            .await?;
+
+        let mut seen = Vec::new();
+        let mut offset = 0;
+        loop {
+            let page = service
+                .list_project_interfaces(
+                    "catalog",
+                    &InterfaceListQuery {
+                        limit: Some(2),
+                        offset: Some(offset),
+                    },
+                )
+                .await?;
+            assert_eq!(page.limit, 2);
+            assert_eq!(page.offset, offset);
+            assert!(page.interfaces.len() <= 2);
+            seen.extend(page.interfaces.into_iter().map(|i| i.path));
+            if !page.has_more {
+                break;
+            }
+            offset += 2;
+        }
+        let mut expected: Vec<String> = (1..=5).map(|i| format!("/items/{}", i)).collect();
+        seen.sort();
+        expected.sort();
+        assert_eq!(seen, expected);
+
+        // 超出范围的页为空，limit 限制在 1..=500
+        let page = service
+            .list_project_interfaces(
+                "catalog",
+                &InterfaceListQuery {
+                    limit: Some(0),
+                    offset: Some(10),
+                },
+            )
+            .await?;
+        assert_eq!(page.limit, 1);
+        assert!(page.interfaces.is_empty());
+        assert!(!page.has_more);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_sync_project_force_embeddings() -> Result<()> {
+        let memory = MemorySearch::default();
+        let service = InterfaceRetrievalService {
+            search: Box::new(memory.clone()),
+            default_similarity_threshold: 0.3,
+            default_vector_weight: 0.5,
+        };
+
+        service.sync_project("p", spec("all users"), true).await?;
+        let users = memory.embedding_of("/users");
+        let orders = memory.embedding_of("/orders");
+
+        // 未变化的 spec 不重新向量化
+        let stats = service.sync_project("p", spec("all users"), false).await?;
+        assert_eq!(stats.reembedded, 0);
+        assert_eq!(stats.unchanged, 2);
+        assert_eq!(memory.embedding_of("/users"), users);
+
+        // 描述变化时仅重新向量化该接口
+        let stats = service
+            .sync_project("p", spec("all registered users"), false)
+            .await?;
+        assert_eq!(stats.reembedded, 1);
+        assert_ne!(memory.embedding_of("/users"), users);
+        assert_eq!(memory.embedding_of("/orders"), orders);
+
+        // force_embeddings 重新计算全部接口
+        let users = memory.embedding_of("/users");
+        let stats = service
+            .sync_project("p", spec("all registered users"), true)
+            .await?;
+        assert_eq!(stats.reembedded, 2);
+        assert_ne!(memory.embedding_of("/users"), users);
+        assert_ne!(memory.embedding_of("/orders"), orders);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_search_uses_configured_default_threshold() -> Result<()> {
+        let memory = MemorySearch::default();
+        let service = InterfaceRetrievalService {
+            search: Box::new(memory.clone()),
+            default_similarity_threshold: 0.42,
+            default_vector_weight: 0.5,
+        };
+        let request = |similarity_threshold| InterfaceSearchRequest {
+            query: "list users".to_string(),
+            search_type: SearchType::Hybrid,
+            max_results: 5,
+            similarity_threshold,
+            vector_weight: None,
+            filters: None,
+        };
+
+        service.search_interfaces(request(None)).await?;
+        service.search_interfaces(request(Some(0.9))).await?;
+        assert_eq!(
+            *memory.thresholds.lock().unwrap(),
+            vec![Some(0.42), Some(0.9)]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_search_uses_configured_default_vector_weight() -> Result<()> {
+        let memory = MemorySearch::default();
+        let service = InterfaceRetrievalService {
+            search: Box::new(memory.clone()),
+            default_similarity_threshold: 0.3,
+            default_vector_weight: 0.8,
+        };
+        let request = |vector_weight| InterfaceSearchRequest {
+            query: "list users".to_string(),
+            search_type: SearchType::Hybrid,
+            max_results: 5,
+            similarity_threshold: None,
+            vector_weight,
+            filters: None,
+        };
+
+        service.search_interfaces(request(None)).await?;
+        service.search_interfaces(request(Some(0.0))).await?;
+        assert_eq!(
+            *memory.vector_weights.lock().unwrap(),
+            vec![Some(0.8), Some(0.0)]
+        );
+
+        // 超出范围的权重在检索前拒绝
+        for weight in [-0.1, 1.5, f32::NAN] {
+            let error = service
+                .search_interfaces(request(Some(weight)))
+                .await
+                .unwrap_err();
+            assert!(error.downcast_ref::<InvalidVectorWeight>().is_some());
+        }
+        assert_eq!(memory.vector_weights.lock().unwrap().len(), 2);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_empty_backend_returns_hint() -> Result<()> {
+        let memory = MemorySearch::default();
+        let service = InterfaceRetrievalService {
+            search: Box::new(memory.clone()),
+            default_similarity_threshold: 0.3,
+            default_vector_weight: 0.5,
+        };
+        let request = || InterfaceSearchRequest {
+            query: "list users".to_string(),
+            search_type: SearchType::Hybrid,
+            max_results: 5,
+            similarity_threshold: None,
+            vector_weight: None,
+            filters: None,
+        };
+        let list = InterfaceListQuery {
+            limit: None,
+            offset: None,
+        };
+
+        let result = service.search_interfaces(request()).await?;
+        assert!(result.chunks.is_empty());
+        assert_eq!(result.hint.as_deref(), Some(EMPTY_INDEX_HINT));
+        let page = service.list_project_interfaces("p", &list).await?;
+        assert!(page.interfaces.is_empty());
+        assert_eq!(page.hint.as_deref(), Some(EMPTY_INDEX_HINT));
+
+        // 已有接口时空结果只是没有匹配，不提示注册端点
+        service.sync_project("p", spec("all users"), true).await?;
+        assert!(service.search_interfaces(request()).await?.hint.is_none());
+        let page = service.list_project_interfaces("other", &list).await?;
+        assert!(page.interfaces.is_empty());
+        assert!(page.hint.is_none());
+        Ok(())
+    }
+
+    fn endpoint(name: &str, status: EndpointStatus, spec: serde_json::Value) -> Endpoint {
+        Endpoint {
+            name: name.to_string(),
+            swagger_content: spec.to_string(),
+            status,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_search_endpoints_queries_only_summaries() -> Result<()> {
+        let memory = MemorySearch::default();
+        let service = InterfaceRetrievalService {
+            search: Box::new(memory.clone()),
+            default_similarity_threshold: 0.3,
+            default_vector_weight: 0.5,
+        };
+        let payments_spec = |refund_summary: &str| {
+            serde_json::json!({
+                "openapi": "3.0.0",
+                "info": {"title": "Payments", "version": "1.0.0"},
+                "paths": {
+                    "/refunds": {"post": {"summary": refund_summary, "tags": ["billing"]}},
+                    "/invoices": {"get": {"summary": "list invoices"}}
+                }
+            })
+        };
+        let weather_spec = serde_json::json!({
+            "openapi": "3.0.0",
+            "info": {"title": "Weather", "version": "1.0.0"},
+            "paths": {
+                "/forecast": {"get": {"summary": "daily weather forecast"}},
+                "/alerts": {"get": {"summary": "storm alerts"}}
+            }
+        });
+        let mut payments = endpoint(
+            "payments",
+            EndpointStatus::Running,
+            payments_spec("refund a charge"),
+        );
+        let weather = endpoint("weather", EndpointStatus::Stopped, weather_spec.clone());
+        service
+            .sync_project("payments", payments_spec("refund a charge"), true)
+            .await?;
+        service.sync_project("weather", weather_spec, true).await?;
+        service.sync_endpoint_summary(&payments).await?;
+        service.sync_endpoint_summary(&weather).await?;
+
+        let query = |query: &str, only_running| EndpointSearchQuery {
+            query: query.to_string(),
+            max_results: None,
+            only_running,
+        };
+        let matches = service
+            .search_endpoints(&query("refund the charge", false))
+            .await?;
+        assert_eq!(matches[0].slug, "payments");
+        assert_eq!(matches[0].endpoint_id, payments.id.to_string());
+        let matches = service
+            .search_endpoints(&query("weather forecast", false))
+            .await?;
+        assert_eq!(matches[0].slug, "weather");
+        // 只查询端点摘要，不扫描接口文档
+        assert_eq!(*memory.interface_queries.lock().unwrap(), 0);
+        assert_eq!(*memory.endpoint_queries.lock().unwrap(), 2);
+
+        // 已停止的端点被过滤
+        let matches = service
+            .search_endpoints(&query("weather forecast", true))
+            .await?;
+        assert!(matches.is_empty());
+
+        // 更新后摘要被替换
+        payments.swagger_content = payments_spec("reverse a payment").to_string();
+        service.sync_endpoint_summary(&payments).await?;
+        assert_eq!(memory.summaries.lock().unwrap().len(), 2);
+        let matches = service
+            .search_endpoints(&query("reverse payment", true))
+            .await?;
+        assert_eq!(matches[0].slug, "payments");
+        assert!(matches[0]
+            .summary
+            .contains("POST /refunds reverse a payment"));
+
+        // 删除项目数据时摘要一并删除
+        service.delete_project_data("payments").await?;
+        let slugs: Vec<String> = memory
+            .summaries
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|summary| summary.slug.clone())
+            .collect();
+        assert_eq!(slugs, vec!["weather"]);
+        Ok(())
+    }
+}
@@ -9,8 +9,14 @@ pub type ProjectId = String;
 
 pub enum EndpointEvent {
     Created(ProjectId),
-    DELETE(ProjectId),
+    /// 增量同步，仅重新向量化变化的接口
     UPDATE(ProjectId),
+    /// 强制重新向量化项目的全部接口
+    Reembed(ProjectId),
+    /// 端点启动预热：补齐待同步的接口向量，端点未变化，不使解析缓存失效
+    Warmup(ProjectId),
+    /// 端点启停，只刷新端点摘要中的状态
+    StatusChanged(ProjectId),
 }
 
 /// 监听Endpoint增删改, 对应操作向量数据库数据
@@ -57,10 +63,60 @@ impl EndpointListener {
         }
     }
 
+    /// 重建端点摘要文档，失败只记录日志
+    async fn sync_summary(&self, project_id: &ProjectId) {
+        let result = match self
+            .endpoint_service
+            .get_endpoint_by_name(project_id.to_string())
+            .await
+        {
+            Ok(endpoint) => self.retrieval.sync_endpoint_summary(&endpoint).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            error!("Failed to sync summary for endpoint {}: {}", project_id, e);
+        }
+    }
+
+    async fn sync_project(&self, project_id: &ProjectId, force: bool) {
+        let Some(parse_request) = self.find_endpoint_to_spr(project_id).await else {
+            return;
+        };
+        match self
+            .retrieval
+            .sync_project(project_id, parse_request.swagger_json, force)
+            .await
+        {
+            Ok(stats) => info!(
+                "Synced interfaces for endpoint {} (force: {}): {:?}",
+                project_id, force, stats
+            ),
+            Err(e) => error!(
+                "Failed to sync interfaces for endpoint {}: {}",
+                project_id, e
+            ),
+        }
+        self.sync_summary(project_id).await;
+    }
+
     pub fn run(self, mut receive: mpsc::Receiver<EndpointEvent>) {
         tokio::task::spawn(async move {
             loop {
-                match receive.recv().await {
+                let event = receive.recv().await;
+                // 端点变更后解析缓存失效
+                match &event {
+                    Some(EndpointEvent::Created(project_id))
+                    | Some(EndpointEvent::UPDATE(project_id))
+                    | Some(EndpointEvent::Reembed(project_id)) => {
+                        self.endpoint_service
+                            .spec_cache()
+                            .invalidate_name(project_id);
+                    }
+                    Some(EndpointEvent::Warmup(_))
+                    | Some(EndpointEvent::StatusChanged(_))
+                    | None => {}
+                }
+                match event {
                     Some(EndpointEvent::Created(project_id)) => {
                         match self.find_endpoint_to_spr(&project_id).await {
                             None => {}
@@ -76,25 +132,19 @@ impl EndpointListener {
                                         );
                                     }
                                 }
+                                self.sync_summary(&project_id).await;
                             }
                         };
                     }
-                    Some(EndpointEvent::DELETE(project_id)) => {
-                        let d = self
-                            .retrieval
-                            .delete_project_data(project_id.as_str())
-                            .await;
-                        info!("delete project: {:?}, result: {:?}", project_id, d);
+                    Some(EndpointEvent::UPDATE(project_id))
+                    | Some(EndpointEvent::Warmup(project_id)) => {
+                        self.sync_project(&project_id, false).await;
+                    }
+                    Some(EndpointEvent::Reembed(project_id)) => {
+                        self.sync_project(&project_id, true).await;
                     }
-                    Some(EndpointEvent::UPDATE(project_id)) => {
-                        self.update_sender
-                            .send(EndpointEvent::DELETE(project_id.clone()))
-                            .await
-                            .unwrap();
-                        self.update_sender
-                            .send(EndpointEvent::Created(project_id))
-                            .await
-                            .unwrap();
+                    Some(EndpointEvent::StatusChanged(project_id)) => {
+                        self.sync_summary(&project_id).await;
                     }
                     None => {}
                 }
@@ -0,0 +1,259 @@
+use crate::models::{DbPool, LocaleSettings, Operation};
+use anyhow::{anyhow, Result};
+use dashmap::DashMap;
+use serde_json::Value;
+use sqlx::Row;
+use std::sync::OnceLock;
+use uuid::Uuid;
+
+/// 指定会话或单次请求语言的请求头，如 "zh-CN"
+pub const LOCALE_HEADER: &str = "X-MCP-Locale";
+/// 覆盖单次调用语言的工具参数，执行前从参数中移除，不会发往上游
+pub const LOCALE_ARGUMENT: &str = "_locale";
+/// 会话在 initialize 时通过 experimental 能力声明语言使用的键，形如 {"locale": {"tag": "zh-CN"}}
+pub const LOCALE_CAPABILITY: &str = "locale";
+/// 转发给上游的请求头
+pub const ACCEPT_LANGUAGE: &str = "Accept-Language";
+
+/// 视为操作自带语言参数的参数名，不区分大小写
+const LANGUAGE_PARAMETERS: &[&str] = &["accept-language", "lang", "language", "locale"];
+/// BCP 47 语言标签的最大长度
+const MAX_LOCALE_LEN: usize = 35;
+
+/// 端点语言设置的内存副本，启动时加载，配置接口修改后立即生效
+static LOCALE_SETTINGS: OnceLock<DashMap<Uuid, LocaleSettings>> = OnceLock::new();
+
+pub fn locale_settings() -> &'static DashMap<Uuid, LocaleSettings> {
+    LOCALE_SETTINGS.get_or_init(DashMap::new)
+}
+
+/// 规范化语言标签：下划线转为连字符，须形如 zh-CN、en、zh-Hant-TW，否则返回 None
+pub fn normalize_locale(value: &str) -> Option<String> {
+    let tag = value.trim().replace('_', "-");
+    if tag.len() > MAX_LOCALE_LEN {
+        return None;
+    }
+    let mut subtags = tag.split('-');
+    let primary = subtags.next()?;
+    if !(2..=8).contains(&primary.len()) || !primary.chars().all(|c| c.is_ascii_alphabetic()) {
+        return None;
+    }
+    if !subtags.all(|s| (1..=8).contains(&s.len()) && s.chars().all(|c| c.is_ascii_alphanumeric()))
+    {
+        return None;
+    }
+    Some(tag)
+}
+
+/// 从参数中取出 _locale，返回去掉该键后的参数与规范化后的语言，不合法的语言忽略
+pub fn take_locale_override(arguments: &Value) -> (Value, Option<String>) {
+    match arguments {
+        Value::Object(map) if map.contains_key(LOCALE_ARGUMENT) => {
+            let mut map = map.clone();
+            let locale = map
+                .remove(LOCALE_ARGUMENT)
+                .and_then(|v| v.as_str().and_then(normalize_locale));
+            (Value::Object(map), locale)
+        }
+        _ => (arguments.clone(), None),
+    }
+}
+
+/// initialize 请求 experimental 能力中声明的语言
+pub fn locale_from_capability(capability: &serde_json::Map<String, Value>) -> Option<String> {
+    capability
+        .get("tag")
+        .and_then(Value::as_str)
+        .and_then(normalize_locale)
+}
+
+/// 操作已声明语言参数（如 lang 查询参数或 Accept-Language 请求头）时由参数决定语言
+pub fn declares_language_parameter(operation: &Operation) -> bool {
+    operation.parameters.iter().flatten().any(|parameter| {
+        LANGUAGE_PARAMETERS
+            .iter()
+            .any(|name| parameter.name.eq_ignore_ascii_case(name))
+    })
+}
+
+/// 发往上游的语言：调用或会话指定的语言优先，其次为端点默认语言；端点关闭转发时为 None
+pub fn resolve_locale(requested: Option<String>, settings: &LocaleSettings) -> Option<String> {
+    if !settings.forward {
+        return None;
+    }
+    requested.or_else(|| settings.default_locale.clone())
+}
+
+/// 按端点当前设置解析语言，未配置的端点使用默认设置
+pub fn effective_locale(endpoint_id: Uuid, requested: Option<String>) -> Option<String> {
+    match locale_settings().get(&endpoint_id) {
+        Some(settings) => resolve_locale(requested, &settings),
+        None => resolve_locale(requested, &LocaleSettings::default()),
+    }
+}
+
+pub struct LocaleService {
+    pool: DbPool,
+}
+
+impl LocaleService {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    /// 启动时加载全部端点的语言设置
+    pub async fn load_all(&self) -> Result<usize> {
+        let rows = sqlx::query(
+            "SELECT endpoint_id, forward, default_locale FROM endpoint_locale_settings",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        locale_settings().clear();
+        for row in &rows {
+            let endpoint_id: String = row.try_get("endpoint_id")?;
+            locale_settings().insert(Uuid::parse_str(&endpoint_id)?, settings_from_row(row)?);
+        }
+        Ok(rows.len())
+    }
+
+    /// 未配置时返回默认设置
+    pub async fn get_settings(&self, endpoint_id: Uuid) -> Result<LocaleSettings> {
+        let row = sqlx::query(
+            "SELECT forward, default_locale FROM endpoint_locale_settings WHERE endpoint_id = ?",
+        )
+        .bind(endpoint_id.to_string())
+        .fetch_optional(&self.pool)
+        .await?;
+        row.as_ref()
+            .map(settings_from_row)
+            .transpose()
+            .map(Option::unwrap_or_default)
+    }
+
+    /// 修改端点语言设置，对后续调用立即生效
+    pub async fn update_settings(
+        &self,
+        endpoint_id: Uuid,
+        settings: LocaleSettings,
+    ) -> Result<LocaleSettings> {
+        let default_locale = match settings.default_locale.as_deref().map(str::trim) {
+            None | Some("") => None,
+            Some(locale) => Some(normalize_locale(locale).ok_or_else(|| {
+                anyhow!(
+                    "Invalid locale settings: '{}' is not a language tag",
+                    locale
+                )
+            })?),
+        };
+        let settings = LocaleSettings {
+            forward: settings.forward,
+            default_locale,
+        };
+        sqlx::query(
+            "INSERT INTO endpoint_locale_settings (endpoint_id, forward, default_locale) VALUES (?, ?, ?) ON DUPLICATE KEY UPDATE forward = VALUES(forward), default_locale = VALUES(default_locale)",
+        )
+        .bind(endpoint_id.to_string())
+        .bind(settings.forward)
+        .bind(settings.default_locale.as_deref())
+        .execute(&self.pool)
+        .await?;
+        locale_settings().insert(endpoint_id, settings.clone());
+        Ok(settings)
+    }
+}
+
+fn settings_from_row(row: &sqlx::mysql::MySqlRow) -> Result<LocaleSettings> {
+    Ok(LocaleSettings {
+        forward: row.try_get("forward")?,
+        default_locale: row.try_get("default_locale")?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_normalize_locale() {
+        assert_eq!(normalize_locale(" zh_CN "), Some("zh-CN".to_string()));
+        assert_eq!(normalize_locale("en"), Some("en".to_string()));
+        assert_eq!(
+            normalize_locale("zh-Hant-TW"),
+            Some("zh-Hant-TW".to_string())
+        );
+        assert_eq!(normalize_locale(""), None);
+        assert_eq!(normalize_locale("e"), None);
+        assert_eq!(normalize_locale("en-"), None);
+        assert_eq!(normalize_locale("en;q=0.9"), None);
+        assert_eq!(normalize_locale("en\r\nX-Injected: 1"), None);
+    }
+
+    #[test]
+    fn test_take_locale_override() {
+        let (arguments, locale) = take_locale_override(&json!({"petId": 7, "_locale": "ja_JP"}));
+        assert_eq!(arguments, json!({"petId": 7}));
+        assert_eq!(locale, Some("ja-JP".to_string()));
+
+        let (arguments, locale) = take_locale_override(&json!({"petId": 7, "_locale": 1}));
+        assert_eq!(arguments, json!({"petId": 7}));
+        assert_eq!(locale, None);
+
+        let (arguments, locale) = take_locale_override(&json!({"petId": 7}));
+        assert_eq!(arguments, json!({"petId": 7}));
+        assert_eq!(locale, None);
+    }
+
+    #[test]
+    fn test_resolve_locale() {
+        let settings = LocaleSettings {
+            forward: true,
+            default_locale: Some("en-US".to_string()),
+        };
+        // 调用或会话指定的语言优先于端点默认语言
+        assert_eq!(
+            resolve_locale(Some("zh-CN".to_string()), &settings),
+            Some("zh-CN".to_string())
+        );
+        assert_eq!(resolve_locale(None, &settings), Some("en-US".to_string()));
+        assert_eq!(resolve_locale(None, &LocaleSettings::default()), None);
+        // 关闭转发时一律不发送
+        let disabled = LocaleSettings {
+            forward: false,
+            ..settings
+        };
+        assert_eq!(resolve_locale(Some("zh-CN".to_string()), &disabled), None);
+    }
+
+    #[test]
+    fn test_declares_language_parameter() {
+        let operation = |parameters: Value| -> Operation {
+            serde_json::from_value(json!({ "parameters": parameters })).unwrap()
+        };
+        assert!(declares_language_parameter(&operation(json!([
+            {"name": "Accept-Language", "in": "header"}
+        ]))));
+        assert!(declares_language_parameter(&operation(json!([
+            {"name": "petId", "in": "path"},
+            {"name": "Lang", "in": "query"}
+        ]))));
+        assert!(!declares_language_parameter(&operation(json!([
+            {"name": "petId", "in": "path"}
+        ]))));
+        assert!(!declares_language_parameter(&operation(json!([]))));
+    }
+
+    #[test]
+    fn test_locale_from_capability() {
+        let capability = json!({"tag": "fr_FR"});
+        assert_eq!(
+            locale_from_capability(capability.as_object().unwrap()),
+            Some("fr-FR".to_string())
+        );
+        let capability = json!({"tag": 42});
+        assert_eq!(
+            locale_from_capability(capability.as_object().unwrap()),
+            None
+        );
+    }
+}
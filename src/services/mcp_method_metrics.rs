@@ -0,0 +1,132 @@
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+use std::sync::OnceLock;
+use uuid::Uuid;
+
+/// Prometheus 文本格式中的指标名
+pub const MCP_METHOD_METRIC: &str = "mcp_method_total";
+
+pub fn mcp_method_counters() -> &'static McpMethodCounters {
+    static MCP_METHOD_COUNTERS: OnceLock<McpMethodCounters> = OnceLock::new();
+    MCP_METHOD_COUNTERS.get_or_init(McpMethodCounters::new)
+}
+
+/// 端点收到的某个 MCP 方法的累计次数
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct McpMethodCount {
+    pub endpoint_id: Uuid,
+    pub method: String,
+    pub total: u64,
+}
+
+/// format=prometheus 时返回文本格式，默认 JSON
+#[derive(Debug, Deserialize)]
+pub struct McpMethodMetricsQuery {
+    pub format: Option<String>,
+}
+
+/// 进程内按端点与方法累计的 MCP 请求数，即 mcp_method_total{endpoint,method}；重启后清零
+#[derive(Default)]
+pub struct McpMethodCounters {
+    counts: DashMap<(Uuid, String), u64>,
+}
+
+impl McpMethodCounters {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn increment(&self, endpoint_id: Uuid, method: &str) {
+        *self
+            .counts
+            .entry((endpoint_id, method.to_string()))
+            .or_insert(0) += 1;
+    }
+
+    pub fn get(&self, endpoint_id: Uuid, method: &str) -> u64 {
+        self.counts
+            .get(&(endpoint_id, method.to_string()))
+            .map_or(0, |count| *count)
+    }
+
+    /// 端点删除后丢弃其计数
+    pub fn remove_endpoint(&self, endpoint_id: Uuid) {
+        self.counts.retain(|(id, _), _| *id != endpoint_id);
+    }
+
+    /// 按端点、方法排序的计数
+    pub fn snapshot(&self) -> Vec<McpMethodCount> {
+        let mut counts: Vec<McpMethodCount> = self
+            .counts
+            .iter()
+            .map(|entry| McpMethodCount {
+                endpoint_id: entry.key().0,
+                method: entry.key().1.clone(),
+                total: *entry.value(),
+            })
+            .collect();
+        counts.sort_by(|a, b| (a.endpoint_id, &a.method).cmp(&(b.endpoint_id, &b.method)));
+        counts
+    }
+
+    /// Prometheus 文本格式
+    pub fn render_prometheus(&self) -> String {
+        let mut output = format!(
+            "# HELP {0} MCP requests received per endpoint and method\n# TYPE {0} counter\n",
+            MCP_METHOD_METRIC
+        );
+        for count in self.snapshot() {
+            let _ = writeln!(
+                output,
+                "{}{{endpoint=\"{}\",method=\"{}\"}} {}",
+                MCP_METHOD_METRIC,
+                count.endpoint_id,
+                count.method.replace('\\', "\\\\").replace('"', "\\\""),
+                count.total
+            );
+        }
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counts_per_endpoint_and_method() {
+        let counters = McpMethodCounters::new();
+        let (a, b) = (Uuid::from_u128(1), Uuid::from_u128(2));
+        for method in ["initialize", "tools/list", "tools/list", "tools/call"] {
+            counters.increment(a, method);
+        }
+        counters.increment(b, "tools/list");
+
+        assert_eq!(counters.get(a, "tools/list"), 2);
+        assert_eq!(counters.get(a, "initialize"), 1);
+        assert_eq!(counters.get(b, "tools/list"), 1);
+        assert_eq!(counters.get(b, "tools/call"), 0);
+        let snapshot = counters.snapshot();
+        let methods: Vec<(&str, u64)> = snapshot
+            .iter()
+            .filter(|c| c.endpoint_id == a)
+            .map(|c| (c.method.as_str(), c.total))
+            .collect();
+        assert_eq!(
+            methods,
+            vec![("initialize", 1), ("tools/call", 1), ("tools/list", 2)]
+        );
+
+        let text = counters.render_prometheus();
+        assert!(text.contains("# TYPE mcp_method_total counter"));
+        assert!(text.contains(&format!(
+            "mcp_method_total{{endpoint=\"{}\",method=\"tools/list\"}} 2",
+            a
+        )));
+
+        counters.remove_endpoint(a);
+        assert_eq!(counters.get(a, "tools/list"), 0);
+        assert_eq!(counters.snapshot().len(), 1);
+    }
+}
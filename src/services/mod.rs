@@ -1,24 +1,82 @@
+pub mod analytics_export_service;
+pub mod batch_call_service;
+pub mod canary_service;
+pub mod composite_tool_service;
+pub mod contract_test_service;
+pub mod dataset_access_service;
+pub mod demo_seed_service;
+pub mod dns_override_service;
 pub mod elastic_search;
 pub mod embedding_service;
+pub mod embedding_text;
+pub mod endpoint_cleanup;
+pub mod endpoint_lifecycle;
 pub mod endpoint_service;
+pub mod endpoint_warmup;
+pub mod execution_policy_service;
+pub mod fair_scheduler;
 pub mod file_service;
 pub mod interface_retrieval_service;
+pub mod job_service;
+pub mod kv_store_service;
 mod listener_enpoint_event;
+pub mod locale_service;
+pub mod mcp_method_metrics;
 pub mod mcp_service;
+pub mod metrics_history_service;
+pub mod mock_service;
+pub mod operation_note_service;
 pub mod pgvectorrs_search;
+pub mod plugin_service;
+pub mod recording_service;
 pub mod search;
+pub mod search_feedback_service;
 mod session_service;
+pub mod session_transcript_service;
+pub mod spec_cache;
 pub mod swagger_service;
 pub mod table_rag_service;
+pub mod tool_stats;
+pub mod tools_version;
+pub mod usage_report_service;
 
+pub use analytics_export_service::*;
+pub use batch_call_service::*;
+pub use canary_service::*;
+pub use composite_tool_service::*;
+pub use contract_test_service::*;
+pub use dataset_access_service::*;
+pub use demo_seed_service::*;
+pub use dns_override_service::*;
 pub use elastic_search::*;
-pub use embedding_service::EmbeddingService;
+pub use embedding_service::{EmbeddingError, EmbeddingService};
+pub use embedding_text::*;
+pub use endpoint_cleanup::*;
+pub use endpoint_lifecycle::*;
 pub use endpoint_service::*;
+pub use endpoint_warmup::*;
+pub use execution_policy_service::*;
+pub use fair_scheduler::*;
 pub use file_service::FileService;
+pub use job_service::*;
+pub use kv_store_service::*;
 pub use listener_enpoint_event::*;
-pub use mcp_service::McpService;
+pub use locale_service::*;
+pub use mcp_method_metrics::*;
+pub use mcp_service::{apply_status_mapping, log_cancelled_call, McpService, UpstreamStatusError};
+pub use metrics_history_service::*;
+pub use mock_service::*;
+pub use operation_note_service::*;
 pub use pgvectorrs_search::*;
+pub use plugin_service::*;
+pub use recording_service::*;
 pub use search::*;
+pub use search_feedback_service::*;
 pub use session_service::*;
+pub use session_transcript_service::*;
+pub use spec_cache::*;
 pub use swagger_service::*;
 pub use table_rag_service::*;
+pub use tool_stats::*;
+pub use tools_version::*;
+pub use usage_report_service::*;
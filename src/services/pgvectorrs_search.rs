@@ -1,13 +1,14 @@
-use crate::config::EmbeddingConfig;
+use crate::config::{EmbeddingConfig, PgvectorIndexConfig, PgvectorIndexType};
 use crate::models::interface_retrieval::*;
 use crate::models::swagger::SwaggerSpec;
-use crate::services::{merge_content, Chunk, EmbeddingService, Filter, Meta, Search};
-use crate::utils::generate_api_details;
+use crate::services::{
+    degrade_vector_results, document_metadata, embedding_text_builder, interfaces_from_spec,
+    merge_content, Chunk, EmbeddingService, Filter, Meta, Search, SearchResult,
+};
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
 
 use chrono::{DateTime, Utc};
-use serde_json::json;
 use sqlx::postgres::{PgPoolOptions, PgRow};
 use sqlx::{Pool, Postgres, Row};
 use std::collections::HashMap;
@@ -29,23 +30,160 @@ impl From<&PgRow> for Chunk {
             score: row.get("score"),
             embedding: Vec::with_capacity(0),
             api_content,
+            highlights: Vec::new(),
             created_at: Some(created_at),
             updated_at: Some(updated_at),
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 enum ParamValue {
     I64(i64),
     Text(String),
+    TextArray(Vec<String>),
     // 添加更多类型...
 }
 
+/// PostgreSQL undefined_table
+const UNDEFINED_TABLE: &str = "42P01";
+
+/// 表不存在（如启动后被手动删除）时视为没有数据，而不是返回数据库错误
+fn empty_if_undefined_table<T: Default>(result: sqlx::Result<T>) -> sqlx::Result<T> {
+    match result {
+        Err(sqlx::Error::Database(e)) if e.code().as_deref() == Some(UNDEFINED_TABLE) => {
+            tracing::warn!("pgvecto.rs table is missing, treating it as empty: {}", e);
+            Ok(T::default())
+        }
+        result => result,
+    }
+}
+
+/// 支持的最低 pgvecto.rs 版本：索引 options 与检索参数 GUC 自 0.2 起可用
+const MIN_EXTENSION_VERSION: (u64, u64) = (0, 2);
+
+/// 检查已安装的 vectors 扩展版本
+fn check_extension_version(version: Option<&str>) -> Result<()> {
+    let Some(version) = version else {
+        return Err(anyhow!(
+            "pgvecto.rs extension 'vectors' is not installed; use a PostgreSQL image with pgvecto.rs \
+             (e.g. tensorchord/pgvecto-rs) and grant the user CREATE on the database"
+        ));
+    };
+    let mut parts = version
+        .trim_start_matches('v')
+        .split(['.', '-'])
+        .map(|part| part.parse::<u64>().unwrap_or(0));
+    let installed = (parts.next().unwrap_or(0), parts.next().unwrap_or(0));
+    if installed < MIN_EXTENSION_VERSION {
+        return Err(anyhow!(
+            "pgvecto.rs extension version {} is not supported, {}.{} or later is required",
+            version,
+            MIN_EXTENSION_VERSION.0,
+            MIN_EXTENSION_VERSION.1
+        ));
+    }
+    Ok(())
+}
+
+/// 已存在的向量列维度需与 embedding.dimension 一致
+fn check_vector_dims(table: &str, column_type: Option<&str>, dimension: usize) -> Result<()> {
+    let dims = column_type
+        .and_then(|t| t.strip_prefix("vector("))
+        .and_then(|t| t.strip_suffix(')'))
+        .and_then(|t| t.parse::<usize>().ok());
+    match dims {
+        Some(dims) if dims == dimension => Ok(()),
+        existing => Err(anyhow!(
+            "pgvecto.rs table '{table}' has vector dims {existing} but embedding.dimension is {dimension}. \
+             Recreate the table and re-embed after changing the embedding model: \
+             DROP TABLE {table}, restart the gateway, \
+             then PUT /api/endpoint/{{id}} with {{\"force_embeddings\": true}} for each endpoint",
+            table = table,
+            existing = existing.map_or_else(|| "<missing>".to_string(), |d| d.to_string()),
+            dimension = dimension,
+        )),
+    }
+}
+
+/// 向量索引 DDL，索引参数来自配置
+fn vector_index_sql(index: &PgvectorIndexConfig) -> String {
+    let indexing = match index.index_type {
+        PgvectorIndexType::Hnsw => format!(
+            "[indexing.hnsw]\n                    m = {}\n                    ef_construction = {}",
+            index.m, index.ef_construction
+        ),
+        PgvectorIndexType::Ivf => format!(
+            "[indexing.ivf]\n                    nlist = {}",
+            index.lists
+        ),
+    };
+    format!(
+        r#"
+            CREATE INDEX IF NOT EXISTS idx_embedding
+            ON interfaces_v2 USING vectors(embedding vector_l2_ops)
+            WITH (options = $$
+                    optimizing.optimizing_threads = 30
+                    segment.max_growing_segment_size = 2000
+                    segment.max_sealed_segment_size = 30000000
+                    {}
+                    $$);
+        "#,
+        indexing
+    )
+}
+
+/// 当前事务内的检索参数，过滤条件中的 ef_search / probes 优先于配置
+fn search_parameter_sql(index: &PgvectorIndexConfig, filter: Option<&Filter>) -> String {
+    match index.index_type {
+        PgvectorIndexType::Hnsw => format!(
+            "SET LOCAL vectors.hnsw_ef_search = {}",
+            filter
+                .and_then(|f| f.ef_search)
+                .unwrap_or(index.ef_search)
+                .max(1)
+        ),
+        PgvectorIndexType::Ivf => format!(
+            "SET LOCAL vectors.ivf_nprobe = {}",
+            filter.and_then(|f| f.probes).unwrap_or(index.probes).max(1)
+        ),
+    }
+}
+
+/// 过滤条件使用 meta 的生成列，以便走索引；参数编号从 first_param 开始
+fn filter_conditions(
+    filter: Option<&Filter>,
+    first_param: usize,
+) -> (Vec<String>, Vec<ParamValue>) {
+    let mut conditions = Vec::new();
+    let mut params = Vec::new();
+    let Some(filter) = filter else {
+        return (conditions, params);
+    };
+    if let Some(project_id) = &filter.project_id {
+        params.push(ParamValue::Text(project_id.to_string()));
+        conditions.push(format!("project_id = ${}", first_param + params.len() - 1));
+    }
+    if let Some(prefix_path) = &filter.prefix_path {
+        let escaped = prefix_path
+            .replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_");
+        params.push(ParamValue::Text(format!("{}%", escaped)));
+        conditions.push(format!("path LIKE ${}", first_param + params.len() - 1));
+    }
+    if let Some(methods) = filter.methods.as_ref().filter(|m| !m.is_empty()) {
+        params.push(ParamValue::TextArray(methods.clone()));
+        conditions.push(format!("method = ANY(${})", first_param + params.len() - 1));
+    }
+    (conditions, params)
+}
+
 /// PgVector-RS 向量检索服务
 pub struct PgvectorRsSearch {
     pool: Pool<Postgres>,
     embedding_service: Arc<EmbeddingService>,
+    index: PgvectorIndexConfig,
 }
 
 impl PgvectorRsSearch {
@@ -78,24 +216,31 @@ impl PgvectorRsSearch {
         let service = Self {
             pool,
             embedding_service,
+            index: pgvector_config.index.clone(),
         };
 
         // 初始化数据库schema
-        service.init_schema().await?;
+        service.init_schema(config.dimension).await?;
 
         Ok(service)
     }
 
-    /// 初始化数据库schema
-    async fn init_schema(&self) -> Result<()> {
+    /// 初始化数据库schema，可重复执行
+    async fn init_schema(&self, dimension: usize) -> Result<()> {
         // 创建pgvecto-rs扩展
         sqlx::query(r#"CREATE EXTENSION IF NOT EXISTS vectors"#)
             .execute(&self.pool)
-            .await?;
+            .await
+            .map_err(|e| anyhow!("Failed to create pgvecto.rs extension 'vectors': {}", e))?;
+        let version: Option<String> =
+            sqlx::query_scalar("SELECT extversion FROM pg_extension WHERE extname = 'vectors'")
+                .fetch_optional(&self.pool)
+                .await?;
+        check_extension_version(version.as_deref())?;
 
         // meta: project_id, method, path,
         // embedding: summary, description, service_description
-        sqlx::query(
+        sqlx::query(&format!(
             r#"
             CREATE TABLE IF NOT EXISTS interfaces_v2 (
                 id UUID PRIMARY KEY,
@@ -103,57 +248,89 @@ impl PgvectorRsSearch {
                 api_content TEXT NOT NULL,
                 text_tsvector TSVECTOR DEFAULT NULL,
                 meta JSONB NOT NULL,
-                embedding vector(1024) NOT NULL,
+                embedding vector({dimension}) NOT NULL,
                 created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
                 updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
             ) using heap;
-        "#,
-        )
+        "#
+        ))
         .execute(&self.pool)
         .await?;
+        self.check_table_dims("interfaces_v2", dimension).await?;
+
+        // meta 字段的生成列，过滤条件走 btree 索引而不是扫描 jsonb
+        for column in ["project_id", "path", "method"] {
+            sqlx::query(&format!(
+                "ALTER TABLE interfaces_v2 ADD COLUMN IF NOT EXISTS {column} TEXT GENERATED ALWAYS AS (meta->>'{column}') STORED"
+            ))
+            .execute(&self.pool)
+            .await?;
+        }
+        for index in [
+            "CREATE INDEX IF NOT EXISTS idx_interfaces_project ON interfaces_v2 (project_id, method)",
+            "CREATE INDEX IF NOT EXISTS idx_interfaces_path ON interfaces_v2 (path text_pattern_ops)",
+        ] {
+            sqlx::query(index).execute(&self.pool).await?;
+        }
 
         // 创建索引
+        sqlx::query(&vector_index_sql(&self.index))
+            .execute(&self.pool)
+            .await?;
+
         sqlx::query(
             r#"
-            CREATE INDEX IF NOT EXISTS idx_embedding
-            ON interfaces_v2 USING vectors(embedding vector_l2_ops)
-            WITH (options = $$
-                    optimizing.optimizing_threads = 30
-                    segment.max_growing_segment_size = 2000
-                    segment.max_sealed_segment_size = 30000000
-                    [indexing.hnsw]
-                    m=30
-                    ef_construction=500
-                    $$);
+            CREATE INDEX IF NOT EXISTS idx_fts ON interfaces_v2 USING GIN (text_tsvector)
         "#,
         )
         .execute(&self.pool)
         .await?;
 
-        sqlx::query(
+        // 端点摘要，每个端点一行
+        sqlx::query(&format!(
             r#"
-            CREATE INDEX IF NOT EXISTS idx_fts ON interfaces_v2 USING GIN (text_tsvector)
-        "#,
+            CREATE TABLE IF NOT EXISTS endpoint_summaries_v1 (
+                slug TEXT PRIMARY KEY,
+                endpoint_id TEXT NOT NULL,
+                status TEXT NOT NULL,
+                text TEXT NOT NULL,
+                embedding vector({dimension}) NOT NULL,
+                updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+            ) using heap;
+        "#
+        ))
+        .execute(&self.pool)
+        .await?;
+        self.check_table_dims("endpoint_summaries_v1", dimension)
+            .await?;
+        sqlx::query(
+            "CREATE INDEX IF NOT EXISTS idx_endpoint_summaries_status ON endpoint_summaries_v1 (status)",
         )
         .execute(&self.pool)
         .await?;
-        // todo: 添加meta字段索引
 
         info!("PgVector-RS schema initialized successfully");
         Ok(())
     }
 
+    /// 表已存在时校验向量列维度
+    async fn check_table_dims(&self, table: &str, dimension: usize) -> Result<()> {
+        let column_type: Option<String> = sqlx::query_scalar(
+            "SELECT format_type(atttypid, atttypmod) FROM pg_attribute WHERE attrelid = $1::regclass AND attname = 'embedding'",
+        )
+        .bind(table)
+        .fetch_optional(&self.pool)
+        .await?;
+        check_vector_dims(table, column_type.as_deref(), dimension)
+    }
+
     /// 存储接口到数据库
     async fn store_interfaces(&self, interfaces: &[ApiInterface], project_id: &str) -> Result<u64> {
         let mut stored_count = 0;
 
         for interface in interfaces {
             // 插入或更新接口
-            let meta_value = json!({
-                "project_id": project_id,
-                "method": interface.method,
-                "path": interface.path
-            });
+            let meta_value = document_metadata(project_id, interface);
 
             let text = merge_content(interface);
             let embedding = self.embedding_service.embed_text(&text).await?;
@@ -196,20 +373,9 @@ impl Search for PgvectorRsSearch {
 
         // 解析Swagger JSON
         let swagger_spec: SwaggerSpec = serde_json::from_value(request.swagger_json)?;
-        let api_details = generate_api_details(&swagger_spec)?;
-
-        info!("Found {} interfaces in Swagger", api_details.len());
-
-        // 将ApiDetail转换为ApiInterface
-        let interfaces: Vec<ApiInterface> = api_details
-            .into_iter()
-            .map(|detail| {
-                let mut interface = ApiInterface::from(detail);
-                interface.service_description = swagger_spec.info.description.clone();
-                interface.tags = vec![swagger_spec.info.title.clone()];
-                interface
-            })
-            .collect();
+        let interfaces = interfaces_from_spec(&swagger_spec)?;
+
+        info!("Found {} interfaces in Swagger", interfaces.len());
 
         // 存储接口
         let stored_count = self
@@ -229,57 +395,41 @@ impl Search for PgvectorRsSearch {
         query: &str,
         max_results: u32,
         _similarity_threshold: f32,
-        _filters: Option<&Filter>,
+        filters: Option<&Filter>,
     ) -> Result<Vec<Chunk>> {
         // 获取查询向量
         let query_embedding = self.embedding_service.embed_text(query).await?;
 
-        // 构建SQL查询
-        // let query_vector_str = format!("[{}]", query_embedding.iter().map(|f| f.to_string()).collect::<Vec<_>>().join(","));
-        let sql = r#"
+        let (conditions, params) = filter_conditions(filters, 3);
+        let mut sql = r#"
             SELECT *, embedding <=> $1 AS score
             FROM interfaces_v2
-            ORDER BY score
-            LIMIT $2
         "#
         .to_string();
+        if !conditions.is_empty() {
+            sql.push_str(" WHERE ");
+            sql.push_str(conditions.join(" AND ").as_str());
+        }
+        sql.push_str(" ORDER BY score LIMIT $2");
 
-        // let mut param_count = 1;
-        // let mut boxed_params: Vec<Box<dyn tokio_postgres::types::ToSql + Send + Sync>> = vec![
-        //     Box::new(similarity_threshold as f64),
-        // ];
-        //
-        // // 添加项目ID过滤
-        // let project_id_owned = project_id.map(|s| s.to_string());
-        // if let Some(ref pid) = project_id_owned {
-        //     param_count += 1;
-        //     sql.push_str(&format!(" AND project_id = ${}", param_count));
-        //     boxed_params.push(Box::new(pid.clone()));
-        // }
-        //
-        // // 添加过滤条件
-        // let filter_conditions = self.build_filter_conditions(filters, &mut param_count, &mut boxed_params);
-        // sql.push_str(&filter_conditions);
-        //
-        // // 添加排序和限制
-        // sql.push_str(" ORDER BY similarity DESC");
-        // param_count += 1;
-        // sql.push_str(&format!(" LIMIT ${}", param_count));
-        // let limit_value = max_results as i64;
-        // boxed_params.push(Box::new(limit_value));
-        //
-        // // 转换为引用参数
-        // let params: Vec<&(dyn tokio_postgres::types::ToSql + Sync)> = boxed_params
-        //     .iter()
-        //     .map(|p| p.as_ref() as &(dyn tokio_postgres::types::ToSql + Sync))
-        //     .collect();
-
-        // 执行查询
-        let rows = sqlx::query(&sql)
+        let mut query = sqlx::query(&sql)
             .bind(query_embedding)
-            .bind(max_results as i64)
-            .fetch_all(&self.pool)
+            .bind(max_results as i64);
+        for param in params {
+            query = match param {
+                ParamValue::I64(val) => query.bind(val),
+                ParamValue::Text(val) => query.bind(val),
+                ParamValue::TextArray(val) => query.bind(val),
+            };
+        }
+
+        // 检索参数只对本事务生效
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(&search_parameter_sql(&self.index, filters))
+            .execute(&mut *tx)
             .await?;
+        let rows = empty_if_undefined_table(query.fetch_all(&mut *tx).await)?;
+        tx.commit().await?;
 
         let results: Vec<Chunk> = rows.iter().map(Chunk::from).collect();
 
@@ -300,33 +450,9 @@ impl Search for PgvectorRsSearch {
             FROM interfaces_v2
         "#
         .to_string();
-        let mut param_count = 2;
-
-        let mut condition_sql = vec![];
-        if let Some(condition) = filter {
-            if let Some(project_id) = &condition.project_id {
-                params.push(ParamValue::Text(project_id.to_string()));
-                let c = format!(" meta->>'project_id' = ${} ", param_count);
-                condition_sql.push(c);
-                param_count += 1;
-            }
-
-            if let Some(prefix_path) = &condition.prefix_path {
-                let mut path = prefix_path.to_string();
-                path.push_str("%");
-                params.push(ParamValue::Text(path));
-                let c = format!(" meta->>'path' LIKE ${} ", param_count);
-                condition_sql.push(c);
-                param_count += 1;
-            }
-
-            if let Some(methods) = &condition.methods {
-                params.push(ParamValue::Text(methods.join(", ")));
-                let c = format!(" meta->>'method' in (${}) ", param_count);
-                condition_sql.push(c);
-                param_count += 1;
-            }
-        }
+        let (condition_sql, filter_params) = filter_conditions(filter, 2);
+        params.extend(filter_params);
+        let param_count = params.len() + 1;
 
         if !condition_sql.is_empty() {
             sql.push_str(" WHERE ");
@@ -341,31 +467,35 @@ impl Search for PgvectorRsSearch {
             match param {
                 ParamValue::I64(val) => query = query.bind(val),
                 ParamValue::Text(val) => query = query.bind(val),
+                ParamValue::TextArray(val) => query = query.bind(val),
             }
         }
 
         // 执行查询
-        let rows = query.fetch_all(&self.pool).await?;
+        let rows = empty_if_undefined_table(query.fetch_all(&self.pool).await)?;
 
         let results = rows.iter().map(Chunk::from).collect::<Vec<Chunk>>();
 
         Ok(results)
     }
 
-    async fn hybrid_search(&self, request: InterfaceSearchRequest) -> Result<Vec<Chunk>> {
-        // 执行向量搜索，传递过滤器
-        let vector_results = self
-            .vector_search(
-                request.query.as_str(),
+    async fn hybrid_search(&self, request: InterfaceSearchRequest) -> Result<SearchResult> {
+        // 执行向量搜索，传递过滤器；短查询追加同义词
+        let vector_query = embedding_text_builder().expand_query(&request.query);
+        let (vector_results, degraded) = degrade_vector_results(
+            request.query.as_str(),
+            self.vector_search(
+                vector_query.as_str(),
                 request.max_results * 2,
-                request.similarity_threshold.unwrap_or(0.5),
+                request.similarity_threshold.unwrap_or_default(),
                 request.filters.as_ref(),
             )
-            .await?;
+            .await,
+        );
 
         let (vector_weight, _) = match &request.vector_weight {
-            None => (0.0f32, 1f32),
-            Some(vector_weight) => (*vector_weight, 1.0 - vector_weight),
+            Some(vector_weight) if !degraded => (*vector_weight, 1.0 - vector_weight),
+            _ => (0.0f32, 1f32),
         };
 
         // 执行关键词搜索，传递过滤器
@@ -415,29 +545,44 @@ impl Search for PgvectorRsSearch {
         // 限制结果数量
         results.truncate(request.max_results as usize);
 
-        Ok(results)
+        Ok(SearchResult {
+            chunks: results,
+            degraded,
+            hint: None,
+        })
     }
 
-    async fn get_project_interfaces(&self, project_id: &str) -> Result<Vec<Chunk>> {
+    async fn get_project_interfaces(
+        &self,
+        project_id: &str,
+        limit: u32,
+        offset: u32,
+    ) -> Result<Vec<Chunk>> {
         let rows = sqlx::query(
             r#"
-            SELECT * FROM interfaces_v2 WHERE meta->>'project_id' = $1 ORDER BY path, method
+            SELECT * FROM interfaces_v2 WHERE project_id = $1
+            ORDER BY path, method
+            LIMIT $2 OFFSET $3
         "#,
         )
         .bind(project_id)
+        .bind(limit as i64)
+        .bind(offset as i64)
         .fetch_all(&self.pool)
-        .await?;
+        .await;
+        let rows = empty_if_undefined_table(rows)?;
 
         let result = rows.iter().map(Chunk::from).collect::<Vec<Chunk>>();
         Ok(result)
     }
 
     async fn delete_project_data(&self, project_id: &str) -> Result<u64> {
-        let pqr = sqlx::query(r#"DELETE FROM interfaces_v2 WHERE meta->>'project_id' = $1"#)
+        let pqr = sqlx::query(r#"DELETE FROM interfaces_v2 WHERE project_id = $1"#)
             .bind(project_id)
             .execute(&self.pool)
-            .await?;
-        Ok(pqr.rows_affected())
+            .await
+            .map(|result| result.rows_affected());
+        Ok(empty_if_undefined_table(pqr)?)
     }
 
     async fn delete_by_meta(&self, meta: Meta) -> Result<()> {
@@ -447,7 +592,7 @@ impl Search for PgvectorRsSearch {
         let _pqr = sqlx::query(
             r#"
             DELETE FROM interfaces_v2
-            WHERE meta->>'project_id' = $1 and meta->>'path' = $2 and meta->>'method' = $3
+            WHERE project_id = $1 and path = $2 and method = $3
             "#,
         )
         .bind(meta.project_id)
@@ -457,4 +602,187 @@ impl Search for PgvectorRsSearch {
         .await?;
         Ok(())
     }
+
+    async fn store_endpoint_summary(&self, summary: EndpointSummaryDoc) -> Result<()> {
+        let embedding = self.embedding_service.embed_text(&summary.text).await?;
+        sqlx::query(
+            r#"
+            INSERT INTO endpoint_summaries_v1 (slug, endpoint_id, status, text, embedding, updated_at)
+            VALUES ($1, $2, $3, $4, $5, NOW())
+            ON CONFLICT (slug) DO UPDATE SET
+                endpoint_id = EXCLUDED.endpoint_id,
+                status = EXCLUDED.status,
+                text = EXCLUDED.text,
+                embedding = EXCLUDED.embedding,
+                updated_at = NOW()
+            "#,
+        )
+        .bind(summary.slug)
+        .bind(summary.endpoint_id)
+        .bind(summary.status)
+        .bind(summary.text)
+        .bind(embedding)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn delete_endpoint_summary(&self, slug: &str) -> Result<()> {
+        let result = sqlx::query(r#"DELETE FROM endpoint_summaries_v1 WHERE slug = $1"#)
+            .bind(slug)
+            .execute(&self.pool)
+            .await
+            .map(|_| ());
+        Ok(empty_if_undefined_table(result)?)
+    }
+
+    async fn search_endpoints(
+        &self,
+        query: &str,
+        max_results: u32,
+        only_running: bool,
+    ) -> Result<Vec<EndpointMatch>> {
+        let query_embedding = self.embedding_service.embed_text(query).await?;
+        let rows = sqlx::query(
+            r#"
+            SELECT slug, endpoint_id, status, text, (1 - (embedding <=> $1))::float8 AS score
+            FROM endpoint_summaries_v1
+            WHERE NOT $3 OR status = 'running'
+            ORDER BY embedding <=> $1
+            LIMIT $2
+            "#,
+        )
+        .bind(query_embedding)
+        .bind(max_results as i64)
+        .bind(only_running)
+        .fetch_all(&self.pool)
+        .await;
+        let rows = empty_if_undefined_table(rows)?;
+        Ok(rows
+            .iter()
+            .map(|row| EndpointMatch {
+                endpoint_id: row.get("endpoint_id"),
+                slug: row.get("slug"),
+                status: row.get("status"),
+                summary: row.get("text"),
+                score: row.get("score"),
+            })
+            .collect())
+    }
+
+    async fn document_count(&self) -> Result<u64> {
+        let count: i64 = empty_if_undefined_table(
+            sqlx::query_scalar("SELECT COUNT(*) FROM interfaces_v2")
+                .fetch_one(&self.pool)
+                .await,
+        )?;
+        Ok(count as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter() -> Filter {
+        Filter {
+            project_id: Some("p1".to_string()),
+            prefix_path: Some("/api/user_%".to_string()),
+            methods: Some(vec!["GET".to_string(), "POST".to_string()]),
+            ef_search: None,
+            probes: None,
+        }
+    }
+
+    #[test]
+    fn test_check_extension_version() {
+        assert!(check_extension_version(Some("0.2.1")).is_ok());
+        assert!(check_extension_version(Some("0.3.0")).is_ok());
+        let error = check_extension_version(Some("0.1.11")).unwrap_err();
+        assert!(error.to_string().contains("0.2 or later"), "{}", error);
+        let error = check_extension_version(None).unwrap_err();
+        assert!(error.to_string().contains("not installed"), "{}", error);
+    }
+
+    #[test]
+    fn test_check_vector_dims() {
+        assert!(check_vector_dims("interfaces_v2", Some("vector(1024)"), 1024).is_ok());
+        let error = check_vector_dims("interfaces_v2", Some("vector(768)"), 1024).unwrap_err();
+        assert!(error.to_string().contains("vector dims 768"), "{}", error);
+        assert!(check_vector_dims("interfaces_v2", None, 1024).is_err());
+    }
+
+    #[test]
+    fn test_vector_index_sql() {
+        let mut index = PgvectorIndexConfig {
+            m: 16,
+            ef_construction: 200,
+            ..Default::default()
+        };
+        let sql = vector_index_sql(&index);
+        assert!(sql.contains("[indexing.hnsw]"), "{}", sql);
+        assert!(sql.contains("m = 16") && sql.contains("ef_construction = 200"));
+
+        index.index_type = PgvectorIndexType::Ivf;
+        index.lists = 256;
+        let sql = vector_index_sql(&index);
+        assert!(
+            sql.contains("[indexing.ivf]") && sql.contains("nlist = 256"),
+            "{}",
+            sql
+        );
+        assert!(!sql.contains("hnsw"));
+    }
+
+    #[test]
+    fn test_search_parameter_sql() {
+        let mut index = PgvectorIndexConfig::default();
+        assert_eq!(
+            search_parameter_sql(&index, None),
+            "SET LOCAL vectors.hnsw_ef_search = 100"
+        );
+        // 请求中的 ef_search 覆盖配置
+        let mut request = filter();
+        request.ef_search = Some(40);
+        assert_eq!(
+            search_parameter_sql(&index, Some(&request)),
+            "SET LOCAL vectors.hnsw_ef_search = 40"
+        );
+
+        index.index_type = PgvectorIndexType::Ivf;
+        assert_eq!(
+            search_parameter_sql(&index, Some(&request)),
+            "SET LOCAL vectors.ivf_nprobe = 10"
+        );
+        request.probes = Some(0);
+        assert_eq!(
+            search_parameter_sql(&index, Some(&request)),
+            "SET LOCAL vectors.ivf_nprobe = 1"
+        );
+    }
+
+    #[test]
+    fn test_filter_conditions() {
+        let (conditions, params) = filter_conditions(Some(&filter()), 3);
+        assert_eq!(
+            conditions,
+            vec!["project_id = $3", "path LIKE $4", "method = ANY($5)"]
+        );
+        assert_eq!(
+            params,
+            vec![
+                ParamValue::Text("p1".to_string()),
+                ParamValue::Text("/api/user\\_\\%%".to_string()),
+                ParamValue::TextArray(vec!["GET".to_string(), "POST".to_string()]),
+            ]
+        );
+
+        let (conditions, params) = filter_conditions(None, 2);
+        assert!(conditions.is_empty() && params.is_empty());
+        let only_methods = Filter {
+            methods: Some(vec![]),
+            ..Default::default()
+        };
+        assert!(filter_conditions(Some(&only_methods), 2).0.is_empty());
+    }
 }
@@ -0,0 +1,289 @@
+use crate::models::{DbPool, EndpointPlugin, PluginHook, TestPluginRequest, TestPluginResponse};
+use crate::services::FileService;
+use crate::utils::{get_china_time, plugin_runtime, CompiledPlugin, PluginError, PluginRun};
+use anyhow::{anyhow, Result};
+use dashmap::DashMap;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use sqlx::Row;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use uuid::Uuid;
+
+/// 已编译的端点插件，启动时加载，上传或删除后立即生效
+static ENDPOINT_PLUGINS: OnceLock<DashMap<Uuid, Arc<LoadedPlugin>>> = OnceLock::new();
+
+pub fn endpoint_plugins() -> &'static DashMap<Uuid, Arc<LoadedPlugin>> {
+    ENDPOINT_PLUGINS.get_or_init(DashMap::new)
+}
+
+pub struct LoadedPlugin {
+    pub meta: EndpointPlugin,
+    pub compiled: CompiledPlugin,
+}
+
+/// 在阻塞线程中执行钩子，不占用异步运行时
+pub async fn run_plugin_hook(
+    plugin: Arc<LoadedPlugin>,
+    hook: PluginHook,
+    input: Value,
+) -> Result<PluginRun, PluginError> {
+    tokio::task::spawn_blocking(move || plugin_runtime().invoke(&plugin.compiled, hook, &input))
+        .await
+        .unwrap_or_else(|e| Err(PluginError::Trap(e.to_string())))
+}
+
+/// 执行钩子并取出输出中的 field；模块未导出该钩子时原样返回，
+/// trap、超限或输出无效时记录日志并透传 original，错误追加到 errors
+pub async fn transform_or_pass_through(
+    plugin: &Arc<LoadedPlugin>,
+    hook: PluginHook,
+    envelope: Value,
+    field: &str,
+    original: &Value,
+    errors: &mut Vec<String>,
+) -> Value {
+    if !plugin.compiled.has_hook(hook) {
+        return original.clone();
+    }
+    let result = run_plugin_hook(plugin.clone(), hook, envelope)
+        .await
+        .and_then(|run| match run.output.get(field) {
+            Some(value) => Ok(value.clone()),
+            None => Err(PluginError::InvalidOutput(format!("missing '{}'", field))),
+        });
+    match result {
+        Ok(value) => value,
+        Err(e) => {
+            tracing::warn!(
+                "Plugin v{} of endpoint {} failed in {}, passing through: {}",
+                plugin.meta.version,
+                plugin.meta.endpoint_id,
+                hook,
+                e
+            );
+            errors.push(format!("{}: {}", hook, e));
+            original.clone()
+        }
+    }
+}
+
+/// 记录经插件处理的调用，附带模块版本与哈希；插件失败透传时错误写入 error_message
+#[allow(clippy::too_many_arguments)]
+pub async fn log_plugin_call<'e, E>(
+    executor: E,
+    plugin: &EndpointPlugin,
+    method: &str,
+    path: &str,
+    status: u16,
+    latency: Duration,
+    request_body: &Value,
+    response_body: &Value,
+    errors: &[String],
+) -> Result<()>
+where
+    E: sqlx::Executor<'e, Database = sqlx::MySql>,
+{
+    sqlx::query(
+        "INSERT INTO endpoint_logs (id, endpoint_id, request_id, method, path, status_code, response_time_ms, request_body, response_body, error_message, plugin_version, plugin_hash) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(Uuid::new_v4().to_string())
+    .bind(plugin.endpoint_id.to_string())
+    .bind(Uuid::new_v4().to_string())
+    .bind(method)
+    .bind(path)
+    .bind(i32::from(status))
+    .bind(latency.as_millis() as i64)
+    .bind(request_body.to_string())
+    .bind(response_body.to_string())
+    .bind((!errors.is_empty()).then(|| errors.join("; ")))
+    .bind(plugin.version)
+    .bind(&plugin.sha256)
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+pub struct PluginService {
+    pool: DbPool,
+    file_service: Arc<FileService>,
+}
+
+impl PluginService {
+    pub fn new(pool: DbPool, file_service: Arc<FileService>) -> Self {
+        Self { pool, file_service }
+    }
+
+    /// 启动时加载并编译全部插件，单个插件失败不影响其他端点
+    pub async fn load_all(&self) -> Result<usize> {
+        let rows = sqlx::query(&format!("{} FROM endpoint_plugins p", SELECT_PLUGIN))
+            .fetch_all(&self.pool)
+            .await?;
+        endpoint_plugins().clear();
+        let mut loaded = 0;
+        for row in &rows {
+            let (meta, path) = plugin_from_row(row)?;
+            let compiled = self
+                .file_service
+                .read_by_path(&path)
+                .await
+                .and_then(|bytes| Ok(plugin_runtime().compile(&bytes)?));
+            match compiled {
+                Ok(compiled) => {
+                    endpoint_plugins()
+                        .insert(meta.endpoint_id, Arc::new(LoadedPlugin { meta, compiled }));
+                    loaded += 1;
+                }
+                Err(e) => tracing::warn!(
+                    "Failed to load plugin of endpoint {}: {}",
+                    meta.endpoint_id,
+                    e
+                ),
+            }
+        }
+        Ok(loaded)
+    }
+
+    pub async fn get(&self, endpoint_id: Uuid) -> Result<Option<EndpointPlugin>> {
+        let row = sqlx::query(&format!(
+            "{} FROM endpoint_plugins p WHERE p.endpoint_id = ?",
+            SELECT_PLUGIN
+        ))
+        .bind(endpoint_id.to_string())
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row
+            .as_ref()
+            .map(plugin_from_row)
+            .transpose()?
+            .map(|(meta, _)| meta))
+    }
+
+    /// 上传新版本：校验通过后经 FileService 存储，版本号递增，旧模块文件删除
+    pub async fn upload(&self, endpoint_id: Uuid, bytes: Vec<u8>) -> Result<EndpointPlugin> {
+        let exists: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM endpoints WHERE id = ?")
+            .bind(endpoint_id.to_string())
+            .fetch_one(&self.pool)
+            .await?;
+        if exists == 0 {
+            return Err(anyhow!("Endpoint {} not found", endpoint_id));
+        }
+        let compiled = plugin_runtime().compile(&bytes)?;
+        let sha256 = hex::encode(Sha256::digest(&bytes));
+        let size = bytes.len() as i64;
+        let previous = self.get(endpoint_id).await?;
+
+        let file = self
+            .file_service
+            .upload_and_save(&format!("plugin-{}.wasm", endpoint_id), bytes)
+            .await?;
+        let version = previous.as_ref().map(|p| p.version + 1).unwrap_or(1);
+        sqlx::query(
+            "INSERT INTO endpoint_plugins (endpoint_id, file_id, version, sha256, size, transform_request, transform_response, updated_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?) ON DUPLICATE KEY UPDATE file_id = VALUES(file_id), version = VALUES(version), sha256 = VALUES(sha256), size = VALUES(size), transform_request = VALUES(transform_request), transform_response = VALUES(transform_response), updated_at = VALUES(updated_at)",
+        )
+        .bind(endpoint_id.to_string())
+        .bind(file.id.to_string())
+        .bind(version)
+        .bind(&sha256)
+        .bind(size)
+        .bind(compiled.transform_request)
+        .bind(compiled.transform_response)
+        .bind(get_china_time())
+        .execute(&self.pool)
+        .await?;
+
+        let meta = self
+            .get(endpoint_id)
+            .await?
+            .ok_or_else(|| anyhow!("Plugin for endpoint {} not found", endpoint_id))?;
+        endpoint_plugins().insert(
+            endpoint_id,
+            Arc::new(LoadedPlugin {
+                meta: meta.clone(),
+                compiled,
+            }),
+        );
+        if let Some(previous) = previous {
+            self.delete_module_file(previous.file_id).await;
+        }
+        Ok(meta)
+    }
+
+    /// 删除插件，之后的调用不再经过插件
+    pub async fn delete(&self, endpoint_id: Uuid) -> Result<Option<EndpointPlugin>> {
+        let plugin = self.get(endpoint_id).await?;
+        sqlx::query("DELETE FROM endpoint_plugins WHERE endpoint_id = ?")
+            .bind(endpoint_id.to_string())
+            .execute(&self.pool)
+            .await?;
+        endpoint_plugins().remove(&endpoint_id);
+        if let Some(plugin) = &plugin {
+            self.delete_module_file(plugin.file_id).await;
+        }
+        Ok(plugin)
+    }
+
+    /// 用样例数据试运行当前插件，输入输出即钩子收到与返回的 JSON，不会透传
+    pub async fn test_invoke(
+        &self,
+        endpoint_id: Uuid,
+        request: TestPluginRequest,
+    ) -> Result<TestPluginResponse> {
+        let plugin = endpoint_plugins()
+            .get(&endpoint_id)
+            .map(|p| p.clone())
+            .ok_or_else(|| anyhow!("Plugin for endpoint {} not found", endpoint_id))?;
+        let run = run_plugin_hook(plugin.clone(), request.hook, request.payload).await?;
+        Ok(TestPluginResponse {
+            version: plugin.meta.version,
+            sha256: plugin.meta.sha256.clone(),
+            output: run.output,
+            fuel_consumed: run.fuel_consumed,
+            elapsed_ms: run.elapsed.as_millis() as u64,
+        })
+    }
+
+    async fn delete_module_file(&self, file_id: Uuid) {
+        if let Err(e) = self.file_service.delete_file(file_id).await {
+            tracing::warn!("Failed to delete plugin module file {}: {}", file_id, e);
+        }
+    }
+}
+
+/// 插件的请求钩子输入：工具名、方法、路径与参数，输出取 arguments
+pub fn request_envelope(tool: &str, method: &str, path: &str, arguments: &Value) -> Value {
+    json!({
+        "tool": tool,
+        "method": method,
+        "path": path,
+        "arguments": arguments,
+    })
+}
+
+/// 插件的响应钩子输入：工具名、上游状态码与响应，输出取 response
+pub fn response_envelope(tool: &str, status: u16, response: &Value) -> Value {
+    json!({
+        "tool": tool,
+        "status": status,
+        "response": response,
+    })
+}
+
+const SELECT_PLUGIN: &str = "SELECT p.endpoint_id, p.file_id, p.version, p.sha256, p.size, p.transform_request, p.transform_response, p.updated_at, (SELECT f.path FROM t_file f WHERE f.id = p.file_id) AS path";
+
+fn plugin_from_row(row: &sqlx::mysql::MySqlRow) -> Result<(EndpointPlugin, String)> {
+    let endpoint_id: String = row.try_get("endpoint_id")?;
+    let file_id: String = row.try_get("file_id")?;
+    let path: Option<String> = row.try_get("path")?;
+    let meta = EndpointPlugin {
+        endpoint_id: Uuid::parse_str(&endpoint_id)?,
+        file_id: Uuid::parse_str(&file_id)?,
+        version: row.try_get("version")?,
+        sha256: row.try_get("sha256")?,
+        size: row.try_get("size")?,
+        transform_request: row.try_get("transform_request")?,
+        transform_response: row.try_get("transform_response")?,
+        updated_at: row.try_get("updated_at")?,
+    };
+    Ok((meta, path.unwrap_or_default()))
+}
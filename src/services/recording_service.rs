@@ -0,0 +1,706 @@
+use crate::config::RecordingConfig;
+use crate::models::{
+    DbPool, Endpoint, RecordedCall, SwaggerSpec, SynthesizedOperation, SynthesizedSpecResponse,
+};
+use crate::utils::generate_mcp_tools;
+use anyhow::{anyhow, Result};
+use rmcp::model::Tool;
+use serde_json::{json, Map, Value};
+use sqlx::Row;
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::{Arc, OnceLock};
+use uuid::Uuid;
+
+/// 录制模式端点暴露的唯一工具
+pub const HTTP_REQUEST_TOOL: &str = "http_request";
+
+/// swagger 顶层扩展字段，标记端点处于录制模式
+pub const RECORDING_EXTENSION: &str = "x-mcp-recording";
+
+/// 单次合成最多读取的录制条数（取最新）
+pub const MAX_SYNTHESIS_RECORDINGS: i64 = 5000;
+
+const RECORDED_METHODS: [&str; 5] = ["GET", "POST", "PUT", "PATCH", "DELETE"];
+
+/// 全局录制配置，启动时设置
+pub static RECORDING_CONFIG: OnceLock<RecordingConfig> = OnceLock::new();
+
+pub fn recording_config() -> &'static RecordingConfig {
+    RECORDING_CONFIG.get_or_init(RecordingConfig::default)
+}
+
+/// 录制模式端点的占位 swagger：只有 servers，没有 paths
+pub fn recording_spec(name: &str, base_url: &str) -> Result<Value> {
+    let base_url = base_url.trim().trim_end_matches('/');
+    let parsed = reqwest::Url::parse(base_url)
+        .map_err(|e| anyhow!("Invalid base_url '{}': {}", base_url, e))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(anyhow!("Invalid base_url '{}': must be http(s)", base_url));
+    }
+    Ok(json!({
+        "openapi": "3.0.0",
+        "info": {
+            "title": name,
+            "version": "recording",
+            "description": "Recording endpoint, calls are proxied through the http_request tool"
+        },
+        "servers": [{"url": base_url}],
+        "paths": {},
+        RECORDING_EXTENSION: true
+    }))
+}
+
+pub fn is_recording_spec(spec: &Value) -> bool {
+    spec.get(RECORDING_EXTENSION)
+        .and_then(Value::as_bool)
+        .unwrap_or(false)
+}
+
+/// 通用转发工具，path 相对于端点 base URL
+pub fn http_request_tool() -> Tool {
+    let schema = json!({
+        "type": "object",
+        "properties": {
+            "method": {"type": "string", "enum": RECORDED_METHODS},
+            "path": {
+                "type": "string",
+                "pattern": "^/",
+                "description": "Path relative to the endpoint base URL, e.g. /users/42"
+            },
+            "query": {"type": "object", "description": "Query parameters"},
+            "headers": {"type": "object", "description": "Request headers"},
+            "body": {"description": "JSON request body"}
+        },
+        "required": ["method", "path"]
+    });
+    Tool {
+        name: HTTP_REQUEST_TOOL.into(),
+        description: Some(
+            "Send an HTTP request to the upstream service. Calls are recorded to learn its API."
+                .into(),
+        ),
+        input_schema: Arc::new(schema.as_object().cloned().unwrap_or_default()),
+        output_schema: None,
+        annotations: None,
+    }
+}
+
+/// 按采样率决定是否记录本次调用
+pub fn should_record(sample_rate: f64) -> bool {
+    if sample_rate >= 1.0 {
+        return true;
+    }
+    if sample_rate <= 0.0 {
+        return false;
+    }
+    let roll = (Uuid::new_v4().as_u128() % 1_000_000) as f64 / 1_000_000.0;
+    roll < sample_rate
+}
+
+/// 序列化后超过上限的 body 不记录
+pub fn cap_body(body: Option<Value>, max_bytes: usize) -> Option<Value> {
+    body.filter(|value| value.to_string().len() <= max_bytes)
+}
+
+pub async fn record_call(pool: &DbPool, endpoint_id: Uuid, call: &RecordedCall) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO endpoint_recordings (id, endpoint_id, method, path, query_params, request_body, status_code, response_body) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(Uuid::new_v4().to_string())
+    .bind(endpoint_id.to_string())
+    .bind(&call.method)
+    .bind(&call.path)
+    .bind(Value::Object(call.query.clone()).to_string())
+    .bind(call.request_body.as_ref().map(Value::to_string))
+    .bind(call.status)
+    .bind(call.response_body.as_ref().map(Value::to_string))
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// 路径参数：纯数字段为 integer，UUID 段为 string/uuid
+#[derive(Debug, Clone, PartialEq)]
+pub struct PathParameter {
+    pub name: String,
+    pub schema: Value,
+}
+
+/// 将实际路径归一为模板，参数名取前一个静态段的单数形式加 Id
+pub fn path_template(path: &str) -> (String, Vec<PathParameter>) {
+    let path = path.split('?').next().unwrap_or_default();
+    let mut template = String::new();
+    let mut parameters: Vec<PathParameter> = Vec::new();
+    let mut previous: Option<&str> = None;
+
+    for segment in path.split('/').filter(|s| !s.is_empty()) {
+        let schema = if !segment.is_empty() && segment.chars().all(|c| c.is_ascii_digit()) {
+            Some(json!({"type": "integer"}))
+        } else if Uuid::parse_str(segment).is_ok() {
+            Some(json!({"type": "string", "format": "uuid"}))
+        } else {
+            None
+        };
+        match schema {
+            Some(schema) => {
+                let base = match previous {
+                    Some(prev) => {
+                        let singular = prev.strip_suffix('s').filter(|s| !s.is_empty());
+                        format!("{}Id", camel_case(singular.unwrap_or(prev), false))
+                    }
+                    None => "id".to_string(),
+                };
+                let mut name = base.clone();
+                let mut suffix = 2;
+                while parameters.iter().any(|p| p.name == name) {
+                    name = format!("{}{}", base, suffix);
+                    suffix += 1;
+                }
+                template.push_str(&format!("/{{{}}}", name));
+                parameters.push(PathParameter { name, schema });
+                previous = None;
+            }
+            None => {
+                template.push('/');
+                template.push_str(segment);
+                previous = Some(segment);
+            }
+        }
+    }
+    if template.is_empty() {
+        template.push('/');
+    }
+    (template, parameters)
+}
+
+fn camel_case(text: &str, capitalize_first: bool) -> String {
+    let mut out = String::new();
+    let mut upper = capitalize_first;
+    for c in text.chars() {
+        if c.is_ascii_alphanumeric() {
+            if upper {
+                out.push(c.to_ascii_uppercase());
+            } else {
+                out.push(c);
+            }
+            upper = false;
+        } else {
+            upper = !out.is_empty() || capitalize_first;
+        }
+    }
+    out
+}
+
+/// 由单个 JSON 值推断 schema，对象的全部字段视为必填，合并时再放宽
+pub fn infer_schema(value: &Value) -> Value {
+    match value {
+        Value::Null => json!({"nullable": true}),
+        Value::Bool(_) => json!({"type": "boolean"}),
+        Value::Number(n) if n.is_i64() || n.is_u64() => json!({"type": "integer"}),
+        Value::Number(_) => json!({"type": "number"}),
+        Value::String(s) if Uuid::parse_str(s).is_ok() => {
+            json!({"type": "string", "format": "uuid"})
+        }
+        Value::String(_) => json!({"type": "string"}),
+        Value::Array(items) => {
+            let items = items
+                .iter()
+                .map(infer_schema)
+                .reduce(|a, b| merge_schemas(&a, &b))
+                .unwrap_or_else(|| json!({}));
+            json!({"type": "array", "items": items})
+        }
+        Value::Object(fields) => {
+            let properties: Map<String, Value> = fields
+                .iter()
+                .map(|(key, value)| (key.clone(), infer_schema(value)))
+                .collect();
+            let required: Vec<&String> = fields.keys().collect();
+            json!({"type": "object", "properties": properties, "required": required})
+        }
+    }
+}
+
+/// 合并两个观察到的 schema：字段取并集，必填取交集；null 使字段可空；
+/// integer 与 number 合并为 number，其他类型冲突时放宽为任意类型
+pub fn merge_schemas(a: &Value, b: &Value) -> Value {
+    let type_a = a.get("type").and_then(Value::as_str);
+    let type_b = b.get("type").and_then(Value::as_str);
+    let nullable = is_nullable(a) || is_nullable(b);
+
+    let mut merged = match (type_a, type_b) {
+        (None, None) => json!({}),
+        (Some(_), None) if is_null_only(b) => a.clone(),
+        (None, Some(_)) if is_null_only(a) => b.clone(),
+        (Some("object"), Some("object")) => merge_objects(a, b),
+        (Some("array"), Some("array")) => {
+            // 空数组推断不出元素类型，以另一侧为准
+            let items = match (&a["items"], &b["items"]) {
+                (items, empty) | (empty, items) if empty == &json!({}) => items.clone(),
+                (x, y) => merge_schemas(x, y),
+            };
+            json!({"type": "array", "items": items})
+        }
+        (Some("integer"), Some("number")) | (Some("number"), Some("integer")) => {
+            json!({"type": "number"})
+        }
+        (Some(x), Some(y)) if x == y => {
+            let mut merged = a.clone();
+            if a.get("format") != b.get("format") {
+                if let Some(fields) = merged.as_object_mut() {
+                    fields.remove("format");
+                }
+            }
+            merged
+        }
+        _ => json!({}),
+    };
+    if let Some(fields) = merged.as_object_mut() {
+        fields.remove("nullable");
+        if nullable {
+            fields.insert("nullable".to_string(), Value::Bool(true));
+        }
+    }
+    merged
+}
+
+fn is_nullable(schema: &Value) -> bool {
+    schema.get("nullable").and_then(Value::as_bool) == Some(true)
+}
+
+fn is_null_only(schema: &Value) -> bool {
+    schema.get("type").is_none() && is_nullable(schema)
+}
+
+fn merge_objects(a: &Value, b: &Value) -> Value {
+    let empty = Map::new();
+    let props_a = a["properties"].as_object().unwrap_or(&empty);
+    let props_b = b["properties"].as_object().unwrap_or(&empty);
+
+    let mut properties = props_a.clone();
+    for (key, schema_b) in props_b {
+        let merged = match properties.get(key) {
+            Some(schema_a) => merge_schemas(schema_a, schema_b),
+            None => schema_b.clone(),
+        };
+        properties.insert(key.clone(), merged);
+    }
+
+    let required_of = |schema: &Value| -> BTreeSet<String> {
+        schema["required"]
+            .as_array()
+            .map(|r| {
+                r.iter()
+                    .filter_map(Value::as_str)
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+    let required: Vec<String> = required_of(a)
+        .intersection(&required_of(b))
+        .cloned()
+        .collect();
+
+    let mut merged = json!({"type": "object", "properties": properties});
+    if !required.is_empty() {
+        merged["required"] = json!(required);
+    }
+    merged
+}
+
+/// 查询参数值都按字符串记录，推断时识别整数与布尔
+fn infer_query_schema(values: &[&Value]) -> Value {
+    let texts: Vec<String> = values
+        .iter()
+        .map(|v| match v {
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        })
+        .collect();
+    if !texts.is_empty() && texts.iter().all(|t| t.parse::<i64>().is_ok()) {
+        json!({"type": "integer"})
+    } else if !texts.is_empty() && texts.iter().all(|t| t == "true" || t == "false") {
+        json!({"type": "boolean"})
+    } else {
+        json!({"type": "string"})
+    }
+}
+
+fn operation_id(method: &str, template: &str) -> String {
+    let mut id = method.to_ascii_lowercase();
+    for segment in template.split('/').filter(|s| !s.is_empty()) {
+        match segment.strip_prefix('{').and_then(|s| s.strip_suffix('}')) {
+            Some(param) => {
+                id.push_str("By");
+                id.push_str(&camel_case(param, true));
+            }
+            None => id.push_str(&camel_case(segment, true)),
+        }
+    }
+    id
+}
+
+/// 按 method + 路径模板聚类录制，合并出请求/响应 schema，生成 OpenAPI 3.0 草稿
+pub fn synthesize_spec(
+    title: &str,
+    base_url: &str,
+    recordings: &[RecordedCall],
+) -> (Value, Vec<SynthesizedOperation>) {
+    let mut clusters: BTreeMap<(String, String), (Vec<PathParameter>, Vec<&RecordedCall>)> =
+        BTreeMap::new();
+    for call in recordings {
+        let method = call.method.to_ascii_uppercase();
+        if !RECORDED_METHODS.contains(&method.as_str()) {
+            continue;
+        }
+        let (template, parameters) = path_template(&call.path);
+        clusters
+            .entry((template, method))
+            .or_insert_with(|| (parameters, Vec::new()))
+            .1
+            .push(call);
+    }
+
+    let mut paths = Map::new();
+    let mut operations = Vec::new();
+    let mut used_ids = BTreeSet::new();
+    for ((template, method), (path_parameters, calls)) in clusters {
+        let mut id = operation_id(&method, &template);
+        let mut suffix = 2;
+        while !used_ids.insert(id.clone()) {
+            id = format!("{}{}", operation_id(&method, &template), suffix);
+            suffix += 1;
+        }
+
+        let mut parameters: Vec<Value> = path_parameters
+            .iter()
+            .map(|p| json!({"name": p.name, "in": "path", "required": true, "schema": p.schema}))
+            .collect();
+        let query_keys: BTreeSet<&String> = calls.iter().flat_map(|c| c.query.keys()).collect();
+        for key in query_keys {
+            let values: Vec<&Value> = calls.iter().filter_map(|c| c.query.get(key)).collect();
+            parameters.push(json!({
+                "name": key,
+                "in": "query",
+                "required": values.len() == calls.len(),
+                "schema": infer_query_schema(&values)
+            }));
+        }
+
+        let mut operation = json!({
+            "operationId": id,
+            "summary": format!("{} {}", method, template),
+            "description": format!("Synthesized from {} recorded calls", calls.len()),
+            "parameters": parameters,
+        });
+
+        let bodies: Vec<&Value> = calls
+            .iter()
+            .filter_map(|c| c.request_body.as_ref())
+            .collect();
+        if let Some(schema) = bodies
+            .iter()
+            .map(|b| infer_schema(b))
+            .reduce(|a, b| merge_schemas(&a, &b))
+        {
+            operation["requestBody"] = json!({
+                "required": bodies.len() == calls.len(),
+                "content": {"application/json": {"schema": schema}}
+            });
+        }
+
+        let mut by_status: BTreeMap<u16, Vec<&Value>> = BTreeMap::new();
+        for call in &calls {
+            let entry = by_status.entry(call.status).or_default();
+            if let Some(body) = &call.response_body {
+                entry.push(body);
+            }
+        }
+        let mut responses = Map::new();
+        for (status, bodies) in by_status {
+            let mut response = json!({"description": format!("Observed {} response", status)});
+            if let Some(schema) = bodies
+                .iter()
+                .map(|b| infer_schema(b))
+                .reduce(|a, b| merge_schemas(&a, &b))
+            {
+                response["content"] = json!({"application/json": {"schema": schema}});
+            }
+            responses.insert(status.to_string(), response);
+        }
+        operation["responses"] = Value::Object(responses);
+
+        let path_item = paths
+            .entry(template.clone())
+            .or_insert_with(|| Value::Object(Map::new()));
+        path_item[method.to_ascii_lowercase()] = operation;
+        operations.push(SynthesizedOperation {
+            method,
+            path: template,
+            operation_id: id,
+            samples: calls.len(),
+        });
+    }
+
+    let draft = json!({
+        "openapi": "3.0.0",
+        "info": {
+            "title": title,
+            "version": "draft",
+            "description": format!("Synthesized from {} recorded calls", recordings.len())
+        },
+        "servers": [{"url": base_url}],
+        "paths": paths
+    });
+    (draft, operations)
+}
+
+pub struct RecordingService {
+    pool: DbPool,
+}
+
+impl RecordingService {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    /// 最新的录制，按时间正序返回
+    pub async fn list_recordings(
+        &self,
+        endpoint_id: Uuid,
+        limit: i64,
+    ) -> Result<Vec<RecordedCall>> {
+        let rows = sqlx::query(
+            "SELECT method, path, query_params, request_body, status_code, response_body FROM endpoint_recordings WHERE endpoint_id = ? ORDER BY recorded_at DESC LIMIT ?",
+        )
+        .bind(endpoint_id.to_string())
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let parse = |text: Option<String>| text.and_then(|t| serde_json::from_str(&t).ok());
+        let mut recordings = rows
+            .into_iter()
+            .map(|row| {
+                let query: Option<Value> = parse(row.try_get("query_params")?);
+                Ok(RecordedCall {
+                    method: row.try_get("method")?,
+                    path: row.try_get("path")?,
+                    query: match query {
+                        Some(Value::Object(query)) => query,
+                        _ => Map::new(),
+                    },
+                    request_body: parse(row.try_get("request_body")?),
+                    status: row.try_get("status_code")?,
+                    response_body: parse(row.try_get("response_body")?),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        recordings.reverse();
+        Ok(recordings)
+    }
+
+    /// 由录制合成 OpenAPI 草稿，并确认草稿能正常生成工具
+    pub async fn synthesize(&self, endpoint: &Endpoint) -> Result<SynthesizedSpecResponse> {
+        let current: Value = serde_json::from_str(&endpoint.swagger_content)?;
+        if !is_recording_spec(&current) {
+            return Err(anyhow!(
+                "Invalid request: endpoint {} is not in recording mode",
+                endpoint.name
+            ));
+        }
+        let base_url = current["servers"][0]["url"].as_str().unwrap_or_default();
+
+        let recordings = self
+            .list_recordings(endpoint.id, MAX_SYNTHESIS_RECORDINGS)
+            .await?;
+        if recordings.is_empty() {
+            return Err(anyhow!(
+                "Invalid request: no recordings for endpoint {}",
+                endpoint.name
+            ));
+        }
+
+        let (draft, operations) = synthesize_spec(&endpoint.name, base_url, &recordings);
+        let spec: SwaggerSpec = serde_json::from_value(draft.clone())?;
+        let tools = generate_mcp_tools(&spec)?
+            .into_iter()
+            .map(|tool| tool.name)
+            .collect();
+        Ok(SynthesizedSpecResponse {
+            endpoint_id: endpoint.id,
+            recording_count: recordings.len(),
+            operations,
+            tools,
+            draft,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(
+        method: &str,
+        path: &str,
+        query: Value,
+        request_body: Option<Value>,
+        status: u16,
+        response_body: Option<Value>,
+    ) -> RecordedCall {
+        RecordedCall {
+            method: method.to_string(),
+            path: path.to_string(),
+            query: query.as_object().cloned().unwrap_or_default(),
+            request_body,
+            status,
+            response_body,
+        }
+    }
+
+    fn recordings() -> Vec<RecordedCall> {
+        vec![
+            call(
+                "GET",
+                "/users/1",
+                json!({"verbose": "true"}),
+                None,
+                200,
+                Some(json!({"id": 1, "name": "ann", "email": "ann@example.com"})),
+            ),
+            call(
+                "GET",
+                "/users/42",
+                json!({}),
+                None,
+                200,
+                Some(json!({"id": 42, "name": "bob", "email": null})),
+            ),
+            call(
+                "GET",
+                "/users/7",
+                json!({}),
+                None,
+                404,
+                Some(json!({"error": "not found"})),
+            ),
+            call(
+                "POST",
+                "/orders/3f2b8c1e-9d4a-4e6b-8f0a-1c2d3e4f5a6b/items",
+                json!({}),
+                Some(json!({"sku": "A-1", "quantity": 1})),
+                201,
+                Some(json!({"ok": true})),
+            ),
+            call(
+                "POST",
+                "/orders/7d9e0f1a-2b3c-4d5e-8f6a-7b8c9d0e1f2a/items",
+                json!({}),
+                Some(json!({"sku": "B-2", "quantity": 2.5, "note": "gift"})),
+                201,
+                Some(json!({"ok": true})),
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_path_template_detects_parameters() {
+        let (template, params) = path_template("/users/42/posts/7");
+        assert_eq!(template, "/users/{userId}/posts/{postId}");
+        assert_eq!(params[0].schema, json!({"type": "integer"}));
+
+        let (template, params) = path_template("/orders/3f2b8c1e-9d4a-4e6b-8f0a-1c2d3e4f5a6b");
+        assert_eq!(template, "/orders/{orderId}");
+        assert_eq!(params[0].schema["format"], "uuid");
+
+        assert_eq!(path_template("/health").0, "/health");
+        assert_eq!(path_template("/1/2").0, "/{id}/{id2}");
+    }
+
+    #[test]
+    fn test_synthesize_spec_from_recordings() -> anyhow::Result<()> {
+        let (draft, operations) = synthesize_spec("legacy", "http://legacy.local", &recordings());
+
+        // 两个路径模板，各一个操作
+        let paths = draft["paths"].as_object().unwrap();
+        assert_eq!(
+            paths.keys().collect::<Vec<_>>(),
+            vec!["/orders/{orderId}/items", "/users/{userId}"]
+        );
+        assert_eq!(operations.len(), 2);
+        let get_user = operations.iter().find(|o| o.method == "GET").unwrap();
+        assert_eq!(get_user.samples, 3);
+        assert_eq!(get_user.operation_id, "getUsersByUserId");
+
+        let get = &draft["paths"]["/users/{userId}"]["get"];
+        let params = get["parameters"].as_array().unwrap();
+        assert_eq!(
+            params[0],
+            json!({"name": "userId", "in": "path", "required": true, "schema": {"type": "integer"}})
+        );
+        // 仅部分调用携带的查询参数为可选
+        assert_eq!(params[1]["name"], "verbose");
+        assert_eq!(params[1]["required"], false);
+        assert_eq!(params[1]["schema"]["type"], "boolean");
+
+        // email 出现过 null，合并后可空；三个字段在每次响应中都出现
+        let user = &get["responses"]["200"]["content"]["application/json"]["schema"];
+        assert_eq!(user["properties"]["id"]["type"], "integer");
+        assert_eq!(user["properties"]["email"]["type"], "string");
+        assert_eq!(user["properties"]["email"]["nullable"], true);
+        assert_eq!(user["required"], json!(["email", "id", "name"]));
+        assert!(get["responses"]["404"]["content"].is_object());
+
+        let post = &draft["paths"]["/orders/{orderId}/items"]["post"];
+        assert_eq!(post["parameters"][0]["schema"]["format"], "uuid");
+        let body = &post["requestBody"]["content"]["application/json"]["schema"];
+        // note 只出现一次，为可选字段；integer 与 number 合并为 number
+        assert_eq!(body["required"], json!(["quantity", "sku"]));
+        assert_eq!(body["properties"]["note"]["type"], "string");
+        assert_eq!(body["properties"]["quantity"]["type"], "number");
+        assert_eq!(post["requestBody"]["required"], true);
+
+        // 草稿不再带录制标记，可直接生成工具
+        assert!(!is_recording_spec(&draft));
+        let spec: SwaggerSpec = serde_json::from_value(draft)?;
+        let mut tools: Vec<String> = generate_mcp_tools(&spec)?
+            .into_iter()
+            .map(|t| t.name)
+            .collect();
+        tools.sort();
+        assert_eq!(tools, vec!["getUsersByUserId", "postOrdersByOrderIdItems"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_schemas_optional_and_conflicting_fields() {
+        let a = infer_schema(&json!({"id": 1, "tags": ["x"]}));
+        let b = infer_schema(&json!({"id": "abc", "tags": [], "extra": {"k": true}}));
+        let merged = merge_schemas(&a, &b);
+        assert_eq!(merged["required"], json!(["id", "tags"]));
+        // 类型冲突时放宽为任意类型
+        assert_eq!(merged["properties"]["id"], json!({}));
+        assert_eq!(merged["properties"]["tags"]["items"]["type"], "string");
+        assert_eq!(
+            merged["properties"]["extra"]["properties"]["k"]["type"],
+            "boolean"
+        );
+    }
+
+    #[test]
+    fn test_recording_spec_and_body_cap() {
+        let spec = recording_spec("legacy", "http://legacy.local/").unwrap();
+        assert!(is_recording_spec(&spec));
+        assert_eq!(spec["servers"][0]["url"], "http://legacy.local");
+        let parsed: SwaggerSpec = serde_json::from_value(spec).unwrap();
+        assert!(generate_mcp_tools(&parsed).unwrap().is_empty());
+        assert!(recording_spec("legacy", "ftp://legacy.local").is_err());
+
+        assert_eq!(cap_body(Some(json!({"a": 1})), 64), Some(json!({"a": 1})));
+        assert_eq!(cap_body(Some(json!({"a": "x".repeat(100)})), 64), None);
+        assert!(should_record(1.0));
+        assert!(!should_record(0.0));
+    }
+}
@@ -1,4 +1,7 @@
 use crate::models::interface_retrieval::*;
+use crate::models::SwaggerSpec;
+use crate::services::{embedding_text_builder, EMBEDDING_TEXT_VERSION};
+use crate::utils::generate_api_details;
 use anyhow::Result;
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
@@ -31,15 +34,66 @@ pub trait Search: Send + Sync {
     ) -> Result<Vec<Chunk>>;
 
     /// 混合搜索 - 结合向量搜索和关键词搜索
-    async fn hybrid_search(&self, request: InterfaceSearchRequest) -> Result<Vec<Chunk>>;
+    /// 向量化服务不可用时降级为关键词搜索，并在结果中标记 degraded
+    async fn hybrid_search(&self, request: InterfaceSearchRequest) -> Result<SearchResult>;
 
-    /// 获取项目的所有接口
-    async fn get_project_interfaces(&self, project_id: &str) -> Result<Vec<Chunk>>;
+    /// 分页获取项目接口，按 path、method 排序
+    async fn get_project_interfaces(
+        &self,
+        project_id: &str,
+        limit: u32,
+        offset: u32,
+    ) -> Result<Vec<Chunk>>;
 
     /// 删除项目数据
     async fn delete_project_data(&self, project_id: &str) -> Result<u64>;
 
     async fn delete_by_meta(&self, meta: Meta) -> Result<()>;
+
+    /// 写入端点摘要文档，替换同一端点的旧文档
+    async fn store_endpoint_summary(&self, summary: EndpointSummaryDoc) -> Result<()>;
+
+    /// 删除端点摘要文档
+    async fn delete_endpoint_summary(&self, slug: &str) -> Result<()>;
+
+    /// 端点级向量检索，只查询端点摘要索引
+    async fn search_endpoints(
+        &self,
+        query: &str,
+        max_results: u32,
+        only_running: bool,
+    ) -> Result<Vec<EndpointMatch>>;
+
+    /// 已存储的接口文档数，索引或表不存在时为 0
+    async fn document_count(&self) -> Result<u64>;
+}
+
+/// 端点摘要中最多列出的操作数
+pub const ENDPOINT_SUMMARY_OPERATIONS: usize = 20;
+
+/// 混合搜索结果
+#[derive(Debug, Default)]
+pub struct SearchResult {
+    pub chunks: Vec<Chunk>,
+    /// 向量检索失败，结果仅来自关键词检索
+    pub degraded: bool,
+    /// 尚未存储任何接口时提示先注册端点
+    pub hint: Option<String>,
+}
+
+/// 向量检索失败（如向量化服务不可用）时记录告警并返回空结果，由关键词检索兜底
+pub fn degrade_vector_results(query: &str, result: Result<Vec<Chunk>>) -> (Vec<Chunk>, bool) {
+    match result {
+        Ok(chunks) => (chunks, false),
+        Err(e) => {
+            tracing::warn!(
+                "Vector search failed for query '{}', falling back to keyword search: {}",
+                query,
+                e
+            );
+            (Vec::new(), true)
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -50,6 +104,8 @@ pub struct Chunk {
     pub score: f64,
     pub embedding: Vec<f32>,
     pub api_content: Option<ApiInterface>,
+    /// 关键词检索命中的 page_content 高亮片段，命中词以 <em> 包裹
+    pub highlights: Vec<String>,
     pub created_at: Option<DateTime<Utc>>,
     pub updated_at: Option<DateTime<Utc>>,
 }
@@ -65,31 +121,204 @@ pub struct Meta {
     pub project_id: String,
     pub path: String,
     pub method: String,
+    /// 向量化文本构建器版本，旧文档无此字段
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text_version: Option<u32>,
 }
 
 impl Meta {
     pub fn any_empty(&self) -> bool {
-        self.project_id.is_empty() || self.path.is_empty() || self.method.is_empty()
+        !self.missing_fields().is_empty()
+    }
+
+    /// 为空（含仅空白）的定位字段
+    pub fn missing_fields(&self) -> Vec<&'static str> {
+        [
+            ("project_id", &self.project_id),
+            ("path", &self.path),
+            ("method", &self.method),
+        ]
+        .into_iter()
+        .filter(|(_, value)| value.trim().is_empty())
+        .map(|(field, _)| field)
+        .collect()
+    }
+
+    /// 文档由旧版本构建器生成，需要重建索引
+    pub fn text_outdated(&self) -> bool {
+        self.text_version.unwrap_or(1) < EMBEDDING_TEXT_VERSION
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct Filter {
     pub project_id: Option<String>,
     // 路径前置过滤
     pub prefix_path: Option<String>,
     pub methods: Option<Vec<String>>,
+    /// pgvecto.rs HNSW 检索候选集大小，未指定时使用配置
+    #[serde(default)]
+    pub ef_search: Option<u32>,
+    /// pgvecto.rs IVF 检索访问的聚类数，未指定时使用配置
+    #[serde(default)]
+    pub probes: Option<u32>,
 }
 
 /// 需要向量化的内容
 pub fn merge_content(interface: &ApiInterface) -> String {
-    format!(
-        "{} | {} | {}",
-        &interface.summary.clone().unwrap_or("".to_string()),
-        &interface.description.clone().unwrap_or("".to_string()),
-        &interface
-            .service_description
-            .clone()
-            .unwrap_or("".to_string())
-    )
+    embedding_text_builder().build(interface)
+}
+
+/// 文档 metadata，带构建器版本戳
+pub fn document_metadata(project_id: &str, interface: &ApiInterface) -> Value {
+    serde_json::json!({
+        "project_id": project_id,
+        "path": interface.path,
+        "method": interface.method,
+        "text_version": EMBEDDING_TEXT_VERSION
+    })
+}
+
+/// 将 swagger 中的操作转换为待存储的接口文档
+pub fn interfaces_from_spec(spec: &SwaggerSpec) -> Result<Vec<ApiInterface>> {
+    Ok(generate_api_details(spec)?
+        .into_iter()
+        .map(|detail| {
+            let mut interface = ApiInterface::from(detail);
+            interface.service_description = spec.info.description.clone();
+            interface.tags = vec![spec.info.title.clone()];
+            interface
+        })
+        .collect())
+}
+
+/// 端点摘要文本：服务标题、描述、去重后的标签与前 max_operations 个操作摘要
+pub fn endpoint_summary_text(
+    spec: &SwaggerSpec,
+    description: Option<&str>,
+    max_operations: usize,
+) -> String {
+    let mut lines = vec![spec.info.title.clone()];
+    // 端点描述在前，与 spec 描述相同时只保留一份
+    for text in [description, spec.info.description.as_deref()]
+        .into_iter()
+        .flatten()
+        .map(str::trim)
+    {
+        if !text.is_empty() && !lines.iter().any(|line| line == text) {
+            lines.push(text.to_string());
+        }
+    }
+
+    let mut tags: Vec<&str> = Vec::new();
+    let mut operations = Vec::new();
+    for (path, item) in &spec.paths {
+        let methods = [
+            ("GET", &item.get),
+            ("POST", &item.post),
+            ("PUT", &item.put),
+            ("DELETE", &item.delete),
+            ("PATCH", &item.patch),
+        ];
+        for (method, operation) in methods {
+            let Some(operation) = operation else {
+                continue;
+            };
+            for tag in operation.tags.iter().flatten() {
+                if !tags.contains(&tag.as_str()) {
+                    tags.push(tag);
+                }
+            }
+            if operations.len() < max_operations {
+                let summary = operation
+                    .summary
+                    .as_deref()
+                    .or(operation.operation_id.as_deref())
+                    .unwrap_or_default();
+                operations.push(
+                    format!("- {} {} {}", method, path, summary)
+                        .trim_end()
+                        .to_string(),
+                );
+            }
+        }
+    }
+    if !tags.is_empty() {
+        lines.push(format!("Tags: {}", tags.join(", ")));
+    }
+    lines.extend(operations);
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(score: f64) -> Chunk {
+        Chunk {
+            id: Uuid::new_v4(),
+            text: "get user".to_string(),
+            meta: serde_json::json!({"project_id": "p", "path": "/users", "method": "GET"}),
+            score,
+            embedding: Vec::new(),
+            api_content: None,
+            highlights: Vec::new(),
+            created_at: None,
+            updated_at: None,
+        }
+    }
+
+    #[test]
+    fn test_degrade_vector_results() {
+        let (chunks, degraded) = degrade_vector_results("user", Ok(vec![chunk(0.9)]));
+        assert_eq!(chunks.len(), 1);
+        assert!(!degraded);
+
+        let (chunks, degraded) =
+            degrade_vector_results("user", Err(anyhow::anyhow!("embedding unavailable")));
+        assert!(chunks.is_empty());
+        assert!(degraded);
+    }
+
+    #[test]
+    fn test_endpoint_summary_text() {
+        let spec: SwaggerSpec = serde_json::from_value(serde_json::json!({
+            "openapi": "3.0.0",
+            "info": {"title": "Payments", "version": "1.0.0", "description": "Billing service"},
+            "paths": {
+                "/refunds": {"post": {"summary": "refund a charge", "tags": ["billing"]}},
+                "/charges": {
+                    "get": {"operationId": "listCharges", "tags": ["billing", "reports"]},
+                    "delete": {"summary": "void a charge"}
+                }
+            }
+        }))
+        .unwrap();
+        let text = endpoint_summary_text(&spec, Some("Billing service"), 2);
+        assert_eq!(
+            text,
+            "Payments\nBilling service\nTags: billing, reports\n- POST /refunds refund a charge\n- GET /charges listCharges"
+        );
+    }
+
+    #[test]
+    fn test_document_metadata_version() {
+        let spec: SwaggerSpec = serde_json::from_value(serde_json::json!({
+            "openapi": "3.0.0",
+            "info": {"title": "Users", "version": "1.0.0"},
+            "paths": {"/users": {"get": {"summary": "list users"}}}
+        }))
+        .unwrap();
+        let interfaces = interfaces_from_spec(&spec).unwrap();
+        let meta: Meta = serde_json::from_value(document_metadata("p", &interfaces[0])).unwrap();
+        assert_eq!(meta.text_version, Some(EMBEDDING_TEXT_VERSION));
+        assert!(!meta.text_outdated());
+
+        // 旧文档无版本字段
+        let legacy: Meta = serde_json::from_value(
+            serde_json::json!({"project_id": "p", "path": "/users", "method": "GET"}),
+        )
+        .unwrap();
+        assert!(legacy.text_outdated());
+    }
 }
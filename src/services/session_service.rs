@@ -1,5 +1,6 @@
-use crate::models::DbPool;
-use crate::utils::get_china_time;
+use crate::models::{acquire_connection, DbPool, MAIN_POOL};
+use crate::services::{endpoint_counters, session_policies};
+use crate::utils::{cancellation_registry, get_china_time};
 use dashmap::DashMap;
 use rmcp::transport::sse_server::{EndpointId, McpType};
 use rmcp::transport::streamable_http_server::SessionId;
@@ -72,33 +73,49 @@ impl SessionService {
             McpType::STREAMABLE => 2,
         };
 
-        // Insert session log
-        if let Err(e) = sqlx::query(
-            r#"
-                INSERT INTO endpoint_session_logs (id, endpoint_id, session_id, transport_type, connect_at, disconnect_at)
-                VALUES (?, ?, ?, ?, ?, ?)
-                "#,
-        )
-            .bind(id.to_string())
-            .bind(&endpoint_id)
-            .bind(session_id.to_string())
-            .bind(mcp_type_code)
-            .bind(now)
-            .bind(now)
-            .execute(&self.pool)
-            .await
-        {
-            tracing::error!("Failed to insert endpoint session log: {}", e);
-        }
+        // 会话记录与连接计数共用一个连接，连接池繁忙时只记日志
+        match acquire_connection(&self.pool, MAIN_POOL).await {
+            Ok(mut conn) => {
+                // Insert session log
+                if let Err(e) = sqlx::query(
+                    r#"
+                        INSERT INTO endpoint_session_logs (id, endpoint_id, session_id, transport_type, connect_at, disconnect_at)
+                        VALUES (?, ?, ?, ?, ?, ?)
+                        "#,
+                )
+                    .bind(id.to_string())
+                    .bind(&endpoint_id)
+                    .bind(session_id.to_string())
+                    .bind(mcp_type_code)
+                    .bind(now)
+                    .bind(now)
+                    .execute(&mut *conn)
+                    .await
+                {
+                    tracing::error!("Failed to insert endpoint session log: {}", e);
+                }
 
-        // Update connection count
-        if let Err(e) = sqlx::query("INSERT INTO endpoint_connection_counts (id, endpoint_id, connect_num) VALUES (?, ?, 1) ON DUPLICATE KEY UPDATE connect_num = connect_num + 1")
-            .bind(Uuid::new_v4().to_string())
-            .bind(&endpoint_id)
-            .execute(&self.pool)
-            .await
-        {
-            tracing::error!("Failed to update connection count for endpoint {}: {}", endpoint_id, e);
+                // Update connection count
+                if let Err(e) = sqlx::query("INSERT INTO endpoint_connection_counts (id, endpoint_id, connect_num) VALUES (?, ?, 1) ON DUPLICATE KEY UPDATE connect_num = connect_num + 1")
+                    .bind(Uuid::new_v4().to_string())
+                    .bind(&endpoint_id)
+                    .execute(&mut *conn)
+                    .await
+                {
+                    tracing::error!("Failed to update connection count for endpoint {}: {}", endpoint_id, e);
+                }
+            }
+            Err(e) => {
+                tracing::error!(
+                    "Failed to record session {} for endpoint {}: {}",
+                    session_id,
+                    endpoint_id,
+                    e
+                );
+            }
+        }
+        if let Ok(id) = Uuid::parse_str(&endpoint_id) {
+            endpoint_counters().connection_opened(id);
         }
 
         if matches!(mcp_type, McpType::STREAMABLE) {
@@ -112,17 +129,43 @@ impl SessionService {
         session_id: SessionId,
         mcp_type: McpType,
     ) {
+        session_policies().remove(&session_id.to_string());
+        // 会话关闭后其进行中的调用结果无人接收，直接取消
+        let cancelled = cancellation_registry().cancel_session(&session_id.to_string());
+        if cancelled > 0 {
+            tracing::info!(
+                "Cancelled {} in-flight calls of closed session {}",
+                cancelled,
+                session_id
+            );
+        }
+        let mut conn = match acquire_connection(&self.pool, MAIN_POOL).await {
+            Ok(conn) => conn,
+            Err(e) => {
+                tracing::error!(
+                    "Failed to record disconnect of session {}: {}",
+                    session_id,
+                    e
+                );
+                return;
+            }
+        };
         let endpoint_id = match mcp_type {
             McpType::SSE => endpoint_id,
             McpType::STREAMABLE => {
-                let row = sqlx::query(
+                match sqlx::query(
                     "SELECT endpoint_id FROM endpoint_session_logs WHERE session_id = ?",
                 )
                 .bind(session_id.to_string())
-                .fetch_one(&self.pool)
+                .fetch_one(&mut *conn)
                 .await
-                .unwrap();
-                row.get("endpoint_id")
+                {
+                    Ok(row) => row.get("endpoint_id"),
+                    Err(e) => {
+                        tracing::error!("Failed to find endpoint of session {}: {}", session_id, e);
+                        return;
+                    }
+                }
             }
         };
 
@@ -130,7 +173,7 @@ impl SessionService {
             .bind(get_china_time())
             .bind(&endpoint_id)
             .bind(session_id.to_string())
-            .execute(&self.pool)
+            .execute(&mut *conn)
             .await {
             tracing::error!("Failed to update endpoint session log: {}", e);
         }
@@ -138,9 +181,12 @@ impl SessionService {
         if let Err(e) = sqlx::query("INSERT INTO endpoint_connection_counts (id, endpoint_id, connect_num) VALUES (?, ?, 0) ON DUPLICATE KEY UPDATE connect_num = GREATEST(0, connect_num - 1)")
             .bind(Uuid::new_v4().to_string())
             .bind(&endpoint_id)
-            .execute(&self.pool)
+            .execute(&mut *conn)
             .await {
             tracing::error!("Failed to update connection count for endpoint {}: {}", endpoint_id, e);
         }
+        if let Ok(id) = Uuid::parse_str(&endpoint_id) {
+            endpoint_counters().connection_closed(id);
+        }
     }
 }
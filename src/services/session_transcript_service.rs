@@ -0,0 +1,944 @@
+use crate::config::SessionTranscriptConfig;
+use crate::models::{
+    DbPool, SessionEventRecord, SessionTranscript, ToolCallSummary, TranscriptDirection,
+    TranscriptEvent, TranscriptInitialize, TranscriptKind, TranscriptQuery,
+};
+use crate::services::redact_value;
+use crate::utils::get_china_time;
+use anyhow::{anyhow, Result};
+use dashmap::DashMap;
+use serde_json::{json, Value};
+use sqlx::{mysql::MySqlRow, Row};
+use std::sync::OnceLock;
+use std::time::Instant;
+use tokio::sync::{mpsc, oneshot};
+use uuid::Uuid;
+
+/// 会话创建（streamable）或 SSE 连接建立
+pub const LIFECYCLE_OPENED: &str = "opened";
+/// 会话关闭或 SSE 连接断开
+pub const LIFECYCLE_CLOSED: &str = "closed";
+/// 会话事件数达到 max_events_per_session，之后不再记录
+pub const LIFECYCLE_TRUNCATED: &str = "transcript_truncated";
+
+/// 写入队列长度，队列满时丢弃事件而不阻塞 MCP 传输
+const RECORDER_QUEUE_SIZE: usize = 4096;
+/// 单次批量写入的最大事件数
+const WRITE_BATCH_SIZE: usize = 100;
+
+pub static SESSION_TRANSCRIPT_CONFIG: OnceLock<SessionTranscriptConfig> = OnceLock::new();
+
+pub fn session_transcript_config() -> &'static SessionTranscriptConfig {
+    SESSION_TRANSCRIPT_CONFIG.get_or_init(SessionTranscriptConfig::default)
+}
+
+/// 传输层的会话事件记录器，启动时创建；未创建时不记录
+pub static SESSION_RECORDER: OnceLock<SessionRecorder> = OnceLock::new();
+
+pub fn session_recorder() -> Option<&'static SessionRecorder> {
+    SESSION_RECORDER
+        .get()
+        .filter(|recorder| recorder.config.enabled)
+}
+
+enum RecorderMessage {
+    Event(SessionEventRecord),
+    Flush(oneshot::Sender<()>),
+}
+
+/// 等待响应的请求，用于为响应标注方法并生成工具调用摘要
+struct PendingRequest {
+    method: String,
+    started: Instant,
+    /// tools/call 的工具名与脱敏、截断后的参数
+    tool: Option<(String, Value)>,
+}
+
+/// 记录 MCP 传输上收发的消息：写入前脱敏，经队列由单个任务按顺序批量写入
+pub struct SessionRecorder {
+    config: SessionTranscriptConfig,
+    sender: mpsc::Sender<RecorderMessage>,
+    pending: DashMap<(String, String), PendingRequest>,
+    counts: DashMap<String, usize>,
+}
+
+impl SessionRecorder {
+    /// 创建记录器并启动写入任务
+    pub fn spawn(pool: DbPool, config: SessionTranscriptConfig) -> Self {
+        let (sender, receiver) = mpsc::channel(RECORDER_QUEUE_SIZE);
+        tokio::spawn(write_events(pool, receiver, config.max_payload_bytes));
+        Self::with_sender(config, sender)
+    }
+
+    fn with_sender(config: SessionTranscriptConfig, sender: mpsc::Sender<RecorderMessage>) -> Self {
+        Self {
+            config,
+            sender,
+            pending: DashMap::new(),
+            counts: DashMap::new(),
+        }
+    }
+
+    /// 记录客户端发来的消息，批量消息逐条记录
+    pub fn record_received(&self, session_id: &str, endpoint_id: Option<Uuid>, message: &Value) {
+        for message in batch(message) {
+            let (kind, id) = classify_message(message);
+            let method = message["method"].as_str().map(str::to_string);
+            if let (TranscriptKind::Request, Some(method), Some(id)) = (kind, &method, &id) {
+                let tool = (method == "tools/call").then(|| {
+                    (
+                        message["params"]["name"]
+                            .as_str()
+                            .unwrap_or_default()
+                            .to_string(),
+                        self.summary_arguments(&message["params"]["arguments"]),
+                    )
+                });
+                self.pending.insert(
+                    (session_id.to_string(), id.clone()),
+                    PendingRequest {
+                        method: method.clone(),
+                        started: Instant::now(),
+                        tool,
+                    },
+                );
+            }
+            self.enqueue(self.message_record(
+                session_id,
+                endpoint_id,
+                TranscriptDirection::Received,
+                kind,
+                method,
+                id,
+                message,
+            ));
+        }
+    }
+
+    /// 记录发给客户端的消息；tools/call 的响应之后追加工具调用摘要
+    pub fn record_sent(&self, session_id: &str, endpoint_id: Option<Uuid>, message: &Value) {
+        for message in batch(message) {
+            let (kind, id) = classify_message(message);
+            let pending = match (kind, &id) {
+                (TranscriptKind::Response | TranscriptKind::Error, Some(id)) => self
+                    .pending
+                    .remove(&(session_id.to_string(), id.clone()))
+                    .map(|(_, pending)| pending),
+                _ => None,
+            };
+            let method = message["method"]
+                .as_str()
+                .map(str::to_string)
+                .or_else(|| pending.as_ref().map(|p| p.method.clone()));
+            self.enqueue(self.message_record(
+                session_id,
+                endpoint_id,
+                TranscriptDirection::Sent,
+                kind,
+                method,
+                id.clone(),
+                message,
+            ));
+
+            let Some(PendingRequest {
+                started,
+                tool: Some((tool, arguments)),
+                ..
+            }) = pending
+            else {
+                continue;
+            };
+            let summary = ToolCallSummary {
+                tool,
+                request_id: id.clone().unwrap_or_default(),
+                latency_ms: started.elapsed().as_millis() as u64,
+                status: tool_call_status(message).to_string(),
+                arguments,
+            };
+            self.enqueue(SessionEventRecord {
+                session_id: session_id.to_string(),
+                endpoint_id,
+                direction: TranscriptDirection::Gateway,
+                kind: TranscriptKind::ToolCall,
+                method: Some("tools/call".to_string()),
+                message_id: id,
+                payload: serde_json::to_value(&summary).unwrap_or(Value::Null),
+                at: get_china_time(),
+            });
+        }
+    }
+
+    /// 记录会话生命周期事件；closed 之后清理该会话的计数与未完成的请求
+    pub fn record_lifecycle(
+        &self,
+        session_id: &str,
+        endpoint_id: Option<Uuid>,
+        event: &str,
+        detail: Value,
+    ) {
+        self.enqueue(SessionEventRecord {
+            session_id: session_id.to_string(),
+            endpoint_id,
+            direction: TranscriptDirection::Gateway,
+            kind: TranscriptKind::Lifecycle,
+            method: Some(event.to_string()),
+            message_id: None,
+            payload: json!({"event": event, "detail": detail}),
+            at: get_china_time(),
+        });
+        if event == LIFECYCLE_CLOSED {
+            self.counts.remove(session_id);
+            self.pending.retain(|(session, _), _| session != session_id);
+        }
+    }
+
+    /// 等待此前入队的事件全部写入
+    pub async fn flush(&self) {
+        let (done, wait) = oneshot::channel();
+        if self.sender.send(RecorderMessage::Flush(done)).await.is_ok() {
+            let _ = wait.await;
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn message_record(
+        &self,
+        session_id: &str,
+        endpoint_id: Option<Uuid>,
+        direction: TranscriptDirection,
+        kind: TranscriptKind,
+        method: Option<String>,
+        message_id: Option<String>,
+        message: &Value,
+    ) -> SessionEventRecord {
+        SessionEventRecord {
+            session_id: session_id.to_string(),
+            endpoint_id,
+            direction,
+            kind,
+            method,
+            message_id,
+            payload: redact_value(message, &self.config.redact_fields),
+            at: get_china_time(),
+        }
+    }
+
+    /// 摘要中的参数：脱敏后超过 max_argument_chars 时截断为字符串
+    fn summary_arguments(&self, arguments: &Value) -> Value {
+        let redacted = redact_value(arguments, &self.config.redact_fields);
+        let text = redacted.to_string();
+        if text.chars().count() <= self.config.max_argument_chars {
+            return redacted;
+        }
+        let prefix: String = text.chars().take(self.config.max_argument_chars).collect();
+        Value::String(format!("{}…", prefix))
+    }
+
+    /// 超过 max_events_per_session 时以一条 transcript_truncated 事件代替，之后丢弃
+    fn enqueue(&self, record: SessionEventRecord) {
+        let max_events = self.config.max_events_per_session;
+        let mut count = self.counts.entry(record.session_id.clone()).or_insert(0);
+        if *count > max_events {
+            return;
+        }
+        *count += 1;
+        let record = if *count > max_events {
+            SessionEventRecord {
+                direction: TranscriptDirection::Gateway,
+                kind: TranscriptKind::Lifecycle,
+                method: Some(LIFECYCLE_TRUNCATED.to_string()),
+                message_id: None,
+                payload: json!({
+                    "event": LIFECYCLE_TRUNCATED,
+                    "detail": {"max_events_per_session": max_events}
+                }),
+                ..record
+            }
+        } else {
+            record
+        };
+        drop(count);
+        if let Err(e) = self.sender.try_send(RecorderMessage::Event(record)) {
+            tracing::warn!("Dropped session event: {}", e);
+        }
+    }
+}
+
+fn batch(message: &Value) -> Vec<&Value> {
+    match message {
+        Value::Array(messages) => messages.iter().collect(),
+        message => vec![message],
+    }
+}
+
+/// JSON-RPC 消息的类型与 id
+fn classify_message(message: &Value) -> (TranscriptKind, Option<String>) {
+    let id = message
+        .get("id")
+        .filter(|id| !id.is_null())
+        .map(|id| match id {
+            Value::String(id) => id.clone(),
+            other => other.to_string(),
+        });
+    let kind = match (message.get("method").is_some(), &id) {
+        (true, Some(_)) => TranscriptKind::Request,
+        (true, None) => TranscriptKind::Notification,
+        _ if message.get("error").is_some() => TranscriptKind::Error,
+        _ => TranscriptKind::Response,
+    };
+    (kind, id)
+}
+
+/// ok：正常结果；tool_error：isError 工具结果；error：JSON-RPC 错误
+fn tool_call_status(response: &Value) -> &'static str {
+    if response.get("error").is_some() {
+        "error"
+    } else if response["result"]["isError"] == true {
+        "tool_error"
+    } else {
+        "ok"
+    }
+}
+
+/// 存储的 payload，超过 max_bytes 时按字符边界截取前缀
+fn stored_payload(payload: &Value, max_bytes: usize) -> (String, bool) {
+    let text = payload.to_string();
+    if text.len() <= max_bytes {
+        return (text, false);
+    }
+    let mut end = max_bytes;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    (text[..end].to_string(), true)
+}
+
+async fn write_events(
+    pool: DbPool,
+    mut receiver: mpsc::Receiver<RecorderMessage>,
+    max_payload_bytes: usize,
+) {
+    while let Some(first) = receiver.recv().await {
+        let mut events = Vec::new();
+        let mut flushes = Vec::new();
+        let mut next = Some(first);
+        while let Some(message) = next.take() {
+            match message {
+                RecorderMessage::Event(event) => events.push(event),
+                RecorderMessage::Flush(done) => flushes.push(done),
+            }
+            if events.len() < WRITE_BATCH_SIZE {
+                next = receiver.try_recv().ok();
+            }
+        }
+        if let Err(e) = insert_events(&pool, &events, max_payload_bytes).await {
+            tracing::error!("Failed to write {} session events: {}", events.len(), e);
+        }
+        for done in flushes {
+            let _ = done.send(());
+        }
+    }
+}
+
+async fn insert_events(
+    pool: &DbPool,
+    events: &[SessionEventRecord],
+    max_payload_bytes: usize,
+) -> Result<()> {
+    if events.is_empty() {
+        return Ok(());
+    }
+    let placeholders = vec!["(?, ?, ?, ?, ?, ?, ?, ?, ?)"; events.len()].join(", ");
+    let sql = format!(
+        "INSERT INTO session_events (session_id, endpoint_id, direction, kind, method, message_id, payload, truncated, created_at) VALUES {}",
+        placeholders
+    );
+    let mut query = sqlx::query(&sql);
+    for event in events {
+        let (payload, truncated) = stored_payload(&event.payload, max_payload_bytes);
+        query = query
+            .bind(&event.session_id)
+            .bind(event.endpoint_id.map(|id| id.to_string()))
+            .bind(event.direction.as_str())
+            .bind(event.kind.as_str())
+            .bind(&event.method)
+            .bind(&event.message_id)
+            .bind(payload)
+            .bind(truncated)
+            .bind(event.at);
+    }
+    query.execute(pool).await?;
+    Ok(())
+}
+
+/// 导出会话记录的管理 API key 校验失败
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum TranscriptAccessError {
+    #[error("Management API key is required")]
+    MissingKey,
+    #[error("Invalid management API key")]
+    InvalidKey,
+    #[error("Management API key cannot access sessions of this endpoint")]
+    Forbidden,
+}
+
+/// 管理 API key 只能导出其映射中端点的会话；端点已删除的会话只有 "*" 可以导出
+pub fn check_transcript_access(
+    config: &SessionTranscriptConfig,
+    api_key: Option<&str>,
+    endpoint_name: Option<&str>,
+) -> std::result::Result<(), TranscriptAccessError> {
+    let api_key = api_key
+        .filter(|key| !key.is_empty())
+        .ok_or(TranscriptAccessError::MissingKey)?;
+    let endpoints = config
+        .api_keys
+        .get(api_key)
+        .ok_or(TranscriptAccessError::InvalidKey)?;
+    let allowed = endpoints
+        .iter()
+        .any(|name| name == "*" || Some(name.as_str()) == endpoint_name);
+    if allowed {
+        Ok(())
+    } else {
+        Err(TranscriptAccessError::Forbidden)
+    }
+}
+
+/// 会话所属的端点
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionOwner {
+    pub endpoint_id: Option<Uuid>,
+    pub endpoint_name: Option<String>,
+}
+
+pub struct SessionTranscriptService {
+    pool: DbPool,
+    config: SessionTranscriptConfig,
+}
+
+impl SessionTranscriptService {
+    pub fn new(pool: DbPool, config: SessionTranscriptConfig) -> Self {
+        Self { pool, config }
+    }
+
+    pub fn config(&self) -> &SessionTranscriptConfig {
+        &self.config
+    }
+
+    /// 会话所属端点，会话没有任何事件时返回 None
+    pub async fn session_owner(&self, session_id: &str) -> Result<Option<SessionOwner>> {
+        let row = sqlx::query(
+            "SELECT e.endpoint_id, p.name FROM session_events e LEFT JOIN endpoints p ON p.id = e.endpoint_id WHERE e.session_id = ? ORDER BY e.endpoint_id IS NULL, e.seq LIMIT 1",
+        )
+        .bind(session_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        let endpoint_id: Option<String> = row.try_get("endpoint_id")?;
+        Ok(Some(SessionOwner {
+            endpoint_id: endpoint_id.and_then(|id| Uuid::parse_str(&id).ok()),
+            endpoint_name: row.try_get("name")?,
+        }))
+    }
+
+    /// 按 seq 顺序导出一页事件，条数与 payload 总大小超过上限时分页
+    pub async fn transcript(
+        &self,
+        session_id: &str,
+        owner: &SessionOwner,
+        query: &TranscriptQuery,
+    ) -> Result<SessionTranscript> {
+        let kinds = parse_kinds(query.types.as_deref())?;
+        let limit = query
+            .limit
+            .unwrap_or(self.config.page_size)
+            .clamp(1, self.config.max_page_size.max(1));
+
+        let mut sql = "SELECT seq, direction, kind, method, message_id, payload, truncated, created_at FROM session_events WHERE session_id = ? AND seq > ?".to_string();
+        if query.from.is_some() {
+            sql.push_str(" AND created_at >= ?");
+        }
+        if query.to.is_some() {
+            sql.push_str(" AND created_at < ?");
+        }
+        if !kinds.is_empty() {
+            sql.push_str(&format!(
+                " AND kind IN ({})",
+                vec!["?"; kinds.len()].join(", ")
+            ));
+        }
+        sql.push_str(" ORDER BY seq LIMIT ?");
+
+        let mut rows = sqlx::query(&sql)
+            .bind(session_id)
+            .bind(query.cursor.unwrap_or(0));
+        if let Some(from) = query.from {
+            rows = rows.bind(from);
+        }
+        if let Some(to) = query.to {
+            rows = rows.bind(to);
+        }
+        for kind in &kinds {
+            rows = rows.bind(kind.as_str());
+        }
+        let rows = rows.bind((limit + 1) as i64).fetch_all(&self.pool).await?;
+        let events = rows
+            .iter()
+            .map(event_from_row)
+            .collect::<Result<Vec<_>>>()?;
+        let (events, next_cursor) = paginate(events, limit, self.config.max_page_bytes);
+
+        Ok(SessionTranscript {
+            session_id: session_id.to_string(),
+            endpoint_id: owner.endpoint_id,
+            endpoint_name: owner.endpoint_name.clone(),
+            initialize: self.initialize(session_id).await?,
+            events,
+            next_cursor,
+        })
+    }
+
+    /// 会话的 initialize 请求与响应
+    async fn initialize(&self, session_id: &str) -> Result<Option<TranscriptInitialize>> {
+        let rows = sqlx::query(
+            "SELECT seq, direction, kind, method, message_id, payload, truncated, created_at FROM session_events WHERE session_id = ? AND method = 'initialize' AND kind IN ('request', 'response') ORDER BY seq LIMIT 2",
+        )
+        .bind(session_id)
+        .fetch_all(&self.pool)
+        .await?;
+        let events = rows
+            .iter()
+            .map(event_from_row)
+            .collect::<Result<Vec<_>>>()?;
+        Ok(initialize_info(&events))
+    }
+}
+
+fn event_from_row(row: &MySqlRow) -> Result<TranscriptEvent> {
+    let direction: String = row.try_get("direction")?;
+    let kind: String = row.try_get("kind")?;
+    let payload: String = row.try_get("payload")?;
+    let truncated: bool = row.try_get("truncated")?;
+    Ok(TranscriptEvent {
+        seq: row.try_get("seq")?,
+        at: row.try_get("created_at")?,
+        direction: TranscriptDirection::parse(&direction)
+            .ok_or_else(|| anyhow!("Unknown session event direction '{}'", direction))?,
+        kind: TranscriptKind::parse(&kind)
+            .ok_or_else(|| anyhow!("Unknown session event kind '{}'", kind))?,
+        method: row.try_get("method")?,
+        message_id: row.try_get("message_id")?,
+        payload: match truncated {
+            true => Value::String(payload),
+            false => serde_json::from_str(&payload).unwrap_or(Value::String(payload)),
+        },
+        truncated,
+    })
+}
+
+/// 逗号分隔的事件类型，为空表示全部类型
+fn parse_kinds(types: Option<&str>) -> Result<Vec<TranscriptKind>> {
+    types
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .map(|t| TranscriptKind::parse(t).ok_or_else(|| anyhow!("Invalid transcript type '{}'", t)))
+        .collect()
+}
+
+/// 取前 limit 条事件，payload 累计超过 max_bytes 时提前截止（每页至少一条）；
+/// events 应多查询一条以判断是否还有下一页
+fn paginate(
+    mut events: Vec<TranscriptEvent>,
+    limit: usize,
+    max_bytes: usize,
+) -> (Vec<TranscriptEvent>, Option<i64>) {
+    let has_more = events.len() > limit;
+    events.truncate(limit);
+    let mut bytes = 0;
+    let mut keep = 0;
+    for event in &events {
+        bytes += event.payload.to_string().len();
+        if keep > 0 && bytes > max_bytes {
+            break;
+        }
+        keep += 1;
+    }
+    let cut = keep < events.len();
+    events.truncate(keep);
+    let next_cursor = match has_more || cut {
+        true => events.last().map(|event| event.seq),
+        false => None,
+    };
+    (events, next_cursor)
+}
+
+fn initialize_info(events: &[TranscriptEvent]) -> Option<TranscriptInitialize> {
+    let request = events.iter().find(|e| e.kind == TranscriptKind::Request);
+    let response = events.iter().find(|e| e.kind == TranscriptKind::Response);
+    if request.is_none() && response.is_none() {
+        return None;
+    }
+    let params = request.map(|e| &e.payload["params"]);
+    let result = response.map(|e| &e.payload["result"]);
+    let field = |value: Option<&Value>, key: &str| {
+        value
+            .and_then(|v| v.get(key))
+            .filter(|v| !v.is_null())
+            .cloned()
+    };
+    let text = |value: Option<&Value>, key: &str| {
+        field(value, key).and_then(|v| v.as_str().map(str::to_string))
+    };
+    Some(TranscriptInitialize {
+        client_info: field(params, "clientInfo"),
+        client_capabilities: field(params, "capabilities"),
+        requested_version: text(params, "protocolVersion"),
+        negotiated_version: text(result, "protocolVersion"),
+        server_info: field(result, "serverInfo"),
+    })
+}
+
+/// 便于阅读的 markdown 版本，每个事件一节，payload 以 JSON 代码块展示
+pub fn render_transcript_markdown(transcript: &SessionTranscript) -> String {
+    let mut out = format!("# Session transcript `{}`\n\n", transcript.session_id);
+    match (&transcript.endpoint_name, transcript.endpoint_id) {
+        (Some(name), Some(id)) => out.push_str(&format!("- Endpoint: {} (`{}`)\n", name, id)),
+        (None, Some(id)) => out.push_str(&format!("- Endpoint: `{}` (deleted)\n", id)),
+        _ => out.push_str("- Endpoint: unknown\n"),
+    }
+    if let Some(init) = &transcript.initialize {
+        let name = |info: &Option<Value>| {
+            info.as_ref()
+                .map(|i| {
+                    format!(
+                        "{} {}",
+                        i["name"].as_str().unwrap_or("unknown"),
+                        i["version"].as_str().unwrap_or_default()
+                    )
+                })
+                .unwrap_or_else(|| "unknown".to_string())
+        };
+        out.push_str(&format!(
+            "- Client: {}\n",
+            name(&init.client_info).trim_end()
+        ));
+        out.push_str(&format!(
+            "- Server: {}\n",
+            name(&init.server_info).trim_end()
+        ));
+        out.push_str(&format!(
+            "- Protocol: requested {}, negotiated {}\n",
+            init.requested_version.as_deref().unwrap_or("unknown"),
+            init.negotiated_version.as_deref().unwrap_or("unknown")
+        ));
+    }
+    out.push_str(&format!(
+        "- Events: {}\n\n## Events\n",
+        transcript.events.len()
+    ));
+
+    for event in &transcript.events {
+        let at = event.at.format("%Y-%m-%d %H:%M:%S%.3f");
+        let heading = match event.kind {
+            TranscriptKind::ToolCall => format!(
+                "tool call `{}` — {}, {} ms",
+                event.payload["tool"].as_str().unwrap_or_default(),
+                event.payload["status"].as_str().unwrap_or_default(),
+                event.payload["latency_ms"]
+            ),
+            TranscriptKind::Lifecycle => {
+                format!("session {}", event.method.as_deref().unwrap_or_default())
+            }
+            kind => {
+                let mut heading = format!("{} {}", event.direction.as_str(), kind.as_str());
+                if let Some(method) = &event.method {
+                    heading.push_str(&format!(" `{}`", method));
+                }
+                if let Some(id) = &event.message_id {
+                    heading.push_str(&format!(" (id {})", id));
+                }
+                heading
+            }
+        };
+        out.push_str(&format!("\n### #{} · {} · {}\n\n", event.seq, at, heading));
+        let body = match event.kind {
+            TranscriptKind::ToolCall => &event.payload["arguments"],
+            _ => &event.payload,
+        };
+        let body = match body {
+            Value::String(text) if event.truncated => text.clone(),
+            body => serde_json::to_string_pretty(body).unwrap_or_default(),
+        };
+        out.push_str(&format!("```json\n{}\n```\n", body));
+        if event.truncated {
+            out.push_str("\n_Payload truncated._\n");
+        }
+    }
+    if let Some(cursor) = transcript.next_cursor {
+        out.push_str(&format!(
+            "\n_More events available, continue with `cursor={}`._\n",
+            cursor
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::DateTime;
+    use std::collections::HashMap;
+
+    fn recorder(
+        config: SessionTranscriptConfig,
+    ) -> (SessionRecorder, mpsc::Receiver<RecorderMessage>) {
+        let (sender, receiver) = mpsc::channel(100);
+        (SessionRecorder::with_sender(config, sender), receiver)
+    }
+
+    fn drain(receiver: &mut mpsc::Receiver<RecorderMessage>) -> Vec<SessionEventRecord> {
+        let mut events = Vec::new();
+        while let Ok(message) = receiver.try_recv() {
+            if let RecorderMessage::Event(event) = message {
+                events.push(event);
+            }
+        }
+        events
+    }
+
+    fn event(seq: i64, kind: TranscriptKind, payload: Value) -> TranscriptEvent {
+        TranscriptEvent {
+            seq,
+            at: DateTime::from_timestamp(1_700_000_000 + seq, 0).unwrap(),
+            direction: TranscriptDirection::Received,
+            kind,
+            method: None,
+            message_id: None,
+            payload,
+            truncated: false,
+        }
+    }
+
+    #[test]
+    fn test_record_tool_call_summary() {
+        let (recorder, mut receiver) = recorder(SessionTranscriptConfig::default());
+        let endpoint_id = Uuid::new_v4();
+        recorder.record_received(
+            "s1",
+            Some(endpoint_id),
+            &json!({
+                "jsonrpc": "2.0",
+                "id": 3,
+                "method": "tools/call",
+                "params": {"name": "addPet", "arguments": {"name": "doggie", "password": "hunter2"}}
+            }),
+        );
+        recorder.record_sent(
+            "s1",
+            Some(endpoint_id),
+            &json!({"jsonrpc": "2.0", "id": 3, "result": {"content": [], "isError": true}}),
+        );
+
+        let events = drain(&mut receiver);
+        let kinds: Vec<TranscriptKind> = events.iter().map(|e| e.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                TranscriptKind::Request,
+                TranscriptKind::Response,
+                TranscriptKind::ToolCall
+            ]
+        );
+        // 请求与摘要中的敏感参数均已脱敏
+        assert_eq!(
+            events[0].payload["params"]["arguments"]["password"],
+            "[REDACTED]"
+        );
+        assert_eq!(events[1].method.as_deref(), Some("tools/call"));
+        assert_eq!(events[1].message_id.as_deref(), Some("3"));
+        let summary: ToolCallSummary = serde_json::from_value(events[2].payload.clone()).unwrap();
+        assert_eq!(summary.tool, "addPet");
+        assert_eq!(summary.status, "tool_error");
+        assert_eq!(
+            summary.arguments,
+            json!({"name": "doggie", "password": "[REDACTED]"})
+        );
+    }
+
+    #[test]
+    fn test_record_batch_and_event_cap() {
+        let (recorder, mut receiver) = recorder(SessionTranscriptConfig {
+            max_events_per_session: 3,
+            max_argument_chars: 10,
+            ..Default::default()
+        });
+        recorder.record_received(
+            "s1",
+            None,
+            &json!([
+                {"jsonrpc": "2.0", "method": "notifications/initialized"},
+                {"jsonrpc": "2.0", "id": "a", "method": "tools/call", "params": {"name": "t", "arguments": {"text": "a long argument"}}}
+            ]),
+        );
+        recorder.record_sent(
+            "s1",
+            None,
+            &json!({"jsonrpc": "2.0", "id": "a", "error": {"code": -1}}),
+        );
+        recorder.record_sent(
+            "s1",
+            None,
+            &json!({"jsonrpc": "2.0", "method": "notifications/progress"}),
+        );
+
+        let events = drain(&mut receiver);
+        let kinds: Vec<TranscriptKind> = events.iter().map(|e| e.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                TranscriptKind::Notification,
+                TranscriptKind::Request,
+                TranscriptKind::Error,
+                TranscriptKind::Lifecycle
+            ]
+        );
+        assert_eq!(events[3].method.as_deref(), Some(LIFECYCLE_TRUNCATED));
+
+        // 会话关闭后重新计数
+        recorder.record_lifecycle("s1", None, LIFECYCLE_CLOSED, Value::Null);
+        recorder.record_lifecycle("s1", None, LIFECYCLE_OPENED, Value::Null);
+        assert_eq!(drain(&mut receiver).len(), 1);
+        assert_eq!(
+            recorder.summary_arguments(&json!({"text": "a long argument"})),
+            json!("{\"text\":\"a…")
+        );
+    }
+
+    #[test]
+    fn test_stored_payload_truncation() {
+        let payload = json!({"text": "宠物宠物宠物"});
+        assert_eq!(stored_payload(&payload, 1024), (payload.to_string(), false));
+        let (text, truncated) = stored_payload(&payload, 12);
+        assert!(truncated);
+        assert!(text.len() <= 12 && payload.to_string().starts_with(&text));
+    }
+
+    #[test]
+    fn test_paginate_by_count_and_bytes() {
+        let events: Vec<TranscriptEvent> = (1..=5)
+            .map(|seq| event(seq, TranscriptKind::Request, json!({"n": seq})))
+            .collect();
+        let (page, next) = paginate(events.clone(), 2, 1024);
+        assert_eq!(page.iter().map(|e| e.seq).collect::<Vec<_>>(), vec![1, 2]);
+        assert_eq!(next, Some(2));
+        let (page, next) = paginate(events[..3].to_vec(), 3, 1024);
+        assert_eq!(page.len(), 3);
+        assert_eq!(next, None);
+
+        // 每条 payload 7 字节，上限 15 字节时每页 2 条；单条超过上限时仍返回该条
+        let (page, next) = paginate(events[..3].to_vec(), 3, 15);
+        assert_eq!(page.len(), 2);
+        assert_eq!(next, Some(2));
+        let (page, next) = paginate(events[..3].to_vec(), 3, 1);
+        assert_eq!(page.len(), 1);
+        assert_eq!(next, Some(1));
+    }
+
+    #[test]
+    fn test_parse_kinds() {
+        assert!(parse_kinds(None).unwrap().is_empty());
+        assert_eq!(
+            parse_kinds(Some("request, tool_call")).unwrap(),
+            vec![TranscriptKind::Request, TranscriptKind::ToolCall]
+        );
+        assert!(parse_kinds(Some("requests"))
+            .unwrap_err()
+            .to_string()
+            .starts_with("Invalid transcript type"));
+    }
+
+    #[test]
+    fn test_check_transcript_access() {
+        let config = SessionTranscriptConfig {
+            api_keys: HashMap::from([
+                ("admin".to_string(), vec!["*".to_string()]),
+                ("team-a".to_string(), vec!["petstore".to_string()]),
+            ]),
+            ..Default::default()
+        };
+        let check = |key, endpoint| check_transcript_access(&config, key, endpoint);
+        assert_eq!(
+            check(None, Some("petstore")),
+            Err(TranscriptAccessError::MissingKey)
+        );
+        assert_eq!(
+            check(Some("nope"), Some("petstore")),
+            Err(TranscriptAccessError::InvalidKey)
+        );
+        assert_eq!(check(Some("team-a"), Some("petstore")), Ok(()));
+        assert_eq!(
+            check(Some("team-a"), Some("orders")),
+            Err(TranscriptAccessError::Forbidden)
+        );
+        assert_eq!(
+            check(Some("team-a"), None),
+            Err(TranscriptAccessError::Forbidden)
+        );
+        assert_eq!(check(Some("admin"), None), Ok(()));
+    }
+
+    #[test]
+    fn test_render_markdown() {
+        let mut request = event(
+            1,
+            TranscriptKind::Request,
+            json!({"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {
+                "protocolVersion": "2025-03-26",
+                "clientInfo": {"name": "inspector", "version": "1.0"}
+            }}),
+        );
+        request.method = Some("initialize".to_string());
+        request.message_id = Some("1".to_string());
+        let mut response = event(
+            2,
+            TranscriptKind::Response,
+            json!({"jsonrpc": "2.0", "id": 1, "result": {
+                "protocolVersion": "2025-03-26",
+                "serverInfo": {"name": "mcp-gateway", "version": "0.1.0"}
+            }}),
+        );
+        response.direction = TranscriptDirection::Sent;
+        response.method = Some("initialize".to_string());
+        let mut summary = event(
+            3,
+            TranscriptKind::ToolCall,
+            json!({"tool": "getPet", "request_id": "2", "latency_ms": 35, "status": "ok", "arguments": {"petId": 1}}),
+        );
+        summary.direction = TranscriptDirection::Gateway;
+        let initialize = initialize_info(&[request.clone(), response.clone()]);
+        assert_eq!(
+            initialize.as_ref().unwrap().negotiated_version.as_deref(),
+            Some("2025-03-26")
+        );
+
+        let markdown = render_transcript_markdown(&SessionTranscript {
+            session_id: "s1".to_string(),
+            endpoint_id: None,
+            endpoint_name: None,
+            initialize,
+            events: vec![request, response, summary],
+            next_cursor: Some(3),
+        });
+        assert!(markdown.contains("- Client: inspector 1.0"), "{}", markdown);
+        assert!(markdown.contains("- Protocol: requested 2025-03-26, negotiated 2025-03-26"));
+        assert!(markdown.contains("· received request `initialize` (id 1)"));
+        assert!(markdown.contains("· tool call `getPet` — ok, 35 ms"));
+        assert!(markdown.contains("\"petId\": 1"));
+        assert!(markdown.contains("`cursor=3`"));
+    }
+}
@@ -0,0 +1,543 @@
+use crate::config::SpecCacheConfig;
+use crate::models::{Endpoint, Operation, SwaggerSpec};
+use crate::services::{
+    append_operator_notes, http_request_tool, is_recording_spec, OperationNotes,
+};
+use crate::utils::{
+    generate_mcp_tools, inflate_arguments, is_deprecated, parse_tool_name, validate_arguments,
+};
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use rmcp::model::Tool;
+use serde::Serialize;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// 端点解析结果：SwaggerSpec 及派生的工具列表、工具名到操作的映射
+pub struct CachedSpec {
+    pub endpoint_name: String,
+    /// 版本戳，取端点 updated_at
+    pub version: DateTime<Utc>,
+    pub spec: SwaggerSpec,
+    pub tools: Vec<Tool>,
+    /// 录制模式端点：只暴露 http_request 工具
+    pub recording: bool,
+    operations: HashMap<String, (String, String, Operation)>,
+    /// 展开了 inputSchema 的工具及其展开前的 schema
+    nested_schemas: HashMap<String, serde_json::Value>,
+    loaded_at: Instant,
+}
+
+impl CachedSpec {
+    fn parse(endpoint: &Endpoint) -> Result<Self> {
+        let raw: serde_json::Value = serde_json::from_str(&endpoint.swagger_content)?;
+        let recording = is_recording_spec(&raw);
+        // 直接从原文解析为类型化的 spec，paths 按声明顺序保存在 IndexMap 中
+        let spec: SwaggerSpec = serde_json::from_str(&endpoint.swagger_content)?;
+        let mcp_tools = generate_mcp_tools(&spec)?;
+
+        let mut operations = HashMap::with_capacity(mcp_tools.len());
+        let mut nested_schemas = HashMap::new();
+        for tool in &mcp_tools {
+            let (method, path, operation) = parse_tool_name(&spec, &tool.name)?;
+            operations.insert(tool.name.clone(), (method, path, operation.clone()));
+            if let Some(schema) = &tool.nested_input_schema {
+                nested_schemas.insert(tool.name.clone(), schema.clone());
+            }
+        }
+
+        Ok(Self {
+            endpoint_name: endpoint.name.clone(),
+            version: endpoint.updated_at,
+            tools: if recording {
+                vec![http_request_tool()]
+            } else {
+                mcp_tools.iter().map(Tool::from).collect()
+            },
+            recording,
+            spec,
+            operations,
+            nested_schemas,
+            loaded_at: Instant::now(),
+        })
+    }
+
+    /// 按工具名查找 (method, path, operation)
+    pub fn operation(&self, tool_name: &str) -> Result<(&str, &str, &Operation)> {
+        self.operations
+            .get(tool_name)
+            .map(|(method, path, operation)| (method.as_str(), path.as_str(), operation))
+            .ok_or_else(|| anyhow!("Tool not found: {}", tool_name))
+    }
+
+    /// 工具对应的操作在 swagger 中标记为 deprecated
+    pub fn is_deprecated(&self, tool_name: &str) -> bool {
+        self.operations
+            .get(tool_name)
+            .is_some_and(|(_, _, operation)| is_deprecated(operation))
+    }
+
+    /// 展开了 inputSchema 的工具把扁平参数还原为嵌套结构，其余工具原样返回
+    pub fn inflate_arguments<'a>(
+        &self,
+        tool_name: &str,
+        arguments: &'a serde_json::Value,
+    ) -> Result<Cow<'a, serde_json::Value>> {
+        if !self.nested_schemas.contains_key(tool_name) {
+            return Ok(Cow::Borrowed(arguments));
+        }
+        Ok(Cow::Owned(inflate_arguments(arguments)?))
+    }
+
+    /// 按工具 inputSchema 中的约束校验参数，失败时返回 ArgumentError；
+    /// 展开的工具按展开前的 schema 校验还原后的参数
+    pub fn validate_arguments(&self, tool_name: &str, arguments: &serde_json::Value) -> Result<()> {
+        if let Some(schema) = self.nested_schemas.get(tool_name) {
+            validate_arguments(schema, arguments)?;
+        } else if let Some(tool) = self.tools.iter().find(|tool| tool.name == tool_name) {
+            let schema = serde_json::Value::Object(tool.input_schema.as_ref().clone());
+            validate_arguments(&schema, arguments)?;
+        }
+        Ok(())
+    }
+
+    /// 工具列表，描述末尾追加对应操作的运维备注
+    pub fn tools_with_notes(&self, notes: &OperationNotes, max_chars: usize) -> Vec<Tool> {
+        self.tools
+            .iter()
+            .cloned()
+            .map(|mut tool| {
+                let note = self
+                    .operations
+                    .get(tool.name.as_ref())
+                    .and_then(|(method, path, _)| notes.get(&(method.clone(), path.clone())));
+                if let Some(note) = note {
+                    let description = tool.description.as_deref().unwrap_or_default();
+                    tool.description =
+                        Some(append_operator_notes(description, note, max_chars).into());
+                }
+                tool
+            })
+            .collect()
+    }
+}
+
+/// 按请求层策略计算的工具列表版本
+struct CachedToolsVersion {
+    endpoint_name: String,
+    /// 端点版本戳，取端点 updated_at
+    version: DateTime<Utc>,
+    tools_version: String,
+    loaded_at: Instant,
+}
+
+/// 缓存统计
+#[derive(Debug, Clone, Serialize)]
+pub struct SpecCacheStats {
+    pub entries: usize,
+    pub hits: u64,
+    pub misses: u64,
+    pub parses: u64,
+    pub avg_parse_ms: f64,
+}
+
+/// 端点 swagger 解析缓存：
+/// 以 endpoint id 为 key，updated_at 作为版本戳，EndpointEvent 触发失效，TTL 兜底；
+/// 同一端点并发未命中时只解析一次。启动时创建一份，经 AppState 与 Adapter 共用
+pub struct SpecCache {
+    ttl: Duration,
+    entries: DashMap<Uuid, Arc<CachedSpec>>,
+    /// 工具调用链路的端点行缓存，命中时无需查库
+    endpoints: DashMap<Uuid, (Arc<Endpoint>, Instant)>,
+    /// tools/list 版本，key 为 (endpoint id, 请求层策略)，与解析结果一同失效
+    tools_versions: DashMap<(Uuid, String), CachedToolsVersion>,
+    loading: DashMap<Uuid, Arc<tokio::sync::Mutex<()>>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    parses: AtomicU64,
+    parse_micros: AtomicU64,
+}
+
+impl SpecCache {
+    pub fn new(config: &SpecCacheConfig) -> Self {
+        Self {
+            ttl: Duration::from_secs(config.ttl_secs),
+            entries: DashMap::new(),
+            endpoints: DashMap::new(),
+            tools_versions: DashMap::new(),
+            loading: DashMap::new(),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            parses: AtomicU64::new(0),
+            parse_micros: AtomicU64::new(0),
+        }
+    }
+
+    fn lookup(&self, endpoint: &Endpoint) -> Option<Arc<CachedSpec>> {
+        self.entries
+            .get(&endpoint.id)
+            .filter(|entry| {
+                entry.version == endpoint.updated_at && entry.loaded_at.elapsed() < self.ttl
+            })
+            .map(|entry| entry.clone())
+    }
+
+    /// 获取端点解析结果，未命中或版本变化时重新解析
+    pub async fn get_or_parse(&self, endpoint: &Endpoint) -> Result<Arc<CachedSpec>> {
+        if let Some(cached) = self.lookup(endpoint) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(cached);
+        }
+
+        let lock = self
+            .loading
+            .entry(endpoint.id)
+            .or_insert_with(|| Arc::new(tokio::sync::Mutex::new(())))
+            .clone();
+        let _guard = lock.lock().await;
+
+        // 等待期间其他请求已完成解析，没有再次解析，计为命中
+        if let Some(cached) = self.lookup(endpoint) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(cached);
+        }
+        self.misses.fetch_add(1, Ordering::Relaxed);
+
+        let start = Instant::now();
+        let parsed = CachedSpec::parse(endpoint).map(Arc::new);
+        self.parses.fetch_add(1, Ordering::Relaxed);
+        self.parse_micros
+            .fetch_add(start.elapsed().as_micros() as u64, Ordering::Relaxed);
+
+        if let Ok(cached) = &parsed {
+            self.entries.insert(endpoint.id, cached.clone());
+        }
+        // 加载完成后移除锁；仍在等待的请求持有同一把锁，之后新建的锁不受影响
+        self.loading
+            .remove_if(&endpoint.id, |_, current| Arc::ptr_eq(current, &lock));
+        parsed
+    }
+
+    /// 缓存的端点行，超过 TTL 视为未命中
+    pub fn cached_endpoint(&self, endpoint_id: &Uuid) -> Option<Arc<Endpoint>> {
+        self.endpoints
+            .get(endpoint_id)
+            .filter(|entry| entry.1.elapsed() < self.ttl)
+            .map(|entry| entry.0.clone())
+    }
+
+    pub fn store_endpoint(&self, endpoint: Endpoint) -> Arc<Endpoint> {
+        let endpoint = Arc::new(endpoint);
+        self.endpoints
+            .insert(endpoint.id, (endpoint.clone(), Instant::now()));
+        endpoint
+    }
+
+    /// 缓存的工具列表版本，端点版本戳变化或超过 TTL 视为未命中
+    pub fn cached_tools_version(&self, endpoint: &Endpoint, policy_key: &str) -> Option<String> {
+        self.tools_versions
+            .get(&(endpoint.id, policy_key.to_string()))
+            .filter(|entry| {
+                entry.version == endpoint.updated_at && entry.loaded_at.elapsed() < self.ttl
+            })
+            .map(|entry| entry.tools_version.clone())
+    }
+
+    pub fn store_tools_version(&self, endpoint: &Endpoint, policy_key: String, version: String) {
+        self.tools_versions.insert(
+            (endpoint.id, policy_key),
+            CachedToolsVersion {
+                endpoint_name: endpoint.name.clone(),
+                version: endpoint.updated_at,
+                tools_version: version,
+                loaded_at: Instant::now(),
+            },
+        );
+    }
+
+    /// 操作备注、组合工具、键值存储与执行策略变化时只需重算工具列表版本
+    pub fn invalidate_tools_version(&self, endpoint_id: &Uuid) {
+        self.tools_versions.retain(|key, _| key.0 != *endpoint_id);
+    }
+
+    pub fn invalidate(&self, endpoint_id: &Uuid) {
+        self.entries.remove(endpoint_id);
+        self.endpoints.remove(endpoint_id);
+        self.invalidate_tools_version(endpoint_id);
+    }
+
+    /// EndpointEvent 只携带端点名称
+    pub fn invalidate_name(&self, endpoint_name: &str) {
+        self.entries
+            .retain(|_, entry| entry.endpoint_name != endpoint_name);
+        self.endpoints
+            .retain(|_, entry| entry.0.name != endpoint_name);
+        self.tools_versions
+            .retain(|_, entry| entry.endpoint_name != endpoint_name);
+    }
+
+    pub fn stats(&self) -> SpecCacheStats {
+        let parses = self.parses.load(Ordering::Relaxed);
+        let parse_micros = self.parse_micros.load(Ordering::Relaxed);
+        SpecCacheStats {
+            entries: self.entries.len(),
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            parses,
+            avg_parse_ms: if parses == 0 {
+                0.0
+            } else {
+                parse_micros as f64 / parses as f64 / 1000.0
+            },
+        }
+    }
+}
+
+impl Default for SpecCache {
+    fn default() -> Self {
+        Self::new(&SpecCacheConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn endpoint(updated_at: DateTime<Utc>) -> Endpoint {
+        Endpoint {
+            name: "cache-test".to_string(),
+            swagger_content: serde_json::json!({
+                "openapi": "3.0.0",
+                "info": {"title": "Cache", "version": "1.0.0"},
+                "paths": {
+                    "/users": {"get": {"operationId": "listUsers"}},
+                    "/users/{id}": {"delete": {"operationId": "deleteUser"}}
+                }
+            })
+            .to_string(),
+            created_at: updated_at,
+            updated_at,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_second_lookup_does_not_parse() {
+        let cache = SpecCache::default();
+        let endpoint = endpoint(Utc::now());
+
+        let first = cache.get_or_parse(&endpoint).await.unwrap();
+        assert_eq!(first.tools.len(), 2);
+        let second = cache.get_or_parse(&endpoint).await.unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+
+        let stats = cache.stats();
+        assert_eq!(stats.parses, 1);
+        assert_eq!(stats.hits, 1);
+        assert_eq!(stats.misses, 1);
+
+        let (method, path, _) = second.operation("deleteUser").unwrap();
+        assert_eq!((method, path), ("DELETE", "/users/{id}"));
+        assert!(second.operation("missing").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_tools_with_notes() {
+        let cache = SpecCache::default();
+        let cached = cache.get_or_parse(&endpoint(Utc::now())).await.unwrap();
+
+        let mut notes = OperationNotes::new();
+        notes.insert(
+            ("DELETE".to_string(), "/users/{id}".to_string()),
+            "y".repeat(20),
+        );
+        let tools = cached.tools_with_notes(&notes, 5);
+        let delete = tools.iter().find(|t| t.name == "deleteUser").unwrap();
+        assert!(delete
+            .description
+            .as_deref()
+            .unwrap()
+            .ends_with("Operator notes:\nyyyyy..."));
+        let list = tools.iter().find(|t| t.name == "listUsers").unwrap();
+        assert!(!list
+            .description
+            .as_deref()
+            .unwrap_or_default()
+            .contains("Operator notes:"));
+        // 缓存中的原始工具不受影响
+        assert!(cached.tools.iter().all(|t| !t
+            .description
+            .as_deref()
+            .unwrap_or_default()
+            .contains("Operator notes:")));
+    }
+
+    #[tokio::test]
+    async fn test_deprecated_tools_can_be_hidden() {
+        let cache = SpecCache::default();
+        let mut endpoint = endpoint(Utc::now());
+        endpoint.swagger_content = serde_json::json!({
+            "openapi": "3.0.0",
+            "info": {"title": "Cache", "version": "1.0.0"},
+            "paths": {
+                "/users": {"get": {"operationId": "listUsers"}},
+                "/users/{id}": {"delete": {"operationId": "deleteUser", "deprecated": true}}
+            }
+        })
+        .to_string();
+        let cached = cache.get_or_parse(&endpoint).await.unwrap();
+
+        let delete = cached
+            .tools
+            .iter()
+            .find(|t| t.name == "deleteUser")
+            .unwrap();
+        assert!(delete
+            .description
+            .as_deref()
+            .unwrap()
+            .starts_with("[DEPRECATED] "));
+        assert!(cached.is_deprecated("deleteUser"));
+        assert!(!cached.is_deprecated("listUsers"));
+
+        let mut tools = cached.tools.clone();
+        tools.retain(|tool| !cached.is_deprecated(&tool.name));
+        let names: Vec<&str> = tools.iter().map(|t| t.name.as_ref()).collect();
+        assert_eq!(names, vec!["listUsers"]);
+    }
+
+    #[test]
+    fn test_tools_version_follows_entry_invalidation() {
+        let cache = SpecCache::default();
+        let mut endpoint = endpoint(Utc::now());
+        cache.store_tools_version(&endpoint, "None|None".to_string(), "v1".to_string());
+        assert_eq!(
+            cache
+                .cached_tools_version(&endpoint, "None|None")
+                .as_deref(),
+            Some("v1")
+        );
+        // 请求层策略不同的版本分开缓存
+        assert!(cache
+            .cached_tools_version(&endpoint, "None|Some({\"GET\"})")
+            .is_none());
+
+        // 端点更新后版本戳不匹配
+        endpoint.updated_at = endpoint.updated_at + chrono::Duration::seconds(1);
+        assert!(cache.cached_tools_version(&endpoint, "None|None").is_none());
+
+        cache.store_tools_version(&endpoint, "None|None".to_string(), "v2".to_string());
+        cache.invalidate_name(&endpoint.name);
+        assert!(cache.cached_tools_version(&endpoint, "None|None").is_none());
+
+        cache.store_tools_version(&endpoint, "None|None".to_string(), "v3".to_string());
+        cache.invalidate_tools_version(&endpoint.id);
+        assert!(cache.cached_tools_version(&endpoint, "None|None").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_update_invalidates_entry() {
+        let cache = SpecCache::default();
+        let mut endpoint = endpoint(Utc::now());
+        cache.get_or_parse(&endpoint).await.unwrap();
+
+        // 更新后 updated_at 变化，版本戳不匹配
+        endpoint.updated_at = endpoint.updated_at + chrono::Duration::seconds(1);
+        cache.get_or_parse(&endpoint).await.unwrap();
+        assert_eq!(cache.stats().parses, 2);
+
+        // EndpointEvent 按名称失效
+        cache.invalidate_name(&endpoint.name);
+        assert_eq!(cache.stats().entries, 0);
+        cache.get_or_parse(&endpoint).await.unwrap();
+        assert_eq!(cache.stats().parses, 3);
+    }
+
+    #[test]
+    fn test_endpoint_cache_invalidation() {
+        let cache = SpecCache::default();
+        let endpoint = endpoint(Utc::now());
+        let id = endpoint.id;
+        let name = endpoint.name.clone();
+        assert!(cache.cached_endpoint(&id).is_none());
+
+        cache.store_endpoint(endpoint.clone());
+        assert_eq!(cache.cached_endpoint(&id).unwrap().name, name);
+        cache.invalidate(&id);
+        assert!(cache.cached_endpoint(&id).is_none());
+
+        cache.store_endpoint(endpoint);
+        cache.invalidate_name(&name);
+        assert!(cache.cached_endpoint(&id).is_none());
+
+        // TTL 过期视为未命中
+        let expired = SpecCache::new(&SpecCacheConfig { ttl_secs: 0 });
+        let stored = expired.store_endpoint(self::endpoint(Utc::now()));
+        assert!(expired.cached_endpoint(&stored.id).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_cold_requests_parse_once() {
+        let cache = Arc::new(SpecCache::default());
+        let endpoint = Arc::new(endpoint(Utc::now()));
+
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let cache = cache.clone();
+                let endpoint = endpoint.clone();
+                tokio::spawn(async move { cache.get_or_parse(&endpoint).await.unwrap() })
+            })
+            .collect();
+        for handle in handles {
+            handle.await.unwrap();
+        }
+
+        let stats = cache.stats();
+        assert_eq!(stats.parses, 1);
+        // 等锁后命中的请求计为命中
+        assert_eq!(stats.misses, 1);
+        assert_eq!(stats.hits, 7);
+        assert!(cache.loading.is_empty());
+    }
+
+    #[test]
+    fn test_tools_are_byte_identical_across_cache_instances() {
+        // paths 与属性按非字典序声明
+        let mut endpoint = endpoint(Utc::now());
+        endpoint.swagger_content = r#"{
+            "openapi": "3.0.0",
+            "info": {"title": "Order", "version": "1.0.0"},
+            "paths": {
+                "/zoo": {"post": {"operationId": "createZoo", "requestBody": {"content": {
+                    "application/json": {"schema": {"type": "object", "properties": {
+                        "zeta": {"type": "string"}, "alpha": {"type": "integer"}, "mid": {"type": "boolean"}
+                    }}}
+                }}}},
+                "/apple": {"get": {"operationId": "getApple"}, "delete": {"operationId": "deleteApple"}},
+                "/mango": {"put": {"operationId": "putMango"}}
+            }
+        }"#
+        .to_string();
+
+        let serialized = || {
+            let parsed = CachedSpec::parse(&endpoint).unwrap();
+            serde_json::to_string(&parsed.tools).unwrap()
+        };
+        let first = serialized();
+        for _ in 0..10 {
+            assert_eq!(serialized(), first);
+        }
+
+        let parsed = CachedSpec::parse(&endpoint).unwrap();
+        let names: Vec<&str> = parsed.tools.iter().map(|tool| tool.name.as_ref()).collect();
+        assert_eq!(
+            names,
+            vec!["createZoo", "getApple", "deleteApple", "putMango"]
+        );
+    }
+}
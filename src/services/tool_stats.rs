@@ -0,0 +1,276 @@
+use crate::config::ToolStatsConfig;
+use dashmap::DashMap;
+use rmcp::model::Tool;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// 工具统计在 inputSchema._meta 中的键，不计入工具列表版本
+pub const STATS_META_KEY: &str = "stats";
+
+pub static TOOL_STATS: OnceLock<Arc<ToolStatsAggregator>> = OnceLock::new();
+
+pub fn tool_stats() -> &'static Arc<ToolStatsAggregator> {
+    TOOL_STATS.get_or_init(|| Arc::new(ToolStatsAggregator::new(&ToolStatsConfig::default())))
+}
+
+/// 窗口内的工具运行统计
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ToolStats {
+    pub avg_latency_ms: u64,
+    pub p95_latency_ms: u64,
+    /// 0.0 ~ 1.0，保留 4 位小数
+    pub success_rate: f64,
+    pub sample_count: usize,
+    /// 统计窗口，如 "900s"
+    pub window: String,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Sample {
+    at: Instant,
+    latency_ms: u64,
+    success: bool,
+}
+
+/// 内存中的工具调用样本与定时刷新的统计快照；tools/list 只读快照，不查询数据库
+pub struct ToolStatsAggregator {
+    window: Duration,
+    min_samples: usize,
+    max_samples: usize,
+    samples: DashMap<(Uuid, String), VecDeque<Sample>>,
+    snapshot: DashMap<(Uuid, String), ToolStats>,
+}
+
+impl ToolStatsAggregator {
+    pub fn new(config: &ToolStatsConfig) -> Self {
+        Self {
+            window: Duration::from_secs(config.window_secs),
+            min_samples: config.min_samples.max(1),
+            max_samples: config.max_samples_per_tool.max(1),
+            samples: DashMap::new(),
+            snapshot: DashMap::new(),
+        }
+    }
+
+    pub fn record(&self, endpoint_id: Uuid, tool: &str, latency: Duration, success: bool) {
+        let mut samples = self
+            .samples
+            .entry((endpoint_id, tool.to_string()))
+            .or_default();
+        if samples.len() >= self.max_samples {
+            samples.pop_front();
+        }
+        samples.push_back(Sample {
+            at: Instant::now(),
+            latency_ms: latency.as_millis() as u64,
+            success,
+        });
+    }
+
+    /// 丢弃窗口外的样本并重建快照，样本数不足的工具不输出统计
+    pub fn refresh_at(&self, now: Instant) {
+        let window = format!("{}s", self.window.as_secs());
+        self.samples.retain(|key, samples| {
+            while samples
+                .front()
+                .is_some_and(|s| now.saturating_duration_since(s.at) > self.window)
+            {
+                samples.pop_front();
+            }
+            match summarize(samples, self.min_samples, &window) {
+                Some(stats) => {
+                    self.snapshot.insert(key.clone(), stats);
+                }
+                None => {
+                    self.snapshot.remove(key);
+                }
+            }
+            !samples.is_empty()
+        });
+        // 样本已全部过期的工具不保留旧统计
+        self.snapshot
+            .retain(|key, _| self.samples.contains_key(key));
+    }
+
+    pub fn refresh(&self) {
+        self.refresh_at(Instant::now());
+    }
+
+    pub fn stats(&self, endpoint_id: Uuid, tool: &str) -> Option<ToolStats> {
+        self.snapshot
+            .get(&(endpoint_id, tool.to_string()))
+            .map(|stats| stats.clone())
+    }
+
+    /// 端点删除后清理样本与快照
+    pub fn remove_endpoint(&self, endpoint_id: Uuid) {
+        self.samples.retain(|(id, _), _| *id != endpoint_id);
+        self.snapshot.retain(|(id, _), _| *id != endpoint_id);
+    }
+
+    /// 按 refresh_secs 定时刷新快照，0 表示不刷新
+    pub fn spawn_refresher(self: Arc<Self>, refresh_secs: u64) {
+        if refresh_secs == 0 {
+            tracing::info!("Tool stats refresher disabled");
+            return;
+        }
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(Duration::from_secs(refresh_secs));
+            loop {
+                ticker.tick().await;
+                self.refresh();
+            }
+        });
+    }
+}
+
+fn summarize(samples: &VecDeque<Sample>, min_samples: usize, window: &str) -> Option<ToolStats> {
+    if samples.len() < min_samples {
+        return None;
+    }
+    let mut latencies: Vec<u64> = samples.iter().map(|s| s.latency_ms).collect();
+    latencies.sort_unstable();
+    let count = latencies.len();
+    let p95_index = (count * 95).div_ceil(100).saturating_sub(1);
+    let successes = samples.iter().filter(|s| s.success).count();
+    let success_rate = successes as f64 / count as f64;
+    Some(ToolStats {
+        avg_latency_ms: latencies.iter().sum::<u64>() / count as u64,
+        p95_latency_ms: latencies[p95_index],
+        success_rate: (success_rate * 10_000.0).round() / 10_000.0,
+        sample_count: count,
+        window: window.to_string(),
+    })
+}
+
+/// tools/list 中标注工具统计：inputSchema._meta.stats，没有快照的工具不标注
+pub fn annotate_tool_stats(
+    tools: &mut [Tool],
+    endpoint_id: Uuid,
+    aggregator: &ToolStatsAggregator,
+) {
+    for tool in tools.iter_mut() {
+        let Some(stats) = aggregator.stats(endpoint_id, tool.name.as_ref()) else {
+            continue;
+        };
+        let schema = Arc::make_mut(&mut tool.input_schema);
+        let meta = schema
+            .entry("_meta")
+            .or_insert_with(|| Value::Object(Default::default()));
+        if let Value::Object(meta) = meta {
+            meta.insert(
+                STATS_META_KEY.to_string(),
+                serde_json::to_value(stats).unwrap_or(Value::Null),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::tools_version;
+    use serde_json::json;
+
+    fn aggregator(min_samples: usize) -> ToolStatsAggregator {
+        ToolStatsAggregator::new(&ToolStatsConfig {
+            window_secs: 60,
+            min_samples,
+            refresh_secs: 0,
+            max_samples_per_tool: 100,
+        })
+    }
+
+    fn tools() -> Vec<Tool> {
+        ["listPets", "createPet"]
+            .into_iter()
+            .map(|name| {
+                serde_json::from_value(json!({
+                    "name": name,
+                    "description": name,
+                    "inputSchema": {"type": "object", "properties": {}}
+                }))
+                .unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_stats_math() {
+        let endpoint_id = Uuid::new_v4();
+        let stats = aggregator(5);
+        // 延迟 10..=200ms，每 4 次调用失败 1 次
+        for i in 1..=20u64 {
+            stats.record(
+                endpoint_id,
+                "listPets",
+                Duration::from_millis(i * 10),
+                i % 4 != 0,
+            );
+        }
+        stats.refresh();
+
+        let listed = stats.stats(endpoint_id, "listPets").unwrap();
+        assert_eq!(listed.sample_count, 20);
+        assert_eq!(listed.avg_latency_ms, 105);
+        assert_eq!(listed.p95_latency_ms, 190);
+        assert_eq!(listed.success_rate, 0.75);
+        assert_eq!(listed.window, "60s");
+    }
+
+    #[test]
+    fn test_stats_threshold_and_expiry() {
+        let endpoint_id = Uuid::new_v4();
+        let stats = aggregator(3);
+        for _ in 0..2 {
+            stats.record(endpoint_id, "listPets", Duration::from_millis(5), true);
+        }
+        stats.refresh();
+        // 样本数不足时不输出统计
+        assert_eq!(stats.stats(endpoint_id, "listPets"), None);
+
+        stats.record(endpoint_id, "listPets", Duration::from_millis(5), false);
+        stats.refresh();
+        assert_eq!(
+            stats.stats(endpoint_id, "listPets").unwrap().sample_count,
+            3
+        );
+
+        // 窗口内没有调用时移除统计，而不是保留旧数值
+        stats.refresh_at(Instant::now() + Duration::from_secs(61));
+        assert_eq!(stats.stats(endpoint_id, "listPets"), None);
+    }
+
+    #[test]
+    fn test_annotation_does_not_change_version() {
+        let endpoint_id = Uuid::new_v4();
+        let stats = aggregator(1);
+        let plain = tools();
+        let version = tools_version(&plain);
+
+        stats.record(endpoint_id, "listPets", Duration::from_millis(20), true);
+        stats.refresh();
+        let mut annotated = tools();
+        annotate_tool_stats(&mut annotated, endpoint_id, &stats);
+        let listed = serde_json::to_value(&annotated[0]).unwrap();
+        assert_eq!(listed["inputSchema"]["_meta"]["stats"]["sample_count"], 1);
+        let created = serde_json::to_value(&annotated[1]).unwrap();
+        assert!(created["inputSchema"].get("_meta").is_none());
+        assert_eq!(tools_version(&annotated), version);
+
+        // 统计变化后版本仍不变
+        stats.record(endpoint_id, "listPets", Duration::from_millis(900), false);
+        stats.refresh();
+        let mut changed = tools();
+        annotate_tool_stats(&mut changed, endpoint_id, &stats);
+        assert_ne!(
+            serde_json::to_value(&changed).unwrap(),
+            serde_json::to_value(&annotated).unwrap()
+        );
+        assert_eq!(tools_version(&changed), version);
+    }
+}
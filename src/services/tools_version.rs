@@ -0,0 +1,256 @@
+use crate::services::STATS_META_KEY;
+use anyhow::{anyhow, Result};
+use rmcp::model::{ListToolsResult, Tool};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// streamable HTTP 的 tools/list 响应头，值为工具列表版本
+pub const TOOLS_ETAG_HEADER: &str = "Mcp-Tools-Etag";
+/// initialize 结果 capabilities.experimental 中的工具版本
+pub const TOOLS_VERSION_CAPABILITY: &str = "toolsVersion";
+/// tools/list 请求 _meta 中客户端已缓存的版本
+pub const IF_VERSION_META_KEY: &str = "ifVersion";
+
+/// tools/list 单页序列化后的字节上限，0 表示不分页；启动时取 tool_limits.soft_max_list_bytes
+static MAX_TOOLS_PAGE_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+pub fn set_max_tools_page_bytes(bytes: usize) {
+    MAX_TOOLS_PAGE_BYTES.store(bytes, Ordering::Relaxed);
+}
+
+/// 工具列表版本：按工具名排序、对象键排序后的 JSON 的 sha256 前 16 位。
+/// 只取决于客户端看到的列表内容，与进程和 swagger paths 的遍历顺序无关，重启后保持不变；
+/// spec、运维备注、mock、执行策略标注与组合工具的变化都会改变版本，运行统计不会
+pub fn tools_version(tools: &[Tool]) -> String {
+    let values: Vec<Value> = tools
+        .iter()
+        .map(|tool| {
+            let mut value = serde_json::to_value(tool).unwrap_or(Value::Null);
+            strip_stats(&mut value);
+            value
+        })
+        .collect();
+    version_of(values)
+}
+
+/// 去掉 inputSchema._meta.stats，_meta 因此为空时一并去掉
+fn strip_stats(tool: &mut Value) {
+    let Some(schema) = tool.get_mut("inputSchema").and_then(Value::as_object_mut) else {
+        return;
+    };
+    if let Some(Value::Object(meta)) = schema.get_mut("_meta") {
+        meta.remove(STATS_META_KEY);
+        if meta.is_empty() {
+            schema.remove("_meta");
+        }
+    }
+}
+
+fn version_of(mut tools: Vec<Value>) -> String {
+    tools.sort_by(|a, b| {
+        let name = |tool: &Value| tool["name"].as_str().unwrap_or_default().to_string();
+        name(a).cmp(&name(b))
+    });
+    let canonical = Value::Array(tools.into_iter().map(canonicalize).collect());
+    let digest = Sha256::digest(canonical.to_string().as_bytes());
+    hex::encode(&digest[..8])
+}
+
+/// 递归按键排序，保证开启 preserve_order 时序列化结果同样稳定
+pub fn canonicalize(value: Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<(String, Value)> = map.into_iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+            Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key, canonicalize(value)))
+                    .collect(),
+            )
+        }
+        Value::Array(items) => Value::Array(items.into_iter().map(canonicalize).collect()),
+        other => other,
+    }
+}
+
+/// 首页请求携带的版本与当前一致时返回空列表（列表未变化，沿用缓存），否则返回 cursor 所在的一页；
+/// cursor 为上一页返回的 nextCursor，即本页首个工具的序号
+pub fn list_tools_result(
+    tools: Vec<Tool>,
+    if_version: Option<&str>,
+    cursor: Option<&str>,
+) -> Result<ListToolsResult> {
+    let start = match cursor {
+        None if if_version.is_some_and(|v| v == tools_version(&tools)) => {
+            return Ok(ListToolsResult::with_all_items(vec![]));
+        }
+        None => 0,
+        Some(cursor) => cursor
+            .parse::<usize>()
+            .ok()
+            .filter(|start| *start < tools.len())
+            .ok_or_else(|| anyhow!("Invalid tools/list cursor: {}", cursor))?,
+    };
+    Ok(paginate_tools(
+        tools,
+        start,
+        MAX_TOOLS_PAGE_BYTES.load(Ordering::Relaxed),
+    ))
+}
+
+/// 从 start 起取工具，累计字节超过 max_bytes 时截止（每页至少一个工具），还有剩余时返回 nextCursor
+fn paginate_tools(tools: Vec<Tool>, start: usize, max_bytes: usize) -> ListToolsResult {
+    let total = tools.len();
+    let mut page = Vec::new();
+    let mut bytes = 0;
+    for tool in tools.into_iter().skip(start) {
+        bytes += serde_json::to_vec(&tool).map(|v| v.len()).unwrap_or(0);
+        if max_bytes > 0 && !page.is_empty() && bytes > max_bytes {
+            break;
+        }
+        page.push(tool);
+    }
+    let next = start + page.len();
+    ListToolsResult {
+        next_cursor: (next < total).then(|| next.to_string()),
+        tools: page,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn tools() -> Vec<Value> {
+        vec![
+            json!({
+                "name": "listPets",
+                "description": "GET /pets",
+                "inputSchema": {"type": "object", "properties": {"limit": {"type": "integer"}}}
+            }),
+            json!({
+                "name": "createPet",
+                "description": "POST /pets",
+                "inputSchema": {"type": "object", "required": ["name"], "properties": {"name": {"type": "string"}}}
+            }),
+        ]
+    }
+
+    #[test]
+    fn test_version_is_stable() {
+        let version = version_of(tools());
+        // 固定值：版本不依赖进程，重启后客户端缓存的版本仍然有效
+        assert_eq!(version, "a3f9aba45f21494c");
+
+        // 工具顺序与对象键顺序不影响版本
+        let mut reordered = tools();
+        reordered.reverse();
+        reordered[0] = json!({
+            "inputSchema": {"properties": {"limit": {"type": "integer"}}, "type": "object"},
+            "description": "GET /pets",
+            "name": "listPets"
+        });
+        assert_eq!(version_of(reordered), version);
+    }
+
+    #[test]
+    fn test_version_bumps_on_each_mutation() {
+        let base = version_of(tools());
+        let mutations: [(&str, fn(&mut Vec<Value>)); 6] = [
+            ("spec", |t: &mut Vec<Value>| {
+                t[0]["inputSchema"]["properties"]["offset"] = json!({"type": "integer"})
+            }),
+            ("operator notes", |t: &mut Vec<Value>| {
+                t[0]["description"] = json!("GET /pets\n\nNote: paginated")
+            }),
+            ("mock", |t: &mut Vec<Value>| {
+                t[1]["inputSchema"]["_meta"] = json!({"mock": true})
+            }),
+            ("policy", |t: &mut Vec<Value>| {
+                t[1]["inputSchema"]["_meta"] = json!({"blocked": true})
+            }),
+            ("composite", |t: &mut Vec<Value>| {
+                t.push(json!({"name": "adoptPet", "inputSchema": {"type": "object"}}))
+            }),
+            ("removal", |t: &mut Vec<Value>| t.truncate(1)),
+        ];
+        for (mutation, apply) in mutations {
+            let mut changed = tools();
+            apply(&mut changed);
+            assert_ne!(
+                version_of(changed),
+                base,
+                "{} should bump the version",
+                mutation
+            );
+        }
+    }
+
+    #[test]
+    fn test_if_version_fast_path() {
+        let tools: Vec<Tool> = tools()
+            .into_iter()
+            .map(|t| serde_json::from_value(t).unwrap())
+            .collect();
+        let version = tools_version(&tools);
+
+        let unchanged = list_tools_result(tools.clone(), Some(&version), None).unwrap();
+        assert!(unchanged.tools.is_empty());
+        assert!(unchanged.next_cursor.is_none());
+
+        assert_eq!(
+            list_tools_result(tools.clone(), Some("stale"), None)
+                .unwrap()
+                .tools
+                .len(),
+            2
+        );
+        assert_eq!(list_tools_result(tools, None, None).unwrap().tools.len(), 2);
+    }
+
+    #[test]
+    fn test_paginate_by_payload_bytes() {
+        let tools: Vec<Tool> = (0..5)
+            .map(|i| {
+                serde_json::from_value(json!({
+                    "name": format!("tool{}", i),
+                    "inputSchema": {"type": "object"}
+                }))
+                .unwrap()
+            })
+            .collect();
+        let tool_bytes = serde_json::to_vec(&tools[0]).unwrap().len();
+
+        // 每页容纳两个工具
+        let mut cursor = None;
+        let mut pages = Vec::new();
+        loop {
+            let start = cursor.as_deref().map_or(0, |c: &str| c.parse().unwrap());
+            let page = paginate_tools(tools.clone(), start, tool_bytes * 2);
+            pages.push(page.tools.len());
+            cursor = page.next_cursor;
+            if cursor.is_none() {
+                break;
+            }
+        }
+        assert_eq!(pages, vec![2, 2, 1]);
+
+        // 单个工具超过上限时仍单独成页；0 表示不分页
+        let page = paginate_tools(tools.clone(), 0, 1);
+        assert_eq!(
+            (page.tools.len(), page.next_cursor.as_deref()),
+            (1, Some("1"))
+        );
+        assert_eq!(paginate_tools(tools.clone(), 0, 0).tools.len(), 5);
+
+        // 带 cursor 的请求不走 ifVersion 快速路径，越界的 cursor 报错
+        let version = tools_version(&tools);
+        let page = list_tools_result(tools.clone(), Some(&version), Some("4")).unwrap();
+        assert_eq!(page.tools.len(), 1);
+        assert!(list_tools_result(tools.clone(), None, Some("5")).is_err());
+        assert!(list_tools_result(tools, None, Some("next")).is_err());
+    }
+}
@@ -1,5 +1,10 @@
 use crate::models::DbPool;
-use crate::services::{EmbeddingService, EndpointService, SwaggerService};
+use crate::services::{
+    kv_store_config, session_transcript_config, CanaryService, CompositeToolService,
+    ContractTestService, DnsOverrideService, EmbeddingService, EndpointService,
+    ExecutionPolicyService, KvStoreService, LocaleService, MetricsHistoryService,
+    OperationNoteService, RecordingService, SessionTranscriptService, SpecCache, SwaggerService,
+};
 use axum::extract::FromRef;
 use rmcp::transport::sse_server::{App, ConnectionMsg};
 use std::sync::Arc;
@@ -10,6 +15,19 @@ pub struct AppState {
     pub swagger_service: Arc<SwaggerService>,
     pub mcp_service: Arc<crate::services::mcp_service::McpService>,
     pub embedding_service: Arc<EmbeddingService>,
+    pub contract_test_service: Arc<ContractTestService>,
+    pub operation_note_service: Arc<OperationNoteService>,
+    pub composite_tool_service: Arc<CompositeToolService>,
+    pub metrics_history_service: Arc<MetricsHistoryService>,
+    pub execution_policy_service: Arc<ExecutionPolicyService>,
+    pub recording_service: Arc<RecordingService>,
+    pub canary_service: Arc<CanaryService>,
+    pub dns_override_service: Arc<DnsOverrideService>,
+    pub kv_store_service: Arc<KvStoreService>,
+    pub locale_service: Arc<LocaleService>,
+    pub session_transcript_service: Arc<SessionTranscriptService>,
+    /// 端点解析缓存，与 MCP 会话的 Adapter 共用
+    pub spec_cache: Arc<SpecCache>,
     pub pool: DbPool,
     pub connect_tx: tokio::sync::mpsc::UnboundedSender<ConnectionMsg>,
 }
@@ -20,6 +38,7 @@ impl AppState {
         swagger_service: Arc<SwaggerService>,
         mcp_service: Arc<crate::services::mcp_service::McpService>,
         embedding_service: Arc<EmbeddingService>,
+        spec_cache: Arc<SpecCache>,
         pool: DbPool,
         connect_tx: tokio::sync::mpsc::UnboundedSender<ConnectionMsg>,
     ) -> Self {
@@ -28,6 +47,34 @@ impl AppState {
             swagger_service,
             mcp_service,
             embedding_service,
+            contract_test_service: Arc::new(ContractTestService::new(pool.clone())),
+            operation_note_service: Arc::new(OperationNoteService::new(
+                pool.clone(),
+                spec_cache.clone(),
+            )),
+            composite_tool_service: Arc::new(CompositeToolService::new(
+                pool.clone(),
+                spec_cache.clone(),
+            )),
+            metrics_history_service: Arc::new(MetricsHistoryService::new(pool.clone())),
+            execution_policy_service: Arc::new(ExecutionPolicyService::new(
+                pool.clone(),
+                spec_cache.clone(),
+            )),
+            recording_service: Arc::new(RecordingService::new(pool.clone())),
+            canary_service: Arc::new(CanaryService::new(pool.clone())),
+            dns_override_service: Arc::new(DnsOverrideService::new(pool.clone())),
+            kv_store_service: Arc::new(KvStoreService::new(
+                pool.clone(),
+                kv_store_config().clone(),
+                spec_cache.clone(),
+            )),
+            locale_service: Arc::new(LocaleService::new(pool.clone())),
+            session_transcript_service: Arc::new(SessionTranscriptService::new(
+                pool.clone(),
+                session_transcript_config().clone(),
+            )),
+            spec_cache,
             pool,
             connect_tx,
         }
@@ -0,0 +1,97 @@
+#[cfg(test)]
+mod tests {
+    use crate::tests::harness::{block_on, MockUpstream, TestGateway};
+    use reqwest::StatusCode;
+    use serde_json::{json, Value};
+    use uuid::Uuid;
+
+    #[test]
+    #[ignore] // 需要测试数据库
+    fn test_batch_call_returns_results_in_order() {
+        block_on(async {
+            let upstream = MockUpstream::petstore().await.unwrap();
+            let gateway = TestGateway::start().await.unwrap();
+            let http = reqwest::Client::new();
+            let name = format!("harness-batch-{}", Uuid::new_v4().simple());
+            let endpoint_id = gateway
+                .create_endpoint(&name, &upstream.spec())
+                .await
+                .unwrap();
+            gateway.start_endpoint(endpoint_id).await.unwrap();
+            let url = format!(
+                "{}/api/endpoints/{}/tools/batch-call",
+                gateway.base_url, endpoint_id
+            );
+
+            // 一个成功、一个失败（未知工具），失败不影响其余调用
+            let response = http
+                .post(&url)
+                .json(&json!([
+                    {"tool": "getPetById", "arguments": {"petId": 7}},
+                    {"tool": "deletePet", "arguments": {"petId": 7}}
+                ]))
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let results: Vec<Value> = response.json().await.unwrap();
+            assert_eq!(results.len(), 2, "{:#?}", results);
+
+            assert_eq!(results[0]["index"], 0);
+            assert_eq!(results[0]["tool"], "getPetById");
+            assert_eq!(results[0]["status"], "ok", "{}", results[0]);
+            assert_eq!(results[0]["result"]["status"], 200);
+            assert_eq!(results[0]["result"]["response"]["id"], 7);
+            assert!(results[0].get("error").is_none());
+
+            assert_eq!(results[1]["index"], 1);
+            assert_eq!(results[1]["tool"], "deletePet");
+            assert_eq!(results[1]["status"], "error", "{}", results[1]);
+            assert_eq!(results[1]["error"]["code"], -32603);
+            assert!(results[1]["error"]["data"]
+                .as_str()
+                .unwrap()
+                .contains("Tool not found: deletePet"));
+            assert!(results[1].get("result").is_none());
+
+            // 失败的调用没有发往上游
+            let requests = upstream.requests();
+            assert_eq!(requests.len(), 1, "{:?}", requests);
+            assert_eq!(requests[0].path, "/pets/7");
+
+            // 默认状态映射：404 为 isError 结果，500 为 JSON-RPC 错误
+            let response = http
+                .post(&url)
+                .json(&json!([
+                    {"tool": "getPetById", "arguments": {"petId": 404}},
+                    {"tool": "getPetById", "arguments": {"petId": 500}}
+                ]))
+                .send()
+                .await
+                .unwrap();
+            let results: Vec<Value> = response.json().await.unwrap();
+            assert_eq!(results[0]["status"], "tool_error", "{}", results[0]);
+            assert_eq!(results[0]["result"]["status"], 404);
+            assert_eq!(results[1]["status"], "error", "{}", results[1]);
+            assert_eq!(results[1]["error"]["code"], -32035);
+            assert_eq!(results[1]["error"]["data"]["status"], 500);
+
+            // 空批次与未知端点
+            let response = http.post(&url).json(&json!([])).send().await.unwrap();
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+            let response = http
+                .post(format!(
+                    "{}/api/endpoints/{}/tools/batch-call",
+                    gateway.base_url,
+                    Uuid::new_v4()
+                ))
+                .json(&json!([{"tool": "getPetById", "arguments": {"petId": 1}}]))
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+            gateway.delete_endpoint(endpoint_id).await.unwrap();
+        });
+    }
+}
@@ -0,0 +1,44 @@
+#[cfg(test)]
+mod tests {
+    use crate::tests::harness::{block_on, MockUpstream, TestGateway};
+    use serde_json::json;
+    use std::time::{Duration, Instant};
+    use uuid::Uuid;
+
+    #[test]
+    #[ignore] // 需要测试数据库
+    fn test_client_deadline_aborts_slow_upstream() {
+        block_on(async {
+            let upstream = MockUpstream::petstore_with_delay(Duration::from_secs(2))
+                .await
+                .unwrap();
+            let gateway = TestGateway::start().await.unwrap();
+            let name = format!("harness-deadline-{}", Uuid::new_v4().simple());
+            let endpoint_id = gateway
+                .create_endpoint(&name, &upstream.spec())
+                .await
+                .unwrap();
+            gateway.start_endpoint(endpoint_id).await.unwrap();
+            let mut client = gateway.connect(endpoint_id).await.unwrap();
+
+            let started = Instant::now();
+            let error = client
+                .call_tool("getPetById", json!({"petId": 1, "_mcp_deadline_ms": 500}))
+                .await
+                .unwrap_err();
+            assert!(started.elapsed() < Duration::from_millis(1500));
+            let message = error.to_string();
+            assert!(message.contains("-32034"), "{}", message);
+            assert!(message.contains("client"), "{}", message);
+
+            // 剩余预算通过请求头传给上游，参数本身不转发
+            let requests = upstream.requests();
+            assert_eq!(requests.len(), 1);
+            assert!(!requests[0].query.contains_key("_mcp_deadline_ms"));
+            let remaining: u64 = requests[0].headers["x-request-timeout-ms"].parse().unwrap();
+            assert!(remaining > 0 && remaining <= 500, "{}", remaining);
+
+            gateway.delete_endpoint(endpoint_id).await.unwrap();
+        });
+    }
+}
@@ -0,0 +1,149 @@
+#[cfg(test)]
+mod tests {
+    use crate::config::{DemoConfig, LocalStorageConfig, Settings, StorageConfig, StorageProvider};
+    use crate::models::table_rag::{DatasetCaller, RowFilter};
+    use crate::models::DemoEntityKind;
+    use crate::services::{
+        DatasetAccessService, DemoSeedService, EmbeddingService, EndpointService, FileService,
+        JobService, TableRagService, DEMO_DATASET_NAME,
+    };
+    use crate::tests::harness::{block_on, TestGateway};
+    use anyhow::Result;
+    use std::sync::Arc;
+    use tokio::sync::mpsc;
+    use uuid::Uuid;
+
+    async fn demo_service(
+        gateway: &TestGateway,
+    ) -> Result<(DemoSeedService, Arc<TableRagService>)> {
+        let settings = Settings::new()?;
+        let pool = gateway.pool.clone();
+        let (tx, mut rx) = mpsc::channel(100);
+        tokio::spawn(async move { while rx.recv().await.is_some() {} });
+        let root = std::env::temp_dir().join(format!("mcp_demo_seed_{}", Uuid::new_v4()));
+        let files = Arc::new(FileService::new(
+            pool.clone(),
+            Some(StorageConfig {
+                provider: StorageProvider::Local,
+                oss: None,
+                local: Some(LocalStorageConfig {
+                    root: root.to_string_lossy().to_string(),
+                }),
+            }),
+        )?);
+        let table_rag = Arc::new(
+            TableRagService::new(
+                &settings.embedding,
+                Arc::new(EmbeddingService::new(settings.embedding.clone())),
+                pool.clone(),
+                files.clone(),
+                &settings.ingest,
+                Arc::new(JobService::new(pool.clone(), settings.jobs.clone())),
+            )
+            .await?,
+        );
+        let service = DemoSeedService::new(
+            pool.clone(),
+            DemoConfig { seed_enabled: true },
+            Arc::new(EndpointService::new(pool.clone(), tx, Default::default())),
+            table_rag.clone(),
+            files,
+            Arc::new(DatasetAccessService::new(
+                pool,
+                settings.dataset_access.clone(),
+            )),
+        );
+        Ok((service, table_rag))
+    }
+
+    #[test]
+    #[ignore] // 需要 Elasticsearch、Embedding 服务与测试数据库
+    fn test_seed_demo_is_idempotent_and_tears_down() {
+        block_on(async {
+            let gateway = TestGateway::start().await.unwrap();
+            let (service, table_rag) = demo_service(&gateway).await.unwrap();
+            service.teardown().await.unwrap();
+
+            let first = service.seed().await.unwrap();
+            assert_eq!(first.created.len(), 4);
+            assert!(first.existing.is_empty());
+            assert!(first.dataset_token.is_some());
+            assert_eq!(first.ingested_rows, 10);
+            assert_eq!(first.synthetic_logs, 48);
+
+            // 再次写入不重复创建，返回相同的 id
+            let second = service.seed().await.unwrap();
+            assert!(second.created.is_empty());
+            assert_eq!(second.existing, first.created);
+            assert!(second.dataset_token.is_none());
+            assert_eq!(second.synthetic_logs, 0);
+
+            let id_of = |kind: DemoEntityKind| {
+                first
+                    .created
+                    .iter()
+                    .filter(|e| e.kind == kind)
+                    .map(|e| e.id)
+                    .collect::<Vec<_>>()
+            };
+            for endpoint_id in id_of(DemoEntityKind::Endpoint) {
+                let mut client = gateway.connect(endpoint_id).await.unwrap();
+                assert!(!client.list_tools().await.unwrap().is_empty());
+            }
+
+            // 演示令牌只能检索公开商品
+            let dataset_id = id_of(DemoEntityKind::Dataset)[0];
+            let caller = DatasetCaller::Restricted(RowFilter::from([(
+                "visibility".to_string(),
+                "public".to_string(),
+            )]));
+            let result = table_rag
+                .search(dataset_id, "笔记本电脑", 10, Some(0.0), &[], &[], &caller)
+                .await
+                .unwrap();
+            let hits = result["hits"]["hits"]
+                .as_array()
+                .cloned()
+                .unwrap_or_default();
+            assert!(!hits.is_empty());
+            assert!(hits.iter().all(|h| h["_source"]["visibility"] == "public"));
+
+            let removed = service.teardown().await.unwrap().removed;
+            assert_eq!(removed.len(), 4);
+            let count = |sql: &'static str, id: String| {
+                let pool = gateway.pool.clone();
+                async move {
+                    sqlx::query_scalar::<_, i64>(sql)
+                        .bind(id)
+                        .fetch_one(&pool)
+                        .await
+                        .unwrap()
+                }
+            };
+            for endpoint_id in id_of(DemoEntityKind::Endpoint) {
+                for sql in [
+                    "SELECT COUNT(*) FROM endpoints WHERE id = ?",
+                    "SELECT COUNT(*) FROM endpoint_logs WHERE endpoint_id = ?",
+                    "SELECT COUNT(*) FROM endpoint_metrics WHERE endpoint_id = ?",
+                ] {
+                    assert_eq!(count(sql, endpoint_id.to_string()).await, 0, "{}", sql);
+                }
+            }
+            for sql in [
+                "SELECT COUNT(*) FROM t_dataset WHERE id = ?",
+                "SELECT COUNT(*) FROM t_task WHERE dataset_id = ?",
+                "SELECT COUNT(*) FROM t_dataset_token WHERE dataset_id = ?",
+            ] {
+                assert_eq!(count(sql, dataset_id.to_string()).await, 0, "{}", sql);
+            }
+            assert_eq!(
+                count(
+                    "SELECT COUNT(*) FROM t_dataset WHERE name = ?",
+                    DEMO_DATASET_NAME.to_string()
+                )
+                .await,
+                0
+            );
+        });
+    }
+}
@@ -132,7 +132,7 @@ mod tests {
                 // 1.5. 调试：查看存储的数据
                 println!("🔍 调试：查看存储的数据...");
                 match service
-                    .get_project_interfaces(test_project_id.to_string().as_str())
+                    .get_project_interfaces(test_project_id.to_string().as_str(), 100, 0)
                     .await
                 {
                     Ok(chunks) => {
@@ -169,6 +169,8 @@ mod tests {
                     project_id: Some(test_project_id.to_string()),
                     methods: None,
                     prefix_path: None,
+                    ef_search: None,
+                    probes: None,
                 };
 
                 // 先测试嵌入服务是否正常工作
@@ -263,6 +265,8 @@ mod tests {
                     methods: Some(vec!["GET".to_string()]),
                     project_id: Some(test_project_id.to_string()),
                     prefix_path: Some("/api/users".to_string()),
+                    ef_search: None,
+                    probes: None,
                 };
 
                 match service
@@ -306,7 +310,9 @@ mod tests {
                 };
 
                 match service.hybrid_search(hybrid_request).await {
-                    Ok(chunks) => {
+                    Ok(result) => {
+                        assert!(!result.degraded, "向量化服务正常时不应降级");
+                        let chunks = result.chunks;
                         println!("✅ 混合检索成功，找到 {} 个结果", chunks.len());
 
                         if !chunks.is_empty() {
@@ -377,4 +383,235 @@ mod tests {
             }
         }
     }
+
+    #[tokio::test]
+    async fn test_hybrid_search_degrades_when_embedding_fails() {
+        let settings = Settings::new().unwrap();
+        let embedding_config = settings.embedding;
+
+        // 使用正常的向量化服务写入测试数据
+        let embedding_service = Arc::new(EmbeddingService::new(embedding_config.clone()));
+        let service = ElasticSearch::new(&embedding_config, embedding_service)
+            .await
+            .expect("无法连接Elasticsearch");
+        let test_project_id = Uuid::new_v4().to_string();
+        service
+            .parse_and_store_swagger(create_test_parse_request(test_project_id.clone()))
+            .await
+            .expect("接口数据存储失败");
+        sleep(Duration::from_millis(500)).await;
+
+        // 未配置阿里云，embed_text 必然失败
+        let mut failing_config = embedding_config.clone();
+        failing_config.aliyun = None;
+        let failing_service = ElasticSearch::new(
+            &embedding_config,
+            Arc::new(EmbeddingService::new(failing_config)),
+        )
+        .await
+        .expect("无法连接Elasticsearch");
+
+        let project_filter = Filter {
+            project_id: Some(test_project_id.clone()),
+            prefix_path: None,
+            methods: None,
+            ef_search: None,
+            probes: None,
+        };
+        let result = failing_service
+            .hybrid_search(InterfaceSearchRequest {
+                query: "唯一id".to_string(),
+                search_type: SearchType::Hybrid,
+                max_results: 10,
+                similarity_threshold: None,
+                vector_weight: Some(0.7),
+                filters: Some(project_filter),
+            })
+            .await
+            .expect("向量化失败时混合检索不应报错");
+
+        assert!(result.degraded, "应标记为降级模式");
+        assert!(!result.chunks.is_empty(), "降级后应返回关键词检索结果");
+
+        let _ = service.delete_project_data(&test_project_id).await;
+    }
+
+    #[tokio::test]
+    async fn test_keyword_search_returns_highlights() {
+        let settings = Settings::new().unwrap();
+        let embedding_config = settings.embedding;
+        let embedding_service = Arc::new(EmbeddingService::new(embedding_config.clone()));
+        let service = ElasticSearch::new(&embedding_config, embedding_service)
+            .await
+            .expect("无法连接Elasticsearch");
+        let test_project_id = Uuid::new_v4().to_string();
+        service
+            .parse_and_store_swagger(create_test_parse_request(test_project_id.clone()))
+            .await
+            .expect("接口数据存储失败");
+        sleep(Duration::from_millis(500)).await;
+
+        let project_filter = Filter {
+            project_id: Some(test_project_id.clone()),
+            prefix_path: None,
+            methods: None,
+            ef_search: None,
+            probes: None,
+        };
+        let chunks = service
+            .keyword_search("检索", 10, Some(&project_filter))
+            .await
+            .expect("关键词检索失败");
+
+        assert!(!chunks.is_empty(), "应命中测试接口");
+        let highlights: Vec<&String> = chunks.iter().flat_map(|c| &c.highlights).collect();
+        assert!(!highlights.is_empty(), "命中结果应返回高亮片段");
+        assert!(
+            highlights.iter().any(|h| h.contains("<em>")),
+            "高亮片段应标记命中词: {:?}",
+            highlights
+        );
+
+        let _ = service.delete_project_data(&test_project_id).await;
+    }
+
+    #[tokio::test]
+    async fn test_get_project_interfaces_pages_through_large_project() {
+        let settings = Settings::new().unwrap();
+        let embedding_config = settings.embedding;
+        let embedding_service = Arc::new(EmbeddingService::new(embedding_config.clone()));
+        let service = ElasticSearch::new(&embedding_config, embedding_service)
+            .await
+            .expect("无法连接Elasticsearch");
+
+        let test_project_id = Uuid::new_v4().to_string();
+        let mut paths = serde_json::Map::new();
+        for i in 0..150 {
+            paths.insert(
+                format!("/api/items/{:03}", i),
+                serde_json::json!({
+                    "get": {
+                        "summary": format!("查询条目 {}", i),
+                        "operationId": format!("getItem{}", i)
+                    }
+                }),
+            );
+        }
+        let request = SwaggerParseRequest {
+            project_id: test_project_id.clone(),
+            swagger_json: serde_json::json!({
+                "openapi": "3.0.0",
+                "info": {"title": "Items", "version": "1.0.0"},
+                "paths": paths
+            }),
+            version: Some("1.0.0".to_string()),
+            generate_embeddings: Some(true),
+        };
+        service
+            .parse_and_store_swagger(request)
+            .await
+            .expect("接口数据存储失败");
+        sleep(Duration::from_millis(1000)).await;
+
+        let mut seen = std::collections::HashSet::new();
+        let mut offset = 0;
+        loop {
+            let page = service
+                .get_project_interfaces(&test_project_id, 40, offset)
+                .await
+                .expect("分页查询失败");
+            if page.is_empty() {
+                break;
+            }
+            assert!(page.len() <= 40);
+            for chunk in &page {
+                let path = chunk.api_content.as_ref().unwrap().path.clone();
+                assert!(seen.insert(path), "分页结果不应重复");
+            }
+            offset += page.len() as u32;
+        }
+        assert_eq!(seen.len(), 150, "应能分页读取全部接口");
+
+        let _ = service.delete_project_data(&test_project_id).await;
+    }
+
+    #[tokio::test]
+    async fn test_delete_by_meta_removes_only_that_interface() {
+        let settings = Settings::new().unwrap();
+        let embedding_config = settings.embedding;
+        let embedding_service = Arc::new(EmbeddingService::new(embedding_config.clone()));
+        let service = ElasticSearch::new(&embedding_config, embedding_service)
+            .await
+            .expect("无法连接Elasticsearch");
+
+        let test_project_id = Uuid::new_v4().to_string();
+        let request = SwaggerParseRequest {
+            project_id: test_project_id.clone(),
+            swagger_json: serde_json::json!({
+                "openapi": "3.0.0",
+                "info": {"title": "Users", "version": "1.0.0"},
+                "paths": {
+                    "/api/users/{id}": {
+                        "get": {"summary": "查询用户", "operationId": "getUser"},
+                        "delete": {"summary": "删除用户", "operationId": "deleteUser"}
+                    },
+                    "/api/orders": {
+                        "get": {"summary": "查询订单", "operationId": "listOrders"}
+                    }
+                }
+            }),
+            version: Some("1.0.0".to_string()),
+            generate_embeddings: Some(true),
+        };
+        service
+            .parse_and_store_swagger(request)
+            .await
+            .expect("接口数据存储失败");
+        sleep(Duration::from_millis(500)).await;
+
+        let interfaces = |chunks: Vec<crate::services::Chunk>| {
+            let mut keys: Vec<(String, String)> = chunks
+                .iter()
+                .map(|c| {
+                    let meta = c.get_meta();
+                    (meta.path, meta.method.to_lowercase())
+                })
+                .collect();
+            keys.sort();
+            keys
+        };
+        let chunks = service
+            .get_project_interfaces(&test_project_id, 10, 0)
+            .await
+            .expect("查询接口失败");
+        assert_eq!(chunks.len(), 3);
+        let target = chunks
+            .into_iter()
+            .find(|c| {
+                let meta = c.get_meta();
+                meta.path == "/api/users/{id}" && meta.method.eq_ignore_ascii_case("get")
+            })
+            .expect("应包含 GET /api/users/{id}")
+            .get_meta();
+
+        service
+            .delete_by_meta(target)
+            .await
+            .expect("按接口删除失败");
+
+        let remaining = service
+            .get_project_interfaces(&test_project_id, 10, 0)
+            .await
+            .expect("查询接口失败");
+        // 同路径的其他方法与其他路径的接口保留
+        assert_eq!(
+            interfaces(remaining),
+            vec![
+                ("/api/orders".to_string(), "get".to_string()),
+                ("/api/users/{id}".to_string(), "delete".to_string()),
+            ]
+        );
+
+        let _ = service.delete_project_data(&test_project_id).await;
+    }
 }
@@ -0,0 +1,115 @@
+#[cfg(test)]
+mod tests {
+    use crate::models::JobStatus;
+    use crate::services::{pending_cleanup_items, ACTION_VECTOR_CLEANUP};
+    use crate::tests::harness::{block_on, MockUpstream, TestGateway};
+    use serde_json::json;
+    use uuid::Uuid;
+
+    #[test]
+    #[ignore] // 需要测试数据库
+    fn test_create_start_list_and_call_tools() {
+        block_on(async {
+            let upstream = MockUpstream::petstore().await.unwrap();
+            let gateway = TestGateway::start().await.unwrap();
+            let name = format!("harness-petstore-{}", Uuid::new_v4().simple());
+            let endpoint_id = gateway
+                .create_endpoint(&name, &upstream.spec())
+                .await
+                .unwrap();
+            gateway.start_endpoint(endpoint_id).await.unwrap();
+
+            let mut client = gateway.connect(endpoint_id).await.unwrap();
+            assert!(client.session_id().is_some());
+            let tools = client.list_tools().await.unwrap();
+            let mut names: Vec<&str> = tools.iter().filter_map(|t| t["name"].as_str()).collect();
+            names.sort();
+            assert_eq!(names, vec!["addPet", "getPetById"]);
+
+            let result = client
+                .call_tool("getPetById", json!({"petId": 7, "verbose": true}))
+                .await
+                .unwrap();
+            assert_ne!(result["isError"], true, "{}", result);
+            assert_eq!(result["structuredContent"]["response"]["name"], "doggie");
+
+            let result = client
+                .call_tool("addPet", json!({"name": "Rex", "tag": "dog"}))
+                .await
+                .unwrap();
+            assert_ne!(result["isError"], true, "{}", result);
+            assert_eq!(result["structuredContent"]["response"]["id"], 10);
+
+            // 上游按 spec 收到路径参数、查询参数与请求体
+            let requests = upstream.requests();
+            assert_eq!(requests.len(), 2);
+            assert_eq!(requests[0].method, "GET");
+            assert_eq!(requests[0].path, "/pets/7");
+            assert_eq!(
+                requests[0].query.get("verbose").map(String::as_str),
+                Some("true")
+            );
+            assert_eq!(requests[1].method, "POST");
+            assert_eq!(requests[1].body, json!({"name": "Rex", "tag": "dog"}));
+
+            gateway.delete_endpoint(endpoint_id).await.unwrap();
+        });
+    }
+
+    #[test]
+    #[ignore] // 需要测试数据库
+    fn test_start_rejects_endpoint_without_paths() {
+        block_on(async {
+            let gateway = TestGateway::start().await.unwrap();
+            let name = format!("harness-empty-{}", Uuid::new_v4().simple());
+            let spec = json!({
+                "openapi": "3.0.0",
+                "info": {"title": "Empty", "version": "1.0.0"},
+                "servers": [{"url": "http://127.0.0.1:1"}],
+                "paths": {}
+            });
+            let endpoint_id = gateway.create_endpoint(&name, &spec).await.unwrap();
+
+            let error = gateway
+                .start_endpoint(endpoint_id)
+                .await
+                .unwrap_err()
+                .to_string();
+            assert!(error.contains("400"), "{}", error);
+            assert!(error.contains("At least one path is required"), "{}", error);
+
+            gateway.delete_endpoint(endpoint_id).await.unwrap();
+        });
+    }
+
+    #[test]
+    #[ignore] // 需要测试数据库
+    fn test_deleted_endpoint_appears_in_cleanup_outbox() {
+        block_on(async {
+            let upstream = MockUpstream::petstore().await.unwrap();
+            let gateway = TestGateway::start().await.unwrap();
+            let name = format!("harness-outbox-{}", Uuid::new_v4().simple());
+            let endpoint_id = gateway
+                .create_endpoint(&name, &upstream.spec())
+                .await
+                .unwrap();
+            gateway.delete_endpoint(endpoint_id).await.unwrap();
+
+            // 测试网关没有运行任务 worker，清理任务保持 pending
+            let items = pending_cleanup_items(&gateway.pool).await.unwrap();
+            let item = items
+                .iter()
+                .find(|item| item.endpoint_id == endpoint_id)
+                .unwrap();
+            assert_eq!(item.endpoint_name, name);
+            assert_eq!(item.action, ACTION_VECTOR_CLEANUP);
+            assert_eq!(item.status, JobStatus::Pending);
+
+            sqlx::query("DELETE FROM t_jobs WHERE id = ?")
+                .bind(item.id.to_string())
+                .execute(&gateway.pool)
+                .await
+                .unwrap();
+        });
+    }
+}
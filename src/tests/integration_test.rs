@@ -51,7 +51,10 @@ mod integration_tests {
         };
 
         // 搜索功能测试 - 验证搜索不会崩溃
-        let chunks = interface_service.search_interfaces(search_request).await?;
+        let chunks = interface_service
+            .search_interfaces(search_request)
+            .await?
+            .chunks;
 
         // 验证搜索功能正常工作（可能有历史数据）
         // 这个测试主要验证搜索功能不会崩溃，而不是验证具体的结果数量
@@ -192,7 +195,7 @@ mod integration_tests {
 
         // 验证数据已存储 - 通过项目ID查询接口
         let project_interfaces = interface_service
-            .get_project_interfaces("test_project")
+            .get_project_interfaces("test_project", 100, 0)
             .await;
         assert!(
             project_interfaces.is_ok(),
@@ -237,7 +240,7 @@ mod integration_tests {
             search_result.err()
         );
 
-        let chunks = search_result.unwrap();
+        let chunks = search_result.unwrap().chunks;
         assert!(chunks.len() > 0, "应该能搜索到相关接口");
 
         // 验证搜索结果包含预期的接口
@@ -267,7 +270,7 @@ mod integration_tests {
             search_result2.err()
         );
 
-        let chunks2 = search_result2.unwrap();
+        let chunks2 = search_result2.unwrap().chunks;
         assert!(chunks2.len() > 0, "第二次搜索应该能找到相关接口");
 
         // 验证能找到根据ID获取用户的接口
@@ -0,0 +1,165 @@
+#[cfg(test)]
+mod tests {
+    use crate::tests::harness::{block_on, MockUpstream, TestGateway};
+    use reqwest::header::{HeaderMap, HeaderValue};
+    use serde_json::{json, Value};
+    use sqlx::Row;
+    use std::time::Duration;
+    use uuid::Uuid;
+
+    fn api_key(key: &'static str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-api-key", HeaderValue::from_static(key));
+        headers
+    }
+
+    fn tool_names(tools: &[Value]) -> Vec<&str> {
+        tools.iter().filter_map(|t| t["name"].as_str()).collect()
+    }
+
+    #[test]
+    #[ignore] // 需要测试数据库
+    fn test_kv_tools_over_streamable_http() {
+        block_on(async {
+            let upstream = MockUpstream::petstore().await.unwrap();
+            let gateway = TestGateway::start().await.unwrap();
+            let http = reqwest::Client::new();
+            let name = format!("harness-kv-{}", Uuid::new_v4().simple());
+            let endpoint_id = gateway
+                .create_endpoint(&name, &upstream.spec())
+                .await
+                .unwrap();
+            gateway.start_endpoint(endpoint_id).await.unwrap();
+
+            // 默认关闭
+            let mut client = gateway.connect(endpoint_id).await.unwrap();
+            let tools = client.list_tools().await.unwrap();
+            assert!(!tool_names(&tools).contains(&"kv_set"));
+
+            let response = http
+                .put(format!(
+                    "{}/api/endpoints/{}/kv/settings",
+                    gateway.base_url, endpoint_id
+                ))
+                .json(&json!({"enabled": true}))
+                .send()
+                .await
+                .unwrap();
+            assert!(response.status().is_success());
+
+            let mut tenant_a = gateway
+                .connect_with_headers(endpoint_id, api_key("tenant-a"))
+                .await
+                .unwrap();
+            let mut tenant_b = gateway
+                .connect_with_headers(endpoint_id, api_key("tenant-b"))
+                .await
+                .unwrap();
+            let tools = tenant_a.list_tools().await.unwrap();
+            let names = tool_names(&tools);
+            for tool in ["kv_set", "kv_get", "kv_delete", "kv_list", "getPetById"] {
+                assert!(names.contains(&tool), "{:?}", names);
+            }
+
+            let result = tenant_a
+                .call_tool(
+                    "kv_set",
+                    json!({"key": "report", "value": {"id": "r-1", "token": "t0p"}}),
+                )
+                .await
+                .unwrap();
+            assert_ne!(result["isError"], true, "{}", result);
+            let result = tenant_a
+                .call_tool("kv_get", json!({"key": "report"}))
+                .await
+                .unwrap();
+            assert_eq!(result["structuredContent"]["found"], true);
+            assert_eq!(result["structuredContent"]["value"]["id"], "r-1");
+
+            // 另一个 API key 看不到 tenant-a 的键
+            let result = tenant_b
+                .call_tool("kv_get", json!({"key": "report"}))
+                .await
+                .unwrap();
+            assert_eq!(result["structuredContent"]["found"], false);
+            let result = tenant_b.call_tool("kv_list", json!({})).await.unwrap();
+            assert_eq!(result["structuredContent"]["keys"], json!([]));
+            let result = tenant_b
+                .call_tool("kv_delete", json!({"key": "report"}))
+                .await
+                .unwrap();
+            assert_eq!(result["structuredContent"]["deleted"], false);
+
+            // TTL 到期后读不到
+            tenant_a
+                .call_tool("kv_set", json!({"key": "temp", "value": 1, "ttl_secs": 1}))
+                .await
+                .unwrap();
+            tokio::time::sleep(Duration::from_millis(2100)).await;
+            let result = tenant_a
+                .call_tool("kv_get", json!({"key": "temp"}))
+                .await
+                .unwrap();
+            assert_eq!(result["structuredContent"]["found"], false);
+
+            // 超过单值大小配额
+            let error = tenant_a
+                .call_tool(
+                    "kv_set",
+                    json!({"key": "big", "value": "x".repeat(70 * 1024)}),
+                )
+                .await
+                .unwrap_err();
+            assert!(error.to_string().contains("-32033"), "{}", error);
+
+            let result = tenant_a.call_tool("kv_list", json!({})).await.unwrap();
+            let keys: Vec<&str> = result["structuredContent"]["keys"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .filter_map(|k| k["key"].as_str())
+                .collect();
+            assert_eq!(keys, vec!["report"]);
+
+            // 运维接口可以看到全部 namespace，namespace 中不含 API key 原文
+            let entries: Vec<Value> = http
+                .get(format!(
+                    "{}/api/endpoints/{}/kv",
+                    gateway.base_url, endpoint_id
+                ))
+                .send()
+                .await
+                .unwrap()
+                .json()
+                .await
+                .unwrap();
+            assert_eq!(entries.len(), 1);
+            let namespace = entries[0]["namespace"].as_str().unwrap().to_string();
+            assert!(namespace.starts_with("key:"));
+            assert!(!namespace.contains("tenant-a"));
+            let response = http
+                .delete(format!(
+                    "{}/api/endpoints/{}/kv/{}/report",
+                    gateway.base_url, endpoint_id, namespace
+                ))
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(response.status(), reqwest::StatusCode::NO_CONTENT);
+
+            // 调用日志中的敏感字段已脱敏
+            let row = sqlx::query(
+                "SELECT request_body FROM endpoint_logs WHERE endpoint_id = ? AND path = 'kv_set' ORDER BY created_at LIMIT 1",
+            )
+            .bind(endpoint_id.to_string())
+            .fetch_one(&gateway.pool)
+            .await
+            .unwrap();
+            let logged: String = row.try_get("request_body").unwrap();
+            assert!(logged.contains("[REDACTED]"), "{}", logged);
+            assert!(!logged.contains("t0p"), "{}", logged);
+
+            gateway.delete_endpoint(endpoint_id).await.unwrap();
+        });
+    }
+}
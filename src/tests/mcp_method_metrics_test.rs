@@ -0,0 +1,47 @@
+#[cfg(test)]
+mod tests {
+    use crate::services::mcp_method_counters;
+    use crate::tests::harness::{block_on, MockUpstream, TestGateway};
+    use serde_json::json;
+    use uuid::Uuid;
+
+    #[test]
+    #[ignore] // 需要测试数据库
+    fn test_mcp_methods_are_counted_per_endpoint() {
+        block_on(async {
+            let upstream = MockUpstream::petstore().await.unwrap();
+            let gateway = TestGateway::start().await.unwrap();
+            let name = format!("harness-methods-{}", Uuid::new_v4().simple());
+            let endpoint_id = gateway
+                .create_endpoint(&name, &upstream.spec())
+                .await
+                .unwrap();
+            gateway.start_endpoint(endpoint_id).await.unwrap();
+
+            let mut client = gateway.connect(endpoint_id).await.unwrap();
+            for _ in 0..3 {
+                client.list_tools().await.unwrap();
+            }
+            client
+                .call_tool("getPetById", json!({"petId": 7}))
+                .await
+                .unwrap();
+
+            let counters = mcp_method_counters();
+            assert_eq!(counters.get(endpoint_id, "initialize"), 1);
+            assert_eq!(counters.get(endpoint_id, "tools/list"), 3);
+            assert_eq!(counters.get(endpoint_id, "tools/call"), 1);
+            assert_eq!(counters.get(endpoint_id, "resources/list"), 0);
+
+            let text = counters.render_prometheus();
+            assert!(text.contains(&format!(
+                "mcp_method_total{{endpoint=\"{}\",method=\"tools/list\"}} 3",
+                endpoint_id
+            )));
+
+            // 删除端点后计数一并清除
+            gateway.delete_endpoint(endpoint_id).await.unwrap();
+            assert_eq!(counters.get(endpoint_id, "tools/list"), 0);
+        });
+    }
+}
@@ -1,5 +1,16 @@
+mod batch_call_test;
+mod deadline_test;
+mod demo_seed_test;
 pub mod elastic_search_test;
+mod endpoint_flow_test;
+#[cfg(test)]
+pub mod harness;
 mod integration_test;
 pub mod interface_retrieval_models_test;
 pub mod interface_retrieval_test;
+mod kv_store_test;
+mod mcp_method_metrics_test;
 pub mod pgvector_rs_test;
+mod session_transcript_test;
+mod status_mapping_test;
+mod upstream_locale_test;
@@ -164,6 +164,8 @@ mod tests {
                     methods: Some(vec!["GET".to_string()]),
                     project_id: Some(test_project_id.to_string()),
                     prefix_path: Some("/api/users".to_string()),
+                    ef_search: None,
+                    probes: None,
                 };
 
                 match service
@@ -226,4 +228,72 @@ mod tests {
             }
         }
     }
+
+    #[tokio::test]
+    async fn test_pgvector_rs_schema_is_idempotent() {
+        let settings = Settings::new().unwrap();
+        let embedding_config = settings.embedding;
+        let embedding_service = Arc::new(EmbeddingService::new(embedding_config.clone()));
+
+        // 第二次初始化时表、生成列与索引均已存在
+        PgvectorRsSearch::new(&embedding_config, embedding_service.clone())
+            .await
+            .unwrap();
+        PgvectorRsSearch::new(&embedding_config, embedding_service)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_pgvector_rs_filtered_vector_search() {
+        let settings = Settings::new().unwrap();
+        let embedding_config = settings.embedding;
+        let embedding_service = Arc::new(EmbeddingService::new(embedding_config.clone()));
+        let service = PgvectorRsSearch::new(&embedding_config, embedding_service)
+            .await
+            .unwrap();
+
+        let project_a = Uuid::new_v4().to_string();
+        let project_b = Uuid::new_v4().to_string();
+        for project_id in [&project_a, &project_b] {
+            service
+                .parse_and_store_swagger(create_test_parse_request(project_id.clone()))
+                .await
+                .unwrap();
+        }
+
+        let filter = |methods: &str, ef_search: Option<u32>| Filter {
+            project_id: Some(project_a.clone()),
+            prefix_path: Some("/api/users".to_string()),
+            methods: Some(vec![methods.to_string()]),
+            ef_search,
+            probes: None,
+        };
+        // 两个项目的文档相同，过滤后只返回项目 A 的文档；不同的 ef_search 结果一致
+        for ef_search in [None, Some(10), Some(400)] {
+            let chunks = service
+                .vector_search("用户id", 10, 0.0, Some(&filter("GET", ef_search)))
+                .await
+                .unwrap();
+            assert_eq!(chunks.len(), 1, "ef_search {:?}", ef_search);
+            assert_eq!(chunks[0].meta["project_id"], project_a.as_str());
+            assert_eq!(chunks[0].meta["path"], "/api/users/{id}");
+        }
+        let chunks = service
+            .vector_search("用户id", 10, 0.0, Some(&filter("POST", None)))
+            .await
+            .unwrap();
+        assert!(chunks.is_empty());
+        let chunks = service
+            .keyword_search("用户", 10, Some(&filter("GET", None)))
+            .await
+            .unwrap();
+        assert!(chunks
+            .iter()
+            .all(|chunk| chunk.meta["project_id"] == project_a.as_str()));
+
+        for project_id in [&project_a, &project_b] {
+            service.delete_project_data(project_id).await.unwrap();
+        }
+    }
 }
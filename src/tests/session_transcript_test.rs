@@ -0,0 +1,192 @@
+#[cfg(test)]
+mod tests {
+    use crate::services::session_recorder;
+    use crate::tests::harness::{
+        block_on, MockUpstream, TestGateway, TRANSCRIPT_ADMIN_KEY, TRANSCRIPT_OTHER_KEY,
+    };
+    use reqwest::StatusCode;
+    use serde_json::{json, Value};
+    use uuid::Uuid;
+
+    const KEY_HEADER: &str = "x-management-key";
+
+    #[test]
+    #[ignore] // 需要测试数据库
+    fn test_session_transcript_export() {
+        block_on(async {
+            let upstream = MockUpstream::petstore().await.unwrap();
+            let gateway = TestGateway::start().await.unwrap();
+            let http = reqwest::Client::new();
+            let name = format!("harness-transcript-{}", Uuid::new_v4().simple());
+            let endpoint_id = gateway
+                .create_endpoint(&name, &upstream.spec())
+                .await
+                .unwrap();
+            gateway.start_endpoint(endpoint_id).await.unwrap();
+
+            let mut client = gateway.connect(endpoint_id).await.unwrap();
+            let session_id = client.session_id().unwrap().to_string();
+            client.list_tools().await.unwrap();
+            client
+                .call_tool("getPetById", json!({"petId": 1}))
+                .await
+                .unwrap();
+            let _ = client
+                .call_tool("addPet", json!({"name": "doggie", "api_key": "sk-secret"}))
+                .await;
+            client
+                .call_tool("getPetById", json!({"petId": 404}))
+                .await
+                .unwrap();
+            session_recorder().unwrap().flush().await;
+
+            let url = format!(
+                "{}/api/sessions/{}/transcript",
+                gateway.base_url, session_id
+            );
+            let export = |query: Vec<(&'static str, String)>, key: Option<&'static str>| {
+                let mut request = http.get(&url).query(&query);
+                if let Some(key) = key {
+                    request = request.header(KEY_HEADER, key);
+                }
+                request.send()
+            };
+
+            // 管理 API key：缺失 401，不属于该端点 403
+            let response = export(vec![], None).await.unwrap();
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+            let response = export(vec![], Some(TRANSCRIPT_OTHER_KEY)).await.unwrap();
+            assert_eq!(response.status(), StatusCode::FORBIDDEN);
+            let response = export(
+                vec![("types", "requests".to_string())],
+                Some(TRANSCRIPT_ADMIN_KEY),
+            )
+            .await
+            .unwrap();
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+            let response = export(vec![], Some(TRANSCRIPT_ADMIN_KEY)).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let transcript: Value = response.json().await.unwrap();
+            assert_eq!(transcript["endpoint_name"], name.as_str());
+            assert_eq!(
+                transcript["initialize"]["client_info"]["name"],
+                "test-harness"
+            );
+            assert_eq!(transcript["initialize"]["requested_version"], "2025-03-26");
+            assert!(transcript["next_cursor"].is_null());
+
+            // 按发生顺序：会话创建、initialize 往返、initialized 通知，之后每个请求先于其响应
+            let events = transcript["events"].as_array().unwrap().clone();
+            let seqs: Vec<i64> = events.iter().map(|e| e["seq"].as_i64().unwrap()).collect();
+            assert!(seqs.windows(2).all(|w| w[0] < w[1]), "{:?}", seqs);
+            let steps: Vec<String> = events
+                .iter()
+                .map(|e| {
+                    format!(
+                        "{} {} {}",
+                        e["direction"].as_str().unwrap(),
+                        e["kind"].as_str().unwrap(),
+                        e["method"].as_str().unwrap_or_default()
+                    )
+                })
+                .collect();
+            assert_eq!(
+                steps[..9].to_vec(),
+                vec![
+                    "gateway lifecycle opened",
+                    "received request initialize",
+                    "sent response initialize",
+                    "received notification notifications/initialized",
+                    "received request tools/list",
+                    "sent response tools/list",
+                    "received request tools/call",
+                    "sent response tools/call",
+                    "gateway tool_call tools/call",
+                ],
+                "{:#?}",
+                steps
+            );
+
+            // 请求与工具调用摘要中的敏感参数已脱敏
+            let body = Value::Array(events.clone()).to_string();
+            assert!(!body.contains("sk-secret"), "{}", body);
+            let summaries: Vec<&Value> = events
+                .iter()
+                .filter(|e| e["kind"] == "tool_call")
+                .map(|e| &e["payload"])
+                .collect();
+            assert_eq!(summaries.len(), 3, "{:#?}", summaries);
+            assert_eq!(summaries[0]["tool"], "getPetById");
+            assert_eq!(summaries[0]["status"], "ok");
+            assert_eq!(summaries[1]["arguments"]["api_key"], "[REDACTED]");
+            assert_eq!(summaries[1]["arguments"]["name"], "doggie");
+            assert_eq!(summaries[2]["status"], "tool_error");
+
+            // 按类型过滤
+            let response = export(
+                vec![("types", "tool_call".to_string())],
+                Some(TRANSCRIPT_ADMIN_KEY),
+            )
+            .await
+            .unwrap();
+            let filtered: Value = response.json().await.unwrap();
+            assert_eq!(filtered["events"].as_array().unwrap().len(), 3);
+
+            // 分页：依次使用 next_cursor 取完，与一次导出的事件相同
+            let mut paged = Vec::new();
+            let mut cursor: Option<i64> = None;
+            loop {
+                let mut query = vec![("limit", "4".to_string())];
+                if let Some(cursor) = cursor {
+                    query.push(("cursor", cursor.to_string()));
+                }
+                let response = export(query, Some(TRANSCRIPT_ADMIN_KEY)).await.unwrap();
+                let page: Value = response.json().await.unwrap();
+                let events = page["events"].as_array().unwrap();
+                assert!(events.len() <= 4);
+                paged.extend(events.iter().map(|e| e["seq"].as_i64().unwrap()));
+                cursor = page["next_cursor"].as_i64();
+                if cursor.is_none() {
+                    break;
+                }
+            }
+            assert_eq!(paged, seqs);
+
+            let response = export(
+                vec![("format", "markdown".to_string())],
+                Some(TRANSCRIPT_ADMIN_KEY),
+            )
+            .await
+            .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            assert!(response.headers()["content-type"]
+                .to_str()
+                .unwrap()
+                .starts_with("text/markdown"));
+            let markdown = response.text().await.unwrap();
+            assert!(
+                markdown.contains("- Client: test-harness 0.1.0"),
+                "{}",
+                markdown
+            );
+            assert!(markdown.contains("tool call `addPet`"), "{}", markdown);
+            assert!(markdown.contains("[REDACTED]"));
+            assert!(!markdown.contains("sk-secret"));
+
+            let response = http
+                .get(format!(
+                    "{}/api/sessions/{}/transcript",
+                    gateway.base_url,
+                    Uuid::new_v4()
+                ))
+                .header(KEY_HEADER, TRANSCRIPT_ADMIN_KEY)
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+            gateway.delete_endpoint(endpoint_id).await.unwrap();
+        });
+    }
+}
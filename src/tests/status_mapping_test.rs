@@ -0,0 +1,78 @@
+#[cfg(test)]
+mod tests {
+    use crate::tests::harness::{block_on, MockUpstream, TestGateway};
+    use serde_json::json;
+    use uuid::Uuid;
+
+    #[test]
+    #[ignore] // 需要测试数据库
+    fn test_upstream_error_status_mapping() {
+        block_on(async {
+            let upstream = MockUpstream::petstore().await.unwrap();
+            let gateway = TestGateway::start().await.unwrap();
+            let http = reqwest::Client::new();
+            let name = format!("harness-status-{}", Uuid::new_v4().simple());
+            let endpoint_id = gateway
+                .create_endpoint(&name, &upstream.spec())
+                .await
+                .unwrap();
+            gateway.start_endpoint(endpoint_id).await.unwrap();
+            let mut client = gateway.connect(endpoint_id).await.unwrap();
+
+            // 默认：404 为 isError 工具结果，保留状态与响应体
+            let result = client
+                .call_tool("getPetById", json!({"petId": 404}))
+                .await
+                .unwrap();
+            assert_eq!(result["isError"], true, "{}", result);
+            assert_eq!(result["structuredContent"]["status"], 404);
+            assert_eq!(
+                result["structuredContent"]["response"]["message"],
+                "pet not found"
+            );
+
+            // 默认：500 为 JSON-RPC 错误
+            let error = client
+                .call_tool("getPetById", json!({"petId": 500}))
+                .await
+                .unwrap_err();
+            assert!(error.to_string().contains("-32035"), "{}", error);
+
+            // 按端点覆盖：404 作为普通结果，5xx 作为 isError 结果
+            let response = http
+                .put(format!("{}/api/endpoint/{}", gateway.base_url, endpoint_id))
+                .json(&json!({
+                    "status_mapping": {
+                        "server_error": "tool_error",
+                        "overrides": {"404": "result"}
+                    }
+                }))
+                .send()
+                .await
+                .unwrap();
+            assert!(response.status().is_success());
+            let result = client
+                .call_tool("getPetById", json!({"petId": 404}))
+                .await
+                .unwrap();
+            assert_ne!(result["isError"], true, "{}", result);
+            assert_eq!(result["structuredContent"]["status"], 404);
+            let result = client
+                .call_tool("getPetById", json!({"petId": 500}))
+                .await
+                .unwrap();
+            assert_eq!(result["isError"], true, "{}", result);
+            assert_eq!(result["structuredContent"]["status"], 500);
+
+            let response = http
+                .put(format!("{}/api/endpoint/{}", gateway.base_url, endpoint_id))
+                .json(&json!({"status_mapping": {"overrides": {"999": "result"}}}))
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST);
+
+            gateway.delete_endpoint(endpoint_id).await.unwrap();
+        });
+    }
+}
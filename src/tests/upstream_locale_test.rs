@@ -0,0 +1,94 @@
+#[cfg(test)]
+mod tests {
+    use crate::services::LOCALE_HEADER;
+    use crate::tests::harness::{block_on, MockUpstream, TestGateway};
+    use reqwest::header::{HeaderMap, HeaderValue};
+    use reqwest::StatusCode;
+    use serde_json::json;
+    use uuid::Uuid;
+
+    #[test]
+    #[ignore] // 需要测试数据库
+    fn test_session_locale_is_forwarded_to_upstream() {
+        block_on(async {
+            let upstream = MockUpstream::petstore().await.unwrap();
+            let gateway = TestGateway::start().await.unwrap();
+            let http = reqwest::Client::new();
+            let name = format!("harness-locale-{}", Uuid::new_v4().simple());
+            let endpoint_id = gateway
+                .create_endpoint(&name, &upstream.spec())
+                .await
+                .unwrap();
+            gateway.start_endpoint(endpoint_id).await.unwrap();
+            let accept_language = |index: usize| {
+                upstream.requests()[index]
+                    .headers
+                    .get("accept-language")
+                    .cloned()
+            };
+
+            let mut headers = HeaderMap::new();
+            headers.insert(LOCALE_HEADER, HeaderValue::from_static("zh_CN"));
+            let mut client = gateway
+                .connect_with_headers(endpoint_id, headers)
+                .await
+                .unwrap();
+            let result = client
+                .call_tool("getPetById", json!({"petId": 7}))
+                .await
+                .unwrap();
+            assert_eq!(accept_language(0).as_deref(), Some("zh-CN"));
+            assert_eq!(result["structuredContent"]["_meta"]["locale"], "zh-CN");
+
+            // _locale 优先于会话语言，且不会作为查询参数发出
+            client
+                .call_tool("getPetById", json!({"petId": 7, "_locale": "ja-JP"}))
+                .await
+                .unwrap();
+            assert_eq!(accept_language(1).as_deref(), Some("ja-JP"));
+            assert!(upstream.requests()[1].query.is_empty());
+
+            // 未指定语言的会话使用端点默认语言
+            let url = format!(
+                "{}/api/endpoints/{}/locale-settings",
+                gateway.base_url, endpoint_id
+            );
+            let response = http
+                .put(&url)
+                .json(&json!({"forward": true, "default_locale": "en-US"}))
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let mut plain = gateway.connect(endpoint_id).await.unwrap();
+            plain
+                .call_tool("getPetById", json!({"petId": 7}))
+                .await
+                .unwrap();
+            assert_eq!(accept_language(2).as_deref(), Some("en-US"));
+
+            // 关闭转发后会话语言也不再发送
+            http.put(&url)
+                .json(&json!({"forward": false}))
+                .send()
+                .await
+                .unwrap();
+            let result = client
+                .call_tool("getPetById", json!({"petId": 7}))
+                .await
+                .unwrap();
+            assert_eq!(accept_language(3), None);
+            assert!(result["structuredContent"]["_meta"].get("locale").is_none());
+
+            let response = http
+                .put(&url)
+                .json(&json!({"default_locale": "not a locale"}))
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+            gateway.delete_endpoint(endpoint_id).await.unwrap();
+        });
+    }
+}
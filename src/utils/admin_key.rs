@@ -0,0 +1,97 @@
+use crate::config::AdminConfig;
+use axum::http::{HeaderMap, StatusCode};
+use sha2::{Digest, Sha256};
+use std::sync::OnceLock;
+
+/// 全局管理员凭据配置，启动时设置
+pub static ADMIN_CONFIG: OnceLock<AdminConfig> = OnceLock::new();
+
+pub fn admin_config() -> &'static AdminConfig {
+    ADMIN_CONFIG.get_or_init(AdminConfig::default)
+}
+
+/// 管理员凭据校验失败
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum AdminAccessError {
+    #[error("Admin API key is required")]
+    MissingKey,
+    #[error("Invalid admin API key")]
+    InvalidKey,
+}
+
+impl AdminAccessError {
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            AdminAccessError::MissingKey => StatusCode::UNAUTHORIZED,
+            AdminAccessError::InvalidKey => StatusCode::FORBIDDEN,
+        }
+    }
+}
+
+/// 比较摘要而不是原始 key，避免按前缀逐字节比较泄露耗时差异
+fn digest(key: &str) -> [u8; 32] {
+    Sha256::digest(key.as_bytes()).into()
+}
+
+/// 校验管理员 key；未配置任何 key 时所有请求都被拒绝
+pub fn check_admin_key(
+    config: &AdminConfig,
+    api_key: Option<&str>,
+) -> Result<(), AdminAccessError> {
+    let api_key = api_key
+        .filter(|key| !key.is_empty())
+        .ok_or(AdminAccessError::MissingKey)?;
+    let presented = digest(api_key);
+    let matched = config
+        .api_keys
+        .iter()
+        .filter(|key| !key.is_empty())
+        .fold(false, |matched, key| matched | (digest(key) == presented));
+    if matched {
+        Ok(())
+    } else {
+        Err(AdminAccessError::InvalidKey)
+    }
+}
+
+/// 从请求头读取管理员 key 并校验
+pub fn require_admin(headers: &HeaderMap) -> Result<(), AdminAccessError> {
+    let config = admin_config();
+    let api_key = headers
+        .get(&config.api_key_header)
+        .and_then(|v| v.to_str().ok());
+    check_admin_key(config, api_key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_admin_key() {
+        let config = AdminConfig {
+            api_keys: vec!["root-key".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            check_admin_key(&config, None),
+            Err(AdminAccessError::MissingKey)
+        );
+        assert_eq!(
+            check_admin_key(&config, Some("")),
+            Err(AdminAccessError::MissingKey)
+        );
+        assert_eq!(
+            check_admin_key(&config, Some("root")),
+            Err(AdminAccessError::InvalidKey)
+        );
+        assert_eq!(check_admin_key(&config, Some("root-key")), Ok(()));
+
+        // 未配置 key 时没有请求能通过
+        let disabled = AdminConfig::default();
+        assert_eq!(
+            check_admin_key(&disabled, Some("root-key")),
+            Err(AdminAccessError::InvalidKey)
+        );
+    }
+}
@@ -0,0 +1,229 @@
+use regex::Regex;
+use serde_json::Value;
+
+/// 参数违反工具 inputSchema 中的取值约束，MCP 层映射为 -32602 invalid params
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[error("Invalid argument '{path}': {message} (violates {constraint})")]
+pub struct ArgumentError {
+    pub path: String,
+    pub constraint: String,
+    pub message: String,
+}
+
+/// 校验 enum、const、pattern、数值范围、长度、元素数与 multipleOf，递归检查对象属性与数组元素；
+/// 与值类型不匹配的约束跳过，缺失字段不在此处校验
+pub fn validate_arguments(schema: &Value, arguments: &Value) -> Result<(), ArgumentError> {
+    validate_value(schema, arguments, "")
+}
+
+fn validate_value(schema: &Value, value: &Value, path: &str) -> Result<(), ArgumentError> {
+    let fail = |constraint: &str, message: String| {
+        Err(ArgumentError {
+            path: if path.is_empty() {
+                "arguments".to_string()
+            } else {
+                path.to_string()
+            },
+            constraint: constraint.to_string(),
+            message,
+        })
+    };
+
+    if let Some(values) = schema.get("enum").and_then(Value::as_array) {
+        if !values.contains(value) {
+            return fail(
+                "enum",
+                format!("{} is not one of {}", value, Value::Array(values.clone())),
+            );
+        }
+    }
+    if let Some(expected) = schema.get("const") {
+        if value != expected {
+            return fail("const", format!("{} must equal {}", value, expected));
+        }
+    }
+
+    match value {
+        Value::String(text) => {
+            if let Some(pattern) = schema.get("pattern").and_then(Value::as_str) {
+                // spec 中的非法正则不拦截调用
+                match Regex::new(pattern) {
+                    Ok(regex) if !regex.is_match(text) => {
+                        return fail(
+                            "pattern",
+                            format!("\"{}\" does not match pattern {}", text, pattern),
+                        );
+                    }
+                    Ok(_) => {}
+                    Err(e) => tracing::debug!("Skipping invalid pattern {}: {}", pattern, e),
+                }
+            }
+            let length = text.chars().count() as u64;
+            if let Some(min) = schema.get("minLength").and_then(Value::as_u64) {
+                if length < min {
+                    return fail(
+                        "minLength",
+                        format!("length {} is shorter than {}", length, min),
+                    );
+                }
+            }
+            if let Some(max) = schema.get("maxLength").and_then(Value::as_u64) {
+                if length > max {
+                    return fail(
+                        "maxLength",
+                        format!("length {} is longer than {}", length, max),
+                    );
+                }
+            }
+        }
+        Value::Number(number) => {
+            let Some(v) = number.as_f64() else {
+                return Ok(());
+            };
+            let bound = |key: &str| schema.get(key).and_then(Value::as_f64);
+            if let Some(min) = bound("minimum") {
+                if v < min {
+                    return fail("minimum", format!("{} is less than {}", number, min));
+                }
+            }
+            if let Some(max) = bound("maximum") {
+                if v > max {
+                    return fail("maximum", format!("{} is greater than {}", number, max));
+                }
+            }
+            if let Some(min) = bound("exclusiveMinimum") {
+                if v <= min {
+                    return fail(
+                        "exclusiveMinimum",
+                        format!("{} must be greater than {}", number, min),
+                    );
+                }
+            }
+            if let Some(max) = bound("exclusiveMaximum") {
+                if v >= max {
+                    return fail(
+                        "exclusiveMaximum",
+                        format!("{} must be less than {}", number, max),
+                    );
+                }
+            }
+            if let Some(step) = bound("multipleOf").filter(|step| *step > 0.0) {
+                let quotient = v / step;
+                if (quotient - quotient.round()).abs() > 1e-9 {
+                    return fail(
+                        "multipleOf",
+                        format!("{} is not a multiple of {}", number, step),
+                    );
+                }
+            }
+        }
+        Value::Array(items) => {
+            let count = items.len() as u64;
+            if let Some(min) = schema.get("minItems").and_then(Value::as_u64) {
+                if count < min {
+                    return fail("minItems", format!("{} items is fewer than {}", count, min));
+                }
+            }
+            if let Some(max) = schema.get("maxItems").and_then(Value::as_u64) {
+                if count > max {
+                    return fail("maxItems", format!("{} items is more than {}", count, max));
+                }
+            }
+            if let Some(item_schema) = schema.get("items") {
+                for (i, item) in items.iter().enumerate() {
+                    validate_value(item_schema, item, &format!("{}[{}]", path, i))?;
+                }
+            }
+        }
+        Value::Object(object) => {
+            if let Some(properties) = schema.get("properties").and_then(Value::as_object) {
+                for (name, property_schema) in properties {
+                    if let Some(property) = object.get(name) {
+                        let property_path = if path.is_empty() {
+                            name.clone()
+                        } else {
+                            format!("{}.{}", path, name)
+                        };
+                        validate_value(property_schema, property, &property_path)?;
+                    }
+                }
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "status": {"type": "string", "enum": ["available", "sold"]},
+                "kind": {"type": "string", "const": "pet"},
+                "code": {"type": "string", "pattern": "^[A-Z]{3}$"},
+                "name": {"type": "string", "minLength": 2, "maxLength": 5},
+                "age": {"type": "integer", "minimum": 0, "maximum": 30},
+                "score": {"type": "number", "exclusiveMinimum": 0, "exclusiveMaximum": 1},
+                "quantity": {"type": "integer", "multipleOf": 5},
+                "tags": {
+                    "type": "array",
+                    "minItems": 1,
+                    "maxItems": 2,
+                    "items": {"type": "string", "maxLength": 3}
+                },
+                "owner": {
+                    "type": "object",
+                    "properties": {"level": {"type": "integer", "maximum": 3}}
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn test_valid_arguments_pass() {
+        let arguments = json!({
+            "status": "sold",
+            "kind": "pet",
+            "code": "ABC",
+            "name": "Rex",
+            "age": 3,
+            "score": 0.5,
+            "quantity": 15,
+            "tags": ["a"],
+            "owner": {"level": 2},
+            "extra": "ignored"
+        });
+        assert_eq!(validate_arguments(&schema(), &arguments), Ok(()));
+    }
+
+    #[test]
+    fn test_rejects_each_constraint_class() {
+        let cases = [
+            (json!({"status": "lost"}), "status", "enum"),
+            (json!({"kind": "toy"}), "kind", "const"),
+            (json!({"code": "abc"}), "code", "pattern"),
+            (json!({"name": "R"}), "name", "minLength"),
+            (json!({"name": "Rexxxx"}), "name", "maxLength"),
+            (json!({"age": -1}), "age", "minimum"),
+            (json!({"age": 31}), "age", "maximum"),
+            (json!({"score": 0}), "score", "exclusiveMinimum"),
+            (json!({"score": 1}), "score", "exclusiveMaximum"),
+            (json!({"quantity": 7}), "quantity", "multipleOf"),
+            (json!({"tags": []}), "tags", "minItems"),
+            (json!({"tags": ["a", "b", "c"]}), "tags", "maxItems"),
+            (json!({"tags": ["long"]}), "tags[0]", "maxLength"),
+            (json!({"owner": {"level": 4}}), "owner.level", "maximum"),
+        ];
+        for (arguments, path, constraint) in cases {
+            let error = validate_arguments(&schema(), &arguments).unwrap_err();
+            assert_eq!(error.path, path, "{}", arguments);
+            assert_eq!(error.constraint, constraint, "{}", arguments);
+            assert!(error.to_string().contains(constraint));
+        }
+    }
+}
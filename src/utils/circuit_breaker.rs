@@ -0,0 +1,400 @@
+use crate::config::CircuitBreakerConfig;
+use crate::utils::get_china_time;
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use reqwest::header::HeaderMap;
+use serde::Serialize;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+/// 全局上游熔断状态，启动时由配置初始化
+pub static CIRCUIT_BREAKERS: OnceLock<CircuitBreakers> = OnceLock::new();
+
+pub fn circuit_breakers() -> &'static CircuitBreakers {
+    CIRCUIT_BREAKERS.get_or_init(CircuitBreakers::default)
+}
+
+/// 上游限流暂停的 JSON-RPC 错误码，data 中携带剩余等待时间
+pub const UPSTREAM_PAUSED_CODE: i32 = -32005;
+
+/// 上游主机处于限流暂停期，调用被快速拒绝
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[error("Upstream host {host} is rate limited, retry after {retry_after_ms}ms")]
+pub struct UpstreamPaused {
+    pub host: String,
+    pub retry_after_ms: u64,
+}
+
+impl From<&UpstreamPaused> for rmcp::ErrorData {
+    fn from(paused: &UpstreamPaused) -> Self {
+        rmcp::ErrorData::new(
+            rmcp::model::ErrorCode(UPSTREAM_PAUSED_CODE),
+            paused.to_string(),
+            Some(serde_json::json!({
+                "host": paused.host,
+                "retry_after_ms": paused.retry_after_ms,
+                "retryable": true
+            })),
+        )
+    }
+}
+
+/// 上游主机的熔断状态
+#[derive(Debug, Clone, Serialize)]
+pub struct CircuitBreakerStatus {
+    pub host: String,
+    pub paused_until: DateTime<Utc>,
+    pub remaining_ms: u64,
+    /// 触发暂停的上游状态码
+    pub status: u16,
+    /// 暂停时长来源：Retry-After 或 X-RateLimit-Reset
+    pub source: String,
+}
+
+#[derive(Debug, Clone)]
+struct HostPause {
+    until: Instant,
+    until_at: DateTime<Utc>,
+    status: u16,
+    source: &'static str,
+}
+
+/// 上游响应状态能否重试：429 表示请求未被处理，502/503/504 时上游可能已处理，只有幂等操作可以重试
+pub fn retryable_status(status: u16, idempotent: bool) -> bool {
+    match status {
+        429 => true,
+        502..=504 => idempotent,
+        _ => false,
+    }
+}
+
+/// 按上游主机记录限流暂停：429/503 响应带 Retry-After 或 X-RateLimit-Reset 时暂停该主机，
+/// 到期后自动恢复；暂停时长不超过 max_pause
+pub struct CircuitBreakers {
+    max_pause: Duration,
+    max_retries: u32,
+    retry_backoff: Duration,
+    pauses: DashMap<String, HostPause>,
+}
+
+impl CircuitBreakers {
+    pub fn new(config: &CircuitBreakerConfig) -> Self {
+        Self {
+            max_pause: Duration::from_secs(config.max_pause_secs),
+            max_retries: config.max_retries,
+            retry_backoff: Duration::from_millis(config.retry_backoff_ms),
+            pauses: DashMap::new(),
+        }
+    }
+
+    /// 第 attempt 次重试前的等待时间；不可重试或已达重试上限时返回 None
+    pub fn retry_delay(&self, attempt: u32, retryable: bool) -> Option<Duration> {
+        (retryable && attempt < self.max_retries).then_some(self.retry_backoff)
+    }
+
+    /// 暂停期内返回 UpstreamPaused，已到期的暂停在此清除
+    pub fn check(&self, host: &str) -> std::result::Result<(), UpstreamPaused> {
+        let now = Instant::now();
+        self.pauses.remove_if(host, |_, pause| pause.until <= now);
+        match self.pauses.get(host) {
+            Some(pause) => Err(UpstreamPaused {
+                host: host.to_string(),
+                retry_after_ms: pause.until.saturating_duration_since(now).as_millis() as u64,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// 根据上游响应记录暂停，返回本次暂停时长
+    pub fn record_response(
+        &self,
+        host: &str,
+        status: u16,
+        headers: &HeaderMap,
+    ) -> Option<Duration> {
+        if status != 429 && status != 503 {
+            return None;
+        }
+        let (wait, source) = pause_from_headers(headers, get_china_time())?;
+        let wait = wait.min(self.max_pause);
+        if wait.is_zero() {
+            return None;
+        }
+        tracing::warn!(
+            "Upstream host {} returned {}, pausing calls for {}ms ({})",
+            host,
+            status,
+            wait.as_millis(),
+            source
+        );
+        self.pauses.insert(
+            host.to_string(),
+            HostPause {
+                until: Instant::now() + wait,
+                until_at: get_china_time()
+                    + chrono::Duration::from_std(wait).unwrap_or(chrono::Duration::zero()),
+                status,
+                source,
+            },
+        );
+        Some(wait)
+    }
+
+    /// 当前处于暂停期的主机，按主机名排序
+    pub fn statuses(&self) -> Vec<CircuitBreakerStatus> {
+        let now = Instant::now();
+        self.pauses.retain(|_, pause| pause.until > now);
+        let mut statuses: Vec<CircuitBreakerStatus> = self
+            .pauses
+            .iter()
+            .map(|entry| {
+                let pause = entry.value();
+                CircuitBreakerStatus {
+                    host: entry.key().clone(),
+                    paused_until: pause.until_at,
+                    remaining_ms: pause.until.saturating_duration_since(now).as_millis() as u64,
+                    status: pause.status,
+                    source: pause.source.to_string(),
+                }
+            })
+            .collect();
+        statuses.sort_by(|a, b| a.host.cmp(&b.host));
+        statuses
+    }
+}
+
+impl Default for CircuitBreakers {
+    fn default() -> Self {
+        Self::new(&CircuitBreakerConfig::default())
+    }
+}
+
+/// 熔断状态的主机键：host:port，无法解析时返回 None
+pub fn upstream_host(url: &str) -> Option<String> {
+    let parsed = reqwest::Url::parse(url).ok()?;
+    let host = parsed.host_str()?.to_ascii_lowercase();
+    Some(match parsed.port_or_known_default() {
+        Some(port) => format!("{}:{}", host, port),
+        None => host,
+    })
+}
+
+/// 解析 Retry-After（秒数或 HTTP-date），其次 X-RateLimit-Reset（秒数或 Unix 时间戳）
+fn pause_from_headers(headers: &HeaderMap, now: DateTime<Utc>) -> Option<(Duration, &'static str)> {
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(str::trim)
+    };
+    let until = |at: DateTime<Utc>| (at - now).to_std().unwrap_or(Duration::ZERO);
+
+    if let Some(value) = header("retry-after") {
+        if let Ok(secs) = value.parse::<u64>() {
+            return Some((Duration::from_secs(secs), "Retry-After"));
+        }
+        if let Ok(at) = DateTime::parse_from_rfc2822(value) {
+            return Some((until(at.with_timezone(&Utc)), "Retry-After"));
+        }
+    }
+    if let Some(value) = header("x-ratelimit-reset") {
+        let secs = value.parse::<u64>().ok()?;
+        // 大于 10 亿视为 Unix 时间戳，否则为剩余秒数
+        let wait = if secs >= 1_000_000_000 {
+            until(DateTime::from_timestamp(secs as i64, 0)?)
+        } else {
+            Duration::from_secs(secs)
+        };
+        return Some((wait, "X-RateLimit-Reset"));
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.insert(*name, HeaderValue::from_str(value).unwrap());
+        }
+        headers
+    }
+
+    fn breakers(max_pause_secs: u64) -> CircuitBreakers {
+        CircuitBreakers::new(&CircuitBreakerConfig {
+            max_pause_secs,
+            ..Default::default()
+        })
+    }
+
+    /// 启动总是返回 429 的上游，返回其地址
+    async fn spawn_rate_limited_upstream(retry_after: &'static str) -> String {
+        let app = axum::Router::new().route(
+            "/pets",
+            axum::routing::get(move || async move {
+                (
+                    axum::http::StatusCode::TOO_MANY_REQUESTS,
+                    [("Retry-After", retry_after)],
+                    "slow down",
+                )
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        format!("http://{}/pets", addr)
+    }
+
+    #[test]
+    fn test_pause_from_headers() {
+        let now = DateTime::parse_from_rfc3339("2024-05-01T10:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(
+            pause_from_headers(&headers(&[("retry-after", "2")]), now),
+            Some((Duration::from_secs(2), "Retry-After"))
+        );
+        assert_eq!(
+            pause_from_headers(
+                &headers(&[("retry-after", "Wed, 01 May 2024 10:00:30 GMT")]),
+                now
+            ),
+            Some((Duration::from_secs(30), "Retry-After"))
+        );
+        assert_eq!(
+            pause_from_headers(&headers(&[("x-ratelimit-reset", "15")]), now),
+            Some((Duration::from_secs(15), "X-RateLimit-Reset"))
+        );
+        assert_eq!(
+            pause_from_headers(
+                &headers(&[("x-ratelimit-reset", &(now.timestamp() + 5).to_string())]),
+                now
+            ),
+            Some((Duration::from_secs(5), "X-RateLimit-Reset"))
+        );
+        // 过去的时间不暂停
+        assert_eq!(
+            pause_from_headers(
+                &headers(&[("retry-after", "Wed, 01 May 2024 09:00:00 GMT")]),
+                now
+            ),
+            Some((Duration::ZERO, "Retry-After"))
+        );
+        assert_eq!(
+            pause_from_headers(&headers(&[("retry-after", "soon")]), now),
+            None
+        );
+        assert_eq!(pause_from_headers(&HeaderMap::new(), now), None);
+    }
+
+    #[test]
+    fn test_retry_decisions() {
+        for idempotent in [true, false] {
+            assert!(retryable_status(429, idempotent));
+            assert!(!retryable_status(500, idempotent));
+            assert!(!retryable_status(404, idempotent));
+        }
+        for status in [502, 503, 504] {
+            assert!(retryable_status(status, true));
+            assert!(!retryable_status(status, false));
+        }
+
+        // 默认不重试
+        assert_eq!(breakers(300).retry_delay(0, true), None);
+        let breakers = CircuitBreakers::new(&CircuitBreakerConfig {
+            max_retries: 2,
+            retry_backoff_ms: 50,
+            ..Default::default()
+        });
+        assert_eq!(
+            breakers.retry_delay(0, true),
+            Some(Duration::from_millis(50))
+        );
+        assert_eq!(
+            breakers.retry_delay(1, true),
+            Some(Duration::from_millis(50))
+        );
+        assert_eq!(breakers.retry_delay(2, true), None);
+        assert_eq!(breakers.retry_delay(0, false), None);
+    }
+
+    #[test]
+    fn test_upstream_host() {
+        assert_eq!(
+            upstream_host("https://API.example.com/v1/pets").as_deref(),
+            Some("api.example.com:443")
+        );
+        assert_eq!(
+            upstream_host("http://127.0.0.1:8080/pets").as_deref(),
+            Some("127.0.0.1:8080")
+        );
+        assert_eq!(upstream_host("not a url"), None);
+    }
+
+    #[test]
+    fn test_only_rate_limit_statuses_pause() {
+        let breakers = breakers(300);
+        let retry_after = headers(&[("retry-after", "2")]);
+        assert_eq!(breakers.record_response("a:80", 500, &retry_after), None);
+        assert_eq!(breakers.record_response("a:80", 200, &retry_after), None);
+        assert_eq!(
+            breakers.record_response("a:80", 429, &HeaderMap::new()),
+            None
+        );
+        assert!(breakers.check("a:80").is_ok());
+        assert_eq!(
+            breakers.record_response("a:80", 503, &retry_after),
+            Some(Duration::from_secs(2))
+        );
+        assert!(breakers.check("a:80").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_retry_after_pauses_host_until_deadline() {
+        let url = spawn_rate_limited_upstream("2").await;
+        let host = upstream_host(&url).unwrap();
+        let breakers = breakers(300);
+
+        let response = reqwest::get(&url).await.unwrap();
+        let pause = breakers.record_response(&host, response.status().as_u16(), response.headers());
+        assert_eq!(pause, Some(Duration::from_secs(2)));
+
+        // 暂停期内立即失败并给出剩余等待时间
+        let paused = breakers.check(&host).unwrap_err();
+        assert_eq!(paused.host, host);
+        assert!(paused.retry_after_ms > 1500 && paused.retry_after_ms <= 2000);
+        let statuses = breakers.statuses();
+        assert_eq!(statuses.len(), 1);
+        assert_eq!(statuses[0].status, 429);
+        assert_eq!(statuses[0].source, "Retry-After");
+
+        let error = rmcp::ErrorData::from(&paused);
+        assert_eq!(error.code.0, UPSTREAM_PAUSED_CODE);
+        assert_eq!(error.data.unwrap()["retry_after_ms"], paused.retry_after_ms);
+
+        // 到期后自动恢复
+        tokio::time::sleep(Duration::from_millis(2100)).await;
+        assert!(breakers.check(&host).is_ok());
+        assert!(breakers.statuses().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_absurd_retry_after_is_capped() {
+        let url = spawn_rate_limited_upstream("86400000").await;
+        let host = upstream_host(&url).unwrap();
+        let breakers = breakers(1);
+
+        let response = reqwest::get(&url).await.unwrap();
+        let pause = breakers.record_response(&host, response.status().as_u16(), response.headers());
+        assert_eq!(pause, Some(Duration::from_secs(1)));
+        assert!(breakers.check(&host).unwrap_err().retry_after_ms <= 1000);
+
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+        assert!(breakers.check(&host).is_ok());
+    }
+}
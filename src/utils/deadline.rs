@@ -0,0 +1,225 @@
+use crate::config::DeadlineConfig;
+use serde_json::Value;
+use std::future::Future;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+/// 客户端声明截止时间（毫秒）的参数，不参与校验也不发往上游
+pub const DEADLINE_ARGUMENT: &str = "_mcp_deadline_ms";
+/// 请求 _meta 中的截止时间（毫秒）
+pub const DEADLINE_META_KEY: &str = "timeout";
+/// 工具调用超过截止时间的 JSON-RPC 错误码
+pub const DEADLINE_EXCEEDED_CODE: i32 = -32034;
+
+pub static DEADLINE_CONFIG: OnceLock<DeadlineConfig> = OnceLock::new();
+
+pub fn deadline_config() -> &'static DeadlineConfig {
+    DEADLINE_CONFIG.get_or_init(DeadlineConfig::default)
+}
+
+tokio::task_local! {
+    /// 当前工具调用的截止时刻，上游请求据此传递剩余预算
+    static CALL_DEADLINE: Instant;
+}
+
+/// 截止时间的来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeadlineOrigin {
+    /// 客户端声明的截止时间
+    Client,
+    /// 服务端的上游调用超时
+    Server,
+}
+
+impl DeadlineOrigin {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeadlineOrigin::Client => "client",
+            DeadlineOrigin::Server => "server",
+        }
+    }
+}
+
+impl std::fmt::Display for DeadlineOrigin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// 工具调用在截止时间内未完成，上游请求已被丢弃
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[error("Tool call exceeded the {origin} deadline of {deadline_ms}ms")]
+pub struct DeadlineExceeded {
+    pub deadline_ms: u64,
+    pub origin: DeadlineOrigin,
+}
+
+impl From<&DeadlineExceeded> for rmcp::ErrorData {
+    fn from(exceeded: &DeadlineExceeded) -> Self {
+        rmcp::ErrorData::new(
+            rmcp::model::ErrorCode(DEADLINE_EXCEEDED_CODE),
+            exceeded.to_string(),
+            Some(serde_json::json!({
+                "deadline_ms": exceeded.deadline_ms,
+                "origin": exceeded.origin.as_str(),
+                "client_deadline": exceeded.origin == DeadlineOrigin::Client
+            })),
+        )
+    }
+}
+
+/// 单次工具调用的时间预算
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CallDeadline {
+    pub budget: Duration,
+    pub origin: DeadlineOrigin,
+}
+
+impl CallDeadline {
+    /// 客户端截止时间限制在 [min_ms, max_ms] 内，与服务端超时取较小者；两者都没有时返回 None
+    pub fn resolve(client_ms: Option<u64>, config: &DeadlineConfig) -> Option<Self> {
+        let client = client_ms.map(|ms| ms.clamp(config.min_ms, config.max_ms.max(config.min_ms)));
+        let server = Some(config.upstream_timeout_ms).filter(|ms| *ms > 0);
+        let (ms, origin) = match (client, server) {
+            (Some(client), Some(server)) if server < client => (server, DeadlineOrigin::Server),
+            (Some(client), _) => (client, DeadlineOrigin::Client),
+            (None, Some(server)) => (server, DeadlineOrigin::Server),
+            (None, None) => return None,
+        };
+        Some(Self {
+            budget: Duration::from_millis(ms),
+            origin,
+        })
+    }
+
+    pub fn exceeded(&self) -> DeadlineExceeded {
+        DeadlineExceeded {
+            deadline_ms: self.budget.as_millis() as u64,
+            origin: self.origin,
+        }
+    }
+}
+
+fn deadline_ms(value: &Value) -> Option<u64> {
+    value
+        .as_u64()
+        .or_else(|| value.as_f64().filter(|ms| *ms > 0.0).map(|ms| ms as u64))
+}
+
+/// 取出参数中的 _mcp_deadline_ms，与 _meta.timeout 同时声明时取较小者
+pub fn take_client_deadline(arguments: &mut Value, meta_timeout: Option<&Value>) -> Option<u64> {
+    let argument = arguments
+        .as_object_mut()
+        .and_then(|map| map.remove(DEADLINE_ARGUMENT))
+        .as_ref()
+        .and_then(deadline_ms);
+    let meta = meta_timeout.and_then(deadline_ms);
+    match (argument, meta) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+/// 在截止时刻的作用域内执行 work，其中的上游请求可读取剩余预算
+pub async fn with_call_deadline<F: Future>(deadline: Option<Instant>, work: F) -> F::Output {
+    match deadline {
+        Some(at) => CALL_DEADLINE.scope(at, work).await,
+        None => work.await,
+    }
+}
+
+/// 当前调用的剩余预算（毫秒），不在截止时间作用域内时返回 None
+pub fn remaining_budget_ms() -> Option<u64> {
+    CALL_DEADLINE
+        .try_with(|at| at.saturating_duration_since(Instant::now()).as_millis() as u64)
+        .ok()
+}
+
+/// 通过配置的请求头向上游传递剩余预算，便于上游提前放弃
+pub fn propagate_deadline(request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+    let header = &deadline_config().header;
+    match remaining_budget_ms() {
+        Some(remaining) if !header.is_empty() => {
+            request.header(header.as_str(), remaining.to_string())
+        }
+        _ => request,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn config(upstream_timeout_ms: u64) -> DeadlineConfig {
+        DeadlineConfig {
+            min_ms: 100,
+            max_ms: 10_000,
+            upstream_timeout_ms,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_resolve_takes_smaller_deadline() {
+        let client = |ms| CallDeadline::resolve(Some(ms), &config(2_000)).unwrap();
+        assert_eq!(client(500).budget, Duration::from_millis(500));
+        assert_eq!(client(500).origin, DeadlineOrigin::Client);
+        assert_eq!(client(5_000).budget, Duration::from_millis(2_000));
+        assert_eq!(client(5_000).origin, DeadlineOrigin::Server);
+        // 限制在 min_ms..max_ms 内
+        assert_eq!(client(1).budget, Duration::from_millis(100));
+        let unlimited = CallDeadline::resolve(Some(60_000), &config(0)).unwrap();
+        assert_eq!(unlimited.budget, Duration::from_millis(10_000));
+
+        let server = CallDeadline::resolve(None, &config(2_000)).unwrap();
+        assert_eq!(server.origin, DeadlineOrigin::Server);
+        assert_eq!(CallDeadline::resolve(None, &config(0)), None);
+    }
+
+    #[test]
+    fn test_take_client_deadline() {
+        let mut arguments = json!({"petId": 1, "_mcp_deadline_ms": 800});
+        assert_eq!(take_client_deadline(&mut arguments, None), Some(800));
+        assert_eq!(arguments, json!({"petId": 1}));
+
+        let mut arguments = json!({"_mcp_deadline_ms": 800});
+        assert_eq!(
+            take_client_deadline(&mut arguments, Some(&json!(500))),
+            Some(500)
+        );
+        let mut arguments = Value::Null;
+        assert_eq!(
+            take_client_deadline(&mut arguments, Some(&json!(1500.0))),
+            Some(1500)
+        );
+        let mut arguments = json!({"_mcp_deadline_ms": "soon"});
+        assert_eq!(take_client_deadline(&mut arguments, None), None);
+        assert_eq!(arguments, json!({}));
+    }
+
+    #[tokio::test]
+    async fn test_remaining_budget_in_scope() {
+        assert_eq!(remaining_budget_ms(), None);
+        let at = Instant::now() + Duration::from_secs(2);
+        let remaining = with_call_deadline(Some(at), async { remaining_budget_ms() })
+            .await
+            .unwrap();
+        assert!(remaining > 1_500 && remaining <= 2_000, "{}", remaining);
+    }
+
+    #[test]
+    fn test_exceeded_error_data() {
+        let exceeded = CallDeadline {
+            budget: Duration::from_millis(500),
+            origin: DeadlineOrigin::Client,
+        }
+        .exceeded();
+        let error = rmcp::ErrorData::from(&exceeded);
+        assert_eq!(error.code.0, DEADLINE_EXCEEDED_CODE);
+        assert_eq!(
+            error.data.unwrap(),
+            json!({"deadline_ms": 500, "origin": "client", "client_deadline": true})
+        );
+    }
+}
@@ -0,0 +1,438 @@
+use crate::config::DnsConfig;
+use crate::utils::{get_china_time, upstream_guard};
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use hickory_resolver::config::{ResolverConfig, ResolverOpts};
+use hickory_resolver::TokioAsyncResolver;
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+
+/// 全局上游解析缓存，启动时由配置初始化
+pub static DNS_RESOLVER: OnceLock<Arc<CachingResolver>> = OnceLock::new();
+
+pub fn dns_resolver() -> &'static Arc<CachingResolver> {
+    DNS_RESOLVER.get_or_init(|| Arc::new(CachingResolver::from_config(&DnsConfig::default())))
+}
+
+/// 静态解析：小写主机名 → IP，优先于缓存与 DNS
+pub type HostOverrides = HashMap<String, IpAddr>;
+
+/// 主机名解析失败
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[error("DNS resolution failed for {host}: {error}")]
+pub struct DnsFailure {
+    pub host: String,
+    pub error: String,
+}
+
+/// 一次成功的查询，ttl 为记录剩余的有效期
+#[derive(Debug, Clone)]
+pub struct DnsAnswer {
+    pub addresses: Vec<IpAddr>,
+    pub ttl: Duration,
+}
+
+/// 实际的 DNS 查询，生产环境为 hickory，测试中替换为桩
+#[async_trait::async_trait]
+pub trait DnsLookup: Send + Sync {
+    async fn lookup(&self, host: &str) -> Result<DnsAnswer, String>;
+}
+
+pub struct HickoryLookup {
+    resolver: TokioAsyncResolver,
+}
+
+impl HickoryLookup {
+    /// 读取系统解析配置（/etc/resolv.conf 与 hosts），失败时使用默认上游。
+    /// 失败结果由 CachingResolver 按 negative_ttl_secs 缓存，hickory 自身不缓存
+    pub fn from_system_conf() -> Self {
+        let (config, mut opts) =
+            hickory_resolver::system_conf::read_system_conf().unwrap_or_else(|e| {
+                tracing::warn!("Failed to read system DNS config, using defaults: {}", e);
+                (ResolverConfig::default(), ResolverOpts::default())
+            });
+        opts.negative_max_ttl = Some(Duration::ZERO);
+        Self {
+            resolver: TokioAsyncResolver::tokio(config, opts),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl DnsLookup for HickoryLookup {
+    async fn lookup(&self, host: &str) -> Result<DnsAnswer, String> {
+        let lookup = self
+            .resolver
+            .lookup_ip(host)
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(DnsAnswer {
+            ttl: lookup
+                .valid_until()
+                .saturating_duration_since(Instant::now()),
+            addresses: lookup.iter().collect(),
+        })
+    }
+}
+
+/// 主机的解析统计；lookups 含命中缓存的解析
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct HostDnsStats {
+    pub host: String,
+    pub lookups: u64,
+    pub cache_hits: u64,
+    pub failures: u64,
+    /// 最近一次成功解析的地址
+    pub last_addresses: Vec<IpAddr>,
+    pub last_error: Option<String>,
+    pub last_lookup_at: Option<DateTime<Utc>>,
+}
+
+struct CachedAnswer {
+    result: Result<Vec<IpAddr>, String>,
+    expires: Instant,
+}
+
+/// 带 TTL 限制与失败缓存的解析器，所有上游客户端共享缓存与统计
+pub struct CachingResolver {
+    lookup: Arc<dyn DnsLookup>,
+    min_ttl: Duration,
+    max_ttl: Duration,
+    negative_ttl: Duration,
+    cache: DashMap<String, CachedAnswer>,
+    stats: DashMap<String, HostDnsStats>,
+}
+
+impl CachingResolver {
+    pub fn new(lookup: Arc<dyn DnsLookup>, config: &DnsConfig) -> Self {
+        Self {
+            lookup,
+            min_ttl: Duration::from_secs(config.min_ttl_secs),
+            max_ttl: Duration::from_secs(config.max_ttl_secs),
+            negative_ttl: Duration::from_secs(config.negative_ttl_secs),
+            cache: DashMap::new(),
+            stats: DashMap::new(),
+        }
+    }
+
+    pub fn from_config(config: &DnsConfig) -> Self {
+        Self::new(Arc::new(HickoryLookup::from_system_conf()), config)
+    }
+
+    /// 解析主机名，IP 字面量直接返回；缓存未过期时不再查询 DNS
+    pub async fn resolve(&self, host: &str) -> Result<Vec<IpAddr>, DnsFailure> {
+        let host = host.to_ascii_lowercase();
+        if let Ok(ip) = host.parse::<IpAddr>() {
+            return Ok(vec![ip]);
+        }
+        let cached = self
+            .cache
+            .get(&host)
+            .filter(|cached| cached.expires > Instant::now())
+            .map(|cached| cached.result.clone());
+        let hit = cached.is_some();
+        let result = match cached {
+            Some(result) => result,
+            None => {
+                let (result, ttl) = match self.lookup.lookup(&host).await {
+                    Ok(answer) if !answer.addresses.is_empty() => (
+                        Ok(answer.addresses),
+                        answer.ttl.max(self.min_ttl).min(self.max_ttl),
+                    ),
+                    Ok(_) => (Err("no addresses found".to_string()), self.negative_ttl),
+                    Err(e) => (Err(e), self.negative_ttl),
+                };
+                if !ttl.is_zero() {
+                    self.cache.insert(
+                        host.clone(),
+                        CachedAnswer {
+                            result: result.clone(),
+                            expires: Instant::now() + ttl,
+                        },
+                    );
+                }
+                result
+            }
+        };
+        self.record(&host, &result, hit);
+        result.map_err(|error| DnsFailure { host, error })
+    }
+
+    /// 静态解析优先，不计入统计
+    pub async fn resolve_with(
+        &self,
+        host: &str,
+        overrides: &HostOverrides,
+    ) -> Result<Vec<IpAddr>, DnsFailure> {
+        match overrides.get(&host.to_ascii_lowercase()) {
+            Some(ip) => Ok(vec![*ip]),
+            None => self.resolve(host).await,
+        }
+    }
+
+    fn record(&self, host: &str, result: &Result<Vec<IpAddr>, String>, hit: bool) {
+        let mut stats = self
+            .stats
+            .entry(host.to_string())
+            .or_insert_with(|| HostDnsStats {
+                host: host.to_string(),
+                ..Default::default()
+            });
+        stats.lookups += 1;
+        if hit {
+            stats.cache_hits += 1;
+        }
+        match result {
+            Ok(addresses) => {
+                stats.last_addresses = addresses.clone();
+                stats.last_error = None;
+            }
+            Err(e) => {
+                stats.failures += 1;
+                stats.last_error = Some(e.clone());
+            }
+        }
+        stats.last_lookup_at = Some(get_china_time());
+    }
+
+    /// 各主机的解析统计，按主机名排序
+    pub fn stats(&self) -> Vec<HostDnsStats> {
+        let mut stats: Vec<HostDnsStats> = self.stats.iter().map(|s| s.clone()).collect();
+        stats.sort_by(|a, b| a.host.cmp(&b.host));
+        stats
+    }
+}
+
+/// 接入 reqwest 的解析器；返回的端口为 0，由 reqwest 按 URL 替换
+#[derive(Clone)]
+pub struct UpstreamResolver {
+    resolver: Arc<CachingResolver>,
+    overrides: Arc<HostOverrides>,
+}
+
+impl UpstreamResolver {
+    pub fn new(resolver: Arc<CachingResolver>, overrides: HostOverrides) -> Self {
+        Self {
+            resolver,
+            overrides: Arc::new(overrides),
+        }
+    }
+}
+
+impl Resolve for UpstreamResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let this = self.clone();
+        Box::pin(async move {
+            let addresses = this
+                .resolver
+                .resolve_with(name.as_str(), &this.overrides)
+                .await?;
+            // 重定向到主机名时在此检查解析出的地址
+            upstream_guard().check_addresses(name.as_str(), &addresses)?;
+            let addrs: Addrs = Box::new(addresses.into_iter().map(|ip| SocketAddr::new(ip, 0)));
+            Ok(addrs)
+        })
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod stub {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// 可编程的解析桩：按主机名返回预设结果并记录查询次数
+    #[derive(Default)]
+    pub struct StubLookup {
+        answers: Mutex<HashMap<String, Result<DnsAnswer, String>>>,
+        calls: Mutex<HashMap<String, usize>>,
+    }
+
+    impl StubLookup {
+        pub fn answer(&self, host: &str, addresses: &[&str], ttl: Duration) {
+            let addresses = addresses.iter().map(|a| a.parse().unwrap()).collect();
+            self.answers
+                .lock()
+                .unwrap()
+                .insert(host.to_string(), Ok(DnsAnswer { addresses, ttl }));
+        }
+
+        pub fn fail(&self, host: &str, error: &str) {
+            self.answers
+                .lock()
+                .unwrap()
+                .insert(host.to_string(), Err(error.to_string()));
+        }
+
+        pub fn calls(&self, host: &str) -> usize {
+            self.calls.lock().unwrap().get(host).copied().unwrap_or(0)
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl DnsLookup for StubLookup {
+        async fn lookup(&self, host: &str) -> Result<DnsAnswer, String> {
+            *self
+                .calls
+                .lock()
+                .unwrap()
+                .entry(host.to_string())
+                .or_default() += 1;
+            self.answers
+                .lock()
+                .unwrap()
+                .get(host)
+                .cloned()
+                .unwrap_or_else(|| Err(format!("no record found for {}", host)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::stub::StubLookup;
+    use super::*;
+
+    fn stub_resolver(config: DnsConfig) -> (Arc<StubLookup>, CachingResolver) {
+        let stub = Arc::new(StubLookup::default());
+        let resolver = CachingResolver::new(stub.clone(), &config);
+        (stub, resolver)
+    }
+
+    #[tokio::test]
+    async fn test_answers_cached_within_clamped_ttl() {
+        let (stub, resolver) = stub_resolver(DnsConfig {
+            min_ttl_secs: 0,
+            max_ttl_secs: 300,
+            negative_ttl_secs: 0,
+        });
+        stub.answer("api.internal", &["10.0.0.1"], Duration::from_secs(60));
+        for _ in 0..3 {
+            assert_eq!(
+                resolver.resolve("API.internal").await.unwrap(),
+                vec!["10.0.0.1".parse::<IpAddr>().unwrap()]
+            );
+        }
+        assert_eq!(stub.calls("api.internal"), 1);
+
+        // 记录 TTL 为 0 且 min_ttl_secs 为 0 时不缓存
+        stub.answer("short.internal", &["10.0.0.2"], Duration::ZERO);
+        resolver.resolve("short.internal").await.unwrap();
+        resolver.resolve("short.internal").await.unwrap();
+        assert_eq!(stub.calls("short.internal"), 2);
+
+        let stats = resolver.stats();
+        assert_eq!(stats[0].host, "api.internal");
+        assert_eq!(stats[0].lookups, 3);
+        assert_eq!(stats[0].cache_hits, 2);
+        assert_eq!(stats[0].failures, 0);
+        assert_eq!(
+            stats[0].last_addresses,
+            vec!["10.0.0.1".parse::<IpAddr>().unwrap()]
+        );
+        assert_eq!(stats[1].cache_hits, 0);
+    }
+
+    #[tokio::test]
+    async fn test_ttl_clamps() {
+        // 记录 TTL 为 0，min_ttl_secs 仍保证缓存
+        let (stub, resolver) = stub_resolver(DnsConfig {
+            min_ttl_secs: 60,
+            max_ttl_secs: 300,
+            negative_ttl_secs: 0,
+        });
+        stub.answer("api.internal", &["10.0.0.1"], Duration::ZERO);
+        resolver.resolve("api.internal").await.unwrap();
+        resolver.resolve("api.internal").await.unwrap();
+        assert_eq!(stub.calls("api.internal"), 1);
+
+        // max_ttl_secs 为 0 时不缓存，记录 TTL 再长也每次查询
+        let (stub, resolver) = stub_resolver(DnsConfig {
+            min_ttl_secs: 5,
+            max_ttl_secs: 0,
+            negative_ttl_secs: 0,
+        });
+        stub.answer("api.internal", &["10.0.0.1"], Duration::from_secs(3600));
+        resolver.resolve("api.internal").await.unwrap();
+        resolver.resolve("api.internal").await.unwrap();
+        assert_eq!(stub.calls("api.internal"), 2);
+    }
+
+    #[tokio::test]
+    async fn test_failures_negatively_cached() {
+        let (stub, resolver) = stub_resolver(DnsConfig::default());
+        stub.fail("flaky.internal", "request timed out");
+        let error = resolver.resolve("flaky.internal").await.unwrap_err();
+        assert_eq!(
+            error,
+            DnsFailure {
+                host: "flaky.internal".to_string(),
+                error: "request timed out".to_string(),
+            }
+        );
+
+        // 失败缓存期内 DNS 恢复也不会立即生效
+        stub.answer("flaky.internal", &["10.0.0.3"], Duration::from_secs(60));
+        assert!(resolver.resolve("flaky.internal").await.is_err());
+        assert_eq!(stub.calls("flaky.internal"), 1);
+
+        let stats = resolver.stats();
+        assert_eq!(stats[0].lookups, 2);
+        assert_eq!(stats[0].cache_hits, 1);
+        assert_eq!(stats[0].failures, 2);
+        assert_eq!(stats[0].last_error.as_deref(), Some("request timed out"));
+
+        // 不缓存失败时下一次重新查询
+        let (stub, resolver) = stub_resolver(DnsConfig {
+            negative_ttl_secs: 0,
+            ..DnsConfig::default()
+        });
+        stub.fail("flaky.internal", "request timed out");
+        assert!(resolver.resolve("flaky.internal").await.is_err());
+        stub.answer("flaky.internal", &["10.0.0.3"], Duration::from_secs(60));
+        assert!(resolver.resolve("flaky.internal").await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_override_takes_precedence() {
+        let (stub, resolver) = stub_resolver(DnsConfig::default());
+        stub.answer("api.internal", &["10.0.0.1"], Duration::from_secs(60));
+        stub.fail("broken.internal", "SERVFAIL");
+        let overrides: HostOverrides = [
+            ("api.internal".to_string(), "192.168.1.10".parse().unwrap()),
+            (
+                "broken.internal".to_string(),
+                "192.168.1.11".parse().unwrap(),
+            ),
+        ]
+        .into();
+
+        assert_eq!(
+            resolver
+                .resolve_with("Api.Internal", &overrides)
+                .await
+                .unwrap(),
+            vec!["192.168.1.10".parse::<IpAddr>().unwrap()]
+        );
+        assert!(resolver
+            .resolve_with("broken.internal", &overrides)
+            .await
+            .is_ok());
+        assert_eq!(stub.calls("api.internal"), 0);
+        assert_eq!(stub.calls("broken.internal"), 0);
+
+        // 未配置静态解析的主机照常查询
+        assert!(resolver
+            .resolve_with("other.internal", &overrides)
+            .await
+            .is_err());
+        assert_eq!(stub.calls("other.internal"), 1);
+
+        // IP 字面量不查询也不统计
+        assert!(resolver.resolve("10.1.2.3").await.is_ok());
+        assert!(resolver.stats().iter().all(|s| s.host != "10.1.2.3"));
+    }
+}
@@ -0,0 +1,605 @@
+use crate::models::{ActiveFault, FaultKind, FaultList, FaultSpec};
+use crate::utils::get_china_time;
+use anyhow::{anyhow, Result};
+use dashmap::DashMap;
+use rmcp::model::{CallToolResult, Content, ErrorCode};
+use serde_json::{json, Value};
+use std::future::Future;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+/// 全局故障注入器，只能由启动参数开启，默认关闭
+pub static FAULT_INJECTOR: OnceLock<FaultInjector> = OnceLock::new();
+
+pub fn fault_injector() -> &'static FaultInjector {
+    FAULT_INJECTOR.get_or_init(|| FaultInjector::new(false))
+}
+
+/// 开启故障注入的启动参数；不提供配置项，避免被持久化到配置文件或环境变量
+pub const FAULT_INJECTION_FLAG: &str = "--enable-fault-injection";
+
+/// 工具结果与错误 data 中标记注入故障的键
+pub const FAULT_META_KEY: &str = "fault_injection";
+
+pub const MAX_FAULT_TTL_SECS: u64 = 3600;
+pub const MAX_FAULT_LATENCY_MS: u64 = 60_000;
+
+/// 进程启动时未开启故障注入
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[error("Fault injection is disabled, restart the gateway with --enable-fault-injection")]
+pub struct FaultInjectionDisabled;
+
+/// 会话已被 terminate_session 故障终止
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[error("Session terminated by fault {fault_id}")]
+pub struct SessionTerminated {
+    pub fault_id: Uuid,
+}
+
+impl From<&SessionTerminated> for rmcp::ErrorData {
+    fn from(terminated: &SessionTerminated) -> Self {
+        rmcp::ErrorData::invalid_request(
+            terminated.to_string(),
+            Some(json!({
+                FAULT_META_KEY: [{
+                    "fault_id": terminated.fault_id,
+                    "type": "terminate_session"
+                }]
+            })),
+        )
+    }
+}
+
+/// 一次调用要注入的故障，由命中的全部故障汇总而来
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FaultPlan {
+    pub delay: Option<Duration>,
+    pub error: Option<(i32, String)>,
+    pub truncate: Option<usize>,
+    /// 丢弃结果，最长挂起到故障到期
+    pub drop_for: Option<Duration>,
+    /// 本次调用后会话被终止
+    pub terminate: bool,
+    /// 写入 _meta.fault_injection 的标记
+    pub tags: Vec<Value>,
+}
+
+impl FaultPlan {
+    pub fn is_empty(&self) -> bool {
+        self.tags.is_empty()
+    }
+}
+
+struct FaultEntry {
+    fault: ActiveFault,
+    expires: Instant,
+}
+
+/// 按端点或工具注入故障，故障只保存在内存中，到期自动失效
+pub struct FaultInjector {
+    enabled: bool,
+    faults: DashMap<Uuid, FaultEntry>,
+    /// (故障, 会话) → 已发生的调用次数
+    session_calls: DashMap<(Uuid, String), u32>,
+    /// 已终止的会话 → 触发终止的故障
+    terminated: DashMap<String, Uuid>,
+}
+
+impl FaultInjector {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            faults: DashMap::new(),
+            session_calls: DashMap::new(),
+            terminated: DashMap::new(),
+        }
+    }
+
+    /// 启动参数中包含 --enable-fault-injection 时开启
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> Self {
+        Self::new(args.into_iter().any(|arg| arg == FAULT_INJECTION_FLAG))
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn add(&self, spec: FaultSpec) -> Result<ActiveFault> {
+        if !self.enabled {
+            return Err(FaultInjectionDisabled.into());
+        }
+        validate_fault(&spec)?;
+        let ttl = Duration::from_secs(spec.ttl_secs);
+        let now = get_china_time();
+        let fault = ActiveFault {
+            id: Uuid::new_v4(),
+            spec,
+            created_at: now,
+            expires_at: now + chrono::Duration::from_std(ttl)?,
+            injected: 0,
+        };
+        tracing::warn!(
+            "Fault {} ({}) enabled on endpoint {} tool {} for {}s",
+            fault.id,
+            fault.spec.kind.as_str(),
+            fault.spec.endpoint_id,
+            fault.spec.tool.as_deref().unwrap_or("*"),
+            fault.spec.ttl_secs
+        );
+        self.faults.insert(
+            fault.id,
+            FaultEntry {
+                fault: fault.clone(),
+                expires: Instant::now() + ttl,
+            },
+        );
+        Ok(fault)
+    }
+
+    pub fn remove(&self, id: Uuid) -> Option<ActiveFault> {
+        let (_, entry) = self.faults.remove(&id)?;
+        self.session_calls
+            .retain(|(fault_id, _), _| *fault_id != id);
+        tracing::warn!("Fault {} disabled", id);
+        Some(entry.fault)
+    }
+
+    /// 生效中的故障，按创建时间排序
+    pub fn list(&self) -> FaultList {
+        self.prune();
+        let mut faults: Vec<ActiveFault> = self
+            .faults
+            .iter()
+            .map(|entry| entry.fault.clone())
+            .collect();
+        faults.sort_by_key(|fault| fault.created_at);
+        FaultList {
+            enabled: self.enabled,
+            faults,
+        }
+    }
+
+    /// 汇总命中 endpoint_id/tool 的故障并计数；每个注入的故障记录一条警告日志
+    pub fn plan(&self, endpoint_id: Uuid, tool: &str, session: &str) -> FaultPlan {
+        let mut plan = FaultPlan::default();
+        if !self.enabled || self.faults.is_empty() {
+            return plan;
+        }
+        self.prune();
+        let now = Instant::now();
+        for mut entry in self.faults.iter_mut() {
+            let entry = entry.value_mut();
+            let fault = &mut entry.fault;
+            if fault.spec.endpoint_id != endpoint_id
+                || fault.spec.tool.as_deref().is_some_and(|t| t != tool)
+            {
+                continue;
+            }
+            let mut tag = match &fault.spec.kind {
+                // 会话调用次数不受 percentage 影响
+                FaultKind::TerminateSession { after_calls } => {
+                    let mut calls = self
+                        .session_calls
+                        .entry((fault.id, session.to_string()))
+                        .or_insert(0);
+                    *calls += 1;
+                    if *calls < *after_calls {
+                        continue;
+                    }
+                    plan.terminate = true;
+                    self.terminated.insert(session.to_string(), fault.id);
+                    json!({"after_calls": after_calls})
+                }
+                _ if !roll(fault.spec.percentage) => continue,
+                FaultKind::Latency { min_ms, max_ms } => {
+                    let delay_ms = random_between(*min_ms, *max_ms);
+                    plan.delay =
+                        Some(plan.delay.unwrap_or_default() + Duration::from_millis(delay_ms));
+                    json!({"delay_ms": delay_ms})
+                }
+                FaultKind::Error { code, message } => {
+                    plan.error.get_or_insert((*code, message.clone()));
+                    json!({"code": code})
+                }
+                FaultKind::Truncate { max_bytes } => {
+                    plan.truncate = Some(plan.truncate.map_or(*max_bytes, |m| m.min(*max_bytes)));
+                    json!({"max_bytes": max_bytes})
+                }
+                FaultKind::DropEvent => {
+                    plan.drop_for = Some(entry.expires.saturating_duration_since(now));
+                    json!({})
+                }
+            };
+            fault.injected += 1;
+            tag["fault_id"] = json!(fault.id);
+            tag["type"] = json!(fault.spec.kind.as_str());
+            tracing::warn!(
+                "Injected {} fault {} into tool {} of endpoint {} (session {})",
+                fault.spec.kind.as_str(),
+                fault.id,
+                tool,
+                endpoint_id,
+                session
+            );
+            plan.tags.push(tag);
+        }
+        plan
+    }
+
+    /// 会话已被终止时返回触发的故障
+    pub fn terminated_by(&self, session: &str) -> Option<Uuid> {
+        self.terminated.get(session).map(|id| *id)
+    }
+
+    /// 取出已终止的会话，由会话管理器关闭
+    pub fn take_terminated(&self, session: &str) -> Option<Uuid> {
+        self.terminated.remove(session).map(|(_, id)| id)
+    }
+
+    fn prune(&self) {
+        let now = Instant::now();
+        self.faults.retain(|id, entry| {
+            let alive = entry.expires > now;
+            if !alive {
+                tracing::warn!("Fault {} expired", id);
+            }
+            alive
+        });
+        self.session_calls
+            .retain(|(fault_id, _), _| self.faults.contains_key(fault_id));
+    }
+}
+
+fn validate_fault(spec: &FaultSpec) -> Result<()> {
+    if spec.ttl_secs == 0 || spec.ttl_secs > MAX_FAULT_TTL_SECS {
+        return Err(anyhow!(
+            "Invalid fault: ttl_secs must be between 1 and {}",
+            MAX_FAULT_TTL_SECS
+        ));
+    }
+    if spec.percentage == 0 || spec.percentage > 100 {
+        return Err(anyhow!(
+            "Invalid fault: percentage must be between 1 and 100"
+        ));
+    }
+    if spec.tool.as_deref().is_some_and(|t| t.trim().is_empty()) {
+        return Err(anyhow!("Invalid fault: tool must not be empty"));
+    }
+    match &spec.kind {
+        FaultKind::Latency { min_ms, max_ms } if min_ms > max_ms => {
+            Err(anyhow!("Invalid fault: min_ms must not exceed max_ms"))
+        }
+        FaultKind::Latency { max_ms, .. } if *max_ms > MAX_FAULT_LATENCY_MS => Err(anyhow!(
+            "Invalid fault: max_ms must not exceed {}",
+            MAX_FAULT_LATENCY_MS
+        )),
+        FaultKind::TerminateSession { after_calls: 0 } => {
+            Err(anyhow!("Invalid fault: after_calls must be at least 1"))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn roll(percentage: u8) -> bool {
+    percentage >= 100 || (Uuid::new_v4().as_u128() % 100) < u128::from(percentage)
+}
+
+fn random_between(min: u64, max: u64) -> u64 {
+    if max <= min {
+        return min;
+    }
+    min + (Uuid::new_v4().as_u128() % u128::from(max - min + 1)) as u64
+}
+
+/// 按计划注入故障后执行调用：延迟发生在调用前，错误故障不执行调用，
+/// 丢弃故障执行调用但不返回结果，截断作用于结果文本。
+/// 注入的故障列在结果或错误 data 的 fault_injection 中
+pub async fn inject_faults<F>(
+    plan: FaultPlan,
+    ct: CancellationToken,
+    call: F,
+) -> Result<CallToolResult, rmcp::ErrorData>
+where
+    F: Future<Output = Result<CallToolResult, rmcp::ErrorData>>,
+{
+    if plan.is_empty() {
+        return call.await;
+    }
+    let tags = Value::Array(plan.tags);
+    if let Some(delay) = plan.delay {
+        tokio::select! {
+            _ = tokio::time::sleep(delay) => {}
+            _ = ct.cancelled() => {}
+        }
+    }
+    if let Some((code, message)) = plan.error {
+        return Err(rmcp::ErrorData::new(
+            ErrorCode(code),
+            message,
+            Some(json!({ FAULT_META_KEY: tags })),
+        ));
+    }
+    let result = call.await;
+    if let Some(drop_for) = plan.drop_for {
+        // 客户端收不到结果，直到取消或故障到期
+        tokio::select! {
+            _ = tokio::time::sleep(drop_for) => {}
+            _ = ct.cancelled() => {}
+        }
+        return Err(rmcp::ErrorData::internal_error(
+            "Result dropped by fault injection",
+            Some(json!({ FAULT_META_KEY: tags })),
+        ));
+    }
+    match result {
+        Ok(result) => Ok(match plan.truncate {
+            Some(max_bytes) => truncate_result(result, max_bytes, tags),
+            None => tag_result(result, tags),
+        }),
+        Err(error) => Err(tag_error(error, tags)),
+    }
+}
+
+/// 成功结果在 _meta.fault_injection 中标记
+fn tag_result(result: CallToolResult, tags: Value) -> CallToolResult {
+    let Some(mut value) = result.structured_content.clone() else {
+        return result;
+    };
+    let Some(map) = value.as_object_mut() else {
+        return result;
+    };
+    let meta = map.entry("_meta").or_insert_with(|| json!({}));
+    let Some(meta) = meta.as_object_mut() else {
+        return result;
+    };
+    meta.insert(FAULT_META_KEY.to_string(), tags);
+    let mut tagged = CallToolResult::structured(value);
+    tagged.is_error = result.is_error;
+    tagged
+}
+
+/// 结果文本截断到 max_bytes（按字符边界），结构化内容只保留故障标记
+fn truncate_result(result: CallToolResult, max_bytes: usize, tags: Value) -> CallToolResult {
+    let Some(value) = &result.structured_content else {
+        return result;
+    };
+    let text = value.to_string();
+    let mut end = max_bytes.min(text.len());
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    let mut truncated = CallToolResult::structured(json!({"_meta": { FAULT_META_KEY: tags }}));
+    truncated.content = vec![Content::text(&text[..end])];
+    truncated.is_error = result.is_error;
+    truncated
+}
+
+fn tag_error(mut error: rmcp::ErrorData, tags: Value) -> rmcp::ErrorData {
+    match &mut error.data {
+        Some(Value::Object(data)) => {
+            data.insert(FAULT_META_KEY.to_string(), tags);
+        }
+        None => error.data = Some(json!({ FAULT_META_KEY: tags })),
+        Some(_) => {}
+    }
+    error
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    fn spec(endpoint_id: Uuid, tool: Option<&str>, kind: FaultKind) -> FaultSpec {
+        FaultSpec {
+            endpoint_id,
+            tool: tool.map(str::to_string),
+            kind,
+            ttl_secs: 60,
+            percentage: 100,
+        }
+    }
+
+    fn upstream_result() -> Value {
+        json!({"status": 200, "success": true, "response": {"id": 1}})
+    }
+
+    async fn ok_call() -> Result<CallToolResult, rmcp::ErrorData> {
+        Ok(CallToolResult::structured(upstream_result()))
+    }
+
+    #[test]
+    fn test_disabled_without_startup_flag() {
+        let injector = FaultInjector::from_args(["mcp-gateway".to_string()]);
+        assert!(!injector.is_enabled());
+        let endpoint_id = Uuid::new_v4();
+        let error = injector
+            .add(spec(endpoint_id, None, FaultKind::DropEvent))
+            .unwrap_err();
+        assert!(error.downcast_ref::<FaultInjectionDisabled>().is_some());
+        assert!(injector.plan(endpoint_id, "getPet", "s1").is_empty());
+        assert!(!injector.list().enabled);
+
+        let injector =
+            FaultInjector::from_args(["mcp-gateway", FAULT_INJECTION_FLAG].map(str::to_string));
+        assert!(injector.is_enabled());
+        assert!(!fault_injector().is_enabled());
+    }
+
+    #[test]
+    fn test_invalid_faults_rejected() {
+        let injector = FaultInjector::new(true);
+        let endpoint_id = Uuid::new_v4();
+        let mut too_long = spec(endpoint_id, None, FaultKind::DropEvent);
+        too_long.ttl_secs = MAX_FAULT_TTL_SECS + 1;
+        for invalid in [
+            too_long,
+            spec(
+                endpoint_id,
+                None,
+                FaultKind::Latency {
+                    min_ms: 20,
+                    max_ms: 10,
+                },
+            ),
+            spec(
+                endpoint_id,
+                None,
+                FaultKind::TerminateSession { after_calls: 0 },
+            ),
+            spec(endpoint_id, Some(" "), FaultKind::DropEvent),
+        ] {
+            let error = injector.add(invalid).unwrap_err();
+            assert!(error.to_string().starts_with("Invalid fault"));
+        }
+        assert!(injector.list().faults.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_latency_fault_delays_and_tags_result() {
+        let injector = FaultInjector::new(true);
+        let endpoint_id = Uuid::new_v4();
+        let fault = injector
+            .add(spec(
+                endpoint_id,
+                Some("getPet"),
+                FaultKind::Latency {
+                    min_ms: 50,
+                    max_ms: 50,
+                },
+            ))
+            .unwrap();
+
+        // 其他工具不受影响
+        assert!(injector.plan(endpoint_id, "listPets", "s1").is_empty());
+
+        let plan = injector.plan(endpoint_id, "getPet", "s1");
+        assert_eq!(plan.delay, Some(Duration::from_millis(50)));
+        let started = Instant::now();
+        let result = inject_faults(plan, CancellationToken::new(), ok_call())
+            .await
+            .unwrap();
+        assert!(started.elapsed() >= Duration::from_millis(50));
+
+        let value = result.structured_content.unwrap();
+        assert_eq!(value["response"]["id"], 1);
+        assert_eq!(
+            value["_meta"][FAULT_META_KEY],
+            json!([{"fault_id": fault.id, "type": "latency", "delay_ms": 50}])
+        );
+        assert_eq!(injector.list().faults[0].injected, 1);
+    }
+
+    #[tokio::test]
+    async fn test_error_fault_skips_call() {
+        let injector = FaultInjector::new(true);
+        let endpoint_id = Uuid::new_v4();
+        let fault = injector
+            .add(spec(
+                endpoint_id,
+                None,
+                FaultKind::Error {
+                    code: -32050,
+                    message: "injected outage".to_string(),
+                },
+            ))
+            .unwrap();
+
+        let called = AtomicBool::new(false);
+        let error = inject_faults(
+            injector.plan(endpoint_id, "getPet", "s1"),
+            CancellationToken::new(),
+            async {
+                called.store(true, Ordering::SeqCst);
+                ok_call().await
+            },
+        )
+        .await
+        .unwrap_err();
+        assert!(!called.load(Ordering::SeqCst));
+        assert_eq!(error.code, ErrorCode(-32050));
+        assert_eq!(error.message, "injected outage");
+        assert_eq!(
+            error.data.unwrap()[FAULT_META_KEY],
+            json!([{"fault_id": fault.id, "type": "error", "code": -32050}])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_truncate_fault_cuts_result_text() {
+        let injector = FaultInjector::new(true);
+        let endpoint_id = Uuid::new_v4();
+        injector
+            .add(spec(
+                endpoint_id,
+                None,
+                FaultKind::Truncate { max_bytes: 10 },
+            ))
+            .unwrap();
+
+        let result = inject_faults(
+            injector.plan(endpoint_id, "getPet", "s1"),
+            CancellationToken::new(),
+            ok_call(),
+        )
+        .await
+        .unwrap();
+        let text = serde_json::to_value(&result.content).unwrap()[0]["text"].clone();
+        assert_eq!(text, json!(upstream_result().to_string()[..10]));
+        assert_eq!(
+            result.structured_content.unwrap()["_meta"][FAULT_META_KEY][0]["type"],
+            "truncate"
+        );
+    }
+
+    #[test]
+    fn test_terminate_session_after_calls() {
+        let injector = FaultInjector::new(true);
+        let endpoint_id = Uuid::new_v4();
+        let fault = injector
+            .add(spec(
+                endpoint_id,
+                None,
+                FaultKind::TerminateSession { after_calls: 2 },
+            ))
+            .unwrap();
+
+        assert!(!injector.plan(endpoint_id, "getPet", "s1").terminate);
+        assert!(!injector.plan(endpoint_id, "getPet", "s2").terminate);
+        assert!(injector.terminated_by("s1").is_none());
+        assert!(injector.plan(endpoint_id, "listPets", "s1").terminate);
+        assert_eq!(injector.terminated_by("s1"), Some(fault.id));
+        assert!(injector.terminated_by("s2").is_none());
+
+        assert_eq!(injector.take_terminated("s1"), Some(fault.id));
+        assert!(injector.take_terminated("s1").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_faults_expire_after_ttl() {
+        let injector = FaultInjector::new(true);
+        let endpoint_id = Uuid::new_v4();
+        let mut short = spec(endpoint_id, None, FaultKind::Truncate { max_bytes: 1 });
+        short.ttl_secs = 1;
+        injector.add(short).unwrap();
+        assert!(!injector.plan(endpoint_id, "getPet", "s1").is_empty());
+
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+        assert!(injector.plan(endpoint_id, "getPet", "s1").is_empty());
+        assert!(injector.list().faults.is_empty());
+    }
+
+    #[test]
+    fn test_remove_fault() {
+        let injector = FaultInjector::new(true);
+        let endpoint_id = Uuid::new_v4();
+        let fault = injector
+            .add(spec(endpoint_id, None, FaultKind::DropEvent))
+            .unwrap();
+        assert_eq!(injector.remove(fault.id).map(|f| f.id), Some(fault.id));
+        assert!(injector.remove(fault.id).is_none());
+        assert!(injector.plan(endpoint_id, "getPet", "s1").is_empty());
+    }
+}
@@ -0,0 +1,179 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::mysql::MySqlRow;
+use sqlx::{MySql, Row};
+use std::collections::BTreeSet;
+
+/// 管理接口的字段选择查询参数，如 `?fields=id,name,status`
+#[derive(Debug, Default, Deserialize)]
+pub struct FieldsQuery {
+    pub fields: Option<String>,
+}
+
+/// fields 中包含未知字段，错误信息列出全部合法字段
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("Invalid fields: {}. Valid fields: {}", .unknown.join(", "), .valid.join(", "))]
+pub struct InvalidFields {
+    pub unknown: Vec<String>,
+    pub valid: Vec<String>,
+}
+
+/// 逗号分隔的返回字段白名单；未指定时返回全部字段
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FieldSelection {
+    fields: Option<BTreeSet<String>>,
+}
+
+impl FieldSelection {
+    pub fn all() -> Self {
+        Self::default()
+    }
+
+    /// 解析 fields 参数，忽略空白项；全部为空白时视为未指定
+    pub fn parse(raw: Option<&str>, valid: &[&str]) -> Result<Self, InvalidFields> {
+        let fields: BTreeSet<String> = raw
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|field| !field.is_empty())
+            .map(str::to_string)
+            .collect();
+        if fields.is_empty() {
+            return Ok(Self::all());
+        }
+        let unknown: Vec<String> = fields
+            .iter()
+            .filter(|field| !valid.contains(&field.as_str()))
+            .cloned()
+            .collect();
+        if !unknown.is_empty() {
+            return Err(InvalidFields {
+                unknown,
+                valid: valid.iter().map(|field| field.to_string()).collect(),
+            });
+        }
+        Ok(Self {
+            fields: Some(fields),
+        })
+    }
+
+    pub fn is_all(&self) -> bool {
+        self.fields.is_none()
+    }
+
+    pub fn includes(&self, field: &str) -> bool {
+        self.fields
+            .as_ref()
+            .is_none_or(|fields| fields.contains(field))
+    }
+
+    pub fn includes_any(&self, fields: &[&str]) -> bool {
+        fields.iter().any(|field| self.includes(field))
+    }
+
+    /// 选中字段需要查询的列，按 mapping 顺序去重；一个字段可对应多列，required 中的列总是查询
+    pub fn columns(
+        &self,
+        mapping: &[(&str, &'static str)],
+        required: &[&'static str],
+    ) -> Vec<&'static str> {
+        let mut columns: Vec<&'static str> = required.to_vec();
+        for (field, column) in mapping {
+            if self.includes(field) && !columns.contains(column) {
+                columns.push(column);
+            }
+        }
+        columns
+    }
+
+    /// 字段与列同名时选中字段需要查询的列
+    pub fn named_columns(
+        &self,
+        columns: &[&'static str],
+        required: &[&'static str],
+    ) -> Vec<&'static str> {
+        let mapping: Vec<(&str, &'static str)> =
+            columns.iter().map(|column| (*column, *column)).collect();
+        self.columns(&mapping, required)
+    }
+
+    /// 序列化后只保留选中的字段；未指定时原样返回
+    pub fn apply<T: Serialize>(&self, value: &T) -> serde_json::Result<Value> {
+        let mut value = serde_json::to_value(value)?;
+        if let (Some(fields), Value::Object(map)) = (&self.fields, &mut value) {
+            map.retain(|key, _| fields.contains(key));
+        }
+        Ok(value)
+    }
+
+    pub fn apply_each<T: Serialize>(&self, values: &[T]) -> serde_json::Result<Value> {
+        values
+            .iter()
+            .map(|value| self.apply(value))
+            .collect::<serde_json::Result<Vec<_>>>()
+            .map(Value::Array)
+    }
+}
+
+/// 读取按字段选择查询的列，查询未包含该列时返回 None
+pub fn try_get_selected<'r, T>(row: &'r MySqlRow, column: &str) -> Result<Option<T>, sqlx::Error>
+where
+    T: sqlx::Decode<'r, MySql> + sqlx::Type<MySql>,
+{
+    match row.try_get(column) {
+        Ok(value) => Ok(Some(value)),
+        Err(sqlx::Error::ColumnNotFound(_)) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const VALID: &[&str] = &["id", "name", "status", "swagger_spec"];
+
+    #[test]
+    fn test_parse_fields() {
+        assert!(FieldSelection::parse(None, VALID).unwrap().is_all());
+        assert!(FieldSelection::parse(Some(" , "), VALID).unwrap().is_all());
+
+        let fields = FieldSelection::parse(Some("name, status,"), VALID).unwrap();
+        assert!(fields.includes("name"));
+        assert!(!fields.includes("swagger_spec"));
+        assert!(fields.includes_any(&["id", "status"]));
+
+        let error = FieldSelection::parse(Some("name,swagger,tools"), VALID).unwrap_err();
+        assert_eq!(error.unknown, vec!["swagger", "tools"]);
+        assert_eq!(
+            error.to_string(),
+            "Invalid fields: swagger, tools. Valid fields: id, name, status, swagger_spec"
+        );
+    }
+
+    #[test]
+    fn test_columns_and_apply() {
+        let mapping = [
+            ("name", "name"),
+            ("status", "status"),
+            ("swagger_spec", "swagger_content"),
+            ("base_url", "swagger_content"),
+        ];
+        let fields = FieldSelection::parse(Some("status"), VALID).unwrap();
+        assert_eq!(fields.columns(&mapping, &["id"]), vec!["id", "status"]);
+        assert_eq!(
+            FieldSelection::all().columns(&mapping, &["id"]),
+            vec!["id", "name", "status", "swagger_content"]
+        );
+        assert_eq!(fields.named_columns(VALID, &[]), vec!["status"]);
+
+        let row = json!({"id": 1, "name": "petstore", "status": "running"});
+        assert_eq!(fields.apply(&row).unwrap(), json!({"status": "running"}));
+        assert_eq!(FieldSelection::all().apply(&row).unwrap(), row);
+        assert_eq!(
+            fields.apply_each(&[row.clone(), row]).unwrap(),
+            json!([{"status": "running"}, {"status": "running"}])
+        );
+    }
+}
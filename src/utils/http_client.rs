@@ -0,0 +1,309 @@
+use crate::utils::{
+    dns_resolver, upstream_guard, DnsFailure, HostOverrides, UpstreamDenied, UpstreamResolver,
+};
+use dashmap::DashMap;
+use reqwest::Client;
+use std::sync::{Arc, OnceLock};
+use uuid::Uuid;
+
+/// 上游调用共用的 HTTP 客户端；reqwest::Client 克隆后共享连接池与 TLS 会话，
+/// 端点预热建立的连接可被后续工具调用复用
+pub static HTTP_CLIENT: OnceLock<Client> = OnceLock::new();
+
+pub fn http_client() -> &'static Client {
+    HTTP_CLIENT.get_or_init(|| build_upstream_client(HostOverrides::new()))
+}
+
+/// 主机名经由全局解析缓存解析，overrides 中的主机使用静态地址；
+/// 重定向的每一跳都经过上游访问控制
+pub fn build_upstream_client(overrides: HostOverrides) -> Client {
+    Client::builder()
+        .dns_resolver(Arc::new(UpstreamResolver::new(
+            dns_resolver().clone(),
+            overrides,
+        )))
+        .redirect(upstream_guard().redirect_policy())
+        .build()
+        .expect("failed to build upstream HTTP client")
+}
+
+struct EndpointDns {
+    overrides: HostOverrides,
+    client: Client,
+}
+
+/// 配置了静态解析的端点使用独立客户端，避免连接池中同一主机的连接指向不同地址
+static ENDPOINT_DNS: OnceLock<DashMap<Uuid, EndpointDns>> = OnceLock::new();
+
+fn endpoint_dns() -> &'static DashMap<Uuid, EndpointDns> {
+    ENDPOINT_DNS.get_or_init(DashMap::new)
+}
+
+/// 替换端点的静态解析，为空时移除，端点改回共享客户端
+pub fn set_endpoint_dns_overrides(endpoint_id: Uuid, overrides: HostOverrides) {
+    if overrides.is_empty() {
+        endpoint_dns().remove(&endpoint_id);
+        return;
+    }
+    let client = build_upstream_client(overrides.clone());
+    endpoint_dns().insert(endpoint_id, EndpointDns { overrides, client });
+}
+
+pub fn endpoint_dns_overrides(endpoint_id: Uuid) -> Option<HostOverrides> {
+    endpoint_dns()
+        .get(&endpoint_id)
+        .map(|dns| dns.overrides.clone())
+}
+
+/// 端点配置了静态解析时返回其专用客户端
+pub fn endpoint_http_client(endpoint_id: Uuid) -> Option<Client> {
+    endpoint_dns()
+        .get(&endpoint_id)
+        .map(|dns| dns.client.clone())
+}
+
+/// 上游请求失败的类别，写入 JSON-RPC 错误 data.cause
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpstreamFailureCause {
+    Dns,
+    Connect,
+    Timeout,
+}
+
+impl UpstreamFailureCause {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UpstreamFailureCause::Dns => "dns",
+            UpstreamFailureCause::Connect => "connect",
+            UpstreamFailureCause::Timeout => "timeout",
+        }
+    }
+}
+
+impl std::fmt::Display for UpstreamFailureCause {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// 请求未能到达上游：解析失败、连接失败或超时
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[error("Upstream {cause} failure for {host}: {error}")]
+pub struct UpstreamFailure {
+    pub cause: UpstreamFailureCause,
+    pub host: String,
+    pub error: String,
+    /// 请求的操作是否幂等，未知时为 false
+    pub idempotent: bool,
+}
+
+impl UpstreamFailure {
+    /// 解析与连接失败时请求未发出，总是可以重试；超时时上游可能已处理请求，只有幂等操作可以重试
+    pub fn retryable(&self) -> bool {
+        match self.cause {
+            UpstreamFailureCause::Dns | UpstreamFailureCause::Connect => true,
+            UpstreamFailureCause::Timeout => self.idempotent,
+        }
+    }
+}
+
+impl From<DnsFailure> for UpstreamFailure {
+    fn from(failure: DnsFailure) -> Self {
+        Self {
+            cause: UpstreamFailureCause::Dns,
+            host: failure.host,
+            error: failure.error,
+            idempotent: false,
+        }
+    }
+}
+
+impl From<&UpstreamFailure> for rmcp::ErrorData {
+    fn from(failure: &UpstreamFailure) -> Self {
+        rmcp::ErrorData::internal_error(
+            failure.to_string(),
+            Some(serde_json::json!({
+                "cause": failure.cause.as_str(),
+                "host": failure.host,
+                "error": failure.error,
+                "retryable": failure.retryable()
+            })),
+        )
+    }
+}
+
+/// 区分解析、连接与超时失败，其他错误原样返回。
+/// reqwest 只给出 "error sending request"，实际原因取错误链最内层
+pub fn classify_send_error(error: reqwest::Error) -> anyhow::Error {
+    let mut root: &(dyn std::error::Error + 'static) = &error;
+    while let Some(source) = root.source() {
+        if let Some(dns) = source.downcast_ref::<DnsFailure>() {
+            return UpstreamFailure::from(dns.clone()).into();
+        }
+        // 重定向目标或其解析地址被访问控制拒绝
+        if let Some(denied) = source.downcast_ref::<UpstreamDenied>() {
+            return denied.clone().into();
+        }
+        root = source;
+    }
+    let cause = if error.is_timeout() {
+        UpstreamFailureCause::Timeout
+    } else if error.is_connect() {
+        UpstreamFailureCause::Connect
+    } else {
+        return error.into();
+    };
+    UpstreamFailure {
+        cause,
+        host: error
+            .url()
+            .and_then(|url| url.host_str())
+            .unwrap_or_default()
+            .to_string(),
+        error: root.to_string(),
+        idempotent: false,
+    }
+    .into()
+}
+
+/// 工具调用的发送错误：同 classify_send_error，并记录操作是否幂等，决定超时后能否重试
+pub fn classify_call_error(error: reqwest::Error, idempotent: bool) -> anyhow::Error {
+    let mut error = classify_send_error(error);
+    if let Some(failure) = error.downcast_mut::<UpstreamFailure>() {
+        failure.idempotent = idempotent;
+    }
+    error
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::DnsConfig;
+    use crate::utils::dns_resolver::stub::StubLookup;
+    use crate::utils::CachingResolver;
+    use std::time::Duration;
+
+    fn stub_client(stub: Arc<StubLookup>, overrides: HostOverrides) -> Client {
+        let resolver = Arc::new(CachingResolver::new(stub, &DnsConfig::default()));
+        Client::builder()
+            .dns_resolver(Arc::new(UpstreamResolver::new(resolver, overrides)))
+            .build()
+            .unwrap()
+    }
+
+    async fn send_error(client: &Client, url: &str) -> anyhow::Error {
+        let error = client
+            .get(url)
+            .timeout(Duration::from_millis(200))
+            .send()
+            .await
+            .unwrap_err();
+        classify_send_error(error)
+    }
+
+    #[tokio::test]
+    async fn test_dns_failure_classified() {
+        let stub = Arc::new(StubLookup::default());
+        stub.fail("api.internal", "no record found for api.internal");
+        let client = stub_client(stub, HostOverrides::new());
+
+        let error = send_error(&client, "http://api.internal:8080/pets").await;
+        let failure = error.downcast_ref::<UpstreamFailure>().unwrap();
+        assert_eq!(failure.cause, UpstreamFailureCause::Dns);
+        assert_eq!(failure.host, "api.internal");
+        assert_eq!(failure.error, "no record found for api.internal");
+
+        let data = rmcp::ErrorData::from(failure).data.unwrap();
+        assert_eq!(
+            data,
+            serde_json::json!({
+                "cause": "dns",
+                "host": "api.internal",
+                "error": "no record found for api.internal",
+                "retryable": true
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_connect_and_timeout_classified() {
+        let stub = Arc::new(StubLookup::default());
+        stub.answer("api.internal", &["127.0.0.1"], Duration::from_secs(60));
+        let client = stub_client(stub, HostOverrides::new());
+
+        // 端口已关闭：解析成功，连接被拒绝
+        let closed = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = closed.local_addr().unwrap().port();
+        drop(closed);
+        let error = send_error(&client, &format!("http://api.internal:{}/", port)).await;
+        let failure = error.downcast_ref::<UpstreamFailure>().unwrap();
+        assert_eq!(failure.cause, UpstreamFailureCause::Connect);
+        assert_eq!(failure.host, "api.internal");
+        assert!(failure.retryable());
+
+        // 接受连接但不响应
+        let silent = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = silent.local_addr().unwrap().port();
+        let error = send_error(&client, &format!("http://api.internal:{}/", port)).await;
+        let failure = error.downcast_ref::<UpstreamFailure>().unwrap();
+        assert_eq!(failure.cause, UpstreamFailureCause::Timeout);
+        // 超时的请求可能已被上游处理，只有幂等操作可以重试
+        assert!(!failure.retryable());
+        let url = format!("http://api.internal:{}/", port);
+        for (idempotent, retryable) in [(true, true), (false, false)] {
+            let error = client
+                .get(&url)
+                .timeout(Duration::from_millis(200))
+                .send()
+                .await
+                .unwrap_err();
+            let error = classify_call_error(error, idempotent);
+            let failure = error.downcast_ref::<UpstreamFailure>().unwrap();
+            assert_eq!(failure.retryable(), retryable);
+            let data = rmcp::ErrorData::from(failure).data.unwrap();
+            assert_eq!(data["retryable"], retryable);
+        }
+        drop(silent);
+    }
+
+    #[tokio::test]
+    async fn test_endpoint_override_reaches_static_address() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let app = axum::Router::new().route("/ping", axum::routing::get(|| async { "pong" }));
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        // DNS 返回不可达地址，静态解析指向本地服务
+        let stub = Arc::new(StubLookup::default());
+        stub.answer("api.internal", &["192.0.2.1"], Duration::from_secs(60));
+        let overrides: HostOverrides =
+            [("api.internal".to_string(), "127.0.0.1".parse().unwrap())].into();
+        let client = stub_client(stub.clone(), overrides);
+
+        let body = client
+            .get(format!("http://api.internal:{}/ping", port))
+            .send()
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+        assert_eq!(body, "pong");
+        assert_eq!(stub.calls("api.internal"), 0);
+    }
+
+    #[tokio::test]
+    async fn test_endpoint_overrides_registry() {
+        let endpoint_id = Uuid::new_v4();
+        assert!(endpoint_http_client(endpoint_id).is_none());
+        let overrides: HostOverrides =
+            [("api.internal".to_string(), "10.0.0.5".parse().unwrap())].into();
+        set_endpoint_dns_overrides(endpoint_id, overrides.clone());
+        assert_eq!(endpoint_dns_overrides(endpoint_id), Some(overrides));
+        assert!(endpoint_http_client(endpoint_id).is_some());
+
+        set_endpoint_dns_overrides(endpoint_id, HostOverrides::new());
+        assert!(endpoint_dns_overrides(endpoint_id).is_none());
+        assert!(endpoint_http_client(endpoint_id).is_none());
+    }
+}
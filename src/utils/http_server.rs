@@ -0,0 +1,162 @@
+use crate::config::ServerConfig;
+use axum::{body::Body, extract::ConnectInfo, http::Request, Router};
+use hyper::body::Incoming;
+use hyper_util::rt::{TokioExecutor, TokioIo, TokioTimer};
+use hyper_util::server::conn::auto::Builder;
+use hyper_util::service::TowerToHyperService;
+use std::future::Future;
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio_util::sync::CancellationToken;
+use tower::ServiceExt;
+use tower_http::timeout::RequestBodyTimeoutLayer;
+
+/// 入站连接的超时，防止慢速请求长期占用连接
+#[derive(Debug, Clone, Copy)]
+pub struct InboundTimeouts {
+    /// 读取完整请求头的最长时间
+    pub header_read: Duration,
+    /// 请求体两次数据之间的最长间隔
+    pub body_read: Duration,
+}
+
+impl From<&ServerConfig> for InboundTimeouts {
+    fn from(config: &ServerConfig) -> Self {
+        Self {
+            header_read: Duration::from_secs(config.header_read_timeout_secs),
+            body_read: Duration::from_secs(config.body_read_timeout_secs),
+        }
+    }
+}
+
+/// 启动 HTTP 服务，直到 signal 完成后停止接受新连接，并通知已有连接优雅关闭
+pub async fn serve<F>(listener: TcpListener, app: Router, timeouts: InboundTimeouts, signal: F)
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    let app = app.layer(RequestBodyTimeoutLayer::new(timeouts.body_read));
+    let shutdown = CancellationToken::new();
+    tokio::pin!(signal);
+
+    loop {
+        let (stream, remote) = tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    tracing::warn!(error = %e, "failed to accept connection");
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                    continue;
+                }
+            },
+            _ = &mut signal => break,
+        };
+
+        // 来源地址放入请求扩展，供按 IP 限制的中间件使用
+        let service =
+            TowerToHyperService::new(app.clone().map_request(move |req: Request<Incoming>| {
+                let mut req = req.map(Body::new);
+                req.extensions_mut().insert(ConnectInfo(remote));
+                req
+            }));
+        let shutdown = shutdown.clone();
+        tokio::spawn(async move {
+            let mut builder = Builder::new(TokioExecutor::new());
+            builder
+                .http1()
+                .timer(TokioTimer::new())
+                .header_read_timeout(timeouts.header_read);
+            let conn = builder.serve_connection_with_upgrades(TokioIo::new(stream), service);
+            tokio::pin!(conn);
+            let mut draining = false;
+            loop {
+                tokio::select! {
+                    result = conn.as_mut() => {
+                        if let Err(e) = result {
+                            tracing::debug!(error = %e, remote = %remote, "connection closed with error");
+                        }
+                        break;
+                    }
+                    _ = shutdown.cancelled(), if !draining => {
+                        draining = true;
+                        conn.as_mut().graceful_shutdown();
+                    }
+                }
+            }
+        });
+    }
+
+    shutdown.cancel();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Bytes, routing::post};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpStream;
+
+    async fn start(timeouts: InboundTimeouts) -> std::net::SocketAddr {
+        let app = Router::new().route(
+            "/echo",
+            post(|body: Bytes| async move { body.len().to_string() }),
+        );
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(serve(listener, app, timeouts, std::future::pending()));
+        addr
+    }
+
+    #[tokio::test]
+    async fn test_slow_body_closes_connection() {
+        let addr = start(InboundTimeouts {
+            header_read: Duration::from_secs(5),
+            body_read: Duration::from_millis(200),
+        })
+        .await;
+
+        // 正常速度的分块请求体可以读完
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(b"POST /echo HTTP/1.1\r\nHost: localhost\r\nTransfer-Encoding: chunked\r\nConnection: close\r\n\r\n5\r\nhello\r\n0\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = Vec::new();
+        stream.read_to_end(&mut response).await.unwrap();
+        let response = String::from_utf8_lossy(&response);
+        assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+        assert!(response.ends_with("\r\n\r\n5"), "{}", response);
+
+        // 第一个分块之后停顿超过超时时间，服务端中止读取并关闭连接
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(b"POST /echo HTTP/1.1\r\nHost: localhost\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nhello\r\n")
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(600)).await;
+        let _ = stream.write_all(b"5\r\nworld\r\n").await;
+        let mut response = Vec::new();
+        let closed =
+            tokio::time::timeout(Duration::from_secs(5), stream.read_to_end(&mut response)).await;
+        assert!(closed.is_ok(), "connection should be closed by the server");
+        assert!(!String::from_utf8_lossy(&response).starts_with("HTTP/1.1 200"));
+    }
+
+    #[tokio::test]
+    async fn test_slow_headers_close_connection() {
+        let addr = start(InboundTimeouts {
+            header_read: Duration::from_millis(200),
+            body_read: Duration::from_secs(5),
+        })
+        .await;
+
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(b"POST /echo HTTP/1.1\r\nHost: localhost\r\n")
+            .await
+            .unwrap();
+        let mut response = Vec::new();
+        let closed =
+            tokio::time::timeout(Duration::from_secs(5), stream.read_to_end(&mut response)).await;
+        assert!(closed.is_ok(), "connection should be closed by the server");
+    }
+}
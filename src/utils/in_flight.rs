@@ -0,0 +1,273 @@
+use dashmap::DashMap;
+use serde::Serialize;
+use std::future::Future;
+use std::hash::Hash;
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
+
+/// 被取消的调用在调用日志中的状态码（客户端关闭请求）
+pub const CANCELLED_STATUS_CODE: u16 = 499;
+
+/// 已取消请求的标记保留时间，超时未被响应过滤消费的标记清除
+const CANCELLED_MARKER_TTL: Duration = Duration::from_secs(60);
+
+/// 全局取消登记表，按 (会话, 请求 id) 跟踪进行中的 tools/call
+static CANCELLATION_REGISTRY: OnceLock<CancellationRegistry> = OnceLock::new();
+
+pub fn cancellation_registry() -> &'static CancellationRegistry {
+    CANCELLATION_REGISTRY.get_or_init(CancellationRegistry::default)
+}
+
+/// JSON-RPC 请求 id 的登记键：数字与字符串 id 按 JSON 表示区分
+pub fn request_id_key<T: Serialize>(id: &T) -> String {
+    serde_json::to_string(id).unwrap_or_default()
+}
+
+/// 请求被客户端通过 notifications/cancelled 取消
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[error("Request cancelled by client")]
+pub struct RequestCancelled;
+
+/// 进行中的请求及其取消令牌，按请求 id 查找；请求结束后自动移除
+#[derive(Debug)]
+pub struct InFlightRequests<K: Eq + Hash> {
+    tokens: Arc<DashMap<K, CancellationToken>>,
+}
+
+impl<K: Eq + Hash> Clone for InFlightRequests<K> {
+    fn clone(&self) -> Self {
+        Self {
+            tokens: self.tokens.clone(),
+        }
+    }
+}
+
+impl<K: Eq + Hash> Default for InFlightRequests<K> {
+    fn default() -> Self {
+        Self {
+            tokens: Arc::new(DashMap::new()),
+        }
+    }
+}
+
+impl<K: Eq + Hash + Clone> InFlightRequests<K> {
+    /// 执行 work，令牌被取消时丢弃 work（进行中的上游请求随之中断）
+    pub async fn run<F, T>(
+        &self,
+        id: K,
+        ct: CancellationToken,
+        work: F,
+    ) -> Result<T, RequestCancelled>
+    where
+        F: Future<Output = T>,
+    {
+        self.tokens.insert(id.clone(), ct.clone());
+        let result = tokio::select! {
+            biased;
+            _ = ct.cancelled() => Err(RequestCancelled),
+            output = work => Ok(output),
+        };
+        self.tokens.remove(&id);
+        result
+    }
+
+    /// 取消指定请求；未知或已结束的请求返回 false
+    pub fn cancel(&self, id: &K) -> bool {
+        match self.tokens.remove(id) {
+            Some((_, ct)) => {
+                ct.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 取消键满足条件的全部请求，返回被取消的键
+    pub fn cancel_matching<F>(&self, matches: F) -> Vec<K>
+    where
+        F: Fn(&K) -> bool,
+    {
+        let keys: Vec<K> = self
+            .tokens
+            .iter()
+            .filter(|entry| matches(entry.key()))
+            .map(|entry| entry.key().clone())
+            .collect();
+        keys.into_iter().filter(|key| self.cancel(key)).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.tokens.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tokens.is_empty()
+    }
+}
+
+type SessionRequest = (String, String);
+
+/// 进行中的调用按 (会话, 请求 id) 登记；取消后记下标记，供响应过滤丢弃该 id 的结果帧
+#[derive(Default)]
+pub struct CancellationRegistry {
+    in_flight: InFlightRequests<SessionRequest>,
+    cancelled: DashMap<SessionRequest, Instant>,
+}
+
+impl CancellationRegistry {
+    /// 执行 work，调用结束（完成或取消）后移除登记；被取消时记下标记。
+    /// 取消令牌可能由 rmcp 先于 on_cancelled 触发，因此标记在此处统一记录
+    pub async fn run<F, T>(
+        &self,
+        session: &str,
+        request_id: &str,
+        ct: CancellationToken,
+        work: F,
+    ) -> Result<T, RequestCancelled>
+    where
+        F: Future<Output = T>,
+    {
+        let key = (session.to_string(), request_id.to_string());
+        let result = self.in_flight.run(key.clone(), ct, work).await;
+        if result.is_err() {
+            let now = Instant::now();
+            self.cancelled
+                .retain(|_, at| now.duration_since(*at) < CANCELLED_MARKER_TTL);
+            self.cancelled.insert(key, now);
+        }
+        result
+    }
+
+    /// 取消进行中的调用；未知或已结束的请求为空操作，返回 false
+    pub fn cancel(&self, session: &str, request_id: &str) -> bool {
+        self.in_flight
+            .cancel(&(session.to_string(), request_id.to_string()))
+    }
+
+    /// 会话关闭时取消其全部进行中的调用
+    pub fn cancel_session(&self, session: &str) -> usize {
+        self.in_flight.cancel_matching(|(s, _)| s == session).len()
+    }
+
+    /// 该请求已被取消且结果尚未丢弃时返回 true，并消费标记
+    pub fn take_cancelled(&self, session: &str, request_id: &str) -> bool {
+        self.cancelled
+            .remove(&(session.to_string(), request_id.to_string()))
+            .is_some()
+    }
+
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::time::Duration;
+
+    struct DropFlag(Arc<AtomicBool>);
+
+    impl Drop for DropFlag {
+        fn drop(&mut self) {
+            self.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cancel_aborts_in_flight_upstream_call() {
+        let in_flight: InFlightRequests<u32> = InFlightRequests::default();
+        let aborted = Arc::new(AtomicBool::new(false));
+        let completed = Arc::new(AtomicBool::new(false));
+
+        // 模拟长时间未返回的上游调用
+        let guard = DropFlag(aborted.clone());
+        let done = completed.clone();
+        let upstream = async move {
+            let _guard = guard;
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            done.store(true, Ordering::SeqCst);
+            "response"
+        };
+        let task = tokio::spawn({
+            let in_flight = in_flight.clone();
+            async move { in_flight.run(7, CancellationToken::new(), upstream).await }
+        });
+
+        while in_flight.is_empty() {
+            tokio::task::yield_now().await;
+        }
+        assert!(in_flight.cancel(&7));
+
+        let result = tokio::time::timeout(Duration::from_secs(1), task)
+            .await
+            .expect("cancelled call should finish promptly")
+            .unwrap();
+        assert_eq!(result, Err(RequestCancelled));
+        assert!(aborted.load(Ordering::SeqCst));
+        assert!(!completed.load(Ordering::SeqCst));
+        assert!(in_flight.is_empty());
+
+        // 已结束或未知的请求取消为空操作
+        assert!(!in_flight.cancel(&7));
+    }
+
+    #[tokio::test]
+    async fn test_completed_request_is_removed() {
+        let in_flight: InFlightRequests<u32> = InFlightRequests::default();
+        let result = in_flight
+            .run(1, CancellationToken::new(), async { 42 })
+            .await;
+        assert_eq!(result, Ok(42));
+        assert!(in_flight.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_registry_marks_cancelled_requests() {
+        let registry = CancellationRegistry::default();
+        let key = request_id_key(&serde_json::json!(7));
+        assert_eq!(key, "7");
+        assert_ne!(request_id_key(&"7"), key);
+
+        let task = registry.run("session-a", &key, CancellationToken::new(), async {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+        });
+        tokio::pin!(task);
+        // 推进到登记完成
+        assert!(futures::poll!(task.as_mut()).is_pending());
+        assert_eq!(registry.in_flight(), 1);
+
+        // 同一 id 在其他会话中不受影响
+        assert!(!registry.cancel("session-b", &key));
+        assert!(registry.cancel("session-a", &key));
+        assert_eq!(task.await, Err(RequestCancelled));
+        assert_eq!(registry.in_flight(), 0);
+
+        // 标记只消费一次；已结束的请求再取消为空操作
+        assert!(registry.take_cancelled("session-a", &key));
+        assert!(!registry.take_cancelled("session-a", &key));
+        assert!(!registry.cancel("session-a", &key));
+        assert!(!registry.take_cancelled("session-a", &key));
+    }
+
+    #[tokio::test]
+    async fn test_cancel_session_aborts_all_calls() {
+        let registry = CancellationRegistry::default();
+        let slow = |id: &'static str| {
+            registry.run("closing", id, CancellationToken::new(), async {
+                tokio::time::sleep(Duration::from_secs(60)).await;
+            })
+        };
+        let (first, second) = (slow("1"), slow("2"));
+        tokio::pin!(first, second);
+        assert!(futures::poll!(first.as_mut()).is_pending());
+        assert!(futures::poll!(second.as_mut()).is_pending());
+
+        assert_eq!(registry.cancel_session("closing"), 2);
+        assert_eq!(first.await, Err(RequestCancelled));
+        assert_eq!(second.await, Err(RequestCancelled));
+        assert!(registry.take_cancelled("closing", "1"));
+    }
+}
@@ -0,0 +1,420 @@
+use axum::body::{Body, Bytes};
+use axum::http::header;
+use axum::response::{IntoResponse, Response};
+use futures::stream::{self, BoxStream, Stream, StreamExt};
+use serde::Serialize;
+use std::fmt::Display;
+use std::io::{self, Write};
+use tokio::sync::mpsc;
+
+/// 分块目标大小：元素序列化后累积到该大小再输出，峰值内存约为一个分块加一个元素
+pub const JSON_CHUNK_BYTES: usize = 64 * 1024;
+
+/// 流式 JSON 输出格式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JsonFraming {
+    /// 单个 JSON 数组
+    Array,
+    /// 每行一个元素，末尾追加 {"summary":{"count":N}} 汇总行
+    Ndjson,
+}
+
+struct EncoderState<T, E> {
+    items: BoxStream<'static, Result<T, E>>,
+    framing: JsonFraming,
+    buf: Vec<u8>,
+    count: usize,
+    error: Option<io::Error>,
+    done: bool,
+}
+
+impl<T: Serialize, E> EncoderState<T, E> {
+    fn push(&mut self, item: &T) -> serde_json::Result<()> {
+        if self.framing == JsonFraming::Array && self.count > 0 {
+            self.buf.push(b',');
+        }
+        serde_json::to_writer(&mut self.buf, item)?;
+        if self.framing == JsonFraming::Ndjson {
+            self.buf.push(b'\n');
+        }
+        self.count += 1;
+        Ok(())
+    }
+
+    fn close(&mut self) {
+        match self.framing {
+            JsonFraming::Array => self.buf.push(b']'),
+            JsonFraming::Ndjson => {
+                let summary = serde_json::json!({"summary": {"count": self.count}});
+                let _ = writeln!(self.buf, "{}", summary);
+            }
+        }
+    }
+}
+
+/// 逐个序列化元素并按分块输出；上游出错时先输出已缓冲的数据，再以错误结束响应体（不 panic）
+pub fn encode_json_stream<S, T, E>(
+    items: S,
+    framing: JsonFraming,
+) -> BoxStream<'static, io::Result<Bytes>>
+where
+    S: Stream<Item = Result<T, E>> + Send + 'static,
+    T: Serialize + Send + 'static,
+    E: Display + Send + 'static,
+{
+    let mut buf = Vec::with_capacity(JSON_CHUNK_BYTES);
+    if framing == JsonFraming::Array {
+        buf.push(b'[');
+    }
+    let state = EncoderState {
+        items: items.boxed(),
+        framing,
+        buf,
+        count: 0,
+        error: None,
+        done: false,
+    };
+
+    stream::unfold(state, |mut state| async move {
+        if let Some(error) = state.error.take() {
+            state.done = true;
+            return Some((Err(error), state));
+        }
+        if state.done {
+            return None;
+        }
+        while state.buf.len() < JSON_CHUNK_BYTES {
+            match state.items.next().await {
+                Some(Ok(item)) => {
+                    if let Err(e) = state.push(&item) {
+                        tracing::error!("Failed to serialize streamed item {}: {}", state.count, e);
+                        state.error = Some(io::Error::other(e));
+                        break;
+                    }
+                }
+                Some(Err(e)) => {
+                    tracing::error!(
+                        "Streamed response aborted after {} items: {}",
+                        state.count,
+                        e
+                    );
+                    state.error = Some(io::Error::other(e.to_string()));
+                    break;
+                }
+                None => {
+                    state.close();
+                    state.done = true;
+                    break;
+                }
+            }
+        }
+        if state.buf.is_empty() {
+            return match state.error.take() {
+                Some(error) => {
+                    state.done = true;
+                    Some((Err(error), state))
+                }
+                None => None,
+            };
+        }
+        let chunk = std::mem::replace(&mut state.buf, Vec::with_capacity(JSON_CHUNK_BYTES));
+        Some((Ok(Bytes::from(chunk)), state))
+    })
+    .boxed()
+}
+
+/// 流式响应：Array 为 application/json，Ndjson 为 application/x-ndjson
+pub fn json_stream_response<S, T, E>(items: S, framing: JsonFraming) -> Response
+where
+    S: Stream<Item = Result<T, E>> + Send + 'static,
+    T: Serialize + Send + 'static,
+    E: Display + Send + 'static,
+{
+    let content_type = match framing {
+        JsonFraming::Array => "application/json",
+        JsonFraming::Ndjson => "application/x-ndjson",
+    };
+    (
+        [(header::CONTENT_TYPE, content_type)],
+        Body::from_stream(encode_json_stream(items, framing)),
+    )
+        .into_response()
+}
+
+/// 写满一个分块即发送到通道，阻塞线程上使用
+struct ChunkWriter {
+    tx: mpsc::Sender<io::Result<Bytes>>,
+    buf: Vec<u8>,
+}
+
+impl ChunkWriter {
+    fn send(&mut self) -> io::Result<()> {
+        if self.buf.is_empty() {
+            return Ok(());
+        }
+        let chunk = std::mem::replace(&mut self.buf, Vec::with_capacity(JSON_CHUNK_BYTES));
+        self.tx
+            .blocking_send(Ok(Bytes::from(chunk)))
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "client disconnected"))
+    }
+}
+
+impl Write for ChunkWriter {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.buf.extend_from_slice(data);
+        if self.buf.len() >= JSON_CHUNK_BYTES {
+            self.send()?;
+        }
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.send()
+    }
+}
+
+/// 单个大对象的流式响应：在阻塞线程中序列化并分块发送，避免整体序列化为一个 String
+pub fn json_value_response<T>(value: T) -> Response
+where
+    T: Serialize + Send + 'static,
+{
+    let (tx, rx) = mpsc::channel::<io::Result<Bytes>>(4);
+    tokio::task::spawn_blocking(move || {
+        let mut writer = ChunkWriter {
+            tx: tx.clone(),
+            buf: Vec::with_capacity(JSON_CHUNK_BYTES),
+        };
+        let result = serde_json::to_writer(&mut writer, &value)
+            .map_err(io::Error::other)
+            .and_then(|_| writer.flush());
+        if let Err(e) = result {
+            if e.kind() != io::ErrorKind::BrokenPipe {
+                tracing::error!("Streamed JSON response aborted: {}", e);
+                let _ = tx.blocking_send(Err(e));
+            }
+        }
+    });
+    let body = stream::unfold(rx, |mut rx| async move {
+        rx.recv().await.map(|chunk| (chunk, rx))
+    });
+    (
+        [(header::CONTENT_TYPE, "application/json")],
+        Body::from_stream(body),
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::de::{Deserializer, SeqAccess, Visitor};
+    use serde::Deserialize;
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::cell::Cell;
+    use std::convert::Infallible;
+    use std::io::{BufReader, Read};
+
+    /// 只统计开启跟踪的线程上的内存分配，避免并行测试互相干扰
+    struct CountingAllocator;
+
+    thread_local! {
+        static TRACKING: Cell<bool> = const { Cell::new(false) };
+        static LIVE: Cell<isize> = const { Cell::new(0) };
+        static PEAK: Cell<isize> = const { Cell::new(0) };
+    }
+
+    fn record(delta: isize) {
+        let _ = TRACKING.try_with(|tracking| {
+            if tracking.get() {
+                let live = LIVE.with(|l| {
+                    l.set(l.get() + delta);
+                    l.get()
+                });
+                PEAK.with(|p| p.set(p.get().max(live)));
+            }
+        });
+    }
+
+    unsafe impl GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let ptr = System.alloc(layout);
+            if !ptr.is_null() {
+                record(layout.size() as isize);
+            }
+            ptr
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            System.dealloc(ptr, layout);
+            record(-(layout.size() as isize));
+        }
+
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            let new_ptr = System.realloc(ptr, layout, new_size);
+            if !new_ptr.is_null() {
+                record(new_size as isize - layout.size() as isize);
+            }
+            new_ptr
+        }
+    }
+
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+    #[derive(Serialize)]
+    struct ExportRow {
+        id: u64,
+        payload: String,
+    }
+
+    #[derive(Deserialize)]
+    struct RowId {
+        id: u64,
+    }
+
+    /// 客户端侧：边读边解析，校验是合法 JSON 数组且元素 id 连续
+    struct CountRows;
+
+    impl<'de> Visitor<'de> for CountRows {
+        type Value = u64;
+
+        fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            f.write_str("an array of rows")
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<u64, A::Error> {
+            let mut count = 0;
+            while let Some(row) = seq.next_element::<RowId>()? {
+                assert_eq!(row.id, count);
+                count += 1;
+            }
+            Ok(count)
+        }
+    }
+
+    /// 在当前线程上逐块拉取响应体
+    struct StreamReader {
+        chunks: BoxStream<'static, io::Result<Bytes>>,
+        current: Bytes,
+        received: usize,
+    }
+
+    impl Read for StreamReader {
+        fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+            while self.current.is_empty() {
+                match futures::executor::block_on(self.chunks.next()) {
+                    Some(chunk) => {
+                        self.current = chunk?;
+                        self.received += self.current.len();
+                    }
+                    None => return Ok(0),
+                }
+            }
+            let n = out.len().min(self.current.len());
+            out[..n].copy_from_slice(&self.current.split_to(n));
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn test_large_export_streams_under_memory_ceiling() {
+        const ROWS: u64 = 52_000;
+        const PAYLOAD_BYTES: usize = 1_000;
+
+        TRACKING.with(|t| t.set(true));
+        LIVE.with(|l| l.set(0));
+        PEAK.with(|p| p.set(0));
+
+        let rows = stream::iter((0..ROWS).map(|id| {
+            Ok::<_, Infallible>(ExportRow {
+                id,
+                payload: "x".repeat(PAYLOAD_BYTES),
+            })
+        }));
+        let mut reader = BufReader::new(StreamReader {
+            chunks: encode_json_stream(rows, JsonFraming::Array),
+            current: Bytes::new(),
+            received: 0,
+        });
+        let count = serde_json::Deserializer::from_reader(&mut reader)
+            .deserialize_seq(CountRows)
+            .expect("reassembled JSON should be valid");
+        let received = reader.get_ref().received;
+        drop(reader);
+
+        TRACKING.with(|t| t.set(false));
+        let peak = PEAK.with(|p| p.get());
+
+        assert_eq!(count, ROWS);
+        assert!(received > 50 * 1024 * 1024, "streamed {} bytes", received);
+        // 峰值内存只与分块大小相关，远小于导出总量
+        assert!(peak < 2 * 1024 * 1024, "peak allocation {} bytes", peak);
+    }
+
+    #[test]
+    fn test_error_mid_stream_terminates_body() {
+        let rows = stream::iter(vec![
+            Ok(ExportRow {
+                id: 0,
+                payload: "a".to_string(),
+            }),
+            Err("database connection lost"),
+        ]);
+        let chunks: Vec<io::Result<Bytes>> =
+            futures::executor::block_on(encode_json_stream(rows, JsonFraming::Array).collect());
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(
+            chunks[0].as_ref().unwrap().as_ref(),
+            br#"[{"id":0,"payload":"a"}"#
+        );
+        assert!(chunks[1]
+            .as_ref()
+            .unwrap_err()
+            .to_string()
+            .contains("database connection lost"));
+    }
+
+    #[test]
+    fn test_ndjson_appends_summary() {
+        let rows = stream::iter((0..3).map(|id| {
+            Ok::<_, Infallible>(ExportRow {
+                id,
+                payload: String::new(),
+            })
+        }));
+        let chunks: Vec<io::Result<Bytes>> =
+            futures::executor::block_on(encode_json_stream(rows, JsonFraming::Ndjson).collect());
+        let body: Vec<u8> = chunks
+            .into_iter()
+            .flat_map(|c| c.unwrap().to_vec())
+            .collect();
+        let lines: Vec<serde_json::Value> = String::from_utf8(body)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[2]["id"], 2);
+        assert_eq!(lines[3], serde_json::json!({"summary": {"count": 3}}));
+
+        let empty = stream::iter(Vec::<Result<ExportRow, Infallible>>::new());
+        let chunks: Vec<io::Result<Bytes>> =
+            futures::executor::block_on(encode_json_stream(empty, JsonFraming::Array).collect());
+        assert_eq!(chunks[0].as_ref().unwrap().as_ref(), b"[]");
+    }
+
+    #[tokio::test]
+    async fn test_json_value_response_reassembles() {
+        let value = serde_json::json!({
+            "name": "large",
+            "paths": (0..5000).map(|i| format!("/path/{}", i)).collect::<Vec<_>>()
+        });
+        let response = json_value_response(value.clone());
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "application/json");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let parsed: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(parsed, value);
+    }
+}
@@ -5,14 +5,48 @@ use rmcp::transport::streamable_http_server::{SessionId, SessionManager};
 use std::future::Future;
 use std::sync::Arc;
 
+pub mod admin_key;
+pub mod argument_validation;
+pub mod circuit_breaker;
+pub mod deadline;
+pub mod dns_resolver;
+pub mod fault_injection;
+pub mod field_selection;
+pub mod http_client;
+pub mod http_server;
+pub mod in_flight;
+pub mod json_stream;
+pub mod postman_import;
+pub mod schema_flatten;
+pub mod session_secret;
 pub mod shutdown;
 pub mod swagger_util;
+pub mod tool_limits;
+pub mod upstream_guard;
 pub mod util;
+pub mod wasm_plugin;
 
-use crate::services::SessionService;
+use crate::services::{session_recorder, SessionService, LIFECYCLE_CLOSED, LIFECYCLE_OPENED};
+pub use admin_key::*;
+pub use argument_validation::*;
+pub use circuit_breaker::*;
+pub use deadline::*;
+pub use dns_resolver::*;
+pub use fault_injection::*;
+pub use field_selection::*;
+pub use http_client::*;
+pub use http_server::*;
+pub use in_flight::*;
+pub use json_stream::*;
+pub use postman_import::*;
+pub use schema_flatten::*;
+pub use session_secret::*;
 pub use shutdown::*;
 pub use swagger_util::*;
+pub use tool_limits::*;
+pub use upstream_guard::*;
 pub use util::*;
+pub use wasm_plugin::*;
 
 pub struct MonitoredSessionManager<SM> {
     inner: SM,
@@ -44,6 +78,14 @@ where
             match future.await {
                 Ok((session_id, transport)) => {
                     self.session_service.pre_save_cache(session_id.clone());
+                    if let Some(recorder) = session_recorder() {
+                        recorder.record_lifecycle(
+                            &session_id,
+                            None,
+                            LIFECYCLE_OPENED,
+                            serde_json::json!({"transport": "streamable"}),
+                        );
+                    }
                     Ok((session_id, transport))
                 }
                 Err(e) => Err(e),
@@ -63,7 +105,15 @@ where
         &self,
         id: &SessionId,
     ) -> impl Future<Output = Result<bool, Self::Error>> + Send {
-        self.inner.has_session(id)
+        async {
+            // 被 terminate_session 故障终止的会话在下一次请求时关闭
+            if let Some(fault_id) = fault_injector().take_terminated(id) {
+                tracing::warn!("Closing session {} terminated by fault {}", id, fault_id);
+                self.close_session(id).await?;
+                return Ok(false);
+            }
+            self.inner.has_session(id).await
+        }
     }
 
     fn close_session(
@@ -72,6 +122,9 @@ where
     ) -> impl Future<Output = Result<(), Self::Error>> + Send {
         async {
             self.session_service.destroy_session(id).await;
+            if let Some(recorder) = session_recorder() {
+                recorder.record_lifecycle(id, None, LIFECYCLE_CLOSED, serde_json::Value::Null);
+            }
             self.inner.close_session(id).await
         }
     }
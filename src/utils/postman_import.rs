@@ -0,0 +1,971 @@
+use anyhow::{anyhow, Result};
+use regex::Regex;
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::OnceLock;
+
+/// 转换生成的 OpenAPI 文档中记录来源的扩展字段
+pub const IMPORT_SOURCE_KEY: &str = "x-imported-from";
+
+/// OpenAPI PathItem 支持的方法，其余方法的请求跳过
+const SUPPORTED_METHODS: [&str; 5] = ["get", "post", "put", "delete", "patch"];
+/// 由 requestBody 或 securitySchemes 表达的请求头，不生成 header 参数
+const SKIPPED_HEADERS: [&str; 4] = ["content-type", "content-length", "host", "authorization"];
+
+/// Postman collection 转换结果：OpenAPI 3 文档与转换告警
+#[derive(Debug, Clone)]
+pub struct PostmanConversion {
+    pub spec: Value,
+    pub warnings: Vec<String>,
+}
+
+fn variable_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| Regex::new(r"\{\{\s*([^{}]+?)\s*\}\}").unwrap())
+}
+
+/// JSON 体中不在引号内的 {{变量}}
+fn bare_variable_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| Regex::new(r#"(^|[^"])(\{\{[^{}]+\}\})"#).unwrap())
+}
+
+/// 展开后的单个请求及其所在的文件夹链
+struct FlatRequest<'a> {
+    name: String,
+    folders: Vec<String>,
+    item: &'a Value,
+    /// 最近一层文件夹或请求自身的 auth，None 表示沿用 collection 级 auth
+    auth: Option<&'a Value>,
+}
+
+struct ParsedUrl {
+    base: String,
+    segments: Vec<String>,
+    query: Vec<(String, String, Option<String>)>,
+    /// url.variable 中声明的路径变量：值与描述
+    path_variables: HashMap<String, (String, Option<String>)>,
+}
+
+/// 将 Postman Collection v2.1 转换为 OpenAPI 3 文档：
+/// 文件夹转为 tags，{{变量}} 转为路径/服务器变量，示例请求体推断 JSON schema，
+/// collection 级 auth 转为 securitySchemes；脚本与不支持的 auth 类型记入告警
+pub fn postman_to_openapi(collection: &Value) -> Result<PostmanConversion> {
+    let info = collection
+        .get("info")
+        .ok_or_else(|| anyhow!("Invalid Postman collection: missing info"))?;
+    let items = collection
+        .get("item")
+        .and_then(Value::as_array)
+        .ok_or_else(|| anyhow!("Invalid Postman collection: missing item array"))?;
+    let schema = info["schema"].as_str().unwrap_or_default();
+    let mut converter = Converter::new(collection);
+    if !schema.contains("/collection/v2") {
+        return Err(anyhow!(
+            "Invalid Postman collection: only Collection v2.x is supported"
+        ));
+    }
+    if !schema.contains("v2.1") {
+        converter.warn(format!("Collection schema '{}' converted as v2.1", schema));
+    }
+    converter.warn_scripts("collection", collection);
+
+    let mut requests = Vec::new();
+    converter.flatten(items, &[], None, &mut requests);
+    if requests.is_empty() {
+        return Err(anyhow!("Postman collection contains no requests"));
+    }
+    converter.convert(info, collection.get("auth"), &requests)
+}
+
+struct Converter {
+    variables: HashMap<String, String>,
+    server_variables: BTreeMap<String, String>,
+    security_schemes: Map<String, Value>,
+    operation_ids: HashSet<String>,
+    warnings: Vec<String>,
+}
+
+impl Converter {
+    fn new(collection: &Value) -> Self {
+        let variables = collection["variable"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|v| {
+                let key = v["key"].as_str()?;
+                Some((key.to_string(), value_text(&v["value"])))
+            })
+            .collect();
+        Self {
+            variables,
+            server_variables: BTreeMap::new(),
+            security_schemes: Map::new(),
+            operation_ids: HashSet::new(),
+            warnings: Vec::new(),
+        }
+    }
+
+    fn warn(&mut self, warning: String) {
+        if !self.warnings.contains(&warning) {
+            self.warnings.push(warning);
+        }
+    }
+
+    fn warn_scripts(&mut self, owner: &str, item: &Value) {
+        let has_script = item["event"].as_array().into_iter().flatten().any(|event| {
+            event["script"]["exec"]
+                .as_array()
+                .is_some_and(|lines| lines.iter().any(|l| !value_text(l).trim().is_empty()))
+        });
+        if has_script {
+            self.warn(format!("Scripts in {} are ignored", owner));
+        }
+    }
+
+    fn flatten<'a>(
+        &mut self,
+        items: &'a [Value],
+        folders: &[String],
+        auth: Option<&'a Value>,
+        out: &mut Vec<FlatRequest<'a>>,
+    ) {
+        for item in items {
+            let name = item["name"].as_str().unwrap_or_default().to_string();
+            let item_auth = item.get("auth").filter(|a| !a.is_null()).or(auth);
+            if let Some(children) = item.get("item").and_then(Value::as_array) {
+                self.warn_scripts(&format!("folder '{}'", name), item);
+                let mut chain = folders.to_vec();
+                chain.push(name);
+                self.flatten(children, &chain, item_auth, out);
+            } else if item.get("request").is_some() {
+                self.warn_scripts(&format!("request '{}'", name), item);
+                let request_auth = item["request"].get("auth").filter(|a| !a.is_null());
+                out.push(FlatRequest {
+                    name,
+                    folders: folders.to_vec(),
+                    item,
+                    auth: request_auth.or(item_auth),
+                });
+            }
+        }
+    }
+
+    fn convert(
+        mut self,
+        info: &Value,
+        collection_auth: Option<&Value>,
+        requests: &[FlatRequest],
+    ) -> Result<PostmanConversion> {
+        let parsed: Vec<ParsedUrl> = requests
+            .iter()
+            .map(|r| parse_url(&r.item["request"]["url"]))
+            .collect();
+        let base = most_common_base(&parsed);
+        let server_url = self.server_url(&base);
+
+        let security = collection_auth
+            .and_then(|auth| self.security_requirement(auth, "collection"))
+            .unwrap_or_else(|| json!([]));
+
+        let mut paths = Map::new();
+        for (request, url) in requests.iter().zip(&parsed) {
+            let method = request.item["request"]["method"]
+                .as_str()
+                .unwrap_or("GET")
+                .to_lowercase();
+            if !SUPPORTED_METHODS.contains(&method.as_str()) {
+                self.warn(format!(
+                    "Request '{}' skipped: method {} is not supported",
+                    request.name,
+                    method.to_uppercase()
+                ));
+                continue;
+            }
+            if url.base != base {
+                self.warn(format!(
+                    "Request '{}' uses host '{}', converted relative to '{}'",
+                    request.name, url.base, base
+                ));
+            }
+            let path = self.path_template(url);
+            let path_item = paths
+                .entry(path.clone())
+                .or_insert_with(|| Value::Object(Map::new()));
+            if path_item.get(&method).is_some() {
+                self.warn(format!(
+                    "Request '{}' skipped: {} {} is already defined",
+                    request.name,
+                    method.to_uppercase(),
+                    path
+                ));
+                continue;
+            }
+            let operation = self.operation(request, url, &method, &path);
+            paths[&path][&method] = operation;
+        }
+
+        let mut spec = json!({
+            "openapi": "3.0.3",
+            "info": {
+                "title": info["name"].as_str().unwrap_or("Postman collection"),
+                "version": "1.0.0"
+            },
+            "servers": [{"url": server_url}],
+            "paths": paths,
+            IMPORT_SOURCE_KEY: {
+                "format": "postman",
+                "schema": info["schema"],
+                "postman_id": info["_postman_id"],
+                "name": info["name"]
+            }
+        });
+        if let Some(description) = description_text(&info["description"]) {
+            spec["info"]["description"] = json!(description);
+        }
+        if !self.server_variables.is_empty() {
+            let variables: Map<String, Value> = self
+                .server_variables
+                .iter()
+                .map(|(name, default)| (name.clone(), json!({"default": default})))
+                .collect();
+            spec["servers"][0]["variables"] = Value::Object(variables);
+        }
+        if !self.security_schemes.is_empty() {
+            spec["components"] = json!({"securitySchemes": self.security_schemes});
+        }
+        if security.as_array().is_some_and(|s| !s.is_empty()) {
+            spec["security"] = security;
+        }
+        Ok(PostmanConversion {
+            spec,
+            warnings: self.warnings,
+        })
+    }
+
+    /// {{变量}} 转为服务器变量，默认值取 collection 变量
+    fn server_url(&mut self, base: &str) -> String {
+        let mut names = Vec::new();
+        let url = variable_pattern()
+            .replace_all(base, |caps: &regex::Captures| {
+                names.push(caps[1].to_string());
+                format!("{{{}}}", &caps[1])
+            })
+            .to_string();
+        for name in names {
+            let default = self.variables.get(&name).cloned().unwrap_or_default();
+            if default.is_empty() {
+                self.warn(format!(
+                    "Collection variable '{}' has no value, set it via server_variables",
+                    name
+                ));
+            }
+            self.server_variables.insert(name, default);
+        }
+        url
+    }
+
+    /// :id 与 {{id}} 形式的路径段转为 {id}
+    fn path_template(&self, url: &ParsedUrl) -> String {
+        let segments: Vec<String> = url
+            .segments
+            .iter()
+            .map(|segment| match segment.strip_prefix(':') {
+                Some(name) => format!("{{{}}}", name),
+                None => variable_pattern()
+                    .replace_all(segment, |caps: &regex::Captures| {
+                        format!("{{{}}}", &caps[1])
+                    })
+                    .to_string(),
+            })
+            .collect();
+        format!("/{}", segments.join("/"))
+    }
+
+    fn operation(
+        &mut self,
+        request: &FlatRequest,
+        url: &ParsedUrl,
+        method: &str,
+        path: &str,
+    ) -> Value {
+        let spec_request = &request.item["request"];
+        let mut operation = Map::new();
+        operation.insert(
+            "operationId".to_string(),
+            json!(self.operation_id(&request.name, method, path)),
+        );
+        if !request.name.is_empty() {
+            operation.insert("summary".to_string(), json!(request.name));
+        }
+        if let Some(description) = description_text(&spec_request["description"]) {
+            operation.insert("description".to_string(), json!(description));
+        }
+        if !request.folders.is_empty() {
+            operation.insert("tags".to_string(), json!([request.folders.join(" / ")]));
+        }
+
+        let mut parameters = Vec::new();
+        for name in path_parameter_names(path) {
+            let (example, description) = match url.path_variables.get(&name) {
+                Some((value, description)) => (value.clone(), description.clone()),
+                None => (self.variables.get(&name).cloned().unwrap_or_default(), None),
+            };
+            parameters.push(parameter(&name, "path", true, &example, description));
+        }
+        for (key, value, description) in &url.query {
+            let example = self.resolve(value);
+            parameters.push(parameter(
+                key,
+                "query",
+                false,
+                &example,
+                description.clone(),
+            ));
+        }
+        for header in spec_request["header"].as_array().into_iter().flatten() {
+            let Some(key) = header["key"].as_str().filter(|k| !k.is_empty()) else {
+                continue;
+            };
+            if SKIPPED_HEADERS.contains(&key.to_lowercase().as_str()) {
+                continue;
+            }
+            let example = self.resolve(&value_text(&header["value"]));
+            let description = description_text(&header["description"]);
+            parameters.push(parameter(key, "header", false, &example, description));
+        }
+        if !parameters.is_empty() {
+            operation.insert("parameters".to_string(), Value::Array(parameters));
+        }
+
+        if let Some(body) = self.request_body(&request.name, &spec_request["body"]) {
+            operation.insert("requestBody".to_string(), body);
+        }
+        operation.insert(
+            "responses".to_string(),
+            responses(&request.item["response"]),
+        );
+
+        if let Some(auth) = request.auth {
+            if let Some(security) = self.security_requirement(auth, &format!("'{}'", request.name))
+            {
+                operation.insert("security".to_string(), security);
+            }
+        }
+        Value::Object(operation)
+    }
+
+    /// 请求名转为 camelCase 的 operationId，重名时追加序号
+    fn operation_id(&mut self, name: &str, method: &str, path: &str) -> String {
+        let source = if name.trim().is_empty() {
+            format!("{} {}", method, path)
+        } else {
+            name.to_string()
+        };
+        let words: Vec<String> = source
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .filter(|w| !w.is_empty())
+            .map(str::to_lowercase)
+            .collect();
+        let mut base: String = words
+            .iter()
+            .enumerate()
+            .map(|(i, word)| match i {
+                0 => word.clone(),
+                _ => capitalize(word),
+            })
+            .collect();
+        if base.is_empty() || base.starts_with(|c: char| c.is_ascii_digit()) {
+            base = format!("{}{}", method, capitalize(&base));
+        }
+        let mut id = base.clone();
+        let mut index = 2;
+        while !self.operation_ids.insert(id.clone()) {
+            id = format!("{}{}", base, index);
+            index += 1;
+        }
+        id
+    }
+
+    fn request_body(&mut self, name: &str, body: &Value) -> Option<Value> {
+        let mode = body["mode"].as_str()?;
+        let (media_type, media) = match mode {
+            "raw" => {
+                let raw = body["raw"].as_str().unwrap_or_default();
+                if raw.trim().is_empty() {
+                    return None;
+                }
+                let language = body["options"]["raw"]["language"].as_str();
+                match parse_json_example(raw) {
+                    Some(example) if matches!(language, None | Some("json")) => (
+                        "application/json",
+                        json!({"schema": infer_schema(&example), "example": example}),
+                    ),
+                    _ => {
+                        let media_type = match language {
+                            Some("xml") => "application/xml",
+                            Some("html") => "text/html",
+                            _ => "text/plain",
+                        };
+                        (
+                            media_type,
+                            json!({"schema": {"type": "string"}, "example": raw}),
+                        )
+                    }
+                }
+            }
+            "urlencoded" | "formdata" => {
+                let mut properties = Map::new();
+                for field in body[mode].as_array().into_iter().flatten() {
+                    let Some(key) = field["key"].as_str().filter(|k| !k.is_empty()) else {
+                        continue;
+                    };
+                    let schema = if field["type"] == "file" {
+                        json!({"type": "string", "format": "binary"})
+                    } else {
+                        infer_scalar_schema(&self.resolve(&value_text(&field["value"])))
+                    };
+                    properties.insert(key.to_string(), schema);
+                }
+                let media_type = match mode {
+                    "urlencoded" => "application/x-www-form-urlencoded",
+                    _ => "multipart/form-data",
+                };
+                (
+                    media_type,
+                    json!({"schema": {"type": "object", "properties": properties}}),
+                )
+            }
+            other => {
+                self.warn(format!(
+                    "Request '{}': body mode '{}' is not supported, body omitted",
+                    name, other
+                ));
+                return None;
+            }
+        };
+        Some(json!({"content": {media_type: media}}))
+    }
+
+    /// auth 转为 security 要求并登记 securitySchemes；noauth 返回空数组，不支持的类型返回 None
+    fn security_requirement(&mut self, auth: &Value, owner: &str) -> Option<Value> {
+        let auth_type = auth["type"].as_str().unwrap_or("noauth");
+        let (name, scheme) = match auth_type {
+            "noauth" => return Some(json!([])),
+            "bearer" => ("bearerAuth", json!({"type": "http", "scheme": "bearer"})),
+            "basic" => ("basicAuth", json!({"type": "http", "scheme": "basic"})),
+            "apikey" => {
+                let field = |key: &str| {
+                    auth["apikey"]
+                        .as_array()
+                        .into_iter()
+                        .flatten()
+                        .find(|f| f["key"] == key)
+                        .map(|f| value_text(&f["value"]))
+                };
+                let location = match field("in").as_deref() {
+                    Some("query") => "query",
+                    _ => "header",
+                };
+                let key = field("key")
+                    .filter(|k| !k.is_empty())
+                    .unwrap_or_else(|| "X-API-Key".to_string());
+                (
+                    "apiKeyAuth",
+                    json!({"type": "apiKey", "name": key, "in": location}),
+                )
+            }
+            other => {
+                self.warn(format!(
+                    "Auth type '{}' in {} is not supported and was ignored",
+                    other, owner
+                ));
+                return None;
+            }
+        };
+        let name = self.add_security_scheme(name, scheme);
+        Some(json!([{ name: [] }]))
+    }
+
+    fn add_security_scheme(&mut self, base: &str, scheme: Value) -> String {
+        let mut name = base.to_string();
+        let mut index = 2;
+        while let Some(existing) = self.security_schemes.get(&name) {
+            if *existing == scheme {
+                return name;
+            }
+            name = format!("{}{}", base, index);
+            index += 1;
+        }
+        self.security_schemes.insert(name.clone(), scheme);
+        name
+    }
+
+    /// 值为单个 {{变量}} 时取 collection 变量的值作为示例
+    fn resolve(&self, value: &str) -> String {
+        variable_pattern()
+            .captures(value)
+            .filter(|caps| caps[0].len() == value.trim().len())
+            .and_then(|caps| self.variables.get(&caps[1]).cloned())
+            .unwrap_or_else(|| value.to_string())
+    }
+}
+
+fn parse_url(url: &Value) -> ParsedUrl {
+    let mut parsed = match url {
+        Value::String(raw) => parse_raw_url(raw),
+        Value::Object(fields) if fields.contains_key("host") || fields.contains_key("path") => {
+            let host = match &url["host"] {
+                Value::Array(parts) => parts.iter().map(value_text).collect::<Vec<_>>().join("."),
+                other => value_text(other),
+            };
+            let mut base = match url["protocol"].as_str() {
+                Some(protocol) => format!("{}://{}", protocol, host),
+                None if variable_pattern().is_match(&host) => host,
+                None => format!("http://{}", host),
+            };
+            if let Some(port) = url.get("port").map(value_text).filter(|p| !p.is_empty()) {
+                base = format!("{}:{}", base, port);
+            }
+            let segments = match &url["path"] {
+                Value::Array(parts) => parts.iter().map(value_text).collect(),
+                Value::String(path) => path.split('/').map(str::to_string).collect(),
+                _ => Vec::new(),
+            };
+            let query = url["query"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|q| {
+                    let key = q["key"].as_str().filter(|k| !k.is_empty())?;
+                    Some((
+                        key.to_string(),
+                        value_text(&q["value"]),
+                        description_text(&q["description"]),
+                    ))
+                })
+                .collect();
+            ParsedUrl {
+                base,
+                segments,
+                query,
+                path_variables: HashMap::new(),
+            }
+        }
+        _ => parse_raw_url(url["raw"].as_str().unwrap_or_default()),
+    };
+    parsed.segments.retain(|s| !s.is_empty());
+    for variable in url["variable"].as_array().into_iter().flatten() {
+        if let Some(key) = variable["key"].as_str() {
+            parsed.path_variables.insert(
+                key.to_string(),
+                (
+                    value_text(&variable["value"]),
+                    description_text(&variable["description"]),
+                ),
+            );
+        }
+    }
+    parsed
+}
+
+fn parse_raw_url(raw: &str) -> ParsedUrl {
+    let (without_query, query) = raw.split_once('?').unwrap_or((raw, ""));
+    let (base_prefix, rest) = match without_query.split_once("://") {
+        Some((protocol, rest)) => (format!("{}://", protocol), rest),
+        None => (String::new(), without_query),
+    };
+    let (host, path) = rest.split_once('/').unwrap_or((rest, ""));
+    let base = if base_prefix.is_empty() && !variable_pattern().is_match(host) {
+        format!("http://{}", host)
+    } else {
+        format!("{}{}", base_prefix, host)
+    };
+    ParsedUrl {
+        base,
+        segments: path.split('/').map(str::to_string).collect(),
+        query: query
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| {
+                let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+                (key.to_string(), value.to_string(), None)
+            })
+            .collect(),
+        path_variables: HashMap::new(),
+    }
+}
+
+/// 出现次数最多的服务器地址，次数相同时取先出现的
+fn most_common_base(urls: &[ParsedUrl]) -> String {
+    let mut counts: Vec<(&str, usize)> = Vec::new();
+    for url in urls {
+        match counts.iter_mut().find(|(base, _)| *base == url.base) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((url.base.as_str(), 1)),
+        }
+    }
+    let (mut best, mut best_count) = ("", 0);
+    for (base, count) in counts {
+        if count > best_count {
+            (best, best_count) = (base, count);
+        }
+    }
+    best.to_string()
+}
+
+fn path_parameter_names(path: &str) -> Vec<String> {
+    path.split('/')
+        .flat_map(|segment| {
+            segment
+                .split('{')
+                .skip(1)
+                .filter_map(|part| part.split_once('}').map(|(name, _)| name.to_string()))
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+fn parameter(
+    name: &str,
+    location: &str,
+    required: bool,
+    example: &str,
+    description: Option<String>,
+) -> Value {
+    let mut parameter = json!({
+        "name": name,
+        "in": location,
+        "required": required,
+        "schema": infer_scalar_schema(example)
+    });
+    if !example.is_empty() && !variable_pattern().is_match(example) {
+        parameter["example"] = json!(example);
+    }
+    if let Some(description) = description {
+        parameter["description"] = json!(description);
+    }
+    parameter
+}
+
+/// 保存的响应示例转为 responses，没有示例时生成默认的 200
+fn responses(examples: &Value) -> Value {
+    let mut responses = Map::new();
+    for example in examples.as_array().into_iter().flatten() {
+        let code = example["code"]
+            .as_u64()
+            .map(|c| c.to_string())
+            .unwrap_or_else(|| "200".to_string());
+        if responses.contains_key(&code) {
+            continue;
+        }
+        let description = example["name"]
+            .as_str()
+            .or(example["status"].as_str())
+            .unwrap_or("Response")
+            .to_string();
+        let mut response = json!({"description": description});
+        let body = example["body"].as_str().unwrap_or_default();
+        if let Some(parsed) = parse_json_example(body) {
+            response["content"] = json!({
+                "application/json": {"schema": infer_schema(&parsed), "example": parsed}
+            });
+        }
+        responses.insert(code, response);
+    }
+    if responses.is_empty() {
+        responses.insert(
+            "200".to_string(),
+            json!({"description": "Successful response"}),
+        );
+    }
+    Value::Object(responses)
+}
+
+/// 解析 JSON 示例，不在引号内的 {{变量}} 按字符串处理
+fn parse_json_example(raw: &str) -> Option<Value> {
+    if raw.trim().is_empty() {
+        return None;
+    }
+    serde_json::from_str(raw).ok().or_else(|| {
+        let quoted = bare_variable_pattern().replace_all(raw, r#"$1"$2""#);
+        serde_json::from_str(&quoted).ok()
+    })
+}
+
+/// 按示例值推断 JSON schema，对象的字段均视为可选
+pub fn infer_schema(example: &Value) -> Value {
+    match example {
+        Value::Null => json!({}),
+        Value::Bool(_) => json!({"type": "boolean"}),
+        Value::Number(n) if n.is_i64() || n.is_u64() => json!({"type": "integer"}),
+        Value::Number(_) => json!({"type": "number"}),
+        Value::String(_) => json!({"type": "string"}),
+        Value::Array(items) => json!({
+            "type": "array",
+            "items": items.first().map(infer_schema).unwrap_or_else(|| json!({}))
+        }),
+        Value::Object(fields) => {
+            let properties: Map<String, Value> = fields
+                .iter()
+                .map(|(key, value)| (key.clone(), infer_schema(value)))
+                .collect();
+            json!({"type": "object", "properties": properties})
+        }
+    }
+}
+
+/// 查询参数、请求头等文本值：能解析为整数、小数或布尔值时按对应类型推断
+fn infer_scalar_schema(value: &str) -> Value {
+    if value.parse::<i64>().is_ok() {
+        json!({"type": "integer"})
+    } else if value.parse::<f64>().is_ok_and(f64::is_finite) {
+        json!({"type": "number"})
+    } else if value == "true" || value == "false" {
+        json!({"type": "boolean"})
+    } else {
+        json!({"type": "string"})
+    }
+}
+
+fn value_text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+/// description 可以是字符串或 {content, type}
+fn description_text(description: &Value) -> Option<String> {
+    let text = match description {
+        Value::String(text) => text.as_str(),
+        Value::Object(_) => description["content"].as_str()?,
+        _ => return None,
+    };
+    Some(text.trim().to_string()).filter(|t| !t.is_empty())
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::SwaggerSpec;
+    use crate::utils::generate_mcp_tools;
+
+    fn collection() -> Value {
+        json!({
+            "info": {
+                "_postman_id": "5b0f6a8e-7c1d-4f58-9a55-2d7c3b3c9e01",
+                "name": "Petstore",
+                "description": "Partner petstore API",
+                "schema": "https://schema.getpostman.com/json/collection/v2.1.0/collection.json"
+            },
+            "auth": {
+                "type": "bearer",
+                "bearer": [{"key": "token", "value": "{{token}}", "type": "string"}]
+            },
+            "variable": [
+                {"key": "baseUrl", "value": "https://petstore.example.com/v1"},
+                {"key": "petId", "value": "42"}
+            ],
+            "event": [
+                {"listen": "prerequest", "script": {"type": "text/javascript", "exec": ["pm.environment.set('ts', Date.now());"]}}
+            ],
+            "item": [
+                {
+                    "name": "Pets",
+                    "item": [
+                        {
+                            "name": "List pets",
+                            "request": {
+                                "method": "GET",
+                                "header": [{"key": "X-Request-Source", "value": "partner"}],
+                                "url": {
+                                    "raw": "{{baseUrl}}/pets?limit=20&status=available",
+                                    "host": ["{{baseUrl}}"],
+                                    "path": ["pets"],
+                                    "query": [
+                                        {"key": "limit", "value": "20", "description": "page size"},
+                                        {"key": "status", "value": "available"}
+                                    ]
+                                }
+                            },
+                            "response": [{
+                                "name": "OK",
+                                "code": 200,
+                                "body": "[{\"id\": 1, \"name\": \"doggie\"}]"
+                            }]
+                        },
+                        {
+                            "name": "Admin",
+                            "item": [{
+                                "name": "Create pet",
+                                "event": [{"listen": "test", "script": {"exec": ["pm.test('ok', () => {});"]}}],
+                                "request": {
+                                    "method": "POST",
+                                    "header": [{"key": "Content-Type", "value": "application/json"}],
+                                    "body": {
+                                        "mode": "raw",
+                                        "raw": "{\"name\": \"doggie\", \"age\": 3, \"weight\": 4.5, \"vaccinated\": true, \"tags\": [\"small\"], \"owner\": {\"id\": {{ownerId}}}}",
+                                        "options": {"raw": {"language": "json"}}
+                                    },
+                                    "url": "{{baseUrl}}/pets"
+                                }
+                            }]
+                        }
+                    ]
+                },
+                {
+                    "name": "Get pet",
+                    "request": {
+                        "method": "GET",
+                        "auth": {"type": "digest", "digest": []},
+                        "url": {
+                            "raw": "{{baseUrl}}/pets/:petId",
+                            "host": ["{{baseUrl}}"],
+                            "path": ["pets", ":petId"],
+                            "variable": [{"key": "petId", "value": "1", "description": "pet id"}]
+                        }
+                    }
+                },
+                {
+                    "name": "Upload photo",
+                    "request": {
+                        "method": "POST",
+                        "auth": {"type": "noauth"},
+                        "body": {
+                            "mode": "formdata",
+                            "formdata": [
+                                {"key": "file", "type": "file", "src": "/tmp/a.png"},
+                                {"key": "caption", "value": "front", "type": "text"}
+                            ]
+                        },
+                        "url": "{{baseUrl}}/pets/{{petId}}/photos"
+                    }
+                }
+            ]
+        })
+    }
+
+    #[test]
+    fn test_convert_paths_and_variables() {
+        let conversion = postman_to_openapi(&collection()).unwrap();
+        let spec = &conversion.spec;
+
+        assert_eq!(spec["servers"][0]["url"], "{baseUrl}");
+        assert_eq!(
+            spec["servers"][0]["variables"]["baseUrl"]["default"],
+            "https://petstore.example.com/v1"
+        );
+        let paths: Vec<&String> = spec["paths"].as_object().unwrap().keys().collect();
+        assert_eq!(
+            paths,
+            vec!["/pets", "/pets/{petId}", "/pets/{petId}/photos"]
+        );
+
+        let list = &spec["paths"]["/pets"]["get"];
+        assert_eq!(list["operationId"], "listPets");
+        assert_eq!(list["tags"], json!(["Pets"]));
+        let parameters = list["parameters"].as_array().unwrap();
+        assert_eq!(parameters[0]["name"], "limit");
+        assert_eq!(parameters[0]["schema"]["type"], "integer");
+        assert_eq!(parameters[0]["description"], "page size");
+        assert_eq!(parameters[2]["in"], "header");
+        assert_eq!(
+            list["responses"]["200"]["content"]["application/json"]["schema"]["items"]
+                ["properties"]["id"]["type"],
+            "integer"
+        );
+
+        let get = &spec["paths"]["/pets/{petId}"]["get"];
+        assert_eq!(get["parameters"][0]["in"], "path");
+        assert_eq!(get["parameters"][0]["required"], true);
+        assert_eq!(get["parameters"][0]["description"], "pet id");
+        // 不支持的 auth 类型沿用 collection 级 auth
+        assert!(get.get("security").is_none());
+
+        let upload = &spec["paths"]["/pets/{petId}/photos"]["post"];
+        assert_eq!(upload["security"], json!([]));
+        assert_eq!(
+            upload["requestBody"]["content"]["multipart/form-data"]["schema"]["properties"]["file"]
+                ["format"],
+            "binary"
+        );
+        assert_eq!(spec[IMPORT_SOURCE_KEY]["format"], "postman");
+    }
+
+    #[test]
+    fn test_infer_json_body_schema() {
+        let conversion = postman_to_openapi(&collection()).unwrap();
+        let create = &conversion.spec["paths"]["/pets"]["post"];
+        assert_eq!(create["tags"], json!(["Pets / Admin"]));
+        let media = &create["requestBody"]["content"]["application/json"];
+        assert_eq!(
+            media["schema"],
+            json!({
+                "type": "object",
+                "properties": {
+                    "name": {"type": "string"},
+                    "age": {"type": "integer"},
+                    "weight": {"type": "number"},
+                    "vaccinated": {"type": "boolean"},
+                    "tags": {"type": "array", "items": {"type": "string"}},
+                    "owner": {"type": "object", "properties": {"id": {"type": "string"}}}
+                }
+            })
+        );
+        assert_eq!(media["example"]["owner"]["id"], "{{ownerId}}");
+        // Content-Type 由 requestBody 表达
+        assert!(create.get("parameters").is_none());
+    }
+
+    #[test]
+    fn test_auth_and_warnings() {
+        let conversion = postman_to_openapi(&collection()).unwrap();
+        let spec = &conversion.spec;
+        assert_eq!(
+            spec["components"]["securitySchemes"]["bearerAuth"],
+            json!({"type": "http", "scheme": "bearer"})
+        );
+        assert_eq!(spec["security"], json!([{"bearerAuth": []}]));
+
+        let warnings = conversion.warnings.join("\n");
+        assert!(
+            warnings.contains("Scripts in collection are ignored"),
+            "{}",
+            warnings
+        );
+        assert!(warnings.contains("Scripts in request 'Create pet' are ignored"));
+        assert!(warnings.contains("Auth type 'digest'"));
+    }
+
+    #[test]
+    fn test_converted_spec_generates_tools() {
+        let conversion = postman_to_openapi(&collection()).unwrap();
+        let spec: SwaggerSpec = serde_json::from_value(conversion.spec).unwrap();
+        let tools = generate_mcp_tools(&spec).unwrap();
+        let mut names: Vec<&str> = tools.iter().map(|t| t.name.as_str()).collect();
+        names.sort();
+        assert_eq!(
+            names,
+            vec!["createPet", "getPet", "listPets", "uploadPhoto"]
+        );
+    }
+
+    #[test]
+    fn test_reject_non_v2_collection() {
+        let error = postman_to_openapi(&json!({"info": {"name": "old"}, "item": []})).unwrap_err();
+        assert!(error.to_string().contains("Collection v2"));
+        let mut empty = collection();
+        empty["item"] = json!([]);
+        assert!(postman_to_openapi(&empty).is_err());
+    }
+}
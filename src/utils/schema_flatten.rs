@@ -0,0 +1,556 @@
+use crate::config::SchemaFlattenConfig;
+use crate::utils::ArgumentError;
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashSet};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// 展开的最大嵌套层数与数组展开的元素个数，启动时由配置初始化
+static MAX_FLATTEN_DEPTH: AtomicUsize = AtomicUsize::new(3);
+static MAX_FLATTEN_ARRAY_ITEMS: AtomicUsize = AtomicUsize::new(3);
+
+pub fn set_flatten_limits(config: &SchemaFlattenConfig) {
+    MAX_FLATTEN_DEPTH.store(config.max_depth, Ordering::Relaxed);
+    MAX_FLATTEN_ARRAY_ITEMS.store(config.max_array_items, Ordering::Relaxed);
+}
+
+pub fn flatten_limits() -> FlattenLimits {
+    FlattenLimits {
+        max_depth: MAX_FLATTEN_DEPTH.load(Ordering::Relaxed),
+        max_array_items: MAX_FLATTEN_ARRAY_ITEMS.load(Ordering::Relaxed),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FlattenLimits {
+    /// 顶层参数之下最多展开的层数，超过后以对象原样作为参数
+    pub max_depth: usize,
+    /// 数组展开为 name[0]..name[n-1] 的个数
+    pub max_array_items: usize,
+}
+
+/// 展开结果：schema 为 None 表示没有可展开的参数，沿用原 inputSchema
+#[derive(Debug, Default)]
+pub struct FlattenedSchema {
+    pub schema: Option<Value>,
+    pub warnings: Vec<String>,
+}
+
+struct Flattener {
+    limits: FlattenLimits,
+    properties: Map<String, Value>,
+    required: Vec<String>,
+    warnings: Vec<String>,
+    expanded: bool,
+}
+
+fn required_names(schema: &Value) -> HashSet<&str> {
+    schema
+        .get("required")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .collect()
+}
+
+/// 键名不固定的对象（additionalProperties 映射或未声明 properties）无法展开
+fn has_dynamic_keys(schema: &Value) -> bool {
+    let is_object = schema.get("type").and_then(Value::as_str) == Some("object")
+        || schema.get("properties").is_some()
+        || schema.get("additionalProperties").is_some();
+    let fixed_keys = schema
+        .get("properties")
+        .and_then(Value::as_object)
+        .is_some_and(|p| !p.is_empty())
+        && schema
+            .get("additionalProperties")
+            .is_none_or(|a| a == &Value::Bool(false));
+    is_object && !fixed_keys
+}
+
+/// 属性名本身含分隔符时无法无损还原
+fn separator_key(schema: &Value) -> Option<String> {
+    if let Some(properties) = schema.get("properties").and_then(Value::as_object) {
+        for (name, property) in properties {
+            if name.contains(['.', '[', ']']) {
+                return Some(name.clone());
+            }
+            if let Some(key) = separator_key(property) {
+                return Some(key);
+            }
+        }
+    }
+    schema.get("items").and_then(separator_key)
+}
+
+fn label(name: &str, schema: &Value) -> String {
+    schema
+        .get("description")
+        .and_then(Value::as_str)
+        .filter(|d| !d.is_empty())
+        .unwrap_or(name)
+        .to_string()
+}
+
+impl Flattener {
+    fn flatten(&mut self, key: String, schema: &Value, required: bool, labels: Vec<String>) {
+        let depth = labels.len() - 1;
+        let can_expand = depth < self.limits.max_depth;
+        if schema.get("type").and_then(Value::as_str) == Some("array") {
+            if can_expand && self.limits.max_array_items > 0 {
+                self.expanded = true;
+                let items = schema
+                    .get("items")
+                    .cloned()
+                    .unwrap_or_else(|| Value::Object(Map::new()));
+                for index in 0..self.limits.max_array_items {
+                    let mut labels = labels.clone();
+                    labels.push(format!(
+                        "item {} (at most {} items)",
+                        index, self.limits.max_array_items
+                    ));
+                    self.flatten(format!("{}[{}]", key, index), &items, false, labels);
+                }
+                return;
+            }
+        } else if can_expand && has_dynamic_keys(schema) {
+            self.warnings.push(format!(
+                "'{}' has no fixed properties (e.g. an additionalProperties map) and is kept nested",
+                key
+            ));
+        } else if let Some(properties) = schema.get("properties").and_then(Value::as_object) {
+            if can_expand {
+                self.expanded = true;
+                let required_children = required_names(schema);
+                for (name, property) in properties {
+                    let mut labels = labels.clone();
+                    labels.push(label(name, property));
+                    self.flatten(
+                        format!("{}.{}", key, name),
+                        property,
+                        required && required_children.contains(name.as_str()),
+                        labels,
+                    );
+                }
+                return;
+            }
+        }
+
+        let mut property = schema.clone();
+        if labels.len() > 1 {
+            property["description"] = Value::String(labels.join(" > "));
+        }
+        if required {
+            self.required.push(key.clone());
+        }
+        self.properties.insert(key, property);
+    }
+}
+
+/// 把嵌套的 inputSchema 展开为单层：对象属性用 a.b，数组元素用 a[0]，
+/// 描述由嵌套路径组成；数组元素中的字段不标记为必填，调用时还原后再按原 schema 校验
+pub fn flatten_input_schema(input_schema: &Value, limits: FlattenLimits) -> FlattenedSchema {
+    let Some(properties) = input_schema.get("properties").and_then(Value::as_object) else {
+        return FlattenedSchema::default();
+    };
+    if let Some(key) = separator_key(input_schema) {
+        return FlattenedSchema {
+            schema: None,
+            warnings: vec![format!(
+                "property '{}' contains '.', '[' or ']', inputSchema is kept nested",
+                key
+            )],
+        };
+    }
+
+    let required = required_names(input_schema);
+    let mut flattener = Flattener {
+        limits,
+        properties: Map::new(),
+        required: Vec::new(),
+        warnings: Vec::new(),
+        expanded: false,
+    };
+    for (name, property) in properties {
+        flattener.flatten(
+            name.clone(),
+            property,
+            required.contains(name.as_str()),
+            vec![label(name, property)],
+        );
+    }
+    if !flattener.expanded {
+        return FlattenedSchema {
+            schema: None,
+            warnings: flattener.warnings,
+        };
+    }
+
+    let mut schema = input_schema.clone();
+    schema["properties"] = Value::Object(flattener.properties);
+    schema["required"] = flattener.required.into_iter().map(Value::String).collect();
+    // 示例参数同样改为扁平形式
+    if let Some(example) = schema.pointer("/_meta/exampleArguments").cloned() {
+        let flat = flatten_arguments(&example, &schema);
+        schema["_meta"]["exampleArguments"] = flat;
+    }
+    FlattenedSchema {
+        schema: Some(schema),
+        warnings: flattener.warnings,
+    }
+}
+
+fn flatten_value(
+    key: String,
+    value: &Value,
+    keys: &Map<String, Value>,
+    flat: &mut Map<String, Value>,
+) {
+    let children: Vec<(String, &Value)> = match value {
+        _ if keys.contains_key(&key) => Vec::new(),
+        Value::Object(fields) => fields
+            .iter()
+            .map(|(name, v)| (format!("{}.{}", key, name), v))
+            .collect(),
+        Value::Array(items) => items
+            .iter()
+            .enumerate()
+            .map(|(index, v)| (format!("{}[{}]", key, index), v))
+            .collect(),
+        _ => Vec::new(),
+    };
+    if children.is_empty() {
+        flat.insert(key, value.clone());
+    }
+    for (key, value) in children {
+        flatten_value(key, value, keys, flat);
+    }
+}
+
+/// 嵌套参数转为扁平参数；flat_schema 中已有的键整体保留，空对象与空数组原样保留
+pub fn flatten_arguments(arguments: &Value, flat_schema: &Value) -> Value {
+    let Some(object) = arguments.as_object() else {
+        return arguments.clone();
+    };
+    let empty = Map::new();
+    let keys = flat_schema
+        .get("properties")
+        .and_then(Value::as_object)
+        .unwrap_or(&empty);
+    let mut flat = Map::new();
+    for (name, value) in object {
+        flatten_value(name.clone(), value, keys, &mut flat);
+    }
+    Value::Object(flat)
+}
+
+enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+/// 解析 a.b[0].c 形式的键，不符合格式时返回 None
+fn parse_flat_key(key: &str) -> Option<Vec<Segment>> {
+    let end = key.find(['.', '[']).unwrap_or(key.len());
+    if end == 0 {
+        return None;
+    }
+    let mut segments = vec![Segment::Key(key[..end].to_string())];
+    let mut rest = &key[end..];
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix('.') {
+            let end = after.find(['.', '[']).unwrap_or(after.len());
+            if end == 0 {
+                return None;
+            }
+            segments.push(Segment::Key(after[..end].to_string()));
+            rest = &after[end..];
+        } else {
+            let (index, tail) = rest.strip_prefix('[')?.split_once(']')?;
+            if index.is_empty() || !index.bytes().all(|b| b.is_ascii_digit()) {
+                return None;
+            }
+            segments.push(Segment::Index(index.parse().ok()?));
+            rest = tail;
+        }
+    }
+    Some(segments)
+}
+
+enum Node {
+    Empty,
+    Value(Value),
+    Object(BTreeMap<String, Node>),
+    Array(BTreeMap<usize, Node>),
+}
+
+fn flatten_error(path: &str, message: &str) -> ArgumentError {
+    ArgumentError {
+        path: path.to_string(),
+        constraint: "flatten".to_string(),
+        message: message.to_string(),
+    }
+}
+
+fn insert(
+    node: &mut Node,
+    segments: &[Segment],
+    value: Value,
+    key: &str,
+) -> Result<(), ArgumentError> {
+    let Some((segment, rest)) = segments.split_first() else {
+        return match node {
+            Node::Empty => {
+                *node = Node::Value(value);
+                Ok(())
+            }
+            _ => Err(flatten_error(
+                key,
+                "conflicts with another flattened argument",
+            )),
+        };
+    };
+    if matches!(node, Node::Empty) {
+        *node = match segment {
+            Segment::Key(_) => Node::Object(BTreeMap::new()),
+            Segment::Index(_) => Node::Array(BTreeMap::new()),
+        };
+    }
+    let child = match (node, segment) {
+        (Node::Object(fields), Segment::Key(name)) => {
+            fields.entry(name.clone()).or_insert(Node::Empty)
+        }
+        (Node::Array(items), Segment::Index(index)) => items.entry(*index).or_insert(Node::Empty),
+        _ => {
+            return Err(flatten_error(
+                key,
+                "conflicts with another flattened argument",
+            ))
+        }
+    };
+    insert(child, rest, value, key)
+}
+
+fn into_value(node: Node, path: &str) -> Result<Value, ArgumentError> {
+    match node {
+        Node::Empty => Ok(Value::Null),
+        Node::Value(value) => Ok(value),
+        Node::Object(fields) => fields
+            .into_iter()
+            .map(|(name, child)| {
+                let path = if path.is_empty() {
+                    name.clone()
+                } else {
+                    format!("{}.{}", path, name)
+                };
+                into_value(child, &path).map(|value| (name, value))
+            })
+            .collect::<Result<Map<_, _>, _>>()
+            .map(Value::Object),
+        Node::Array(items) => {
+            let mut values = Vec::with_capacity(items.len());
+            for (expected, (index, child)) in items.into_iter().enumerate() {
+                if index != expected {
+                    return Err(flatten_error(
+                        &format!("{}[{}]", path, expected),
+                        "array items must be given from index 0 without gaps",
+                    ));
+                }
+                values.push(into_value(child, &format!("{}[{}]", path, index))?);
+            }
+            Ok(Value::Array(values))
+        }
+    }
+}
+
+/// 扁平参数还原为嵌套结构；不含分隔符的键与格式不符的键原样保留
+pub fn inflate_arguments(arguments: &Value) -> Result<Value, ArgumentError> {
+    let Some(object) = arguments.as_object() else {
+        return Ok(arguments.clone());
+    };
+    let mut root = Node::Object(BTreeMap::new());
+    for (key, value) in object {
+        let segments = parse_flat_key(key).unwrap_or_else(|| vec![Segment::Key(key.clone())]);
+        insert(&mut root, &segments, value.clone(), key)?;
+    }
+    into_value(root, "")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const LIMITS: FlattenLimits = FlattenLimits {
+        max_depth: 3,
+        max_array_items: 2,
+    };
+
+    fn profile_schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "id": {"type": "integer"},
+                "profile": {
+                    "type": "object",
+                    "description": "User profile",
+                    "properties": {
+                        "age": {"type": "integer", "minimum": 0},
+                        "skills": {
+                            "type": "array",
+                            "items": {
+                                "type": "object",
+                                "properties": {
+                                    "name": {"type": "string", "description": "Skill name"},
+                                    "level": {"type": "integer"}
+                                },
+                                "required": ["name"]
+                            }
+                        }
+                    },
+                    "required": ["age"]
+                },
+                "tags": {"type": "array", "items": {"type": "string"}}
+            },
+            "required": ["id", "profile"]
+        })
+    }
+
+    #[test]
+    fn test_flatten_nested_objects_and_arrays() {
+        let flattened = flatten_input_schema(&profile_schema(), LIMITS);
+        assert!(flattened.warnings.is_empty());
+        let schema = flattened.schema.unwrap();
+        let properties = schema["properties"].as_object().unwrap();
+        let keys: Vec<&str> = properties.keys().map(String::as_str).collect();
+        assert_eq!(
+            keys,
+            vec![
+                "id",
+                "profile.age",
+                "profile.skills[0].level",
+                "profile.skills[0].name",
+                "profile.skills[1].level",
+                "profile.skills[1].name",
+                "tags[0]",
+                "tags[1]",
+            ]
+        );
+        assert_eq!(properties["profile.age"]["minimum"], 0);
+        assert_eq!(
+            properties["profile.skills[1].name"]["description"],
+            "User profile > skills > item 1 (at most 2 items) > Skill name"
+        );
+        // 必填沿路径传递，数组元素中的字段不标记为必填
+        assert_eq!(schema["required"], json!(["id", "profile.age"]));
+    }
+
+    #[test]
+    fn test_flatten_inflate_round_trip() {
+        let schema = flatten_input_schema(&profile_schema(), LIMITS)
+            .schema
+            .unwrap();
+        let nested = json!({
+            "id": 7,
+            "profile": {
+                "age": 30,
+                "skills": [{"name": "rust", "level": 3}, {"name": "go"}, {"name": "sql"}]
+            },
+            "tags": [],
+            "extra": {"note": null}
+        });
+        let flat = flatten_arguments(&nested, &schema);
+        assert_eq!(flat["profile.skills[0].name"], "rust");
+        // 超出展开个数的元素与空数组同样可以表示
+        assert_eq!(flat["profile.skills[2].name"], "sql");
+        assert_eq!(flat["tags"], json!([]));
+        assert_eq!(flat["extra.note"], Value::Null);
+        assert_eq!(inflate_arguments(&flat).unwrap(), nested);
+
+        let inflated = inflate_arguments(&json!({
+            "id": 1,
+            "profile.age": 20,
+            "tags[0]": "a",
+            "tags[1]": "b"
+        }))
+        .unwrap();
+        assert_eq!(
+            inflated,
+            json!({"id": 1, "profile": {"age": 20}, "tags": ["a", "b"]})
+        );
+    }
+
+    #[test]
+    fn test_inflate_rejects_gaps_and_conflicts() {
+        let error = inflate_arguments(&json!({"tags[1]": "b"})).unwrap_err();
+        assert_eq!(error.path, "tags[0]");
+        let error =
+            inflate_arguments(&json!({"profile": {"age": 1}, "profile.age": 2})).unwrap_err();
+        assert_eq!(error.constraint, "flatten");
+    }
+
+    #[test]
+    fn test_depth_limit_keeps_deeper_objects_nested() {
+        let limits = FlattenLimits {
+            max_depth: 1,
+            max_array_items: 2,
+        };
+        let schema = flatten_input_schema(&profile_schema(), limits)
+            .schema
+            .unwrap();
+        let properties = schema["properties"].as_object().unwrap();
+        assert!(properties.contains_key("profile.age"));
+        assert_eq!(properties["profile.skills"]["type"], "array");
+        assert!(properties.contains_key("tags[1]"));
+
+        let nested = json!({"id": 1, "profile": {"age": 2, "skills": [{"name": "rust"}]}});
+        let flat = flatten_arguments(&nested, &schema);
+        assert_eq!(flat["profile.skills"], json!([{"name": "rust"}]));
+        assert_eq!(inflate_arguments(&flat).unwrap(), nested);
+
+        let limits = FlattenLimits {
+            max_depth: 0,
+            max_array_items: 2,
+        };
+        assert!(flatten_input_schema(&profile_schema(), limits)
+            .schema
+            .is_none());
+    }
+
+    #[test]
+    fn test_dynamic_keys_fall_back_with_warning() {
+        let input_schema = json!({
+            "type": "object",
+            "properties": {
+                "labels": {"type": "object", "additionalProperties": {"type": "string"}},
+                "owner": {"type": "object", "properties": {"name": {"type": "string"}}}
+            }
+        });
+        let flattened = flatten_input_schema(&input_schema, LIMITS);
+        assert_eq!(flattened.warnings.len(), 1);
+        assert!(flattened.warnings[0].contains("'labels'"));
+        let schema = flattened.schema.unwrap();
+        assert_eq!(
+            schema["properties"]["labels"]["additionalProperties"]["type"],
+            "string"
+        );
+        assert!(schema["properties"].get("owner.name").is_some());
+
+        // 只有动态键对象时整个 inputSchema 保持嵌套
+        let input_schema = json!({
+            "type": "object",
+            "properties": {"labels": {"type": "object", "additionalProperties": true}}
+        });
+        let flattened = flatten_input_schema(&input_schema, LIMITS);
+        assert!(flattened.schema.is_none());
+        assert_eq!(flattened.warnings.len(), 1);
+
+        let input_schema = json!({
+            "type": "object",
+            "properties": {"a.b": {"type": "object", "properties": {"c": {"type": "string"}}}}
+        });
+        let flattened = flatten_input_schema(&input_schema, LIMITS);
+        assert!(flattened.schema.is_none());
+        assert!(flattened.warnings[0].contains("'a.b'"));
+    }
+}
@@ -0,0 +1,194 @@
+use crate::config::SseSessionConfig;
+use dashmap::DashMap;
+use std::net::IpAddr;
+use std::sync::OnceLock;
+use std::time::Duration;
+use tokio::time::Instant;
+use uuid::Uuid;
+
+/// 全局 SSE 会话密钥登记表，启动时由配置初始化
+pub static SESSION_SECRETS: OnceLock<SessionSecrets> = OnceLock::new();
+
+pub fn session_secrets() -> &'static SessionSecrets {
+    SESSION_SECRETS.get_or_init(|| SessionSecrets::new(&SseSessionConfig::default()))
+}
+
+/// 密钥校验结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SecretCheck {
+    Valid,
+    /// 会话未登记，交由传输层按会话不存在处理
+    UnknownSession,
+    Mismatch,
+}
+
+struct SessionSecret {
+    current: String,
+    /// 轮换前的密钥及其失效时间
+    previous: Option<(String, Instant)>,
+}
+
+/// 校验失败计数的时间窗口
+struct FailureWindow {
+    started: Instant,
+    count: u32,
+}
+
+/// 旧版 SSE 会话的密钥与来源 IP 的校验失败计数
+pub struct SessionSecrets {
+    sessions: DashMap<String, SessionSecret>,
+    failures: DashMap<IpAddr, FailureWindow>,
+    rotation_interval: Option<Duration>,
+    grace_period: Duration,
+    max_failed_attempts: u32,
+    failure_window: Duration,
+}
+
+fn generate_secret() -> String {
+    format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())
+}
+
+impl SessionSecrets {
+    pub fn new(config: &SseSessionConfig) -> Self {
+        Self {
+            sessions: DashMap::new(),
+            failures: DashMap::new(),
+            rotation_interval: (config.rotation_interval_secs > 0)
+                .then(|| Duration::from_secs(config.rotation_interval_secs)),
+            grace_period: Duration::from_secs(config.grace_period_secs),
+            max_failed_attempts: config.max_failed_attempts,
+            failure_window: Duration::from_secs(config.failed_attempt_window_secs),
+        }
+    }
+
+    pub fn rotation_interval(&self) -> Option<Duration> {
+        self.rotation_interval
+    }
+
+    /// 为新建立的会话生成密钥
+    pub fn issue(&self, session_id: &str) -> String {
+        let secret = generate_secret();
+        self.sessions.insert(
+            session_id.to_string(),
+            SessionSecret {
+                current: secret.clone(),
+                previous: None,
+            },
+        );
+        secret
+    }
+
+    /// 轮换密钥，旧密钥在宽限期内仍然有效；会话已关闭时返回 None
+    pub fn rotate(&self, session_id: &str) -> Option<String> {
+        let mut entry = self.sessions.get_mut(session_id)?;
+        let secret = generate_secret();
+        let previous = std::mem::replace(&mut entry.current, secret.clone());
+        entry.previous = Some((previous, Instant::now() + self.grace_period));
+        Some(secret)
+    }
+
+    pub fn verify(&self, session_id: &str, secret: Option<&str>) -> SecretCheck {
+        let Some(entry) = self.sessions.get(session_id) else {
+            return SecretCheck::UnknownSession;
+        };
+        let Some(secret) = secret else {
+            return SecretCheck::Mismatch;
+        };
+        let previous_valid = entry
+            .previous
+            .as_ref()
+            .is_some_and(|(previous, expires)| previous == secret && Instant::now() < *expires);
+        if entry.current == secret || previous_valid {
+            SecretCheck::Valid
+        } else {
+            SecretCheck::Mismatch
+        }
+    }
+
+    /// SSE 连接断开后移除会话
+    pub fn remove(&self, session_id: &str) {
+        self.sessions.remove(session_id);
+    }
+
+    /// 来源 IP 在当前窗口内的失败次数是否已达上限
+    pub fn is_blocked(&self, ip: IpAddr) -> bool {
+        self.failures.get(&ip).is_some_and(|window| {
+            window.started.elapsed() < self.failure_window
+                && window.count >= self.max_failed_attempts
+        })
+    }
+
+    pub fn record_failure(&self, ip: IpAddr) {
+        let now = Instant::now();
+        let mut window = self.failures.entry(ip).or_insert(FailureWindow {
+            started: now,
+            count: 0,
+        });
+        if now.duration_since(window.started) >= self.failure_window {
+            window.started = now;
+            window.count = 0;
+        }
+        window.count += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secrets() -> SessionSecrets {
+        SessionSecrets::new(&SseSessionConfig {
+            rotation_interval_secs: 60,
+            grace_period_secs: 10,
+            max_failed_attempts: 3,
+            failed_attempt_window_secs: 60,
+        })
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_rotation_invalidates_old_secret_after_grace_period() {
+        let secrets = secrets();
+        let old = secrets.issue("session-1");
+        assert_eq!(secrets.verify("session-1", Some(&old)), SecretCheck::Valid);
+        assert_eq!(secrets.verify("session-1", None), SecretCheck::Mismatch);
+        assert_eq!(
+            secrets.verify("session-2", Some(&old)),
+            SecretCheck::UnknownSession
+        );
+
+        let new = secrets.rotate("session-1").unwrap();
+        assert_ne!(old, new);
+        assert_eq!(secrets.verify("session-1", Some(&new)), SecretCheck::Valid);
+        // 宽限期内旧密钥仍可用
+        tokio::time::advance(Duration::from_secs(5)).await;
+        assert_eq!(secrets.verify("session-1", Some(&old)), SecretCheck::Valid);
+        tokio::time::advance(Duration::from_secs(6)).await;
+        assert_eq!(
+            secrets.verify("session-1", Some(&old)),
+            SecretCheck::Mismatch
+        );
+        assert_eq!(secrets.verify("session-1", Some(&new)), SecretCheck::Valid);
+
+        secrets.remove("session-1");
+        assert!(secrets.rotate("session-1").is_none());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_failed_attempts_are_rate_limited_per_ip() {
+        let secrets = secrets();
+        let attacker: IpAddr = "10.0.0.1".parse().unwrap();
+        let other: IpAddr = "10.0.0.2".parse().unwrap();
+        for _ in 0..3 {
+            assert!(!secrets.is_blocked(attacker));
+            secrets.record_failure(attacker);
+        }
+        assert!(secrets.is_blocked(attacker));
+        assert!(!secrets.is_blocked(other));
+
+        // 窗口结束后重新计数
+        tokio::time::advance(Duration::from_secs(61)).await;
+        assert!(!secrets.is_blocked(attacker));
+        secrets.record_failure(attacker);
+        assert!(!secrets.is_blocked(attacker));
+    }
+}
@@ -1,7 +1,10 @@
-use crate::models::endpoint::{ApiDetail, ApiParameter};
-use crate::models::{DbPool, McpTool, SwaggerSpec};
+use crate::models::endpoint::{ApiDetail, ApiParameter, WebhookDetail};
+use crate::models::{McpTool, MediaType, RequestBody, Server, SwaggerSpec};
+use crate::services::{endpoint_counters, synthesize_arguments};
+use crate::utils::{flatten_input_schema, flatten_limits, validate_arguments, ArgumentError};
 use anyhow::anyhow;
 use serde_json::Value;
+use std::collections::HashMap;
 use uuid::Uuid;
 
 /// Generate API details from swagger spec
@@ -11,7 +14,9 @@ pub fn generate_api_details(spec: &SwaggerSpec) -> anyhow::Result<Vec<ApiDetail>
         .servers
         .as_ref()
         .and_then(|servers| servers.first())
-        .map(|server| server.url.clone());
+        .map(|server| {
+            resolve_server_url(server, &HashMap::new()).unwrap_or_else(|_| server.url.clone())
+        });
 
     for (path, path_item) in &spec.paths {
         // Generate details for each HTTP method
@@ -82,7 +87,7 @@ pub fn create_api_detail(
 
     // Process request body
     if let Some(request_body) = &operation.request_body {
-        if let Some(content) = request_body.content.get("application/json") {
+        if let Some((_, content)) = select_request_media_type(request_body, None) {
             if let Some(schema) = &content.schema {
                 request_body_schema = Some(schema_to_json_schema(schema, spec)?);
             }
@@ -116,6 +121,13 @@ pub fn create_api_detail(
         }
     }
 
+    let mut callbacks: Vec<String> = operation
+        .callbacks
+        .as_ref()
+        .map(|c| c.keys().cloned().collect())
+        .unwrap_or_default();
+    callbacks.sort();
+
     Ok(ApiDetail {
         path: path.to_string(),
         method: method.to_string(),
@@ -128,12 +140,66 @@ pub fn create_api_detail(
         request_body_schema,
         response_schema,
         responses,
+        callbacks,
+        notes: None,
     })
 }
 
+/// webhook 资源 URI
+pub fn webhook_resource_uri(name: &str, method: &str) -> String {
+    format!("webhook://{}/{}", name, method.to_lowercase())
+}
+
+/// Generate webhook details (OpenAPI 3.1 `webhooks`) from swagger spec
+pub fn generate_webhook_details(spec: &SwaggerSpec) -> anyhow::Result<Vec<WebhookDetail>> {
+    let mut details = Vec::new();
+    let Some(webhooks) = &spec.webhooks else {
+        return Ok(details);
+    };
+
+    let mut names: Vec<&String> = webhooks.keys().collect();
+    names.sort();
+
+    for name in names {
+        let path_item = &webhooks[name];
+        let methods = [
+            ("GET", &path_item.get),
+            ("POST", &path_item.post),
+            ("PUT", &path_item.put),
+            ("DELETE", &path_item.delete),
+            ("PATCH", &path_item.patch),
+        ];
+        for (method, operation_opt) in methods {
+            if let Some(operation) = operation_opt {
+                let request_body_schema = match operation
+                    .request_body
+                    .as_ref()
+                    .and_then(|body| select_request_media_type(body, None))
+                    .and_then(|(_, media)| media.schema.as_ref())
+                {
+                    Some(schema) => Some(schema_to_json_schema(schema, spec)?),
+                    None => None,
+                };
+
+                details.push(WebhookDetail {
+                    name: name.clone(),
+                    method: method.to_string(),
+                    summary: operation.summary.clone(),
+                    description: operation.description.clone(),
+                    request_body_schema,
+                    uri: webhook_resource_uri(name, method),
+                });
+            }
+        }
+    }
+
+    Ok(details)
+}
+
 pub fn generate_mcp_tools(spec: &SwaggerSpec) -> anyhow::Result<Vec<McpTool>> {
     let mut tools = Vec::new();
 
+    // 按 spec 中 paths 的声明顺序输出，同一 spec 每次生成的工具顺序一致
     for (path, path_item) in &spec.paths {
         // Generate tools for each HTTP method
         if let Some(operation) = &path_item.get {
@@ -152,6 +218,10 @@ pub fn generate_mcp_tools(spec: &SwaggerSpec) -> anyhow::Result<Vec<McpTool>> {
             tools.push(create_mcp_tool("PATCH", path, operation, spec)?);
         }
     }
+    // 声明了 x-mcp-tool-allowlist 时只保留列出的工具，顺序不变
+    if let Some(allowlist) = &spec.tool_allowlist {
+        tools.retain(|tool| allowlist.contains(&tool.name));
+    }
 
     Ok(tools)
 }
@@ -210,10 +280,7 @@ pub fn create_mcp_tool(
             if param.location == "path" {
                 properties.insert(
                     param.name.clone(),
-                    serde_json::json!({
-                        "type": "string",
-                        "description": param.description.clone().unwrap_or_default()
-                    }),
+                    parameter_property(param, "string".to_string(), spec),
                 );
                 if param.required.unwrap_or(false) {
                     required.push(param.name.clone());
@@ -228,10 +295,7 @@ pub fn create_mcp_tool(
 
                 properties.insert(
                     param.name.clone(),
-                    serde_json::json!({
-                        "type": param_type,
-                        "description": param.description.clone().unwrap_or_default()
-                    }),
+                    parameter_property(param, param_type, spec),
                 );
                 if param.required.unwrap_or(false) {
                     required.push(param.name.clone());
@@ -240,9 +304,12 @@ pub fn create_mcp_tool(
         }
     }
 
+    // 请求体示例，对象示例的字段作为对应属性的 examples
+    let body_example = request_body_example(operation, spec);
+
     // Add request body if present
     if let Some(request_body) = &operation.request_body {
-        if let Some(content) = request_body.content.get("application/json") {
+        if let Some((_, content)) = select_request_media_type(request_body, None) {
             if let Some(schema) = &content.schema {
                 // Instead of wrapping in "body", directly expand the schema properties
                 let body_schema = schema_to_json_schema(schema, spec)?;
@@ -251,7 +318,11 @@ pub fn create_mcp_tool(
                 {
                     // Insert all properties from the body schema directly
                     for (key, value) in body_properties {
-                        properties.insert(key.clone(), value.clone());
+                        let mut property = value.clone();
+                        if let Some(example) = body_example.as_ref().and_then(|e| e.get(key)) {
+                            prepend_example(&mut property, example.clone());
+                        }
+                        properties.insert(key.clone(), property);
                     }
 
                     // Handle required fields from the body schema
@@ -290,7 +361,7 @@ pub fn create_mcp_tool(
     }
 
     // Create input schema - use default empty object if no properties
-    let input_schema = if properties.is_empty() {
+    let mut input_schema = if properties.is_empty() {
         serde_json::json!({
             "type": "object",
             "title": "EmptyObject",
@@ -322,15 +393,331 @@ pub fn create_mcp_tool(
         None
     };
 
+    attach_example_arguments(&tool_name, &mut input_schema);
+
+    // 开启 x-mcp-flatten 时展开为单层参数，原 schema 留作调用时校验
+    let mut nested_input_schema = None;
+    let mut warnings = Vec::new();
+    if operation.flatten.or(spec.flatten).unwrap_or(false) {
+        let flattened = flatten_input_schema(&input_schema, flatten_limits());
+        warnings = flattened
+            .warnings
+            .into_iter()
+            .map(|warning| format!("Tool {}: {}", tool_name, warning))
+            .collect();
+        if let Some(schema) = flattened.schema {
+            nested_input_schema = Some(std::mem::replace(&mut input_schema, schema));
+        }
+    }
+
+    // 请求体示例追加到描述中，帮助模型构造参数
+    let title = match body_example.and_then(|e| serde_json::to_string_pretty(&e).ok()) {
+        Some(example) => format!("{}\n\nExample request body:\n{}", title, example),
+        None => title,
+    };
+
+    let title = if is_deprecated(operation) {
+        mark_deprecated(&mut input_schema);
+        format!("{}{}", DEPRECATED_PREFIX, title)
+    } else {
+        title
+    };
+
     Ok(McpTool {
         name: tool_name,
         title: description,
         description: title,
         input_schema,
         output_schema,
+        nested_input_schema,
+        warnings,
     })
 }
 
+/// 提取首选 media type 的请求体示例：
+/// 优先 media type 的 example，其次 examples 中按名称排序的第一个，最后是 schema(含 $ref) 的 example
+fn request_body_example(operation: &crate::models::Operation, spec: &SwaggerSpec) -> Option<Value> {
+    let (_, media_type) = select_request_media_type(operation.request_body.as_ref()?, None)?;
+
+    media_type
+        .example
+        .clone()
+        .or_else(|| {
+            let examples = media_type.examples.as_ref()?;
+            let mut names: Vec<&String> = examples.keys().collect();
+            names.sort();
+            names
+                .into_iter()
+                .find_map(|name| examples[name].get("value").cloned())
+        })
+        .or_else(|| {
+            let schema = media_type.schema.as_ref()?;
+            if schema.example.is_some() {
+                return schema.example.clone();
+            }
+            let name = schema
+                .reference
+                .as_ref()?
+                .strip_prefix("#/components/schemas/")?;
+            spec.components
+                .as_ref()?
+                .schemas
+                .as_ref()?
+                .get(name)?
+                .example
+                .clone()
+        })
+}
+
+/// tools/list 中 inputSchema._meta 下的示例参数
+pub const EXAMPLE_ARGUMENTS_META_KEY: &str = "exampleArguments";
+
+/// 示例放到属性 examples 的最前面，已存在时只调整顺序
+fn prepend_example(property: &mut Value, example: Value) {
+    let Some(property) = property.as_object_mut() else {
+        return;
+    };
+    let examples = property
+        .entry("examples")
+        .or_insert_with(|| Value::Array(Vec::new()));
+    if let Value::Array(examples) = examples {
+        examples.retain(|e| e != &example);
+        examples.insert(0, example);
+    }
+}
+
+/// 由 inputSchema 合成完整示例参数：必填字段按 example > default > 占位值取值，
+/// 可选字段仅在 spec 提供示例时填入；没有任何字段时返回 None
+pub fn example_arguments(input_schema: &Value) -> Option<Value> {
+    let properties = input_schema.get("properties")?.as_object()?;
+    let mut example = synthesize_arguments(input_schema);
+    let object = example.as_object_mut()?;
+    for (name, property) in properties {
+        if object.contains_key(name) {
+            continue;
+        }
+        if let Some(value) = property
+            .get("examples")
+            .and_then(|e| e.as_array())
+            .and_then(|e| e.first())
+        {
+            object.insert(name.clone(), value.clone());
+        }
+    }
+    (!object.is_empty()).then_some(example)
+}
+
+/// 示例参数须覆盖全部必填字段并通过工具自身 inputSchema 的校验
+pub fn check_example_arguments(input_schema: &Value, example: &Value) -> Result<(), ArgumentError> {
+    let required = input_schema
+        .get("required")
+        .and_then(|r| r.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|r| r.as_str());
+    for name in required {
+        if example.get(name).is_none() {
+            return Err(ArgumentError {
+                path: name.to_string(),
+                constraint: "required".to_string(),
+                message: "required field is missing".to_string(),
+            });
+        }
+    }
+    validate_arguments(input_schema, example)
+}
+
+/// 工具已附带的示例参数，契约测试等直接复用
+pub fn tool_example_arguments(input_schema: &Value) -> Option<&Value> {
+    input_schema.get("_meta")?.get(EXAMPLE_ARGUMENTS_META_KEY)
+}
+
+/// 按 HTTP 语义幂等的方法，重复请求不会产生额外副作用
+pub fn is_idempotent_method(method: &str) -> bool {
+    matches!(
+        method.to_uppercase().as_str(),
+        "GET" | "HEAD" | "OPTIONS" | "TRACE" | "PUT" | "DELETE"
+    )
+}
+
+/// 操作能否安全重试：x-mcp-idempotent 优先，未声明时按 HTTP 方法判断
+pub fn is_idempotent(method: &str, operation: &crate::models::Operation) -> bool {
+    operation
+        .idempotent
+        .unwrap_or_else(|| is_idempotent_method(method))
+}
+
+/// tools/list 中 inputSchema._meta 下的废弃标注
+pub const DEPRECATED_META_KEY: &str = "deprecated";
+
+/// 废弃操作的工具描述前缀
+pub const DEPRECATED_PREFIX: &str = "[DEPRECATED] ";
+
+pub fn is_deprecated(operation: &crate::models::Operation) -> bool {
+    operation.deprecated.unwrap_or(false)
+}
+
+/// 写入 inputSchema._meta.deprecated = true，保留已有的示例参数
+fn mark_deprecated(input_schema: &mut Value) {
+    let Some(schema) = input_schema.as_object_mut() else {
+        return;
+    };
+    let meta = schema
+        .entry("_meta")
+        .or_insert_with(|| Value::Object(Default::default()));
+    if let Value::Object(meta) = meta {
+        meta.insert(DEPRECATED_META_KEY.to_string(), Value::Bool(true));
+    }
+}
+
+/// 合成示例参数写入 inputSchema._meta；自校验失败时只告警并不附带示例，不影响工具生成
+fn attach_example_arguments(tool_name: &str, input_schema: &mut Value) {
+    let Some(example) = example_arguments(input_schema) else {
+        return;
+    };
+    match check_example_arguments(input_schema, &example) {
+        Ok(()) => {
+            input_schema["_meta"] = serde_json::json!({ EXAMPLE_ARGUMENTS_META_KEY: example });
+        }
+        Err(e) => tracing::warn!(
+            "Example arguments of tool {} do not match its inputSchema, omitted: {}",
+            tool_name,
+            e
+        ),
+    }
+}
+
+/// 生成工具参数 schema 时保留的取值约束关键字
+pub const CONSTRAINT_KEYWORDS: &[&str] = &[
+    "enum",
+    "const",
+    "pattern",
+    "minimum",
+    "maximum",
+    "exclusiveMinimum",
+    "exclusiveMaximum",
+    "minLength",
+    "maxLength",
+    "minItems",
+    "maxItems",
+    "multipleOf",
+];
+
+/// 取值约束带入 JSON Schema；OpenAPI 3.0 的布尔 exclusiveMinimum/Maximum 转为 3.1 的数值形式
+fn insert_schema_constraints(
+    json_schema: &mut serde_json::Map<String, Value>,
+    schema: &crate::models::Schema,
+) {
+    if let Some(values) = &schema.enum_values {
+        json_schema.insert("enum".to_string(), Value::Array(values.clone()));
+    }
+    if let Some(value) = &schema.const_value {
+        json_schema.insert("const".to_string(), value.clone());
+    }
+    if let Some(pattern) = &schema.pattern {
+        json_schema.insert("pattern".to_string(), Value::String(pattern.clone()));
+    }
+    for (bound, exclusive, key, exclusive_key) in [
+        (
+            &schema.minimum,
+            &schema.exclusive_minimum,
+            "minimum",
+            "exclusiveMinimum",
+        ),
+        (
+            &schema.maximum,
+            &schema.exclusive_maximum,
+            "maximum",
+            "exclusiveMaximum",
+        ),
+    ] {
+        match (bound, exclusive) {
+            (Some(bound), Some(Value::Bool(true))) => {
+                json_schema.insert(exclusive_key.to_string(), Value::Number(bound.clone()));
+            }
+            (bound, exclusive) => {
+                if let Some(bound) = bound {
+                    json_schema.insert(key.to_string(), Value::Number(bound.clone()));
+                }
+                if let Some(Value::Number(exclusive)) = exclusive {
+                    json_schema.insert(exclusive_key.to_string(), Value::Number(exclusive.clone()));
+                }
+            }
+        }
+    }
+    for (key, value) in [
+        ("minLength", schema.min_length),
+        ("maxLength", schema.max_length),
+        ("minItems", schema.min_items),
+        ("maxItems", schema.max_items),
+    ] {
+        if let Some(value) = value {
+            json_schema.insert(key.to_string(), Value::from(value));
+        }
+    }
+    if let Some(multiple_of) = &schema.multiple_of {
+        json_schema.insert("multipleOf".to_string(), Value::Number(multiple_of.clone()));
+    }
+}
+
+/// 路径、查询参数的 schema 约束（含 $ref 解析后的）合并到参数属性上
+fn parameter_property(
+    param: &crate::models::Parameter,
+    param_type: String,
+    spec: &SwaggerSpec,
+) -> Value {
+    let mut property = serde_json::json!({
+        "type": param_type,
+        "description": param.description.clone().unwrap_or_default()
+    });
+    if let Some(Ok(Value::Object(param_schema))) = param
+        .schema
+        .as_ref()
+        .map(|schema| schema_to_json_schema(schema, spec))
+    {
+        for key in CONSTRAINT_KEYWORDS {
+            if let Some(value) = param_schema.get(*key) {
+                property[*key] = value.clone();
+            }
+        }
+        for key in ["items", "default", "examples"] {
+            if let Some(value) = param_schema.get(key) {
+                property[key] = value.clone();
+            }
+        }
+    }
+    // 参数级示例优先于 schema 上的示例，命名示例按名称排序
+    let mut examples: Vec<Value> = Vec::new();
+    examples.extend(param.example.clone());
+    if let Some(named) = &param.examples {
+        let mut names: Vec<&String> = named.keys().collect();
+        names.sort();
+        examples.extend(
+            names
+                .into_iter()
+                .filter_map(|name| named[name].get("value").cloned()),
+        );
+    }
+    for example in examples.into_iter().rev() {
+        prepend_example(&mut property, example);
+    }
+    // 路径参数统一为字符串类型，示例与默认值中的数字、布尔值转为字符串
+    if property["type"] == "string" {
+        let stringify = |value: &mut Value| match value {
+            Value::Number(n) => *value = Value::String(n.to_string()),
+            Value::Bool(b) => *value = Value::String(b.to_string()),
+            _ => {}
+        };
+        if let Some(default) = property.get_mut("default") {
+            stringify(default);
+        }
+        if let Some(Value::Array(examples)) = property.get_mut("examples") {
+            examples.iter_mut().for_each(stringify);
+        }
+    }
+    property
+}
+
 pub fn schema_to_json_schema(
     schema: &crate::models::Schema,
     spec: &SwaggerSpec,
@@ -436,6 +823,15 @@ fn schema_to_json_schema_with_context(
         );
     }
 
+    insert_schema_constraints(&mut json_schema, schema);
+
+    if let Some(default) = &schema.default {
+        json_schema.insert("default".to_string(), default.clone());
+    }
+    if let Some(example) = &schema.example {
+        json_schema.insert("examples".to_string(), Value::Array(vec![example.clone()]));
+    }
+
     if let Some(properties) = &schema.properties {
         let mut props = serde_json::Map::new();
         for (key, prop_schema) in properties {
@@ -496,7 +892,18 @@ fn schema_to_json_schema_with_context(
     Ok(Value::Object(json_schema))
 }
 
-pub async fn update_metrics(pool: &DbPool, endpoint_id: Uuid, success: bool) -> anyhow::Result<()> {
+/// 更新端点累计指标，并记入快照窗口的内存计数器；
+/// executor 可以是连接池或调用方已获取的连接
+pub async fn update_metrics<'e, E>(
+    executor: E,
+    endpoint_id: Uuid,
+    success: bool,
+    latency: std::time::Duration,
+) -> anyhow::Result<()>
+where
+    E: sqlx::Executor<'e, Database = sqlx::MySql>,
+{
+    endpoint_counters().record_call(endpoint_id, success, latency);
     let error_increment = if success { 0 } else { 1 };
     sqlx::query(
         "UPDATE endpoint_metrics SET
@@ -507,19 +914,75 @@ pub async fn update_metrics(pool: &DbPool, endpoint_id: Uuid, success: bool) ->
     )
     .bind(error_increment)
     .bind(endpoint_id.to_string())
-    .execute(pool)
+    .execute(executor)
     .await?;
 
     Ok(())
 }
 
+/// 请求体 media type 的默认优先级：json > form > 其他；同级按名称排序，保证结果确定
+fn media_type_rank(content_type: &str) -> (u8, String) {
+    let essence = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    let rank = match essence.as_str() {
+        "application/json" => 0,
+        e if e.ends_with("/json") || e.ends_with("+json") => 1,
+        "application/x-www-form-urlencoded" => 2,
+        "multipart/form-data" => 3,
+        _ => 4,
+    };
+    (rank, essence)
+}
+
+/// 选择构建请求体使用的 media type：端点配置的首选类型在 spec 中存在时优先，否则按默认优先级
+pub fn select_request_media_type<'a>(
+    request_body: &'a RequestBody,
+    preferred: Option<&str>,
+) -> Option<(&'a str, &'a MediaType)> {
+    if let Some(preferred) = preferred.map(str::trim).filter(|p| !p.is_empty()) {
+        if let Some((content_type, media_type)) = request_body
+            .content
+            .iter()
+            .find(|(content_type, _)| content_type.eq_ignore_ascii_case(preferred))
+        {
+            return Some((content_type.as_str(), media_type));
+        }
+    }
+    request_body
+        .content
+        .iter()
+        .min_by(|(a, _), (b, _)| media_type_rank(a).cmp(&media_type_rank(b)).then(a.cmp(b)))
+        .map(|(content_type, media_type)| (content_type.as_str(), media_type))
+}
+
+pub fn is_form_urlencoded(content_type: &str) -> bool {
+    media_type_rank(content_type).1 == "application/x-www-form-urlencoded"
+}
+
+/// 实际发送的 Content-Type：json 与 form 按所选类型发送，其余类型仍以 JSON 编码
+fn request_content_type(selected: Option<&str>) -> String {
+    match selected {
+        Some(content_type) if media_type_rank(content_type).0 <= 2 => content_type.to_string(),
+        _ => "application/json".to_string(),
+    }
+}
+
 pub fn extract_request_parts(
     arguments: &Value,
     operation: &crate::models::Operation,
+    preferred_content_type: Option<&str>,
 ) -> anyhow::Result<(Vec<(String, String)>, Vec<(String, String)>, Option<Value>)> {
     let mut query_params = Vec::new();
     let mut headers = Vec::new();
     let mut body = None;
+    let selected = operation
+        .request_body
+        .as_ref()
+        .and_then(|request_body| select_request_media_type(request_body, preferred_content_type));
 
     // 根据Swagger规范中的参数定义来组织参数
     if let Some(parameters) = &operation.parameters {
@@ -555,13 +1018,13 @@ pub fn extract_request_parts(
     }
 
     // 对于POST/PUT/PATCH请求，处理请求体
-    if let Some(request_body) = &operation.request_body {
+    if operation.request_body.is_some() {
         // 检查arguments中是否有body字段
         if let Some(body_value) = arguments.get("body") {
             body = Some(body_value.clone());
         } else {
             // 根据requestBody的schema定义来确定请求体内容
-            if let Some(content) = request_body.content.values().next() {
+            if let Some((_, content)) = selected {
                 if let Some(schema) = &content.schema {
                     if let Some(properties) = &schema.properties {
                         // 创建请求体对象，只包含schema中定义的属性
@@ -633,9 +1096,12 @@ pub fn extract_request_parts(
         body = None;
     }
 
-    // Add default content-type for JSON if we have a body
+    // 按所选 media type 设置 Content-Type
     if body.is_some() {
-        headers.push(("Content-Type".to_string(), "application/json".to_string()));
+        headers.push((
+            "Content-Type".to_string(),
+            request_content_type(selected.map(|(content_type, _)| content_type)),
+        ));
     }
 
     Ok((query_params, headers, body))
@@ -679,12 +1145,81 @@ pub fn build_url(base_url: &str, path: &str, arguments: &Value) -> anyhow::Resul
     Ok(format!("{}{}", base_url.trim_end_matches('/'), url_path))
 }
 
-pub fn build_base_url(swagger_spec: &crate::models::SwaggerSpec) -> anyhow::Result<String> {
+/// 替换 server url 中的 `{name}` 变量：端点覆盖值优先，其次为变量的 default
+pub fn resolve_server_url(
+    server: &Server,
+    overrides: &HashMap<String, String>,
+) -> anyhow::Result<String> {
+    let mut url = String::with_capacity(server.url.len());
+    let mut rest = server.url.as_str();
+    while let Some(start) = rest.find('{') {
+        let end = rest[start..]
+            .find('}')
+            .map(|end| start + end)
+            .ok_or_else(|| anyhow!("Unclosed server variable in {}", server.url))?;
+        let name = &rest[start + 1..end];
+        let variable = server
+            .variables
+            .as_ref()
+            .and_then(|variables| variables.get(name));
+        let value = match (overrides.get(name), variable) {
+            (Some(value), Some(variable)) => {
+                if let Some(allowed) = &variable.enum_values {
+                    if !allowed.contains(value) {
+                        return Err(anyhow!(
+                            "Server variable {} must be one of {:?}, got {}",
+                            name,
+                            allowed,
+                            value
+                        ));
+                    }
+                }
+                value
+            }
+            (Some(value), None) => value,
+            (None, Some(variable)) => &variable.default,
+            (None, None) => return Err(anyhow!("Server variable {} has no value", name)),
+        };
+        url.push_str(&rest[..start]);
+        url.push_str(value);
+        rest = &rest[end + 1..];
+    }
+    url.push_str(rest);
+    Ok(url)
+}
+
+/// 端点覆盖的变量必须在第一个 server 中声明，且替换后得到完整的 url
+pub fn validate_server_variables(
+    swagger_spec: &SwaggerSpec,
+    server_variables: &HashMap<String, String>,
+) -> anyhow::Result<()> {
+    let server = swagger_spec
+        .servers
+        .as_ref()
+        .and_then(|servers| servers.first());
+    for name in server_variables.keys() {
+        let declared = server
+            .and_then(|server| server.variables.as_ref())
+            .is_some_and(|variables| variables.contains_key(name));
+        if !declared {
+            return Err(anyhow!(
+                "Server variable {} is not declared in servers",
+                name
+            ));
+        }
+    }
+    build_base_url(swagger_spec, server_variables).map(|_| ())
+}
+
+pub fn build_base_url(
+    swagger_spec: &SwaggerSpec,
+    server_variables: &HashMap<String, String>,
+) -> anyhow::Result<String> {
     // Build base URL from swagger spec
     // For OpenAPI 3.x, use servers array
     if let Some(servers) = &swagger_spec.servers {
-        if let Some(server) = servers.get(0) {
-            return Ok(server.url.clone());
+        if let Some(server) = servers.first() {
+            return resolve_server_url(server, server_variables);
         }
     }
 
@@ -1016,4 +1551,827 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_webhooks_and_callbacks_are_not_tools() -> anyhow::Result<()> {
+        let spec: SwaggerSpec = serde_json::from_str(
+            r###"{
+  "openapi": "3.1.0",
+  "info": {
+    "title": "Test API",
+    "version": "1.0.0"
+  },
+  "paths": {
+    "/orders": {
+      "post": {
+        "summary": "Create order",
+        "operationId": "createOrder",
+        "callbacks": {
+          "onPaid": {
+            "{$request.body#/callbackUrl}": {
+              "post": {
+                "summary": "Order paid",
+                "responses": { "200": { "description": "OK" } }
+              }
+            }
+          }
+        },
+        "responses": { "200": { "description": "Success" } }
+      }
+    }
+  },
+  "webhooks": {
+    "orderShipped": {
+      "post": {
+        "summary": "Order shipped",
+        "requestBody": {
+          "content": {
+            "application/json": {
+              "schema": {
+                "type": "object",
+                "properties": { "orderId": { "type": "string" } }
+              }
+            }
+          }
+        },
+        "responses": { "200": { "description": "OK" } }
+      }
+    }
+  }
+}"###,
+        )?;
+
+        let tools = generate_mcp_tools(&spec)?;
+        assert_eq!(tools.len(), 1);
+        assert_eq!(tools[0].name, "createOrder");
+
+        let details = generate_api_details(&spec)?;
+        assert_eq!(details[0].callbacks, vec!["onPaid".to_string()]);
+
+        let webhooks = generate_webhook_details(&spec)?;
+        assert_eq!(webhooks.len(), 1);
+        assert_eq!(webhooks[0].name, "orderShipped");
+        assert_eq!(webhooks[0].method, "POST");
+        assert_eq!(webhooks[0].uri, "webhook://orderShipped/post");
+        assert_eq!(
+            webhooks[0].request_body_schema.as_ref().unwrap()["properties"]["orderId"]["type"],
+            "string"
+        );
+
+        // 序列化后仍保留 webhooks 和 callbacks
+        let value = serde_json::to_value(&spec)?;
+        assert!(value["webhooks"].get("orderShipped").is_some());
+        assert!(value["paths"]["/orders"]["post"]["callbacks"]
+            .get("onPaid")
+            .is_some());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_generate_mcp_tools_order_is_stable() -> anyhow::Result<()> {
+        let mut paths = serde_json::Map::new();
+        for i in (0..30).rev() {
+            paths.insert(
+                format!("/items/{:02}", i),
+                serde_json::json!({
+                    "get": { "operationId": format!("getItem{}", i) },
+                    "delete": { "operationId": format!("deleteItem{}", i) }
+                }),
+            );
+        }
+        let content = serde_json::json!({
+            "openapi": "3.0.0",
+            "info": { "title": "Items", "version": "1.0.0" },
+            "paths": paths
+        })
+        .to_string();
+
+        let names = |content: &str| -> anyhow::Result<Vec<String>> {
+            // 每次重新反序列化
+            let spec: SwaggerSpec = serde_json::from_str(content)?;
+            Ok(generate_mcp_tools(&spec)?
+                .into_iter()
+                .map(|tool| tool.name)
+                .collect())
+        };
+
+        let first = names(&content)?;
+        assert_eq!(first[0], "getItem0");
+        assert_eq!(first[1], "deleteItem0");
+        for _ in 0..10 {
+            assert_eq!(names(&content)?, first);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_generate_mcp_tools_follows_declaration_order() -> anyhow::Result<()> {
+        let spec: SwaggerSpec = serde_json::from_str(
+            r#"{
+            "openapi": "3.0.0",
+            "info": { "title": "Order", "version": "1.0.0" },
+            "paths": {
+                "/orders": { "post": { "operationId": "createOrder" }, "get": { "operationId": "listOrders" } },
+                "/accounts": { "get": { "operationId": "listAccounts" } },
+                "/items/{id}": { "delete": { "operationId": "deleteItem" } }
+            },
+            "components": { "schemas": { "Zeta": { "type": "string" }, "Alpha": { "type": "integer" } } }
+        }"#,
+        )?;
+        let names: Vec<String> = generate_mcp_tools(&spec)?
+            .into_iter()
+            .map(|tool| tool.name)
+            .collect();
+        // 同一路径下按 GET/POST/PUT/DELETE/PATCH 的固定顺序
+        assert_eq!(
+            names,
+            vec!["listOrders", "createOrder", "listAccounts", "deleteItem"]
+        );
+        let details: Vec<String> = generate_api_details(&spec)?
+            .into_iter()
+            .map(|detail| format!("{} {}", detail.method, detail.path))
+            .collect();
+        assert_eq!(
+            details,
+            vec![
+                "GET /orders",
+                "POST /orders",
+                "GET /accounts",
+                "DELETE /items/{id}"
+            ]
+        );
+
+        // 序列化同样保留声明顺序
+        let serialized = serde_json::to_string(&spec)?;
+        let position = |needle: &str| serialized.find(needle).unwrap();
+        assert!(position("\"/orders\"") < position("\"/accounts\""));
+        assert!(position("\"Zeta\"") < position("\"Alpha\""));
+        Ok(())
+    }
+
+    #[test]
+    fn test_tool_allowlist_limits_generated_tools() -> anyhow::Result<()> {
+        let spec: SwaggerSpec = serde_json::from_str(
+            r#"{
+            "openapi": "3.0.0",
+            "info": { "title": "Allowlist", "version": "1.0.0" },
+            "x-mcp-tool-allowlist": ["deleteItem", "listOrders", "missingTool"],
+            "paths": {
+                "/orders": { "post": { "operationId": "createOrder" }, "get": { "operationId": "listOrders" } },
+                "/items/{id}": { "delete": { "operationId": "deleteItem" } }
+            }
+        }"#,
+        )?;
+        let names: Vec<String> = generate_mcp_tools(&spec)?
+            .into_iter()
+            .map(|tool| tool.name)
+            .collect();
+        // 只保留列出的工具，顺序仍按声明顺序，未知名称忽略
+        assert_eq!(names, vec!["listOrders", "deleteItem"]);
+        // 操作详情不受影响
+        assert_eq!(generate_api_details(&spec)?.len(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_request_body_example_in_description() -> anyhow::Result<()> {
+        let spec: SwaggerSpec = serde_json::from_value(serde_json::json!({
+            "openapi": "3.0.0",
+            "info": { "title": "Pets", "version": "1.0.0" },
+            "paths": {
+                "/pets": {
+                    "post": {
+                        "operationId": "createPet",
+                        "summary": "Create a pet",
+                        "requestBody": {
+                            "content": {
+                                "application/json": {
+                                    "schema": { "$ref": "#/components/schemas/Pet" },
+                                    "examples": {
+                                        "dog": { "summary": "A dog", "value": { "name": "Rex", "kind": "dog" } }
+                                    }
+                                }
+                            }
+                        }
+                    },
+                    "put": {
+                        "operationId": "replacePet",
+                        "summary": "Replace a pet",
+                        "requestBody": {
+                            "content": {
+                                "application/json": {
+                                    "schema": { "$ref": "#/components/schemas/Pet" }
+                                }
+                            }
+                        }
+                    }
+                }
+            },
+            "components": {
+                "schemas": {
+                    "Pet": {
+                        "type": "object",
+                        "properties": { "name": { "type": "string" }, "kind": { "type": "string" } },
+                        "example": { "name": "Tom", "kind": "cat" }
+                    }
+                }
+            }
+        }))?;
+
+        let tools = generate_mcp_tools(&spec)?;
+        let create = tools.iter().find(|t| t.name == "createPet").unwrap();
+        assert!(create.description.starts_with("Create a pet"));
+        assert!(create.description.contains("Example request body:"));
+        assert!(create.description.contains("\"Rex\""));
+
+        // 无 media type 示例时使用 schema 上的 example
+        let replace = tools.iter().find(|t| t.name == "replacePet").unwrap();
+        assert!(replace.description.contains("\"Tom\""));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_request_body_prefers_json_media_type() -> anyhow::Result<()> {
+        let spec: SwaggerSpec = serde_json::from_value(serde_json::json!({
+            "openapi": "3.0.0",
+            "info": { "title": "Pets", "version": "1.0.0" },
+            "paths": {
+                "/pets": {
+                    "post": {
+                        "operationId": "createPet",
+                        "requestBody": {
+                            "content": {
+                                "application/xml": {
+                                    "schema": { "type": "object", "properties": { "xmlName": { "type": "string" } } }
+                                },
+                                "application/x-www-form-urlencoded": {
+                                    "schema": { "type": "object", "properties": { "formName": { "type": "string" } } }
+                                },
+                                "application/json": {
+                                    "schema": { "type": "object", "properties": { "name": { "type": "string" } } }
+                                },
+                                "text/plain": {
+                                    "schema": { "type": "string" }
+                                }
+                            }
+                        },
+                        "responses": { "201": { "description": "created" } }
+                    }
+                }
+            }
+        }))?;
+        let operation = spec.paths["/pets"].post.as_ref().unwrap();
+        let arguments = serde_json::json!({ "name": "Rex", "formName": "Tom", "xmlName": "Max" });
+
+        // 多次执行结果一致，且总是选择 JSON
+        for _ in 0..10 {
+            let (_, headers, body) = extract_request_parts(&arguments, operation, None)?;
+            assert_eq!(body, Some(serde_json::json!({ "name": "Rex" })));
+            assert!(headers.contains(&("Content-Type".to_string(), "application/json".to_string())));
+        }
+        let tool = generate_mcp_tools(&spec)?.remove(0);
+        assert!(tool.input_schema["properties"].get("name").is_some());
+        assert!(tool.input_schema["properties"].get("xmlName").is_none());
+
+        // 端点覆盖首选类型
+        let (_, headers, body) = extract_request_parts(
+            &arguments,
+            operation,
+            Some("application/x-www-form-urlencoded"),
+        )?;
+        assert_eq!(body, Some(serde_json::json!({ "formName": "Tom" })));
+        assert!(headers
+            .iter()
+            .any(|(k, v)| k == "Content-Type" && is_form_urlencoded(v)));
+
+        // 覆盖的类型不存在时回退到默认顺序
+        let (_, _, body) = extract_request_parts(&arguments, operation, Some("application/yaml"))?;
+        assert_eq!(body, Some(serde_json::json!({ "name": "Rex" })));
+
+        let request_body = operation.request_body.as_ref().unwrap();
+        let without_json = RequestBody {
+            description: None,
+            required: None,
+            content: request_body
+                .content
+                .iter()
+                .filter(|(k, _)| k.as_str() != "application/json")
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
+        };
+        assert_eq!(
+            select_request_media_type(&without_json, None).map(|(k, _)| k),
+            Some("application/x-www-form-urlencoded")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_schema_constraints_survive_into_tool_schema() -> anyhow::Result<()> {
+        let spec: SwaggerSpec = serde_json::from_value(serde_json::json!({
+            "openapi": "3.0.0",
+            "info": { "title": "Pets", "version": "1.0.0" },
+            "paths": {
+                "/pets": {
+                    "post": {
+                        "operationId": "createPet",
+                        "parameters": [{
+                            "name": "limit",
+                            "in": "query",
+                            "schema": { "type": "integer", "minimum": 1, "maximum": 100, "multipleOf": 5 }
+                        }],
+                        "requestBody": {
+                            "content": {
+                                "application/json": {
+                                    "schema": { "$ref": "#/components/schemas/Pet" }
+                                }
+                            }
+                        },
+                        "responses": {
+                            "200": {
+                                "description": "ok",
+                                "content": {
+                                    "application/json": {
+                                        "schema": { "$ref": "#/components/schemas/Pet" }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            },
+            "components": {
+                "schemas": {
+                    "Pet": {
+                        "type": "object",
+                        "properties": {
+                            "status": { "type": "string", "enum": ["available", "sold"] },
+                            "kind": { "type": "string", "const": "pet" },
+                            "code": { "type": "string", "pattern": "^[A-Z]{3}$", "minLength": 3, "maxLength": 3 },
+                            "weight": { "type": "number", "minimum": 0, "exclusiveMinimum": true, "maximum": 50 },
+                            "tags": {
+                                "type": "array",
+                                "minItems": 1,
+                                "maxItems": 5,
+                                "items": { "type": "string", "enum": ["a", "b"] }
+                            }
+                        }
+                    }
+                }
+            }
+        }))?;
+
+        let tool = generate_mcp_tools(&spec)?.remove(0);
+        let properties = &tool.input_schema["properties"];
+        assert_eq!(properties["limit"]["minimum"], 1);
+        assert_eq!(properties["limit"]["maximum"], 100);
+        assert_eq!(properties["limit"]["multipleOf"], 5);
+        assert_eq!(
+            properties["status"]["enum"],
+            serde_json::json!(["available", "sold"])
+        );
+        assert_eq!(properties["kind"]["const"], "pet");
+        assert_eq!(properties["code"]["pattern"], "^[A-Z]{3}$");
+        assert_eq!(properties["code"]["minLength"], 3);
+        assert_eq!(properties["code"]["maxLength"], 3);
+        // OpenAPI 3.0 布尔形式转为数值形式
+        assert_eq!(properties["weight"]["exclusiveMinimum"], 0);
+        assert!(properties["weight"].get("minimum").is_none());
+        assert_eq!(properties["weight"]["maximum"], 50);
+        assert_eq!(properties["tags"]["minItems"], 1);
+        assert_eq!(properties["tags"]["maxItems"], 5);
+        assert_eq!(
+            properties["tags"]["items"]["enum"],
+            serde_json::json!(["a", "b"])
+        );
+
+        let output_schema = tool.output_schema.expect("output schema");
+        assert_eq!(
+            output_schema["properties"]["status"]["enum"],
+            serde_json::json!(["available", "sold"])
+        );
+
+        let error = crate::utils::validate_arguments(
+            &tool.input_schema,
+            &serde_json::json!({ "limit": 7 }),
+        )
+        .unwrap_err();
+        assert_eq!(error.constraint, "multipleOf");
+        Ok(())
+    }
+
+    #[test]
+    fn test_example_arguments_precedence() -> anyhow::Result<()> {
+        let spec: SwaggerSpec = serde_json::from_value(serde_json::json!({
+            "openapi": "3.0.0",
+            "info": { "title": "Pets", "version": "1.0.0" },
+            "paths": {
+                "/pets/{petId}": {
+                    "get": {
+                        "operationId": "getPet",
+                        "parameters": [
+                            {
+                                "name": "petId", "in": "path", "required": true,
+                                "schema": { "type": "integer", "example": 1 },
+                                "example": 42
+                            },
+                            {
+                                "name": "limit", "in": "query", "required": true,
+                                "schema": { "type": "integer", "default": 20, "maximum": 100 }
+                            },
+                            {
+                                "name": "sort", "in": "query", "required": true,
+                                "schema": { "type": "string" }
+                            },
+                            {
+                                "name": "lang", "in": "query",
+                                "schema": { "type": "string" },
+                                "examples": {
+                                    "zh": { "value": "zh-CN" },
+                                    "en": { "value": "en-US" }
+                                }
+                            },
+                            {
+                                "name": "verbose", "in": "query",
+                                "schema": { "type": "boolean" }
+                            }
+                        ]
+                    }
+                }
+            }
+        }))?;
+
+        let tool = generate_mcp_tools(&spec)?.remove(0);
+        let properties = &tool.input_schema["properties"];
+        // 参数级示例排在 schema 示例之前，路径参数转为字符串
+        assert_eq!(
+            properties["petId"]["examples"],
+            serde_json::json!(["42", "1"])
+        );
+        assert_eq!(properties["limit"]["default"], 20);
+        assert_eq!(
+            properties["lang"]["examples"],
+            serde_json::json!(["en-US", "zh-CN"])
+        );
+
+        // example > default > 占位值；可选字段只在有示例时出现
+        let example = tool_example_arguments(&tool.input_schema).unwrap();
+        assert_eq!(
+            example,
+            &serde_json::json!({
+                "petId": "42",
+                "limit": 20,
+                "sort": "string",
+                "lang": "en-US"
+            })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_example_arguments_from_nested_body_examples() -> anyhow::Result<()> {
+        let spec: SwaggerSpec = serde_json::from_value(serde_json::json!({
+            "openapi": "3.0.0",
+            "info": { "title": "Pets", "version": "1.0.0" },
+            "paths": {
+                "/pets": {
+                    "post": {
+                        "operationId": "createPet",
+                        "requestBody": {
+                            "content": {
+                                "application/json": {
+                                    "schema": { "$ref": "#/components/schemas/Pet" },
+                                    "example": {
+                                        "name": "Rex",
+                                        "owner": { "id": 7, "email": "rex@example.com" }
+                                    }
+                                }
+                            }
+                        }
+                    },
+                    "put": {
+                        "operationId": "replacePet",
+                        "requestBody": {
+                            "content": {
+                                "application/json": {
+                                    "schema": { "$ref": "#/components/schemas/Pet" }
+                                }
+                            }
+                        }
+                    }
+                }
+            },
+            "components": {
+                "schemas": {
+                    "Pet": {
+                        "type": "object",
+                        "required": ["name", "owner"],
+                        "properties": {
+                            "name": { "type": "string", "example": "Tom" },
+                            "owner": { "$ref": "#/components/schemas/Owner" },
+                            "tags": { "type": "array", "items": { "type": "string" } }
+                        }
+                    },
+                    "Owner": {
+                        "type": "object",
+                        "required": ["id"],
+                        "properties": {
+                            "id": { "type": "integer", "example": 1 },
+                            "email": { "type": "string", "format": "email" }
+                        }
+                    }
+                }
+            }
+        }))?;
+
+        let tools = generate_mcp_tools(&spec)?;
+        let create = tools.iter().find(|t| t.name == "createPet").unwrap();
+        // 请求体示例的嵌套对象整体作为属性示例
+        assert_eq!(
+            create.input_schema["properties"]["name"]["examples"],
+            serde_json::json!(["Rex", "Tom"])
+        );
+        assert_eq!(
+            tool_example_arguments(&create.input_schema).unwrap(),
+            &serde_json::json!({
+                "name": "Rex",
+                "owner": { "id": 7, "email": "rex@example.com" }
+            })
+        );
+
+        // 无请求体示例时使用嵌套 schema 的示例
+        let replace = tools.iter().find(|t| t.name == "replacePet").unwrap();
+        assert_eq!(
+            tool_example_arguments(&replace.input_schema).unwrap(),
+            &serde_json::json!({ "name": "Tom", "owner": { "id": 1 } })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_example_arguments_validate_against_input_schema() -> anyhow::Result<()> {
+        let spec: SwaggerSpec = serde_json::from_value(serde_json::json!({
+            "openapi": "3.0.0",
+            "info": { "title": "Pets", "version": "1.0.0" },
+            "paths": {
+                "/pets": {
+                    "get": {
+                        "operationId": "listPets",
+                        "parameters": [{
+                            "name": "status", "in": "query", "required": true,
+                            "schema": { "type": "string", "enum": ["available", "sold"], "default": "available" }
+                        }, {
+                            "name": "code", "in": "query", "required": true,
+                            "schema": { "type": "string", "pattern": "^[A-Z]{3}$", "example": "ABC" }
+                        }]
+                    },
+                    "post": {
+                        "operationId": "createPet",
+                        "parameters": [{
+                            "name": "code", "in": "query", "required": true,
+                            "schema": { "type": "string", "pattern": "^[A-Z]{3}$" },
+                            "example": "not-a-code"
+                        }]
+                    }
+                }
+            }
+        }))?;
+
+        let tools = generate_mcp_tools(&spec)?;
+        for tool in &tools {
+            if let Some(example) = tool_example_arguments(&tool.input_schema) {
+                assert_eq!(check_example_arguments(&tool.input_schema, example), Ok(()));
+            }
+        }
+        let list = tools.iter().find(|t| t.name == "listPets").unwrap();
+        assert_eq!(
+            tool_example_arguments(&list.input_schema).unwrap(),
+            &serde_json::json!({ "status": "available", "code": "ABC" })
+        );
+
+        // 示例违反 pattern：工具照常生成，但不附带示例参数
+        let create = tools.iter().find(|t| t.name == "createPet").unwrap();
+        assert!(tool_example_arguments(&create.input_schema).is_none());
+        let error = check_example_arguments(
+            &create.input_schema,
+            &example_arguments(&create.input_schema).unwrap(),
+        )
+        .unwrap_err();
+        assert_eq!(error.constraint, "pattern");
+
+        // 缺少必填字段同样不通过
+        let error =
+            check_example_arguments(&list.input_schema, &serde_json::json!({ "code": "ABC" }))
+                .unwrap_err();
+        assert_eq!(
+            (error.path.as_str(), error.constraint.as_str()),
+            ("status", "required")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_build_base_url_substitutes_server_variables() -> anyhow::Result<()> {
+        let spec: SwaggerSpec = serde_json::from_value(serde_json::json!({
+            "openapi": "3.0.0",
+            "info": {"title": "Regional API", "version": "1.0.0"},
+            "servers": [{
+                "url": "https://{region}.api.example.com:{port}/{basePath}",
+                "variables": {
+                    "region": {"default": "us-east", "enum": ["us-east", "eu-west"]},
+                    "port": {"default": "443"},
+                    "basePath": {"default": "v1"}
+                }
+            }],
+            "paths": {}
+        }))?;
+
+        assert_eq!(
+            build_base_url(&spec, &HashMap::new())?,
+            "https://us-east.api.example.com:443/v1"
+        );
+
+        // 端点覆盖值优先，未覆盖的变量仍取 default
+        let overrides: HashMap<String, String> =
+            [("region".to_string(), "eu-west".to_string())].into();
+        assert_eq!(
+            build_base_url(&spec, &overrides)?,
+            "https://eu-west.api.example.com:443/v1"
+        );
+        validate_server_variables(&spec, &overrides)?;
+
+        let overrides: HashMap<String, String> =
+            [("region".to_string(), "ap-south".to_string())].into();
+        assert!(build_base_url(&spec, &overrides)
+            .unwrap_err()
+            .to_string()
+            .contains("must be one of"));
+
+        let overrides: HashMap<String, String> =
+            [("tenant".to_string(), "acme".to_string())].into();
+        assert!(validate_server_variables(&spec, &overrides).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_undeclared_server_variable_is_rejected() -> anyhow::Result<()> {
+        let spec: SwaggerSpec = serde_json::from_value(serde_json::json!({
+            "openapi": "3.0.0",
+            "info": {"title": "Regional API", "version": "1.0.0"},
+            "servers": [{"url": "https://{region}.api.example.com"}],
+            "paths": {}
+        }))?;
+        assert!(build_base_url(&spec, &HashMap::new())
+            .unwrap_err()
+            .to_string()
+            .contains("region"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_flatten_extension_produces_flat_input_schema() -> anyhow::Result<()> {
+        let spec: SwaggerSpec = serde_json::from_value(serde_json::json!({
+            "openapi": "3.0.0",
+            "info": {"title": "Users", "version": "1.0.0"},
+            "x-mcp-flatten": true,
+            "paths": {
+                "/users": {
+                    "post": {
+                        "operationId": "createUser",
+                        "requestBody": {
+                            "content": {
+                                "application/json": {
+                                    "schema": {
+                                        "type": "object",
+                                        "required": ["profile"],
+                                        "properties": {
+                                            "profile": {
+                                                "type": "object",
+                                                "required": ["age"],
+                                                "properties": {
+                                                    "age": {"type": "integer"}
+                                                }
+                                            },
+                                            "labels": {
+                                                "type": "object",
+                                                "additionalProperties": {"type": "string"}
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    },
+                    "put": {
+                        "operationId": "replaceUser",
+                        "x-mcp-flatten": false,
+                        "requestBody": {
+                            "content": {
+                                "application/json": {
+                                    "schema": {
+                                        "type": "object",
+                                        "properties": {
+                                            "profile": {
+                                                "type": "object",
+                                                "properties": {"age": {"type": "integer"}}
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }))?;
+
+        let tools = generate_mcp_tools(&spec)?;
+        let create = tools.iter().find(|t| t.name == "createUser").unwrap();
+        assert_eq!(
+            create.input_schema["properties"]["profile.age"]["type"],
+            "integer"
+        );
+        assert_eq!(
+            create.input_schema["required"],
+            serde_json::json!(["profile.age"])
+        );
+        assert!(create.input_schema["properties"]["labels"].is_object());
+        assert!(create.nested_input_schema.as_ref().unwrap()["properties"]["profile"].is_object());
+        assert_eq!(create.warnings.len(), 1);
+        assert!(create.warnings[0].starts_with("Tool createUser: 'labels'"));
+
+        // 操作级别的扩展覆盖端点级别
+        let replace = tools.iter().find(|t| t.name == "replaceUser").unwrap();
+        assert!(replace.input_schema["properties"]["profile"].is_object());
+        assert!(replace.nested_input_schema.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_idempotent_classification() -> anyhow::Result<()> {
+        let plain: crate::models::Operation = serde_json::from_value(serde_json::json!({}))?;
+        for method in ["GET", "HEAD", "PUT", "DELETE", "get", "delete"] {
+            assert!(is_idempotent(method, &plain), "{}", method);
+        }
+        for method in ["POST", "PATCH", "post"] {
+            assert!(!is_idempotent(method, &plain), "{}", method);
+        }
+
+        // x-mcp-idempotent 覆盖按方法的判断
+        let idempotent_post: crate::models::Operation =
+            serde_json::from_value(serde_json::json!({"x-mcp-idempotent": true}))?;
+        assert!(is_idempotent("POST", &idempotent_post));
+        let unsafe_put: crate::models::Operation =
+            serde_json::from_value(serde_json::json!({"x-mcp-idempotent": false}))?;
+        assert!(!is_idempotent("PUT", &unsafe_put));
+        Ok(())
+    }
+
+    #[test]
+    fn test_deprecated_operation_is_flagged() -> anyhow::Result<()> {
+        let spec: SwaggerSpec = serde_json::from_value(serde_json::json!({
+            "openapi": "3.0.0",
+            "info": {"title": "Pets", "version": "1.0.0"},
+            "paths": {
+                "/pets/{id}": {
+                    "get": {
+                        "operationId": "getPet",
+                        "summary": "Get a pet",
+                        "deprecated": true,
+                        "parameters": [{
+                            "name": "id",
+                            "in": "path",
+                            "required": true,
+                            "schema": {"type": "string"},
+                            "example": "p-1"
+                        }]
+                    },
+                    "delete": {
+                        "operationId": "deletePet",
+                        "summary": "Delete a pet",
+                        "deprecated": false
+                    }
+                }
+            }
+        }))?;
+
+        let tools = generate_mcp_tools(&spec)?;
+        let get = tools.iter().find(|t| t.name == "getPet").unwrap();
+        assert_eq!(get.description, "[DEPRECATED] Get a pet");
+        assert_eq!(get.input_schema["_meta"][DEPRECATED_META_KEY], true);
+        // 示例参数不被覆盖
+        assert_eq!(
+            tool_example_arguments(&get.input_schema),
+            Some(&serde_json::json!({"id": "p-1"}))
+        );
+
+        let delete = tools.iter().find(|t| t.name == "deletePet").unwrap();
+        assert_eq!(delete.description, "Delete a pet");
+        assert!(delete.input_schema.get("_meta").is_none());
+        Ok(())
+    }
 }
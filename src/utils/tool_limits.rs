@@ -0,0 +1,199 @@
+use crate::config::ToolLimitsConfig;
+use crate::models::endpoint::ToolLimitAlert;
+use crate::models::McpTool;
+use rmcp::model::Tool;
+use std::fmt::Write;
+
+/// Prometheus 文本格式中的告警指标名
+pub const TOOL_LIMIT_WARNINGS_METRIC: &str = "mcp_tool_limit_warnings";
+
+/// 工具数量/体积检查结果
+#[derive(Debug, Default)]
+pub struct ToolLimitReport {
+    /// 超过软阈值，仅告警
+    pub warnings: Vec<String>,
+    /// 超过硬阈值，拒绝创建/更新
+    pub violations: Vec<String>,
+}
+
+impl ToolLimitReport {
+    fn check(&mut self, what: &str, actual: usize, soft: usize, hard: usize) {
+        // 阈值为 0 表示不限制
+        if hard > 0 && actual > hard {
+            self.violations
+                .push(format!("{} {} exceeds hard limit {}", what, actual, hard));
+        } else if soft > 0 && actual > soft {
+            self.warnings
+                .push(format!("{} {} exceeds soft limit {}", what, actual, soft));
+        }
+    }
+}
+
+/// 检查工具数量、单个 inputSchema 大小以及 tools/list 总大小，生成工具时的告警一并带出
+pub fn check_tool_limits(tools: &[McpTool], limits: &ToolLimitsConfig) -> ToolLimitReport {
+    let mut report = ToolLimitReport::default();
+    report.check(
+        "tool count",
+        tools.len(),
+        limits.soft_max_tools,
+        limits.hard_max_tools,
+    );
+
+    let mut list_bytes = 0;
+    for tool in tools {
+        let schema_bytes = serde_json::to_vec(&tool.input_schema)
+            .map(|v| v.len())
+            .unwrap_or(0);
+        report.check(
+            &format!("tool '{}' inputSchema bytes", tool.name),
+            schema_bytes,
+            limits.soft_max_schema_bytes,
+            limits.hard_max_schema_bytes,
+        );
+        list_bytes += serde_json::to_vec(&Tool::from(tool))
+            .map(|v| v.len())
+            .unwrap_or(0);
+        report.warnings.extend(tool.warnings.iter().cloned());
+    }
+    report.check(
+        "tools/list payload bytes",
+        list_bytes,
+        limits.soft_max_list_bytes,
+        limits.hard_max_list_bytes,
+    );
+
+    report
+}
+
+/// 各端点当前的工具阈值告警数，Prometheus gauge 格式，可直接配置告警规则
+pub fn render_tool_limit_alerts(alerts: &[ToolLimitAlert]) -> String {
+    let mut output = format!(
+        "# HELP {0} Tool limit warnings stored for each endpoint\n# TYPE {0} gauge\n",
+        TOOL_LIMIT_WARNINGS_METRIC
+    );
+    for alert in alerts {
+        let _ = writeln!(
+            output,
+            "{}{{endpoint=\"{}\",name=\"{}\"}} {}",
+            TOOL_LIMIT_WARNINGS_METRIC,
+            alert.endpoint_id,
+            alert
+                .endpoint_name
+                .replace('\\', "\\\\")
+                .replace('"', "\\\""),
+            alert.warnings.len()
+        );
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::SwaggerSpec;
+    use crate::utils::generate_mcp_tools;
+
+    /// 生成 tool_count 个操作、每个请求体含 fields 个字段的 spec
+    fn synthetic_tools(tool_count: usize, fields: usize) -> Vec<McpTool> {
+        let mut properties = serde_json::Map::new();
+        for i in 0..fields {
+            properties.insert(
+                format!("field_{:04}", i),
+                serde_json::json!({"type": "string", "description": "synthetic field"}),
+            );
+        }
+        let mut paths = serde_json::Map::new();
+        for i in 0..tool_count {
+            paths.insert(
+                format!("/resources/{}", i),
+                serde_json::json!({
+                    "post": {
+                        "operationId": format!("createResource{}", i),
+                        "requestBody": {
+                            "content": {
+                                "application/json": {
+                                    "schema": {"type": "object", "properties": properties}
+                                }
+                            }
+                        }
+                    }
+                }),
+            );
+        }
+        let spec: SwaggerSpec = serde_json::from_value(serde_json::json!({
+            "openapi": "3.0.0",
+            "info": {"title": "Synthetic", "version": "1.0.0"},
+            "paths": paths
+        }))
+        .unwrap();
+        generate_mcp_tools(&spec).unwrap()
+    }
+
+    fn limits() -> ToolLimitsConfig {
+        ToolLimitsConfig {
+            soft_max_tools: 10,
+            hard_max_tools: 20,
+            soft_max_schema_bytes: 2_000,
+            hard_max_schema_bytes: 10_000,
+            soft_max_list_bytes: 50_000,
+            hard_max_list_bytes: 200_000,
+            max_arguments_bytes: 0,
+        }
+    }
+
+    #[test]
+    fn test_within_limits() {
+        let report = check_tool_limits(&synthetic_tools(5, 2), &limits());
+        assert!(report.warnings.is_empty());
+        assert!(report.violations.is_empty());
+    }
+
+    #[test]
+    fn test_tool_count_thresholds() {
+        let report = check_tool_limits(&synthetic_tools(15, 1), &limits());
+        assert_eq!(report.warnings.len(), 1);
+        assert!(report.warnings[0].contains("tool count"));
+        assert!(report.violations.is_empty());
+
+        let report = check_tool_limits(&synthetic_tools(25, 1), &limits());
+        assert!(report.violations[0].contains("tool count"));
+    }
+
+    #[test]
+    fn test_schema_size_thresholds() {
+        let report = check_tool_limits(&synthetic_tools(1, 50), &limits());
+        assert!(report.warnings[0].contains("inputSchema"));
+        assert!(report.violations.is_empty());
+
+        let report = check_tool_limits(&synthetic_tools(1, 300), &limits());
+        assert!(report.violations[0].contains("inputSchema"));
+    }
+
+    #[test]
+    fn test_list_payload_thresholds() {
+        let mut limits = limits();
+        limits.soft_max_schema_bytes = 0;
+        limits.hard_max_schema_bytes = 0;
+
+        let report = check_tool_limits(&synthetic_tools(10, 100), &limits);
+        assert!(report.warnings.iter().any(|w| w.contains("tools/list")));
+        assert!(report.violations.is_empty());
+
+        let report = check_tool_limits(&synthetic_tools(20, 200), &limits);
+        assert!(report.violations.iter().any(|v| v.contains("tools/list")));
+    }
+
+    #[test]
+    fn test_render_tool_limit_alerts() {
+        let alert = ToolLimitAlert {
+            endpoint_id: uuid::Uuid::from_u128(1),
+            endpoint_name: "pet \"store\"".to_string(),
+            warnings: vec!["tool count 200 exceeds soft limit 128".to_string(); 2],
+        };
+        let output = render_tool_limit_alerts(&[alert]);
+        assert!(output.contains("# TYPE mcp_tool_limit_warnings gauge"));
+        assert!(output.ends_with(
+            "mcp_tool_limit_warnings{endpoint=\"00000000-0000-0000-0000-000000000001\",name=\"pet \\\"store\\\"\"} 2\n"
+        ));
+    }
+}
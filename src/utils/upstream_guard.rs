@@ -0,0 +1,356 @@
+use crate::config::UpstreamConfig;
+use crate::utils::{dns_resolver, endpoint_dns_overrides, HostOverrides, UpstreamFailure};
+use anyhow::{anyhow, Result};
+use std::net::IpAddr;
+use std::sync::OnceLock;
+use uuid::Uuid;
+
+/// 全局上游访问控制，启动时由配置初始化
+pub static UPSTREAM_GUARD: OnceLock<UpstreamGuard> = OnceLock::new();
+
+pub fn upstream_guard() -> &'static UpstreamGuard {
+    UPSTREAM_GUARD.get_or_init(UpstreamGuard::default)
+}
+
+/// 与 reqwest 默认策略相同的最大重定向次数
+const MAX_REDIRECTS: usize = 10;
+
+/// 上游地址被访问控制拒绝
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[error("Upstream host {host} is not allowed: {reason}")]
+pub struct UpstreamDenied {
+    pub host: String,
+    pub reason: String,
+}
+
+/// IPv4/IPv6 网段，如 169.254.0.0/16
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cidr {
+    network: IpAddr,
+    prefix: u8,
+}
+
+impl Cidr {
+    pub fn parse(text: &str) -> Result<Self> {
+        let text = text.trim();
+        let (addr, prefix) = match text.split_once('/') {
+            Some((addr, prefix)) => (addr, Some(prefix)),
+            None => (text, None),
+        };
+        let network: IpAddr = addr
+            .parse()
+            .map_err(|_| anyhow!("Invalid CIDR '{}': bad address", text))?;
+        let max = if network.is_ipv4() { 32 } else { 128 };
+        let prefix = match prefix {
+            Some(prefix) => prefix
+                .parse::<u8>()
+                .ok()
+                .filter(|p| *p <= max)
+                .ok_or_else(|| anyhow!("Invalid CIDR '{}': bad prefix length", text))?,
+            None => max,
+        };
+        Ok(Self { network, prefix })
+    }
+
+    pub fn contains(&self, ip: &IpAddr) -> bool {
+        // IPv4 映射的 IPv6 地址按 IPv4 比较，避免 ::ffff:169.254.169.254 绕过
+        let ip = match ip {
+            IpAddr::V6(v6) => v6
+                .to_ipv4_mapped()
+                .map(IpAddr::V4)
+                .unwrap_or(IpAddr::V6(*v6)),
+            v4 => *v4,
+        };
+        match (self.network, ip) {
+            (IpAddr::V4(net), IpAddr::V4(ip)) => {
+                let mask = u32::MAX.checked_shl(32 - self.prefix as u32).unwrap_or(0);
+                u32::from(net) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(net), IpAddr::V6(ip)) => {
+                let mask = u128::MAX.checked_shl(128 - self.prefix as u32).unwrap_or(0);
+                u128::from(net) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+/// 上游调用前的 SSRF 防护：主机需在白名单内（为空不限制），
+/// 且解析出的所有地址都不能落在禁止网段
+#[derive(Debug, Clone, Default)]
+pub struct UpstreamGuard {
+    allowed_hosts: Vec<String>,
+    denied_cidrs: Vec<Cidr>,
+}
+
+impl UpstreamGuard {
+    pub fn from_config(config: &UpstreamConfig) -> Result<Self> {
+        Ok(Self {
+            allowed_hosts: config
+                .allowed_hosts
+                .iter()
+                .map(|h| h.trim().to_ascii_lowercase())
+                .filter(|h| !h.is_empty())
+                .collect(),
+            denied_cidrs: config
+                .denied_cidrs
+                .iter()
+                .map(|c| Cidr::parse(c))
+                .collect::<Result<_>>()?,
+        })
+    }
+
+    /// 支持精确主机名与 *.example.com 通配（不匹配 example.com 本身）
+    fn host_allowed(&self, host: &str) -> bool {
+        self.allowed_hosts.is_empty()
+            || self
+                .allowed_hosts
+                .iter()
+                .any(|allowed| match allowed.strip_prefix("*.") {
+                    Some(suffix) => host
+                        .strip_suffix(suffix)
+                        .is_some_and(|prefix| prefix.ends_with('.')),
+                    None => allowed == host,
+                })
+    }
+
+    fn denied_cidr(&self, ip: &IpAddr) -> Option<&Cidr> {
+        self.denied_cidrs.iter().find(|cidr| cidr.contains(ip))
+    }
+
+    /// 校验完整上游 URL，主机名会被解析后逐个检查地址。
+    /// 拒绝时返回 UpstreamDenied，解析失败时返回 UpstreamFailure
+    pub async fn check(&self, url: &str) -> Result<()> {
+        self.check_with(url, &HostOverrides::new()).await
+    }
+
+    /// 端点配置了静态解析时按静态地址检查
+    pub async fn check_for_endpoint(&self, url: &str, endpoint_id: Uuid) -> Result<()> {
+        let overrides = endpoint_dns_overrides(endpoint_id).unwrap_or_default();
+        self.check_with(url, &overrides).await
+    }
+
+    pub async fn check_with(&self, url: &str, overrides: &HostOverrides) -> Result<()> {
+        let parsed = reqwest::Url::parse(url).map_err(|e| UpstreamDenied {
+            host: url.to_string(),
+            reason: e.to_string(),
+        })?;
+        let host = self.check_url(&parsed)?;
+        if self.denied_cidrs.is_empty() {
+            return Ok(());
+        }
+
+        let addresses = dns_resolver()
+            .resolve_with(&host, overrides)
+            .await
+            .map_err(UpstreamFailure::from)?;
+        self.check_addresses(&host, &addresses)?;
+        Ok(())
+    }
+
+    /// 不需要解析的检查：协议、主机白名单，以及 IP 字面量主机的禁止网段。
+    /// 返回小写的主机名
+    pub fn check_url(&self, url: &reqwest::Url) -> std::result::Result<String, UpstreamDenied> {
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(UpstreamDenied {
+                host: url.to_string(),
+                reason: format!("scheme {} is not allowed", url.scheme()),
+            });
+        }
+        let host = url
+            .host_str()
+            .ok_or_else(|| UpstreamDenied {
+                host: url.to_string(),
+                reason: "missing host".to_string(),
+            })?
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .to_ascii_lowercase();
+        if !self.host_allowed(&host) {
+            return Err(UpstreamDenied {
+                host,
+                reason: "not in upstream.allowed_hosts".to_string(),
+            });
+        }
+        if let Ok(ip) = host.parse::<IpAddr>() {
+            self.check_addresses(&host, &[ip])?;
+        }
+        Ok(host)
+    }
+
+    /// 主机解析出的任一地址落在禁止网段即拒绝
+    pub fn check_addresses(
+        &self,
+        host: &str,
+        addresses: &[IpAddr],
+    ) -> std::result::Result<(), UpstreamDenied> {
+        for ip in addresses {
+            if let Some(cidr) = self.denied_cidr(ip) {
+                return Err(UpstreamDenied {
+                    host: host.to_string(),
+                    reason: format!("{} is in denied range {}/{}", ip, cidr.network, cidr.prefix),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// 上游返回重定向时逐跳检查目标地址，主机名目标在连接前由 UpstreamResolver 检查
+    pub fn redirect_policy(&'static self) -> reqwest::redirect::Policy {
+        reqwest::redirect::Policy::custom(move |attempt| {
+            if attempt.previous().len() >= MAX_REDIRECTS {
+                return attempt.error(format!("too many redirects (max {})", MAX_REDIRECTS));
+            }
+            match self.check_url(attempt.url()) {
+                Ok(_) => attempt.follow(),
+                Err(denied) => attempt.error(denied),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn guard(allowed_hosts: &[&str], denied_cidrs: &[&str]) -> UpstreamGuard {
+        UpstreamGuard::from_config(&UpstreamConfig {
+            allowed_hosts: allowed_hosts.iter().map(|s| s.to_string()).collect(),
+            denied_cidrs: denied_cidrs.iter().map(|s| s.to_string()).collect(),
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_cidr_contains() {
+        let cidr = Cidr::parse("169.254.0.0/16").unwrap();
+        assert!(cidr.contains(&"169.254.169.254".parse().unwrap()));
+        assert!(!cidr.contains(&"169.255.0.1".parse().unwrap()));
+        assert!(cidr.contains(&"::ffff:169.254.169.254".parse().unwrap()));
+
+        let v6 = Cidr::parse("fd00::/8").unwrap();
+        assert!(v6.contains(&"fd00:ec2::254".parse().unwrap()));
+        assert!(!v6.contains(&"2001:db8::1".parse().unwrap()));
+
+        assert!(Cidr::parse("0.0.0.0/0")
+            .unwrap()
+            .contains(&"8.8.8.8".parse().unwrap()));
+        assert!(Cidr::parse("10.0.0.0/33").is_err());
+        assert!(Cidr::parse("not-an-ip/8").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_denied_cidr_is_rejected() {
+        let guard = guard(&[], &["169.254.0.0/16", "127.0.0.0/8", "::1/128"]);
+        let error = guard
+            .check("http://169.254.169.254/latest/meta-data/")
+            .await
+            .unwrap_err()
+            .downcast::<UpstreamDenied>()
+            .unwrap();
+        assert_eq!(error.host, "169.254.169.254");
+        assert!(error.reason.contains("169.254.0.0/16"));
+
+        // 主机名解析后的地址同样受限
+        assert!(guard.check("http://localhost:8080/api").await.is_err());
+        assert!(guard.check("http://[::ffff:127.0.0.1]/").await.is_err());
+
+        assert!(guard.check("http://10.1.2.3/api").await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_allowed_hosts() {
+        let guard = guard(&["api.example.com", "*.internal.example.com"], &[]);
+        assert!(guard.check("https://api.example.com/v1/pets").await.is_ok());
+        assert!(guard
+            .check("https://billing.internal.example.com/invoices")
+            .await
+            .is_ok());
+
+        let error = guard
+            .check("http://169.254.169.254/")
+            .await
+            .unwrap_err()
+            .downcast::<UpstreamDenied>()
+            .unwrap();
+        assert!(error.reason.contains("allowed_hosts"));
+        assert!(guard.check("https://internal.example.com/").await.is_err());
+        assert!(guard.check("https://evil-api.example.com/").await.is_err());
+        assert!(guard.check("file:///etc/passwd").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_static_override_is_checked() {
+        let guard = guard(&[], &["169.254.0.0/16"]);
+        let overrides: HostOverrides = [(
+            "metadata.internal".to_string(),
+            "169.254.169.254".parse().unwrap(),
+        )]
+        .into();
+        let error = guard
+            .check_with("http://metadata.internal/latest/", &overrides)
+            .await
+            .unwrap_err()
+            .downcast::<UpstreamDenied>()
+            .unwrap();
+        assert_eq!(error.host, "metadata.internal");
+        assert!(error.reason.contains("169.254.169.254"));
+
+        let overrides: HostOverrides =
+            [("api.internal".to_string(), "10.0.0.5".parse().unwrap())].into();
+        assert!(guard
+            .check_with("http://api.internal/pets", &overrides)
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_redirect_to_denied_cidr_is_rejected() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let app = axum::Router::new()
+            .route(
+                "/pets",
+                axum::routing::get(|| async {
+                    axum::response::Redirect::temporary("http://169.254.169.254/latest/meta-data/")
+                }),
+            )
+            .route(
+                "/moved",
+                axum::routing::get(|| async { axum::response::Redirect::temporary("/pets-v2") }),
+            )
+            .route("/pets-v2", axum::routing::get(|| async { "ok" }));
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let guard: &'static UpstreamGuard = Box::leak(Box::new(guard(&[], &["169.254.0.0/16"])));
+        let client = reqwest::Client::builder()
+            .redirect(guard.redirect_policy())
+            .build()
+            .unwrap();
+
+        let error = client
+            .get(format!("http://127.0.0.1:{}/pets", port))
+            .send()
+            .await
+            .unwrap_err();
+        let denied = crate::utils::classify_send_error(error)
+            .downcast::<UpstreamDenied>()
+            .unwrap();
+        assert_eq!(denied.host, "169.254.169.254");
+        assert!(denied.reason.contains("169.254.0.0/16"));
+
+        // 允许的重定向照常跟随
+        let response = client
+            .get(format!("http://127.0.0.1:{}/moved", port))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.text().await.unwrap(), "ok");
+    }
+
+    #[tokio::test]
+    async fn test_default_guard_allows_everything() {
+        let guard = UpstreamGuard::default();
+        assert!(guard.check("http://localhost:8080/").await.is_ok());
+    }
+}
@@ -0,0 +1,464 @@
+use crate::config::PluginsConfig;
+use crate::models::PluginHook;
+use serde_json::Value;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+use wasmtime::{
+    Config, Engine, ExternType, Instance, Module, Store, StoreLimits, StoreLimitsBuilder, Trap,
+};
+
+/// 全局插件运行时，启动时由配置初始化
+pub static PLUGIN_RUNTIME: OnceLock<PluginRuntime> = OnceLock::new();
+
+pub fn plugin_runtime() -> &'static PluginRuntime {
+    PLUGIN_RUNTIME.get_or_init(|| {
+        PluginRuntime::new(&PluginsConfig::default()).expect("failed to create plugin runtime")
+    })
+}
+
+/// 模块分配输入缓冲区的导出函数：alloc(len) -> ptr
+const ALLOC_EXPORT: &str = "alloc";
+const MEMORY_EXPORT: &str = "memory";
+/// 执行时间按 epoch 计量，后台线程每个 tick 推进一次
+const EPOCH_TICK: Duration = Duration::from_millis(10);
+
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum PluginError {
+    #[error("Invalid plugin module: {0}")]
+    Invalid(String),
+    #[error("Plugin module is {size} bytes, exceeds limit of {max} bytes")]
+    TooLarge { size: usize, max: usize },
+    #[error("Plugin does not export {0}")]
+    MissingHook(PluginHook),
+    #[error("Plugin exceeded fuel limit of {0}")]
+    FuelExhausted(u64),
+    #[error("Plugin exceeded time limit of {0}ms")]
+    Timeout(u64),
+    #[error("Plugin trapped: {0}")]
+    Trap(String),
+    #[error("Plugin returned invalid output: {0}")]
+    InvalidOutput(String),
+}
+
+/// 编译并校验通过的插件模块
+#[derive(Clone)]
+pub struct CompiledPlugin {
+    module: Module,
+    pub transform_request: bool,
+    pub transform_response: bool,
+}
+
+impl CompiledPlugin {
+    pub fn has_hook(&self, hook: PluginHook) -> bool {
+        match hook {
+            PluginHook::Request => self.transform_request,
+            PluginHook::Response => self.transform_response,
+        }
+    }
+}
+
+/// 单次执行结果
+#[derive(Debug, Clone)]
+pub struct PluginRun {
+    pub output: Value,
+    pub fuel_consumed: u64,
+    pub elapsed: Duration,
+}
+
+/// WASM 插件运行时。模块不允许导入任何宿主函数（没有 WASI 文件系统与网络），
+/// ABI 为 JSON 输入输出：宿主调用 alloc(len) 写入输入，
+/// 钩子 (ptr, len) -> i64 返回输出位置，高 32 位为指针，低 32 位为长度
+pub struct PluginRuntime {
+    engine: Engine,
+    config: PluginsConfig,
+}
+
+impl PluginRuntime {
+    pub fn new(config: &PluginsConfig) -> anyhow::Result<Self> {
+        let mut engine_config = Config::new();
+        engine_config.consume_fuel(true).epoch_interruption(true);
+        let engine = Engine::new(&engine_config)?;
+
+        // 运行时释放后线程随之退出
+        let weak = engine.weak();
+        std::thread::Builder::new()
+            .name("wasm-plugin-epoch".to_string())
+            .spawn(move || {
+                while let Some(engine) = weak.upgrade() {
+                    engine.increment_epoch();
+                    drop(engine);
+                    std::thread::sleep(EPOCH_TICK);
+                }
+            })?;
+
+        Ok(Self {
+            engine,
+            config: config.clone(),
+        })
+    }
+
+    pub fn config(&self) -> &PluginsConfig {
+        &self.config
+    }
+
+    /// 上传时校验：大小、二进制格式、不导入宿主函数、导出内存与 alloc，
+    /// 且至少导出一个签名正确的钩子
+    pub fn compile(&self, bytes: &[u8]) -> Result<CompiledPlugin, PluginError> {
+        if bytes.len() > self.config.max_module_bytes {
+            return Err(PluginError::TooLarge {
+                size: bytes.len(),
+                max: self.config.max_module_bytes,
+            });
+        }
+        if !bytes.starts_with(b"\0asm") {
+            return Err(PluginError::Invalid("not a wasm binary".to_string()));
+        }
+        let module =
+            Module::new(&self.engine, bytes).map_err(|e| PluginError::Invalid(e.to_string()))?;
+        if let Some(import) = module.imports().next() {
+            return Err(PluginError::Invalid(format!(
+                "imports are not allowed: {}::{}",
+                import.module(),
+                import.name()
+            )));
+        }
+        if !matches!(
+            module.get_export(MEMORY_EXPORT),
+            Some(ExternType::Memory(_))
+        ) {
+            return Err(PluginError::Invalid("missing memory export".to_string()));
+        }
+
+        let exports_hook = |hook: PluginHook| {
+            matches!(
+                module.get_export(hook.export_name()),
+                Some(ExternType::Func(_))
+            )
+        };
+        let plugin = CompiledPlugin {
+            transform_request: exports_hook(PluginHook::Request),
+            transform_response: exports_hook(PluginHook::Response),
+            module: module.clone(),
+        };
+        if !plugin.transform_request && !plugin.transform_response {
+            return Err(PluginError::Invalid(
+                "module exports neither transform_request nor transform_response".to_string(),
+            ));
+        }
+
+        // 实例化一次校验函数签名
+        let (mut store, instance) = self.instantiate(&plugin)?;
+        instance
+            .get_typed_func::<i32, i32>(&mut store, ALLOC_EXPORT)
+            .map_err(|e| PluginError::Invalid(format!("{}: {}", ALLOC_EXPORT, e)))?;
+        for hook in [PluginHook::Request, PluginHook::Response] {
+            if plugin.has_hook(hook) {
+                instance
+                    .get_typed_func::<(i32, i32), i64>(&mut store, hook.export_name())
+                    .map_err(|e| PluginError::Invalid(format!("{}: {}", hook, e)))?;
+            }
+        }
+        Ok(plugin)
+    }
+
+    /// 同步执行钩子，受 fuel、时间与内存限制；在阻塞线程中调用
+    pub fn invoke(
+        &self,
+        plugin: &CompiledPlugin,
+        hook: PluginHook,
+        input: &Value,
+    ) -> Result<PluginRun, PluginError> {
+        if !plugin.has_hook(hook) {
+            return Err(PluginError::MissingHook(hook));
+        }
+        let started = Instant::now();
+        let (mut store, instance) = self.instantiate(plugin)?;
+        let trap = |e: wasmtime::Error| self.trap_error(e);
+
+        let memory = instance
+            .get_memory(&mut store, MEMORY_EXPORT)
+            .ok_or_else(|| PluginError::Invalid("missing memory export".to_string()))?;
+        let alloc = instance
+            .get_typed_func::<i32, i32>(&mut store, ALLOC_EXPORT)
+            .map_err(|e| PluginError::Invalid(e.to_string()))?;
+        let transform = instance
+            .get_typed_func::<(i32, i32), i64>(&mut store, hook.export_name())
+            .map_err(|e| PluginError::Invalid(e.to_string()))?;
+
+        let input = serde_json::to_vec(input).map_err(|e| PluginError::Invalid(e.to_string()))?;
+        let len = i32::try_from(input.len())
+            .map_err(|_| PluginError::Invalid("input too large".to_string()))?;
+        let ptr = alloc.call(&mut store, len).map_err(trap)?;
+        memory
+            .write(&mut store, ptr as u32 as usize, &input)
+            .map_err(|e| PluginError::Trap(format!("alloc returned invalid pointer: {}", e)))?;
+
+        let packed = transform.call(&mut store, (ptr, len)).map_err(trap)? as u64;
+        let (out_ptr, out_len) = ((packed >> 32) as usize, (packed & 0xffff_ffff) as usize);
+        let output = memory
+            .data(&store)
+            .get(out_ptr..out_ptr + out_len)
+            .ok_or_else(|| PluginError::InvalidOutput("output out of bounds".to_string()))?;
+        let output = serde_json::from_slice(output)
+            .map_err(|e| PluginError::InvalidOutput(e.to_string()))?;
+
+        let fuel_consumed = self
+            .config
+            .fuel
+            .saturating_sub(store.get_fuel().unwrap_or(0));
+        Ok(PluginRun {
+            output,
+            fuel_consumed,
+            elapsed: started.elapsed(),
+        })
+    }
+
+    fn instantiate(
+        &self,
+        plugin: &CompiledPlugin,
+    ) -> Result<(Store<StoreLimits>, Instance), PluginError> {
+        let limits = StoreLimitsBuilder::new()
+            .memory_size(self.config.max_memory_bytes)
+            .instances(1)
+            .build();
+        let mut store = Store::new(&self.engine, limits);
+        store.limiter(|limits| limits);
+        store
+            .set_fuel(self.config.fuel)
+            .map_err(|e| PluginError::Invalid(e.to_string()))?;
+        // 多留一个 tick，避免刚开始执行就赶上 epoch 推进
+        let ticks = self
+            .config
+            .timeout_ms
+            .div_ceil(EPOCH_TICK.as_millis() as u64)
+            + 1;
+        store.set_epoch_deadline(ticks);
+        let instance =
+            Instance::new(&mut store, &plugin.module, &[]).map_err(|e| self.trap_error(e))?;
+        Ok((store, instance))
+    }
+
+    fn trap_error(&self, error: wasmtime::Error) -> PluginError {
+        match error.downcast_ref::<Trap>() {
+            Some(Trap::OutOfFuel) => PluginError::FuelExhausted(self.config.fuel),
+            Some(Trap::Interrupt) => PluginError::Timeout(self.config.timeout_ms),
+            _ => PluginError::Trap(format!("{:#}", error)),
+        }
+    }
+}
+
+/// 测试用插件模块，由 WAT 编译
+#[cfg(test)]
+pub(crate) mod fixtures {
+    /// 内存与简单的线性分配器
+    const PRELUDE: &str = r#"
+        (memory (export "memory") 1)
+        (global $next (mut i32) (i32.const 1024))
+        (func (export "alloc") (param $len i32) (result i32)
+          (local $ptr i32)
+          (local.set $ptr (global.get $next))
+          (global.set $next (i32.add (global.get $next) (local.get $len)))
+          (local.get $ptr))
+        (func $pack (param $ptr i32) (param $len i32) (result i64)
+          (i64.or
+            (i64.shl (i64.extend_i32_u (local.get $ptr)) (i64.const 32))
+            (i64.extend_i32_u (local.get $len))))
+    "#;
+
+    fn module(hooks: &str) -> Vec<u8> {
+        wat::parse_str(format!("(module {} {})", PRELUDE, hooks)).unwrap()
+    }
+
+    /// transform_request 原样返回输入
+    pub fn echo() -> Vec<u8> {
+        module(
+            r#"(func (export "transform_request") (param i32 i32) (result i64)
+                 (call $pack (local.get 0) (local.get 1)))"#,
+        )
+    }
+
+    /// 两个钩子都把 JSON 中字符串值的小写字母改为大写，键保持不变
+    pub fn uppercase() -> Vec<u8> {
+        module(
+            r#"(func $upper (param $ptr i32) (param $len i32) (result i64)
+                 (local $i i32) (local $c i32) (local $in_str i32) (local $is_value i32) (local $last i32)
+                 (block $done
+                   (loop $scan
+                     (br_if $done (i32.ge_u (local.get $i) (local.get $len)))
+                     (local.set $c (i32.load8_u (i32.add (local.get $ptr) (local.get $i))))
+                     (if (i32.eq (local.get $c) (i32.const 34))
+                       (then
+                         (if (local.get $in_str)
+                           (then
+                             (local.set $in_str (i32.const 0))
+                             (local.set $last (i32.const 34)))
+                           (else
+                             (local.set $in_str (i32.const 1))
+                             (local.set $is_value (i32.eq (local.get $last) (i32.const 58))))))
+                       (else
+                         (if (local.get $in_str)
+                           (then
+                             (if (i32.and
+                                   (local.get $is_value)
+                                   (i32.and
+                                     (i32.ge_u (local.get $c) (i32.const 97))
+                                     (i32.le_u (local.get $c) (i32.const 122))))
+                               (then
+                                 (i32.store8
+                                   (i32.add (local.get $ptr) (local.get $i))
+                                   (i32.sub (local.get $c) (i32.const 32))))))
+                           (else (local.set $last (local.get $c))))))
+                     (local.set $i (i32.add (local.get $i) (i32.const 1)))
+                     (br $scan)))
+                 (call $pack (local.get $ptr) (local.get $len)))
+               (export "transform_request" (func $upper))
+               (export "transform_response" (func $upper))"#,
+        )
+    }
+
+    /// 死循环，只能由 fuel 或时间限制终止
+    pub fn infinite_loop() -> Vec<u8> {
+        module(
+            r#"(func (export "transform_request") (param i32 i32) (result i64)
+                 (loop $forever (br $forever))
+                 (unreachable))"#,
+        )
+    }
+
+    /// 执行即 trap
+    pub fn trap() -> Vec<u8> {
+        module(
+            r#"(func (export "transform_request") (param i32 i32) (result i64)
+                 (unreachable))"#,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn runtime() -> PluginRuntime {
+        PluginRuntime::new(&PluginsConfig::default()).unwrap()
+    }
+
+    #[test]
+    fn test_echo_plugin_round_trips_json() {
+        let runtime = runtime();
+        let plugin = runtime.compile(&fixtures::echo()).unwrap();
+        assert!(plugin.transform_request);
+        assert!(!plugin.transform_response);
+
+        let input = json!({"arguments": {"name": "rex"}});
+        let run = runtime
+            .invoke(&plugin, PluginHook::Request, &input)
+            .unwrap();
+        assert_eq!(run.output, input);
+        assert!(run.fuel_consumed > 0);
+        assert_eq!(
+            runtime
+                .invoke(&plugin, PluginHook::Response, &input)
+                .unwrap_err(),
+            PluginError::MissingHook(PluginHook::Response)
+        );
+    }
+
+    #[test]
+    fn test_uppercase_plugin_only_touches_values() {
+        let runtime = runtime();
+        let plugin = runtime.compile(&fixtures::uppercase()).unwrap();
+        let run = runtime
+            .invoke(
+                &plugin,
+                PluginHook::Response,
+                &json!({"response": {"name": "rex", "age": 3}}),
+            )
+            .unwrap();
+        assert_eq!(run.output, json!({"response": {"name": "REX", "age": 3}}));
+    }
+
+    #[test]
+    fn test_limits_stop_runaway_plugins() {
+        let runtime = runtime();
+        let plugin = runtime.compile(&fixtures::infinite_loop()).unwrap();
+        assert_eq!(
+            runtime
+                .invoke(&plugin, PluginHook::Request, &json!({}))
+                .unwrap_err(),
+            PluginError::FuelExhausted(PluginsConfig::default().fuel)
+        );
+
+        // fuel 充足时由时间限制终止
+        let runtime = PluginRuntime::new(&PluginsConfig {
+            fuel: 1 << 40,
+            timeout_ms: 50,
+            ..PluginsConfig::default()
+        })
+        .unwrap();
+        let plugin = runtime.compile(&fixtures::infinite_loop()).unwrap();
+        let started = Instant::now();
+        assert_eq!(
+            runtime
+                .invoke(&plugin, PluginHook::Request, &json!({}))
+                .unwrap_err(),
+            PluginError::Timeout(50)
+        );
+        assert!(started.elapsed() < Duration::from_secs(2));
+
+        let plugin = runtime.compile(&fixtures::trap()).unwrap();
+        assert!(matches!(
+            runtime.invoke(&plugin, PluginHook::Request, &json!({})),
+            Err(PluginError::Trap(_))
+        ));
+    }
+
+    #[test]
+    fn test_compile_rejects_invalid_modules() {
+        let runtime = PluginRuntime::new(&PluginsConfig {
+            max_module_bytes: 64,
+            ..PluginsConfig::default()
+        })
+        .unwrap();
+        assert!(matches!(
+            runtime.compile(&[0u8; 65]),
+            Err(PluginError::TooLarge { size: 65, max: 64 })
+        ));
+        assert!(matches!(
+            runtime.compile(b"(module)"),
+            Err(PluginError::Invalid(_))
+        ));
+
+        let runtime = self::runtime();
+        // 导入宿主函数（如 WASI）的模块被拒绝
+        let wasi = wat::parse_str(
+            r#"(module
+                 (import "wasi_snapshot_preview1" "fd_write" (func (param i32 i32 i32 i32) (result i32)))
+                 (memory (export "memory") 1))"#,
+        )
+        .unwrap();
+        let error = runtime.compile(&wasi).unwrap_err();
+        assert!(error.to_string().contains("imports are not allowed"));
+
+        let no_hooks = wat::parse_str(
+            r#"(module
+                 (memory (export "memory") 1)
+                 (func (export "alloc") (param i32) (result i32) (i32.const 0)))"#,
+        )
+        .unwrap();
+        assert!(runtime.compile(&no_hooks).is_err());
+
+        let wrong_signature = wat::parse_str(
+            r#"(module
+                 (memory (export "memory") 1)
+                 (func (export "alloc") (param i32) (result i32) (i32.const 0))
+                 (func (export "transform_request") (param i32) (result i32) (i32.const 0)))"#,
+        )
+        .unwrap();
+        assert!(runtime
+            .compile(&wrong_signature)
+            .unwrap_err()
+            .to_string()
+            .contains("transform_request"));
+    }
+}